@@ -0,0 +1,15 @@
+CREATE TABLE caller_memories (
+    phone_number VARCHAR(20) NOT NULL PRIMARY KEY,
+    summary TEXT NOT NULL DEFAULT '',
+    key_facts JSONB NOT NULL DEFAULT '[]'::jsonb,
+    call_count INT NOT NULL DEFAULT 0 CHECK (call_count >= 0),
+    last_call_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT chk_caller_memories_phone_e164
+        CHECK (phone_number ~ '^\+[1-9][0-9]{1,14}$')
+);
+
+CREATE INDEX idx_caller_memories_last_call_at
+    ON caller_memories(last_call_at);
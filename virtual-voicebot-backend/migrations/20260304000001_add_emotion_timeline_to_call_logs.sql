@@ -0,0 +1,2 @@
+ALTER TABLE call_logs
+    ADD COLUMN emotion_timeline JSONB NOT NULL DEFAULT '[]'::jsonb;
@@ -0,0 +1,9 @@
+ALTER TABLE call_logs
+    ADD COLUMN schedule_id UUID;
+
+-- 日跨ぎの時間帯（例: 18:00-09:00）を許可する
+ALTER TABLE schedule_time_slots
+    DROP CONSTRAINT chk_time_order;
+
+ALTER TABLE schedule_time_slots
+    ADD CONSTRAINT chk_time_order CHECK (start_time <> end_time);
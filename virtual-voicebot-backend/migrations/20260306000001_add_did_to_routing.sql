@@ -0,0 +1,24 @@
+-- 着信番号（DID）別ルーティング
+-- did が NULL の行は全 DID 共通として扱い、DID 一致の行を優先する
+
+ALTER TABLE registered_numbers
+    ADD COLUMN did VARCHAR(32);
+
+ALTER TABLE call_action_rules
+    ADD COLUMN did VARCHAR(32);
+
+ALTER TABLE routing_rules
+    ADD COLUMN did VARCHAR(32);
+
+-- 同じ発信者番号を DID ごとに登録できるよう一意制約を (phone_number, did) に変更
+DROP INDEX uq_registered_numbers_phone;
+CREATE UNIQUE INDEX uq_registered_numbers_phone_did
+    ON registered_numbers(phone_number, (COALESCE(did, ''))) WHERE deleted_at IS NULL;
+
+DROP INDEX idx_routing_rules_category;
+CREATE INDEX idx_routing_rules_category
+    ON routing_rules(caller_category, did, priority) WHERE is_active;
+
+COMMENT ON COLUMN registered_numbers.did IS '適用する着信番号（E.164）。NULL は全 DID 共通';
+COMMENT ON COLUMN call_action_rules.did IS '適用する着信番号（E.164）。NULL は全 DID 共通';
+COMMENT ON COLUMN routing_rules.did IS '適用する着信番号（E.164）。NULL は全 DID 共通';
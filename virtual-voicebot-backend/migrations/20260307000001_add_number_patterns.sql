@@ -0,0 +1,64 @@
+-- 登録番号・迷惑電話番号のパターン照合（前方一致・範囲）
+-- match_type:
+--   exact  : phone_number と完全一致
+--   prefix : phone_number で始まる番号すべて（例: +8150 → IP 電話）
+--   range  : phone_number 〜 range_end の同じ桁数の番号（迷惑電話番号のみ）
+
+ALTER TABLE registered_numbers
+    ADD COLUMN match_type VARCHAR(10) NOT NULL DEFAULT 'exact';
+
+ALTER TABLE registered_numbers
+    ADD CONSTRAINT chk_registered_match_type
+        CHECK (match_type IN ('exact', 'prefix'));
+
+-- 前方一致は "+1" のような短い値を許可する
+ALTER TABLE registered_numbers
+    DROP CONSTRAINT chk_registered_phone_e164;
+ALTER TABLE registered_numbers
+    ADD CONSTRAINT chk_registered_phone_e164
+        CHECK (phone_number ~ '^\+[1-9][0-9]{0,14}$'
+               AND (match_type = 'prefix' OR LENGTH(phone_number) >= 3));
+
+DROP INDEX uq_registered_numbers_phone_did;
+CREATE UNIQUE INDEX uq_registered_numbers_phone_did
+    ON registered_numbers(phone_number, (COALESCE(did, '')), match_type) WHERE deleted_at IS NULL;
+
+ALTER TABLE spam_numbers
+    ADD COLUMN match_type VARCHAR(10) NOT NULL DEFAULT 'exact',
+    ADD COLUMN range_end VARCHAR(20);
+
+ALTER TABLE spam_numbers
+    ADD CONSTRAINT chk_spam_match_type
+        CHECK (match_type IN ('exact', 'prefix', 'range'));
+
+ALTER TABLE spam_numbers
+    DROP CONSTRAINT chk_spam_phone_e164;
+ALTER TABLE spam_numbers
+    ADD CONSTRAINT chk_spam_phone_e164
+        CHECK (phone_number ~ '^\+[1-9][0-9]{0,14}$'
+               AND (match_type = 'prefix' OR LENGTH(phone_number) >= 3));
+
+ALTER TABLE spam_numbers
+    ADD CONSTRAINT chk_spam_range
+        CHECK (
+            (match_type = 'range'
+             AND range_end ~ '^\+[1-9][0-9]{1,14}$'
+             AND LENGTH(range_end) = LENGTH(phone_number)
+             AND range_end >= phone_number)
+            OR (match_type <> 'range' AND range_end IS NULL)
+        );
+
+DROP INDEX uq_spam_numbers_phone;
+CREATE UNIQUE INDEX uq_spam_numbers_phone
+    ON spam_numbers(phone_number, match_type) WHERE deleted_at IS NULL;
+
+-- 範囲照合用（式は routing_repo / postgres の照合 SQL と一致させること）
+CREATE INDEX idx_spam_numbers_range
+    ON spam_numbers USING gist (
+        int8range(SUBSTRING(phone_number FROM 2)::bigint, SUBSTRING(range_end FROM 2)::bigint, '[]')
+    )
+    WHERE match_type = 'range' AND deleted_at IS NULL;
+
+COMMENT ON COLUMN registered_numbers.match_type IS 'exact: 完全一致 / prefix: 前方一致';
+COMMENT ON COLUMN spam_numbers.match_type IS 'exact: 完全一致 / prefix: 前方一致 / range: phone_number〜range_end';
+COMMENT ON COLUMN spam_numbers.range_end IS 'match_type=range の終了番号（phone_number と同じ桁数）';
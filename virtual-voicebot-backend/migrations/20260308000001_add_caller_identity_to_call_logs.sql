@@ -0,0 +1,15 @@
+-- 発信者識別ヘッダ（PAI/PPI, Privacy, Remote-Party-ID, Diversion, History-Info）の記録
+-- caller_number は従来どおり From ヘッダの番号（表示上の番号）を保持する
+
+ALTER TABLE call_logs
+    ADD COLUMN asserted_caller_number VARCHAR(32),
+    ADD COLUMN caller_display_name VARCHAR(255),
+    ADD COLUMN caller_privacy BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN original_called_number VARCHAR(32),
+    ADD COLUMN diversion_reason VARCHAR(64);
+
+COMMENT ON COLUMN call_logs.asserted_caller_number IS 'P-Asserted-Identity 等による網側の発信者番号';
+COMMENT ON COLUMN call_logs.caller_display_name IS 'From ヘッダの表示名';
+COMMENT ON COLUMN call_logs.caller_privacy IS '発信者が番号非通知（Privacy）を要求した';
+COMMENT ON COLUMN call_logs.original_called_number IS '転送着信の転送前の着信先（Diversion / History-Info）';
+COMMENT ON COLUMN call_logs.diversion_reason IS '転送理由（Diversion reason / History-Info cause）';
@@ -0,0 +1,49 @@
+-- 留守番電話（メールボックスとメッセージ）
+-- pin_hash は encode(sha256(convert_to(mailbox_number || ':' || pin, 'UTF8')), 'hex')
+-- did が NULL のメールボックスは全 DID 共通（既定）として扱い、DID 一致のメールボックスを優先する
+
+CREATE TABLE voicemail_mailboxes (
+    id UUID NOT NULL PRIMARY KEY,
+    mailbox_number VARCHAR(16) NOT NULL,
+    did VARCHAR(32),
+    pin_hash CHAR(64) NOT NULL,
+    notify_line BOOLEAN NOT NULL DEFAULT TRUE,
+    webhook_url TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMPTZ,
+
+    CONSTRAINT chk_voicemail_mailbox_number
+        CHECK (mailbox_number ~ '^[0-9]{1,16}$')
+);
+
+CREATE UNIQUE INDEX uq_voicemail_mailboxes_number
+    ON voicemail_mailboxes(mailbox_number) WHERE deleted_at IS NULL;
+CREATE UNIQUE INDEX uq_voicemail_mailboxes_did
+    ON voicemail_mailboxes((COALESCE(did, ''))) WHERE deleted_at IS NULL;
+
+-- call_log_id はパーティション表 call_logs を参照するため外部キーにしない
+CREATE TABLE voicemail_messages (
+    id UUID NOT NULL PRIMARY KEY,
+    mailbox_id UUID NOT NULL REFERENCES voicemail_mailboxes(id),
+    call_log_id UUID,
+    caller_number VARCHAR(32),
+    duration_sec INT NOT NULL CHECK (duration_sec >= 0),
+    file_path TEXT NOT NULL,
+    transcript TEXT,
+    status VARCHAR(10) NOT NULL DEFAULT 'new',
+    listened_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT chk_voicemail_message_status
+        CHECK (status IN ('new', 'saved', 'deleted'))
+);
+
+CREATE INDEX idx_voicemail_messages_mailbox
+    ON voicemail_messages(mailbox_id, status, created_at) WHERE status <> 'deleted';
+
+COMMENT ON COLUMN voicemail_mailboxes.did IS '対象の着信番号（E.164）。NULL は全 DID 共通';
+COMMENT ON COLUMN voicemail_mailboxes.webhook_url IS '新着通知の送信先。NULL は VOICEMAIL_WEBHOOK_URL';
+COMMENT ON COLUMN voicemail_messages.file_path IS '発信者側チャネルのモノラル WAV';
+COMMENT ON COLUMN voicemail_messages.status IS 'new: 未保存 / saved: 保存 / deleted: 削除済み';
@@ -0,0 +1,29 @@
+-- IVR 入力ノードの拡張
+-- DIGITS: 複数桁の DTMF を収集し、呼変数に格納して COMPLETE 遷移へ進む
+-- SPEECH: 発話を ASR で認識し、遷移ごとのキーワード／意図と照合する
+
+ALTER TABLE ivr_nodes DROP CONSTRAINT chk_node_type;
+ALTER TABLE ivr_nodes ADD CONSTRAINT chk_node_type
+    CHECK (node_type IN ('ANNOUNCE', 'KEYPAD', 'DIGITS', 'SPEECH', 'FORWARD', 'TRANSFER', 'RECORD', 'EXIT'));
+
+ALTER TABLE ivr_nodes
+    ADD COLUMN min_digits SMALLINT NOT NULL DEFAULT 1,
+    ADD COLUMN max_digits SMALLINT NOT NULL DEFAULT 16,
+    ADD COLUMN terminator VARCHAR(1),
+    ADD COLUMN inter_digit_timeout_sec INT NOT NULL DEFAULT 3,
+    ADD COLUMN variable_name VARCHAR(64),
+    ADD CONSTRAINT chk_node_digits
+        CHECK (min_digits >= 0 AND max_digits >= 1 AND min_digits <= max_digits);
+
+ALTER TABLE ivr_transitions DROP CONSTRAINT chk_transition_input_type;
+ALTER TABLE ivr_transitions ADD CONSTRAINT chk_transition_input_type
+    CHECK (input_type IN ('DTMF', 'TIMEOUT', 'INVALID', 'COMPLETE', 'SPEECH'));
+
+ALTER TABLE ivr_transitions
+    ADD COLUMN keywords TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN intent VARCHAR(64);
+
+COMMENT ON COLUMN ivr_nodes.terminator IS 'DIGITS の入力終端キー（NULL は終端キーなし）';
+COMMENT ON COLUMN ivr_nodes.variable_name IS '収集した値（DIGITS の数字列 / SPEECH の認識テキスト）を格納する呼変数名';
+COMMENT ON COLUMN ivr_transitions.keywords IS 'SPEECH 遷移: 認識テキストに含まれていれば一致とするキーワード';
+COMMENT ON COLUMN ivr_transitions.intent IS 'SPEECH 遷移: 意図分類の結果と一致すれば遷移する意図名';
@@ -0,0 +1,3 @@
+-- IVR で収集した呼変数（変数名 → 値）
+ALTER TABLE call_logs
+    ADD COLUMN call_variables JSONB NOT NULL DEFAULT '{}'::jsonb;
@@ -0,0 +1,3 @@
+-- 転送先グループの各メンバーへの呼び出し結果（huntGroup, member, startedAt, endedAt, outcome, sipStatus）
+ALTER TABLE call_logs
+    ADD COLUMN transfer_events JSONB NOT NULL DEFAULT '[]'::jsonb;
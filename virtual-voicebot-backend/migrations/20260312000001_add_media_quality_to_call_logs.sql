@@ -0,0 +1,9 @@
+-- 受信メディア品質の要約（packetsReceived, packetsLost, maxJitterMs, avgJitterMs, reorderedPackets,
+-- lateDroppedPackets, rttMs, mos, belowThreshold）。RTP を受けなかった通話は NULL
+ALTER TABLE call_logs
+    ADD COLUMN media_quality JSONB;
+
+-- 品質アラート（推定 MOS が閾値割れ）の通話を拾うための部分インデックス
+CREATE INDEX idx_call_logs_media_quality_below_threshold
+    ON call_logs(started_at)
+    WHERE (media_quality ->> 'belowThreshold')::boolean;
@@ -0,0 +1,4 @@
+-- 録音の一時停止区間（pausedAt, resumedAt, startSec, endSec, pausedBy, resumedBy）
+-- カード番号の読み上げなど、録音してはならない区間を無音にした記録
+ALTER TABLE call_logs
+    ADD COLUMN recording_pauses JSONB NOT NULL DEFAULT '[]'::jsonb;
@@ -0,0 +1,29 @@
+-- 外部 webhook の配送キュー（送信待ち・送信済み・dead letter をまとめて配送ログとして残す）
+-- payload はテンプレート適用済みの本文。署名は送信のたびに設定中の secret で付け直す
+-- call_id は外部の通話 ID（call_logs.external_call_id）。留守番電話などで無い場合は NULL
+
+CREATE TABLE webhook_deliveries (
+    id UUID NOT NULL PRIMARY KEY,
+    subscription_id VARCHAR(64) NOT NULL,
+    event_type VARCHAR(32) NOT NULL,
+    event_id UUID NOT NULL,
+    call_id VARCHAR(255),
+    url TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(10) NOT NULL DEFAULT 'pending',
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_status_code INT,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ,
+
+    CONSTRAINT chk_webhook_delivery_status
+        CHECK (status IN ('pending', 'delivered', 'dead'))
+);
+
+CREATE INDEX idx_webhook_deliveries_due
+    ON webhook_deliveries(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX idx_webhook_deliveries_created
+    ON webhook_deliveries(created_at DESC);
@@ -0,0 +1,8 @@
+-- 留守番電話 PIN のハッシュを PBKDF2-HMAC-SHA256（メールボックスごとのランダムなソルト）に移行する
+-- 新形式: pbkdf2-sha256$<反復回数>$<ソルト hex>$<ハッシュ hex>
+-- 旧形式（encode(sha256(convert_to(mailbox_number || ':' || pin, 'UTF8')), 'hex')）は
+-- 照合に成功した時点でアプリが新形式に置き換える
+
+ALTER TABLE voicemail_mailboxes ALTER COLUMN pin_hash TYPE TEXT;
+
+COMMENT ON COLUMN voicemail_mailboxes.pin_hash IS 'pbkdf2-sha256$<反復回数>$<ソルト hex>$<ハッシュ hex>（旧形式の SHA-256 hex は初回認証時に置換）';
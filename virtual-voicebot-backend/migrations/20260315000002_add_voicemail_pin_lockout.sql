@@ -0,0 +1,8 @@
+-- 留守番電話 PIN の連続誤りによるメールボックス単位のロック（通話をまたいで数える）
+
+ALTER TABLE voicemail_mailboxes
+    ADD COLUMN failed_pin_attempts INT NOT NULL DEFAULT 0,
+    ADD COLUMN pin_locked_until TIMESTAMPTZ;
+
+COMMENT ON COLUMN voicemail_mailboxes.failed_pin_attempts IS '直近の認証成功以降の PIN 連続誤り回数（ロック時に 0 に戻す）';
+COMMENT ON COLUMN voicemail_mailboxes.pin_locked_until IS 'この時刻まで PIN 認証を拒否する。NULL はロックなし';
@@ -0,0 +1,67 @@
+use std::env;
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+use virtual_voicebot_backend::interface::db::{PostgresAdapter, RoutingRepoImpl};
+use virtual_voicebot_backend::service::routing::RuleEvaluator;
+use virtual_voicebot_backend::shared::entities::CallerIdentity;
+use virtual_voicebot_backend::shared::logging;
+
+const USAGE: &str = "usage: routing_explain --caller <number> [--callee <did>] [--at <rfc3339>]";
+
+/// 着信を発生させずにルーティング評価を行い、各段の照合結果を JSON で出力する。
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    logging::init();
+
+    let args = parse_args(env::args().skip(1))?;
+    let database_url = env::var("DATABASE_URL").context("DATABASE_URL must be set")?;
+    let postgres_adapter = Arc::new(PostgresAdapter::new(database_url).await?);
+
+    let evaluator = RuleEvaluator::new(Arc::new(RoutingRepoImpl::new(
+        postgres_adapter.pool().clone(),
+    )))
+    .with_schedule_port(postgres_adapter);
+    let caller = CallerIdentity::from_number(args.caller.as_deref().unwrap_or("anonymous"));
+    let explanation = evaluator
+        .explain(
+            &caller,
+            args.callee.as_deref(),
+            args.at.unwrap_or_else(Utc::now),
+        )
+        .await?;
+
+    println!("{}", serde_json::to_string_pretty(&explanation)?);
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct Args {
+    caller: Option<String>,
+    callee: Option<String>,
+    at: Option<DateTime<Utc>>,
+}
+
+fn parse_args<I>(mut args: I) -> anyhow::Result<Args>
+where
+    I: Iterator<Item = String>,
+{
+    let mut parsed = Args::default();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .with_context(|| format!("{} requires a value\n{}", flag, USAGE))?;
+        match flag.as_str() {
+            "--caller" => parsed.caller = Some(value),
+            "--callee" => parsed.callee = Some(value),
+            "--at" => {
+                let at = DateTime::parse_from_rfc3339(&value)
+                    .with_context(|| format!("invalid --at: {}", value))?;
+                parsed.at = Some(at.with_timezone(&Utc));
+            }
+            _ => bail!("unknown argument: {}\n{}", flag, USAGE),
+        }
+    }
+    Ok(parsed)
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+
+use crate::shared::ports::caller_memory_port::{
+    CallerMemory, CallerMemoryError, CallerMemoryFuture, CallerMemoryPort,
+};
+
+pub struct CallerMemoryRepoImpl {
+    pool: PgPool,
+}
+
+impl CallerMemoryRepoImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl CallerMemoryPort for CallerMemoryRepoImpl {
+    fn load(
+        &self,
+        phone_number: &str,
+        not_before: DateTime<Utc>,
+    ) -> CallerMemoryFuture<Option<CallerMemory>> {
+        let pool = self.pool.clone();
+        let phone_number = phone_number.to_string();
+        Box::pin(async move {
+            let row = sqlx::query(
+                "SELECT phone_number, summary, key_facts, call_count, last_call_at
+                 FROM caller_memories
+                 WHERE phone_number = $1 AND last_call_at >= $2
+                 LIMIT 1",
+            )
+            .bind(phone_number)
+            .bind(not_before)
+            .fetch_optional(&pool)
+            .await
+            .map_err(map_read_err)?;
+
+            let Some(row) = row else {
+                return Ok(None);
+            };
+
+            let key_facts: Value = row.try_get("key_facts").map_err(map_read_err)?;
+            Ok(Some(CallerMemory {
+                phone_number: row.try_get("phone_number").map_err(map_read_err)?,
+                summary: row.try_get("summary").map_err(map_read_err)?,
+                key_facts: key_facts_from_json(&key_facts),
+                call_count: row.try_get("call_count").map_err(map_read_err)?,
+                last_call_at: row.try_get("last_call_at").map_err(map_read_err)?,
+            }))
+        })
+    }
+
+    fn save(&self, memory: CallerMemory) -> CallerMemoryFuture<()> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO caller_memories (
+                    phone_number, summary, key_facts, call_count, last_call_at
+                 ) VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (phone_number) DO UPDATE SET
+                     summary = EXCLUDED.summary,
+                     key_facts = EXCLUDED.key_facts,
+                     call_count = EXCLUDED.call_count,
+                     last_call_at = EXCLUDED.last_call_at,
+                     updated_at = NOW()",
+            )
+            .bind(memory.phone_number)
+            .bind(memory.summary)
+            .bind(Value::from(memory.key_facts))
+            .bind(memory.call_count)
+            .bind(memory.last_call_at)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(())
+        })
+    }
+
+    fn erase(&self, phone_number: &str) -> CallerMemoryFuture<bool> {
+        let pool = self.pool.clone();
+        let phone_number = phone_number.to_string();
+        Box::pin(async move {
+            let result = sqlx::query("DELETE FROM caller_memories WHERE phone_number = $1")
+                .bind(phone_number)
+                .execute(&pool)
+                .await
+                .map_err(map_write_err)?;
+            Ok(result.rows_affected() > 0)
+        })
+    }
+
+    fn purge_expired(&self, not_before: DateTime<Utc>) -> CallerMemoryFuture<u64> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            let result = sqlx::query("DELETE FROM caller_memories WHERE last_call_at < $1")
+                .bind(not_before)
+                .execute(&pool)
+                .await
+                .map_err(map_write_err)?;
+            Ok(result.rows_affected())
+        })
+    }
+}
+
+fn key_facts_from_json(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn map_read_err(err: sqlx::Error) -> CallerMemoryError {
+    CallerMemoryError::ReadFailed(err.to_string())
+}
+
+fn map_write_err(err: sqlx::Error) -> CallerMemoryError {
+    CallerMemoryError::WriteFailed(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::key_facts_from_json;
+
+    #[test]
+    fn key_facts_from_json_keeps_only_strings() {
+        let value = json!(["予約は毎週火曜", 1, null, "田中さん"]);
+        assert_eq!(
+            key_facts_from_json(&value),
+            vec!["予約は毎週火曜".to_string(), "田中さん".to_string()]
+        );
+    }
+
+    #[test]
+    fn key_facts_from_json_returns_empty_for_non_array() {
+        assert!(key_facts_from_json(&json!({"a": 1})).is_empty());
+    }
+}
@@ -1,5 +1,12 @@
+pub mod caller_memory_repo;
+pub mod number_pattern;
 pub mod postgres;
 pub mod routing_repo;
+pub mod voicemail_repo;
+pub mod webhook_repo;
 
+pub use caller_memory_repo::CallerMemoryRepoImpl;
 pub use postgres::PostgresAdapter;
 pub use routing_repo::RoutingRepoImpl;
+pub use voicemail_repo::VoicemailRepoImpl;
+pub use webhook_repo::WebhookRepoImpl;
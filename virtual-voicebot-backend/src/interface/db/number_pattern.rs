@@ -0,0 +1,264 @@
+//! 発信者番号パターン（完全一致・前方一致・範囲）の照合。
+//! DB からはインデックスで絞り込んだ候補だけを取得し、優先順位の決定はここで行う。
+
+pub const MATCH_EXACT: &str = "exact";
+pub const MATCH_PREFIX: &str = "prefix";
+pub const MATCH_RANGE: &str = "range";
+
+/// 迷惑電話番号の照合 SQL（一致すれば true）。
+/// $1: `number_prefixes` の検索キー, $2: 番号, $3: `number_as_i64` の値
+pub const SPAM_MATCH_SQL: &str = "SELECT EXISTS(
+        SELECT 1
+        FROM spam_numbers
+        WHERE phone_number = ANY($1)
+          AND (match_type = 'prefix' OR (match_type = 'exact' AND phone_number = $2))
+          AND deleted_at IS NULL
+    ) OR EXISTS(
+        SELECT 1
+        FROM spam_numbers
+        WHERE match_type = 'range'
+          AND deleted_at IS NULL
+          AND LENGTH(phone_number) = LENGTH($2)
+          AND int8range(SUBSTRING(phone_number FROM 2)::bigint, SUBSTRING(range_end FROM 2)::bigint, '[]') @> $3::bigint
+    )";
+
+/// 前方一致の最短長（"+" と先頭 1 桁）
+const MIN_PREFIX_LEN: usize = 2;
+
+/// 番号パターン。電話番号は E.164（"+" 付き）で正規化済みであること。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumberPattern {
+    Exact(String),
+    /// "+8150*" のような前方一致
+    Prefix(String),
+    /// 同じ桁数の番号範囲（両端を含む）
+    Range {
+        start: String,
+        end: String,
+    },
+}
+
+impl NumberPattern {
+    /// 入力表記を解釈する。"+8150*" は前方一致、"+81120000000-+81120999999" は範囲。
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if let Some((start, end)) = raw.split_once('-') {
+            return Self::range(start.trim(), end.trim());
+        }
+        if let Some(prefix) = raw.strip_suffix('*') {
+            return is_e164_prefix(prefix).then(|| Self::Prefix(prefix.to_string()));
+        }
+        is_e164_prefix(raw).then(|| Self::Exact(raw.to_string()))
+    }
+
+    /// DB の (phone_number, match_type, range_end) 列から組み立てる。
+    pub fn from_columns(phone_number: &str, match_type: &str, range_end: Option<&str>) -> Self {
+        match (match_type, range_end) {
+            (MATCH_PREFIX, _) => Self::Prefix(phone_number.to_string()),
+            (MATCH_RANGE, Some(end)) => Self::Range {
+                start: phone_number.to_string(),
+                end: end.to_string(),
+            },
+            _ => Self::Exact(phone_number.to_string()),
+        }
+    }
+
+    fn range(start: &str, end: &str) -> Option<Self> {
+        let valid = is_e164_prefix(start)
+            && is_e164_prefix(end)
+            && start.len() == end.len()
+            && start <= end;
+        valid.then(|| Self::Range {
+            start: start.to_string(),
+            end: end.to_string(),
+        })
+    }
+
+    /// DB に保存する phone_number 列の値（範囲は開始番号）
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Exact(number) | Self::Prefix(number) => number,
+            Self::Range { start, .. } => start,
+        }
+    }
+
+    pub fn match_type(&self) -> &'static str {
+        match self {
+            Self::Exact(_) => MATCH_EXACT,
+            Self::Prefix(_) => MATCH_PREFIX,
+            Self::Range { .. } => MATCH_RANGE,
+        }
+    }
+
+    pub fn range_end(&self) -> Option<&str> {
+        match self {
+            Self::Range { end, .. } => Some(end),
+            _ => None,
+        }
+    }
+
+    pub fn matches(&self, number: &str) -> bool {
+        match self {
+            Self::Exact(value) => value == number,
+            Self::Prefix(prefix) => number.starts_with(prefix.as_str()),
+            Self::Range { start, end } => {
+                number.len() == start.len() && start.as_str() <= number && number <= end.as_str()
+            }
+        }
+    }
+
+    /// 一致の具体性。完全一致が最優先で、以降は固定される先頭桁が長いものほど優先する。
+    /// 固定桁数が同じなら桁数まで決まる範囲を前方一致より優先する。
+    fn specificity(&self) -> (u8, usize, u8) {
+        match self {
+            Self::Exact(value) => (1, value.len(), 0),
+            Self::Prefix(prefix) => (0, prefix.len(), 0),
+            Self::Range { start, end } => (0, common_prefix_len(start, end), 1),
+        }
+    }
+}
+
+/// 照合候補（DB 行）。`did` は DID 限定の行なら Some。
+#[derive(Debug, Clone)]
+pub struct PatternCandidate<T> {
+    pub pattern: NumberPattern,
+    pub did: Option<String>,
+    pub value: T,
+}
+
+/// 最長一致の候補を返す。具体性が同じなら DID 限定の行を優先する。
+pub fn best_match<T>(number: &str, candidates: Vec<PatternCandidate<T>>) -> Option<T> {
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.pattern.matches(number))
+        .max_by_key(|candidate| (candidate.pattern.specificity(), candidate.did.is_some()))
+        .map(|candidate| candidate.value)
+}
+
+/// 前方一致をインデックスで引くための検索キー（番号自身を含む全ての先頭部分）。
+pub fn number_prefixes(number: &str) -> Vec<String> {
+    (MIN_PREFIX_LEN..=number.len())
+        .filter(|end| number.is_char_boundary(*end))
+        .map(|end| number[..end].to_string())
+        .collect()
+}
+
+/// 範囲検索（int8range）用に "+" を除いた数値へ変換する。
+pub fn number_as_i64(number: &str) -> Option<i64> {
+    let digits = number.strip_prefix('+')?;
+    if digits.is_empty() || !digits.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+fn is_e164_prefix(value: &str) -> bool {
+    let Some(digits) = value.strip_prefix('+') else {
+        return false;
+    };
+    let mut chars = digits.chars();
+    matches!(chars.next(), Some('1'..='9'))
+        && digits.len() <= 15
+        && chars.all(|ch| ch.is_ascii_digit())
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.bytes().zip(b.bytes()).take_while(|(x, y)| x == y).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(
+        raw: &str,
+        did: Option<&str>,
+        value: &'static str,
+    ) -> PatternCandidate<&'static str> {
+        PatternCandidate {
+            pattern: NumberPattern::parse(raw).expect("valid pattern"),
+            did: did.map(str::to_string),
+            value,
+        }
+    }
+
+    #[test]
+    fn parse_recognizes_exact_prefix_and_range() {
+        assert_eq!(
+            NumberPattern::parse("+819012345678"),
+            Some(NumberPattern::Exact("+819012345678".to_string()))
+        );
+        assert_eq!(
+            NumberPattern::parse("+8150*"),
+            Some(NumberPattern::Prefix("+8150".to_string()))
+        );
+        assert_eq!(
+            NumberPattern::parse("+81120000000-+81120999999"),
+            Some(NumberPattern::Range {
+                start: "+81120000000".to_string(),
+                end: "+81120999999".to_string(),
+            })
+        );
+        assert_eq!(NumberPattern::parse("+81120999999-+81120000000"), None);
+        assert_eq!(NumberPattern::parse("+8112000-+81120999999"), None);
+        assert_eq!(NumberPattern::parse("0901234*"), None);
+    }
+
+    #[test]
+    fn exact_match_beats_any_pattern() {
+        let candidates = vec![
+            candidate("+81*", None, "japan"),
+            candidate("+8190123*", Some("+81312345678"), "mobile-prefix"),
+            candidate("+819012345678", None, "exact"),
+        ];
+        assert_eq!(best_match("+819012345678", candidates), Some("exact"));
+    }
+
+    #[test]
+    fn longest_prefix_wins_then_did_specific_row() {
+        let candidates = vec![
+            candidate("+81*", None, "japan"),
+            candidate("+8150*", None, "ip-phone"),
+            candidate("+8150*", Some("+81312345678"), "ip-phone-support"),
+            candidate("+1*", None, "nanp"),
+        ];
+        assert_eq!(
+            best_match("+815012345678", candidates.clone()),
+            Some("ip-phone-support")
+        );
+        assert_eq!(
+            best_match("+819012345678", candidates.clone()),
+            Some("japan")
+        );
+        assert_eq!(best_match("+12025550100", candidates.clone()), Some("nanp"));
+        assert_eq!(best_match("+442071234567", candidates), None);
+    }
+
+    #[test]
+    fn range_requires_same_length_and_beats_shorter_prefix() {
+        let candidates = vec![
+            candidate("+81120*", None, "toll-free"),
+            candidate("+81120100000-+81120199999", None, "spam-block"),
+        ];
+        assert_eq!(
+            best_match("+81120150000", candidates.clone()),
+            Some("spam-block")
+        );
+        assert_eq!(
+            best_match("+81120250000", candidates.clone()),
+            Some("toll-free")
+        );
+        assert_eq!(
+            best_match("+811201500001", candidates),
+            Some("toll-free"),
+            "range never matches numbers of a different length"
+        );
+    }
+
+    #[test]
+    fn prefixes_cover_every_leading_substring() {
+        assert_eq!(number_prefixes("+8150"), vec!["+8", "+81", "+815", "+8150"]);
+        assert_eq!(number_as_i64("+81120150000"), Some(81_120_150_000));
+        assert_eq!(number_as_i64("anonymous"), None);
+    }
+}
@@ -7,11 +7,15 @@ use sqlx::postgres::PgPoolOptions;
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
+use super::number_pattern::{
+    best_match, number_as_i64, number_prefixes, NumberPattern, PatternCandidate, SPAM_MATCH_SQL,
+};
 use crate::shared::ports::announcement_port::{
     Announcement, AnnouncementError, AnnouncementFuture, AnnouncementPort, UpsertAnnouncement,
 };
 use crate::shared::ports::call_log_port::{
-    CallLogFuture, CallLogPort, CallLogPortError, EndedCallLog,
+    CallLogFuture, CallLogPort, CallLogPortError, EmotionTurn, EndedCallLog, MediaQuality,
+    RecordingPause, TransferAttempt,
 };
 use crate::shared::ports::folder_port::{
     Folder, FolderError, FolderFuture, FolderPort, UpsertFolder,
@@ -42,10 +46,14 @@ const INSERT_CALL_LOG_SQL: &str = "INSERT INTO call_logs (
                     id, started_at, external_call_id, sip_call_id, caller_number, caller_category,
                     direction, callee_number, action_code, ivr_flow_id, answered_at, ended_at, duration_sec, end_reason, status,
                     call_disposition, final_action, transfer_status,
-                    transfer_started_at, transfer_answered_at, transfer_ended_at
+                    transfer_started_at, transfer_answered_at, transfer_ended_at, emotion_timeline,
+                    schedule_id, asserted_caller_number, caller_display_name, caller_privacy,
+                    original_called_number, diversion_reason, call_variables, transfer_events,
+                    media_quality, recording_pauses
                  ) VALUES (
                     $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13,
-                    $14, $15, $16, $17, $18, $19, $20, $21
+                    $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26,
+                    $27, $28, $29, $30, $31, $32
                  )";
 
 pub struct PostgresAdapter {
@@ -62,6 +70,11 @@ impl PostgresAdapter {
         Ok(Self { pool })
     }
 
+    /// 既存の接続プールを共有する（HTTP ハンドラなどから使う）
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
     pub fn pool(&self) -> &PgPool {
         &self.pool
     }
@@ -82,17 +95,32 @@ impl PostgresAdapter {
             }));
         }
 
-        if let Some(row) = sqlx::query(
-            "SELECT action_code, ivr_flow_id, recording_enabled, announce_enabled
+        let rows = sqlx::query(
+            "SELECT phone_number, match_type, did,
+                    action_code, ivr_flow_id, recording_enabled, announce_enabled
              FROM registered_numbers
-             WHERE phone_number = $1 AND deleted_at IS NULL
-             LIMIT 1",
+             WHERE phone_number = ANY($1)
+               AND (match_type = 'prefix' OR phone_number = $2)
+               AND did IS NULL
+               AND deleted_at IS NULL",
         )
+        .bind(number_prefixes(phone_number))
         .bind(phone_number)
-        .fetch_optional(pool)
+        .fetch_all(pool)
         .await
-        .map_err(map_lookup_err)?
-        {
+        .map_err(map_lookup_err)?;
+        let mut candidates = Vec::with_capacity(rows.len());
+        for row in rows {
+            let pattern_number: String = row.try_get("phone_number").map_err(map_lookup_err)?;
+            let match_type: String = row.try_get("match_type").map_err(map_lookup_err)?;
+            candidates.push(PatternCandidate {
+                pattern: NumberPattern::from_columns(&pattern_number, &match_type, None),
+                did: row.try_get("did").map_err(map_lookup_err)?,
+                value: row,
+            });
+        }
+
+        if let Some(row) = best_match(phone_number, candidates) {
             let action_code: String = row.try_get("action_code").map_err(map_lookup_err)?;
             let ivr_flow_id: Option<Uuid> = row.try_get("ivr_flow_id").map_err(map_lookup_err)?;
             let recording_enabled: bool =
@@ -538,7 +566,7 @@ impl PostgresAdapter {
         pool: &PgPool,
     ) -> Result<Vec<RegisteredNumber>, RegisteredNumberError> {
         let rows = sqlx::query(
-            "SELECT id, phone_number, name, category, action_code, ivr_flow_id,
+            "SELECT id, phone_number, did, match_type, name, category, action_code, ivr_flow_id,
                     recording_enabled, announce_enabled, notes, folder_id,
                     version, deleted_at, created_at, updated_at
              FROM registered_numbers
@@ -556,6 +584,10 @@ impl PostgresAdapter {
                 phone_number: row
                     .try_get("phone_number")
                     .map_err(map_registered_number_read_err)?,
+                did: row.try_get("did").map_err(map_registered_number_read_err)?,
+                match_type: row
+                    .try_get("match_type")
+                    .map_err(map_registered_number_read_err)?,
                 name: row
                     .try_get("name")
                     .map_err(map_registered_number_read_err)?,
@@ -605,11 +637,14 @@ impl PostgresAdapter {
         let upsert_result = sqlx::query(
             "INSERT INTO registered_numbers (
                  id, phone_number, name, category, action_code, ivr_flow_id,
-                 recording_enabled, announce_enabled, notes, folder_id, version, deleted_at
+                 recording_enabled, announce_enabled, notes, folder_id, version, deleted_at, did,
+                 match_type
              )
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NULL)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NULL, $12, $13)
              ON CONFLICT (id) DO UPDATE SET
                  phone_number = EXCLUDED.phone_number,
+                 did = EXCLUDED.did,
+                 match_type = EXCLUDED.match_type,
                  name = EXCLUDED.name,
                  category = EXCLUDED.category,
                  action_code = EXCLUDED.action_code,
@@ -634,6 +669,8 @@ impl PostgresAdapter {
         .bind(input.notes)
         .bind(input.folder_id)
         .bind(input.version)
+        .bind(input.did)
+        .bind(input.match_type)
         .execute(pool)
         .await
         .map_err(map_registered_number_write_err)?;
@@ -651,7 +688,7 @@ impl PostgresAdapter {
         pool: &PgPool,
     ) -> Result<Vec<RoutingRule>, RoutingRuleError> {
         let rows = sqlx::query(
-            "SELECT id, caller_category, action_code, ivr_flow_id, priority,
+            "SELECT id, caller_category, did, action_code, ivr_flow_id, priority,
                     is_active, folder_id, version, created_at, updated_at
              FROM routing_rules
              ORDER BY priority ASC, created_at ASC",
@@ -667,6 +704,7 @@ impl PostgresAdapter {
                 caller_category: row
                     .try_get("caller_category")
                     .map_err(map_routing_rule_read_err)?,
+                did: row.try_get("did").map_err(map_routing_rule_read_err)?,
                 action_code: row
                     .try_get("action_code")
                     .map_err(map_routing_rule_read_err)?,
@@ -700,11 +738,12 @@ impl PostgresAdapter {
         let upsert_result = sqlx::query(
             "INSERT INTO routing_rules (
                  id, caller_category, action_code, ivr_flow_id, priority,
-                 is_active, folder_id, version
+                 is_active, folder_id, version, did
              )
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
              ON CONFLICT (id) DO UPDATE SET
                  caller_category = EXCLUDED.caller_category,
+                 did = EXCLUDED.did,
                  action_code = EXCLUDED.action_code,
                  ivr_flow_id = EXCLUDED.ivr_flow_id,
                  priority = EXCLUDED.priority,
@@ -722,6 +761,7 @@ impl PostgresAdapter {
         .bind(input.is_active)
         .bind(input.folder_id)
         .bind(input.version)
+        .bind(input.did)
         .execute(pool)
         .await
         .map_err(map_routing_rule_write_err)?;
@@ -760,9 +800,87 @@ fn build_call_log_sync_payload(call_log: &EndedCallLog) -> Value {
         "transferStartedAt": call_log.transfer_started_at.as_ref().map(DateTime::to_rfc3339),
         "transferAnsweredAt": call_log.transfer_answered_at.as_ref().map(DateTime::to_rfc3339),
         "transferEndedAt": call_log.transfer_ended_at.as_ref().map(DateTime::to_rfc3339),
+        "emotionTimeline": build_emotion_timeline_json(&call_log.emotion_timeline),
+        "scheduleId": call_log.schedule_id.map(|value| value.to_string()),
+        "assertedCallerNumber": call_log.asserted_caller_number.clone(),
+        "callerDisplayName": call_log.caller_display_name.clone(),
+        "callerPrivacy": call_log.caller_privacy,
+        "originalCalledNumber": call_log.original_called_number.clone(),
+        "diversionReason": call_log.diversion_reason.clone(),
+        "callVariables": call_log.call_variables.clone(),
+        "transferEvents": build_transfer_events_json(&call_log.transfer_events),
+        "mediaQuality": call_log.media_quality.as_ref().map(build_media_quality_json),
+        "recordingPauses": build_recording_pauses_json(&call_log.recording_pauses),
+    })
+}
+
+fn build_recording_pauses_json(pauses: &[RecordingPause]) -> Value {
+    Value::Array(
+        pauses
+            .iter()
+            .map(|pause| {
+                json!({
+                    "pausedAt": pause.paused_at.to_rfc3339(),
+                    "resumedAt": pause.resumed_at.to_rfc3339(),
+                    "startSec": pause.start_sec,
+                    "endSec": pause.end_sec,
+                    "pausedBy": pause.paused_by,
+                    "resumedBy": pause.resumed_by,
+                })
+            })
+            .collect(),
+    )
+}
+
+fn build_media_quality_json(quality: &MediaQuality) -> Value {
+    json!({
+        "packetsReceived": quality.packets_received,
+        "packetsLost": quality.packets_lost,
+        "maxJitterMs": quality.max_jitter_ms,
+        "avgJitterMs": quality.avg_jitter_ms,
+        "reorderedPackets": quality.reordered_packets,
+        "lateDroppedPackets": quality.late_dropped_packets,
+        "rttMs": quality.rtt_ms,
+        "mos": quality.mos,
+        "belowThreshold": quality.below_threshold,
     })
 }
 
+fn build_transfer_events_json(events: &[TransferAttempt]) -> Value {
+    Value::Array(
+        events
+            .iter()
+            .map(|event| {
+                json!({
+                    "huntGroup": event.hunt_group.clone(),
+                    "member": event.member.clone(),
+                    "startedAt": event.started_at.to_rfc3339(),
+                    "endedAt": event.ended_at.to_rfc3339(),
+                    "outcome": event.outcome.clone(),
+                    "sipStatus": event.sip_status,
+                })
+            })
+            .collect(),
+    )
+}
+
+fn build_emotion_timeline_json(timeline: &[EmotionTurn]) -> Value {
+    Value::Array(
+        timeline
+            .iter()
+            .map(|turn| {
+                json!({
+                    "turn": turn.turn,
+                    "occurredAt": turn.occurred_at.to_rfc3339(),
+                    "emotion": turn.emotion.clone(),
+                    "confidence": turn.confidence,
+                    "transcript": turn.transcript.clone(),
+                })
+            })
+            .collect(),
+    )
+}
+
 impl PhoneLookupPort for PostgresAdapter {
     fn lookup_phone(&self, phone_number: String) -> PhoneLookupFuture {
         let pool = self.pool.clone();
@@ -805,6 +923,22 @@ impl CallLogPort for PostgresAdapter {
                 .bind(call_log.transfer_started_at)
                 .bind(call_log.transfer_answered_at)
                 .bind(call_log.transfer_ended_at)
+                .bind(build_emotion_timeline_json(&call_log.emotion_timeline))
+                .bind(call_log.schedule_id)
+                .bind(call_log.asserted_caller_number.clone())
+                .bind(call_log.caller_display_name.clone())
+                .bind(call_log.caller_privacy)
+                .bind(call_log.original_called_number.clone())
+                .bind(call_log.diversion_reason.clone())
+                .bind(json!(call_log.call_variables))
+                .bind(build_transfer_events_json(&call_log.transfer_events))
+                .bind(
+                    call_log
+                        .media_quality
+                        .as_ref()
+                        .map(build_media_quality_json),
+                )
+                .bind(build_recording_pauses_json(&call_log.recording_pauses))
                 .execute(&mut *tx)
                 .await
                 .map_err(map_call_log_write_err)?;
@@ -1118,17 +1252,13 @@ impl SyncOutboxPort for PostgresAdapter {
 }
 
 async fn is_spam_number(pool: &PgPool, phone_number: &str) -> Result<bool, PhoneLookupError> {
-    let exists = sqlx::query_scalar::<_, bool>(
-        "SELECT EXISTS(
-            SELECT 1
-            FROM spam_numbers
-            WHERE phone_number = $1 AND deleted_at IS NULL
-        )",
-    )
-    .bind(phone_number)
-    .fetch_one(pool)
-    .await
-    .map_err(map_lookup_err)?;
+    let exists = sqlx::query_scalar::<_, bool>(SPAM_MATCH_SQL)
+        .bind(number_prefixes(phone_number))
+        .bind(phone_number)
+        .bind(number_as_i64(phone_number))
+        .fetch_one(pool)
+        .await
+        .map_err(map_lookup_err)?;
     Ok(exists)
 }
 
@@ -1227,7 +1357,8 @@ fn map_sync_outbox_write_err(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
+    use std::collections::BTreeMap;
     use uuid::Uuid;
 
     fn sample_ended_call_log() -> EndedCallLog {
@@ -1240,11 +1371,17 @@ mod tests {
             external_call_id: "external-1".to_string(),
             sip_call_id: "sip-1".to_string(),
             caller_number: Some("+819011112222".to_string()),
+            asserted_caller_number: None,
+            caller_display_name: None,
+            caller_privacy: false,
+            original_called_number: None,
+            diversion_reason: None,
             direction: "outbound".to_string(),
             callee_number: Some("09012345678".to_string()),
             caller_category: "registered".to_string(),
             action_code: "VR".to_string(),
             ivr_flow_id: None,
+            schedule_id: None,
             answered_at: None,
             end_reason: "normal".to_string(),
             status: "ended".to_string(),
@@ -1255,6 +1392,11 @@ mod tests {
             transfer_answered_at: None,
             transfer_ended_at: None,
             ivr_events: Vec::new(),
+            emotion_timeline: Vec::new(),
+            call_variables: BTreeMap::new(),
+            transfer_events: Vec::new(),
+            media_quality: None,
+            recording_pauses: Vec::new(),
             recording: None,
         }
     }
@@ -1283,5 +1425,164 @@ mod tests {
 
         assert_eq!(payload["direction"], "inbound");
         assert!(payload["calleeNumber"].is_null());
+        assert!(payload["scheduleId"].is_null());
+    }
+
+    #[test]
+    fn call_log_sync_payload_includes_matched_schedule_id() {
+        let mut call_log = sample_ended_call_log();
+        let schedule_id = Uuid::now_v7();
+        call_log.schedule_id = Some(schedule_id);
+        let payload = build_call_log_sync_payload(&call_log);
+
+        assert_eq!(payload["scheduleId"], schedule_id.to_string());
+        assert!(INSERT_CALL_LOG_SQL.contains("schedule_id"));
+    }
+
+    #[test]
+    fn call_log_sync_payload_includes_caller_identity() {
+        let mut call_log = sample_ended_call_log();
+        call_log.caller_number = Some("anonymous".to_string());
+        call_log.asserted_caller_number = Some("+819033334444".to_string());
+        call_log.caller_privacy = true;
+        call_log.original_called_number = Some("+81312345678".to_string());
+        call_log.diversion_reason = Some("no-answer".to_string());
+        let payload = build_call_log_sync_payload(&call_log);
+
+        assert_eq!(payload["callerNumber"], "anonymous");
+        assert_eq!(payload["assertedCallerNumber"], "+819033334444");
+        assert_eq!(payload["callerPrivacy"], true);
+        assert_eq!(payload["originalCalledNumber"], "+81312345678");
+        assert_eq!(payload["diversionReason"], "no-answer");
+        assert!(INSERT_CALL_LOG_SQL.contains("asserted_caller_number"));
+    }
+
+    #[test]
+    fn call_log_sync_payload_includes_call_variables() {
+        let mut call_log = sample_ended_call_log();
+        call_log
+            .call_variables
+            .insert("member_id".to_string(), "12345".to_string());
+        let payload = build_call_log_sync_payload(&call_log);
+
+        assert_eq!(payload["callVariables"]["member_id"], "12345");
+        assert!(INSERT_CALL_LOG_SQL.contains("call_variables"));
+        assert!(INSERT_CALL_LOG_SQL.contains("$29"));
+    }
+
+    #[test]
+    fn call_log_sync_payload_includes_transfer_events() {
+        let mut call_log = sample_ended_call_log();
+        call_log.transfer_events = vec![
+            TransferAttempt {
+                hunt_group: "sales".to_string(),
+                member: "sip:101@pbx.example.com".to_string(),
+                started_at: call_log.started_at,
+                ended_at: call_log.started_at,
+                outcome: "no_answer".to_string(),
+                sip_status: None,
+            },
+            TransferAttempt {
+                hunt_group: "sales".to_string(),
+                member: "sip:102@pbx.example.com".to_string(),
+                started_at: call_log.started_at,
+                ended_at: call_log.ended_at,
+                outcome: "busy".to_string(),
+                sip_status: Some(486),
+            },
+        ];
+        let payload = build_call_log_sync_payload(&call_log);
+
+        assert_eq!(payload["transferEvents"][0]["outcome"], "no_answer");
+        assert!(payload["transferEvents"][0]["sipStatus"].is_null());
+        assert_eq!(
+            payload["transferEvents"][1]["member"],
+            "sip:102@pbx.example.com"
+        );
+        assert_eq!(payload["transferEvents"][1]["sipStatus"], 486);
+        assert!(INSERT_CALL_LOG_SQL.contains("transfer_events"));
+    }
+
+    #[test]
+    fn call_log_sync_payload_includes_media_quality() {
+        let mut call_log = sample_ended_call_log();
+        assert!(build_call_log_sync_payload(&call_log)["mediaQuality"].is_null());
+
+        call_log.media_quality = Some(MediaQuality {
+            packets_received: 1450,
+            packets_lost: 50,
+            max_jitter_ms: 42.5,
+            avg_jitter_ms: 12.0,
+            reordered_packets: 3,
+            late_dropped_packets: 1,
+            rtt_ms: None,
+            mos: 3.25,
+            below_threshold: true,
+        });
+        let payload = build_call_log_sync_payload(&call_log);
+
+        assert_eq!(payload["mediaQuality"]["packetsLost"], 50);
+        assert_eq!(payload["mediaQuality"]["mos"], 3.25);
+        assert!(payload["mediaQuality"]["rttMs"].is_null());
+        assert_eq!(payload["mediaQuality"]["belowThreshold"], true);
+        assert!(INSERT_CALL_LOG_SQL.contains("media_quality"));
+        assert!(INSERT_CALL_LOG_SQL.contains("$31"));
+    }
+
+    #[test]
+    fn call_log_sync_payload_includes_recording_pauses() {
+        let mut call_log = sample_ended_call_log();
+        assert_eq!(
+            build_call_log_sync_payload(&call_log)["recordingPauses"],
+            json!([])
+        );
+
+        let paused_at = Utc.with_ymd_and_hms(2026, 3, 13, 10, 0, 0).unwrap();
+        call_log.recording_pauses = vec![RecordingPause {
+            paused_at,
+            resumed_at: paused_at + chrono::Duration::seconds(40),
+            start_sec: Some(12.5),
+            end_sec: Some(52.5),
+            paused_by: "dtmf".to_string(),
+            resumed_by: "api".to_string(),
+        }];
+        let payload = build_call_log_sync_payload(&call_log);
+
+        let pause = &payload["recordingPauses"][0];
+        assert_eq!(pause["pausedBy"], "dtmf");
+        assert_eq!(pause["startSec"], 12.5);
+        assert_eq!(pause["resumedAt"], "2026-03-13T10:00:40+00:00");
+        assert!(INSERT_CALL_LOG_SQL.contains("recording_pauses"));
+        assert!(INSERT_CALL_LOG_SQL.contains("$32"));
+    }
+
+    #[test]
+    fn call_log_sync_payload_includes_emotion_timeline_in_turn_order() {
+        let mut call_log = sample_ended_call_log();
+        call_log.emotion_timeline = vec![
+            EmotionTurn {
+                turn: 0,
+                occurred_at: call_log.started_at,
+                emotion: "neutral".to_string(),
+                confidence: 0.5,
+                transcript: "もしもし".to_string(),
+            },
+            EmotionTurn {
+                turn: 1,
+                occurred_at: call_log.ended_at,
+                emotion: "angry".to_string(),
+                confidence: 0.75,
+                transcript: "早くしてください".to_string(),
+            },
+        ];
+        let payload = build_call_log_sync_payload(&call_log);
+
+        let timeline = payload["emotionTimeline"]
+            .as_array()
+            .expect("timeline array");
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0]["turn"], 0);
+        assert_eq!(timeline[1]["emotion"], "angry");
+        assert_eq!(timeline[1]["transcript"], "早くしてください");
     }
 }
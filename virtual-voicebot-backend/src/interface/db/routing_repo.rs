@@ -2,9 +2,12 @@ use serde_json::Value;
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
+use super::number_pattern::{
+    best_match, number_as_i64, number_prefixes, NumberPattern, PatternCandidate, SPAM_MATCH_SQL,
+};
 use crate::shared::ports::routing_port::{
-    CallActionRuleRow, IvrDestinationRow, IvrMenuRow, RegisteredNumberRow, RoutingFuture,
-    RoutingPort, RoutingPortError, RoutingRuleRow,
+    CallActionRuleRow, IvrDestinationRow, IvrMenuRow, IvrSpeechRouteRow, RegisteredNumberRow,
+    RoutingFuture, RoutingPort, RoutingPortError, RoutingRuleRow,
 };
 
 pub struct RoutingRepoImpl {
@@ -21,72 +24,107 @@ impl RoutingPort for RoutingRepoImpl {
     fn find_registered_number(
         &self,
         phone_number: &str,
+        did: Option<&str>,
     ) -> RoutingFuture<Option<RegisteredNumberRow>> {
         let pool = self.pool.clone();
         let phone_number = phone_number.to_string();
+        let did = did.map(str::to_string);
         Box::pin(async move {
-            let row = sqlx::query(
-                "SELECT action_code, ivr_flow_id, recording_enabled, announce_enabled, group_id
+            // 検索キーは番号の先頭部分（最大 16 件）なので一意インデックスで引ける
+            let rows = sqlx::query(
+                "SELECT id, phone_number, match_type, did,
+                        action_code, ivr_flow_id, recording_enabled, announce_enabled, group_id
                  FROM registered_numbers
-                 WHERE phone_number = $1 AND deleted_at IS NULL
-                 LIMIT 1",
+                 WHERE phone_number = ANY($1)
+                   AND (match_type = 'prefix' OR phone_number = $2)
+                   AND (did IS NULL OR did = $3)
+                   AND deleted_at IS NULL",
             )
-            .bind(phone_number)
-            .fetch_optional(&pool)
+            .bind(number_prefixes(&phone_number))
+            .bind(&phone_number)
+            .bind(did)
+            .fetch_all(&pool)
             .await
             .map_err(map_read_err)?;
 
-            let Some(row) = row else {
-                return Ok(None);
-            };
-
-            Ok(Some(RegisteredNumberRow {
-                action_code: row.try_get("action_code").map_err(map_read_err)?,
-                ivr_flow_id: row.try_get("ivr_flow_id").map_err(map_read_err)?,
-                recording_enabled: row.try_get("recording_enabled").map_err(map_read_err)?,
-                announce_enabled: row.try_get("announce_enabled").map_err(map_read_err)?,
-                announcement_id: None,
-                group_id: row.try_get("group_id").map_err(map_read_err)?,
-            }))
+            let mut candidates = Vec::with_capacity(rows.len());
+            for row in rows {
+                candidates.push(PatternCandidate {
+                    pattern: pattern_from_row(&row)?,
+                    did: row.try_get("did").map_err(map_read_err)?,
+                    value: RegisteredNumberRow {
+                        id: row.try_get("id").map_err(map_read_err)?,
+                        action_code: row.try_get("action_code").map_err(map_read_err)?,
+                        ivr_flow_id: row.try_get("ivr_flow_id").map_err(map_read_err)?,
+                        recording_enabled: row
+                            .try_get("recording_enabled")
+                            .map_err(map_read_err)?,
+                        announce_enabled: row.try_get("announce_enabled").map_err(map_read_err)?,
+                        announcement_id: None,
+                        group_id: row.try_get("group_id").map_err(map_read_err)?,
+                    },
+                });
+            }
+            Ok(best_match(&phone_number, candidates))
         })
     }
 
-    fn find_caller_group(&self, phone_number: &str) -> RoutingFuture<Option<Uuid>> {
+    fn find_caller_group(
+        &self,
+        phone_number: &str,
+        did: Option<&str>,
+    ) -> RoutingFuture<Option<Uuid>> {
         let pool = self.pool.clone();
         let phone_number = phone_number.to_string();
+        let did = did.map(str::to_string);
         Box::pin(async move {
-            let row = sqlx::query(
-                "SELECT group_id
+            let rows = sqlx::query(
+                "SELECT phone_number, match_type, did, group_id
                  FROM registered_numbers
-                 WHERE phone_number = $1
+                 WHERE phone_number = ANY($1)
+                   AND (match_type = 'prefix' OR phone_number = $2)
+                   AND (did IS NULL OR did = $3)
                    AND group_id IS NOT NULL
-                   AND deleted_at IS NULL
-                 LIMIT 1",
+                   AND deleted_at IS NULL",
             )
-            .bind(phone_number)
-            .fetch_optional(&pool)
+            .bind(number_prefixes(&phone_number))
+            .bind(&phone_number)
+            .bind(did)
+            .fetch_all(&pool)
             .await
             .map_err(map_read_err)?;
 
-            let Some(row) = row else {
-                return Ok(None);
-            };
-
-            row.try_get("group_id").map_err(map_read_err).map(Some)
+            let mut candidates = Vec::with_capacity(rows.len());
+            for row in rows {
+                candidates.push(PatternCandidate {
+                    pattern: pattern_from_row(&row)?,
+                    did: row.try_get("did").map_err(map_read_err)?,
+                    value: row.try_get::<Uuid, _>("group_id").map_err(map_read_err)?,
+                });
+            }
+            Ok(best_match(&phone_number, candidates))
         })
     }
 
-    fn find_call_action_rule(&self, group_id: Uuid) -> RoutingFuture<Option<CallActionRuleRow>> {
+    fn find_call_action_rule(
+        &self,
+        group_id: Uuid,
+        did: Option<&str>,
+    ) -> RoutingFuture<Option<CallActionRuleRow>> {
         let pool = self.pool.clone();
+        let did = did.map(str::to_string);
         Box::pin(async move {
             let row = sqlx::query(
                 "SELECT id, action_config
                  FROM call_action_rules
-                 WHERE caller_group_id = $1 AND is_active = TRUE
-                 ORDER BY priority ASC
+                 WHERE caller_group_id = $1
+                   AND (did IS NULL OR did = $2)
+                   AND is_active = TRUE
+                 ORDER BY (did IS NULL) ASC, priority ASC
                  LIMIT 1",
             )
             .bind(group_id)
+            .bind(did)
             .fetch_optional(&pool)
             .await
             .map_err(map_read_err)?;
@@ -106,17 +144,13 @@ impl RoutingPort for RoutingRepoImpl {
         let pool = self.pool.clone();
         let phone_number = phone_number.to_string();
         Box::pin(async move {
-            sqlx::query_scalar::<_, bool>(
-                "SELECT EXISTS(
-                    SELECT 1
-                    FROM spam_numbers
-                    WHERE phone_number = $1 AND deleted_at IS NULL
-                )",
-            )
-            .bind(phone_number)
-            .fetch_one(&pool)
-            .await
-            .map_err(map_read_err)
+            sqlx::query_scalar::<_, bool>(SPAM_MATCH_SQL)
+                .bind(number_prefixes(&phone_number))
+                .bind(&phone_number)
+                .bind(number_as_i64(&phone_number))
+                .fetch_one(&pool)
+                .await
+                .map_err(map_read_err)
         })
     }
 
@@ -128,28 +162,65 @@ impl RoutingPort for RoutingRepoImpl {
                 "SELECT EXISTS(
                     SELECT 1
                     FROM registered_numbers
-                    WHERE phone_number = $1 AND deleted_at IS NULL
+                    WHERE phone_number = ANY($1)
+                      AND (match_type = 'prefix' OR phone_number = $2)
+                      AND deleted_at IS NULL
                 )",
             )
-            .bind(phone_number)
+            .bind(number_prefixes(&phone_number))
+            .bind(&phone_number)
             .fetch_one(&pool)
             .await
             .map_err(map_read_err)
         })
     }
 
-    fn find_routing_rule(&self, category: &str) -> RoutingFuture<Option<RoutingRuleRow>> {
+    fn find_routing_rule(
+        &self,
+        category: &str,
+        did: Option<&str>,
+    ) -> RoutingFuture<Option<RoutingRuleRow>> {
         let pool = self.pool.clone();
         let category = category.to_string();
+        let did = did.map(str::to_string);
         Box::pin(async move {
             let row = sqlx::query(
                 "SELECT id, action_code, ivr_flow_id
                  FROM routing_rules
-                 WHERE caller_category = $1 AND is_active = TRUE
-                 ORDER BY priority ASC
+                 WHERE caller_category = $1
+                   AND (did IS NULL OR did = $2)
+                   AND is_active = TRUE
+                 ORDER BY (did IS NULL) ASC, priority ASC
                  LIMIT 1",
             )
             .bind(category)
+            .bind(did)
+            .fetch_optional(&pool)
+            .await
+            .map_err(map_read_err)?;
+
+            let Some(row) = row else {
+                return Ok(None);
+            };
+
+            Ok(Some(RoutingRuleRow {
+                id: row.try_get("id").map_err(map_read_err)?,
+                action_code: row.try_get("action_code").map_err(map_read_err)?,
+                ivr_flow_id: row.try_get("ivr_flow_id").map_err(map_read_err)?,
+                announcement_id: None,
+            }))
+        })
+    }
+
+    fn find_routing_rule_by_id(&self, rule_id: Uuid) -> RoutingFuture<Option<RoutingRuleRow>> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            let row = sqlx::query(
+                "SELECT id, action_code, ivr_flow_id
+                 FROM routing_rules
+                 WHERE id = $1 AND is_active = TRUE",
+            )
+            .bind(rule_id)
             .fetch_optional(&pool)
             .await
             .map_err(map_read_err)?;
@@ -221,7 +292,13 @@ impl RoutingPort for RoutingRepoImpl {
                         root.audio_file_url AS root_audio_file_url,
                         keypad.id AS keypad_node_id,
                         keypad.timeout_sec AS keypad_timeout_sec,
-                        keypad.max_retries AS keypad_max_retries
+                        keypad.max_retries AS keypad_max_retries,
+                        keypad.node_type AS input_node_type,
+                        keypad.min_digits,
+                        keypad.max_digits,
+                        keypad.terminator,
+                        keypad.inter_digit_timeout_sec,
+                        keypad.variable_name
                  FROM ivr_flows flow
                  JOIN ivr_nodes root
                    ON root.flow_id = flow.id
@@ -229,7 +306,7 @@ impl RoutingPort for RoutingRepoImpl {
                   AND root.node_type = 'ANNOUNCE'
                  JOIN ivr_nodes keypad
                    ON keypad.parent_id = root.id
-                  AND keypad.node_type = 'KEYPAD'
+                  AND keypad.node_type IN ('KEYPAD', 'DIGITS', 'SPEECH')
                  WHERE flow.id = $1
                    AND flow.is_active = TRUE
                  ORDER BY root.created_at ASC, keypad.created_at ASC
@@ -250,6 +327,14 @@ impl RoutingPort for RoutingRepoImpl {
                 audio_file_url: row.try_get("root_audio_file_url").map_err(map_read_err)?,
                 timeout_sec: row.try_get("keypad_timeout_sec").map_err(map_read_err)?,
                 max_retries: row.try_get("keypad_max_retries").map_err(map_read_err)?,
+                input_node_type: row.try_get("input_node_type").map_err(map_read_err)?,
+                min_digits: row.try_get("min_digits").map_err(map_read_err)?,
+                max_digits: row.try_get("max_digits").map_err(map_read_err)?,
+                terminator: row.try_get("terminator").map_err(map_read_err)?,
+                inter_digit_timeout_sec: row
+                    .try_get("inter_digit_timeout_sec")
+                    .map_err(map_read_err)?,
+                variable_name: row.try_get("variable_name").map_err(map_read_err)?,
             }))
         })
     }
@@ -307,7 +392,7 @@ impl RoutingPort for RoutingRepoImpl {
                  JOIN ivr_nodes dest
                    ON dest.id = transition.to_node_id
                  WHERE from_node.flow_id = $1
-                   AND from_node.node_type = 'KEYPAD'
+                   AND from_node.node_type IN ('KEYPAD', 'DIGITS', 'SPEECH')
                    AND transition.input_type = 'DTMF'
                    AND transition.dtmf_key = $2
                  ORDER BY transition.created_at ASC, transition.id ASC
@@ -370,7 +455,7 @@ impl RoutingPort for RoutingRepoImpl {
                  JOIN ivr_nodes dest
                    ON dest.id = transition.to_node_id
                  WHERE from_node.flow_id = $1
-                   AND from_node.node_type = 'KEYPAD'
+                   AND from_node.node_type IN ('KEYPAD', 'DIGITS', 'SPEECH')
                    AND transition.input_type = 'TIMEOUT'
                  ORDER BY transition.created_at ASC, transition.id ASC
                  LIMIT 1",
@@ -431,7 +516,7 @@ impl RoutingPort for RoutingRepoImpl {
                  JOIN ivr_nodes dest
                    ON dest.id = transition.to_node_id
                  WHERE from_node.flow_id = $1
-                   AND from_node.node_type = 'KEYPAD'
+                   AND from_node.node_type IN ('KEYPAD', 'DIGITS', 'SPEECH')
                    AND transition.input_type = 'INVALID'
                  ORDER BY transition.created_at ASC, transition.id ASC
                  LIMIT 1",
@@ -444,6 +529,75 @@ impl RoutingPort for RoutingRepoImpl {
             map_destination_row(row)
         })
     }
+
+    fn find_ivr_complete_destination(
+        &self,
+        input_node_id: Uuid,
+    ) -> RoutingFuture<Option<IvrDestinationRow>> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            let row = sqlx::query(
+                "SELECT transition.id AS transition_id,
+                        dest.id AS destination_node_id,
+                        dest.action_code,
+                        dest.audio_file_url,
+                        dest.tts_text
+                 FROM ivr_transitions transition
+                 JOIN ivr_nodes dest
+                   ON dest.id = transition.to_node_id
+                 WHERE transition.from_node_id = $1
+                   AND transition.input_type = 'COMPLETE'
+                 ORDER BY transition.created_at ASC
+                 LIMIT 1",
+            )
+            .bind(input_node_id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(map_read_err)?;
+
+            map_destination_row(row)
+        })
+    }
+
+    fn find_ivr_speech_routes(&self, input_node_id: Uuid) -> RoutingFuture<Vec<IvrSpeechRouteRow>> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            let rows = sqlx::query(
+                "SELECT transition.id AS transition_id,
+                        transition.keywords,
+                        transition.intent,
+                        dest.id AS destination_node_id,
+                        dest.action_code,
+                        dest.audio_file_url,
+                        dest.tts_text
+                 FROM ivr_transitions transition
+                 JOIN ivr_nodes dest
+                   ON dest.id = transition.to_node_id
+                 WHERE transition.from_node_id = $1
+                   AND transition.input_type = 'SPEECH'
+                 ORDER BY transition.created_at ASC, transition.id ASC",
+            )
+            .bind(input_node_id)
+            .fetch_all(&pool)
+            .await
+            .map_err(map_read_err)?;
+
+            let mut routes = Vec::with_capacity(rows.len());
+            for row in rows {
+                let keywords: Vec<String> = row.try_get("keywords").map_err(map_read_err)?;
+                let intent: Option<String> = row.try_get("intent").map_err(map_read_err)?;
+                let Some(destination) = map_destination_row(Some(row))? else {
+                    continue;
+                };
+                routes.push(IvrSpeechRouteRow {
+                    keywords,
+                    intent,
+                    destination,
+                });
+            }
+            Ok(routes)
+        })
+    }
 }
 
 fn map_destination_row(
@@ -461,6 +615,16 @@ fn map_destination_row(
     }))
 }
 
+fn pattern_from_row(row: &sqlx::postgres::PgRow) -> Result<NumberPattern, RoutingPortError> {
+    let phone_number: String = row.try_get("phone_number").map_err(map_read_err)?;
+    let match_type: String = row.try_get("match_type").map_err(map_read_err)?;
+    Ok(NumberPattern::from_columns(
+        &phone_number,
+        &match_type,
+        None,
+    ))
+}
+
 fn map_read_err(err: sqlx::Error) -> RoutingPortError {
     RoutingPortError::ReadFailed(err.to_string())
 }
@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::shared::ports::voicemail_port::{
+    NewVoicemailMessage, VoicemailError, VoicemailFuture, VoicemailMailbox, VoicemailMessage,
+    VoicemailPort, MESSAGE_STATUS_DELETED,
+};
+
+const MAILBOX_COLUMNS: &str =
+    "id, mailbox_number, did, pin_hash, pin_locked_until, notify_line, webhook_url";
+
+pub struct VoicemailRepoImpl {
+    pool: PgPool,
+}
+
+impl VoicemailRepoImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl VoicemailPort for VoicemailRepoImpl {
+    fn find_mailbox_for_did(&self, did: Option<&str>) -> VoicemailFuture<Option<VoicemailMailbox>> {
+        let pool = self.pool.clone();
+        let did = did.map(str::to_string);
+        Box::pin(async move {
+            let row = sqlx::query(&format!(
+                "SELECT {MAILBOX_COLUMNS}
+                 FROM voicemail_mailboxes
+                 WHERE deleted_at IS NULL
+                   AND (did IS NULL OR did = $1)
+                 ORDER BY (did IS NULL) ASC
+                 LIMIT 1"
+            ))
+            .bind(did)
+            .fetch_optional(&pool)
+            .await
+            .map_err(map_read_err)?;
+            row.as_ref().map(mailbox_from_row).transpose()
+        })
+    }
+
+    fn find_mailbox(&self, mailbox_number: &str) -> VoicemailFuture<Option<VoicemailMailbox>> {
+        let pool = self.pool.clone();
+        let mailbox_number = mailbox_number.to_string();
+        Box::pin(async move {
+            let row = sqlx::query(&format!(
+                "SELECT {MAILBOX_COLUMNS}
+                 FROM voicemail_mailboxes
+                 WHERE mailbox_number = $1 AND deleted_at IS NULL
+                 LIMIT 1"
+            ))
+            .bind(mailbox_number)
+            .fetch_optional(&pool)
+            .await
+            .map_err(map_read_err)?;
+            row.as_ref().map(mailbox_from_row).transpose()
+        })
+    }
+
+    fn update_pin_hash(&self, mailbox_id: Uuid, pin_hash: String) -> VoicemailFuture<()> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE voicemail_mailboxes
+                 SET pin_hash = $2, updated_at = NOW()
+                 WHERE id = $1",
+            )
+            .bind(mailbox_id)
+            .bind(pin_hash)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(())
+        })
+    }
+
+    fn record_pin_failure(
+        &self,
+        mailbox_id: Uuid,
+        max_attempts: u32,
+        lockout: Duration,
+    ) -> VoicemailFuture<Option<DateTime<Utc>>> {
+        let pool = self.pool.clone();
+        let max_attempts = i32::try_from(max_attempts).unwrap_or(i32::MAX);
+        Box::pin(async move {
+            // ロックしたら回数は 0 に戻す（解除後は再び max_attempts 回まで試せる）
+            let row = sqlx::query(
+                "UPDATE voicemail_mailboxes
+                 SET failed_pin_attempts = CASE
+                         WHEN failed_pin_attempts + 1 >= $2 THEN 0
+                         ELSE failed_pin_attempts + 1
+                     END,
+                     pin_locked_until = CASE
+                         WHEN failed_pin_attempts + 1 >= $2 THEN NOW() + make_interval(secs => $3)
+                         ELSE pin_locked_until
+                     END,
+                     updated_at = NOW()
+                 WHERE id = $1
+                 RETURNING CASE WHEN failed_pin_attempts = 0 THEN pin_locked_until END
+                     AS locked_until",
+            )
+            .bind(mailbox_id)
+            .bind(max_attempts)
+            .bind(lockout.as_secs_f64())
+            .fetch_optional(&pool)
+            .await
+            .map_err(map_write_err)?;
+            match row {
+                Some(row) => row.try_get("locked_until").map_err(map_read_err),
+                None => Ok(None),
+            }
+        })
+    }
+
+    fn reset_pin_failures(&self, mailbox_id: Uuid) -> VoicemailFuture<()> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE voicemail_mailboxes
+                 SET failed_pin_attempts = 0, pin_locked_until = NULL, updated_at = NOW()
+                 WHERE id = $1 AND (failed_pin_attempts > 0 OR pin_locked_until IS NOT NULL)",
+            )
+            .bind(mailbox_id)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(())
+        })
+    }
+
+    fn insert_message(&self, message: NewVoicemailMessage) -> VoicemailFuture<()> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO voicemail_messages (
+                    id, mailbox_id, call_log_id, caller_number, duration_sec, file_path, created_at
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(message.id)
+            .bind(message.mailbox_id)
+            .bind(message.call_log_id)
+            .bind(message.caller_number)
+            .bind(message.duration_sec)
+            .bind(message.file_path)
+            .bind(message.created_at)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(())
+        })
+    }
+
+    fn update_transcript(&self, message_id: Uuid, transcript: String) -> VoicemailFuture<()> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE voicemail_messages
+                 SET transcript = $2, updated_at = NOW()
+                 WHERE id = $1",
+            )
+            .bind(message_id)
+            .bind(transcript)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(())
+        })
+    }
+
+    fn list_messages(&self, mailbox_id: Uuid) -> VoicemailFuture<Vec<VoicemailMessage>> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            let rows = sqlx::query(
+                "SELECT id, mailbox_id, call_log_id, caller_number, duration_sec, file_path,
+                        transcript, status, listened_at, created_at
+                 FROM voicemail_messages
+                 WHERE mailbox_id = $1 AND status <> $2
+                 ORDER BY (status = 'new') DESC, created_at ASC",
+            )
+            .bind(mailbox_id)
+            .bind(MESSAGE_STATUS_DELETED)
+            .fetch_all(&pool)
+            .await
+            .map_err(map_read_err)?;
+            rows.iter().map(message_from_row).collect()
+        })
+    }
+
+    fn mark_listened(&self, message_id: Uuid) -> VoicemailFuture<()> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE voicemail_messages
+                 SET listened_at = COALESCE(listened_at, NOW()), updated_at = NOW()
+                 WHERE id = $1",
+            )
+            .bind(message_id)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(())
+        })
+    }
+
+    fn update_status(&self, message_id: Uuid, status: &'static str) -> VoicemailFuture<()> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE voicemail_messages
+                 SET status = $2, updated_at = NOW()
+                 WHERE id = $1",
+            )
+            .bind(message_id)
+            .bind(status)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(())
+        })
+    }
+}
+
+fn mailbox_from_row(row: &PgRow) -> Result<VoicemailMailbox, VoicemailError> {
+    Ok(VoicemailMailbox {
+        id: row.try_get("id").map_err(map_read_err)?,
+        mailbox_number: row.try_get("mailbox_number").map_err(map_read_err)?,
+        did: row.try_get("did").map_err(map_read_err)?,
+        pin_hash: row
+            .try_get::<String, _>("pin_hash")
+            .map_err(map_read_err)?
+            .trim()
+            .to_ascii_lowercase(),
+        pin_locked_until: row.try_get("pin_locked_until").map_err(map_read_err)?,
+        notify_line: row.try_get("notify_line").map_err(map_read_err)?,
+        webhook_url: row.try_get("webhook_url").map_err(map_read_err)?,
+    })
+}
+
+fn message_from_row(row: &PgRow) -> Result<VoicemailMessage, VoicemailError> {
+    Ok(VoicemailMessage {
+        id: row.try_get("id").map_err(map_read_err)?,
+        mailbox_id: row.try_get("mailbox_id").map_err(map_read_err)?,
+        call_log_id: row.try_get("call_log_id").map_err(map_read_err)?,
+        caller_number: row.try_get("caller_number").map_err(map_read_err)?,
+        duration_sec: row.try_get("duration_sec").map_err(map_read_err)?,
+        file_path: row.try_get("file_path").map_err(map_read_err)?,
+        transcript: row.try_get("transcript").map_err(map_read_err)?,
+        status: row.try_get("status").map_err(map_read_err)?,
+        listened_at: row.try_get("listened_at").map_err(map_read_err)?,
+        created_at: row.try_get("created_at").map_err(map_read_err)?,
+    })
+}
+
+fn map_read_err(err: sqlx::Error) -> VoicemailError {
+    VoicemailError::ReadFailed(err.to_string())
+}
+
+fn map_write_err(err: sqlx::Error) -> VoicemailError {
+    VoicemailError::WriteFailed(err.to_string())
+}
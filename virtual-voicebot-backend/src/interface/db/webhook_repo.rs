@@ -0,0 +1,190 @@
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::shared::ports::webhook_port::{
+    NewWebhookDelivery, WebhookAttemptFailure, WebhookDelivery, WebhookDeliveryError,
+    WebhookDeliveryFilter, WebhookDeliveryFuture, WebhookDeliveryPort, DELIVERY_STATUS_DEAD,
+    DELIVERY_STATUS_DELIVERED, DELIVERY_STATUS_PENDING,
+};
+
+const DELIVERY_COLUMNS: &str = "id, subscription_id, event_type, event_id, call_id, url, payload, \
+     status, attempts, next_attempt_at, last_status_code, last_error, created_at, delivered_at";
+
+pub struct WebhookRepoImpl {
+    pool: PgPool,
+}
+
+impl WebhookRepoImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl WebhookDeliveryPort for WebhookRepoImpl {
+    fn enqueue(&self, delivery: NewWebhookDelivery) -> WebhookDeliveryFuture<()> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO webhook_deliveries (
+                    id, subscription_id, event_type, event_id, call_id, url, payload
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(delivery.id)
+            .bind(delivery.subscription_id)
+            .bind(delivery.event_type.as_str())
+            .bind(delivery.event_id)
+            .bind(delivery.call_id)
+            .bind(delivery.url)
+            .bind(delivery.payload)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(())
+        })
+    }
+
+    fn claim_due(
+        &self,
+        limit: i64,
+        lease: std::time::Duration,
+    ) -> WebhookDeliveryFuture<Vec<WebhookDelivery>> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            // 複数プロセスで同じ行を送らないよう、取り出した行の送信時刻を lease 分ずらす
+            let rows = sqlx::query(&format!(
+                "UPDATE webhook_deliveries
+                 SET next_attempt_at = NOW() + make_interval(secs => $3), updated_at = NOW()
+                 WHERE id IN (
+                     SELECT id FROM webhook_deliveries
+                     WHERE status = $1 AND next_attempt_at <= NOW()
+                     ORDER BY next_attempt_at ASC
+                     LIMIT $2
+                     FOR UPDATE SKIP LOCKED
+                 )
+                 RETURNING {DELIVERY_COLUMNS}"
+            ))
+            .bind(DELIVERY_STATUS_PENDING)
+            .bind(limit)
+            .bind(lease.as_secs_f64())
+            .fetch_all(&pool)
+            .await
+            .map_err(map_write_err)?;
+            rows.iter().map(delivery_from_row).collect()
+        })
+    }
+
+    fn mark_delivered(&self, id: Uuid, status_code: i32) -> WebhookDeliveryFuture<()> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE webhook_deliveries
+                 SET status = $2, attempts = attempts + 1, last_status_code = $3,
+                     last_error = NULL, delivered_at = NOW(), updated_at = NOW()
+                 WHERE id = $1",
+            )
+            .bind(id)
+            .bind(DELIVERY_STATUS_DELIVERED)
+            .bind(status_code)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(())
+        })
+    }
+
+    fn mark_failed(&self, id: Uuid, failure: WebhookAttemptFailure) -> WebhookDeliveryFuture<()> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            let status = if failure.retry_at.is_some() {
+                DELIVERY_STATUS_PENDING
+            } else {
+                DELIVERY_STATUS_DEAD
+            };
+            sqlx::query(
+                "UPDATE webhook_deliveries
+                 SET status = $2, attempts = attempts + 1, last_status_code = $3,
+                     last_error = $4, next_attempt_at = COALESCE($5, next_attempt_at),
+                     updated_at = NOW()
+                 WHERE id = $1",
+            )
+            .bind(id)
+            .bind(status)
+            .bind(failure.status_code)
+            .bind(failure.error)
+            .bind(failure.retry_at)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(())
+        })
+    }
+
+    fn list(&self, filter: WebhookDeliveryFilter) -> WebhookDeliveryFuture<Vec<WebhookDelivery>> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            let rows = sqlx::query(&format!(
+                "SELECT {DELIVERY_COLUMNS}
+                 FROM webhook_deliveries
+                 WHERE ($1::text IS NULL OR status = $1)
+                   AND ($2::text IS NULL OR subscription_id = $2)
+                   AND ($3::text IS NULL OR call_id = $3)
+                 ORDER BY created_at DESC
+                 LIMIT $4"
+            ))
+            .bind(filter.status)
+            .bind(filter.subscription_id)
+            .bind(filter.call_id)
+            .bind(filter.limit)
+            .fetch_all(&pool)
+            .await
+            .map_err(map_read_err)?;
+            rows.iter().map(delivery_from_row).collect()
+        })
+    }
+
+    fn requeue(&self, id: Uuid) -> WebhookDeliveryFuture<bool> {
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            let result = sqlx::query(
+                "UPDATE webhook_deliveries
+                 SET status = $2, attempts = 0, next_attempt_at = NOW(), updated_at = NOW()
+                 WHERE id = $1 AND status = $3",
+            )
+            .bind(id)
+            .bind(DELIVERY_STATUS_PENDING)
+            .bind(DELIVERY_STATUS_DEAD)
+            .execute(&pool)
+            .await
+            .map_err(map_write_err)?;
+            Ok(result.rows_affected() > 0)
+        })
+    }
+}
+
+fn delivery_from_row(row: &PgRow) -> Result<WebhookDelivery, WebhookDeliveryError> {
+    Ok(WebhookDelivery {
+        id: row.try_get("id").map_err(map_read_err)?,
+        subscription_id: row.try_get("subscription_id").map_err(map_read_err)?,
+        event_type: row.try_get("event_type").map_err(map_read_err)?,
+        event_id: row.try_get("event_id").map_err(map_read_err)?,
+        call_id: row.try_get("call_id").map_err(map_read_err)?,
+        url: row.try_get("url").map_err(map_read_err)?,
+        payload: row.try_get("payload").map_err(map_read_err)?,
+        status: row.try_get("status").map_err(map_read_err)?,
+        attempts: row.try_get("attempts").map_err(map_read_err)?,
+        next_attempt_at: row.try_get("next_attempt_at").map_err(map_read_err)?,
+        last_status_code: row.try_get("last_status_code").map_err(map_read_err)?,
+        last_error: row.try_get("last_error").map_err(map_read_err)?,
+        created_at: row.try_get("created_at").map_err(map_read_err)?,
+        delivered_at: row.try_get("delivered_at").map_err(map_read_err)?,
+    })
+}
+
+fn map_read_err(err: sqlx::Error) -> WebhookDeliveryError {
+    WebhookDeliveryError::ReadFailed(err.to_string())
+}
+
+fn map_write_err(err: sqlx::Error) -> WebhookDeliveryError {
+    WebhookDeliveryError::WriteFailed(err.to_string())
+}
@@ -1,25 +1,58 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::OsStr;
-use std::io::SeekFrom;
 use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 use log::info;
 use serde::Serialize;
 use sqlx::PgPool;
-use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
+use uuid::Uuid;
 
+use crate::interface::db::{
+    CallerMemoryRepoImpl, PostgresAdapter, RoutingRepoImpl, WebhookRepoImpl,
+};
+use crate::interface::sync::pending_notification_count;
+use crate::service::call_events::{CallEventHub, StreamEvent};
+use crate::service::recording::{archive, signed_url};
+use crate::service::routing::{normalize_phone_number_e164, RuleEvaluator};
+use crate::service::runtime_config;
+use crate::service::webhook::{
+    self,
+    log::{WebhookDeliveriesResponse, WebhookDeliveryView},
+};
 use crate::shared::config;
+use crate::shared::entities::CallerIdentity;
+use crate::shared::metrics;
+use crate::shared::ports::caller_memory_port::CallerMemoryPort;
+use crate::shared::ports::live_call::{
+    LiveCallCommand, LiveCallError, LiveCallPort, LiveCallStatus,
+};
+use crate::shared::ports::webhook_port::WebhookDeliveryPort;
 
 pub mod ingest;
+mod monitor;
+
+tokio::task_local! {
+    /// このリクエストに返す `Access-Control-Allow-Origin`（handle_conn が Origin を読んでから決める）
+    static CORS_ALLOW_ORIGIN: RefCell<Option<String>>;
+}
 
 /// 録音ファイルを静的配信するシンプルなHTTPサーバ。
-/// GET /recordings/<callId>/mixed.wav のようなパスだけを扱う。
-pub async fn spawn_recording_server(bind: &str, base_dir: PathBuf, pool: Option<PgPool>) {
+/// GET /recordings/<callId>/mixed.{wav,flac,opus} のようなパスだけを扱う（暗号化済みは復号して返す）。
+pub async fn spawn_recording_server(
+    bind: &str,
+    base_dir: PathBuf,
+    pool: Option<PgPool>,
+    live_calls: Arc<dyn LiveCallPort>,
+    call_events: Arc<CallEventHub>,
+) {
     let bind = bind.to_string();
     tokio::spawn(async move {
-        if let Err(e) = run(&bind, base_dir, pool).await {
+        if let Err(e) = run(&bind, base_dir, pool, live_calls, call_events).await {
             log::error!("[http] recording server error: {:?}", e);
         }
     });
@@ -30,43 +63,74 @@ pub async fn spawn_recording_server_with_listener(
     listener: TcpListener,
     base_dir: PathBuf,
     pool: Option<PgPool>,
+    live_calls: Arc<dyn LiveCallPort>,
+    call_events: Arc<CallEventHub>,
 ) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
-        if let Err(e) = run_with_listener(listener, base_dir, pool).await {
+        if let Err(e) = run_with_listener(listener, base_dir, pool, live_calls, call_events).await {
             log::error!("[http] recording server error: {:?}", e);
         }
     })
 }
 
-async fn run(bind: &str, base_dir: PathBuf, pool: Option<PgPool>) -> std::io::Result<()> {
+async fn run(
+    bind: &str,
+    base_dir: PathBuf,
+    pool: Option<PgPool>,
+    live_calls: Arc<dyn LiveCallPort>,
+    call_events: Arc<CallEventHub>,
+) -> std::io::Result<()> {
     let listener = TcpListener::bind(bind).await?;
     log::info!("[http] serving recordings on {}", bind);
+    let security = config::http_security_config();
+    if security.api_tokens.is_empty() {
+        log::warn!("[http] HTTP_API_TOKENS is not set; every /api/* request is rejected (401)");
+    }
+    if security.recording_url_secret.is_none() {
+        log::warn!("[http] RECORDING_URL_SIGNING_SECRET is not set; recordings are served without signed URLs");
+    }
 
-    run_with_listener(listener, base_dir, pool).await
+    run_with_listener(listener, base_dir, pool, live_calls, call_events).await
 }
 
 async fn run_with_listener(
     listener: TcpListener,
     base_dir: PathBuf,
     pool: Option<PgPool>,
+    live_calls: Arc<dyn LiveCallPort>,
+    call_events: Arc<CallEventHub>,
 ) -> std::io::Result<()> {
     loop {
         let (mut socket, _) = listener.accept().await?;
         let base_dir = base_dir.clone();
         let pool = pool.clone();
-        tokio::spawn(async move {
-            let _ = handle_conn(&mut socket, &base_dir, pool).await;
-        });
+        let live_calls = live_calls.clone();
+        let call_events = call_events.clone();
+        tokio::spawn(CORS_ALLOW_ORIGIN.scope(RefCell::new(None), async move {
+            let _ = handle_conn(
+                &mut socket,
+                &base_dir,
+                pool,
+                live_calls.as_ref(),
+                &call_events,
+            )
+            .await;
+        }));
     }
 }
 
 /// Handles a single TCP connection and serves recording files over HTTP.
 ///
 /// This function reads an HTTP request from `socket`, validates and sanitizes the request
-/// path (must begin with `/recordings/` and resolve to `<callId>/mixed.wav`), and responds
+/// path (must begin with `/recordings/` and resolve to `<callId>/mixed.{wav,flac,opus}`), and responds
 /// with the requested audio file or an appropriate HTTP error. It supports GET and HEAD,
-/// Range requests, enforces payload and I/O timeouts, logs each response, and writes
-/// complete HTTP responses to the socket.
+/// Range requests, enforces payload and I/O timeouts, writes an audit log line for each
+/// recording response, and writes complete HTTP responses to the socket.
+///
+/// `/api/*` requires `Authorization: Bearer <token>` matching `HTTP_API_TOKENS` and is refused
+/// entirely when no token is configured. Recordings require a valid signed URL (or an API token) when
+/// `RECORDING_URL_SIGNING_SECRET` is set. `Access-Control-Allow-Origin` follows
+/// `HTTP_CORS_ALLOWED_ORIGINS`.
 ///
 /// # Examples
 ///
@@ -78,7 +142,16 @@ async fn run_with_listener(
 /// // Example usage (illustrative): connect to a server socket and handle the connection.
 /// // In real usage `socket` will be the accepted client stream from a listener.
 /// if let Ok(mut socket) = TcpStream::connect("127.0.0.1:0").await {
-///     let _ = crate::handle_conn(&mut socket, Path::new("/var/lib/recordings"), None).await;
+///     let live_calls = crate::shared::ports::live_call::NoopLiveCallPort::new();
+///     let call_events = crate::service::call_events::CallEventHub::new(100);
+///     let _ = crate::handle_conn(
+///         &mut socket,
+///         Path::new("/var/lib/recordings"),
+///         None,
+///         &live_calls,
+///         &call_events,
+///     )
+///     .await;
 /// }
 /// # })
 /// ```
@@ -86,6 +159,8 @@ async fn handle_conn(
     socket: &mut tokio::net::TcpStream,
     base_dir: &Path,
     pool: Option<PgPool>,
+    live_calls: &dyn LiveCallPort,
+    call_events: &CallEventHub,
 ) -> std::io::Result<()> {
     let mut buf = vec![0u8; 4096];
     let mut read_len = 0usize;
@@ -126,8 +201,37 @@ async fn handle_conn(
         }
     }
 
-    let req_path = path.to_string();
-    let range_value = headers.get("range").map(|v| v.as_str());
+    let security = config::http_security_config();
+    let allow_origin = security.cors_allow_origin(headers.get("origin").map(String::as_str));
+    let _ = CORS_ALLOW_ORIGIN.try_with(|cell| *cell.borrow_mut() = allow_origin);
+    if method == "OPTIONS" {
+        return handle_preflight(socket).await;
+    }
+
+    if let Some(route) = path
+        .split('?')
+        .next()
+        .filter(|route| route.starts_with("/api/"))
+    {
+        if !is_monitor_route(method, route) && !api_authorized(method, path, &headers) {
+            log::warn!(
+                "[http] {} {} rejected: missing or invalid API token",
+                method,
+                route
+            );
+            let headers = [("WWW-Authenticate", "Bearer".to_string())];
+            return write_response_with_headers(
+                socket,
+                401,
+                "Unauthorized",
+                &headers,
+                b"",
+                0,
+                true,
+            )
+            .await;
+        }
+    }
 
     if method == "GET" && path == "/api/sync/status" {
         let json = match pool.as_ref() {
@@ -179,51 +283,178 @@ async fn handle_conn(
         return write_json_response(socket, 200, "OK", json.as_bytes()).await;
     }
 
+    if method == "GET" {
+        let (route, query) = path.split_once('?').unwrap_or((path, ""));
+        if route == "/api/routing/explain" {
+            return handle_routing_explain(socket, query, pool.as_ref()).await;
+        }
+    }
+
+    if let Some(route) = path
+        .split('?')
+        .next()
+        .filter(|route| route.starts_with("/api/webhooks/"))
+    {
+        let query = path.split_once('?').map_or("", |(_, query)| query);
+        if method == "GET" && route == "/api/webhooks/deliveries" {
+            return handle_webhook_deliveries(socket, query, pool.as_ref()).await;
+        }
+        if let Some(id) = route
+            .strip_prefix("/api/webhooks/deliveries/")
+            .and_then(|rest| rest.strip_suffix("/retry"))
+            .filter(|_| method == "POST")
+        {
+            return handle_webhook_retry(socket, id, pool.as_ref()).await;
+        }
+    }
+
+    if method == "POST" && path == "/api/admin/config/reload" {
+        return handle_config_reload(socket).await;
+    }
+
+    if method == "GET" && path == "/metrics" {
+        return handle_metrics(socket, pool.as_ref(), live_calls).await;
+    }
+
+    if method == "GET" && path == "/api/calls" {
+        return handle_live_call_list(socket, live_calls).await;
+    }
+
+    if method == "GET" {
+        let (route, query) = path.split_once('?').unwrap_or((path, ""));
+        let params = parse_query(query);
+        if let Some(call_id) = route
+            .strip_prefix("/api/calls/")
+            .and_then(|rest| rest.strip_suffix("/monitor"))
+        {
+            return monitor::handle_monitor(socket, live_calls, call_id, &params, &headers).await;
+        }
+        let call_filter = if route == "/api/events" {
+            Some(params.get("callId").cloned())
+        } else {
+            route
+                .strip_prefix("/api/calls/")
+                .and_then(|rest| rest.strip_suffix("/events"))
+                .map(|call_id| Some(percent_decode(call_id)))
+        };
+        if let Some(call_filter) = call_filter {
+            let last_event_id = headers
+                .get("last-event-id")
+                .or_else(|| params.get("lastEventId"))
+                .and_then(|id| id.trim().parse::<u64>().ok());
+            return handle_event_stream(socket, call_events, call_filter, last_event_id).await;
+        }
+    }
+
+    if method == "POST" {
+        let (route, query) = path.split_once('?').unwrap_or((path, ""));
+        if let Some(rest) = route.strip_prefix("/api/calls/") {
+            if let Some((call_id, action)) = rest.rsplit_once('/') {
+                return handle_live_call_command(socket, live_calls, call_id, action, query).await;
+            }
+        }
+    }
+
+    if method == "DELETE" {
+        if let Some(number) = path.strip_prefix("/api/caller-memory/") {
+            return handle_caller_memory_erase(socket, number, pool.as_ref()).await;
+        }
+    }
+
     let is_get = method == "GET";
     let is_head = method == "HEAD";
-    if (!is_get && !is_head) || !path.starts_with("/recordings/") {
-        log_recording_response(404, None, &req_path, range_value);
+    // 署名（クエリ）はログに残さない
+    let (route, query) = path.split_once('?').unwrap_or((path, ""));
+    let mut audit = RecordingAudit {
+        client: client_address(socket, &headers),
+        call_id: None,
+        path: route,
+        range: headers.get("range").map(|v| v.as_str()),
+        access: "-",
+    };
+    let range_value = audit.range;
+    if (!is_get && !is_head) || !route.starts_with("/recordings/") {
+        audit.log(404);
         return write_response(socket, 404, "Not Found", b"").await;
     }
 
-    let rel = match path.strip_prefix("/recordings/") {
+    let rel = match route.strip_prefix("/recordings/") {
         Some(rest) if !rest.is_empty() => rest,
         _ => {
-            log_recording_response(404, None, &req_path, range_value);
+            audit.log(404);
             return write_response(socket, 404, "Not Found", b"").await;
         }
     };
     let rel = sanitize_path(rel);
-    if rel.components().count() != 2 || rel.file_name() != Some(OsStr::new("mixed.wav")) {
-        log_recording_response(404, None, &req_path, range_value);
+    let file_name = rel
+        .file_name()
+        .and_then(OsStr::to_str)
+        .filter(|name| is_recording_file_name(name))
+        .map(str::to_string);
+    let Some(file_name) = file_name.filter(|_| rel.components().count() == 2) else {
+        audit.log(404);
         return write_response(socket, 404, "Not Found", b"").await;
-    }
+    };
     let call_id = rel
         .components()
         .next()
         .and_then(|comp| comp.as_os_str().to_str())
         .map(|value| value.to_string());
-    let file_path = base_dir.join(&rel);
+    audit.call_id = call_id.clone();
+    match authorize_recording(call_id.as_deref().unwrap_or_default(), query, &headers) {
+        Ok(access) => audit.access = access,
+        Err(err) => {
+            audit.access = "denied";
+            audit.log(403);
+            log::warn!("[http] recording {} rejected: {}", route, err);
+            return write_response(socket, 403, "Forbidden", b"").await;
+        }
+    }
+    let call_dir = base_dir.join(rel.parent().unwrap_or(Path::new("")));
 
-    let meta = match tokio::time::timeout(
-        config::timeouts().recording_io,
-        tokio::fs::metadata(&file_path),
-    )
-    .await
-    {
-        Ok(Ok(meta)) => meta,
-        Ok(Err(_)) => {
-            log_recording_response(404, call_id.as_deref(), &req_path, range_value);
+    let opened = tokio::time::timeout(config::timeouts().recording_io, async {
+        let requested = file_name.clone();
+        tokio::task::spawn_blocking(move || open_recording(&call_dir, &requested))
+            .await
+            .map_err(std::io::Error::other)
+    })
+    .await;
+    let (stored, mut reader) = match opened {
+        Ok(Ok(RecordingLookup::Found(stored, reader))) => (stored, reader),
+        Ok(Ok(RecordingLookup::Archived(stored))) => {
+            // 後処理で mixed.wav が変換された。旧 URL（通話ログの録音 URL）は変換後へ誘導する
+            // 署名は通話単位なので、クエリをそのまま付け直せば変換後にも使える
+            audit.log(302);
+            let mut location = format!(
+                "/recordings/{}/{}",
+                call_id.as_deref().unwrap_or_default(),
+                stored.file_name
+            );
+            if !query.is_empty() {
+                location.push('?');
+                location.push_str(query);
+            }
+            let headers = [("Location", location)];
+            return write_response_with_headers(socket, 302, "Found", &headers, b"", 0, true).await;
+        }
+        Ok(Ok(RecordingLookup::Missing)) | Ok(Err(_)) => {
+            audit.log(404);
             return write_response(socket, 404, "Not Found", b"").await;
         }
+        Ok(Ok(RecordingLookup::Unreadable(err))) => {
+            log::warn!("[http] recording {} unreadable: {}", route, err);
+            audit.log(500);
+            return write_response(socket, 500, "Internal Server Error", b"").await;
+        }
         Err(_) => {
-            log_recording_response(504, call_id.as_deref(), &req_path, range_value);
+            audit.log(504);
             return write_response(socket, 504, "Gateway Timeout", b"").await;
         }
     };
-    let total_len = meta.len();
+    let content_type = stored.content_type().to_string();
+    let total_len = reader.len();
     if total_len == 0 {
-        log_recording_response(404, call_id.as_deref(), &req_path, range_value);
+        audit.log(404);
         return write_response(socket, 404, "Not Found", b"").await;
     }
 
@@ -231,7 +462,7 @@ async fn handle_conn(
         let (start, end) = match parse_range(range, total_len) {
             Ok(r) => r,
             Err(_) => {
-                log_recording_response(416, call_id.as_deref(), &req_path, range_value);
+                audit.log(416);
                 let mut resp = Vec::new();
                 resp.extend_from_slice(b"HTTP/1.1 416 Range Not Satisfiable\r\n");
                 resp.extend_from_slice(b"Content-Type: text/plain\r\n");
@@ -239,7 +470,7 @@ async fn handle_conn(
                 resp.extend_from_slice(
                     format!("Content-Range: bytes */{}\r\n", total_len).as_bytes(),
                 );
-                resp.extend_from_slice(b"Access-Control-Allow-Origin: *\r\n");
+                resp.extend_from_slice(cors_header_lines().as_bytes());
                 resp.extend_from_slice(b"Content-Length: 0\r\n");
                 resp.extend_from_slice(b"Connection: close\r\n\r\n");
                 return socket.write_all(&resp).await;
@@ -247,19 +478,19 @@ async fn handle_conn(
         };
         let chunk_len = end.saturating_sub(start).saturating_add(1);
         if chunk_len > usize::MAX as u64 {
-            log_recording_response(416, call_id.as_deref(), &req_path, range_value);
+            audit.log(416);
             return write_response(socket, 416, "Range Not Satisfiable", b"").await;
         }
+        let headers = [
+            ("Content-Type", content_type),
+            ("Accept-Ranges", "bytes".to_string()),
+            (
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, end, total_len),
+            ),
+        ];
         if is_head {
-            log_recording_response(206, call_id.as_deref(), &req_path, range_value);
-            let headers = [
-                ("Content-Type", "audio/wav".to_string()),
-                ("Accept-Ranges", "bytes".to_string()),
-                (
-                    "Content-Range",
-                    format!("bytes {}-{}/{}", start, end, total_len),
-                ),
-            ];
+            audit.log(206);
             return write_response_with_headers(
                 socket,
                 206,
@@ -271,25 +502,14 @@ async fn handle_conn(
             )
             .await;
         }
-        let read_res = tokio::time::timeout(config::timeouts().recording_io, async {
-            let mut file = tokio::fs::File::open(&file_path).await?;
-            file.seek(SeekFrom::Start(start)).await?;
-            let mut buf = vec![0u8; chunk_len as usize];
-            file.read_exact(&mut buf).await?;
-            Ok::<_, std::io::Error>(buf)
-        })
+        let read_res = tokio::time::timeout(
+            config::timeouts().recording_io,
+            tokio::task::spawn_blocking(move || reader.read_range(start, chunk_len)),
+        )
         .await;
         match read_res {
-            Ok(Ok(bytes)) => {
-                log_recording_response(206, call_id.as_deref(), &req_path, range_value);
-                let headers = [
-                    ("Content-Type", "audio/wav".to_string()),
-                    ("Accept-Ranges", "bytes".to_string()),
-                    (
-                        "Content-Range",
-                        format!("bytes {}-{}/{}", start, end, total_len),
-                    ),
-                ];
+            Ok(Ok(Ok(bytes))) => {
+                audit.log(206);
                 write_response_with_headers(
                     socket,
                     206,
@@ -301,32 +521,33 @@ async fn handle_conn(
                 )
                 .await
             }
-            Ok(Err(_)) => {
-                log_recording_response(404, call_id.as_deref(), &req_path, range_value);
+            Ok(_) => {
+                audit.log(404);
                 write_response(socket, 404, "Not Found", b"").await
             }
             Err(_) => {
-                log_recording_response(504, call_id.as_deref(), &req_path, range_value);
+                audit.log(504);
                 write_response(socket, 504, "Gateway Timeout", b"").await
             }
         }
-    } else if is_head {
-        log_recording_response(200, call_id.as_deref(), &req_path, range_value);
+    } else {
         let headers = [
-            ("Content-Type", "audio/wav".to_string()),
+            ("Content-Type", content_type),
             ("Accept-Ranges", "bytes".to_string()),
         ];
-        write_response_with_headers(socket, 200, "OK", &headers, &[], total_len, false).await
-    } else {
-        match tokio::time::timeout(config::timeouts().recording_io, tokio::fs::read(&file_path))
-            .await
-        {
-            Ok(Ok(bytes)) => {
-                log_recording_response(200, call_id.as_deref(), &req_path, range_value);
-                let headers = [
-                    ("Content-Type", "audio/wav".to_string()),
-                    ("Accept-Ranges", "bytes".to_string()),
-                ];
+        if is_head {
+            audit.log(200);
+            return write_response_with_headers(socket, 200, "OK", &headers, &[], total_len, false)
+                .await;
+        }
+        let read_res = tokio::time::timeout(
+            config::timeouts().recording_io,
+            tokio::task::spawn_blocking(move || reader.read_all()),
+        )
+        .await;
+        match read_res {
+            Ok(Ok(Ok(bytes))) => {
+                audit.log(200);
                 write_response_with_headers(
                     socket,
                     200,
@@ -338,18 +559,707 @@ async fn handle_conn(
                 )
                 .await
             }
-            Ok(Err(_)) => {
-                log_recording_response(404, call_id.as_deref(), &req_path, range_value);
+            Ok(_) => {
+                audit.log(404);
                 write_response(socket, 404, "Not Found", b"").await
             }
             Err(_) => {
-                log_recording_response(504, call_id.as_deref(), &req_path, range_value);
+                audit.log(504);
                 write_response(socket, 504, "Gateway Timeout", b"").await
             }
         }
     }
 }
 
+enum RecordingLookup {
+    Found(archive::StoredRecording, archive::RecordingReader),
+    /// mixed.wav を求められたが、変換済みの別形式だけが残っている
+    Archived(archive::StoredRecording),
+    Missing,
+    Unreadable(archive::RecordingArchiveError),
+}
+
+/// 配信できる録音ファイル名（`mixed.wav` / `mixed.flac` / `mixed.opus`）
+fn is_recording_file_name(name: &str) -> bool {
+    matches!(
+        name.split_once('.'),
+        Some((archive::MIXED_STEM, "wav" | "flac" | "opus"))
+    )
+}
+
+fn open_recording(call_dir: &Path, file_name: &str) -> RecordingLookup {
+    let stored = match archive::find(call_dir, file_name) {
+        Some(stored) => stored,
+        None if file_name.ends_with(".wav") => {
+            return archive::find_mixed(call_dir)
+                .map_or(RecordingLookup::Missing, RecordingLookup::Archived);
+        }
+        None => return RecordingLookup::Missing,
+    };
+    let keys = &config::recording_storage_config().encryption_keys;
+    match archive::RecordingReader::open(&stored, keys) {
+        Ok(reader) => RecordingLookup::Found(stored, reader),
+        Err(archive::RecordingArchiveError::Io(err))
+            if err.kind() == std::io::ErrorKind::NotFound =>
+        {
+            RecordingLookup::Missing
+        }
+        Err(err) => RecordingLookup::Unreadable(err),
+    }
+}
+
+/// Erases the stored caller memory for a phone number (privacy request).
+///
+/// The number is normalized to E.164 the same way as at call start, so `090-...` and
+/// `%2B8190...` both address the same memory.
+async fn handle_caller_memory_erase(
+    socket: &mut tokio::net::TcpStream,
+    raw_number: &str,
+    pool: Option<&PgPool>,
+) -> std::io::Result<()> {
+    let Some(pool) = pool else {
+        return write_sync_error_response(
+            socket,
+            503,
+            "Service Unavailable",
+            "SERVICE_UNAVAILABLE",
+            "Database not available",
+        )
+        .await;
+    };
+    let decoded = raw_number.replace("%2B", "+").replace("%2b", "+");
+    let Ok(phone_number) = normalize_phone_number_e164(&decoded) else {
+        return write_sync_error_response(
+            socket,
+            400,
+            "Bad Request",
+            "INVALID_PHONE_NUMBER",
+            "Invalid phone number",
+        )
+        .await;
+    };
+    let repo = CallerMemoryRepoImpl::new(pool.clone());
+    match repo.erase(&phone_number).await {
+        Ok(erased) => {
+            info!(
+                "caller_memory_erase erased={} caller={}",
+                erased,
+                crate::shared::utils::mask_phone(&phone_number)
+            );
+            let json = serde_json::to_vec(&CallerMemoryEraseResponse { ok: true, erased })
+                .map_err(std::io::Error::other)?;
+            write_json_response(socket, 200, "OK", &json).await
+        }
+        Err(err) => {
+            log::warn!("[http] caller memory erase failed: {}", err);
+            write_sync_error_response(
+                socket,
+                500,
+                "Internal Server Error",
+                "INTERNAL_ERROR",
+                "Database error",
+            )
+            .await
+        }
+    }
+}
+
+/// Lists outbound webhook deliveries, newest first (the delivery log).
+///
+/// Query: `status` (`pending` / `delivered` / `dead`), `subscription`, `callId` and `limit`
+/// (default 50, max 500). Dead deliveries have used up their retries and stay until retried.
+async fn handle_webhook_deliveries(
+    socket: &mut tokio::net::TcpStream,
+    query: &str,
+    pool: Option<&PgPool>,
+) -> std::io::Result<()> {
+    let Some(pool) = pool else {
+        return write_sync_error_response(
+            socket,
+            503,
+            "Service Unavailable",
+            "SERVICE_UNAVAILABLE",
+            "Database not available",
+        )
+        .await;
+    };
+    let Some(filter) = webhook::log::delivery_filter(&parse_query(query)) else {
+        return write_sync_error_response(
+            socket,
+            400,
+            "Bad Request",
+            "INVALID_STATUS",
+            "status must be pending, delivered or dead",
+        )
+        .await;
+    };
+    match WebhookRepoImpl::new(pool.clone()).list(filter).await {
+        Ok(deliveries) => {
+            let response = WebhookDeliveriesResponse {
+                deliveries: deliveries.iter().map(WebhookDeliveryView::from).collect(),
+            };
+            let json = serde_json::to_vec(&response).map_err(std::io::Error::other)?;
+            write_json_response(socket, 200, "OK", &json).await
+        }
+        Err(err) => {
+            log::warn!("[http] webhook delivery list failed: {}", err);
+            write_sync_error_response(
+                socket,
+                500,
+                "Internal Server Error",
+                "INTERNAL_ERROR",
+                "Database error",
+            )
+            .await
+        }
+    }
+}
+
+/// Puts a dead-lettered webhook delivery back on the queue for immediate redelivery.
+async fn handle_webhook_retry(
+    socket: &mut tokio::net::TcpStream,
+    raw_id: &str,
+    pool: Option<&PgPool>,
+) -> std::io::Result<()> {
+    let Some(pool) = pool else {
+        return write_sync_error_response(
+            socket,
+            503,
+            "Service Unavailable",
+            "SERVICE_UNAVAILABLE",
+            "Database not available",
+        )
+        .await;
+    };
+    let Ok(id) = Uuid::parse_str(raw_id) else {
+        return write_sync_error_response(
+            socket,
+            400,
+            "Bad Request",
+            "INVALID_ID",
+            "Invalid delivery id",
+        )
+        .await;
+    };
+    match WebhookRepoImpl::new(pool.clone()).requeue(id).await {
+        Ok(true) => {
+            info!("webhook_delivery_requeued id={}", id);
+            write_json_response(socket, 200, "OK", br#"{"ok":true}"#).await
+        }
+        Ok(false) => {
+            write_sync_error_response(
+                socket,
+                404,
+                "Not Found",
+                "NOT_FOUND",
+                "No dead-lettered delivery with this id",
+            )
+            .await
+        }
+        Err(err) => {
+            log::warn!("[http] webhook delivery retry failed: {}", err);
+            write_sync_error_response(
+                socket,
+                500,
+                "Internal Server Error",
+                "INTERNAL_ERROR",
+                "Database error",
+            )
+            .await
+        }
+    }
+}
+
+/// Evaluates the routing rules for a hypothetical call without placing it.
+///
+/// Query: `caller` (omitted = anonymous), `callee` (DID, optional) and `at` (RFC 3339,
+/// defaults to now). The response is the full stage trace and the resulting action.
+async fn handle_routing_explain(
+    socket: &mut tokio::net::TcpStream,
+    query: &str,
+    pool: Option<&PgPool>,
+) -> std::io::Result<()> {
+    let Some(pool) = pool else {
+        return write_sync_error_response(
+            socket,
+            503,
+            "Service Unavailable",
+            "SERVICE_UNAVAILABLE",
+            "Database not available",
+        )
+        .await;
+    };
+    let params = parse_query(query);
+    let now = match params.get("at") {
+        Some(at) => match DateTime::parse_from_rfc3339(at) {
+            Ok(at) => at.with_timezone(&Utc),
+            Err(_) => {
+                return write_sync_error_response(
+                    socket,
+                    400,
+                    "Bad Request",
+                    "INVALID_TIMESTAMP",
+                    "at must be RFC 3339",
+                )
+                .await;
+            }
+        },
+        None => Utc::now(),
+    };
+    let caller = CallerIdentity::from_number(
+        params
+            .get("caller")
+            .map(String::as_str)
+            .unwrap_or("anonymous"),
+    );
+    let evaluator = RuleEvaluator::new(Arc::new(RoutingRepoImpl::new(pool.clone())))
+        .with_schedule_port(Arc::new(PostgresAdapter::from_pool(pool.clone())));
+    match evaluator
+        .explain(&caller, params.get("callee").map(String::as_str), now)
+        .await
+    {
+        Ok(explanation) => {
+            let json = serde_json::to_vec(&explanation).map_err(std::io::Error::other)?;
+            write_json_response(socket, 200, "OK", &json).await
+        }
+        Err(err) => {
+            log::warn!("[http] routing explain failed: {}", err);
+            write_sync_error_response(
+                socket,
+                500,
+                "Internal Server Error",
+                "INTERNAL_ERROR",
+                "Routing evaluation failed",
+            )
+            .await
+        }
+    }
+}
+
+/// Re-reads intent_router.yaml and the prompt files for new calls.
+///
+/// Calls already in progress keep the snapshot they started with. An invalid file is
+/// rejected with 422 and the current configuration stays active.
+async fn handle_config_reload(socket: &mut tokio::net::TcpStream) -> std::io::Result<()> {
+    let (status, reason, response) = match runtime_config::reload("http") {
+        Ok(snapshot) => (
+            200,
+            "OK",
+            ConfigReloadResponse {
+                ok: true,
+                generation: snapshot.generation,
+                error: None,
+            },
+        ),
+        Err(err) => (
+            422,
+            "Unprocessable Entity",
+            ConfigReloadResponse {
+                ok: false,
+                generation: runtime_config::current().generation,
+                error: Some(err.to_string()),
+            },
+        ),
+    };
+    let json = serde_json::to_vec(&response).map_err(std::io::Error::other)?;
+    write_json_response(socket, status, reason, &json).await
+}
+
+#[derive(Serialize)]
+struct ConfigReloadResponse {
+    ok: bool,
+    generation: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Streams call events as Server-Sent Events.
+///
+/// `call_filter` limits the stream to one call (`/api/calls/{callId}/events` or
+/// `/api/events?callId=`); `None` streams every call. Events after `last_event_id` are
+/// replayed from the bounded buffer first. If they have already been dropped (or the id is
+/// from before a restart) a `reset` event tells the client to reload the current state.
+/// A client that falls behind skips ahead through the same buffer instead of slowing the
+/// publisher, and one that stops reading is disconnected so it can reconnect.
+async fn handle_event_stream(
+    socket: &mut tokio::net::TcpStream,
+    call_events: &CallEventHub,
+    call_filter: Option<String>,
+    last_event_id: Option<u64>,
+) -> std::io::Result<()> {
+    let mut subscription = call_events.subscribe(last_event_id);
+    let mut head = Vec::new();
+    head.extend_from_slice(b"HTTP/1.1 200 OK\r\n");
+    head.extend_from_slice(b"Content-Type: text/event-stream\r\n");
+    head.extend_from_slice(b"Cache-Control: no-cache\r\n");
+    head.extend_from_slice(cors_header_lines().as_bytes());
+    head.extend_from_slice(b"Connection: keep-alive\r\n\r\n");
+    head.extend_from_slice(format!("retry: {}\n\n", EVENT_STREAM_RETRY_MS).as_bytes());
+    write_event_chunk(socket, &head).await?;
+
+    let mut stream = EventStreamState {
+        call_filter,
+        last_sent_id: last_event_id.unwrap_or(0),
+    };
+    if subscription.gap {
+        write_event_chunk(socket, &reset_event("replay_gap")).await?;
+    }
+    for event in subscription.replay.drain(..) {
+        stream.send(socket, &event).await?;
+    }
+
+    let mut heartbeat = tokio::time::interval(config::call_event_heartbeat());
+    heartbeat.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    heartbeat.tick().await;
+    loop {
+        tokio::select! {
+            received = subscription.rx.recv() => match received {
+                Ok(event) => stream.send(socket, &event).await?,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::debug!("[http] event stream lagged by {} events", skipped);
+                    let (replay, gap) = call_events.replay_after(stream.last_sent_id);
+                    if gap {
+                        write_event_chunk(socket, &reset_event("lagged")).await?;
+                    }
+                    for event in replay {
+                        stream.send(socket, &event).await?;
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = heartbeat.tick() => {
+                write_event_chunk(socket, b"event: ping\ndata: {}\n\n").await?;
+            }
+        }
+    }
+}
+
+/// クライアントが再接続するまでの待ち時間（SSE の retry）
+const EVENT_STREAM_RETRY_MS: u64 = 3000;
+/// この時間内に書き込めないクライアントは切断する（再接続時に Last-Event-ID で追いつく）
+const EVENT_STREAM_WRITE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+struct EventStreamState {
+    call_filter: Option<String>,
+    last_sent_id: u64,
+}
+
+impl EventStreamState {
+    /// 送信済みの id 以下（再送と購読の重なり）と対象外の通話は送らない
+    async fn send(
+        &mut self,
+        socket: &mut tokio::net::TcpStream,
+        event: &StreamEvent,
+    ) -> std::io::Result<()> {
+        if event.id <= self.last_sent_id {
+            return Ok(());
+        }
+        self.last_sent_id = event.id;
+        if let Some(call_id) = self.call_filter.as_deref() {
+            if event.call_id != call_id {
+                return Ok(());
+            }
+        }
+        let frame = format!(
+            "id: {}\nevent: {}\ndata: {}\n\n",
+            event.id, event.event, event.data
+        );
+        write_event_chunk(socket, frame.as_bytes()).await
+    }
+}
+
+fn reset_event(reason: &str) -> Vec<u8> {
+    format!("event: reset\ndata: {{\"reason\":\"{}\"}}\n\n", reason).into_bytes()
+}
+
+async fn write_event_chunk(
+    socket: &mut tokio::net::TcpStream,
+    bytes: &[u8],
+) -> std::io::Result<()> {
+    match tokio::time::timeout(EVENT_STREAM_WRITE_TIMEOUT, socket.write_all(bytes)).await {
+        Ok(result) => result,
+        Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "event stream client is not reading",
+        )),
+    }
+}
+
+/// Lists the calls currently held by the session registry with their state.
+async fn handle_live_call_list(
+    socket: &mut tokio::net::TcpStream,
+    live_calls: &dyn LiveCallPort,
+) -> std::io::Result<()> {
+    match live_calls.list_calls().await {
+        Ok(calls) => {
+            let response = LiveCallListResponse {
+                calls: calls.into_iter().map(LiveCallItem::from).collect(),
+            };
+            let json = serde_json::to_vec(&response).map_err(std::io::Error::other)?;
+            write_json_response(socket, 200, "OK", &json).await
+        }
+        Err(err) => {
+            log::warn!("[http] live call list failed: {}", err);
+            write_sync_error_response(
+                socket,
+                500,
+                "Internal Server Error",
+                "INTERNAL_ERROR",
+                "Failed to list calls",
+            )
+            .await
+        }
+    }
+}
+
+/// Prometheus scrape endpoint.
+///
+/// Counters and histograms recorded by the SIP, RTP and AI layers are rendered as is; active
+/// calls per IVR state, the sync outbox backlog and the notification queue depth are read at
+/// scrape time. A gauge whose source is unavailable (no database, unreadable queue file) is
+/// omitted rather than reported as zero.
+async fn handle_metrics(
+    socket: &mut tokio::net::TcpStream,
+    pool: Option<&PgPool>,
+    live_calls: &dyn LiveCallPort,
+) -> std::io::Result<()> {
+    let mut body = String::new();
+    metrics::render(&mut body);
+
+    match live_calls.list_calls().await {
+        Ok(calls) => {
+            let mut by_state: BTreeMap<String, usize> = BTreeMap::new();
+            for call in calls {
+                *by_state.entry(call.ivr_state).or_default() += 1;
+            }
+            let samples = by_state
+                .iter()
+                .map(|(state, count)| (vec![("ivr_state", state.as_str())], *count as f64))
+                .collect::<Vec<_>>();
+            metrics::write_gauge(
+                &mut body,
+                "voicebot_active_calls",
+                "Calls in progress by IVR state",
+                &samples,
+            );
+        }
+        Err(err) => log::warn!("[http] metrics: live call list failed: {}", err),
+    }
+
+    if let Some(pool) = pool {
+        match count_pending_outbox(pool).await {
+            Ok(pending) => metrics::write_gauge(
+                &mut body,
+                "voicebot_sync_outbox_pending",
+                "Sync outbox entries not yet sent to the frontend",
+                &[(Vec::new(), pending as f64)],
+            ),
+            Err(err) => log::warn!("[http] metrics: outbox count failed: {}", err),
+        }
+    }
+
+    let queue_file = PathBuf::from(config::notification_queue_file());
+    match pending_notification_count(&queue_file).await {
+        Ok(depth) => metrics::write_gauge(
+            &mut body,
+            "voicebot_notification_queue_depth",
+            "Incoming call notifications waiting to be sent",
+            &[(Vec::new(), depth as f64)],
+        ),
+        Err(err) => log::warn!("[http] metrics: notification queue read failed: {}", err),
+    }
+
+    let headers = [(
+        "Content-Type",
+        "text/plain; version=0.0.4; charset=utf-8".to_string(),
+    )];
+    write_response_with_headers(
+        socket,
+        200,
+        "OK",
+        &headers,
+        body.as_bytes(),
+        body.len() as u64,
+        true,
+    )
+    .await
+}
+
+async fn count_pending_outbox(pool: &PgPool) -> Result<i64, std::io::Error> {
+    tokio::time::timeout(
+        config::timeouts().recording_io,
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*)::bigint
+             FROM sync_outbox
+             WHERE processed_at IS NULL",
+        )
+        .fetch_one(pool),
+    )
+    .await
+    .map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "metrics query timeout: sync_outbox",
+        )
+    })?
+    .map_err(std::io::Error::other)
+}
+
+/// Sends an operator command to a call in progress.
+///
+/// Actions: `hangup`, `transfer?target=<group|extension|sip uri>`,
+/// `announcement?id=<announcement uuid>`, `say?text=<text>`, and `recording-pause` /
+/// `recording-resume` (silence every recorded leg and stop feeding ASR, e.g. while a card
+/// number is read out). Unknown calls return 404 and
+/// commands the session cannot take in its current state (not answered, already
+/// transferring, ...) return 409.
+async fn handle_live_call_command(
+    socket: &mut tokio::net::TcpStream,
+    live_calls: &dyn LiveCallPort,
+    raw_call_id: &str,
+    action: &str,
+    query: &str,
+) -> std::io::Result<()> {
+    let call_id = percent_decode(raw_call_id);
+    let params = parse_query(query);
+    let command = match action {
+        "hangup" => Some(LiveCallCommand::Hangup),
+        "transfer" => params
+            .get("target")
+            .map(|target| LiveCallCommand::Transfer {
+                target: target.clone(),
+            }),
+        "announcement" => params
+            .get("id")
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .map(|announcement_id| LiveCallCommand::PlayAnnouncement { announcement_id }),
+        "say" => params
+            .get("text")
+            .map(|text| LiveCallCommand::Say { text: text.clone() }),
+        "recording-pause" => Some(LiveCallCommand::PauseRecording),
+        "recording-resume" => Some(LiveCallCommand::ResumeRecording),
+        _ => {
+            return write_response(socket, 404, "Not Found", b"").await;
+        }
+    };
+    let Some(command) = command else {
+        return write_sync_error_response(
+            socket,
+            400,
+            "Bad Request",
+            "INVALID_PARAMETER",
+            "Missing or invalid command parameter",
+        )
+        .await;
+    };
+    let (status, reason, response) = match live_calls.send_command(call_id, command).await {
+        Ok(()) => (
+            200,
+            "OK",
+            LiveCallCommandResponse {
+                ok: true,
+                error: None,
+            },
+        ),
+        Err(err) => {
+            let (status, reason) = match err {
+                LiveCallError::NotFound(_) => (404, "Not Found"),
+                LiveCallError::Rejected(_) => (409, "Conflict"),
+                LiveCallError::Failed(_) => (502, "Bad Gateway"),
+            };
+            log::warn!("[http] live call command failed: {}", err);
+            (
+                status,
+                reason,
+                LiveCallCommandResponse {
+                    ok: false,
+                    error: Some(err.to_string()),
+                },
+            )
+        }
+    };
+    let json = serde_json::to_vec(&response).map_err(std::io::Error::other)?;
+    write_json_response(socket, status, reason, &json).await
+}
+
+#[derive(Serialize)]
+struct LiveCallListResponse {
+    calls: Vec<LiveCallItem>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LiveCallItem {
+    call_id: String,
+    session_state: String,
+    ivr_state: String,
+    caller: Option<String>,
+    callee: String,
+    outbound: bool,
+    answered_at: Option<String>,
+    duration_sec: u64,
+}
+
+impl From<LiveCallStatus> for LiveCallItem {
+    fn from(status: LiveCallStatus) -> Self {
+        Self {
+            call_id: status.call_id,
+            session_state: status.session_state,
+            ivr_state: status.ivr_state,
+            caller: status.caller,
+            callee: status.callee,
+            outbound: status.outbound,
+            answered_at: status.answered_at,
+            duration_sec: status.duration_sec,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct LiveCallCommandResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// `a=1&b=2` 形式のクエリを分解する。電話番号の `+` はそのまま残す（空白にはしない）
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value);
+            (!value.is_empty()).then(|| (percent_decode(name), value))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[derive(Serialize)]
+struct CallerMemoryEraseResponse {
+    ok: bool,
+    erased: bool,
+}
+
 #[derive(Serialize)]
 struct SyncStatusResponse {
     ok: bool,
@@ -549,7 +1459,7 @@ async fn write_response_with_headers(
     for (name, value) in headers {
         resp.extend_from_slice(format!("{name}: {value}\r\n").as_bytes());
     }
-    resp.extend_from_slice(b"Access-Control-Allow-Origin: *\r\n");
+    resp.extend_from_slice(cors_header_lines().as_bytes());
     resp.extend_from_slice(format!("Content-Length: {content_len}\r\n").as_bytes());
     resp.extend_from_slice(b"Connection: close\r\n\r\n");
     if write_body {
@@ -592,11 +1502,143 @@ fn parse_range(value: &str, total_len: u64) -> Result<(u64, u64), ()> {
     Ok((start, end))
 }
 
-fn log_recording_response(status: u16, call_id: Option<&str>, path: &str, range: Option<&str>) {
-    let call_id = call_id.unwrap_or("-");
-    let range = range.unwrap_or("-");
-    info!(
-        "recording_access status={} call_id={} path={} range={}",
-        status, call_id, path, range
-    );
+/// 録音へのアクセスの監査ログ。配信・拒否を問わず 1 レスポンスにつき 1 行
+struct RecordingAudit<'a> {
+    client: String,
+    call_id: Option<String>,
+    path: &'a str,
+    range: Option<&'a str>,
+    /// signed（署名 URL）/ token（API トークン）/ open（署名なし運用）/ denied
+    access: &'static str,
+}
+
+impl RecordingAudit<'_> {
+    fn log(&self, status: u16) {
+        info!(
+            "recording_access status={} call_id={} path={} range={} access={} client={}",
+            status,
+            self.call_id.as_deref().unwrap_or("-"),
+            self.path,
+            self.range.unwrap_or("-"),
+            self.access,
+            self.client
+        );
+    }
+}
+
+/// 接続元。プロキシ越しなら X-Forwarded-For も併記する
+fn client_address(socket: &tokio::net::TcpStream, headers: &HashMap<String, String>) -> String {
+    let peer = socket
+        .peer_addr()
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|_| "-".to_string());
+    match headers.get("x-forwarded-for") {
+        Some(forwarded) => format!("{}(forwarded-for={})", peer, forwarded),
+        None => peer,
+    }
+}
+
+/// 署名 URL、または API トークンで録音の取得を認可する。署名鍵が未設定なら誰でも取得できる
+fn authorize_recording(
+    call_id: &str,
+    query: &str,
+    headers: &HashMap<String, String>,
+) -> Result<&'static str, signed_url::SignedUrlError> {
+    let security = config::http_security_config();
+    let Some(secret) = security.recording_url_secret.as_deref() else {
+        return Ok("open");
+    };
+    let presented = bearer_token(headers);
+    if !security.api_tokens.is_empty() && presented.is_some_and(is_api_token) {
+        return Ok("token");
+    }
+    let params = parse_query(query);
+    signed_url::verify(
+        secret,
+        call_id,
+        params.get(signed_url::EXPIRES_PARAM).map(String::as_str),
+        params.get(signed_url::SIGNATURE_PARAM).map(String::as_str),
+        Utc::now().timestamp(),
+    )
+    .map(|()| "signed")
+}
+
+/// `/api/*` の Bearer 認証。HTTP_API_TOKENS 未設定なら全て拒否する（閉じた側に倒す）。
+/// SSE はブラウザの EventSource がヘッダを付けられないため `?token=` も受け付ける
+fn api_authorized(method: &str, path: &str, headers: &HashMap<String, String>) -> bool {
+    let (route, query) = path.split_once('?').unwrap_or((path, ""));
+    if config::http_security_config().api_tokens.is_empty() {
+        return false;
+    }
+    let is_event_stream = method == "GET" && (route == "/api/events" || route.ends_with("/events"));
+    let params = parse_query(query);
+    let presented = bearer_token(headers).or_else(|| {
+        params
+            .get("token")
+            .map(String::as_str)
+            .filter(|_| is_event_stream)
+    });
+    presented.is_some_and(is_api_token)
+}
+
+/// `GET /api/calls/{callId}/monitor`（WebSocket）。SUPERVISOR_API_TOKEN で別に認証する
+fn is_monitor_route(method: &str, route: &str) -> bool {
+    method == "GET"
+        && route
+            .strip_prefix("/api/calls/")
+            .and_then(|rest| rest.strip_suffix("/monitor"))
+            .is_some_and(|call_id| !call_id.is_empty() && !call_id.contains('/'))
+}
+
+fn is_api_token(presented: &str) -> bool {
+    config::http_security_config()
+        .api_tokens
+        .iter()
+        .any(|token| token_matches(presented, token))
+}
+
+fn bearer_token(headers: &HashMap<String, String>) -> Option<&str> {
+    headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// 比較にかかる時間からトークンを推測されないよう、長さが同じなら全バイトを比べる
+fn token_matches(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// CORS の許可ヘッダ（許可しない Origin なら空）
+fn cors_header_lines() -> String {
+    let allow_origin = CORS_ALLOW_ORIGIN
+        .try_with(|cell| cell.borrow().clone())
+        .ok()
+        .flatten();
+    match allow_origin.as_deref() {
+        Some("*") => "Access-Control-Allow-Origin: *\r\n".to_string(),
+        Some(origin) => format!("Access-Control-Allow-Origin: {origin}\r\nVary: Origin\r\n"),
+        None => String::new(),
+    }
+}
+
+/// CORS のプリフライト。Authorization ヘッダ付きのブラウザからの呼び出しに必要
+async fn handle_preflight(socket: &mut tokio::net::TcpStream) -> std::io::Result<()> {
+    let headers = [
+        (
+            "Access-Control-Allow-Methods",
+            "GET, HEAD, POST, DELETE, OPTIONS".to_string(),
+        ),
+        (
+            "Access-Control-Allow-Headers",
+            "Authorization, Content-Type, Range, Last-Event-ID".to_string(),
+        ),
+        ("Access-Control-Max-Age", "600".to_string()),
+    ];
+    write_response_with_headers(socket, 204, "No Content", &headers, b"", 0, true).await
 }
@@ -0,0 +1,237 @@
+//! 通話モニタリング（傍聴・ささやき・割り込み）の WebSocket。
+//!
+//! HTTP サーバは自前実装なので、Upgrade の応答だけここで書き、以降は tungstenite に渡す。
+
+use std::collections::HashMap;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::{Message, Role};
+use tokio_tungstenite::WebSocketStream;
+
+use super::{
+    percent_decode, write_json_response, write_response_with_headers, write_sync_error_response,
+    LiveCallCommandResponse,
+};
+use crate::protocol::rtp::codec::mulaw_to_linear16;
+use crate::shared::audio::linear16_to_mulaw;
+use crate::shared::config;
+use crate::shared::ports::live_call::{
+    LiveCallError, LiveCallPort, MonitorChannels, SupervisorMode, SupervisorRequest,
+};
+
+/// 監視音声のサンプルレート（RTP と同じ 8kHz）
+const MONITOR_SAMPLE_RATE: u32 = 8000;
+
+/// WebSocket で送受信する音声の符号化
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MonitorEncoding {
+    Mulaw,
+    /// リトルエンディアンの 16bit リニア PCM
+    Pcm16,
+}
+
+impl MonitorEncoding {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "mulaw" => Some(Self::Mulaw),
+            "pcm16" => Some(Self::Pcm16),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Mulaw => "mulaw",
+            Self::Pcm16 => "pcm16le",
+        }
+    }
+
+    fn encode(self, samples: &[i16]) -> Vec<u8> {
+        match self {
+            Self::Mulaw => samples.iter().map(|&s| linear16_to_mulaw(s)).collect(),
+            Self::Pcm16 => samples.iter().flat_map(|s| s.to_le_bytes()).collect(),
+        }
+    }
+
+    fn decode(self, data: &[u8]) -> Vec<i16> {
+        match self {
+            Self::Mulaw => data.iter().map(|&mu| mulaw_to_linear16(mu)).collect(),
+            Self::Pcm16 => data
+                .chunks_exact(2)
+                .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorStart<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    call_id: &'a str,
+    mode: &'static str,
+    encoding: &'static str,
+    sample_rate: u32,
+    channels: usize,
+    frame_ms: u32,
+}
+
+/// Streams a live call's audio to a supervisor over WebSocket
+/// (`GET /api/calls/{callId}/monitor`).
+///
+/// Query parameters: `mode=listen|whisper|barge` (default `listen`), `channels=mixed|legs`
+/// (default `mixed`; `legs` interleaves caller and callee as stereo) and
+/// `encoding=mulaw|pcm16` (default `mulaw`). The first message is a JSON text frame
+/// describing the stream, followed by one binary frame per 20 ms of audio. In `whisper`
+/// (agent B-leg only) and `barge` (both legs) modes, binary frames sent by the client in the
+/// same encoding (mono, 8 kHz) are mixed into the call.
+///
+/// Requires `SUPERVISOR_API_TOKEN`, presented as `Authorization: Bearer <token>` or
+/// `?token=`; without it configured the endpoint does not exist (404).
+pub(super) async fn handle_monitor(
+    socket: &mut TcpStream,
+    live_calls: &dyn LiveCallPort,
+    raw_call_id: &str,
+    params: &HashMap<String, String>,
+    headers: &HashMap<String, String>,
+) -> std::io::Result<()> {
+    let Some(expected_token) = config::supervisor_api_token() else {
+        return super::write_response(socket, 404, "Not Found", b"").await;
+    };
+    let presented =
+        super::bearer_token(headers).or_else(|| params.get("token").map(String::as_str));
+    if !presented.is_some_and(|token| super::token_matches(token, expected_token)) {
+        log::warn!("[http] monitor request rejected: missing or invalid token");
+        let headers = [("WWW-Authenticate", "Bearer".to_string())];
+        return write_response_with_headers(socket, 401, "Unauthorized", &headers, b"", 0, true)
+            .await;
+    }
+
+    let is_upgrade = headers
+        .get("upgrade")
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+    let Some(key) = headers.get("sec-websocket-key").filter(|_| is_upgrade) else {
+        let headers = [("Upgrade", "websocket".to_string())];
+        return write_response_with_headers(
+            socket,
+            426,
+            "Upgrade Required",
+            &headers,
+            b"",
+            0,
+            true,
+        )
+        .await;
+    };
+
+    let param = |name: &str, default: &'static str| {
+        params
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or(default)
+            .to_string()
+    };
+    let (Some(mode), Some(channels), Some(encoding)) = (
+        SupervisorMode::parse(&param("mode", "listen")),
+        parse_channels(&param("channels", "mixed")),
+        MonitorEncoding::parse(&param("encoding", "mulaw")),
+    ) else {
+        return write_sync_error_response(
+            socket,
+            400,
+            "Bad Request",
+            "INVALID_PARAMETER",
+            "Invalid mode, channels or encoding",
+        )
+        .await;
+    };
+
+    let call_id = percent_decode(raw_call_id);
+    let request = SupervisorRequest { mode, channels };
+    let mut channel = match live_calls.attach_supervisor(call_id.clone(), request).await {
+        Ok(channel) => channel,
+        Err(err) => {
+            let (status, reason) = match err {
+                LiveCallError::NotFound(_) => (404, "Not Found"),
+                LiveCallError::Rejected(_) => (409, "Conflict"),
+                LiveCallError::Failed(_) => (502, "Bad Gateway"),
+            };
+            log::warn!("[http] monitor attach failed: {}", err);
+            let response = LiveCallCommandResponse {
+                ok: false,
+                error: Some(err.to_string()),
+            };
+            let json = serde_json::to_vec(&response).map_err(std::io::Error::other)?;
+            return write_json_response(socket, status, reason, &json).await;
+        }
+    };
+
+    let accept = derive_accept_key(key.as_bytes());
+    let handshake = format!(
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {accept}\r\n\r\n"
+    );
+    tokio::io::AsyncWriteExt::write_all(socket, handshake.as_bytes()).await?;
+    log::info!(
+        "[http] supervisor monitor started call_id={} mode={} channels={:?}",
+        call_id,
+        mode.as_str(),
+        channels
+    );
+
+    let mut ws = WebSocketStream::from_raw_socket(socket, Role::Server, None).await;
+    let start = MonitorStart {
+        kind: "start",
+        call_id: &call_id,
+        mode: mode.as_str(),
+        encoding: encoding.as_str(),
+        sample_rate: MONITOR_SAMPLE_RATE,
+        channels: channels.count(),
+        frame_ms: 20,
+    };
+    let start = serde_json::to_string(&start).map_err(std::io::Error::other)?;
+    if ws.send(Message::Text(start.into())).await.is_err() {
+        return Ok(());
+    }
+
+    loop {
+        tokio::select! {
+            frame = channel.frames.recv() => {
+                let Some(frame) = frame else {
+                    // 通話が終わった
+                    let _ = ws.close(None).await;
+                    break;
+                };
+                if ws.send(Message::Binary(encoding.encode(&frame).into())).await.is_err() {
+                    break;
+                }
+            }
+            message = ws.next() => match message {
+                Some(Ok(Message::Binary(data))) => {
+                    if let Some(talk) = channel.talk.as_ref() {
+                        // 詰まったら捨てる（遅れた音声を後から流すより欠けた方がよい）
+                        let _ = talk.try_send(encoding.decode(&data));
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    log::info!("[http] supervisor monitor ended call_id={}", call_id);
+    Ok(())
+}
+
+fn parse_channels(value: &str) -> Option<MonitorChannels> {
+    match value {
+        "mixed" => Some(MonitorChannels::Mixed),
+        "legs" => Some(MonitorChannels::Legs),
+        _ => None,
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::chat::{ChatService, ChatWebhookAdapter};
+use super::email::{SmtpEmailAdapter, SmtpSecurity, SmtpSettings};
+use super::fanout::{FanoutChannel, NotificationChannel};
+use super::message::{MessageTemplates, NotificationEvent};
+use crate::shared::ports::notification::NotificationError;
+
+const DEFAULT_MAX_ATTACHMENT_MB: u64 = 10;
+
+#[derive(Debug, Error)]
+pub enum ChannelConfigError {
+    #[error("failed to read {path}: {reason}")]
+    Read { path: String, reason: String },
+    #[error("invalid notification channel: {0}")]
+    Invalid(String),
+}
+
+#[derive(Deserialize)]
+struct ChannelFile {
+    #[serde(default)]
+    channels: Vec<RawChannel>,
+}
+
+#[derive(Deserialize)]
+struct RawChannel {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    events: Vec<String>,
+    #[serde(default)]
+    templates: HashMap<String, String>,
+    #[serde(default)]
+    subject: Option<String>,
+    #[serde(default)]
+    smtp: Option<RawSmtp>,
+    #[serde(default)]
+    from: Option<String>,
+    #[serde(default)]
+    to: Vec<String>,
+    #[serde(default)]
+    attach_audio: bool,
+    #[serde(default)]
+    max_attachment_mb: Option<u64>,
+    #[serde(default = "enabled_default")]
+    enabled: bool,
+}
+
+#[derive(Deserialize)]
+struct RawSmtp {
+    host: String,
+    #[serde(default)]
+    port: Option<u16>,
+    #[serde(default)]
+    security: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    /// パスワードを設定ファイルに書かない場合の環境変数名
+    #[serde(default)]
+    password_env: Option<String>,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+/// 設定ファイルに書かれた 1 チャネル（アダプタを作る前）
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChannelTarget {
+    Chat { service: ChatService, url: String },
+    Email(SmtpSettings),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelSpec {
+    pub id: String,
+    pub events: Vec<NotificationEvent>,
+    pub templates: MessageTemplates,
+    pub target: ChannelTarget,
+}
+
+impl ChannelSpec {
+    pub fn build(self) -> Result<FanoutChannel, NotificationError> {
+        let channel: Arc<dyn NotificationChannel> = match self.target {
+            ChannelTarget::Chat { service, url } => {
+                Arc::new(ChatWebhookAdapter::new(service, url)?)
+            }
+            ChannelTarget::Email(settings) => Arc::new(SmtpEmailAdapter::new(settings)?),
+        };
+        Ok(FanoutChannel {
+            id: self.id,
+            events: self.events,
+            templates: self.templates,
+            channel,
+        })
+    }
+}
+
+/// notification_channels.yaml を読み込む。ファイルが無ければチャネルなし
+pub fn load_channels() -> Result<Vec<ChannelSpec>, ChannelConfigError> {
+    let path = config_path();
+    match std::fs::read_to_string(&path) {
+        Ok(text) => parse_channels(&text),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(ChannelConfigError::Read {
+            path: path.display().to_string(),
+            reason: err.to_string(),
+        }),
+    }
+}
+
+fn config_path() -> PathBuf {
+    let base = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..");
+    std::env::var("NOTIFICATION_CHANNELS_CONFIG")
+        .ok()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| base.join("notification_channels.yaml"))
+}
+
+fn parse_channels(text: &str) -> Result<Vec<ChannelSpec>, ChannelConfigError> {
+    let file: ChannelFile =
+        serde_yaml::from_str(text).map_err(|err| ChannelConfigError::Invalid(err.to_string()))?;
+    let mut specs: Vec<ChannelSpec> = Vec::new();
+    for raw in file.channels.into_iter().filter(|raw| raw.enabled) {
+        let id = raw.id.trim().to_string();
+        let invalid = |reason: String| ChannelConfigError::Invalid(format!("{}: {}", id, reason));
+        if id.is_empty() {
+            return Err(ChannelConfigError::Invalid("id is empty".to_string()));
+        }
+        if specs.iter().any(|existing| existing.id == id) {
+            return Err(ChannelConfigError::Invalid(format!("duplicate id {}", id)));
+        }
+        let parse_event = |name: &str| {
+            NotificationEvent::parse(name)
+                .ok_or_else(|| invalid(format!("unknown event {:?}", name)))
+        };
+        let events = raw
+            .events
+            .iter()
+            .map(|name| parse_event(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let bodies = raw
+            .templates
+            .iter()
+            .map(|(name, template)| Ok((parse_event(name)?, template.clone())))
+            .collect::<Result<HashMap<_, _>, ChannelConfigError>>()?;
+        let target = match raw.kind.trim().to_ascii_lowercase().as_str() {
+            kind @ ("slack" | "discord" | "teams") => {
+                let service = match kind {
+                    "slack" => ChatService::Slack,
+                    "discord" => ChatService::Discord,
+                    _ => ChatService::Teams,
+                };
+                let url = raw
+                    .url
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| url.starts_with("https://") || url.starts_with("http://"))
+                    .ok_or_else(|| invalid("url must be http(s)".to_string()))?;
+                ChannelTarget::Chat { service, url }
+            }
+            "email" => {
+                let smtp = raw
+                    .smtp
+                    .ok_or_else(|| invalid("smtp is required".to_string()))?;
+                let security = match smtp.security.as_deref() {
+                    Some(value) => SmtpSecurity::parse(value)
+                        .ok_or_else(|| invalid(format!("unknown smtp security {:?}", value)))?,
+                    None => SmtpSecurity::StartTls,
+                };
+                let password = smtp.password.or_else(|| {
+                    smtp.password_env
+                        .as_deref()
+                        .and_then(|name| std::env::var(name).ok())
+                });
+                let from = raw
+                    .from
+                    .ok_or_else(|| invalid("from is required".to_string()))?;
+                if raw.to.is_empty() {
+                    return Err(invalid("to is required".to_string()));
+                }
+                ChannelTarget::Email(SmtpSettings {
+                    host: smtp.host.trim().to_string(),
+                    port: smtp.port,
+                    security,
+                    username: smtp.username.filter(|value| !value.trim().is_empty()),
+                    password,
+                    from,
+                    to: raw.to,
+                    attach_audio: raw.attach_audio,
+                    max_attachment_bytes: raw
+                        .max_attachment_mb
+                        .unwrap_or(DEFAULT_MAX_ATTACHMENT_MB)
+                        .saturating_mul(1024 * 1024),
+                })
+            }
+            other => return Err(invalid(format!("unknown type {:?}", other))),
+        };
+        specs.push(ChannelSpec {
+            id,
+            events,
+            templates: MessageTemplates {
+                bodies,
+                subject: raw.subject,
+            },
+            target,
+        });
+    }
+    Ok(specs)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, TimeZone};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::interface::notification::fanout::NotificationFanout;
+    use crate::interface::notification::mock::{spawn_http_server, spawn_smtp_server};
+    use crate::shared::entities::CallId;
+    use crate::shared::ports::notification::{
+        CallbackRequest, CallbackRequestNotifier, VoicemailNotice, VoicemailNotifier,
+    };
+
+    const CONFIG: &str = r#"
+channels:
+  - id: ops-slack
+    type: slack
+    url: https://hooks.slack.com/services/T000/B000/XXX
+    events: [missed, voicemail]
+    templates:
+      missed: "不在着信 {{from}} ({{timestamp}})"
+  - id: office-mail
+    type: email
+    smtp:
+      host: smtp.example.com
+      port: 587
+      username: voicebot
+      password: secret
+    from: voicebot@example.com
+    to: [staff@example.com]
+    subject: "[受付] {{title}}"
+    attach_audio: true
+  - id: off
+    type: discord
+    url: https://discord.com/api/webhooks/1/x
+    enabled: false
+"#;
+
+    fn jst() -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(9 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2026, 3, 14, 18, 5, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_channels_and_rejects_unknown_types() {
+        let specs = parse_channels(CONFIG).unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(
+            specs[0].events,
+            vec![NotificationEvent::Missed, NotificationEvent::Voicemail]
+        );
+        assert!(matches!(
+            specs[0].target,
+            ChannelTarget::Chat {
+                service: ChatService::Slack,
+                ..
+            }
+        ));
+        let ChannelTarget::Email(settings) = &specs[1].target else {
+            panic!("expected email channel");
+        };
+        assert_eq!(settings.security, SmtpSecurity::StartTls);
+        assert_eq!(settings.port, Some(587));
+        assert!(settings.attach_audio);
+        assert_eq!(settings.max_attachment_bytes, 10 * 1024 * 1024);
+
+        let err = parse_channels("channels:\n  - id: a\n    type: pager\n").unwrap_err();
+        assert!(err.to_string().contains("unknown type"));
+        let err = parse_channels(
+            "channels:\n  - id: a\n    type: slack\n    url: https://x\n    events: [hangup]\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown event"));
+    }
+
+    #[tokio::test]
+    async fn delivers_to_mock_chat_and_smtp_servers() {
+        let (slack_url, slack) = spawn_http_server(200).await;
+        let (teams_url, teams) = spawn_http_server(200).await;
+        let (smtp_port, mails) = spawn_smtp_server().await;
+        let config = format!(
+            r#"
+channels:
+  - id: slack
+    type: slack
+    url: {slack_url}
+    events: [callback]
+  - id: teams
+    type: teams
+    url: {teams_url}
+    subject: "{{{{title}}}} ({{{{queue}}}})"
+  - id: mail
+    type: email
+    smtp:
+      host: 127.0.0.1
+      port: {smtp_port}
+      security: none
+    from: voicebot@example.com
+    to: [staff@example.com, desk@example.com]
+    events: [voicemail]
+"#
+        );
+        let channels = parse_channels(&config)
+            .unwrap()
+            .into_iter()
+            .map(|spec| spec.build().unwrap())
+            .collect();
+        let fanout = NotificationFanout::new(channels);
+
+        fanout
+            .notify_callback_request(CallbackRequest {
+                call_id: CallId::new("call-9").unwrap(),
+                queue: "support".to_string(),
+                caller_number: Some("09012345678".to_string()),
+                requested_at: jst(),
+            })
+            .await
+            .unwrap();
+
+        let slack = slack.lock().unwrap().clone();
+        let body: Value = serde_json::from_str(&slack[0].1).unwrap();
+        assert!(body["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("折り返し依頼: 09012345678"));
+        let teams = teams.lock().unwrap().clone();
+        let body: Value = serde_json::from_str(&teams[0].1).unwrap();
+        assert_eq!(body["title"], "折り返し依頼 (support)");
+        assert!(mails.lock().unwrap().is_empty());
+
+        fanout
+            .notify_voicemail(VoicemailNotice {
+                message_id: uuid::Uuid::nil(),
+                mailbox_number: "1001".to_string(),
+                caller_number: Some("09012345678".to_string()),
+                duration_sec: 12,
+                transcript: None,
+                received_at: jst(),
+                webhook_url: None,
+                audio_path: None,
+            })
+            .await
+            .unwrap();
+
+        let mails = mails.lock().unwrap().clone();
+        assert_eq!(mails.len(), 1);
+        assert!(mails[0].contains("To: staff@example.com, desk@example.com"));
+        assert_eq!(slack.len(), 1);
+        assert_eq!(teams.len(), 1);
+    }
+}
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use super::fanout::{ChannelMessage, NotificationChannel};
+use crate::shared::ports::notification::{NotificationError, NotificationFuture};
+
+/// Discord の content の上限文字数
+const DISCORD_CONTENT_MAX_CHARS: usize = 2000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChatService {
+    /// Slack の Incoming Webhook
+    Slack,
+    /// Discord のチャンネル webhook
+    Discord,
+    /// Microsoft Teams の Incoming Webhook（コネクタ、MessageCard 形式）
+    Teams,
+}
+
+impl ChatService {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChatService::Slack => "slack",
+            ChatService::Discord => "discord",
+            ChatService::Teams => "teams",
+        }
+    }
+}
+
+/// チャットツールの受信 webhook へテキストを POST する。添付ファイルは送らない。
+pub struct ChatWebhookAdapter {
+    client: Client,
+    service: ChatService,
+    url: String,
+}
+
+impl ChatWebhookAdapter {
+    pub fn new(service: ChatService, url: String) -> Result<Self, NotificationError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .map_err(|e| NotificationError::Failed(e.to_string()))?;
+        Ok(Self {
+            client,
+            service,
+            url,
+        })
+    }
+}
+
+impl NotificationChannel for ChatWebhookAdapter {
+    fn send(&self, message: ChannelMessage) -> NotificationFuture {
+        let client = self.client.clone();
+        let service = self.service;
+        let url = self.url.clone();
+        Box::pin(async move {
+            let resp = client
+                .post(url)
+                .json(&chat_payload(service, &message))
+                .send()
+                .await
+                .map_err(|e| NotificationError::Failed(e.to_string()))?;
+            let status = resp.status();
+            if !status.is_success() {
+                let body = resp.text().await.unwrap_or_default();
+                return Err(NotificationError::Failed(format!(
+                    "{} webhook failed {}: {}",
+                    service.as_str(),
+                    status,
+                    body.trim()
+                )));
+            }
+            Ok(())
+        })
+    }
+}
+
+fn chat_payload(service: ChatService, message: &ChannelMessage) -> Value {
+    match service {
+        ChatService::Slack => json!({ "text": slack_escape(&message.text) }),
+        ChatService::Discord => json!({
+            "content": message.text.chars().take(DISCORD_CONTENT_MAX_CHARS).collect::<String>(),
+            // 本文中の @everyone などでメンションが飛ばないようにする
+            "allowed_mentions": { "parse": [] },
+        }),
+        ChatService::Teams => json!({
+            "@type": "MessageCard",
+            "@context": "https://schema.org/extensions",
+            "summary": message.subject,
+            "title": message.subject,
+            // Teams の markdown は空行でないと改行されない
+            "text": message.text.replace('\n', "\n\n"),
+        }),
+    }
+}
+
+/// Slack の制御文字（&, <, >）をエスケープする
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::notification::message::NotificationEvent;
+    use crate::interface::notification::mock::spawn_http_server;
+
+    fn message(text: &str) -> ChannelMessage {
+        ChannelMessage {
+            event: NotificationEvent::Missed,
+            subject: "[voicebot] 不在着信: 09012345678".to_string(),
+            text: text.to_string(),
+            attachment: None,
+        }
+    }
+
+    #[test]
+    fn payload_matches_each_service_format() {
+        let message = message("不在着信: <09012345678>\n@everyone");
+        assert_eq!(
+            chat_payload(ChatService::Slack, &message),
+            json!({ "text": "不在着信: &lt;09012345678&gt;\n@everyone" })
+        );
+        let discord = chat_payload(ChatService::Discord, &message);
+        assert_eq!(discord["content"], "不在着信: <09012345678>\n@everyone");
+        assert_eq!(discord["allowed_mentions"]["parse"], json!([]));
+        let teams = chat_payload(ChatService::Teams, &message);
+        assert_eq!(teams["@type"], "MessageCard");
+        assert_eq!(teams["title"], "[voicebot] 不在着信: 09012345678");
+        assert_eq!(teams["text"], "不在着信: <09012345678>\n\n@everyone");
+
+        let long = "あ".repeat(DISCORD_CONTENT_MAX_CHARS + 10);
+        let discord = chat_payload(ChatService::Discord, &self::message(&long));
+        assert_eq!(
+            discord["content"].as_str().unwrap().chars().count(),
+            DISCORD_CONTENT_MAX_CHARS
+        );
+    }
+
+    #[tokio::test]
+    async fn posts_to_webhook_and_reports_http_errors() {
+        let (url, captured) = spawn_http_server(204).await;
+        let adapter = ChatWebhookAdapter::new(ChatService::Discord, url).unwrap();
+        adapter.send(message("テスト")).await.unwrap();
+        let requests = captured.lock().unwrap().clone();
+        assert!(requests[0].0.starts_with("post /hook"));
+        let body: Value = serde_json::from_str(&requests[0].1).unwrap();
+        assert_eq!(body["content"], "テスト");
+
+        let (url, _) = spawn_http_server(404).await;
+        let adapter = ChatWebhookAdapter::new(ChatService::Slack, url).unwrap();
+        let err = adapter.send(message("テスト")).await.unwrap_err();
+        assert!(err.to_string().contains("slack webhook failed 404"));
+    }
+}
@@ -0,0 +1,304 @@
+use std::path::Path;
+use std::time::Duration;
+
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use super::fanout::{ChannelMessage, NotificationChannel};
+use crate::shared::ports::notification::{NotificationError, NotificationFuture};
+
+/// 通話終了の直後は録音の結合（mixed.wav）が終わっていないことがあるので、この時間まで待つ
+const ATTACHMENT_WAIT: Duration = Duration::from_secs(10);
+const ATTACHMENT_POLL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// 平文（社内リレーなど）
+    None,
+    /// 平文で接続して STARTTLS（既定、587 番）
+    StartTls,
+    /// 接続時から TLS（465 番）
+    Tls,
+}
+
+impl SmtpSecurity {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" | "plain" => Some(SmtpSecurity::None),
+            "starttls" => Some(SmtpSecurity::StartTls),
+            "tls" | "smtps" => Some(SmtpSecurity::Tls),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SmtpSettings {
+    pub host: String,
+    /// 未設定なら接続方式の標準ポート
+    pub port: Option<u16>,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    /// 留守番電話のメッセージ・通話録音を添付する
+    pub attach_audio: bool,
+    /// これより大きい音声は添付せず、本文にその旨を書く
+    pub max_attachment_bytes: u64,
+}
+
+/// SMTP でメールを送る。音声の添付はチャネルの設定で有効にする。
+#[derive(Clone)]
+pub struct SmtpEmailAdapter {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    attach_audio: bool,
+    max_attachment_bytes: u64,
+    attachment_wait: Duration,
+}
+
+impl SmtpEmailAdapter {
+    pub fn new(settings: SmtpSettings) -> Result<Self, NotificationError> {
+        let failed = |e: &dyn std::fmt::Display| NotificationError::Failed(e.to_string());
+        let from: Mailbox = settings.from.parse().map_err(|e| failed(&e))?;
+        let to = settings
+            .to
+            .iter()
+            .map(|address| address.parse::<Mailbox>().map_err(|e| failed(&e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if to.is_empty() {
+            return Err(NotificationError::Failed(
+                "email channel has no recipients".to_string(),
+            ));
+        }
+        let mut builder = match settings.security {
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+            }
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)
+                    .map_err(|e| failed(&e))?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host)
+                .map_err(|e| failed(&e))?,
+        }
+        .timeout(Some(Duration::from_secs(10)));
+        if let Some(port) = settings.port {
+            builder = builder.port(port);
+        }
+        if let Some(username) = settings.username {
+            builder = builder.credentials(Credentials::new(
+                username,
+                settings.password.unwrap_or_default(),
+            ));
+        }
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            to,
+            attach_audio: settings.attach_audio,
+            max_attachment_bytes: settings.max_attachment_bytes,
+            attachment_wait: ATTACHMENT_WAIT,
+        })
+    }
+
+    async fn build_email(&self, message: ChannelMessage) -> Result<Message, NotificationError> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(message.subject);
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+        let failed = |e: lettre::error::Error| NotificationError::Failed(e.to_string());
+        let audio = match message.attachment.filter(|_| self.attach_audio) {
+            Some(path) => {
+                load_attachment(&path, self.attachment_wait, self.max_attachment_bytes).await
+            }
+            None => AttachmentLoad::None,
+        };
+        let mut text = message.text;
+        match audio {
+            AttachmentLoad::Loaded(name, bytes) => {
+                let audio_type = ContentType::parse("audio/wav").expect("valid content type");
+                builder
+                    .multipart(
+                        MultiPart::mixed()
+                            .singlepart(SinglePart::plain(text))
+                            .singlepart(Attachment::new(name).body(bytes, audio_type)),
+                    )
+                    .map_err(failed)
+            }
+            AttachmentLoad::TooLarge(size) => {
+                text.push_str(&format!(
+                    "\n\n（音声ファイルが大きいため添付していません: {} bytes）",
+                    size
+                ));
+                builder
+                    .header(ContentType::TEXT_PLAIN)
+                    .body(text)
+                    .map_err(failed)
+            }
+            AttachmentLoad::None => builder
+                .header(ContentType::TEXT_PLAIN)
+                .body(text)
+                .map_err(failed),
+        }
+    }
+}
+
+impl NotificationChannel for SmtpEmailAdapter {
+    fn send(&self, message: ChannelMessage) -> NotificationFuture {
+        let adapter = self.clone();
+        Box::pin(async move {
+            let email = adapter.build_email(message).await?;
+            adapter
+                .transport
+                .send(email)
+                .await
+                .map_err(|e| NotificationError::Failed(format!("smtp send failed: {}", e)))?;
+            Ok(())
+        })
+    }
+}
+
+enum AttachmentLoad {
+    None,
+    Loaded(String, Vec<u8>),
+    TooLarge(u64),
+}
+
+/// ファイルが現れてサイズが落ち着くまで `wait` だけ待ってから読む。無ければ添付しない
+async fn load_attachment(path: &Path, wait: Duration, max_bytes: u64) -> AttachmentLoad {
+    let deadline = tokio::time::Instant::now() + wait;
+    let mut last_size = None;
+    let size = loop {
+        let size = tokio::fs::metadata(path).await.ok().map(|meta| meta.len());
+        if size.is_some() && size == last_size {
+            break size;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            break size;
+        }
+        last_size = size;
+        tokio::time::sleep(ATTACHMENT_POLL.min(wait)).await;
+    };
+    let Some(size) = size else {
+        log::warn!("[notification] attachment not found: {}", path.display());
+        return AttachmentLoad::None;
+    };
+    if size > max_bytes {
+        return AttachmentLoad::TooLarge(size);
+    }
+    match tokio::fs::read(path).await {
+        Ok(bytes) => AttachmentLoad::Loaded(attachment_name(path), bytes),
+        Err(err) => {
+            log::warn!(
+                "[notification] attachment read failed {}: {}",
+                path.display(),
+                err
+            );
+            AttachmentLoad::None
+        }
+    }
+}
+
+/// 録音は `<callId>/mixed.wav` なので、ディレクトリ名を付けて区別できる名前にする
+fn attachment_name(path: &Path) -> String {
+    let file = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "audio.wav".to_string());
+    match path.parent().and_then(Path::file_name) {
+        Some(parent) if file == "mixed.wav" => format!("{}.wav", parent.to_string_lossy()),
+        _ => file,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::interface::notification::message::NotificationEvent;
+    use crate::interface::notification::mock::spawn_smtp_server;
+
+    fn adapter(port: u16, attach_audio: bool, max_attachment_bytes: u64) -> SmtpEmailAdapter {
+        let mut adapter = SmtpEmailAdapter::new(SmtpSettings {
+            host: "127.0.0.1".to_string(),
+            port: Some(port),
+            security: SmtpSecurity::None,
+            username: None,
+            password: None,
+            from: "Voicebot <voicebot@example.com>".to_string(),
+            to: vec!["staff@example.com".to_string()],
+            attach_audio,
+            max_attachment_bytes,
+        })
+        .unwrap();
+        adapter.attachment_wait = Duration::from_millis(50);
+        adapter
+    }
+
+    fn message(attachment: Option<PathBuf>) -> ChannelMessage {
+        ChannelMessage {
+            event: NotificationEvent::Voicemail,
+            subject: "[voicebot] 留守番電話: 09012345678".to_string(),
+            text: "折り返しお願いします".to_string(),
+            attachment,
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_mail_with_voicemail_attachment() {
+        let tmp = tempfile::tempdir().unwrap();
+        let audio = tmp.path().join("call-1").join("mixed.wav");
+        std::fs::create_dir_all(audio.parent().unwrap()).unwrap();
+        std::fs::write(&audio, b"RIFF-audio").unwrap();
+        let (port, captured) = spawn_smtp_server().await;
+
+        adapter(port, true, 1024)
+            .send(message(Some(audio)))
+            .await
+            .unwrap();
+
+        let mails = captured.lock().unwrap().clone();
+        assert_eq!(mails.len(), 1);
+        assert!(mails[0].contains("To: staff@example.com"));
+        assert!(mails[0].contains("Content-Type: multipart/mixed"));
+        assert!(mails[0].contains("filename=\"call-1.wav\""));
+        assert!(mails[0].contains("Content-Type: audio/wav"));
+    }
+
+    #[tokio::test]
+    async fn oversized_or_disabled_attachment_is_not_sent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let audio = tmp.path().join("a.wav");
+        std::fs::write(&audio, vec![0u8; 64]).unwrap();
+        let (port, captured) = spawn_smtp_server().await;
+
+        adapter(port, true, 16)
+            .send(message(Some(audio.clone())))
+            .await
+            .unwrap();
+        adapter(port, false, 1024)
+            .send(message(Some(audio)))
+            .await
+            .unwrap();
+        adapter(port, true, 1024)
+            .send(message(Some(tmp.path().join("missing.wav"))))
+            .await
+            .unwrap();
+
+        let mails = captured.lock().unwrap().clone();
+        assert_eq!(mails.len(), 3);
+        assert!(mails.iter().all(|mail| !mail.contains("multipart/mixed")));
+        assert!(matches!(
+            load_attachment(&tmp.path().join("a.wav"), Duration::ZERO, 16).await,
+            AttachmentLoad::TooLarge(64)
+        ));
+    }
+}
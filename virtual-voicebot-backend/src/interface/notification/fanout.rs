@@ -0,0 +1,218 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{DateTime, FixedOffset};
+use futures_util::future::join_all;
+
+use super::message::{MessageTemplates, NotificationEvent, NotificationMessage};
+use crate::shared::entities::CallId;
+use crate::shared::ports::notification::{
+    CallEndedNotifier, CallbackRequest, CallbackRequestNotifier, MissedCallNotifier,
+    NotificationError, NotificationFuture, RingingNotifier, VoicemailNotice, VoicemailNotifier,
+};
+
+/// テンプレートを当てた後の、1 チャネル宛てのメッセージ
+#[derive(Clone, Debug)]
+pub struct ChannelMessage {
+    pub event: NotificationEvent,
+    /// メールの件名・Teams のタイトル
+    pub subject: String,
+    pub text: String,
+    pub attachment: Option<PathBuf>,
+}
+
+/// 整形済みのメッセージを 1 つの送信先へ届ける
+pub trait NotificationChannel: Send + Sync {
+    fn send(&self, message: ChannelMessage) -> NotificationFuture;
+}
+
+pub struct FanoutChannel {
+    pub id: String,
+    /// 空なら全イベント
+    pub events: Vec<NotificationEvent>,
+    pub templates: MessageTemplates,
+    pub channel: Arc<dyn NotificationChannel>,
+}
+
+impl FanoutChannel {
+    /// 全イベントを既定の文面で送るチャネル
+    pub fn new(id: impl Into<String>, channel: Arc<dyn NotificationChannel>) -> Self {
+        Self {
+            id: id.into(),
+            events: Vec::new(),
+            templates: MessageTemplates::default(),
+            channel,
+        }
+    }
+
+    fn accepts(&self, event: NotificationEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// 通知を複数のチャネル（LINE・Slack・Discord・Teams・メール）へ同時に送る。
+/// チャネルごとに対象イベントと文面テンプレートを持ち、1 つが失敗しても他は送る。
+pub struct NotificationFanout {
+    channels: Vec<FanoutChannel>,
+}
+
+impl NotificationFanout {
+    pub fn new(channels: Vec<FanoutChannel>) -> Self {
+        Self { channels }
+    }
+
+    fn dispatch(&self, message: NotificationMessage) -> NotificationFuture {
+        let sends: Vec<_> = self
+            .channels
+            .iter()
+            .filter(|entry| entry.accepts(message.event))
+            .map(|entry| {
+                let send = entry.channel.send(ChannelMessage {
+                    event: message.event,
+                    subject: entry.templates.subject(&message),
+                    text: entry.templates.body(&message),
+                    attachment: message.attachment.clone(),
+                });
+                let id = entry.id.clone();
+                async move { send.await.map_err(|err| format!("{}: {}", id, err)) }
+            })
+            .collect();
+        Box::pin(async move {
+            let failures: Vec<String> = join_all(sends)
+                .await
+                .into_iter()
+                .filter_map(Result::err)
+                .collect();
+            if failures.is_empty() {
+                Ok(())
+            } else {
+                Err(NotificationError::Failed(failures.join("; ")))
+            }
+        })
+    }
+}
+
+impl RingingNotifier for NotificationFanout {
+    fn notify_ringing(
+        &self,
+        call_id: CallId,
+        from: String,
+        timestamp: DateTime<FixedOffset>,
+    ) -> NotificationFuture {
+        self.dispatch(NotificationMessage::ringing(&call_id, &from, timestamp))
+    }
+}
+
+impl MissedCallNotifier for NotificationFanout {
+    fn notify_missed(&self, from: String, timestamp: DateTime<FixedOffset>) -> NotificationFuture {
+        self.dispatch(NotificationMessage::missed(&from, timestamp))
+    }
+}
+
+impl CallEndedNotifier for NotificationFanout {
+    fn notify_ended(&self, call_id: &str, from: String, duration_sec: u64) -> NotificationFuture {
+        self.dispatch(NotificationMessage::ended(call_id, &from, duration_sec))
+    }
+}
+
+impl VoicemailNotifier for NotificationFanout {
+    fn notify_voicemail(&self, notice: VoicemailNotice) -> NotificationFuture {
+        self.dispatch(NotificationMessage::voicemail(&notice))
+    }
+}
+
+impl CallbackRequestNotifier for NotificationFanout {
+    fn notify_callback_request(&self, request: CallbackRequest) -> NotificationFuture {
+        self.dispatch(NotificationMessage::callback(&request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn jst() -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(9 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2026, 3, 14, 18, 5, 0)
+            .unwrap()
+    }
+
+    #[derive(Default)]
+    struct CapturingChannel {
+        messages: Mutex<Vec<ChannelMessage>>,
+    }
+
+    impl NotificationChannel for CapturingChannel {
+        fn send(&self, message: ChannelMessage) -> NotificationFuture {
+            self.messages.lock().unwrap().push(message);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    struct FailingChannel;
+
+    impl NotificationChannel for FailingChannel {
+        fn send(&self, _message: ChannelMessage) -> NotificationFuture {
+            Box::pin(async { Err(NotificationError::Failed("down".to_string())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn fanout_filters_events_and_applies_templates() {
+        let all = Arc::new(CapturingChannel::default());
+        let missed_only = Arc::new(CapturingChannel::default());
+        let mut filtered = FanoutChannel::new("filtered", missed_only.clone());
+        filtered.events = vec![NotificationEvent::Missed];
+        filtered.templates.bodies.insert(
+            NotificationEvent::Missed,
+            "{{from}} / {{timestamp}}".to_string(),
+        );
+        let fanout =
+            NotificationFanout::new(vec![FanoutChannel::new("all", all.clone()), filtered]);
+
+        fanout
+            .notify_ringing(
+                CallId::new("call-1").unwrap(),
+                "0311112222".to_string(),
+                jst(),
+            )
+            .await
+            .unwrap();
+        fanout
+            .notify_missed("09012345678".to_string(), jst())
+            .await
+            .unwrap();
+
+        let all = all.messages.lock().unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].text, "不在着信: 09012345678 (2026-03-14 18:05:00)");
+        let missed_only = missed_only.messages.lock().unwrap();
+        assert_eq!(missed_only.len(), 1);
+        assert_eq!(missed_only[0].text, "09012345678 / 2026-03-14 18:05:00");
+        assert_eq!(missed_only[0].subject, "[voicebot] 不在着信: 09012345678");
+    }
+
+    #[tokio::test]
+    async fn one_failing_channel_does_not_block_others() {
+        let ok = Arc::new(CapturingChannel::default());
+        let fanout = NotificationFanout::new(vec![
+            FanoutChannel::new("broken", Arc::new(FailingChannel)),
+            FanoutChannel::new("ok", ok.clone()),
+        ]);
+
+        let err = fanout
+            .notify_ended("call-1", "09012345678".to_string(), 42)
+            .await
+            .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("broken: notification failed: down"));
+        assert_eq!(ok.messages.lock().unwrap().len(), 1);
+    }
+}
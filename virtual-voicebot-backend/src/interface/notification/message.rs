@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset};
+use serde_json::{json, Value};
+
+use crate::service::recording;
+use crate::service::webhook::render_text;
+use crate::shared::entities::CallId;
+use crate::shared::ports::notification::{CallbackRequest, VoicemailNotice};
+
+/// 通知チャネルで送り分けるイベントの種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NotificationEvent {
+    Ringing,
+    Missed,
+    Ended,
+    Voicemail,
+    Callback,
+}
+
+impl NotificationEvent {
+    pub const ALL: [NotificationEvent; 5] = [
+        NotificationEvent::Ringing,
+        NotificationEvent::Missed,
+        NotificationEvent::Ended,
+        NotificationEvent::Voicemail,
+        NotificationEvent::Callback,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationEvent::Ringing => "ringing",
+            NotificationEvent::Missed => "missed",
+            NotificationEvent::Ended => "ended",
+            NotificationEvent::Voicemail => "voicemail",
+            NotificationEvent::Callback => "callback",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        Self::ALL.into_iter().find(|event| event.as_str() == value)
+    }
+
+    /// 件名などに使う見出し
+    pub fn title(self) -> &'static str {
+        match self {
+            NotificationEvent::Ringing => "着信",
+            NotificationEvent::Missed => "不在着信",
+            NotificationEvent::Ended => "通話終了",
+            NotificationEvent::Voicemail => "留守番電話",
+            NotificationEvent::Callback => "折り返し依頼",
+        }
+    }
+}
+
+/// 1 件の通知。チャネルごとにテンプレートを当ててから送る
+#[derive(Clone, Debug)]
+pub struct NotificationMessage {
+    pub event: NotificationEvent,
+    /// テンプレート未設定のチャネルに送る本文（LINE と同じ文面）
+    pub text: String,
+    /// テンプレートから `{{name}}` で参照できる値（camelCase）
+    pub context: Value,
+    /// メールに添付する音声（留守番電話のメッセージ・通話録音）
+    pub attachment: Option<PathBuf>,
+}
+
+impl NotificationMessage {
+    pub fn ringing(call_id: &CallId, from: &str, timestamp: DateTime<FixedOffset>) -> Self {
+        let caller = caller_label(Some(from));
+        Self::new(
+            NotificationEvent::Ringing,
+            format!(
+                "着信: {} ({}) [call_id={}]",
+                caller,
+                format_timestamp(timestamp),
+                call_id
+            ),
+            json!({
+                "callId": call_id.as_str(),
+                "from": caller,
+                "timestamp": format_timestamp(timestamp),
+            }),
+            None,
+        )
+    }
+
+    pub fn missed(from: &str, timestamp: DateTime<FixedOffset>) -> Self {
+        let caller = caller_label(Some(from));
+        Self::new(
+            NotificationEvent::Missed,
+            format!("不在着信: {} ({})", caller, format_timestamp(timestamp)),
+            json!({
+                "from": caller,
+                "timestamp": format_timestamp(timestamp),
+            }),
+            None,
+        )
+    }
+
+    /// 添付には通話録音（mixed.wav）を使う
+    pub fn ended(call_id: &str, from: &str, duration_sec: u64) -> Self {
+        let caller = caller_label(Some(from));
+        Self::new(
+            NotificationEvent::Ended,
+            format!(
+                "通話終了: {} ({}秒) [call_id={}]",
+                caller, duration_sec, call_id
+            ),
+            json!({
+                "callId": call_id,
+                "from": caller,
+                "durationSec": duration_sec,
+            }),
+            Some(recording::recording_dir(call_id).join("mixed.wav")),
+        )
+    }
+
+    pub fn voicemail(notice: &VoicemailNotice) -> Self {
+        let caller = caller_label(notice.caller_number.as_deref());
+        let transcript = notice
+            .transcript
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        Self::new(
+            NotificationEvent::Voicemail,
+            format!(
+                "留守番電話: {} ({}秒) {} [mailbox={}]\n{}",
+                caller,
+                notice.duration_sec,
+                format_timestamp(notice.received_at),
+                notice.mailbox_number,
+                transcript.unwrap_or("（文字起こしなし）")
+            ),
+            json!({
+                "messageId": notice.message_id.to_string(),
+                "mailbox": notice.mailbox_number,
+                "from": caller,
+                "durationSec": notice.duration_sec,
+                "transcript": transcript,
+                "timestamp": format_timestamp(notice.received_at),
+            }),
+            notice.audio_path.clone(),
+        )
+    }
+
+    pub fn callback(request: &CallbackRequest) -> Self {
+        let caller = caller_label(request.caller_number.as_deref());
+        Self::new(
+            NotificationEvent::Callback,
+            format!(
+                "折り返し依頼: {} ({}) [queue={} call_id={}]",
+                caller,
+                format_timestamp(request.requested_at),
+                request.queue,
+                request.call_id
+            ),
+            json!({
+                "callId": request.call_id.as_str(),
+                "from": caller,
+                "queue": request.queue,
+                "timestamp": format_timestamp(request.requested_at),
+            }),
+            None,
+        )
+    }
+
+    fn new(
+        event: NotificationEvent,
+        text: String,
+        mut context: Value,
+        attachment: Option<PathBuf>,
+    ) -> Self {
+        context["event"] = json!(event.as_str());
+        context["title"] = json!(event.title());
+        context["text"] = json!(text);
+        Self {
+            event,
+            text,
+            context,
+            attachment,
+        }
+    }
+}
+
+/// チャネルごとの文面。イベント別の本文テンプレートと件名（メール・Teams の見出し）
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MessageTemplates {
+    pub bodies: HashMap<NotificationEvent, String>,
+    pub subject: Option<String>,
+}
+
+impl MessageTemplates {
+    const DEFAULT_SUBJECT: &'static str = "[voicebot] {{title}}: {{from}}";
+
+    pub fn body(&self, message: &NotificationMessage) -> String {
+        match self.bodies.get(&message.event) {
+            Some(template) => render_text(template, &message.context),
+            None => message.text.clone(),
+        }
+    }
+
+    pub fn subject(&self, message: &NotificationMessage) -> String {
+        let template = self.subject.as_deref().unwrap_or(Self::DEFAULT_SUBJECT);
+        render_text(template, &message.context)
+    }
+}
+
+fn format_timestamp(timestamp: DateTime<FixedOffset>) -> String {
+    timestamp.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn caller_label(caller: Option<&str>) -> &str {
+    caller
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or("unknown")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn jst(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(9 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(y, mo, d, h, mi, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn voicemail_text_includes_caller_duration_and_transcript() {
+        let mut notice = VoicemailNotice {
+            message_id: Uuid::nil(),
+            mailbox_number: "1001".to_string(),
+            caller_number: Some("+819012345678".to_string()),
+            duration_sec: 12,
+            transcript: Some("折り返しお願いします".to_string()),
+            received_at: jst(2026, 3, 9, 10, 15),
+            webhook_url: None,
+            audio_path: Some(PathBuf::from("storage/voicemail/1001/a.wav")),
+        };
+        let message = NotificationMessage::voicemail(&notice);
+        assert_eq!(
+            message.text,
+            "留守番電話: +819012345678 (12秒) 2026-03-09 10:15:00 [mailbox=1001]\n折り返しお願いします"
+        );
+        assert_eq!(
+            message.attachment.as_deref(),
+            Some(std::path::Path::new("storage/voicemail/1001/a.wav"))
+        );
+
+        notice.caller_number = None;
+        notice.transcript = None;
+        let text = NotificationMessage::voicemail(&notice).text;
+        assert!(text.starts_with("留守番電話: unknown (12秒)"));
+        assert!(text.ends_with("（文字起こしなし）"));
+    }
+
+    #[test]
+    fn callback_text_includes_caller_and_queue() {
+        let mut request = CallbackRequest {
+            call_id: CallId::new("call-1").unwrap(),
+            queue: "support".to_string(),
+            caller_number: Some("+819012345678".to_string()),
+            requested_at: jst(2026, 3, 12, 9, 30),
+        };
+        assert_eq!(
+            NotificationMessage::callback(&request).text,
+            "折り返し依頼: +819012345678 (2026-03-12 09:30:00) [queue=support call_id=call-1]"
+        );
+        request.caller_number = None;
+        assert!(NotificationMessage::callback(&request)
+            .text
+            .starts_with("折り返し依頼: unknown"));
+    }
+
+    #[test]
+    fn templates_render_context_and_fall_back_to_default_text() {
+        let message = NotificationMessage::missed(" 09012345678", jst(2026, 3, 14, 18, 5));
+        let mut templates = MessageTemplates::default();
+        assert_eq!(
+            templates.body(&message),
+            "不在着信: 09012345678 (2026-03-14 18:05:00)"
+        );
+        assert_eq!(
+            templates.subject(&message),
+            "[voicebot] 不在着信: 09012345678"
+        );
+
+        templates.bodies.insert(
+            NotificationEvent::Missed,
+            "{{from}} さんから {{timestamp}} に着信 ({{event}})".to_string(),
+        );
+        templates.subject = Some("{{title}}".to_string());
+        assert_eq!(
+            templates.body(&message),
+            "09012345678 さんから 2026-03-14 18:05:00 に着信 (missed)"
+        );
+        assert_eq!(templates.subject(&message), "不在着信");
+    }
+}
@@ -0,0 +1,101 @@
+//! 通知アダプタのテスト用に、ローカルで受けるだけの HTTP / SMTP サーバ。
+
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+pub(crate) type Captured<T> = Arc<Mutex<Vec<T>>>;
+
+/// 受けたリクエストのヘッダ（リクエスト行を含めて小文字化）と本文を残し、`status` を返し続ける。
+/// 送信先 URL は `http://<addr>/hook`
+pub(crate) async fn spawn_http_server(status: u16) -> (String, Captured<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let captured_clone = captured.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let head = head.to_ascii_lowercase();
+                    let len = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .and_then(|value| value.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if body.len() >= len {
+                        captured_clone
+                            .lock()
+                            .unwrap()
+                            .push((head, body.to_string()));
+                        break;
+                    }
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 {} X\r\nContent-Length: 4\r\nConnection: close\r\n\r\nnope",
+                status
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    (format!("http://{}/hook", addr), captured)
+}
+
+/// 平文の SMTP（認証なし）で受けたメールの DATA 部分を残す。戻り値はポート番号
+pub(crate) async fn spawn_smtp_server() -> (u16, Captured<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let captured_clone = captured.clone();
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            let (reader, mut writer) = socket.into_split();
+            let mut reader = BufReader::new(reader);
+            let _ = writer.write_all(b"220 mock ESMTP\r\n").await;
+            let mut data: Option<String> = None;
+            let mut line = String::new();
+            loop {
+                line.clear();
+                if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+                    break;
+                }
+                if let Some(body) = data.as_mut() {
+                    if line == ".\r\n" {
+                        captured_clone
+                            .lock()
+                            .unwrap()
+                            .push(data.take().unwrap_or_default());
+                        let _ = writer.write_all(b"250 queued\r\n").await;
+                    } else {
+                        body.push_str(&line);
+                    }
+                    continue;
+                }
+                let command = line.trim_end().to_ascii_uppercase();
+                let reply: &[u8] = if command.starts_with("EHLO") {
+                    b"250-mock\r\n250 8BITMIME\r\n"
+                } else if command == "DATA" {
+                    data = Some(String::new());
+                    b"354 go ahead\r\n"
+                } else if command == "QUIT" {
+                    let _ = writer.write_all(b"221 bye\r\n").await;
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                let _ = writer.write_all(reply).await;
+            }
+        }
+    });
+    (port, captured)
+}
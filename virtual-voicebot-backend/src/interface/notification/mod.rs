@@ -4,9 +4,28 @@ use chrono::{DateTime, FixedOffset};
 use reqwest::Client;
 
 use crate::shared::ports::notification::{
-    CallEndedNotifier, MissedCallNotifier, NotificationError, NotificationFuture, RingingNotifier,
+    CallEndedNotifier, CallbackRequest, CallbackRequestNotifier, MissedCallNotifier,
+    NotificationError, NotificationFuture, RingingNotifier, VoicemailNotice, VoicemailNotifier,
 };
 
+mod channels;
+mod chat;
+mod email;
+mod fanout;
+mod message;
+#[cfg(test)]
+mod mock;
+mod webhook;
+mod webhook_delivery;
+
+pub use channels::{load_channels, ChannelConfigError, ChannelSpec, ChannelTarget};
+pub use chat::{ChatService, ChatWebhookAdapter};
+pub use email::{SmtpEmailAdapter, SmtpSecurity, SmtpSettings};
+pub use fanout::{ChannelMessage, FanoutChannel, NotificationChannel, NotificationFanout};
+pub use message::{MessageTemplates, NotificationEvent, NotificationMessage};
+pub use webhook::WebhookAdapter;
+pub use webhook_delivery::WebhookDeliveryWorker;
+
 #[derive(Clone, Debug, Default)]
 pub struct NoopNotification;
 
@@ -49,6 +68,18 @@ impl CallEndedNotifier for NoopNotification {
     }
 }
 
+impl VoicemailNotifier for NoopNotification {
+    fn notify_voicemail(&self, _notice: VoicemailNotice) -> NotificationFuture {
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+impl CallbackRequestNotifier for NoopNotification {
+    fn notify_callback_request(&self, _request: CallbackRequest) -> NotificationFuture {
+        Box::pin(async move { Ok(()) })
+    }
+}
+
 pub struct LineAdapter {
     client: Client,
     user_id: String,
@@ -97,9 +128,11 @@ impl LineAdapter {
             Ok(())
         })
     }
+}
 
-    fn format_timestamp(timestamp: DateTime<FixedOffset>) -> String {
-        timestamp.format("%Y-%m-%d %H:%M:%S").to_string()
+impl NotificationChannel for LineAdapter {
+    fn send(&self, message: ChannelMessage) -> NotificationFuture {
+        self.push_message(message.text)
     }
 }
 
@@ -110,47 +143,30 @@ impl RingingNotifier for LineAdapter {
         from: String,
         timestamp: DateTime<FixedOffset>,
     ) -> NotificationFuture {
-        let text = format!(
-            "着信: {} ({}) [call_id={}]",
-            if from.trim().is_empty() {
-                "unknown"
-            } else {
-                from.as_str()
-            },
-            Self::format_timestamp(timestamp),
-            call_id
-        );
-        self.push_message(text)
+        self.push_message(NotificationMessage::ringing(&call_id, &from, timestamp).text)
     }
 }
 
 impl MissedCallNotifier for LineAdapter {
     fn notify_missed(&self, from: String, timestamp: DateTime<FixedOffset>) -> NotificationFuture {
-        let text = format!(
-            "不在着信: {} ({})",
-            if from.trim().is_empty() {
-                "unknown"
-            } else {
-                from.as_str()
-            },
-            Self::format_timestamp(timestamp)
-        );
-        self.push_message(text)
+        self.push_message(NotificationMessage::missed(&from, timestamp).text)
     }
 }
 
 impl CallEndedNotifier for LineAdapter {
     fn notify_ended(&self, call_id: &str, from: String, duration_sec: u64) -> NotificationFuture {
-        let text = format!(
-            "通話終了: {} ({}秒) [call_id={}]",
-            if from.trim().is_empty() {
-                "unknown"
-            } else {
-                from.as_str()
-            },
-            duration_sec,
-            call_id
-        );
-        self.push_message(text)
+        self.push_message(NotificationMessage::ended(call_id, &from, duration_sec).text)
+    }
+}
+
+impl VoicemailNotifier for LineAdapter {
+    fn notify_voicemail(&self, notice: VoicemailNotice) -> NotificationFuture {
+        self.push_message(NotificationMessage::voicemail(&notice).text)
+    }
+}
+
+impl CallbackRequestNotifier for LineAdapter {
+    fn notify_callback_request(&self, request: CallbackRequest) -> NotificationFuture {
+        self.push_message(NotificationMessage::callback(&request).text)
     }
 }
@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use crate::shared::ports::notification::{
+    NotificationError, NotificationFuture, VoicemailNotice, VoicemailNotifier,
+};
+
+/// 新着留守番電話を JSON で POST する。送信先はメールボックス個別の URL → 既定 URL の順。
+pub struct WebhookAdapter {
+    client: Client,
+    default_url: Option<String>,
+}
+
+impl WebhookAdapter {
+    pub fn new(default_url: Option<String>) -> Result<Self, NotificationError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .map_err(|e| NotificationError::Failed(e.to_string()))?;
+        Ok(Self {
+            client,
+            default_url,
+        })
+    }
+}
+
+impl VoicemailNotifier for WebhookAdapter {
+    fn notify_voicemail(&self, notice: VoicemailNotice) -> NotificationFuture {
+        let client = self.client.clone();
+        let url = notice
+            .webhook_url
+            .clone()
+            .or_else(|| self.default_url.clone());
+        Box::pin(async move {
+            let Some(url) = url else {
+                return Ok(());
+            };
+            let resp = client
+                .post(url)
+                .header("Idempotency-Key", notice.message_id.to_string())
+                .json(&voicemail_payload(&notice))
+                .send()
+                .await
+                .map_err(|e| NotificationError::Failed(e.to_string()))?;
+            let status = resp.status();
+            if !status.is_success() {
+                return Err(NotificationError::Failed(format!(
+                    "voicemail webhook failed {}",
+                    status
+                )));
+            }
+            Ok(())
+        })
+    }
+}
+
+fn voicemail_payload(notice: &VoicemailNotice) -> Value {
+    json!({
+        "event": "voicemail.received",
+        "messageId": notice.message_id.to_string(),
+        "mailbox": notice.mailbox_number,
+        "callerNumber": notice.caller_number,
+        "durationSec": notice.duration_sec,
+        "transcript": notice.transcript,
+        "receivedAt": notice.received_at.to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeZone};
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn voicemail_payload_uses_camel_case_fields() {
+        let notice = VoicemailNotice {
+            message_id: Uuid::nil(),
+            mailbox_number: "1001".to_string(),
+            caller_number: None,
+            duration_sec: 8,
+            transcript: Some("またかけます".to_string()),
+            received_at: FixedOffset::east_opt(9 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2026, 3, 9, 10, 15, 0)
+                .unwrap(),
+            webhook_url: Some("https://example.com/hook".to_string()),
+            audio_path: None,
+        };
+        let payload = voicemail_payload(&notice);
+        assert_eq!(payload["event"], "voicemail.received");
+        assert_eq!(payload["mailbox"], "1001");
+        assert!(payload["callerNumber"].is_null());
+        assert_eq!(payload["durationSec"], 8);
+        assert_eq!(payload["transcript"], "またかけます");
+        assert_eq!(payload["receivedAt"], "2026-03-09T10:15:00+09:00");
+        assert!(payload.get("webhookUrl").is_none());
+    }
+}
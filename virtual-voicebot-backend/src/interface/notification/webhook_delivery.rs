@@ -0,0 +1,336 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::time::{interval, MissedTickBehavior};
+
+use crate::service::webhook::{
+    self, WebhookSubscription, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+use crate::shared::config::WebhookDeliveryConfig;
+use crate::shared::ports::webhook_port::{
+    WebhookAttemptFailure, WebhookDelivery, WebhookDeliveryError, WebhookDeliveryPort,
+};
+
+/// 失敗時に配送ログへ残すレスポンス本文の長さ
+const ERROR_BODY_MAX_CHARS: usize = 200;
+
+/// 配送キュー（`webhook_deliveries`）から送信時刻を過ぎたものを取り出して POST する。
+/// 失敗は指数バックオフで再試行し、上限を超えたら dead letter として残す。
+#[derive(Clone)]
+pub struct WebhookDeliveryWorker {
+    port: Arc<dyn WebhookDeliveryPort>,
+    subscriptions: Arc<Vec<WebhookSubscription>>,
+    client: reqwest::Client,
+    config: WebhookDeliveryConfig,
+}
+
+impl WebhookDeliveryWorker {
+    pub fn new(
+        port: Arc<dyn WebhookDeliveryPort>,
+        subscriptions: Arc<Vec<WebhookSubscription>>,
+        config: WebhookDeliveryConfig,
+    ) -> Result<Self, reqwest::Error> {
+        let client = reqwest::Client::builder().timeout(config.timeout).build()?;
+        Ok(Self {
+            port,
+            subscriptions,
+            client,
+            config,
+        })
+    }
+
+    pub async fn run(&self) {
+        log::info!(
+            "[webhook] delivery worker started (subscriptions={}, poll_interval={}s, max_attempts={})",
+            self.subscriptions.len(),
+            self.config.poll_interval.as_secs(),
+            self.config.max_attempts
+        );
+        let mut ticker = interval(self.config.poll_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            if let Err(error) = self.process_once().await {
+                log::warn!("[webhook] delivery batch failed: {}", error);
+            }
+        }
+    }
+
+    /// 送信時刻を過ぎた配送を 1 バッチ分送る。送った件数を返す
+    pub async fn process_once(&self) -> Result<usize, WebhookDeliveryError> {
+        // 送信中に別のワーカーが同じ行を取らないよう、タイムアウトより長く確保しておく
+        let lease = self.config.timeout * 2 + Duration::from_secs(30);
+        let deliveries = self.port.claim_due(self.config.batch_size, lease).await?;
+        let count = deliveries.len();
+        for delivery in deliveries {
+            match self.send(&delivery).await {
+                Ok(status_code) => {
+                    self.port.mark_delivered(delivery.id, status_code).await?;
+                }
+                Err((status_code, error)) => {
+                    let attempts = u32::try_from(delivery.attempts).unwrap_or(0) + 1;
+                    let retry_at = self
+                        .config
+                        .retry_delay(attempts)
+                        .and_then(|delay| chrono::Duration::from_std(delay).ok())
+                        .map(|delay| Utc::now() + delay)
+                        .filter(|_| self.subscription(&delivery).is_some());
+                    if retry_at.is_some() {
+                        log::warn!(
+                            "[webhook] delivery {} to {} failed (attempt {}): {}",
+                            delivery.id,
+                            delivery.subscription_id,
+                            attempts,
+                            error
+                        );
+                    } else {
+                        log::warn!(
+                            "[webhook] delivery {} to {} moved to dead letter after {} attempts: {}",
+                            delivery.id,
+                            delivery.subscription_id,
+                            attempts,
+                            error
+                        );
+                    }
+                    self.port
+                        .mark_failed(
+                            delivery.id,
+                            WebhookAttemptFailure {
+                                status_code,
+                                error,
+                                retry_at,
+                            },
+                        )
+                        .await?;
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    fn subscription(&self, delivery: &WebhookDelivery) -> Option<&WebhookSubscription> {
+        self.subscriptions
+            .iter()
+            .find(|subscription| subscription.id == delivery.subscription_id)
+    }
+
+    async fn send(&self, delivery: &WebhookDelivery) -> Result<i32, (Option<i32>, String)> {
+        let Some(subscription) = self.subscription(delivery) else {
+            return Err((None, "subscription removed from config".to_string()));
+        };
+        let body = serde_json::to_vec(&delivery.payload).map_err(|e| (None, e.to_string()))?;
+        let timestamp = Utc::now().timestamp();
+        let mut request = self
+            .client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .header("Idempotency-Key", delivery.id.to_string())
+            .header(TIMESTAMP_HEADER, timestamp.to_string());
+        if let Some(secret) = subscription.secret.as_deref() {
+            request = request.header(
+                SIGNATURE_HEADER,
+                webhook::signature(secret, timestamp, &body),
+            );
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| (None, e.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(i32::from(status.as_u16()));
+        }
+        let body = response.text().await.unwrap_or_default();
+        let body: String = body.chars().take(ERROR_BODY_MAX_CHARS).collect();
+        Err((
+            Some(i32::from(status.as_u16())),
+            format!("HTTP {} {}", status, body.trim()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::interface::notification::mock::spawn_http_server;
+    use crate::shared::ports::webhook_port::{
+        NewWebhookDelivery, WebhookDeliveryFilter, WebhookDeliveryFuture, DELIVERY_STATUS_DEAD,
+        DELIVERY_STATUS_DELIVERED, DELIVERY_STATUS_PENDING,
+    };
+
+    /// 送信時刻を見ずに pending をすべて返すメモリ上のキュー
+    #[derive(Default)]
+    struct MemoryPort {
+        rows: Mutex<Vec<WebhookDelivery>>,
+    }
+
+    impl MemoryPort {
+        fn row(&self) -> WebhookDelivery {
+            self.rows.lock().unwrap()[0].clone()
+        }
+    }
+
+    impl WebhookDeliveryPort for MemoryPort {
+        fn enqueue(&self, delivery: NewWebhookDelivery) -> WebhookDeliveryFuture<()> {
+            let now = Utc::now();
+            self.rows.lock().unwrap().push(WebhookDelivery {
+                id: delivery.id,
+                subscription_id: delivery.subscription_id,
+                event_type: delivery.event_type.as_str().to_string(),
+                event_id: delivery.event_id,
+                call_id: delivery.call_id,
+                url: delivery.url,
+                payload: delivery.payload,
+                status: DELIVERY_STATUS_PENDING.to_string(),
+                attempts: 0,
+                next_attempt_at: now,
+                last_status_code: None,
+                last_error: None,
+                created_at: now,
+                delivered_at: None,
+            });
+            Box::pin(async { Ok(()) })
+        }
+
+        fn claim_due(
+            &self,
+            _limit: i64,
+            _lease: Duration,
+        ) -> WebhookDeliveryFuture<Vec<WebhookDelivery>> {
+            let rows = self
+                .rows
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|row| row.status == DELIVERY_STATUS_PENDING)
+                .cloned()
+                .collect();
+            Box::pin(async move { Ok(rows) })
+        }
+
+        fn mark_delivered(&self, id: Uuid, status_code: i32) -> WebhookDeliveryFuture<()> {
+            for row in self.rows.lock().unwrap().iter_mut().filter(|r| r.id == id) {
+                row.status = DELIVERY_STATUS_DELIVERED.to_string();
+                row.attempts += 1;
+                row.last_status_code = Some(status_code);
+            }
+            Box::pin(async { Ok(()) })
+        }
+
+        fn mark_failed(
+            &self,
+            id: Uuid,
+            failure: WebhookAttemptFailure,
+        ) -> WebhookDeliveryFuture<()> {
+            for row in self.rows.lock().unwrap().iter_mut().filter(|r| r.id == id) {
+                row.attempts += 1;
+                row.last_status_code = failure.status_code;
+                row.last_error = Some(failure.error.clone());
+                match failure.retry_at {
+                    Some(retry_at) => row.next_attempt_at = retry_at,
+                    None => row.status = DELIVERY_STATUS_DEAD.to_string(),
+                }
+            }
+            Box::pin(async { Ok(()) })
+        }
+
+        fn list(
+            &self,
+            _filter: WebhookDeliveryFilter,
+        ) -> WebhookDeliveryFuture<Vec<WebhookDelivery>> {
+            let rows = self.rows.lock().unwrap().clone();
+            Box::pin(async move { Ok(rows) })
+        }
+
+        fn requeue(&self, _id: Uuid) -> WebhookDeliveryFuture<bool> {
+            Box::pin(async { Ok(false) })
+        }
+    }
+
+    fn worker(port: Arc<MemoryPort>, url: &str, max_attempts: u32) -> WebhookDeliveryWorker {
+        let subscriptions = vec![WebhookSubscription {
+            id: "crm".to_string(),
+            url: url.to_string(),
+            events: Vec::new(),
+            secret: Some("s3cret".to_string()),
+            template: None,
+        }];
+        let config = WebhookDeliveryConfig {
+            max_attempts,
+            retry_base: Duration::from_secs(30),
+            retry_max_delay: Duration::from_secs(3600),
+            poll_interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(5),
+            batch_size: 20,
+        };
+        WebhookDeliveryWorker::new(port, Arc::new(subscriptions), config).unwrap()
+    }
+
+    async fn enqueue(port: &MemoryPort, url: &str) {
+        port.enqueue(NewWebhookDelivery {
+            id: Uuid::now_v7(),
+            subscription_id: "crm".to_string(),
+            event_type: crate::shared::ports::webhook_port::WebhookEventType::Ended,
+            event_id: Uuid::now_v7(),
+            call_id: Some("call-a".to_string()),
+            url: url.to_string(),
+            payload: json!({ "event": "ended", "callId": "call-a" }),
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn delivers_with_signature_headers() {
+        let (url, captured) = spawn_http_server(200).await;
+        let port = Arc::new(MemoryPort::default());
+        enqueue(&port, &url).await;
+
+        let sent = worker(port.clone(), &url, 3).process_once().await.unwrap();
+
+        assert_eq!(sent, 1);
+        assert_eq!(port.row().status, DELIVERY_STATUS_DELIVERED);
+        let requests = captured.lock().unwrap();
+        let (headers, body) = &requests[0];
+        assert!(headers.contains("x-webhook-event: ended"));
+        let timestamp: i64 = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("x-webhook-timestamp:"))
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        let expected = webhook::signature("s3cret", timestamp, body.as_bytes());
+        assert!(headers.contains(&format!("x-webhook-signature: {}", expected)));
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_retried_then_dead_lettered() {
+        let (url, _) = spawn_http_server(503).await;
+        let port = Arc::new(MemoryPort::default());
+        enqueue(&port, &url).await;
+        let worker = worker(port.clone(), &url, 2);
+
+        worker.process_once().await.unwrap();
+        let row = port.row();
+        assert_eq!(row.status, DELIVERY_STATUS_PENDING);
+        assert_eq!(row.attempts, 1);
+        assert_eq!(row.last_status_code, Some(503));
+        assert!(row.next_attempt_at > Utc::now() + chrono::Duration::seconds(20));
+
+        worker.process_once().await.unwrap();
+        let row = port.row();
+        assert_eq!(row.status, DELIVERY_STATUS_DEAD);
+        assert_eq!(row.attempts, 2);
+        assert!(row.last_error.unwrap().contains("503"));
+    }
+}
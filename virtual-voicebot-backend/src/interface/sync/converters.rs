@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -7,6 +7,8 @@ use sqlx::{Postgres, Row, Transaction};
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::interface::db::number_pattern::{NumberPattern, MATCH_EXACT};
+
 #[derive(Debug, Error)]
 pub enum ConverterError {
     #[error("database failed: {0}")]
@@ -22,6 +24,9 @@ pub struct CallerGroup {
     pub name: String,
     #[serde(default)]
     pub phone_numbers: Vec<String>,
+    /// 適用する着信番号（DID）。未指定なら全 DID 共通
+    #[serde(default)]
+    pub did: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -30,6 +35,9 @@ pub struct IncomingRule {
     pub id: Uuid,
     pub name: String,
     pub caller_group_id: Option<Uuid>,
+    /// 適用する着信番号（DID）。未指定なら全 DID 共通
+    #[serde(default)]
+    pub did: Option<String>,
     pub action_type: String,
     #[serde(default)]
     pub action_config: Value,
@@ -50,6 +58,8 @@ pub struct CallActionsPayload {
     pub rules: Vec<IncomingRule>,
     pub anonymous_action: StoredAction,
     pub default_action: StoredAction,
+    /// 着信番号（DID）別の既定アクション。該当がなければ default_action を使う
+    pub did_default_actions: BTreeMap<String, StoredAction>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -69,14 +79,67 @@ pub struct IvrFlowDefinition {
     pub fallback_action: IvrActionDestination,
     #[serde(default = "default_true")]
     pub is_active: bool,
+    /// メニュー案内後の入力方式。未指定はキー 1 桁（keypad）
+    #[serde(default)]
+    pub input_type: IvrInputType,
+    /// digits の収集条件（未指定なら既定値）
+    #[serde(default)]
+    pub digit_collection: Option<IvrDigitCollection>,
+    /// digits の収集完了後の遷移先。未指定なら fallbackAction
+    #[serde(default)]
+    pub complete_action: Option<IvrActionDestination>,
+    /// 収集した数字列／認識テキストを格納する呼変数名
+    #[serde(default)]
+    pub variable_name: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IvrInputType {
+    #[default]
+    Keypad,
+    Digits,
+    Speech,
+}
+
+impl IvrInputType {
+    fn node_type(self) -> &'static str {
+        match self {
+            Self::Keypad => "KEYPAD",
+            Self::Digits => "DIGITS",
+            Self::Speech => "SPEECH",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IvrDigitCollection {
+    #[serde(default = "default_min_digits")]
+    pub min_digits: i16,
+    #[serde(default = "default_max_digits")]
+    pub max_digits: i16,
+    /// 入力終端キー（"#" または "*"）。空文字なら終端キーなし
+    #[serde(default = "default_digit_terminator")]
+    pub terminator: Option<String>,
+    #[serde(default = "default_inter_digit_timeout_sec")]
+    pub inter_digit_timeout_sec: i32,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IvrRoute {
+    /// speech ではキー操作での選択も受け付ける場合のみ指定する
+    #[serde(default)]
     pub dtmf_key: String,
     pub label: String,
     pub destination: IvrActionDestination,
+    /// speech: 認識テキストにいずれかを含めば遷移
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// speech: 意図分類の結果がこの値なら遷移
+    #[serde(default)]
+    pub intent: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -89,6 +152,8 @@ pub struct IvrActionDestination {
     pub welcome_announcement_id: Option<Uuid>,
     pub recording_enabled: Option<bool>,
     pub include_announcement: Option<bool>,
+    /// VR（転送）時に呼び出す転送先グループ名
+    pub hunt_group: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -141,34 +206,42 @@ async fn convert_caller_groups(
     tx: &mut Transaction<'_, Postgres>,
     groups: &[CallerGroup],
 ) -> Result<(), ConverterError> {
-    let mut all_phone_numbers: Vec<String> = Vec::new();
+    // 同じ番号でも DID・照合方法が異なれば別の登録として扱う
+    let mut unique_entries: HashSet<(String, String, &'static str)> = HashSet::new();
     for group in groups {
+        let did = normalize_did(group.did.as_deref()).unwrap_or_default();
         for phone_number in &group.phone_numbers {
-            let normalized = normalize_phone_number(phone_number);
-            if !normalized.is_empty() {
-                all_phone_numbers.push(normalized);
+            if let Some((number, match_type)) = normalize_registered_pattern(phone_number) {
+                unique_entries.insert((number, did.clone(), match_type));
             }
         }
     }
+    let mut keep_phone_numbers = Vec::with_capacity(unique_entries.len());
+    let mut keep_dids = Vec::with_capacity(unique_entries.len());
+    let mut keep_match_types = Vec::with_capacity(unique_entries.len());
+    for (number, did, match_type) in unique_entries {
+        keep_phone_numbers.push(number);
+        keep_dids.push(did);
+        keep_match_types.push(match_type.to_string());
+    }
 
-    let unique_phone_numbers: Vec<String> = all_phone_numbers
-        .iter()
-        .cloned()
-        .collect::<HashSet<_>>()
-        .into_iter()
-        .collect();
-
-    if !unique_phone_numbers.is_empty() {
+    if !keep_phone_numbers.is_empty() {
         sqlx::query(
             "UPDATE registered_numbers
              SET deleted_at = NOW(),
                  group_id = NULL,
                  group_name = NULL,
                  updated_at = NOW()
-             WHERE phone_number != ALL($1)
+             WHERE (phone_number, COALESCE(did, ''), match_type) NOT IN (
+                     SELECT keep.phone_number, keep.did, keep.match_type
+                     FROM UNNEST($1::text[], $2::text[], $3::text[])
+                         AS keep(phone_number, did, match_type)
+                 )
                AND deleted_at IS NULL",
         )
-        .bind(&unique_phone_numbers)
+        .bind(&keep_phone_numbers)
+        .bind(&keep_dids)
+        .bind(&keep_match_types)
         .execute(&mut **tx)
         .await?;
     } else {
@@ -185,11 +258,11 @@ async fn convert_caller_groups(
     }
 
     for group in groups {
+        let did = normalize_did(group.did.as_deref());
         for phone_number in &group.phone_numbers {
-            let normalized = normalize_phone_number(phone_number);
-            if normalized.is_empty() {
+            let Some((normalized, match_type)) = normalize_registered_pattern(phone_number) else {
                 continue;
-            }
+            };
 
             let update_active = sqlx::query(
                 "UPDATE registered_numbers
@@ -198,11 +271,15 @@ async fn convert_caller_groups(
                      deleted_at = NULL,
                      updated_at = NOW()
                  WHERE phone_number = $1
+                   AND did IS NOT DISTINCT FROM $4
+                   AND match_type = $5
                    AND deleted_at IS NULL",
             )
             .bind(&normalized)
             .bind(group.id)
             .bind(group.name.clone())
+            .bind(did.clone())
+            .bind(match_type)
             .execute(&mut **tx)
             .await?;
             if update_active.rows_affected() > 0 {
@@ -219,6 +296,8 @@ async fn convert_caller_groups(
                      SELECT id
                      FROM registered_numbers
                      WHERE phone_number = $1
+                       AND did IS NOT DISTINCT FROM $4
+                       AND match_type = $5
                        AND deleted_at IS NOT NULL
                      ORDER BY updated_at DESC
                      LIMIT 1
@@ -227,6 +306,8 @@ async fn convert_caller_groups(
             .bind(&normalized)
             .bind(group.id)
             .bind(group.name.clone())
+            .bind(did.clone())
+            .bind(match_type)
             .execute(&mut **tx)
             .await?;
             if revive_deleted.rows_affected() > 0 {
@@ -235,10 +316,10 @@ async fn convert_caller_groups(
 
             sqlx::query(
                 "INSERT INTO registered_numbers
-                    (id, phone_number, group_id, group_name, category, action_code, recording_enabled, announce_enabled, created_at, updated_at)
+                    (id, phone_number, did, match_type, group_id, group_name, category, action_code, recording_enabled, announce_enabled, created_at, updated_at)
                  VALUES
-                    (gen_random_uuid(), $1, $2, $3, 'general', 'VR', TRUE, TRUE, NOW(), NOW())
-                 ON CONFLICT (phone_number) WHERE deleted_at IS NULL
+                    (gen_random_uuid(), $1, $4, $5, $2, $3, 'general', 'VR', TRUE, TRUE, NOW(), NOW())
+                 ON CONFLICT (phone_number, (COALESCE(did, '')), match_type) WHERE deleted_at IS NULL
                  DO UPDATE SET
                     group_id = $2,
                     group_name = $3,
@@ -247,6 +328,8 @@ async fn convert_caller_groups(
             .bind(normalized)
             .bind(group.id)
             .bind(group.name.clone())
+            .bind(did.clone())
+            .bind(match_type)
             .execute(&mut **tx)
             .await?;
         }
@@ -267,9 +350,9 @@ async fn convert_incoming_rules(
         let action_type = normalize_action_type(&rule.action_type);
         sqlx::query(
             "INSERT INTO call_action_rules
-                (id, name, caller_group_id, action_type, action_config, priority, is_active, created_at, updated_at)
+                (id, name, caller_group_id, did, action_type, action_config, priority, is_active, created_at, updated_at)
              VALUES
-                ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())",
+                ($1, $2, $3, $8, $4, $5, $6, $7, NOW(), NOW())",
         )
         .bind(rule.id)
         .bind(rule.name.trim())
@@ -278,6 +361,7 @@ async fn convert_incoming_rules(
         .bind(rule.action_config.clone())
         .bind(index as i32)
         .bind(rule.is_active)
+        .bind(normalize_did(rule.did.as_deref()))
         .execute(&mut **tx)
         .await?;
     }
@@ -305,9 +389,19 @@ async fn save_call_actions_settings(
 
     let anonymous_action = serde_json::to_value(&actions.anonymous_action)?;
     let default_action = serde_json::to_value(&actions.default_action)?;
+    let mut did_default_actions = Map::new();
+    for (did, action) in &actions.did_default_actions {
+        if let Some(did) = normalize_did(Some(did)) {
+            did_default_actions.insert(did, serde_json::to_value(action)?);
+        }
+    }
     if let Some(map) = extra.as_object_mut() {
         map.insert("anonymousAction".to_string(), anonymous_action);
         map.insert("defaultAction".to_string(), default_action);
+        map.insert(
+            "didDefaultActions".to_string(),
+            Value::Object(did_default_actions),
+        );
     }
 
     sqlx::query(
@@ -481,22 +575,41 @@ async fn convert_ivr_flows(
         .await?;
 
         let keypad_node_id = Uuid::new_v4();
+        let digits = normalize_digit_collection(flow.digit_collection.as_ref());
         sqlx::query(
             "INSERT INTO ivr_nodes
-                (id, flow_id, parent_id, node_type, depth, timeout_sec, max_retries, created_at, updated_at)
+                (id, flow_id, parent_id, node_type, depth, timeout_sec, max_retries,
+                 min_digits, max_digits, terminator, inter_digit_timeout_sec, variable_name,
+                 created_at, updated_at)
              VALUES
-                ($1, $2, $3, 'KEYPAD', 1, $4, $5, NOW(), NOW())",
+                ($1, $2, $3, $4, 1, $5, $6, $7, $8, $9, $10, $11, NOW(), NOW())",
         )
         .bind(keypad_node_id)
         .bind(flow.id)
         .bind(root_node_id)
+        .bind(flow.input_type.node_type())
         .bind(timeout_sec)
         .bind(max_retries)
+        .bind(digits.min_digits)
+        .bind(digits.max_digits)
+        .bind(digits.terminator.as_deref())
+        .bind(digits.inter_digit_timeout_sec)
+        .bind(normalize_variable_name(flow.variable_name.as_deref()))
         .execute(&mut **tx)
         .await?;
 
         for route in &flow.routes {
-            if route.dtmf_key.trim().is_empty() {
+            let dtmf_key = route.dtmf_key.trim();
+            let keywords = normalize_speech_keywords(&route.keywords);
+            let intent = route
+                .intent
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty());
+            let has_speech_match = flow.input_type == IvrInputType::Speech
+                && (!keywords.is_empty() || intent.is_some());
+            let has_dtmf_match = flow.input_type != IvrInputType::Digits && !dtmf_key.is_empty();
+            if !has_speech_match && !has_dtmf_match {
                 continue;
             }
             let destination_node_id = create_destination_node(
@@ -510,16 +623,61 @@ async fn convert_ivr_flows(
             )
             .await?;
 
+            if has_dtmf_match {
+                sqlx::query(
+                    "INSERT INTO ivr_transitions
+                        (id, from_node_id, input_type, dtmf_key, to_node_id, created_at)
+                     VALUES
+                        ($1, $2, 'DTMF', $3, $4, NOW())",
+                )
+                .bind(Uuid::new_v4())
+                .bind(keypad_node_id)
+                .bind(dtmf_key)
+                .bind(destination_node_id)
+                .execute(&mut **tx)
+                .await?;
+            }
+            if has_speech_match {
+                sqlx::query(
+                    "INSERT INTO ivr_transitions
+                        (id, from_node_id, input_type, dtmf_key, to_node_id, keywords, intent, created_at)
+                     VALUES
+                        ($1, $2, 'SPEECH', NULL, $3, $4, $5, NOW())",
+                )
+                .bind(Uuid::new_v4())
+                .bind(keypad_node_id)
+                .bind(destination_node_id)
+                .bind(&keywords)
+                .bind(intent)
+                .execute(&mut **tx)
+                .await?;
+            }
+        }
+
+        if flow.input_type == IvrInputType::Digits {
+            let complete_action = flow
+                .complete_action
+                .as_ref()
+                .unwrap_or(&flow.fallback_action);
+            let complete_node_id = create_destination_node(
+                tx,
+                flow.id,
+                keypad_node_id,
+                complete_action,
+                2,
+                timeout_sec,
+                max_retries,
+            )
+            .await?;
             sqlx::query(
                 "INSERT INTO ivr_transitions
                     (id, from_node_id, input_type, dtmf_key, to_node_id, created_at)
                  VALUES
-                    ($1, $2, 'DTMF', $3, $4, NOW())",
+                    ($1, $2, 'COMPLETE', NULL, $3, NOW())",
             )
             .bind(Uuid::new_v4())
             .bind(keypad_node_id)
-            .bind(route.dtmf_key.trim())
-            .bind(destination_node_id)
+            .bind(complete_node_id)
             .execute(&mut **tx)
             .await?;
         }
@@ -650,6 +808,17 @@ fn build_destination_metadata(destination: &IvrActionDestination) -> Option<Stri
     if let Some(value) = destination.include_announcement {
         metadata.insert("includeAnnouncement".to_string(), Value::Bool(value));
     }
+    if let Some(hunt_group) = destination
+        .hunt_group
+        .as_ref()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+    {
+        metadata.insert(
+            "huntGroup".to_string(),
+            Value::String(hunt_group.to_string()),
+        );
+    }
     if metadata.is_empty() {
         None
     } else {
@@ -672,6 +841,27 @@ fn normalize_phone_number(raw: &str) -> String {
     }
 }
 
+/// 番号グループの電話番号を (phone_number, match_type) に変換する。
+/// 末尾が "*" の番号（例: "050*"）は前方一致として登録する。
+fn normalize_registered_pattern(raw: &str) -> Option<(String, &'static str)> {
+    let normalized = normalize_phone_number(raw);
+    if normalized.is_empty() {
+        return None;
+    }
+    match NumberPattern::parse(&normalized) {
+        Some(pattern @ NumberPattern::Prefix(_)) => {
+            Some((pattern.key().to_string(), pattern.match_type()))
+        }
+        _ => Some((normalized, MATCH_EXACT)),
+    }
+}
+
+/// DID は電話番号と同じ規則で正規化し、空文字は未指定（全 DID 共通）とみなす。
+fn normalize_did(raw: Option<&str>) -> Option<String> {
+    raw.map(normalize_phone_number)
+        .filter(|value| !value.is_empty())
+}
+
 fn normalize_action_type(raw: &str) -> &'static str {
     if raw.eq_ignore_ascii_case("deny") {
         "deny"
@@ -744,6 +934,81 @@ fn default_max_retries() -> i32 {
     2
 }
 
+fn default_min_digits() -> i16 {
+    1
+}
+
+fn default_max_digits() -> i16 {
+    16
+}
+
+fn default_digit_terminator() -> Option<String> {
+    Some("#".to_string())
+}
+
+fn default_inter_digit_timeout_sec() -> i32 {
+    3
+}
+
+/// DB の CHECK 制約を満たす範囲に丸めた digits の収集条件
+#[derive(Debug, PartialEq, Eq)]
+struct DigitCollectionColumns {
+    min_digits: i16,
+    max_digits: i16,
+    terminator: Option<String>,
+    inter_digit_timeout_sec: i32,
+}
+
+fn normalize_digit_collection(collection: Option<&IvrDigitCollection>) -> DigitCollectionColumns {
+    let Some(collection) = collection else {
+        return DigitCollectionColumns {
+            min_digits: default_min_digits(),
+            max_digits: default_max_digits(),
+            terminator: default_digit_terminator(),
+            inter_digit_timeout_sec: default_inter_digit_timeout_sec(),
+        };
+    };
+    let max_digits = collection.max_digits.clamp(1, 32);
+    let min_digits = collection.min_digits.clamp(0, max_digits);
+    let terminator = collection
+        .terminator
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| matches!(*value, "#" | "*"))
+        .map(str::to_string);
+    let inter_digit_timeout_sec = if collection.inter_digit_timeout_sec <= 0 {
+        default_inter_digit_timeout_sec()
+    } else {
+        collection.inter_digit_timeout_sec
+    };
+    DigitCollectionColumns {
+        min_digits,
+        max_digits,
+        terminator,
+        inter_digit_timeout_sec,
+    }
+}
+
+/// 呼変数名は英数字と `_` のみ（64 文字以内）。それ以外は格納しない
+fn normalize_variable_name(raw: Option<&str>) -> Option<String> {
+    let name = raw?.trim();
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| name.to_string())
+}
+
+fn normalize_speech_keywords(keywords: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(keywords.len());
+    for keyword in keywords {
+        let keyword = keyword.trim();
+        if !keyword.is_empty() && !normalized.iter().any(|existing| existing == keyword) {
+            normalized.push(keyword.to_string());
+        }
+    }
+    normalized
+}
+
 fn default_announcement_type() -> String {
     "custom".to_string()
 }
@@ -757,12 +1022,18 @@ fn default_ivr_destination() -> IvrActionDestination {
         welcome_announcement_id: None,
         recording_enabled: None,
         include_announcement: None,
+        hunt_group: None,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{default_anonymous_action, default_default_action, normalize_phone_number};
+    use super::{
+        default_anonymous_action, default_default_action, normalize_did,
+        normalize_digit_collection, normalize_phone_number, normalize_registered_pattern,
+        normalize_speech_keywords, normalize_variable_name, DigitCollectionColumns,
+        IvrDigitCollection, IvrFlowDefinition, IvrInputType,
+    };
 
     #[test]
     fn phone_number_normalization_removes_delimiters() {
@@ -776,6 +1047,29 @@ mod tests {
         assert_eq!(normalized, "+818012345678");
     }
 
+    #[test]
+    fn registered_pattern_accepts_trailing_wildcard() {
+        assert_eq!(
+            normalize_registered_pattern("050*"),
+            Some(("+8150".to_string(), "prefix"))
+        );
+        assert_eq!(
+            normalize_registered_pattern("090-1234-5678"),
+            Some(("+819012345678".to_string(), "exact"))
+        );
+        assert_eq!(normalize_registered_pattern(" "), None);
+    }
+
+    #[test]
+    fn did_normalization_treats_blank_as_unscoped() {
+        assert_eq!(
+            normalize_did(Some("03-1234-5678")).as_deref(),
+            Some("+81312345678")
+        );
+        assert_eq!(normalize_did(Some(" ")), None);
+        assert_eq!(normalize_did(None), None);
+    }
+
     #[test]
     fn default_actions_match_contract() {
         let anonymous = default_anonymous_action();
@@ -785,4 +1079,68 @@ mod tests {
         assert_eq!(anonymous.action_config["actionCode"], "BZ");
         assert_eq!(default_action.action_config["actionCode"], "VR");
     }
+
+    #[test]
+    fn ivr_flow_without_input_fields_defaults_to_keypad() {
+        let flow: IvrFlowDefinition = serde_json::from_value(serde_json::json!({
+            "id": "00000000-0000-0000-0000-000000000001",
+            "name": "main",
+            "routes": [{
+                "dtmfKey": "1",
+                "label": "sales",
+                "destination": { "actionCode": "VR" }
+            }]
+        }))
+        .expect("flow parses");
+        assert_eq!(flow.input_type, IvrInputType::Keypad);
+        assert!(flow.digit_collection.is_none());
+        assert!(flow.routes[0].keywords.is_empty());
+    }
+
+    #[test]
+    fn digit_collection_is_clamped_to_db_constraints() {
+        assert_eq!(
+            normalize_digit_collection(None),
+            DigitCollectionColumns {
+                min_digits: 1,
+                max_digits: 16,
+                terminator: Some("#".to_string()),
+                inter_digit_timeout_sec: 3,
+            }
+        );
+        let collection = IvrDigitCollection {
+            min_digits: 8,
+            max_digits: 4,
+            terminator: Some("x".to_string()),
+            inter_digit_timeout_sec: 0,
+        };
+        assert_eq!(
+            normalize_digit_collection(Some(&collection)),
+            DigitCollectionColumns {
+                min_digits: 4,
+                max_digits: 4,
+                terminator: None,
+                inter_digit_timeout_sec: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn variable_names_and_keywords_are_sanitized() {
+        assert_eq!(
+            normalize_variable_name(Some(" member_id ")).as_deref(),
+            Some("member_id")
+        );
+        assert_eq!(normalize_variable_name(Some("会員番号")), None);
+        assert_eq!(normalize_variable_name(Some(" ")), None);
+        assert_eq!(
+            normalize_speech_keywords(&[
+                " 予約 ".to_string(),
+                "".to_string(),
+                "予約".to_string(),
+                "キャンセル".to_string(),
+            ]),
+            vec!["予約".to_string(), "キャンセル".to_string()]
+        );
+    }
 }
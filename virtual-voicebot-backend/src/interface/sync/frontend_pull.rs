@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
@@ -62,6 +62,8 @@ struct CallActionsResponse {
     rules: Vec<crate::interface::sync::converters::IncomingRule>,
     anonymous_action: Option<StoredAction>,
     default_action: Option<StoredAction>,
+    #[serde(default)]
+    did_default_actions: BTreeMap<String, StoredAction>,
     error: Option<String>,
 }
 
@@ -221,6 +223,7 @@ impl FrontendPullWorker {
                 .anonymous_action
                 .unwrap_or_else(default_anonymous_action),
             default_action: body.default_action.unwrap_or_else(default_default_action),
+            did_default_actions: body.did_default_actions,
         })
     }
 
@@ -9,5 +9,7 @@ pub use converters::{
     ConverterError, IncomingRule, IvrActionDestination, IvrFlowDefinition, IvrRoute, StoredAction,
 };
 pub use frontend_pull::{FrontendPullError, FrontendPullWorker};
-pub use notification_worker::{NotificationWorker, NotificationWorkerError};
+pub use notification_worker::{
+    pending_notification_count, NotificationWorker, NotificationWorkerError,
+};
 pub use worker::{OutboxWorker, SyncWorkerError};
@@ -172,6 +172,24 @@ impl NotificationWorker {
     }
 }
 
+/// 送信待ちの通知件数（キューファイルと処理中ファイルの行数の合計）
+pub async fn pending_notification_count(queue_file: &Path) -> std::io::Result<usize> {
+    let mut count = 0;
+    for path in [queue_file.to_path_buf(), processing_file_path(queue_file)] {
+        match tokio::fs::read_to_string(&path).await {
+            Ok(content) => {
+                count += content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .count();
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(count)
+}
+
 fn processing_file_path(queue_file: &Path) -> PathBuf {
     let processing_extension = match queue_file.extension().and_then(|value| value.to_str()) {
         Some(ext) if !ext.is_empty() => format!("{ext}.processing"),
@@ -182,7 +200,10 @@ fn processing_file_path(queue_file: &Path) -> PathBuf {
 
 #[cfg(test)]
 mod tests {
-    use super::{processing_file_path, NotificationWorker, NotificationWorkerError};
+    use super::{
+        pending_notification_count, processing_file_path, NotificationWorker,
+        NotificationWorkerError,
+    };
     use serde_json::{json, Value};
     use std::path::{Path, PathBuf};
     use std::sync::atomic::{AtomicUsize, Ordering};
@@ -345,6 +366,35 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn pending_count_includes_processing_file() {
+        let temp = tempfile::tempdir().expect("tempdir should be creatable");
+        let queue_file = temp.path().join("pending.jsonl");
+        assert_eq!(
+            pending_notification_count(queue_file.as_path())
+                .await
+                .unwrap(),
+            0
+        );
+
+        std::fs::write(
+            queue_file.as_path(),
+            "{\"call_id\":\"a\"}\n\n{\"call_id\":\"b\"}\n",
+        )
+        .expect("jsonl should be writable");
+        write_json_line(
+            processing_file_path(queue_file.as_path()).as_path(),
+            &json!({ "call_id": "c" }),
+        );
+
+        assert_eq!(
+            pending_notification_count(queue_file.as_path())
+                .await
+                .unwrap(),
+            3
+        );
+    }
+
     #[tokio::test]
     async fn send_notification_posts_to_expected_endpoint() {
         let temp = tempfile::tempdir().expect("tempdir should be creatable");
@@ -6,6 +6,9 @@ use serde_json::json;
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::service::recording::archive;
+use crate::shared::config;
+
 #[derive(Clone, Debug)]
 pub struct RecordingUploadRequest {
     pub call_log_id: Uuid,
@@ -57,9 +60,11 @@ impl RecordingUploader {
             ));
         }
 
-        let audio = tokio::fs::read(&request.audio_path)
-            .await
-            .map_err(|e| RecordingUploadError::FileReadFailed(e.to_string()))?;
+        let audio_path = request.audio_path.clone();
+        let (audio, audio_file_name) =
+            tokio::task::spawn_blocking(move || read_recording(&audio_path))
+                .await
+                .map_err(|e| RecordingUploadError::FileReadFailed(e.to_string()))??;
         let meta = match tokio::fs::read_to_string(&request.meta_path).await {
             Ok(meta) => meta,
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
@@ -75,7 +80,7 @@ impl RecordingUploader {
         };
 
         let audio_part = reqwest::multipart::Part::bytes(audio)
-            .file_name("mixed.wav")
+            .file_name(audio_file_name)
             .mime_str("application/octet-stream")
             .map_err(|e| RecordingUploadError::InvalidRequest(e.to_string()))?;
         let meta_part = reqwest::multipart::Part::text(meta)
@@ -114,6 +119,24 @@ impl RecordingUploader {
     }
 }
 
+/// 録音を平文で読む。後処理で変換・暗号化済みなら、同じディレクトリの変換後を復号して読む
+fn read_recording(audio_path: &Path) -> Result<(Vec<u8>, String), RecordingUploadError> {
+    let read_failed =
+        |e: &dyn std::fmt::Display| RecordingUploadError::FileReadFailed(e.to_string());
+    let dir = audio_path.parent().unwrap_or(Path::new(""));
+    let stored = audio_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| archive::find(dir, name))
+        .or_else(|| archive::find_mixed(dir))
+        .ok_or_else(|| read_failed(&format!("{} not found", audio_path.display())))?;
+    let keys = &config::recording_storage_config().encryption_keys;
+    let audio = archive::RecordingReader::open(&stored, keys)
+        .and_then(|mut reader| reader.read_all())
+        .map_err(|e| read_failed(&e))?;
+    Ok((audio, stored.file_name))
+}
+
 fn fallback_meta_json(audio_path: &Path) -> String {
     let call_id = audio_path
         .parent()
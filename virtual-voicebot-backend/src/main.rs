@@ -4,9 +4,14 @@ use std::sync::Arc;
 use tokio::net::{TcpListener, UdpSocket};
 use tokio::sync::{mpsc, Mutex};
 
-use virtual_voicebot_backend::interface::db::{PostgresAdapter, RoutingRepoImpl};
+use virtual_voicebot_backend::interface::db::{
+    CallerMemoryRepoImpl, PostgresAdapter, RoutingRepoImpl, VoicemailRepoImpl, WebhookRepoImpl,
+};
 use virtual_voicebot_backend::interface::http;
-use virtual_voicebot_backend::interface::notification::{LineAdapter, NoopNotification};
+use virtual_voicebot_backend::interface::notification::{
+    self as notification, FanoutChannel, LineAdapter, NoopNotification, NotificationFanout,
+    WebhookAdapter, WebhookDeliveryWorker,
+};
 use virtual_voicebot_backend::protocol::rtp::tx::RtpTxHandle;
 use virtual_voicebot_backend::protocol::session::types::CallId;
 use virtual_voicebot_backend::protocol::session::{
@@ -21,12 +26,28 @@ use virtual_voicebot_backend::protocol::transport::{
 use virtual_voicebot_backend::service::ai;
 use virtual_voicebot_backend::service::call_control as app;
 use virtual_voicebot_backend::service::call_control::AppNotificationPort;
+use virtual_voicebot_backend::service::call_events::{CallEventFanout, CallEventHub};
+use virtual_voicebot_backend::service::call_queue::{CallQueueService, QueueDirective};
+use virtual_voicebot_backend::service::live_call::LiveCallService;
 use virtual_voicebot_backend::service::recording;
+use virtual_voicebot_backend::service::runtime_config;
+use virtual_voicebot_backend::service::voicemail::VoicemailService;
+use virtual_voicebot_backend::service::webhook::{self as webhook, WebhookService};
+use virtual_voicebot_backend::shared::ports::call_event_port::CallEventPublisher;
 use virtual_voicebot_backend::shared::ports::call_log_port::{CallLogPort, NoopCallLogPort};
+use virtual_voicebot_backend::shared::ports::caller_memory_port::{
+    CallerMemoryPort, NoopCallerMemoryPort,
+};
+use virtual_voicebot_backend::shared::ports::notification::VoicemailNotifier;
 use virtual_voicebot_backend::shared::ports::phone_lookup::{NoopPhoneLookup, PhoneLookupPort};
 use virtual_voicebot_backend::shared::ports::routing_port::{NoopRoutingPort, RoutingPort};
+use virtual_voicebot_backend::shared::ports::schedule_port::{NoopSchedulePort, SchedulePort};
 use virtual_voicebot_backend::shared::ports::session_lookup::SessionLookup;
-use virtual_voicebot_backend::shared::{config, logging};
+use virtual_voicebot_backend::shared::ports::voicemail_port::{NoopVoicemailPort, VoicemailPort};
+use virtual_voicebot_backend::shared::ports::webhook_port::{
+    NoopWebhookPublisher, WebhookPublisher,
+};
+use virtual_voicebot_backend::shared::{config, logging, metrics};
 
 const SIP_INPUT_CHANNEL_CAPACITY: usize = 256;
 const SIP_SEND_CHANNEL_CAPACITY: usize = 256;
@@ -137,6 +158,14 @@ async fn main() -> anyhow::Result<()> {
     logging::init();
     ai::llm::init_system_prompt();
     ai::intent::init_intent_prompt();
+    let reload_interval = config::config_reload_poll_interval();
+    if !reload_interval.is_zero() {
+        runtime_config::spawn_file_watcher(reload_interval);
+    }
+    #[cfg(unix)]
+    if let Err(err) = runtime_config::spawn_sighup_listener() {
+        log::warn!("[main] SIGHUP reload disabled: {:?}", err);
+    }
 
     let cfg = config::Config::from_env()?;
     let timeouts = config::timeouts().clone();
@@ -202,13 +231,28 @@ async fn main() -> anyhow::Result<()> {
     );
     log::info!("[recording] static HTTP on {}", recording_http_addr);
 
-    // 録音配信の簡易HTTPサーバ（/recordings/<callId>/... を静的配信）
+    let ai_port = Arc::new(ai::DefaultAiPort::new());
+    let call_events = Arc::new(CallEventHub::from_env());
+
+    // 録音配信の簡易HTTPサーバ（/recordings/<callId>/... を静的配信、通話中の運用 API）
     {
         let base_dir = std::env::current_dir()?.join(recording::RECORDINGS_DIR);
         let recording_http_pool = postgres_adapter
             .as_ref()
             .map(|adapter| adapter.pool().clone());
-        http::spawn_recording_server(&recording_http_addr, base_dir, recording_http_pool).await;
+        let live_calls = Arc::new(LiveCallService::new(
+            session_registry.clone(),
+            ai_port.clone(),
+        ));
+        recording::janitor::spawn_recording_janitor(base_dir.clone());
+        http::spawn_recording_server(
+            &recording_http_addr,
+            base_dir,
+            recording_http_pool,
+            live_calls,
+            call_events.clone(),
+        )
+        .await;
     }
 
     // packetループ起動（UDP受信 → SIP/RTP振り分け → セッションへ）
@@ -237,7 +281,9 @@ async fn main() -> anyhow::Result<()> {
     }
 
     // --- SIP処理ループ: packet層からのSIP入力をセッションへ結線 ---
-    let ai_port = Arc::new(ai::DefaultAiPort::new());
+    // 定型文の TTS を事前合成（TTS サーバ未起動でも起動はブロックしない）
+    tokio::spawn(ai::warm_up_phrase_cache(app::fixed_phrases()));
+    ai::spawn_stage_health_prober();
 
     let phone_lookup: Arc<dyn PhoneLookupPort> = if config::phone_lookup_enabled() {
         match postgres_adapter.clone() {
@@ -264,23 +310,142 @@ async fn main() -> anyhow::Result<()> {
             Arc::new(NoopRoutingPort::new())
         }
     };
+    let schedule_port: Arc<dyn SchedulePort> = match postgres_adapter.clone() {
+        Some(adapter) => adapter,
+        None => Arc::new(NoopSchedulePort::new()),
+    };
+    let schedule_routing = config::schedule_routing_config();
+    if schedule_routing.enabled {
+        log::info!(
+            "[main] schedule routing active (utc_offset={}): schedules are evaluated before routing rules",
+            schedule_routing.utc_offset
+        );
+    } else {
+        log::info!("[main] schedule routing disabled (SCHEDULE_ROUTING_ENABLED=false)");
+    }
+    let voicemail_port: Arc<dyn VoicemailPort> = match postgres_adapter.clone() {
+        Some(adapter) => Arc::new(VoicemailRepoImpl::new(adapter.pool().clone())),
+        None => {
+            log::warn!("[main] voicemail mailboxes disabled (DATABASE_URL unavailable)");
+            Arc::new(NoopVoicemailPort::new())
+        }
+    };
 
+    let caller_memory_port: Arc<dyn CallerMemoryPort> = if app_cfg.caller_memory_enabled {
+        match postgres_adapter.clone() {
+            Some(adapter) => {
+                let repo = Arc::new(CallerMemoryRepoImpl::new(adapter.pool().clone()));
+                app::spawn_caller_memory_purge(repo.clone());
+                repo
+            }
+            None => {
+                log::warn!("[main] caller memory enabled but DATABASE_URL is missing/unavailable");
+                Arc::new(NoopCallerMemoryPort::new())
+            }
+        }
+    } else {
+        Arc::new(NoopCallerMemoryPort::new())
+    };
+
+    // 通知チャネル: LINE（環境変数）と notification_channels.yaml のチャネルへ同時に送る
     let notification_port: Arc<dyn AppNotificationPort> = {
+        let mut channels = Vec::new();
         let cfg = config::line_notify_config();
         if cfg.enabled {
             let token = cfg.channel_access_token.clone().unwrap_or_default();
             let user_id = cfg.user_id.clone().unwrap_or_default();
             match LineAdapter::new(token, user_id) {
-                Ok(adapter) => Arc::new(adapter),
+                Ok(adapter) => channels.push(FanoutChannel::new("line", Arc::new(adapter))),
+                Err(err) => log::warn!("[main] line adapter init failed: {}", err),
+            }
+        }
+        let specs = notification::load_channels().unwrap_or_else(|err| {
+            log::warn!("[main] notification channel config load failed: {}", err);
+            Vec::new()
+        });
+        for spec in specs {
+            let id = spec.id.clone();
+            match spec.build() {
+                Ok(channel) => channels.push(channel),
+                Err(err) => log::warn!("[main] notification channel {} init failed: {}", id, err),
+            }
+        }
+        if channels.is_empty() {
+            Arc::new(NoopNotification::new())
+        } else {
+            log::info!("[main] notification channels={}", channels.len());
+            Arc::new(NotificationFanout::new(channels))
+        }
+    };
+    let voicemail_webhook: Arc<dyn VoicemailNotifier> =
+        match WebhookAdapter::new(config::voicemail_config().webhook_url.clone()) {
+            Ok(adapter) => Arc::new(adapter),
+            Err(err) => {
+                log::warn!("[main] voicemail webhook init failed: {}", err);
+                Arc::new(NoopNotification::new())
+            }
+        };
+    // 外部 webhook: 購読があり DB が使える時だけ配送キューとワーカーを起動する
+    let subscriptions = Arc::new(webhook::load_subscriptions().unwrap_or_else(|err| {
+        log::warn!("[main] webhook config load failed: {}", err);
+        Vec::new()
+    }));
+    let (call_event_publisher, event_webhooks): (
+        Arc<dyn CallEventPublisher>,
+        Arc<dyn WebhookPublisher>,
+    ) = match (subscriptions.is_empty(), postgres_adapter.as_ref()) {
+        (false, Some(adapter)) => {
+            let delivery_port = Arc::new(WebhookRepoImpl::new(adapter.pool().clone()));
+            match WebhookDeliveryWorker::new(
+                delivery_port.clone(),
+                subscriptions.clone(),
+                config::webhook_delivery_config().clone(),
+            ) {
+                Ok(worker) => {
+                    tokio::spawn(async move { worker.run().await });
+                    log::info!(
+                        "[main] webhook delivery enabled subscriptions={}",
+                        subscriptions.len()
+                    );
+                    let service = Arc::new(
+                        WebhookService::new(subscriptions, delivery_port)
+                            .with_recording_base_url(recording_base_url.clone()),
+                    );
+                    (
+                        Arc::new(CallEventFanout::new(vec![
+                            call_events.clone(),
+                            service.clone(),
+                        ])),
+                        service,
+                    )
+                }
                 Err(err) => {
-                    log::warn!("[main] line adapter init failed: {}", err);
-                    Arc::new(NoopNotification::new())
+                    log::warn!("[main] webhook delivery init failed: {}", err);
+                    (call_events.clone(), Arc::new(NoopWebhookPublisher::new()))
                 }
             }
-        } else {
-            Arc::new(NoopNotification::new())
         }
+        (false, None) => {
+            log::warn!("[main] webhook subscriptions ignored: database not configured");
+            (call_events.clone(), Arc::new(NoopWebhookPublisher::new()))
+        }
+        (true, _) => (call_events.clone(), Arc::new(NoopWebhookPublisher::new())),
     };
+    let voicemail_service = Arc::new(
+        VoicemailService::new(
+            voicemail_port.clone(),
+            ai_port.clone(),
+            notification_port.clone(),
+            voicemail_webhook,
+        )
+        .with_event_webhooks(event_webhooks),
+    );
+    let call_queue_service = Arc::new(CallQueueService::new(
+        session_cfg.hunt_groups.queues(),
+        ai_port.clone(),
+        notification_port.clone(),
+    ));
+    let mut queue_directive_rx = call_queue_service.spawn_dispatcher();
     let ingest_port = Arc::new(http::ingest::HttpIngestPort::new(timeouts.ingest_http)?);
     let storage_port = Arc::new(recording::storage::FileStoragePort::new());
     let mut sip_core = SipCore::new(
@@ -310,6 +475,7 @@ async fn main() -> anyhow::Result<()> {
                             call_id,
                             from,
                             to,
+                            identity,
                             offer,
                             session_timer,
                         } => {
@@ -349,6 +515,8 @@ async fn main() -> anyhow::Result<()> {
                                 audio_chunk_rx,
                                 phone_lookup.clone(),
                                 notification_port.clone(),
+                                caller_memory_port.clone(),
+                                call_event_publisher.clone(),
                                 app_cfg.clone(),
                             );
                             let sess_handle = spawn_session(
@@ -367,6 +535,9 @@ async fn main() -> anyhow::Result<()> {
                                 storage_port.clone(),
                                 call_log_port.clone(),
                                 routing_port.clone(),
+                                schedule_port.clone(),
+                                voicemail_port.clone(),
+                                call_event_publisher.clone(),
                                 session_cfg.clone(),
                             )
                             .await;
@@ -395,6 +566,7 @@ async fn main() -> anyhow::Result<()> {
                                     call_id,
                                     from,
                                     to,
+                                    identity,
                                     offer,
                                     session_timer,
                                 })
@@ -507,6 +679,23 @@ async fn main() -> anyhow::Result<()> {
                     }
                 }
             }
+            Some((call_id, directive)) = queue_directive_rx.recv() => {
+                if let Some(sess_tx) = session_registry.get(&call_id).await {
+                    let control = match directive {
+                        QueueDirective::Position {
+                            position,
+                            audio_path,
+                        } => SessionControlIn::QueuePosition {
+                            position,
+                            audio_path,
+                        },
+                        QueueDirective::Connect { agent } => SessionControlIn::QueueConnect { agent },
+                    };
+                    let _ = sess_tx.control_tx.send(control).await;
+                } else {
+                    call_queue_service.leave(&call_id).await;
+                }
+            }
             Some((call_id, out)) = session_out_rx.recv() => {
                 match out {
                     SessionOut::RtpStartTx { dst_ip, dst_port, .. } => {
@@ -546,6 +735,14 @@ async fn main() -> anyhow::Result<()> {
                                 .await;
                         }
                     }
+                    SessionOut::AppEnqueueBotAudioFrames { frames, generation_id } => {
+                        if let Some(sess_tx) = session_registry.get(&call_id).await {
+                            let _ = sess_tx
+                                .control_tx
+                                .send(SessionControlIn::AppBotAudioFramesEnqueue { frames, generation_id })
+                                .await;
+                        }
+                    }
                     SessionOut::AppRequestHangup => {
                         if let Some(sess_tx) = session_registry.get(&call_id).await {
                             let _ = sess_tx
@@ -562,6 +759,83 @@ async fn main() -> anyhow::Result<()> {
                                 .await;
                         }
                     }
+                    SessionOut::AppRequestRecordingPause => {
+                        if let Some(sess_tx) = session_registry.get(&call_id).await {
+                            let _ = sess_tx
+                                .control_tx
+                                .send(SessionControlIn::AppRecordingPause)
+                                .await;
+                        }
+                    }
+                    SessionOut::AppEmotionTurn { turn } => {
+                        if let Some(sess_tx) = session_registry.get(&call_id).await {
+                            let _ = sess_tx
+                                .control_tx
+                                .send(SessionControlIn::AppEmotionTurn { turn })
+                                .await;
+                        }
+                    }
+                    SessionOut::AppIvrSpeechResult { text, intent } => {
+                        if let Some(sess_tx) = session_registry.get(&call_id).await {
+                            let _ = sess_tx
+                                .control_tx
+                                .send(SessionControlIn::IvrSpeechResult { text, intent })
+                                .await;
+                        }
+                    }
+                    SessionOut::VoicemailRecorded { message } => {
+                        let service = voicemail_service.clone();
+                        tokio::spawn(async move {
+                            let call_log_id = message.call_log_id;
+                            match service.process_recording(message).await {
+                                Ok(Some(message_id)) => log::info!(
+                                    "[main] voicemail stored call_log_id={} message_id={}",
+                                    call_log_id,
+                                    message_id
+                                ),
+                                Ok(None) => {}
+                                Err(err) => log::warn!(
+                                    "[main] voicemail processing failed call_log_id={}: {}",
+                                    call_log_id,
+                                    err
+                                ),
+                            }
+                        });
+                    }
+                    SessionOut::QueueJoin { queue, priority } => {
+                        call_queue_service.join(call_id, &queue, priority).await;
+                    }
+                    SessionOut::QueueLeave { queue: _ } => {
+                        call_queue_service.leave(&call_id).await;
+                    }
+                    SessionOut::QueueConnectFailed { queue, agent } => {
+                        call_queue_service
+                            .connect_failed(&call_id, &queue, &agent)
+                            .await;
+                    }
+                    SessionOut::QueueAgentReleased { queue, agent } => {
+                        call_queue_service
+                            .release_agent(&call_id, &queue, &agent)
+                            .await;
+                    }
+                    SessionOut::QueueCallbackRequested {
+                        queue,
+                        caller_number,
+                    } => {
+                        let service = call_queue_service.clone();
+                        tokio::spawn(async move {
+                            if let Err(err) = service
+                                .request_callback(call_id.clone(), queue, caller_number)
+                                .await
+                            {
+                                log::warn!(
+                                    "[main] callback request notification failed call_id={}: {}",
+                                    call_id,
+                                    err
+                                );
+                            }
+                        });
+                    }
                     SessionOut::AppRequestTts { text } => {
                         log::debug!(
                             "[main] AppRequestTts received (stub): call_id={} text_len={}",
@@ -570,6 +844,7 @@ async fn main() -> anyhow::Result<()> {
                         );
                     }
                     SessionOut::Metrics { name, value } => {
+                        metrics::SESSION_METRICS.add(&[name], value.max(0) as u64);
                         if name == "rtp_in" {
                             log::debug!(
                                 "[metrics] name={} value={} call_id={}",
@@ -4,6 +4,7 @@ pub mod dtmf;
 pub mod packet;
 pub mod parser;
 pub mod payload;
+pub mod quality;
 pub mod rtcp;
 pub mod rx;
 pub mod stream;
@@ -0,0 +1,202 @@
+//! 通話ごとの受信メディア品質の集計。
+//!
+//! rx の RTCP 受信レポート計算（受信数・期待数・ジッタ）、ジッタバッファ（並べ替え・遅着破棄）、
+//! 相手から届いた RR（RTT）から値を受け、通話終了時にセッションが `take` で要約を取り出す。
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::shared::ports::call_log_port::MediaQuality;
+
+/// 取り出されないまま残った集計（終了後に遅れて届いたパケット分など）を捨てるまでの時間
+const STALE_AFTER: Duration = Duration::from_secs(600);
+/// MOS 推定に使う送出側の遅延（20ms パケット化）
+const PACKETIZATION_DELAY_MS: f64 = 20.0;
+
+#[derive(Default)]
+struct CallQuality {
+    /// 現在の SSRC と、その SSRC での受信数・期待数（RTCP 計算の累積値）
+    ssrc: Option<u32>,
+    received: u64,
+    expected: u64,
+    /// SSRC が切り替わる前までの受信数・期待数
+    prior_received: u64,
+    prior_expected: u64,
+    jitter_sum_ms: f64,
+    jitter_samples: u64,
+    max_jitter_ms: f64,
+    reordered: u64,
+    late_dropped: u64,
+    rtt_sum_ms: f64,
+    rtt_samples: u64,
+    updated_at: Option<Instant>,
+}
+
+impl CallQuality {
+    fn record_reception(&mut self, ssrc: u32, received: u32, expected: u32, jitter_ms: f64) {
+        if self.ssrc != Some(ssrc) {
+            self.prior_received += self.received;
+            self.prior_expected += self.expected;
+            self.ssrc = Some(ssrc);
+        }
+        self.received = received as u64;
+        self.expected = expected as u64;
+        self.jitter_sum_ms += jitter_ms;
+        self.jitter_samples += 1;
+        self.max_jitter_ms = self.max_jitter_ms.max(jitter_ms);
+    }
+}
+
+static REGISTRY: OnceLock<Mutex<HashMap<String, CallQuality>>> = OnceLock::new();
+
+fn with_call<F: FnOnce(&mut CallQuality)>(call_id: &str, update: F) {
+    let mut registry = REGISTRY
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let entry = registry.entry(call_id.to_string()).or_default();
+    update(entry);
+    entry.updated_at = Some(Instant::now());
+}
+
+/// RTP 受信ごとの累積値（SSRC 単位の受信数・期待数）と、その時点のジッタ推定値
+pub(crate) fn record_reception(
+    call_id: &str,
+    ssrc: u32,
+    received: u32,
+    expected: u32,
+    jitter_ms: f64,
+) {
+    with_call(call_id, |quality| {
+        quality.record_reception(ssrc, received, expected, jitter_ms)
+    });
+}
+
+/// ジッタバッファの累積カウンタ
+pub(crate) fn record_jitter_buffer(call_id: &str, reordered: u64, late_dropped: u64) {
+    with_call(call_id, |quality| {
+        quality.reordered = reordered;
+        quality.late_dropped = late_dropped;
+    });
+}
+
+pub(crate) fn record_rtt(call_id: &str, rtt: Duration) {
+    with_call(call_id, |quality| {
+        quality.rtt_sum_ms += rtt.as_secs_f64() * 1000.0;
+        quality.rtt_samples += 1;
+    });
+}
+
+/// 通話の集計を取り出して要約する（RTP を受けていなければ None）。
+/// `mos_alert_threshold` を下回れば `below_threshold` を立てる（0 で判定しない）
+pub fn take(call_id: &str, mos_alert_threshold: f32) -> Option<MediaQuality> {
+    let mut registry = REGISTRY.get()?.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    registry.retain(|id, quality| {
+        id == call_id
+            || quality
+                .updated_at
+                .is_some_and(|at| now.saturating_duration_since(at) < STALE_AFTER)
+    });
+    let quality = registry.remove(call_id)?;
+    summarize(&quality, mos_alert_threshold)
+}
+
+fn summarize(quality: &CallQuality, mos_alert_threshold: f32) -> Option<MediaQuality> {
+    let packets_received = quality.prior_received + quality.received;
+    if packets_received == 0 {
+        return None;
+    }
+    let packets_expected = (quality.prior_expected + quality.expected).max(packets_received);
+    let packets_lost = packets_expected - packets_received;
+    let avg_jitter_ms = if quality.jitter_samples == 0 {
+        0.0
+    } else {
+        quality.jitter_sum_ms / quality.jitter_samples as f64
+    };
+    let rtt_ms = (quality.rtt_samples > 0).then(|| quality.rtt_sum_ms / quality.rtt_samples as f64);
+    // 受信側のジッタバッファはジッタの 2 倍程度を吸収するものとして遅延に含める
+    let one_way_delay_ms =
+        rtt_ms.unwrap_or(0.0) / 2.0 + 2.0 * avg_jitter_ms + PACKETIZATION_DELAY_MS;
+    let mos = estimate_mos(
+        packets_lost as f64 / packets_expected as f64,
+        one_way_delay_ms,
+    ) as f32;
+    Some(MediaQuality {
+        packets_received,
+        packets_lost,
+        max_jitter_ms: quality.max_jitter_ms as f32,
+        avg_jitter_ms: avg_jitter_ms as f32,
+        reordered_packets: quality.reordered,
+        late_dropped_packets: quality.late_dropped,
+        rtt_ms: rtt_ms.map(|rtt| rtt as f32),
+        mos,
+        below_threshold: mos_alert_threshold > 0.0 && mos < mos_alert_threshold,
+    })
+}
+
+/// E-model（ITU-T G.107）の簡易計算による MOS。G.711 + PLC（Ie=0, Bpl=25.1）、ランダム損失を前提とする
+pub fn estimate_mos(loss_ratio: f64, one_way_delay_ms: f64) -> f64 {
+    const R0: f64 = 93.2;
+    const BPL: f64 = 25.1;
+    let ppl = (loss_ratio * 100.0).clamp(0.0, 100.0);
+    let ie_eff = 95.0 * ppl / (ppl + BPL);
+    let d = one_way_delay_ms.max(0.0);
+    let id = 0.024 * d + if d > 177.3 { 0.11 * (d - 177.3) } else { 0.0 };
+    let r = R0 - id - ie_eff;
+    if r <= 0.0 {
+        1.0
+    } else if r >= 100.0 {
+        4.5
+    } else {
+        1.0 + 0.035 * r + 7.0e-6 * r * (r - 60.0) * (100.0 - r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mos_degrades_with_loss_and_delay() {
+        let clean = estimate_mos(0.0, 20.0);
+        assert!((4.3..4.5).contains(&clean), "clean={clean}");
+
+        let lossy = estimate_mos(0.05, 20.0);
+        assert!((3.5..4.0).contains(&lossy), "lossy={lossy}");
+
+        let bad = estimate_mos(0.2, 20.0);
+        assert!(bad < 3.0, "bad={bad}");
+
+        let delayed = estimate_mos(0.0, 400.0);
+        assert!(delayed < 3.5, "delayed={delayed}");
+    }
+
+    #[test]
+    fn summary_accumulates_across_ssrc_change_and_flags_threshold() {
+        let mut quality = CallQuality::default();
+        quality.record_reception(1, 90, 100, 10.0);
+        quality.record_reception(2, 50, 60, 30.0);
+
+        let summary = summarize(&quality, 4.0).expect("packets received");
+        assert_eq!(summary.packets_received, 140);
+        assert_eq!(summary.packets_lost, 20);
+        assert_eq!(summary.max_jitter_ms, 30.0);
+        assert_eq!(summary.avg_jitter_ms, 20.0);
+        assert!(summary.rtt_ms.is_none());
+        assert!(summary.below_threshold);
+    }
+
+    #[test]
+    fn take_removes_call_and_ignores_calls_without_media() {
+        record_reception("quality-take", 7, 10, 10, 5.0);
+        record_rtt("quality-take", Duration::from_millis(80));
+
+        let summary = take("quality-take", 0.0).expect("recorded call");
+        assert_eq!(summary.packets_lost, 0);
+        assert_eq!(summary.rtt_ms, Some(80.0));
+        assert!(!summary.below_threshold);
+        assert!(take("quality-take", 0.0).is_none());
+    }
+}
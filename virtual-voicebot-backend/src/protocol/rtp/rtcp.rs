@@ -195,10 +195,51 @@ pub fn ntp_timestamp_now() -> u64 {
     (secs << 32) | frac
 }
 
+/// 相手の受信レポートの LSR/DLSR から往復遅延を求める（RFC 3550 6.4.1）。
+/// LSR が 0（こちらの SR をまだ受けていない）か、計算結果が不自然なら None
+pub fn round_trip_time(report: &RtcpReportBlock, now_ntp: u64) -> Option<std::time::Duration> {
+    /// これを超える RTT は時計の巻き戻りなどとみなして捨てる
+    const MAX_RTT_UNITS: u32 = 10 << 16;
+    if report.lsr == 0 {
+        return None;
+    }
+    let now_mid = ((now_ntp >> 16) & 0xFFFF_FFFF) as u32;
+    let rtt_units = now_mid.wrapping_sub(report.lsr).wrapping_sub(report.dlsr);
+    if rtt_units > MAX_RTT_UNITS {
+        return None;
+    }
+    // 単位は 1/65536 秒
+    Some(std::time::Duration::from_micros(
+        rtt_units as u64 * 1_000_000 / 65_536,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn round_trip_time_subtracts_lsr_and_dlsr() {
+        let block = |lsr, dlsr| RtcpReportBlock {
+            ssrc: 1,
+            fraction_lost: 0,
+            cumulative_lost: 0,
+            highest_seq: 0,
+            jitter: 0,
+            lsr,
+            dlsr,
+        };
+        // now = 100s, LSR = 99.5s, DLSR = 0.25s → RTT = 0.25s
+        let now_ntp = 100u64 << 32;
+        let lsr = (99u32 << 16) | 0x8000;
+        let rtt = round_trip_time(&block(lsr, 0x4000), now_ntp).expect("rtt");
+        assert_eq!(rtt.as_millis(), 250);
+
+        assert!(round_trip_time(&block(0, 0), now_ntp).is_none());
+        // DLSR が経過時間より大きい（時計ずれ）
+        assert!(round_trip_time(&block(lsr, 0x10000), now_ntp).is_none());
+    }
+
     #[test]
     fn parse_empty_rtcp_returns_empty() {
         let packets = parse_rtcp_packets(&[]);
@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{btree_map, BTreeMap, HashMap};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -11,12 +11,14 @@ use tokio::time::interval;
 use crate::protocol::rtp::codec::{codec_from_pt, decode_to_mulaw};
 use crate::protocol::rtp::dtmf::DtmfDetector;
 use crate::protocol::rtp::parser::parse_rtp_packet;
+use crate::protocol::rtp::quality;
 use crate::protocol::rtp::rtcp::{
-    build_rr, is_rtcp_packet, parse_rtcp_packets, RtcpEvent, RtcpEventTx, RtcpPacket,
-    RtcpReceiverReport, RtcpReportBlock,
+    build_rr, is_rtcp_packet, ntp_timestamp_now, parse_rtcp_packets, round_trip_time, RtcpEvent,
+    RtcpEventTx, RtcpPacket, RtcpReceiverReport, RtcpReportBlock,
 };
 use crate::shared::config::RtpConfig;
 use crate::shared::entities::CallId;
+use crate::shared::metrics;
 use crate::shared::ports::rtp_sink::RtpEvent;
 use crate::shared::ports::session_lookup::SessionLookup;
 
@@ -100,13 +102,27 @@ impl RtpReceiver {
             };
             for pkt in parse_rtcp_packets(&raw.data) {
                 info!("[rtcp recv] packet {:?}", pkt);
-                if let (Some(call_id), RtcpPacket::SenderReport(sr)) = (&call_id_opt, &pkt) {
-                    self.rtcp_reporter.update_sr(
-                        call_id.as_str(),
-                        sr.ssrc,
-                        sr.ntp_timestamp,
-                        raw.src,
-                    );
+                let Some(call_id) = &call_id_opt else {
+                    continue;
+                };
+                match &pkt {
+                    RtcpPacket::SenderReport(sr) => {
+                        self.rtcp_reporter.update_sr(
+                            call_id.as_str(),
+                            sr.ssrc,
+                            sr.ntp_timestamp,
+                            raw.src,
+                        );
+                    }
+                    RtcpPacket::ReceiverReport(rr) => {
+                        if let Some(rtt) = rr
+                            .report
+                            .as_ref()
+                            .and_then(|block| round_trip_time(block, ntp_timestamp_now()))
+                        {
+                            quality::record_rtt(call_id.as_str(), rtt);
+                        }
+                    }
                 }
             }
             if let Some(tx) = &self.rtcp_tx {
@@ -133,6 +149,7 @@ impl RtpReceiver {
             if let Some(sink) = sink_opt {
                 match parse_rtp_packet(&raw.data) {
                     Ok(pkt) => {
+                        metrics::RTP_PACKETS.inc(&["in"]);
                         self.rtcp_reporter.update_rtp(
                             call_id.as_str(),
                             pkt.ssrc,
@@ -236,7 +253,9 @@ impl RtpReceiver {
     async fn reorder(&self, call_id: &CallId, frame: RtpFrame) -> Vec<RtpFrame> {
         let mut map = self.jitter.lock().await;
         let buffer = map.entry(call_id.clone()).or_default();
-        buffer.push(frame, self.jitter_max_reorder)
+        let frames = buffer.push(frame, self.jitter_max_reorder);
+        quality::record_jitter_buffer(call_id.as_str(), buffer.reordered, buffer.late_dropped);
+        frames
     }
 }
 
@@ -252,6 +271,10 @@ struct RtpFrame {
 struct JitterBuffer {
     expected: Option<u16>,
     buffer: BTreeMap<u16, RtpFrame>,
+    /// 後続パケットより遅れて届いたパケット数（品質統計用）
+    reordered: u64,
+    /// 再生位置を過ぎて届いた・重複したため捨てたパケット数（品質統計用）
+    late_dropped: u64,
 }
 
 impl JitterBuffer {
@@ -268,6 +291,14 @@ impl JitterBuffer {
         };
 
         let diff = frame.seq.wrapping_sub(expected);
+        let furthest_buffered = self
+            .buffer
+            .keys()
+            .map(|seq| seq.wrapping_sub(expected))
+            .max();
+        if diff < 0x8000 && furthest_buffered.is_some_and(|furthest| diff < furthest) {
+            self.reordered += 1;
+        }
         if diff == 0 {
             out.push(frame);
             self.expected = Some(expected.wrapping_add(1));
@@ -277,10 +308,16 @@ impl JitterBuffer {
                 self.expected = Some(frame.seq.wrapping_add(1));
                 out.push(frame);
             } else {
-                self.buffer.entry(frame.seq).or_insert(frame);
+                match self.buffer.entry(frame.seq) {
+                    btree_map::Entry::Vacant(slot) => {
+                        slot.insert(frame);
+                    }
+                    btree_map::Entry::Occupied(_) => self.late_dropped += 1,
+                }
             }
         } else {
             // 古すぎる/重複は捨てる
+            self.late_dropped += 1;
         }
 
         if let Some(mut next) = self.expected {
@@ -384,6 +421,19 @@ struct RtcpRxState {
     last_sr_at: Option<Instant>,
 }
 
+impl RtcpRxState {
+    /// 拡張最大シーケンス番号から求めた受信期待数
+    fn expected(&self) -> u32 {
+        if self.received == 0 {
+            0
+        } else if self.max_seq >= self.base_seq {
+            self.max_seq - self.base_seq + 1
+        } else {
+            0
+        }
+    }
+}
+
 async fn run_rtcp_rr_loop(mut rx: mpsc::Receiver<RtcpReportUpdate>, rtcp_interval: Duration) {
     let mut states: HashMap<String, RtcpRxState> = HashMap::new();
     let local_ssrc = (std::time::SystemTime::now()
@@ -405,7 +455,7 @@ async fn run_rtcp_rr_loop(mut rx: mpsc::Receiver<RtcpReportUpdate>, rtcp_interva
             Some(update) = rx.recv() => {
                 match update {
                     RtcpReportUpdate::RtpPacket { call_id, ssrc, seq, rtp_ts, peer, arrival } => {
-                        let state = states.entry(call_id).or_insert_with(|| RtcpRxState {
+                        let state = states.entry(call_id.clone()).or_insert_with(|| RtcpRxState {
                             ssrc,
                             base_seq: seq as u32,
                             max_seq: seq as u32,
@@ -441,6 +491,13 @@ async fn run_rtcp_rr_loop(mut rx: mpsc::Receiver<RtcpReportUpdate>, rtcp_interva
                         state.received = state.received.saturating_add(1);
                         state.max_seq = extend_highest_seq(state.max_seq, seq);
                         update_jitter(state, arrival, rtp_ts);
+                        quality::record_reception(
+                            &call_id,
+                            state.ssrc,
+                            state.received,
+                            state.expected(),
+                            jitter_seconds(state.jitter) * 1000.0,
+                        );
                     }
                     RtcpReportUpdate::SenderReport { call_id, ssrc, ntp_timestamp, peer, received_at } => {
                         let state = states.entry(call_id).or_insert_with(|| RtcpRxState {
@@ -466,13 +523,7 @@ async fn run_rtcp_rr_loop(mut rx: mpsc::Receiver<RtcpReportUpdate>, rtcp_interva
             }
             _ = tick.tick() => {
                 for state in states.values_mut() {
-                    let expected = if state.received == 0 {
-                        0
-                    } else if state.max_seq >= state.base_seq {
-                        state.max_seq - state.base_seq + 1
-                    } else {
-                        0
-                    };
+                    let expected = state.expected();
                     let expected_interval = expected.saturating_sub(state.expected_prior);
                     let received_interval = state.received.saturating_sub(state.received_prior);
                     let lost_interval = expected_interval as i64 - received_interval as i64;
@@ -482,6 +533,10 @@ async fn run_rtcp_rr_loop(mut rx: mpsc::Receiver<RtcpReportUpdate>, rtcp_interva
                         let lost = lost_interval.max(0) as u64;
                         ((lost << 8) / expected_interval as u64) as u8
                     };
+                    if received_interval > 0 {
+                        metrics::RTP_PACKETS_LOST.add(&[], lost_interval.max(0) as u64);
+                        metrics::RTP_JITTER.observe(&[], jitter_seconds(state.jitter));
+                    }
                     let cumulative_lost = clamp_loss(expected, state.received);
                     let (lsr, dlsr) = match (state.last_sr_mid_ntp, state.last_sr_at) {
                         (Some(lsr), Some(at)) => (lsr, dlsr_from(at)),
@@ -525,8 +580,10 @@ fn extend_highest_seq(current: u32, seq: u16) -> u32 {
     }
 }
 
+/// ジッタ計算に使う RTP クロック（PCMU/PCMA の 8kHz 固定）
+const RTP_CLOCK_RATE: u32 = 8_000;
+
 fn update_jitter(state: &mut RtcpRxState, arrival: Instant, rtp_ts: u32) {
-    const RTP_CLOCK_RATE: u32 = 8_000;
     if let (Some(prev_arrival), Some(prev_rtp_ts)) = (state.last_arrival, state.last_rtp_ts) {
         if arrival >= prev_arrival {
             let arrival_delta = arrival.duration_since(prev_arrival);
@@ -542,6 +599,10 @@ fn update_jitter(state: &mut RtcpRxState, arrival: Instant, rtp_ts: u32) {
     state.last_rtp_ts = Some(rtp_ts);
 }
 
+fn jitter_seconds(jitter: u32) -> f64 {
+    jitter as f64 / RTP_CLOCK_RATE as f64
+}
+
 fn duration_to_rtp_units(duration: Duration, clock_rate: u32) -> u32 {
     let secs = duration.as_secs().saturating_mul(clock_rate as u64);
     let frac = duration.subsec_nanos() as u64 * clock_rate as u64 / 1_000_000_000u64;
@@ -569,3 +630,32 @@ fn clamp_loss(expected: u32, received: u32) -> u32 {
         lost as u32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(seq: u16) -> RtpFrame {
+        RtpFrame {
+            seq,
+            ts: seq as u32 * 160,
+            pt: 0,
+            payload: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn jitter_buffer_counts_reordered_and_late_frames() {
+        let mut buffer = JitterBuffer::default();
+        let mut played = Vec::new();
+        for seq in [1, 3, 2, 4, 2, 6, 6] {
+            played.extend(buffer.push(frame(seq), 5).into_iter().map(|f| f.seq));
+        }
+
+        assert_eq!(played, vec![1, 2, 3, 4]);
+        // 2 は 3 より後に届いた
+        assert_eq!(buffer.reordered, 1);
+        // 再生済みの 2 と、バッファ済みの 6 の重複
+        assert_eq!(buffer.late_dropped, 2);
+    }
+}
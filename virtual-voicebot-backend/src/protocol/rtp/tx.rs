@@ -9,6 +9,7 @@ use crate::protocol::rtp::rtcp::{build_sr, ntp_timestamp_now, RtcpSenderReport};
 use crate::protocol::rtp::stream_manager::StreamManager;
 use crate::protocol::rtp::{build_rtp_packet, RtpPacket};
 use crate::shared::config::RtpConfig;
+use crate::shared::metrics;
 
 #[derive(Debug)]
 pub enum RtpTxCommand {
@@ -159,7 +160,9 @@ async fn run_tx(
                                 })
                                 .await;
                             if let Some((dst, bytes)) = sent.flatten() {
-                                let _ = s.send_to(&bytes, dst).await.ok();
+                                if s.send_to(&bytes, dst).await.is_ok() {
+                                    metrics::RTP_PACKETS.inc(&["out"]);
+                                }
                             } else {
                                 log::warn!("[rtp tx] send requested but stream key not found");
                             }
@@ -6,6 +6,7 @@ use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 use log::{error, info, warn};
 use rand::Rng;
 use tokio::net::UdpSocket;
@@ -25,7 +26,10 @@ use crate::protocol::sip::{
     parse_cseq_header, parse_name_addr, parse_offer_sdp, parse_uri, SipRequestBuilder,
 };
 use crate::protocol::transport::TransportPeer;
-use crate::shared::config::{RegistrarConfig, RegistrarTransport, SessionRuntimeConfig};
+use crate::shared::config::{
+    HuntGroup, HuntStrategy, RegistrarConfig, RegistrarTransport, SessionRuntimeConfig,
+};
+use crate::shared::ports::call_log_port::TransferAttempt;
 use crate::shared::utils::mask_pii;
 
 const RTP_BUFFER_SIZE: usize = 2048;
@@ -33,6 +37,8 @@ const DEFAULT_SIP_PORT: u16 = 5060;
 const CONTROL_EVENT_RETRY_ATTEMPTS: usize = 3;
 const CONTROL_EVENT_RETRY_DELAY: Duration = Duration::from_millis(10);
 const RTP_DROP_WARN_INTERVAL: Duration = Duration::from_secs(5);
+/// INVITE クライアントトランザクションのタイムアウト（Timer B = 64*T1）
+const INVITE_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(32);
 
 #[derive(Debug)]
 pub struct BLeg {
@@ -89,22 +95,22 @@ impl BLeg {
 pub fn spawn_transfer(
     a_call_id: CallId,
     caller_uri: String,
+    group: HuntGroup,
     control_tx: mpsc::Sender<SessionControlIn>,
     media_tx: mpsc::Sender<SessionMediaIn>,
     runtime_cfg: Arc<SessionRuntimeConfig>,
 ) -> tokio::sync::oneshot::Sender<()> {
     let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel();
     tokio::spawn(async move {
-        match run_transfer(
-            a_call_id.clone(),
+        let hunt = HuntContext {
+            a_call_id: a_call_id.clone(),
             caller_uri,
-            control_tx.clone(),
-            media_tx.clone(),
-            cancel_rx,
+            group,
+            control_tx: control_tx.clone(),
+            media_tx,
             runtime_cfg,
-        )
-        .await
-        {
+        };
+        match run_hunt(hunt, cancel_rx).await {
             Ok(Some(b_leg)) => {
                 let _ = control_tx.try_send(SessionControlIn::B2buaEstablished { b_leg });
             }
@@ -129,6 +135,236 @@ pub fn spawn_transfer(
     cancel_tx
 }
 
+/// 転送先グループ 1 回分の呼び出しに必要な情報（メンバーごとのレッグで共有する）
+#[derive(Clone)]
+struct HuntContext {
+    a_call_id: CallId,
+    caller_uri: String,
+    group: HuntGroup,
+    control_tx: mpsc::Sender<SessionControlIn>,
+    media_tx: mpsc::Sender<SessionMediaIn>,
+    runtime_cfg: Arc<SessionRuntimeConfig>,
+}
+
+type HuntLegResult = (String, DateTime<Utc>, Result<Option<BLeg>>);
+
+impl HuntContext {
+    async fn ring(
+        &self,
+        target_uri: String,
+        cancel_rx: tokio::sync::oneshot::Receiver<()>,
+    ) -> Result<Option<BLeg>> {
+        run_transfer(self, target_uri, cancel_rx).await
+    }
+
+    async fn report(
+        &self,
+        member: &str,
+        started_at: DateTime<Utc>,
+        (outcome, sip_status): (&str, Option<u16>),
+    ) {
+        info!(
+            "[b2bua {}] hunt group={} member={} outcome={}",
+            self.a_call_id,
+            self.group.name,
+            mask_pii(member),
+            outcome
+        );
+        let attempt = TransferAttempt {
+            hunt_group: self.group.name.clone(),
+            member: member.to_string(),
+            started_at,
+            ended_at: Utc::now(),
+            outcome: outcome.to_string(),
+            sip_status,
+        };
+        send_control_event_with_retry(
+            &self.control_tx,
+            self.a_call_id.as_str(),
+            "TransferAttempt",
+            || SessionControlIn::TransferAttempt {
+                attempt: attempt.clone(),
+            },
+        )
+        .await;
+    }
+
+    fn exhausted(&self, last_err: Option<anyhow::Error>) -> anyhow::Error {
+        match last_err {
+            Some(err) => anyhow!("hunt group {} exhausted: {}", self.group.name, err),
+            None => anyhow!("hunt group {} has no members", self.group.name),
+        }
+    }
+}
+
+async fn run_hunt(
+    hunt: HuntContext,
+    cancel_rx: tokio::sync::oneshot::Receiver<()>,
+) -> Result<Option<BLeg>> {
+    let members = hunt.group.ring_order();
+    info!(
+        "[b2bua {}] hunting group={} strategy={:?} members={}",
+        hunt.a_call_id,
+        hunt.group.name,
+        hunt.group.strategy,
+        members.len()
+    );
+    match hunt.group.strategy {
+        HuntStrategy::Simultaneous => run_parallel_hunt(hunt, members, cancel_rx).await,
+        HuntStrategy::Sequential | HuntStrategy::RoundRobin => {
+            run_sequential_hunt(hunt, members, cancel_rx).await
+        }
+    }
+}
+
+async fn run_sequential_hunt(
+    hunt: HuntContext,
+    members: Vec<String>,
+    mut cancel_rx: tokio::sync::oneshot::Receiver<()>,
+) -> Result<Option<BLeg>> {
+    let mut last_err = None;
+    for member in members {
+        let (leg_cancel_tx, leg_cancel_rx) = tokio::sync::oneshot::channel();
+        let started_at = Utc::now();
+        let leg = hunt.ring(member.clone(), leg_cancel_rx);
+        tokio::pin!(leg);
+        let mut cancelled = false;
+        let result = tokio::select! {
+            result = &mut leg => result,
+            _ = &mut cancel_rx => {
+                cancelled = true;
+                let _ = leg_cancel_tx.send(());
+                leg.await
+            }
+        };
+        hunt.report(&member, started_at, transfer_attempt_outcome(&result))
+            .await;
+        match result {
+            Ok(Some(b_leg)) => return Ok(Some(b_leg)),
+            Ok(None) => return Ok(None),
+            Err(_) if cancelled => return Ok(None),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(hunt.exhausted(last_err))
+}
+
+async fn run_parallel_hunt(
+    hunt: HuntContext,
+    members: Vec<String>,
+    cancel_rx: tokio::sync::oneshot::Receiver<()>,
+) -> Result<Option<BLeg>> {
+    if members.is_empty() {
+        return Err(hunt.exhausted(None));
+    }
+    let (result_tx, mut result_rx) = mpsc::channel::<HuntLegResult>(members.len());
+    let mut leg_cancels = Vec::with_capacity(members.len());
+    for member in members {
+        let (leg_cancel_tx, leg_cancel_rx) = tokio::sync::oneshot::channel();
+        leg_cancels.push(leg_cancel_tx);
+        let hunt = hunt.clone();
+        let result_tx = result_tx.clone();
+        tokio::spawn(async move {
+            let started_at = Utc::now();
+            let result = hunt.ring(member.clone(), leg_cancel_rx).await;
+            let _ = result_tx.send((member, started_at, result)).await;
+        });
+    }
+    drop(result_tx);
+
+    let mut cancel_fut: Pin<Box<dyn Future<Output = ()> + Send>> = Box::pin(async move {
+        let _ = cancel_rx.await;
+    });
+    let mut cancelled = false;
+    let mut last_err = None;
+    loop {
+        tokio::select! {
+            _ = &mut cancel_fut => {
+                cancelled = true;
+                for leg_cancel in leg_cancels.drain(..) {
+                    let _ = leg_cancel.send(());
+                }
+                cancel_fut = Box::pin(std::future::pending());
+            }
+            maybe_result = result_rx.recv() => {
+                let Some((member, started_at, result)) = maybe_result else {
+                    break;
+                };
+                match result {
+                    Ok(Some(b_leg)) if !cancelled => {
+                        hunt.report(&member, started_at, ("answered", Some(200))).await;
+                        // 最初に応答したメンバーにつなぎ、残りは CANCEL（行き違いで応答したら BYE）
+                        for leg_cancel in leg_cancels.drain(..) {
+                            let _ = leg_cancel.send(());
+                        }
+                        tokio::spawn(release_losing_legs(hunt.clone(), result_rx));
+                        return Ok(Some(b_leg));
+                    }
+                    Ok(Some(b_leg)) => {
+                        hunt.report(&member, started_at, ("cancelled", Some(200))).await;
+                        release_b_leg(b_leg).await;
+                    }
+                    other => {
+                        hunt.report(&member, started_at, transfer_attempt_outcome(&other))
+                            .await;
+                        if let Err(err) = other {
+                            last_err = Some(err);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if cancelled {
+        return Ok(None);
+    }
+    Err(hunt.exhausted(last_err))
+}
+
+async fn release_losing_legs(hunt: HuntContext, mut result_rx: mpsc::Receiver<HuntLegResult>) {
+    while let Some((member, started_at, result)) = result_rx.recv().await {
+        match result {
+            Ok(Some(b_leg)) => {
+                hunt.report(&member, started_at, ("cancelled", Some(200)))
+                    .await;
+                release_b_leg(b_leg).await;
+            }
+            other => {
+                hunt.report(&member, started_at, transfer_attempt_outcome(&other))
+                    .await;
+            }
+        }
+    }
+}
+
+async fn release_b_leg(mut b_leg: BLeg) {
+    if let Err(err) = b_leg.send_bye().await {
+        warn!("[b2bua {}] failed to release leg: {:?}", b_leg.call_id, err);
+    }
+    b_leg.shutdown();
+}
+
+/// レッグの結果を転送イベントの outcome / SIP ステータスに変換する
+fn transfer_attempt_outcome(result: &Result<Option<BLeg>>) -> (&'static str, Option<u16>) {
+    match result {
+        Ok(Some(_)) => ("answered", Some(200)),
+        Ok(None) => ("cancelled", None),
+        Err(err) => match err.downcast_ref::<TransferLegError>() {
+            Some(TransferLegError::NoAnswer(_)) => ("no_answer", None),
+            Some(TransferLegError::Status(status)) => {
+                let outcome = match status {
+                    486 | 600 => "busy",
+                    408 | 480 => "no_answer",
+                    487 => "cancelled",
+                    _ => "rejected",
+                };
+                (outcome, Some(*status))
+            }
+            None => ("failed", None),
+        },
+    }
+}
+
 pub fn spawn_plain_outbound(
     a_call_id: CallId,
     caller_uri: String,
@@ -294,14 +530,19 @@ fn build_outbound_from_header(caller_user: &str, registrar_domain: &str, tag: &s
 }
 
 async fn run_transfer(
-    a_call_id: CallId,
-    caller_uri: String,
-    control_tx: mpsc::Sender<SessionControlIn>,
-    media_tx: mpsc::Sender<SessionMediaIn>,
+    hunt: &HuntContext,
+    target_uri: String,
     cancel_rx: tokio::sync::oneshot::Receiver<()>,
-    runtime_cfg: Arc<SessionRuntimeConfig>,
 ) -> Result<Option<BLeg>> {
-    let target_uri = runtime_cfg.transfer_target_uri.clone();
+    let HuntContext {
+        a_call_id,
+        caller_uri,
+        control_tx,
+        media_tx,
+        runtime_cfg,
+        ..
+    } = hunt;
+    let timeout = hunt.group.member_ring_timeout;
     let target_addr = resolve_target_addr(&target_uri).await?;
 
     let sip_port = runtime_cfg.sip_port;
@@ -341,40 +582,27 @@ async fn run_transfer(
     log_invite("transfer", target_addr, &invite);
     send_b2bua_payload(TransportPeer::Udp(target_addr), invite.to_bytes())?;
 
-    let timeout = runtime_cfg.transfer_timeout;
+    let cancel = SipRequestBuilder::new(SipMethod::Cancel, target_uri.clone())
+        .header("Via", via.clone())
+        .header("Max-Forwards", "70")
+        .header("From", from_header.clone())
+        .header("To", to_header.clone())
+        .header("Call-ID", b_call_id.clone())
+        .header("CSeq", format!("{cseq} CANCEL"))
+        .build();
     let timeout_sleep = sleep(timeout);
     tokio::pin!(timeout_sleep);
     let mut provisional_received = false;
-    let mut cancel_requested = false;
-    let mut cancel_sent = false;
     let mut cancel_fut: Pin<Box<dyn Future<Output = ()> + Send>> = Box::pin(async move {
         let _ = cancel_rx.await;
     });
 
     loop {
-        tokio::select! {
-            _ = &mut cancel_fut => {
-                cancel_requested = true;
-                if provisional_received && !cancel_sent {
-                    let cancel = SipRequestBuilder::new(SipMethod::Cancel, target_uri.clone())
-                        .header("Via", via.clone())
-                        .header("Max-Forwards", "70")
-                        .header("From", from_header.clone())
-                        .header("To", to_header.clone())
-                        .header("Call-ID", b_call_id.clone())
-                        .header("CSeq", format!("{cseq} CANCEL"))
-                        .build();
-                    log_cancel("transfer", target_addr, &cancel);
-                    let _ = send_b2bua_payload(TransportPeer::Udp(target_addr), cancel.to_bytes());
-                    cancel_sent = true;
-                } else if !provisional_received {
-                    info!("[b2bua {}] cancel pending (no provisional)", a_call_id);
-                }
-                cancel_fut = Box::pin(std::future::pending());
-            }
-            _ = &mut timeout_sleep => {
-                return Err(anyhow!("transfer timeout after {}s", timeout.as_secs()));
-            }
+        // 打ち切り・呼び出しタイムアウトではメンバーの結果をすぐ返し、
+        // トランザクションの後始末（CANCEL・行き違いの 2xx の解放）は別タスクに任せる
+        let abandon_result = tokio::select! {
+            _ = &mut cancel_fut => Ok(None),
+            _ = &mut timeout_sleep => Err(TransferLegError::NoAnswer(timeout).into()),
             maybe_msg = sip_rx.recv() => {
                 let Some(msg) = maybe_msg else {
                     return Err(anyhow!("transfer sip channel closed"));
@@ -391,20 +619,6 @@ async fn run_transfer(
                 }
                 if resp.status_code < 200 {
                     provisional_received = true;
-                    if cancel_requested && !cancel_sent {
-                        let cancel = SipRequestBuilder::new(SipMethod::Cancel, target_uri.clone())
-                            .header("Via", via.clone())
-                            .header("Max-Forwards", "70")
-                            .header("From", from_header.clone())
-                            .header("To", to_header.clone())
-                            .header("Call-ID", b_call_id.clone())
-                            .header("CSeq", format!("{cseq} CANCEL"))
-                            .build();
-                        log_cancel("transfer", target_addr, &cancel);
-                        let _ =
-                            send_b2bua_payload(TransportPeer::Udp(target_addr), cancel.to_bytes());
-                        cancel_sent = true;
-                    }
                     info!(
                         "[b2bua {}] provisional response {} from {:?}",
                         a_call_id, resp.status_code, peer
@@ -422,12 +636,8 @@ async fn run_transfer(
                         b_call_id.as_str(),
                         cseq,
                     );
-                    if cancel_requested {
-                        return Ok(None);
-                    }
-                    return Err(anyhow!("transfer failed status {}", resp.status_code));
+                    return Err(TransferLegError::Status(resp.status_code).into());
                 }
-
                 let to_header = header_value(&resp.headers, "To")
                     .ok_or_else(|| anyhow!("missing To header"))?
                     .to_string();
@@ -460,20 +670,6 @@ async fn run_transfer(
                     route_set.as_slice(),
                 )?;
 
-                if cancel_requested {
-                    let bye_cseq = cseq.saturating_add(1).max(2);
-                    let bye = SipRequestBuilder::new(SipMethod::Bye, remote_uri.clone())
-                        .header("Via", build_via(via_host.as_str(), sip_port))
-                        .header("Max-Forwards", "70")
-                        .header("From", from_header.clone())
-                        .header("To", to_header.clone())
-                        .header("Call-ID", b_call_id.clone())
-                        .header("CSeq", format!("{bye_cseq} BYE"))
-                        .build();
-                    let _ = send_b2bua_payload(TransportPeer::Udp(sip_peer), bye.to_bytes());
-                    return Ok(None);
-                }
-
                 let ack_ctx = InviteAckContext {
                     request_uri: remote_uri.clone(),
                     from_header: from_header.clone(),
@@ -522,10 +718,186 @@ async fn run_transfer(
                 };
                 return Ok(Some(b_leg));
             }
+        };
+        let cancel_sent = provisional_received;
+        if cancel_sent {
+            log_cancel("transfer", target_addr, &cancel);
+            let _ = send_b2bua_payload(TransportPeer::Udp(target_addr), cancel.to_bytes());
+        } else {
+            info!("[b2bua {}] cancel pending (no provisional)", a_call_id);
         }
+        tokio::spawn(drain_abandoned_invite(AbandonedInvite {
+            a_call_id: a_call_id.clone(),
+            b_call_id,
+            target_uri,
+            target_addr,
+            via,
+            via_host,
+            via_port: sip_port,
+            from_header,
+            cseq,
+            cancel,
+            cancel_sent,
+            sip_rx,
+            _b2bua_reg: b2bua_reg,
+        }));
+        return abandon_result;
+    }
+}
+
+/// 諦めたメンバーの INVITE トランザクション（最終応答が届くまで後始末を続ける）
+struct AbandonedInvite {
+    a_call_id: CallId,
+    b_call_id: String,
+    target_uri: String,
+    target_addr: SocketAddr,
+    via: String,
+    via_host: String,
+    via_port: u16,
+    from_header: String,
+    cseq: u32,
+    cancel: SipRequest,
+    cancel_sent: bool,
+    sip_rx: mpsc::Receiver<B2buaSipMessage>,
+    _b2bua_reg: B2buaRegistration,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AbandonedInviteAction {
+    Wait,
+    /// 仮応答が届いたので保留していた CANCEL を送る
+    SendCancel,
+    /// 最終応答（非 2xx）に ACK して終える
+    AckFinal,
+    /// 行き違いで応答したレッグに ACK して BYE で切る
+    ReleaseAnswer,
+}
+
+fn abandoned_invite_action(status_code: u16, cancel_sent: bool) -> AbandonedInviteAction {
+    match status_code {
+        100..=199 if cancel_sent => AbandonedInviteAction::Wait,
+        100..=199 => AbandonedInviteAction::SendCancel,
+        200..=299 => AbandonedInviteAction::ReleaseAnswer,
+        _ => AbandonedInviteAction::AckFinal,
     }
 }
 
+async fn drain_abandoned_invite(mut invite: AbandonedInvite) {
+    let deadline = tokio::time::Instant::now() + INVITE_TRANSACTION_TIMEOUT;
+    loop {
+        let msg = match tokio::time::timeout_at(deadline, invite.sip_rx.recv()).await {
+            Ok(Some(msg)) => msg,
+            Ok(None) => return,
+            Err(_) => {
+                warn!(
+                    "[b2bua {}] no final response for abandoned leg {}",
+                    invite.a_call_id, invite.b_call_id
+                );
+                return;
+            }
+        };
+        let B2buaSipMessage { peer, message } = msg;
+        let SipMessage::Response(resp) = message else {
+            continue;
+        };
+        if !response_matches_call_id(&resp, &invite.b_call_id) || is_cancel_response(&resp) {
+            continue;
+        }
+        match abandoned_invite_action(resp.status_code, invite.cancel_sent) {
+            AbandonedInviteAction::Wait => {}
+            AbandonedInviteAction::SendCancel => {
+                log_cancel("transfer", invite.target_addr, &invite.cancel);
+                let _ = send_b2bua_payload(
+                    TransportPeer::Udp(invite.target_addr),
+                    invite.cancel.to_bytes(),
+                );
+                invite.cancel_sent = true;
+            }
+            AbandonedInviteAction::AckFinal => {
+                let ack_to = header_value(&resp.headers, "To").unwrap_or("");
+                send_non2xx_ack(
+                    peer,
+                    invite.target_uri.as_str(),
+                    invite.via.as_str(),
+                    invite.from_header.as_str(),
+                    ack_to,
+                    invite.b_call_id.as_str(),
+                    invite.cseq,
+                );
+                return;
+            }
+            AbandonedInviteAction::ReleaseAnswer => {
+                info!(
+                    "[b2bua {}] abandoned leg {} answered late, releasing",
+                    invite.a_call_id, invite.b_call_id
+                );
+                if let Err(err) = release_late_answer(&invite, &resp).await {
+                    warn!(
+                        "[b2bua {}] failed to release late answer on {}: {}",
+                        invite.a_call_id, invite.b_call_id, err
+                    );
+                }
+                return;
+            }
+        }
+    }
+}
+
+async fn release_late_answer(invite: &AbandonedInvite, resp: &SipResponse) -> Result<()> {
+    let to_header =
+        header_value(&resp.headers, "To").ok_or_else(|| anyhow!("missing To header"))?;
+    let route_set = collect_record_route_values(&resp.headers);
+    let remote_uri = header_value(&resp.headers, "Contact")
+        .map(extract_contact_uri)
+        .unwrap_or(invite.target_uri.as_str())
+        .to_string();
+    let sip_peer = resolve_target_addr(&remote_uri)
+        .await
+        .unwrap_or(invite.target_addr);
+    send_invite_ack(
+        TransportPeer::Udp(sip_peer),
+        remote_uri.as_str(),
+        invite.from_header.as_str(),
+        to_header,
+        invite.b_call_id.as_str(),
+        invite.cseq,
+        invite.via_host.as_str(),
+        invite.via_port,
+        route_set.as_slice(),
+    )?;
+    let bye_cseq = invite.cseq.saturating_add(1).max(2);
+    let bye = SipRequestBuilder::new(SipMethod::Bye, remote_uri)
+        .header("Via", build_via(invite.via_host.as_str(), invite.via_port))
+        .header("Max-Forwards", "70")
+        .header("From", invite.from_header.clone())
+        .header("To", to_header.to_string())
+        .header("Call-ID", invite.b_call_id.clone())
+        .header("CSeq", format!("{bye_cseq} BYE"));
+    let bye = route_set
+        .iter()
+        .fold(bye, |builder, route| builder.header("Route", route.clone()))
+        .build();
+    send_b2bua_payload(TransportPeer::Udp(sip_peer), bye.to_bytes())
+}
+
+/// 転送先メンバー 1 件分の呼び出し失敗
+#[derive(Debug)]
+enum TransferLegError {
+    NoAnswer(Duration),
+    Status(u16),
+}
+
+impl std::fmt::Display for TransferLegError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoAnswer(timeout) => write!(f, "transfer timeout after {}s", timeout.as_secs()),
+            Self::Status(status) => write!(f, "transfer failed status {}", status),
+        }
+    }
+}
+
+impl std::error::Error for TransferLegError {}
+
 #[derive(Debug)]
 struct OutboundError {
     status: u16,
@@ -1630,6 +2002,61 @@ mod tests {
     use super::*;
     use tokio::time::{timeout, Duration};
 
+    #[test]
+    fn abandoned_invite_cancels_on_first_provisional_and_releases_late_answer() {
+        // タイムアウト時に仮応答がなかったレッグは、届いた仮応答で CANCEL する
+        assert_eq!(
+            abandoned_invite_action(180, false),
+            AbandonedInviteAction::SendCancel
+        );
+        assert_eq!(
+            abandoned_invite_action(183, true),
+            AbandonedInviteAction::Wait
+        );
+        // CANCEL と行き違いの 2xx は ACK して BYE する
+        assert_eq!(
+            abandoned_invite_action(200, true),
+            AbandonedInviteAction::ReleaseAnswer
+        );
+        assert_eq!(
+            abandoned_invite_action(200, false),
+            AbandonedInviteAction::ReleaseAnswer
+        );
+        assert_eq!(
+            abandoned_invite_action(487, true),
+            AbandonedInviteAction::AckFinal
+        );
+        assert_eq!(
+            abandoned_invite_action(486, false),
+            AbandonedInviteAction::AckFinal
+        );
+    }
+
+    #[test]
+    fn transfer_attempt_outcome_classifies_leg_results() {
+        let no_answer: Result<Option<BLeg>> =
+            Err(TransferLegError::NoAnswer(Duration::from_secs(15)).into());
+        assert_eq!(transfer_attempt_outcome(&no_answer), ("no_answer", None));
+        assert_eq!(
+            TransferLegError::NoAnswer(Duration::from_secs(15)).to_string(),
+            "transfer timeout after 15s"
+        );
+
+        let busy: Result<Option<BLeg>> = Err(TransferLegError::Status(486).into());
+        assert_eq!(transfer_attempt_outcome(&busy), ("busy", Some(486)));
+        let unavailable: Result<Option<BLeg>> = Err(TransferLegError::Status(480).into());
+        assert_eq!(
+            transfer_attempt_outcome(&unavailable),
+            ("no_answer", Some(480))
+        );
+        let declined: Result<Option<BLeg>> = Err(TransferLegError::Status(603).into());
+        assert_eq!(transfer_attempt_outcome(&declined), ("rejected", Some(603)));
+
+        let dns: Result<Option<BLeg>> = Err(anyhow!("failed to resolve target"));
+        assert_eq!(transfer_attempt_outcome(&dns), ("failed", None));
+        assert_eq!(transfer_attempt_outcome(&Ok(None)), ("cancelled", None));
+    }
+
     #[tokio::test]
     async fn send_control_event_with_retry_delivers_when_channel_is_released() {
         let (tx, mut rx) = mpsc::channel(1);
@@ -7,7 +7,12 @@ use serde::Deserialize;
 use tokio::time::{Duration, Instant};
 use uuid::Uuid;
 
+use super::services::ivr_input_service::{
+    finish_collected_digits, push_collected_digit, DigitStep, IvrInputMode,
+};
 use super::services::ivr_service::{ivr_action_for_digit, ivr_state_after_action, IvrAction};
+use super::services::queue_service::QueueKeyAction;
+use super::services::voicemail_service::is_voicemail_access_number;
 use super::SessionCoordinator;
 use crate::protocol::rtp::codec::mulaw_to_linear16;
 use crate::protocol::session::b2bua;
@@ -16,7 +21,10 @@ use crate::protocol::session::types::{
     SessionTimerInfo,
 };
 use crate::service::routing::{ActionConfig, ActionExecutor, RuleEvaluator};
+use crate::shared::config::{self, HuntNoAnswerAction};
 use crate::shared::ports::app::{AppEvent, EndReason, RtpAudioChunk};
+use crate::shared::ports::call_event_port::CallEvent;
+use crate::shared::ports::live_call::LiveCallError;
 
 #[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -29,6 +37,9 @@ struct IvrDestinationMetadata {
     recording_enabled: Option<bool>,
     #[serde(default)]
     include_announcement: Option<bool>,
+    /// VR（転送）時に呼び出す転送先グループ名
+    #[serde(default)]
+    hunt_group: Option<String>,
 }
 
 impl SessionCoordinator {
@@ -42,6 +53,7 @@ impl SessionCoordinator {
             (
                 SessState::Idle,
                 SessionControlIn::SipInvite {
+                    identity,
                     offer,
                     session_timer,
                     ..
@@ -61,33 +73,49 @@ impl SessionCoordinator {
                 self.reset_action_modes();
                 self.reset_call_log_tracking();
                 self.stop_ring_delay();
+                self.caller_identity = identity;
 
-                let caller_id =
-                    sip_handler::extract_user_from_to(self.from_uri.as_str()).unwrap_or_default();
+                let called_number = sip_handler::extract_user_from_to(self.to_uri.as_str());
                 let call_id_str = self.call_id.to_string();
-                let evaluator = RuleEvaluator::new(self.routing_port.clone());
-                match evaluator.evaluate(&caller_id, &call_id_str).await {
-                    Ok(action) => {
-                        info!(
-                            "[SessionCoordinator] call_id={} evaluated action_code={}",
-                            self.call_id, action.action_code
-                        );
-                        let executor = ActionExecutor::new();
-                        if let Err(err) = executor.execute(&action, &call_id_str, self).await {
+                let evaluator = RuleEvaluator::new(self.routing_port.clone())
+                    .with_schedule_port(self.schedule_port.clone());
+                if is_voicemail_access_number(
+                    called_number.as_deref(),
+                    config::voicemail_config().access_number.as_deref(),
+                ) {
+                    self.enter_voicemail_retrieval();
+                } else {
+                    match evaluator
+                        .evaluate(
+                            &self.caller_identity,
+                            called_number.as_deref(),
+                            &call_id_str,
+                        )
+                        .await
+                    {
+                        Ok(action) => {
+                            self.matched_schedule_id = action.schedule_id;
+                            info!(
+                                "[SessionCoordinator] call_id={} evaluated action_code={}",
+                                self.call_id, action.action_code
+                            );
+                            let executor = ActionExecutor::new();
+                            if let Err(err) = executor.execute(&action, &call_id_str, self).await {
+                                error!(
+                                    "[SessionCoordinator] call_id={} action execution failed: {}",
+                                    self.call_id, err
+                                );
+                                self.set_outbound_mode(false);
+                            }
+                        }
+                        Err(err) => {
                             error!(
-                                "[SessionCoordinator] call_id={} action execution failed: {}",
+                                "[SessionCoordinator] call_id={} rule evaluation failed: {}",
                                 self.call_id, err
                             );
                             self.set_outbound_mode(false);
                         }
                     }
-                    Err(err) => {
-                        error!(
-                            "[SessionCoordinator] call_id={} rule evaluation failed: {}",
-                            self.call_id, err
-                        );
-                        self.set_outbound_mode(false);
-                    }
                 }
 
                 if self.invite_rejected {
@@ -181,6 +209,14 @@ impl SessionCoordinator {
                             );
                         }
                         let from = sip_handler::extract_notify_from(self.from_uri.as_str());
+                        self.publish_call_event(CallEvent::Ringing {
+                            from: if self.caller_identity.privacy {
+                                "anonymous".to_string()
+                            } else {
+                                from.clone()
+                            },
+                            to: self.to_uri.clone(),
+                        });
                         let _ = self
                             .app_tx
                             .send(AppEvent::CallRinging {
@@ -243,6 +279,7 @@ impl SessionCoordinator {
                 self.ensure_call_log_id();
                 self.started_at = Some(Instant::now());
                 self.started_wall = Some(std::time::SystemTime::now());
+                self.publish_call_event(CallEvent::Answered);
                 if let Err(e) = self.recording.start_main() {
                     warn!(
                         "[session {}] failed to start recorder: {:?}",
@@ -273,7 +310,9 @@ impl SessionCoordinator {
                     .await;
 
                 if !self.outbound_mode {
-                    if self.transfer_after_answer_pending {
+                    if self.is_voicemail_retrieval() {
+                        self.start_voicemail_retrieval().await;
+                    } else if self.transfer_after_answer_pending {
                         self.transfer_after_answer_pending = false;
                         self.start_b2bua_transfer("vr_initial");
                     } else if self.announce_mode {
@@ -392,6 +431,10 @@ impl SessionCoordinator {
                     ));
                     self.outbound_mode = false;
                     self.invite_rejected = true;
+                } else if self.ivr_state == IvrState::Queued {
+                    self.handle_queue_connect_failed();
+                } else if self.b_leg.is_none() && self.apply_transfer_no_answer_fallback().await {
+                    self.mark_transfer_ended();
                 } else {
                     info!(
                         "[session {}] transfer failed in IVR mode, ending call",
@@ -416,6 +459,21 @@ impl SessionCoordinator {
                     self.send_call_ended(EndReason::Error);
                 }
             }
+            (_, SessionControlIn::TransferAttempt { attempt }) => {
+                self.record_transfer_attempt(attempt);
+            }
+            (
+                _,
+                SessionControlIn::QueuePosition {
+                    position,
+                    audio_path,
+                },
+            ) => {
+                self.play_queue_position(position, &audio_path).await;
+            }
+            (_, SessionControlIn::QueueConnect { agent }) => {
+                self.connect_queue_agent(agent);
+            }
             (_, SessionControlIn::BLegBye) => {
                 info!("[session {}] B-leg BYE received, ending call", self.call_id);
                 self.cancel_transfer();
@@ -436,6 +494,7 @@ impl SessionCoordinator {
                 self.send_call_ended(EndReason::Bye);
             }
             (_, SessionControlIn::B2buaRinging) => {
+                self.publish_transfer_status("ringing");
                 if self.outbound_mode && !self.outbound_sent_180 && !self.outbound_sent_183 {
                     let _ = self
                         .session_out_tx
@@ -514,6 +573,8 @@ impl SessionCoordinator {
             }
             (SessState::Established, SessionControlIn::MediaTimerTick) => {
                 self.recording.flush_tick();
+                self.supervisor.tick();
+                self.check_recording_pause_timeout();
                 if let Err(e) = self.send_silence_frame().await {
                     warn!("[session {}] silence send failed: {:?}", self.call_id, e);
                 }
@@ -588,6 +649,8 @@ impl SessionCoordinator {
                         "[session {}] failed to send app audio: {:?}",
                         self.call_id, e
                     );
+                    // 再生できなかった音声を app が待ち続けないようにする
+                    self.notify_playback_finished();
                 }
             }
             (
@@ -604,6 +667,20 @@ impl SessionCoordinator {
                     );
                 }
             }
+            (
+                SessState::Established,
+                SessionControlIn::AppBotAudioFramesEnqueue {
+                    frames,
+                    generation_id,
+                },
+            ) => {
+                if let Err(e) = self.enqueue_playback_frames(frames, generation_id) {
+                    warn!(
+                        "[session {}] failed to enqueue app audio frames: {:?}",
+                        self.call_id, e
+                    );
+                }
+            }
             (_, SessionControlIn::AppHangup) => {
                 warn!("[session {}] app requested hangup", self.call_id);
                 self.stop_ring_delay();
@@ -636,6 +713,60 @@ impl SessionCoordinator {
                     self.call_id, state, person
                 );
             }
+            (state, SessionControlIn::ApiDescribe { reply }) => {
+                let _ = reply.send(self.live_call_status(state));
+            }
+            (SessState::Established, SessionControlIn::ApiTransfer { target, reply }) => {
+                let _ = reply.send(self.start_api_transfer(target.as_str()));
+            }
+            (
+                SessState::Established,
+                SessionControlIn::ApiPlayAnnouncement {
+                    announcement_id,
+                    reply,
+                },
+            ) => {
+                let _ = reply.send(self.play_api_announcement(announcement_id).await);
+            }
+            (SessState::Established, SessionControlIn::ApiPlayAudio { path, reply }) => {
+                let _ = reply.send(self.play_api_audio(path.as_str()).await);
+            }
+            (SessState::Established, SessionControlIn::ApiSupervise { request, reply }) => {
+                let _ = reply.send(self.attach_supervisor(request));
+            }
+            (SessState::Established, SessionControlIn::ApiRecordingPause { paused, reply }) => {
+                let _ = reply.send(self.set_recording_paused_by_api(paused));
+            }
+            (
+                state,
+                SessionControlIn::ApiTransfer { reply, .. }
+                | SessionControlIn::ApiPlayAnnouncement { reply, .. }
+                | SessionControlIn::ApiPlayAudio { reply, .. }
+                | SessionControlIn::ApiRecordingPause { reply, .. },
+            ) => {
+                let _ = reply.send(Err(LiveCallError::Rejected(format!(
+                    "call is not established ({:?})",
+                    state
+                ))));
+            }
+            (state, SessionControlIn::ApiSupervise { reply, .. }) => {
+                let _ = reply.send(Err(LiveCallError::Rejected(format!(
+                    "call is not established ({:?})",
+                    state
+                ))));
+            }
+            (_, SessionControlIn::AppEmotionTurn { turn }) => {
+                self.record_emotion_turn(turn);
+            }
+            (SessState::Established, SessionControlIn::AppRecordingPause) => {
+                self.pause_recording("intent");
+            }
+            (state, SessionControlIn::AppRecordingPause) => {
+                warn!(
+                    "[session {}] AppRecordingPause ignored in state {:?}",
+                    self.call_id, state
+                );
+            }
             (_, SessionControlIn::SipSessionExpires { timer }) => {
                 self.update_session_expires(timer);
             }
@@ -657,8 +788,13 @@ impl SessionCoordinator {
                             self.reset_ivr_timeout();
                         }
                     }
+                } else if self.ivr_state == IvrState::VoicemailRetrieval {
+                    self.handle_voicemail_timeout().await;
                 }
             }
+            (_, SessionControlIn::IvrSpeechResult { text, intent }) => {
+                self.handle_db_ivr_speech_result(text, intent).await;
+            }
             (_, SessionControlIn::SessionRefreshDue) => {
                 if let (Some(expires), Some(SessionRefresher::Uas)) =
                     (self.session_expires, self.session_refresher)
@@ -743,11 +879,18 @@ impl SessionCoordinator {
                 );
                 let payload_len = payload.len();
                 self.recording.push_rx(&payload);
+                self.supervisor.push_caller(&payload);
                 if self.ivr_state == IvrState::B2buaMode {
+                    let mut to_agent = payload.clone();
+                    self.supervisor.mix_to_agent(&mut to_agent);
+                    self.recording.push_b_leg_tx(&to_agent);
                     if let Some(b_leg) = &self.b_leg {
-                        self.rtp.send_payload(&b_leg.rtp_key, payload.clone());
+                        self.rtp.send_payload(&b_leg.rtp_key, to_agent);
                     }
-                    self.recording.push_b_leg_tx(&payload);
+                } else if self.recording.is_paused() {
+                    // 一時停止中は ASR（音声 IVR・ボイスボットとも）に音声を渡さない
+                } else if self.is_ivr_speech_listening() {
+                    self.ingest_ivr_speech(&payload);
                 } else if self.ivr_state == IvrState::VoicebotMode {
                     let was_in_speech = self.capture.is_in_speech();
                     let capture_result = self.capture.ingest(&payload);
@@ -817,7 +960,27 @@ impl SessionCoordinator {
                 if self.state_machine.state() != SessState::Established {
                     return;
                 }
+                if self.handle_recording_dtmf(digit) {
+                    return;
+                }
+                if self.recording.is_paused() {
+                    // 一時停止中の桁（カード番号の入力など）はログ・イベントに残さない
+                    info!(
+                        "[session {}] DTMF received while recording paused",
+                        self.call_id
+                    );
+                    return;
+                }
                 info!("[session {}] DTMF received: '{}'", self.call_id, digit);
+                self.publish_call_event(CallEvent::Dtmf { digit });
+                if self.ivr_state == IvrState::VoicemailRetrieval {
+                    self.handle_voicemail_dtmf(digit).await;
+                    return;
+                }
+                if self.ivr_state == IvrState::Queued {
+                    self.handle_queue_dtmf(digit).await;
+                    return;
+                }
                 if self.ivr_state == IvrState::VoicebotIntroPlaying {
                     info!(
                         "[session {}] ignoring DTMF during voicebot intro",
@@ -886,13 +1049,7 @@ impl SessionCoordinator {
                             );
                         }
                         self.start_transfer_announce();
-                        self.transfer_cancel = Some(b2bua::spawn_transfer(
-                            self.call_id.clone(),
-                            self.from_uri.clone(),
-                            self.control_tx.clone(),
-                            self.media_tx.clone(),
-                            self.runtime_cfg.clone(),
-                        ));
+                        self.spawn_hunt_transfer("ivr_legacy");
                     }
                     IvrAction::ReplayMenu => {
                         info!("[session {}] replaying IVR menu", self.call_id);
@@ -938,8 +1095,11 @@ impl SessionCoordinator {
                     return;
                 }
                 if self.ivr_state == IvrState::B2buaMode {
-                    self.recording.push_tx(&payload);
                     self.recording.push_b_leg_rx(&payload);
+                    let mut payload = payload;
+                    self.supervisor.mix_to_caller(&mut payload);
+                    self.recording.push_tx(&payload);
+                    self.supervisor.push_callee(&payload);
                     self.rtp.send_payload(self.call_id.as_str(), payload);
                     self.rtp_last_sent = Some(Instant::now());
                 }
@@ -963,16 +1123,112 @@ impl SessionCoordinator {
         self.stop_ivr_timeout();
         self.ivr_state = IvrState::Transferring;
         self.mark_transfer_trying();
-        self.transfer_cancel = Some(b2bua::spawn_transfer(
-            self.call_id.clone(),
-            self.from_uri.clone(),
-            self.control_tx.clone(),
-            self.media_tx.clone(),
-            self.runtime_cfg.clone(),
-        ));
+        self.spawn_hunt_transfer(person);
         true
     }
 
+    /// 転送先グループの全員が応答しなかったときのフォールバック。通話を続ける場合は true
+    async fn apply_transfer_no_answer_fallback(&mut self) -> bool {
+        match self.transfer_no_answer {
+            HuntNoAnswerAction::Hangup => false,
+            HuntNoAnswerAction::Voicemail => {
+                info!(
+                    "[session {}] no transfer member answered, falling back to voicemail",
+                    self.call_id
+                );
+                self.start_voicemail_fallback().await;
+                true
+            }
+            HuntNoAnswerAction::Voicebot => {
+                info!(
+                    "[session {}] no transfer member answered, falling back to voicebot",
+                    self.call_id
+                );
+                self.cancel_playback();
+                self.register_action_for_call_log("VB");
+                self.transition_to_voicebot_mode(Some(super::TRANSFER_FAIL_WAV_PATH.to_string()))
+                    .await;
+                true
+            }
+            HuntNoAnswerAction::Queue => {
+                let Some((queue, priority)) = self.transfer_queue.clone() else {
+                    return false;
+                };
+                info!(
+                    "[session {}] no transfer member answered, entering queue {}",
+                    self.call_id, queue
+                );
+                self.enter_call_queue(&queue, priority).await
+            }
+        }
+    }
+
+    /// 案内の後、通話録音をそのまま留守番電話として残す
+    async fn start_voicemail_fallback(&mut self) {
+        self.cancel_playback();
+        self.register_action_for_call_log("VM");
+        self.recording.set_enabled(true);
+        if !self.recording.is_started() {
+            if let Err(e) = self.recording.start_main() {
+                warn!(
+                    "[session {}] failed to start voicemail recorder: {:?}",
+                    self.call_id, e
+                );
+            }
+        }
+        self.voicemail_mode = true;
+        self.announce_mode = true;
+        self.ivr_state = IvrState::Transferring;
+        if let Err(e) = self.start_playback(&[super::TRANSFER_FAIL_WAV_PATH]).await {
+            warn!(
+                "[session {}] failed to play voicemail greeting: {:?}",
+                self.call_id, e
+            );
+            self.announce_mode = false;
+        }
+    }
+
+    /// 待ち中の DTMF（留守番電話・折り返し依頼）。エージェント呼び出し中は受け付けない
+    async fn handle_queue_dtmf(&mut self, digit: char) {
+        if self.queue_agent().is_some() {
+            debug!(
+                "[session {}] ignoring DTMF while ringing queue agent",
+                self.call_id
+            );
+            return;
+        }
+        match self.queue_key_action(digit) {
+            Some(QueueKeyAction::Voicemail) => {
+                info!("[session {}] queue caller chose voicemail", self.call_id);
+                self.leave_call_queue();
+                self.start_voicemail_fallback().await;
+            }
+            Some(QueueKeyAction::Callback) => {
+                self.request_queue_callback();
+                self.cancel_playback();
+                self.ivr_state = IvrState::Transferring;
+                self.announce_mode = true;
+                if let Err(e) = self
+                    .start_playback(&[super::VOICEMAIL_GOODBYE_WAV_PATH])
+                    .await
+                {
+                    warn!(
+                        "[session {}] failed to play callback confirmation: {:?}",
+                        self.call_id, e
+                    );
+                    self.announce_mode = false;
+                    let _ = self.control_tx.try_send(SessionControlIn::AppHangup);
+                }
+            }
+            None => {
+                debug!(
+                    "[session {}] DTMF '{}' has no meaning in queue",
+                    self.call_id, digit
+                );
+            }
+        }
+    }
+
     async fn start_legacy_ivr_menu(&mut self) {
         self.ivr_state = IvrState::IvrMenuWaiting;
         self.mark_ivr_started_if_needed();
@@ -981,6 +1237,9 @@ impl SessionCoordinator {
         self.ivr_retry_count = 0;
         self.ivr_max_retries = 0;
         self.ivr_timeout_override = None;
+        self.ivr_input = IvrInputMode::Keypad;
+        self.ivr_digits.clear();
+        self.ivr_variable_name = None;
 
         let mut playback_paths: Vec<String> = Vec::with_capacity(2);
         if self.recording_notice_pending {
@@ -1042,6 +1301,27 @@ impl SessionCoordinator {
         self.ivr_max_retries = normalize_max_retries(menu.max_retries);
         self.ivr_timeout_override =
             Some(Duration::from_secs(normalize_timeout_sec(menu.timeout_sec)));
+        let speech_routes = if menu.input_node_type.eq_ignore_ascii_case("SPEECH") {
+            match self
+                .routing_port
+                .find_ivr_speech_routes(menu.keypad_node_id)
+                .await
+            {
+                Ok(routes) => routes,
+                Err(err) => {
+                    warn!(
+                        "[session {}] failed to read IVR speech routes node_id={} error={}",
+                        self.call_id, menu.keypad_node_id, err
+                    );
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        self.ivr_input = IvrInputMode::from_menu(&menu, speech_routes);
+        self.ivr_digits.clear();
+        self.ivr_variable_name = menu.variable_name.clone();
         let resolved_menu_path = match menu.audio_file_url.clone() {
             Some(url) => self
                 .resolve_audio_file_url(url)
@@ -1057,11 +1337,12 @@ impl SessionCoordinator {
             .unwrap_or_else(|| super::IVR_INTRO_WAV_PATH.to_string());
 
         info!(
-            "[session {}] starting DB IVR flow id={} root_node_id={} keypad_node_id={} timeout_sec={} max_retries={}",
+            "[session {}] starting DB IVR flow id={} root_node_id={} keypad_node_id={} input={} timeout_sec={} max_retries={}",
             self.call_id,
             ivr_flow_id,
             menu.root_node_id,
             menu.keypad_node_id,
+            menu.input_node_type,
             normalize_timeout_sec(menu.timeout_sec),
             self.ivr_max_retries
         );
@@ -1102,12 +1383,115 @@ impl SessionCoordinator {
     }
 
     async fn handle_db_ivr_timeout(&mut self) {
+        if let Some(collection) = self.ivr_digit_collection() {
+            if !self.ivr_digits.is_empty() {
+                info!(
+                    "[session {}] IVR inter-digit timeout digits={}",
+                    self.call_id,
+                    self.ivr_digits.len()
+                );
+                let step = finish_collected_digits(&collection, &mut self.ivr_digits);
+                self.apply_db_ivr_digit_step(step).await;
+                return;
+            }
+        }
+        self.stop_ivr_speech_listening();
         info!("[session {}] IVR timeout detected", self.call_id);
         self.handle_db_ivr_retry("TIMEOUT").await;
     }
 
+    async fn apply_db_ivr_digit_step(&mut self, step: DigitStep) {
+        let value = match step {
+            DigitStep::Continue => return,
+            DigitStep::Invalid => {
+                info!("[session {}] IVR digit collection invalid", self.call_id);
+                self.handle_db_ivr_retry("INVALID").await;
+                return;
+            }
+            DigitStep::Complete(value) => value,
+        };
+        self.store_ivr_variable(&value);
+        let Some(input_node_id) = self.ivr_keypad_node_id else {
+            warn!(
+                "[session {}] IVR digit collection completed without input node",
+                self.call_id
+            );
+            self.handle_db_ivr_retry("INVALID").await;
+            return;
+        };
+        match self
+            .routing_port
+            .find_ivr_complete_destination(input_node_id)
+            .await
+        {
+            Ok(Some(destination)) => {
+                info!(
+                    "[session {}] IVR digits collected len={} destination_node_id={}",
+                    self.call_id,
+                    value.len(),
+                    destination.node_id
+                );
+                self.ivr_retry_count = 0;
+                self.execute_db_ivr_destination(destination).await;
+            }
+            Ok(None) => {
+                warn!(
+                    "[session {}] IVR COMPLETE transition missing node_id={}",
+                    self.call_id, input_node_id
+                );
+                self.handle_db_ivr_retry("INVALID").await;
+            }
+            Err(err) => {
+                warn!(
+                    "[session {}] failed to read IVR COMPLETE transition node_id={} error={}",
+                    self.call_id, input_node_id, err
+                );
+                self.reset_ivr_timeout();
+            }
+        }
+    }
+
+    async fn handle_db_ivr_speech_result(&mut self, text: String, intent: Option<String>) {
+        if self.ivr_state != IvrState::IvrMenuWaiting || !self.ivr_input.is_speech() {
+            debug!(
+                "[session {}] ignoring IVR speech result in {:?}",
+                self.call_id, self.ivr_state
+            );
+            return;
+        }
+        self.stop_ivr_timeout();
+        let text = text.trim();
+        let Some(route) = self.ivr_speech_route(text, intent.as_deref()) else {
+            info!(
+                "[session {}] IVR speech did not match any route text_len={} intent={:?}",
+                self.call_id,
+                text.chars().count(),
+                intent
+            );
+            self.handle_db_ivr_retry("INVALID").await;
+            return;
+        };
+        info!(
+            "[session {}] IVR speech matched destination_node_id={} intent={:?}",
+            self.call_id, route.destination.node_id, intent
+        );
+        self.store_ivr_variable(text);
+        self.ivr_retry_count = 0;
+        self.execute_db_ivr_destination(route.destination).await;
+    }
+
     async fn handle_db_ivr_dtmf(&mut self, digit: char) {
         self.record_ivr_event("dtmf_input", None, Some(digit), None, None, None);
+        if let Some(collection) = self.ivr_digit_collection() {
+            let step = push_collected_digit(&collection, &mut self.ivr_digits, digit);
+            if step == DigitStep::Continue {
+                self.start_ivr_timeout_after(collection.inter_digit_timeout);
+                return;
+            }
+            self.apply_db_ivr_digit_step(step).await;
+            return;
+        }
+        self.stop_ivr_speech_listening();
         let Some(ivr_flow_id) = self.ivr_flow_id else {
             warn!(
                 "[session {}] DB IVR flow missing while handling DTMF '{}'",
@@ -1149,6 +1533,7 @@ impl SessionCoordinator {
     }
 
     async fn handle_db_ivr_retry(&mut self, input_type: &'static str) {
+        self.ivr_digits.clear();
         let event_type = if input_type == "TIMEOUT" {
             "timeout"
         } else {
@@ -1235,12 +1620,15 @@ impl SessionCoordinator {
         action.announcement_audio_file_url = destination.audio_file_url.clone();
         action.scenario_id = metadata.scenario_id;
         action.include_announcement = metadata.include_announcement;
+        let hunt_group = metadata.hunt_group;
         let previous_ivr_flow_id = self.ivr_flow_id;
         let previous_ivr_menu_audio_file_url = self.ivr_menu_audio_file_url.clone();
         let previous_ivr_keypad_node_id = self.ivr_keypad_node_id;
         let previous_ivr_retry_count = self.ivr_retry_count;
         let previous_ivr_max_retries = self.ivr_max_retries;
         let previous_ivr_timeout_override = self.ivr_timeout_override;
+        let previous_ivr_input = self.ivr_input.clone();
+        let previous_ivr_variable_name = self.ivr_variable_name.clone();
         let call_id = self.call_id.to_string();
 
         info!(
@@ -1269,6 +1657,8 @@ impl SessionCoordinator {
             self.ivr_retry_count = previous_ivr_retry_count;
             self.ivr_max_retries = previous_ivr_max_retries;
             self.ivr_timeout_override = previous_ivr_timeout_override;
+            self.ivr_input = previous_ivr_input;
+            self.ivr_variable_name = previous_ivr_variable_name;
             self.replay_current_ivr_menu().await;
             return;
         }
@@ -1291,6 +1681,8 @@ impl SessionCoordinator {
                         self.ivr_retry_count = previous_ivr_retry_count;
                         self.ivr_max_retries = previous_ivr_max_retries;
                         self.ivr_timeout_override = previous_ivr_timeout_override;
+                        self.ivr_input = previous_ivr_input;
+                        self.ivr_variable_name = previous_ivr_variable_name;
                         self.replay_current_ivr_menu().await;
                     }
                 } else {
@@ -1315,7 +1707,7 @@ impl SessionCoordinator {
                 );
                 self.set_transfer_after_answer_pending(false);
                 self.notify_ivr_transfer_if_needed().await;
-                self.start_b2bua_transfer("ivr_vr");
+                self.start_b2bua_transfer(hunt_group.as_deref().unwrap_or("ivr_vr"));
             }
             "VB" => {
                 self.record_ivr_event(
@@ -1368,6 +1760,8 @@ impl SessionCoordinator {
                 self.ivr_retry_count = previous_ivr_retry_count;
                 self.ivr_max_retries = previous_ivr_max_retries;
                 self.ivr_timeout_override = previous_ivr_timeout_override;
+                self.ivr_input = previous_ivr_input;
+                self.ivr_variable_name = previous_ivr_variable_name;
                 self.replay_current_ivr_menu().await;
             }
         }
@@ -1375,12 +1769,16 @@ impl SessionCoordinator {
 
     async fn transition_to_voicebot_mode(&mut self, intro_path: Option<String>) {
         self.stop_ivr_timeout();
+        self.stop_ivr_speech_listening();
         self.ivr_state = IvrState::VoicebotMode;
         self.ivr_keypad_node_id = None;
         self.ivr_menu_audio_file_url = None;
         self.ivr_timeout_override = None;
         self.ivr_retry_count = 0;
         self.ivr_max_retries = 0;
+        self.ivr_input = IvrInputMode::Keypad;
+        self.ivr_digits.clear();
+        self.ivr_variable_name = None;
 
         if let Some(path) = intro_path {
             info!(
@@ -1479,17 +1877,18 @@ mod tests {
     use crate::protocol::session::state_machine::SessionStateMachine;
     use crate::protocol::session::timers::SessionTimers;
     use crate::protocol::session::types::{
-        CallId, IvrState, MediaConfig, Sdp, SessState, SessionControlIn, SessionOut,
+        CallId, CallerIdentity, IvrState, MediaConfig, Sdp, SessState, SessionControlIn, SessionOut,
     };
     use crate::shared::config::{
-        OutboundConfig, RegistrarConfig, RegistrarTransport, SessionRuntimeConfig,
+        HuntGroup, HuntGroupsConfig, HuntNoAnswerAction, HuntStrategy, OutboundConfig,
+        RegistrarConfig, RegistrarTransport, SessionRuntimeConfig,
     };
     use crate::shared::ports::app::app_event_channel;
-    use crate::shared::ports::call_log_port::{CallLogPort, EndedCallLog};
+    use crate::shared::ports::call_log_port::{CallLogPort, EndedCallLog, TransferAttempt};
     use crate::shared::ports::ingest::{IngestError, IngestFuture, IngestPayload, IngestPort};
     use crate::shared::ports::routing_port::{
-        CallActionRuleRow, IvrDestinationRow, IvrMenuRow, NoopRoutingPort, RegisteredNumberRow,
-        RoutingFuture, RoutingPort, RoutingRuleRow,
+        CallActionRuleRow, IvrDestinationRow, IvrMenuRow, IvrSpeechRouteRow, NoopRoutingPort,
+        RegisteredNumberRow, RoutingFuture, RoutingPort, RoutingRuleRow,
     };
     use crate::shared::ports::storage::{StorageError, StoragePort};
     use serde_json::Value;
@@ -1569,19 +1968,25 @@ mod tests {
         fn find_registered_number(
             &self,
             phone_number: &str,
+            did: Option<&str>,
         ) -> RoutingFuture<Option<RegisteredNumberRow>> {
-            self.noop.find_registered_number(phone_number)
+            self.noop.find_registered_number(phone_number, did)
         }
 
-        fn find_caller_group(&self, phone_number: &str) -> RoutingFuture<Option<Uuid>> {
-            self.noop.find_caller_group(phone_number)
+        fn find_caller_group(
+            &self,
+            phone_number: &str,
+            did: Option<&str>,
+        ) -> RoutingFuture<Option<Uuid>> {
+            self.noop.find_caller_group(phone_number, did)
         }
 
         fn find_call_action_rule(
             &self,
             group_id: Uuid,
+            did: Option<&str>,
         ) -> RoutingFuture<Option<CallActionRuleRow>> {
-            self.noop.find_call_action_rule(group_id)
+            self.noop.find_call_action_rule(group_id, did)
         }
 
         fn is_spam(&self, phone_number: &str) -> RoutingFuture<bool> {
@@ -1592,8 +1997,16 @@ mod tests {
             self.noop.is_registered(phone_number)
         }
 
-        fn find_routing_rule(&self, category: &str) -> RoutingFuture<Option<RoutingRuleRow>> {
-            self.noop.find_routing_rule(category)
+        fn find_routing_rule(
+            &self,
+            category: &str,
+            did: Option<&str>,
+        ) -> RoutingFuture<Option<RoutingRuleRow>> {
+            self.noop.find_routing_rule(category, did)
+        }
+
+        fn find_routing_rule_by_id(&self, rule_id: Uuid) -> RoutingFuture<Option<RoutingRuleRow>> {
+            self.noop.find_routing_rule_by_id(rule_id)
         }
 
         fn get_system_settings_extra(&self) -> RoutingFuture<Option<Value>> {
@@ -1688,11 +2101,31 @@ mod tests {
                 Ok(destination)
             })
         }
+
+        fn find_ivr_complete_destination(
+            &self,
+            input_node_id: Uuid,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop.find_ivr_complete_destination(input_node_id)
+        }
+
+        fn find_ivr_speech_routes(
+            &self,
+            input_node_id: Uuid,
+        ) -> RoutingFuture<Vec<IvrSpeechRouteRow>> {
+            self.noop.find_ivr_speech_routes(input_node_id)
+        }
     }
 
+    /// 録音と通知キューは返す TempDir の下に書く（テストが終わるまで保持すること）
     fn build_test_session(
         routing_port: Arc<dyn RoutingPort>,
-    ) -> (SessionCoordinator, mpsc::Receiver<(CallId, SessionOut)>) {
+    ) -> (
+        SessionCoordinator,
+        mpsc::Receiver<(CallId, SessionOut)>,
+        tempfile::TempDir,
+    ) {
+        let storage = tempfile::tempdir().expect("temp storage dir should be creatable");
         let (session_out_tx, session_out_rx) = mpsc::channel(32);
         let (app_tx, _app_rx) = app_event_channel(16);
         let (control_tx, _control_rx) =
@@ -1725,12 +2158,22 @@ mod tests {
             media_cfg: MediaConfig::pcmu("127.0.0.1", 10000),
             call_log_port: Arc::new(DummyCallLogPort),
             routing_port,
+            schedule_port: Arc::new(crate::shared::ports::schedule_port::NoopSchedulePort::new()),
+            voicemail_port: Arc::new(
+                crate::shared::ports::voicemail_port::NoopVoicemailPort::new(),
+            ),
+            call_events: Arc::new(
+                crate::shared::ports::call_event_port::NoopCallEventPublisher::new(),
+            ),
             rtp: crate::protocol::session::rtp_stream_manager::RtpStreamManager::new(
                 RtpTxHandle::new(crate::shared::config::rtp_config().clone()),
             ),
-            recording: crate::protocol::session::recording_manager::RecordingManager::new(
+            recording: crate::protocol::session::recording_manager::RecordingManager::in_dir(
                 "test-call",
+                storage.path().join("recordings").join("test-call"),
             ),
+            supervisor: Default::default(),
+            dtmf_sequence: String::new(),
             started_at: None,
             started_wall: None,
             rtp_last_sent: None,
@@ -1747,6 +2190,9 @@ mod tests {
             ivr_timeout_stop: None,
             b_leg: None,
             transfer_cancel: None,
+            transfer_no_answer: HuntNoAnswerAction::Hangup,
+            transfer_queue: None,
+            queue: None,
             transfer_announce_stop: None,
             ring_delay_cancel: None,
             pending_answer: None,
@@ -1759,6 +2205,7 @@ mod tests {
             announce_mode: false,
             voicebot_direct_mode: false,
             voicemail_mode: false,
+            voicemail_retrieval: None,
             recording_notice_pending: false,
             transfer_after_answer_pending: false,
             announcement_id: None,
@@ -1769,7 +2216,13 @@ mod tests {
             ivr_retry_count: 0,
             ivr_max_retries: 0,
             ivr_timeout_override: None,
+            ivr_input: IvrInputMode::default(),
+            ivr_digits: String::new(),
+            ivr_variable_name: None,
+            call_variables: std::collections::BTreeMap::new(),
             call_log_id: None,
+            matched_schedule_id: None,
+            caller_identity: CallerIdentity::default(),
             initial_action_code: None,
             caller_category: "unknown".to_string(),
             call_disposition: "allowed".to_string(),
@@ -1780,18 +2233,18 @@ mod tests {
             transfer_ended_at: None,
             ivr_event_sequence: 0,
             ivr_events: Vec::new(),
+            emotion_timeline: Vec::new(),
+            transfer_events: Vec::new(),
             ingest_persisted: false,
             session_expires: None,
             session_refresher: None,
-            notification_queue_file: std::path::PathBuf::from(
-                "storage/notifications/pending.jsonl",
-            ),
+            notification_queue_file: storage.path().join("pending.jsonl"),
             is_ivr_call: false,
             ivr_started_at: None,
             dtmf_history: Vec::new(),
             notification_sent: false,
         };
-        (session, session_out_rx)
+        (session, session_out_rx, storage)
     }
 
     fn test_registrar(user: &str) -> RegistrarConfig {
@@ -1834,6 +2287,7 @@ mod tests {
             call_id: CallId::new(call_id.to_string()).expect("valid call id"),
             from: "sip:from@example.com".to_string(),
             to: "sip:to@example.com".to_string(),
+            identity: CallerIdentity::from_number("from"),
             offer: Sdp::pcmu("127.0.0.1", 10000),
             session_timer: None,
         }
@@ -1859,19 +2313,6 @@ mod tests {
         }
     }
 
-    fn unique_notification_queue_file() -> std::path::PathBuf {
-        let dir = std::env::temp_dir().join(format!("vvb-handler-notify-test-{}", Uuid::now_v7()));
-        std::fs::create_dir_all(&dir).expect("temp notification dir should be creatable");
-        dir.join("pending.jsonl")
-    }
-
-    fn cleanup_notification_queue_file(path: &std::path::Path) {
-        let _ = std::fs::remove_file(path);
-        if let Some(parent) = path.parent() {
-            let _ = std::fs::remove_dir_all(parent);
-        }
-    }
-
     #[test]
     fn parse_ivr_destination_metadata_reads_known_fields() {
         let metadata = parse_ivr_destination_metadata(
@@ -1911,7 +2352,7 @@ mod tests {
     #[tokio::test]
     async fn invite_with_non_matching_from_user_is_treated_as_inbound() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, mut session_out_rx) = build_test_session(routing_port);
+        let (mut session, mut session_out_rx, _storage) = build_test_session(routing_port);
         set_runtime_cfg_for_invite_judgment(
             &mut session,
             Some("09012345678"),
@@ -1948,7 +2389,7 @@ mod tests {
     #[tokio::test]
     async fn invite_from_registered_user_and_non_matching_to_is_outbound() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, mut session_out_rx) = build_test_session(routing_port);
+        let (mut session, mut session_out_rx, _storage) = build_test_session(routing_port);
         set_runtime_cfg_for_invite_judgment(&mut session, Some("09012345678"), "example.com", &[]);
         session.from_uri = "sip:09012345678@local".to_string();
         session.to_uri = "sip:09028894539@domain".to_string();
@@ -1988,7 +2429,7 @@ mod tests {
     #[tokio::test]
     async fn invite_returns_503_when_outbound_cfg_missing_target() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, mut session_out_rx) = build_test_session(routing_port);
+        let (mut session, mut session_out_rx, _storage) = build_test_session(routing_port);
         set_runtime_cfg_for_invite_judgment(&mut session, Some("09012345678"), "", &[]);
         session.from_uri = "sip:09012345678@local".to_string();
         session.to_uri = "sip:09028894539@domain".to_string();
@@ -2020,7 +2461,7 @@ mod tests {
     #[tokio::test]
     async fn invite_to_registered_user_is_always_inbound() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, mut session_out_rx) = build_test_session(routing_port);
+        let (mut session, mut session_out_rx, _storage) = build_test_session(routing_port);
         set_runtime_cfg_for_invite_judgment(&mut session, Some("09012345678"), "example.com", &[]);
         session.from_uri = "sip:+819099998888@carrier.example.com".to_string();
         session.to_uri = "sip:09012345678@domain".to_string();
@@ -2049,7 +2490,7 @@ mod tests {
     #[tokio::test]
     async fn invite_is_inbound_when_registrar_is_not_configured() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, mut session_out_rx) = build_test_session(routing_port);
+        let (mut session, mut session_out_rx, _storage) = build_test_session(routing_port);
         set_runtime_cfg_for_invite_judgment(
             &mut session,
             None,
@@ -2083,7 +2524,7 @@ mod tests {
     #[tokio::test]
     async fn invite_with_malformed_to_is_treated_as_inbound_without_rejection() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, mut session_out_rx) = build_test_session(routing_port);
+        let (mut session, mut session_out_rx, _storage) = build_test_session(routing_port);
         set_runtime_cfg_for_invite_judgment(&mut session, Some("09012345678"), "example.com", &[]);
         session.from_uri = "sip:09012345678@local".to_string();
         session.to_uri = "invalid to header".to_string();
@@ -2123,7 +2564,7 @@ mod tests {
             None,
             None,
         ));
-        let (mut session, _session_out_rx) = build_test_session(routing_port);
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
         session.ivr_flow_id = Some(flow_id);
         session.ivr_keypad_node_id = Some(keypad_node_id);
 
@@ -2144,7 +2585,7 @@ mod tests {
             Some(voicebot_destination()),
             None,
         ));
-        let (mut session, _session_out_rx) = build_test_session(routing_port);
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
         session.ivr_flow_id = Some(flow_id);
         session.ivr_max_retries = 0;
 
@@ -2165,7 +2606,7 @@ mod tests {
             None,
             Some(voicebot_destination()),
         ));
-        let (mut session, _session_out_rx) = build_test_session(routing_port);
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
         session.ivr_flow_id = Some(flow_id);
         session.ivr_max_retries = 0;
 
@@ -2179,9 +2620,8 @@ mod tests {
     #[tokio::test]
     async fn db_ivr_vr_destination_emits_ivr_transfer_notification() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, _session_out_rx) = build_test_session(routing_port);
-        let queue_file = unique_notification_queue_file();
-        session.notification_queue_file = queue_file.clone();
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
+        let queue_file = session.notification_queue_file.clone();
         session.from_uri = "sip:09012345678@example.com".to_string();
         session.ivr_started_at = Some(Instant::now() - Duration::from_secs(2));
         session.dtmf_history = vec!['1', '3'];
@@ -2212,16 +2652,13 @@ mod tests {
             dwell_time_sec >= 2,
             "dwellTimeSec should reflect elapsed ivr time"
         );
-
-        cleanup_notification_queue_file(queue_file.as_path());
     }
 
     #[tokio::test]
     async fn db_ivr_vr_destination_skips_notification_when_already_sent() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, _session_out_rx) = build_test_session(routing_port);
-        let queue_file = unique_notification_queue_file();
-        session.notification_queue_file = queue_file.clone();
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
+        let queue_file = session.notification_queue_file.clone();
         session.notification_sent = true;
         session.ivr_started_at = Some(Instant::now() - Duration::from_secs(2));
         session.dtmf_history = vec!['1', '3'];
@@ -2234,14 +2671,12 @@ mod tests {
             !queue_file.exists(),
             "notification should not be appended when notification_sent is true"
         );
-
-        cleanup_notification_queue_file(queue_file.as_path());
     }
 
     #[tokio::test]
     async fn b2bua_failed_in_ivr_mode_sends_bye_and_rtp_stop() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, mut session_out_rx) = build_test_session(routing_port);
+        let (mut session, mut session_out_rx, _storage) = build_test_session(routing_port);
         session.outbound_mode = false;
         session.ivr_state = IvrState::Transferring;
 
@@ -2269,10 +2704,147 @@ mod tests {
         assert!(saw_rtp_stop, "IVR mode B2buaFailed should send RtpStopTx");
     }
 
+    #[tokio::test]
+    async fn b2bua_failed_with_voicemail_fallback_keeps_call_and_records_attempts() {
+        let routing_port = Arc::new(NoopRoutingPort::new());
+        let (mut session, mut session_out_rx, _storage) = build_test_session(routing_port);
+        session.outbound_mode = false;
+        session.ivr_state = IvrState::Transferring;
+        session.transfer_no_answer = HuntNoAnswerAction::Voicemail;
+
+        let now = chrono::Utc::now();
+        let _ = session
+            .handle_control_event(
+                SessState::Established,
+                SessionControlIn::TransferAttempt {
+                    attempt: TransferAttempt {
+                        hunt_group: "sales".to_string(),
+                        member: "sip:101@pbx.example.com".to_string(),
+                        started_at: now,
+                        ended_at: now,
+                        outcome: "no_answer".to_string(),
+                        sip_status: None,
+                    },
+                },
+            )
+            .await;
+        let _ = session
+            .handle_control_event(
+                SessState::Established,
+                SessionControlIn::B2buaFailed {
+                    reason: "hunt group sales exhausted: transfer timeout after 15s".to_string(),
+                    status: None,
+                },
+            )
+            .await;
+
+        let mut saw_bye = false;
+        while let Ok((_call_id, out)) = session_out_rx.try_recv() {
+            if matches!(out, SessionOut::SipSendBye) {
+                saw_bye = true;
+            }
+        }
+        assert!(!saw_bye, "voicemail fallback should keep the A-leg");
+        assert!(session.voicemail_mode);
+        assert_eq!(session.transfer_status, "failed");
+        assert_eq!(session.transfer_events.len(), 1);
+        assert_eq!(session.transfer_events[0].outcome, "no_answer");
+    }
+
+    #[tokio::test]
+    async fn b2bua_failed_with_queue_fallback_waits_and_requeues_on_agent_failure() {
+        let routing_port = Arc::new(NoopRoutingPort::new());
+        let (mut session, mut session_out_rx, _storage) = build_test_session(routing_port);
+        let fallback = HuntGroup::new(
+            "default",
+            HuntStrategy::Sequential,
+            vec!["sip:zoiper@127.0.0.1:5060".to_string()],
+            Duration::from_secs(30),
+            HuntNoAnswerAction::Hangup,
+        );
+        let yaml = r#"
+queues:
+  support:
+    agents: ["101"]
+    voicemail_key: "1"
+    callback_key: "2"
+"#;
+        let mut runtime_cfg = (*session.runtime_cfg).clone();
+        runtime_cfg.hunt_groups =
+            HuntGroupsConfig::parse(yaml, fallback, "sip:zoiper@127.0.0.1:5060")
+                .expect("yaml should parse");
+        session.runtime_cfg = Arc::new(runtime_cfg);
+        session.outbound_mode = false;
+        session.ivr_state = IvrState::Transferring;
+        session.transfer_no_answer = HuntNoAnswerAction::Queue;
+        session.transfer_queue = Some(("support".to_string(), 3));
+
+        let _ = session
+            .handle_control_event(
+                SessState::Established,
+                SessionControlIn::B2buaFailed {
+                    reason: "hunt group sales exhausted".to_string(),
+                    status: Some(486),
+                },
+            )
+            .await;
+        assert_eq!(session.ivr_state, IvrState::Queued);
+
+        let _ = session
+            .handle_control_event(
+                SessState::Established,
+                SessionControlIn::QueueConnect {
+                    agent: "sip:101@127.0.0.1:5060".to_string(),
+                },
+            )
+            .await;
+        assert_eq!(session.queue_agent(), Some("sip:101@127.0.0.1:5060"));
+        assert!(session.transfer_cancel.is_some());
+
+        let _ = session
+            .handle_control_event(
+                SessState::Established,
+                SessionControlIn::B2buaFailed {
+                    reason: "transfer timeout after 15s".to_string(),
+                    status: None,
+                },
+            )
+            .await;
+        assert_eq!(session.ivr_state, IvrState::Queued);
+        assert_eq!(session.queue_agent(), None);
+        session.leave_call_queue();
+
+        let mut events = Vec::new();
+        while let Ok((_call_id, out)) = session_out_rx.try_recv() {
+            assert!(
+                !matches!(out, SessionOut::SipSendBye),
+                "queued call should keep the A-leg"
+            );
+            match out {
+                SessionOut::QueueJoin { queue, priority } => {
+                    events.push(format!("join:{}:{}", queue, priority))
+                }
+                SessionOut::QueueConnectFailed { queue, agent } => {
+                    events.push(format!("failed:{}:{}", queue, agent))
+                }
+                SessionOut::QueueLeave { queue } => events.push(format!("leave:{}", queue)),
+                _ => {}
+            }
+        }
+        assert_eq!(
+            events,
+            vec![
+                "join:support:3".to_string(),
+                "failed:support:sip:101@127.0.0.1:5060".to_string(),
+                "leave:support".to_string(),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn b2bua_failed_in_outbound_mode_keeps_error_response_behavior() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, mut session_out_rx) = build_test_session(routing_port);
+        let (mut session, mut session_out_rx, _storage) = build_test_session(routing_port);
         session.outbound_mode = true;
 
         let _ = session
@@ -2309,7 +2881,7 @@ mod tests {
     #[tokio::test]
     async fn app_transfer_request_is_ignored_outside_established() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, _session_out_rx) = build_test_session(routing_port);
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
 
         let advance = session
             .handle_control_event(
@@ -2337,7 +2909,7 @@ mod tests {
     #[tokio::test]
     async fn app_bot_audio_file_enqueue_starts_playback_in_established() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, _session_out_rx) = build_test_session(routing_port);
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
 
         let advance = session
             .handle_control_event(
@@ -2367,7 +2939,7 @@ mod tests {
     #[tokio::test]
     async fn app_bot_audio_file_enqueue_is_ignored_outside_established() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, _session_out_rx) = build_test_session(routing_port);
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
 
         let advance = session
             .handle_control_event(
@@ -2397,7 +2969,7 @@ mod tests {
     #[tokio::test]
     async fn app_bot_audio_file_enqueue_interrupts_different_generation() {
         let routing_port = Arc::new(NoopRoutingPort::new());
-        let (mut session, _session_out_rx) = build_test_session(routing_port);
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
 
         let advance_first = session
             .handle_control_event(
@@ -2441,4 +3013,97 @@ mod tests {
         );
         assert!(session.playback.is_some());
     }
+
+    #[tokio::test]
+    async fn app_bot_audio_frames_enqueue_queues_same_generation_in_order() {
+        let routing_port = Arc::new(NoopRoutingPort::new());
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
+
+        let advance_first = session
+            .handle_control_event(
+                SessState::Established,
+                SessionControlIn::AppBotAudioFramesEnqueue {
+                    frames: vec![vec![0xFF; 160], vec![0xFF; 160]],
+                    generation_id: 20,
+                },
+            )
+            .await;
+        assert!(advance_first);
+        assert!(session.playback.is_some());
+        assert!(session.sending_audio);
+        assert_eq!(session.playback_generation_id, Some(20));
+
+        let advance_second = session
+            .handle_control_event(
+                SessState::Established,
+                SessionControlIn::AppBotAudioFramesEnqueue {
+                    frames: vec![vec![0x7F; 160]],
+                    generation_id: 20,
+                },
+            )
+            .await;
+        assert!(advance_second);
+        assert_eq!(
+            session.playback_queue.len(),
+            1,
+            "same generation segment should be queued behind current playback"
+        );
+    }
+
+    #[tokio::test]
+    async fn app_bot_audio_frames_enqueue_is_ignored_outside_established() {
+        let routing_port = Arc::new(NoopRoutingPort::new());
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
+
+        let advance = session
+            .handle_control_event(
+                SessState::Terminated,
+                SessionControlIn::AppBotAudioFramesEnqueue {
+                    frames: vec![vec![0xFF; 160]],
+                    generation_id: 1,
+                },
+            )
+            .await;
+
+        assert!(advance, "ignored enqueue should keep session loop running");
+        assert!(session.playback.is_none());
+        assert!(session.playback_queue.is_empty());
+        assert!(!session.sending_audio);
+    }
+
+    #[tokio::test]
+    async fn app_emotion_turn_is_recorded_until_call_log_persisted() {
+        let routing_port = Arc::new(NoopRoutingPort::new());
+        let (mut session, _session_out_rx, _storage) = build_test_session(routing_port);
+        let turn = |turn: i32| crate::shared::ports::call_log_port::EmotionTurn {
+            turn,
+            occurred_at: chrono::Utc::now(),
+            emotion: "angry".to_string(),
+            confidence: 0.8,
+            transcript: "まだですか".to_string(),
+        };
+
+        let advance = session
+            .handle_control_event(
+                SessState::Established,
+                SessionControlIn::AppEmotionTurn { turn: turn(0) },
+            )
+            .await;
+        assert!(advance);
+        assert_eq!(session.emotion_timeline.len(), 1);
+        assert_eq!(session.emotion_timeline[0].emotion, "angry");
+
+        session.ingest_persisted = true;
+        session
+            .handle_control_event(
+                SessState::Terminated,
+                SessionControlIn::AppEmotionTurn { turn: turn(1) },
+            )
+            .await;
+        assert_eq!(
+            session.emotion_timeline.len(),
+            1,
+            "late turns must not be appended after the call log is persisted"
+        );
+    }
 }
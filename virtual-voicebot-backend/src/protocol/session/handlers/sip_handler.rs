@@ -4,6 +4,7 @@ use super::super::SessionCoordinator;
 use crate::protocol::session::types::{Sdp, SessionOut};
 use crate::protocol::sip::utils::extract_user_from_to as extract_sip_user;
 use crate::shared::ports::app::{AppEvent, EndReason};
+use crate::shared::ports::call_event_port::CallEvent;
 
 /// Extracts a candidate user identifier or telephone number from a SIP `To`/`From`-style header string.
 ///
@@ -34,6 +35,17 @@ impl SessionCoordinator {
         let from = extract_notify_from(self.from_uri.as_str());
         let timestamp = now_jst();
         let duration_sec = self.started_at.map(|started| started.elapsed().as_secs());
+        let reason_label = match reason {
+            EndReason::Bye => "bye",
+            EndReason::Cancel => "cancel",
+            EndReason::Timeout => "timeout",
+            EndReason::Error => "error",
+            EndReason::AppHangup => "app_hangup",
+        };
+        self.publish_call_event(CallEvent::Ended {
+            reason: reason_label.to_string(),
+            duration_sec,
+        });
         if let Err(err) = self.app_tx.try_send(AppEvent::CallEnded {
             call_id: self.call_id.clone(),
             from,
@@ -8,6 +8,7 @@ pub mod recording_manager;
 pub mod rtp_stream_manager;
 pub mod session;
 pub mod state_machine;
+mod supervisor;
 mod timers;
 pub mod types;
 pub mod writing;
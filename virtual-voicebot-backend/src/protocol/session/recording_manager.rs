@@ -1,9 +1,14 @@
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use chrono::Utc;
 use thiserror::Error;
 
+use crate::service::recording;
 use crate::shared::media::merge::merge_stereo_files;
 use crate::shared::media::Recorder;
+use crate::shared::ports::call_log_port::RecordingPause;
 
 #[derive(Debug, Error, Clone)]
 pub enum RecordingError {
@@ -34,23 +39,44 @@ impl RecordingErrorSink {
     }
 }
 
+/// 一時停止中の区間
+struct ActivePause {
+    since: Instant,
+    paused_at: chrono::DateTime<Utc>,
+    start_sec: Option<f64>,
+    paused_by: String,
+}
+
 pub struct RecordingManager {
     call_id: String,
+    dir: PathBuf,
     recorder: Recorder,
     b_leg_recorder: Option<Recorder>,
     error_sink: RecordingErrorSink,
     enabled: bool,
+    paused: Option<ActivePause>,
+    pauses: Vec<RecordingPause>,
 }
 
 impl RecordingManager {
     pub fn new(call_id: impl Into<String>) -> Self {
+        let call_id = call_id.into();
+        let dir = recording::recording_dir(&call_id);
+        Self::in_dir(call_id, dir)
+    }
+
+    /// 録音ファイル（mixed.wav / b_leg.wav）を `dir` に書く
+    pub fn in_dir(call_id: impl Into<String>, dir: PathBuf) -> Self {
         let call_id = call_id.into();
         Self {
-            recorder: Recorder::new(call_id.clone()),
+            recorder: Recorder::in_dir(call_id.clone(), dir.clone(), "mixed.wav", true),
             b_leg_recorder: None,
             call_id,
+            dir,
             error_sink: RecordingErrorSink::default(),
             enabled: true,
+            paused: None,
+            pauses: Vec::new(),
         }
     }
 
@@ -84,12 +110,73 @@ impl RecordingManager {
             return;
         }
         if self.b_leg_recorder.is_none() {
-            self.b_leg_recorder = Some(Recorder::with_file(
-                self.call_id.clone(),
-                "b_leg.wav",
-                false,
-            ));
+            let mut recorder =
+                Recorder::in_dir(self.call_id.clone(), self.dir.clone(), "b_leg.wav", false);
+            if self.paused.is_some() {
+                recorder.pause();
+            }
+            self.b_leg_recorder = Some(recorder);
+        }
+    }
+
+    /// 録音を一時停止する（全レグを無音にする）。すでに停止中なら false
+    pub fn pause(&mut self, paused_by: &str) -> bool {
+        if self.paused.is_some() {
+            return false;
+        }
+        self.recorder.pause();
+        if let Some(recorder) = self.b_leg_recorder.as_mut() {
+            recorder.pause();
+        }
+        self.paused = Some(ActivePause {
+            since: Instant::now(),
+            paused_at: Utc::now(),
+            start_sec: self.position_sec(),
+            paused_by: paused_by.to_string(),
+        });
+        true
+    }
+
+    /// 一時停止を解除する。停止中でなければ false
+    pub fn resume(&mut self, resumed_by: &str) -> bool {
+        let Some(pause) = self.paused.take() else {
+            return false;
+        };
+        self.recorder.resume();
+        if let Some(recorder) = self.b_leg_recorder.as_mut() {
+            recorder.resume();
+        }
+        self.pauses.push(RecordingPause {
+            paused_at: pause.paused_at,
+            resumed_at: Utc::now(),
+            start_sec: pause.start_sec,
+            end_sec: self.position_sec(),
+            paused_by: pause.paused_by,
+            resumed_by: resumed_by.to_string(),
+        });
+        true
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// 一時停止してからの経過時間
+    pub fn paused_for(&self) -> Option<Duration> {
+        self.paused.as_ref().map(|pause| pause.since.elapsed())
+    }
+
+    /// 通話ログ用の一時停止区間。停止中のまま終わった区間は call_ended で閉じる
+    pub fn take_pauses(&mut self) -> Vec<RecordingPause> {
+        self.resume("call_ended");
+        std::mem::take(&mut self.pauses)
+    }
+
+    fn position_sec(&self) -> Option<f64> {
+        if !self.enabled {
+            return None;
         }
+        self.recorder.position_sec()
     }
 
     pub fn is_started(&self) -> bool {
@@ -231,3 +318,32 @@ impl RecordingManager {
         self.error_sink.drain()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_and_resume_are_tracked_for_call_log() {
+        let mut manager = RecordingManager::new("pause-test");
+        manager.set_enabled(false);
+
+        assert!(manager.pause("dtmf"));
+        assert!(!manager.pause("api"));
+        assert!(manager.is_paused());
+        assert!(manager.resume("api"));
+        assert!(!manager.resume("api"));
+        assert!(manager.pause("intent"));
+
+        let pauses = manager.take_pauses();
+        assert!(!manager.is_paused());
+        assert_eq!(pauses.len(), 2);
+        assert_eq!(
+            (pauses[0].paused_by.as_str(), pauses[0].resumed_by.as_str()),
+            ("dtmf", "api")
+        );
+        assert_eq!(pauses[1].resumed_by, "call_ended");
+        assert!(pauses[1].start_sec.is_none());
+        assert!(manager.take_pauses().is_empty());
+    }
+}
@@ -3,7 +3,11 @@ use tokio::sync::oneshot;
 use tokio::time::{interval, MissedTickBehavior};
 
 use super::super::SessionCoordinator;
+use crate::protocol::session::b2bua;
 use crate::protocol::session::types::{IvrState, SessionControlIn};
+use crate::shared::config::{HuntGroup, HuntNoAnswerAction};
+use crate::shared::ports::call_event_port::CallEvent;
+use crate::shared::ports::call_log_port::TransferAttempt;
 
 impl SessionCoordinator {
     pub(crate) fn cancel_transfer(&mut self) {
@@ -13,6 +17,48 @@ impl SessionCoordinator {
         self.stop_transfer_announce();
     }
 
+    /// 転送先キー（電話帳の名前・グループ名）に対応する転送先グループの呼び出しを始める
+    pub(crate) fn spawn_hunt_transfer(&mut self, target: &str) {
+        let group = self.runtime_cfg.hunt_groups.resolve(target).clone();
+        self.spawn_transfer_group(target, group);
+    }
+
+    /// 解決済みの転送先グループを呼び出す
+    pub(crate) fn spawn_transfer_group(&mut self, target: &str, group: HuntGroup) {
+        info!(
+            "[session {}] transfer target={} hunt_group={} strategy={:?} no_answer={:?}",
+            self.call_id, target, group.name, group.strategy, group.no_answer
+        );
+        self.transfer_no_answer = group.no_answer;
+        self.transfer_queue = (group.no_answer == HuntNoAnswerAction::Queue)
+            .then(|| (group.queue_name().to_string(), group.queue_priority));
+        self.transfer_cancel = Some(b2bua::spawn_transfer(
+            self.call_id.clone(),
+            self.from_uri.clone(),
+            group,
+            self.control_tx.clone(),
+            self.media_tx.clone(),
+            self.runtime_cfg.clone(),
+        ));
+    }
+
+    pub(crate) fn record_transfer_attempt(&mut self, attempt: TransferAttempt) {
+        self.publish_call_event(CallEvent::Transfer {
+            status: "attempt".to_string(),
+            member: Some(attempt.member.clone()),
+            outcome: Some(attempt.outcome.clone()),
+            sip_status: attempt.sip_status,
+        });
+        if self.ingest_persisted {
+            debug!(
+                "[session {}] transfer attempt to {} arrived after call log persisted; dropped",
+                self.call_id, attempt.member
+            );
+            return;
+        }
+        self.transfer_events.push(attempt);
+    }
+
     pub(crate) fn start_transfer_announce(&mut self) {
         self.stop_transfer_announce();
         let (stop_tx, mut stop_rx) = oneshot::channel();
@@ -0,0 +1,373 @@
+use log::{info, warn};
+use tokio::time::Duration;
+
+use super::super::SessionCoordinator;
+use crate::protocol::session::types::IvrState;
+use crate::shared::ports::app::AppEvent;
+use crate::shared::ports::routing_port::{IvrMenuRow, IvrSpeechRouteRow};
+
+/// DB IVR の入力ノード（メニュー案内後に何を待つか）
+#[derive(Debug, Clone, Default)]
+pub(crate) enum IvrInputMode {
+    /// キー 1 桁で遷移先を選ぶ（従来の KEYPAD）
+    #[default]
+    Keypad,
+    Digits(DigitCollection),
+    Speech {
+        routes: Vec<IvrSpeechRouteRow>,
+        /// 案内再生後、発話の取り込み中
+        listening: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DigitCollection {
+    pub(crate) min_digits: usize,
+    pub(crate) max_digits: usize,
+    pub(crate) terminator: Option<char>,
+    pub(crate) inter_digit_timeout: Duration,
+}
+
+impl DigitCollection {
+    pub(crate) fn from_menu(menu: &IvrMenuRow) -> Self {
+        let max_digits = usize::try_from(menu.max_digits).unwrap_or(1).max(1);
+        let min_digits = usize::try_from(menu.min_digits)
+            .unwrap_or(0)
+            .min(max_digits);
+        let inter_digit_timeout_sec = u64::try_from(menu.inter_digit_timeout_sec)
+            .ok()
+            .filter(|sec| *sec > 0)
+            .unwrap_or(3);
+        Self {
+            min_digits,
+            max_digits,
+            terminator: menu
+                .terminator
+                .as_deref()
+                .and_then(|value| value.trim().chars().next()),
+            inter_digit_timeout: Duration::from_secs(inter_digit_timeout_sec),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum DigitStep {
+    Continue,
+    Complete(String),
+    Invalid,
+}
+
+impl IvrInputMode {
+    pub(crate) fn from_menu(menu: &IvrMenuRow, speech_routes: Vec<IvrSpeechRouteRow>) -> Self {
+        match menu.input_node_type.trim().to_ascii_uppercase().as_str() {
+            "DIGITS" => Self::Digits(DigitCollection::from_menu(menu)),
+            "SPEECH" => Self::Speech {
+                routes: speech_routes,
+                listening: false,
+            },
+            _ => Self::Keypad,
+        }
+    }
+
+    pub(crate) fn is_speech(&self) -> bool {
+        matches!(self, Self::Speech { .. })
+    }
+
+    fn set_listening(&mut self, value: bool) {
+        if let Self::Speech { listening, .. } = self {
+            *listening = value;
+        }
+    }
+}
+
+/// 1 桁受け取るごとの判定。終端キーまたは最大桁数で確定する
+pub(crate) fn push_collected_digit(
+    collection: &DigitCollection,
+    buffer: &mut String,
+    digit: char,
+) -> DigitStep {
+    if collection.terminator == Some(digit) {
+        let value = std::mem::take(buffer);
+        return if value.len() >= collection.min_digits {
+            DigitStep::Complete(value)
+        } else {
+            DigitStep::Invalid
+        };
+    }
+    if !digit.is_ascii_digit() {
+        return DigitStep::Continue;
+    }
+    buffer.push(digit);
+    if buffer.len() >= collection.max_digits {
+        return DigitStep::Complete(std::mem::take(buffer));
+    }
+    DigitStep::Continue
+}
+
+/// 桁間タイムアウト時の判定（最小桁数に達していれば確定）
+pub(crate) fn finish_collected_digits(
+    collection: &DigitCollection,
+    buffer: &mut String,
+) -> DigitStep {
+    let value = std::mem::take(buffer);
+    if !value.is_empty() && value.len() >= collection.min_digits {
+        DigitStep::Complete(value)
+    } else {
+        DigitStep::Invalid
+    }
+}
+
+/// 認識テキストと照合するための正規化（大文字小文字・空白・句読点の差を無視）
+fn normalize_speech_text(text: &str) -> String {
+    text.chars()
+        .filter(|c| {
+            !c.is_whitespace()
+                && !matches!(
+                    c,
+                    '、' | '。' | '，' | '．' | ',' | '.' | '!' | '?' | '！' | '？'
+                )
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// キーワード一致を優先し、なければ意図の一致する遷移を返す
+pub(crate) fn match_speech_route<'a>(
+    text: &str,
+    intent: Option<&str>,
+    routes: &'a [IvrSpeechRouteRow],
+) -> Option<&'a IvrSpeechRouteRow> {
+    let normalized = normalize_speech_text(text);
+    if !normalized.is_empty() {
+        let by_keyword = routes.iter().find(|route| {
+            route.keywords.iter().any(|keyword| {
+                let keyword = normalize_speech_text(keyword);
+                !keyword.is_empty() && normalized.contains(&keyword)
+            })
+        });
+        if by_keyword.is_some() {
+            return by_keyword;
+        }
+    }
+    let intent = intent.map(str::trim).filter(|value| !value.is_empty())?;
+    routes.iter().find(|route| {
+        route
+            .intent
+            .as_deref()
+            .is_some_and(|expected| expected.trim().eq_ignore_ascii_case(intent))
+    })
+}
+
+impl SessionCoordinator {
+    /// 収集値を呼変数へ格納する（変数名が未設定のノードでは何もしない）
+    pub(crate) fn store_ivr_variable(&mut self, value: &str) {
+        let Some(name) = self.ivr_variable_name.clone() else {
+            return;
+        };
+        info!(
+            "[session {}] IVR variable stored name={} len={}",
+            self.call_id,
+            name,
+            value.chars().count()
+        );
+        self.call_variables.insert(name, value.to_string());
+    }
+
+    /// 案内再生が終わったら発話の取り込みを始める（SPEECH ノードのみ）
+    pub(crate) fn start_ivr_speech_listening(&mut self) {
+        if self.ivr_state != IvrState::IvrMenuWaiting || !self.ivr_input.is_speech() {
+            return;
+        }
+        self.capture.reset();
+        self.capture.start();
+        self.ivr_input.set_listening(true);
+    }
+
+    pub(crate) fn stop_ivr_speech_listening(&mut self) {
+        if matches!(
+            self.ivr_input,
+            IvrInputMode::Speech {
+                listening: true,
+                ..
+            }
+        ) {
+            self.capture.reset();
+        }
+        self.ivr_input.set_listening(false);
+    }
+
+    pub(crate) fn is_ivr_speech_listening(&self) -> bool {
+        self.ivr_state == IvrState::IvrMenuWaiting
+            && matches!(
+                self.ivr_input,
+                IvrInputMode::Speech {
+                    listening: true,
+                    ..
+                }
+            )
+    }
+
+    /// 発話区間が確定したら app へ認識を依頼する（結果は IvrSpeechResult で戻る）
+    pub(crate) fn ingest_ivr_speech(&mut self, payload: &[u8]) {
+        let was_in_speech = self.capture.is_in_speech();
+        let captured = self.capture.ingest(payload);
+        if !was_in_speech && self.capture.is_in_speech() {
+            // 話し始めたら無入力タイムアウトで遮らない
+            self.stop_ivr_timeout();
+        }
+        let Some(pcm_mulaw) = captured else {
+            return;
+        };
+        self.stop_ivr_speech_listening();
+        let classify_intent = match &self.ivr_input {
+            IvrInputMode::Speech { routes, .. } => {
+                routes.iter().any(|route| route.intent.is_some())
+            }
+            _ => false,
+        };
+        info!(
+            "[session {}] IVR speech captured ({} bytes), requesting recognition",
+            self.call_id,
+            pcm_mulaw.len()
+        );
+        if let Err(err) = self.app_tx.try_send_latest(AppEvent::IvrSpeechInput {
+            call_id: self.call_id.clone(),
+            pcm_mulaw,
+            classify_intent,
+        }) {
+            warn!(
+                "[session {}] dropped IvrSpeechInput event: {:?}",
+                self.call_id, err
+            );
+        }
+        // 認識結果が戻らない場合に備えて無入力タイムアウトを張り直す
+        self.reset_ivr_timeout();
+    }
+
+    pub(crate) fn ivr_digit_collection(&self) -> Option<DigitCollection> {
+        match &self.ivr_input {
+            IvrInputMode::Digits(collection) => Some(collection.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn ivr_speech_route(
+        &self,
+        text: &str,
+        intent: Option<&str>,
+    ) -> Option<IvrSpeechRouteRow> {
+        match &self.ivr_input {
+            IvrInputMode::Speech { routes, .. } => {
+                match_speech_route(text, intent, routes).cloned()
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::ports::routing_port::IvrDestinationRow;
+    use uuid::Uuid;
+
+    fn collection(min: usize, max: usize, terminator: Option<char>) -> DigitCollection {
+        DigitCollection {
+            min_digits: min,
+            max_digits: max,
+            terminator,
+            inter_digit_timeout: Duration::from_secs(3),
+        }
+    }
+
+    fn route(keywords: &[&str], intent: Option<&str>) -> IvrSpeechRouteRow {
+        IvrSpeechRouteRow {
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            intent: intent.map(str::to_string),
+            destination: IvrDestinationRow {
+                transition_id: Uuid::new_v4(),
+                node_id: Uuid::new_v4(),
+                action_code: "VR".to_string(),
+                audio_file_url: None,
+                metadata_json: None,
+            },
+        }
+    }
+
+    #[test]
+    fn digits_complete_on_terminator_or_max_length() {
+        let cfg = collection(2, 4, Some('#'));
+        let mut buffer = String::new();
+        assert_eq!(
+            push_collected_digit(&cfg, &mut buffer, '1'),
+            DigitStep::Continue
+        );
+        assert_eq!(
+            push_collected_digit(&cfg, &mut buffer, '*'),
+            DigitStep::Continue
+        );
+        assert_eq!(
+            push_collected_digit(&cfg, &mut buffer, '2'),
+            DigitStep::Continue
+        );
+        assert_eq!(
+            push_collected_digit(&cfg, &mut buffer, '#'),
+            DigitStep::Complete("12".to_string())
+        );
+
+        for digit in ['5', '6', '7'] {
+            assert_eq!(
+                push_collected_digit(&cfg, &mut buffer, digit),
+                DigitStep::Continue
+            );
+        }
+        assert_eq!(
+            push_collected_digit(&cfg, &mut buffer, '8'),
+            DigitStep::Complete("5678".to_string())
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn digits_below_minimum_are_invalid() {
+        let cfg = collection(3, 8, Some('#'));
+        let mut buffer = String::new();
+        push_collected_digit(&cfg, &mut buffer, '1');
+        assert_eq!(
+            push_collected_digit(&cfg, &mut buffer, '#'),
+            DigitStep::Invalid
+        );
+
+        push_collected_digit(&cfg, &mut buffer, '1');
+        push_collected_digit(&cfg, &mut buffer, '2');
+        assert_eq!(
+            finish_collected_digits(&cfg, &mut buffer),
+            DigitStep::Invalid
+        );
+
+        for digit in ['1', '2', '3'] {
+            push_collected_digit(&cfg, &mut buffer, digit);
+        }
+        assert_eq!(
+            finish_collected_digits(&cfg, &mut buffer),
+            DigitStep::Complete("123".to_string())
+        );
+    }
+
+    #[test]
+    fn speech_route_prefers_keywords_over_intent() {
+        let routes = vec![
+            route(&["予約"], Some("reservation")),
+            route(&["キャンセル", "取り消し"], None),
+            route(&[], Some("transfer")),
+        ];
+        let matched = match_speech_route("予約を 取り消し たい。", None, &routes).unwrap();
+        assert_eq!(matched.destination.node_id, routes[0].destination.node_id);
+
+        let matched = match_speech_route("とりけし", Some("Transfer"), &routes).unwrap();
+        assert_eq!(matched.destination.node_id, routes[2].destination.node_id);
+
+        assert!(match_speech_route("こんにちは", Some("general_chat"), &routes).is_none());
+        assert!(match_speech_route("", None, &routes).is_none());
+    }
+}
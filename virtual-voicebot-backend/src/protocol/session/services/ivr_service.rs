@@ -1,5 +1,6 @@
 use chrono::{Local, Timelike};
 use tokio::sync::oneshot;
+use tokio::time::Duration;
 
 use super::super::SessionCoordinator;
 use crate::protocol::session::types::{IvrState, SessionControlIn};
@@ -48,6 +49,11 @@ impl SessionCoordinator {
         let timeout = self
             .ivr_timeout_override
             .unwrap_or(self.runtime_cfg.ivr_timeout);
+        self.start_ivr_timeout_after(timeout);
+    }
+
+    /// 指定時間で IvrTimeout を発火する（複数桁収集の桁間タイムアウトにも使う）
+    pub(crate) fn start_ivr_timeout_after(&mut self, timeout: Duration) {
         let (stop_tx, mut stop_rx) = oneshot::channel();
         let tx = self.control_tx.clone();
         self.ivr_timeout_stop = Some(stop_tx);
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use uuid::Uuid;
+
+use super::super::SessionCoordinator;
+use crate::protocol::session::types::{IvrState, SessState};
+use crate::shared::ports::live_call::{
+    LiveCallError, LiveCallStatus, SupervisorChannel, SupervisorRequest,
+};
+
+impl SessionCoordinator {
+    /// 運用 API に返す現在の通話状態
+    pub(crate) fn live_call_status(&self, state: SessState) -> LiveCallStatus {
+        let caller = if self.caller_identity.privacy {
+            None
+        } else {
+            self.caller_identity.routing_number().map(str::to_string)
+        };
+        LiveCallStatus {
+            call_id: self.call_id.as_str().to_string(),
+            session_state: format!("{:?}", state),
+            ivr_state: format!("{:?}", self.ivr_state),
+            caller,
+            callee: self.to_uri.clone(),
+            outbound: self.outbound_mode,
+            answered_at: self
+                .started_wall
+                .map(|wall| DateTime::<Utc>::from(wall).to_rfc3339()),
+            duration_sec: self
+                .started_at
+                .map(|started| started.elapsed().as_secs())
+                .unwrap_or(0),
+        }
+    }
+
+    /// 運用 API からの転送。待ち呼キューにいる場合はキューから抜けて転送する
+    pub(crate) fn start_api_transfer(&mut self, target: &str) -> Result<(), LiveCallError> {
+        if self.transfer_cancel.is_some() || self.b_leg.is_some() {
+            return Err(LiveCallError::Rejected(
+                "transfer already in progress".to_string(),
+            ));
+        }
+        let Some(group) = self.runtime_cfg.hunt_groups.resolve_target(target) else {
+            return Err(LiveCallError::Rejected(format!(
+                "unknown transfer target: {}",
+                target
+            )));
+        };
+        info!(
+            "[session {}] transfer requested by operator (target={})",
+            self.call_id, target
+        );
+        if self.ivr_state == IvrState::Queued {
+            self.leave_call_queue();
+        }
+        self.cancel_playback();
+        self.stop_ivr_timeout();
+        self.ivr_state = IvrState::Transferring;
+        self.mark_transfer_trying();
+        self.spawn_transfer_group(target, group);
+        Ok(())
+    }
+
+    /// 運用 API からの登録済みアナウンス再生
+    pub(crate) async fn play_api_announcement(
+        &mut self,
+        announcement_id: Uuid,
+    ) -> Result<(), LiveCallError> {
+        self.ensure_api_playback_allowed()?;
+        let Some(path) = self.resolve_announcement_path_by_id(announcement_id).await else {
+            return Err(LiveCallError::Failed(format!(
+                "announcement {} is not available",
+                announcement_id
+            )));
+        };
+        self.play_api_audio(&path).await
+    }
+
+    /// 運用 API からの音声再生。再生中のボット応答・案内は打ち切る
+    pub(crate) async fn play_api_audio(&mut self, path: &str) -> Result<(), LiveCallError> {
+        self.ensure_api_playback_allowed()?;
+        info!(
+            "[session {}] operator audio playback path={}",
+            self.call_id, path
+        );
+        self.start_playback(&[path]).await.map_err(|e| {
+            warn!(
+                "[session {}] operator audio playback failed: {:?}",
+                self.call_id, e
+            );
+            LiveCallError::Failed(e.to_string())
+        })
+    }
+
+    /// 運用 API からの監視者の参加。ささやきは担当者と接続中（B2BUA）の通話だけ
+    pub(crate) fn attach_supervisor(
+        &mut self,
+        request: SupervisorRequest,
+    ) -> Result<SupervisorChannel, LiveCallError> {
+        let bridged = self.ivr_state == IvrState::B2buaMode && self.b_leg.is_some();
+        let channel = self.supervisor.attach(request, bridged)?;
+        info!(
+            "[session {}] supervisor attached (mode={}, channels={:?})",
+            self.call_id,
+            request.mode.as_str(),
+            request.channels
+        );
+        Ok(channel)
+    }
+
+    /// 転送中・転送済み・待ち呼中は A レグへの送出が他の音声と衝突するため受け付けない
+    fn ensure_api_playback_allowed(&self) -> Result<(), LiveCallError> {
+        match self.ivr_state {
+            IvrState::Transferring | IvrState::B2buaMode | IvrState::Queued => Err(
+                LiveCallError::Rejected(format!("playback not allowed in {:?}", self.ivr_state)),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
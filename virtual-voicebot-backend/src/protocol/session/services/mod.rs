@@ -1,3 +1,8 @@
 pub(super) mod b2bua_service;
+pub(super) mod ivr_input_service;
 pub(super) mod ivr_service;
+pub(super) mod live_control_service;
 pub(super) mod playback_service;
+pub(super) mod queue_service;
+pub(super) mod recording_pause_service;
+pub(super) mod voicemail_service;
@@ -10,6 +10,7 @@ use tokio::time::timeout;
 
 use crate::protocol::session::types::{IvrState, PlaybackGenerationId};
 use crate::shared::config;
+use crate::shared::ports::app::AppEvent;
 
 #[derive(Debug)]
 pub(crate) struct PlaybackState {
@@ -30,9 +31,11 @@ impl SessionCoordinator {
                 "[session {}] start_playback skipped: no peer RTP address",
                 self.call_id
             );
+            // 再生できない音声を app が待ち続けないようにする
+            self.notify_playback_finished();
             return Ok(());
         };
-        self.cancel_playback();
+        self.discard_playback();
         self.stop_ivr_timeout();
         let mut frames = Vec::new();
         for path in paths {
@@ -60,6 +63,15 @@ impl SessionCoordinator {
                 return Err(e);
             }
         };
+        self.enqueue_playback_frames(frames, generation_id)
+    }
+
+    /// 変換済み PCMU フレームを再生キューへ積む（同一 generation は追記、別 generation は割り込み）。
+    pub(crate) fn enqueue_playback_frames(
+        &mut self,
+        frames: Vec<Vec<u8>>,
+        generation_id: PlaybackGenerationId,
+    ) -> Result<(), Error> {
         if frames.is_empty() {
             anyhow::bail!("no frames");
         }
@@ -76,7 +88,7 @@ impl SessionCoordinator {
                 "[session {}] interrupt-first enqueue: replace playback old_generation={:?} new_generation={}",
                 self.call_id, self.playback_generation_id, generation_id
             );
-            self.cancel_playback();
+            self.discard_playback();
         } else if self
             .playback_queue
             .front()
@@ -102,9 +114,11 @@ impl SessionCoordinator {
             self.finish_playback(true);
             return;
         }
-        let frame = state.frames[state.index].clone();
+        let mut frame = state.frames[state.index].clone();
         state.index += 1;
+        self.supervisor.mix_to_caller(&mut frame);
         self.recording.push_tx(&frame);
+        self.supervisor.push_callee(&frame);
         self.rtp.send_payload(self.call_id.as_str(), frame);
         self.rtp_last_sent = Some(tokio::time::Instant::now());
         if state.index < state.frames.len() {
@@ -127,6 +141,7 @@ impl SessionCoordinator {
             }
         }
         self.clear_playback_state();
+        self.notify_playback_finished();
 
         if self.announce_mode {
             if self.voicemail_mode {
@@ -161,29 +176,63 @@ impl SessionCoordinator {
             }
         }
 
+        if self.ivr_state == IvrState::Queued {
+            self.play_queue_hold_music();
+            return;
+        }
         if self.ivr_state == IvrState::VoicebotIntroPlaying {
             self.ivr_state = IvrState::VoicebotMode;
             self.capture.reset();
             self.capture.start();
         }
-        if restart_ivr_timeout && self.ivr_state == IvrState::IvrMenuWaiting {
+        if restart_ivr_timeout
+            && matches!(
+                self.ivr_state,
+                IvrState::IvrMenuWaiting | IvrState::VoicemailRetrieval
+            )
+        {
             self.reset_ivr_timeout();
         }
+        if restart_ivr_timeout {
+            self.start_ivr_speech_listening();
+        }
     }
 
+    /// 再生の終了（完了・打ち切り・再生不可）を app へ知らせる（お詫びの後の転送など、再生を待っている処理がある）
+    pub(crate) fn notify_playback_finished(&self) {
+        if let Err(err) = self.app_tx.try_send(AppEvent::PlaybackFinished {
+            call_id: self.call_id.clone(),
+        }) {
+            warn!(
+                "[session {}] failed to notify playback finished: {:?}",
+                self.call_id, err
+            );
+        }
+    }
+
+    /// 再生中・待ちの音声を打ち切る。打ち切った音声があれば app へ知らせる
     pub(crate) fn cancel_playback(&mut self) {
+        if self.discard_playback() {
+            self.notify_playback_finished();
+        }
+    }
+
+    /// 再生中・待ちの音声を捨て、捨てたものがあれば true を返す。
+    /// 新しい音声への差し替えではこちらを使う（差し替えた音声の終わりで app へ知らせる）
+    fn discard_playback(&mut self) -> bool {
         if self.playback.is_none() && self.playback_queue.is_empty() {
             self.sending_audio = false;
-            return;
+            return false;
         }
         info!("[session {}] playback cancelled", self.call_id);
         self.clear_playback_state();
         self.playback_queue.clear();
         self.announce_mode = false;
         self.recording_notice_pending = false;
+        true
     }
 
-    async fn load_frames_with_timeout(&self, path: &str) -> Result<Vec<Vec<u8>>, Error> {
+    pub(crate) async fn load_frames_with_timeout(&self, path: &str) -> Result<Vec<Vec<u8>>, Error> {
         let io_timeout = config::timeouts().recording_io;
         let storage_port = self.storage_port.clone();
         let path = path.to_string();
@@ -223,7 +272,7 @@ impl SessionCoordinator {
         self.sending_audio = false;
     }
 
-    fn begin_playback_frames(
+    pub(crate) fn begin_playback_frames(
         &mut self,
         frames: Vec<Vec<u8>>,
         generation_id: Option<PlaybackGenerationId>,
@@ -0,0 +1,276 @@
+use log::{info, warn};
+
+use super::super::SessionCoordinator;
+use crate::protocol::session::b2bua;
+use crate::protocol::session::types::{IvrState, SessionOut};
+use crate::service::routing::normalize_phone_number_e164;
+use crate::shared::config::{CallQueueConfig, HuntGroup, HuntNoAnswerAction, HuntStrategy};
+
+/// 待ち呼キューに入っている間の状態
+#[derive(Debug)]
+pub(crate) struct QueuedCall {
+    pub(crate) config: CallQueueConfig,
+    /// ループ再生する保留音
+    hold_music: Vec<Vec<u8>>,
+    /// 呼び出し中・接続済みのエージェント
+    pub(crate) agent: Option<String>,
+}
+
+/// 待ち中に押されたキーの意味
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum QueueKeyAction {
+    Voicemail,
+    Callback,
+}
+
+pub(crate) fn queue_key_action(config: &CallQueueConfig, digit: char) -> Option<QueueKeyAction> {
+    if config.voicemail_key == Some(digit) {
+        Some(QueueKeyAction::Voicemail)
+    } else if config.callback_key == Some(digit) {
+        Some(QueueKeyAction::Callback)
+    } else {
+        None
+    }
+}
+
+impl SessionCoordinator {
+    /// 待ち呼キューに入り保留音を流す。未定義のキューなら false
+    pub(crate) async fn enter_call_queue(&mut self, queue: &str, priority: i32) -> bool {
+        let Some(config) = self.runtime_cfg.hunt_groups.queue(queue).cloned() else {
+            warn!(
+                "[session {}] call queue {} is not configured",
+                self.call_id, queue
+            );
+            return false;
+        };
+        let hold_path = match config.moh_announcement_id {
+            Some(announcement_id) => self.resolve_announcement_path_by_id(announcement_id).await,
+            None => None,
+        }
+        .unwrap_or_else(|| super::super::TRANSFER_WAV_PATH.to_string());
+        let hold_music = match self.load_frames_with_timeout(&hold_path).await {
+            Ok(frames) => frames,
+            Err(e) => {
+                warn!(
+                    "[session {}] failed to load hold music {}: {:?}",
+                    self.call_id, hold_path, e
+                );
+                Vec::new()
+            }
+        };
+        info!(
+            "[session {}] entering call queue={} priority={}",
+            self.call_id, config.name, priority
+        );
+        self.cancel_playback();
+        self.stop_ivr_timeout();
+        self.ivr_state = IvrState::Queued;
+        self.send_queue_event(SessionOut::QueueJoin {
+            queue: config.name.clone(),
+            priority,
+        });
+        self.queue = Some(QueuedCall {
+            config,
+            hold_music,
+            agent: None,
+        });
+        self.play_queue_hold_music();
+        true
+    }
+
+    /// 保留音を先頭から流す（再生が終わるたびに finish_playback から呼ばれる）
+    pub(crate) fn play_queue_hold_music(&mut self) {
+        if self.ivr_state != IvrState::Queued {
+            return;
+        }
+        let Some(frames) = self
+            .queue
+            .as_ref()
+            .map(|queued| queued.hold_music.clone())
+            .filter(|frames| !frames.is_empty())
+        else {
+            return;
+        };
+        if let Err(e) = self.begin_playback_frames(frames, None) {
+            warn!(
+                "[session {}] failed to start hold music: {:?}",
+                self.call_id, e
+            );
+        }
+    }
+
+    /// 待ち順位の案内で保留音を割り込む（案内が終われば保留音に戻る）
+    pub(crate) async fn play_queue_position(&mut self, position: usize, audio_path: &str) {
+        if self.ivr_state != IvrState::Queued || self.queue_agent().is_some() {
+            return;
+        }
+        info!(
+            "[session {}] announcing queue position={}",
+            self.call_id, position
+        );
+        match self.load_frames_with_timeout(audio_path).await {
+            Ok(frames) => {
+                self.cancel_playback();
+                if let Err(e) = self.begin_playback_frames(frames, None) {
+                    warn!(
+                        "[session {}] failed to play queue position: {:?}",
+                        self.call_id, e
+                    );
+                }
+            }
+            Err(e) => warn!(
+                "[session {}] failed to load queue position audio: {:?}",
+                self.call_id, e
+            ),
+        }
+    }
+
+    /// キューが割り当てたエージェントを既存の転送経路で呼び出す
+    pub(crate) fn connect_queue_agent(&mut self, agent: String) {
+        if self.ivr_state != IvrState::Queued
+            || self.transfer_cancel.is_some()
+            || self.b_leg.is_some()
+        {
+            warn!(
+                "[session {}] queue agent {} ignored in {:?}",
+                self.call_id, agent, self.ivr_state
+            );
+            return;
+        }
+        let Some(queued) = self.queue.as_mut() else {
+            return;
+        };
+        info!(
+            "[session {}] connecting queue={} to agent={}",
+            self.call_id, queued.config.name, agent
+        );
+        queued.agent = Some(agent.clone());
+        let group = HuntGroup::new(
+            queued.config.name.clone(),
+            HuntStrategy::Sequential,
+            vec![agent],
+            queued.config.agent_ring_timeout,
+            HuntNoAnswerAction::Hangup,
+        );
+        self.transfer_no_answer = HuntNoAnswerAction::Hangup;
+        // 前回の呼び出し失敗時の終了時刻を持ち越さない
+        self.transfer_ended_at = None;
+        self.mark_transfer_trying();
+        self.transfer_cancel = Some(b2bua::spawn_transfer(
+            self.call_id.clone(),
+            self.from_uri.clone(),
+            group,
+            self.control_tx.clone(),
+            self.media_tx.clone(),
+            self.runtime_cfg.clone(),
+        ));
+    }
+
+    /// エージェントが応答しなかったので待ちに戻る
+    pub(crate) fn handle_queue_connect_failed(&mut self) {
+        let Some(queued) = self.queue.as_mut() else {
+            return;
+        };
+        let Some(agent) = queued.agent.take() else {
+            return;
+        };
+        let queue = queued.config.name.clone();
+        self.send_queue_event(SessionOut::QueueConnectFailed { queue, agent });
+        if self.playback.is_none() {
+            self.play_queue_hold_music();
+        }
+    }
+
+    /// キューから抜ける。待ち中なら QueueLeave、エージェント接続後なら QueueAgentReleased を送る
+    pub(crate) fn leave_call_queue(&mut self) {
+        let Some(queued) = self.queue.take() else {
+            return;
+        };
+        let queue = queued.config.name;
+        let event = match queued.agent {
+            Some(agent) => SessionOut::QueueAgentReleased { queue, agent },
+            None => SessionOut::QueueLeave { queue },
+        };
+        self.send_queue_event(event);
+    }
+
+    /// 折り返し依頼を受け付けてキューから抜ける
+    pub(crate) fn request_queue_callback(&mut self) {
+        let Some(queued) = self.queue.take() else {
+            return;
+        };
+        let caller_number = self.queue_callback_number();
+        info!(
+            "[session {}] callback requested in queue={}",
+            self.call_id, queued.config.name
+        );
+        self.send_queue_event(SessionOut::QueueCallbackRequested {
+            queue: queued.config.name,
+            caller_number,
+        });
+    }
+
+    pub(crate) fn queue_agent(&self) -> Option<&str> {
+        self.queue
+            .as_ref()
+            .and_then(|queued| queued.agent.as_deref())
+    }
+
+    pub(crate) fn queue_key_action(&self, digit: char) -> Option<QueueKeyAction> {
+        self.queue
+            .as_ref()
+            .and_then(|queued| queue_key_action(&queued.config, digit))
+    }
+
+    /// 非通知の発信者には折り返し番号を渡さない
+    fn queue_callback_number(&self) -> Option<String> {
+        if self.caller_identity.privacy {
+            return None;
+        }
+        self.caller_identity
+            .asserted_number
+            .as_deref()
+            .map(|number| {
+                normalize_phone_number_e164(number).unwrap_or_else(|_| number.to_string())
+            })
+            .or_else(|| super::super::extract_e164_caller_number(self.from_uri.as_str()))
+    }
+
+    fn send_queue_event(&self, event: SessionOut) {
+        if let Err(err) = self.session_out_tx.try_send((self.call_id.clone(), event)) {
+            warn!(
+                "[session {}] dropped call queue event (channel full): {:?}",
+                self.call_id, err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn queue_key_action_matches_configured_keys() {
+        let config = CallQueueConfig {
+            name: "support".to_string(),
+            agents: vec!["sip:101@pbx".to_string()],
+            moh_announcement_id: None,
+            position_interval: Duration::from_secs(30),
+            agent_ring_timeout: Duration::from_secs(15),
+            wrap_up: Duration::from_secs(10),
+            voicemail_key: Some('1'),
+            callback_key: Some('#'),
+        };
+        assert_eq!(
+            queue_key_action(&config, '1'),
+            Some(QueueKeyAction::Voicemail)
+        );
+        assert_eq!(
+            queue_key_action(&config, '#'),
+            Some(QueueKeyAction::Callback)
+        );
+        assert_eq!(queue_key_action(&config, '2'), None);
+    }
+}
@@ -0,0 +1,114 @@
+use log::info;
+
+use super::super::SessionCoordinator;
+use crate::protocol::session::types::IvrState;
+use crate::shared::config;
+use crate::shared::ports::call_event_port::CallEvent;
+use crate::shared::ports::live_call::LiveCallError;
+
+/// DTMF の並びとして覚えておく桁数の上限
+const MAX_DTMF_SEQUENCE_LEN: usize = 8;
+
+impl SessionCoordinator {
+    /// 録音を一時停止する（カード番号の読み上げなど）。全レグを無音にし、ASR にも音声を渡さない
+    pub(crate) fn pause_recording(&mut self, paused_by: &str) -> bool {
+        if !self.recording.pause(paused_by) {
+            return false;
+        }
+        info!(
+            "[session {}] recording paused (by={})",
+            self.call_id, paused_by
+        );
+        // 停止前から話している途中の発話は捨てる（停止後の音声と一緒に認識させない）
+        if self.ivr_state == IvrState::VoicebotMode {
+            self.capture.reset();
+        }
+        self.publish_call_event(CallEvent::RecordingPaused {
+            by: paused_by.to_string(),
+        });
+        true
+    }
+
+    pub(crate) fn resume_recording(&mut self, resumed_by: &str) -> bool {
+        if !self.recording.resume(resumed_by) {
+            return false;
+        }
+        info!(
+            "[session {}] recording resumed (by={})",
+            self.call_id, resumed_by
+        );
+        if self.ivr_state == IvrState::VoicebotMode {
+            self.capture.reset();
+            self.capture.start();
+        }
+        self.publish_call_event(CallEvent::RecordingResumed {
+            by: resumed_by.to_string(),
+        });
+        true
+    }
+
+    /// 運用 API からの一時停止・再開
+    pub(crate) fn set_recording_paused_by_api(
+        &mut self,
+        paused: bool,
+    ) -> Result<(), LiveCallError> {
+        let changed = if paused {
+            self.pause_recording("api")
+        } else {
+            self.resume_recording("api")
+        };
+        if changed {
+            Ok(())
+        } else {
+            Err(LiveCallError::Rejected(
+                if paused {
+                    "recording is already paused"
+                } else {
+                    "recording is not paused"
+                }
+                .to_string(),
+            ))
+        }
+    }
+
+    /// 一時停止・再開の DTMF の並びを見る。並びが揃ったら true（その桁は IVR に渡さない）
+    pub(crate) fn handle_recording_dtmf(&mut self, digit: char) -> bool {
+        let pause = config::recording_pause_dtmf();
+        let resume = config::recording_resume_dtmf();
+        if pause.is_none() && resume.is_none() {
+            return false;
+        }
+        self.dtmf_sequence.push(digit);
+        if self.dtmf_sequence.len() > MAX_DTMF_SEQUENCE_LEN {
+            self.dtmf_sequence.remove(0);
+        }
+        let matched = |sequence: Option<&str>| {
+            sequence.is_some_and(|sequence| self.dtmf_sequence.ends_with(sequence))
+        };
+        let handled = if matched(pause) {
+            self.pause_recording("dtmf");
+            true
+        } else if matched(resume) {
+            self.resume_recording("dtmf");
+            true
+        } else {
+            false
+        };
+        if handled {
+            self.dtmf_sequence.clear();
+        }
+        handled
+    }
+
+    /// 一時停止のまま放置された録音を再開する（MediaTimerTick から呼ぶ）
+    pub(crate) fn check_recording_pause_timeout(&mut self) {
+        let (Some(max), Some(paused_for)) =
+            (config::recording_pause_max(), self.recording.paused_for())
+        else {
+            return;
+        };
+        if paused_for >= max {
+            self.resume_recording("timeout");
+        }
+    }
+}
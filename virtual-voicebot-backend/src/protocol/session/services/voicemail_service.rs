@@ -0,0 +1,553 @@
+use std::io::ErrorKind;
+
+use chrono::Utc;
+use log::{info, warn};
+
+use super::super::SessionCoordinator;
+use crate::protocol::session::types::{IvrState, SessionControlIn};
+use crate::service::routing::normalize_phone_number_e164;
+use crate::shared::config;
+use crate::shared::ports::voicemail_port::{
+    hash_pin, VoicemailMailbox, VoicemailMessage, MESSAGE_STATUS_DELETED, MESSAGE_STATUS_SAVED,
+};
+
+/// メールボックス番号・PIN の最大桁数（これを超えた入力は捨てる）
+const MAX_INPUT_DIGITS: usize = 16;
+
+/// 留守番電話の再生メニュー（アクセス番号への着信）の進行状態
+#[derive(Debug)]
+pub(crate) struct VoicemailRetrieval {
+    stage: RetrievalStage,
+    digits: String,
+    failures: u32,
+    timeouts: u32,
+}
+
+#[derive(Debug)]
+enum RetrievalStage {
+    Mailbox,
+    /// メールボックスの存在有無は PIN 照合まで明かさない
+    Pin {
+        mailbox_number: String,
+    },
+    Menu {
+        mailbox: VoicemailMailbox,
+        messages: Vec<VoicemailMessage>,
+        current: Option<usize>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DigitInput {
+    Pending,
+    Complete(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VoicemailMenuAction {
+    Next,
+    Replay,
+    Delete,
+    Save,
+    Exit,
+    Invalid,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RetrievalStep {
+    /// 入力途中（タイムアウトだけ張り直す）
+    Wait,
+    Prompt(Vec<String>),
+    /// 指定の案内のあとに終了案内を流して切断する
+    Goodbye(Vec<String>),
+}
+
+impl VoicemailRetrieval {
+    pub(crate) fn new() -> Self {
+        Self {
+            stage: RetrievalStage::Mailbox,
+            digits: String::new(),
+            failures: 0,
+            timeouts: 0,
+        }
+    }
+
+    /// '#' で確定、'*' で入力し直し
+    fn collect_digit(&mut self, digit: char) -> DigitInput {
+        match digit {
+            '#' => DigitInput::Complete(std::mem::take(&mut self.digits)),
+            '*' => {
+                self.digits.clear();
+                DigitInput::Pending
+            }
+            d if d.is_ascii_digit() => {
+                if self.digits.len() < MAX_INPUT_DIGITS {
+                    self.digits.push(d);
+                }
+                DigitInput::Pending
+            }
+            _ => DigitInput::Pending,
+        }
+    }
+
+    fn stage_prompt(&self) -> &'static str {
+        match self.stage {
+            RetrievalStage::Mailbox => super::super::VOICEMAIL_ENTER_MAILBOX_WAV_PATH,
+            RetrievalStage::Pin { .. } => super::super::VOICEMAIL_ENTER_PIN_WAV_PATH,
+            RetrievalStage::Menu { .. } => super::super::VOICEMAIL_MENU_WAV_PATH,
+        }
+    }
+
+    /// 認証失敗を数え、上限に達したら true
+    fn record_auth_failure(&mut self, max_attempts: u32) -> bool {
+        self.failures += 1;
+        self.stage = RetrievalStage::Mailbox;
+        self.failures >= max_attempts
+    }
+}
+
+pub(crate) fn voicemail_menu_action_for_digit(digit: char) -> VoicemailMenuAction {
+    match digit {
+        '1' => VoicemailMenuAction::Next,
+        '2' => VoicemailMenuAction::Replay,
+        '7' => VoicemailMenuAction::Delete,
+        '9' => VoicemailMenuAction::Save,
+        '*' => VoicemailMenuAction::Exit,
+        _ => VoicemailMenuAction::Invalid,
+    }
+}
+
+fn next_message_index(current: Option<usize>, len: usize) -> Option<usize> {
+    let next = current.map(|index| index + 1).unwrap_or(0);
+    (next < len).then_some(next)
+}
+
+/// 着信番号がアクセス番号か（表記ゆれは E.164 に正規化して比較）
+pub(crate) fn is_voicemail_access_number(
+    called_number: Option<&str>,
+    access_number: Option<&str>,
+) -> bool {
+    let (Some(called), Some(access)) = (called_number, access_number) else {
+        return false;
+    };
+    if called == access {
+        return true;
+    }
+    match (
+        normalize_phone_number_e164(called),
+        normalize_phone_number_e164(access),
+    ) {
+        (Ok(called), Ok(access)) => called == access,
+        _ => false,
+    }
+}
+
+impl SessionCoordinator {
+    /// INVITE 時点で再生メニュー通話として扱う（ルーティング評価は行わない）
+    pub(crate) fn enter_voicemail_retrieval(&mut self) {
+        info!("[session {}] voicemail retrieval call", self.call_id);
+        self.voicemail_retrieval = Some(VoicemailRetrieval::new());
+        self.register_action_for_call_log("VM");
+        // PIN の DTMF や他人のメッセージを録音に残さない
+        self.set_recording_enabled(false);
+    }
+
+    pub(crate) fn is_voicemail_retrieval(&self) -> bool {
+        self.voicemail_retrieval.is_some()
+    }
+
+    pub(crate) async fn start_voicemail_retrieval(&mut self) {
+        self.ivr_state = IvrState::VoicemailRetrieval;
+        self.play_voicemail_prompts(&[super::super::VOICEMAIL_ENTER_MAILBOX_WAV_PATH.to_string()])
+            .await;
+    }
+
+    pub(crate) async fn handle_voicemail_dtmf(&mut self, digit: char) {
+        // 終了案内の再生中は入力を受け付けない（切断まで流し切る）
+        let Some(mut retrieval) = self.voicemail_retrieval.take() else {
+            return;
+        };
+        self.cancel_playback();
+        self.stop_ivr_timeout();
+        retrieval.timeouts = 0;
+        let step = self
+            .advance_voicemail_retrieval(&mut retrieval, digit)
+            .await;
+        self.voicemail_retrieval = Some(retrieval);
+        self.apply_voicemail_step(step).await;
+    }
+
+    pub(crate) async fn handle_voicemail_timeout(&mut self) {
+        let max_attempts = config::voicemail_config().max_pin_attempts;
+        let Some(retrieval) = self.voicemail_retrieval.as_mut() else {
+            return;
+        };
+        retrieval.digits.clear();
+        retrieval.timeouts += 1;
+        let step = if retrieval.timeouts >= max_attempts {
+            info!(
+                "[session {}] voicemail retrieval timed out, hanging up",
+                self.call_id
+            );
+            RetrievalStep::Goodbye(Vec::new())
+        } else {
+            RetrievalStep::Prompt(vec![retrieval.stage_prompt().to_string()])
+        };
+        self.apply_voicemail_step(step).await;
+    }
+
+    async fn advance_voicemail_retrieval(
+        &self,
+        retrieval: &mut VoicemailRetrieval,
+        digit: char,
+    ) -> RetrievalStep {
+        if matches!(retrieval.stage, RetrievalStage::Menu { .. }) {
+            return self.handle_voicemail_menu(retrieval, digit).await;
+        }
+        let input = match retrieval.collect_digit(digit) {
+            DigitInput::Pending => return RetrievalStep::Wait,
+            DigitInput::Complete(input) => input,
+        };
+        let max_attempts = config::voicemail_config().max_pin_attempts;
+        match std::mem::replace(&mut retrieval.stage, RetrievalStage::Mailbox) {
+            RetrievalStage::Mailbox => {
+                if input.is_empty() {
+                    return RetrievalStep::Prompt(vec![
+                        super::super::IVR_INVALID_WAV_PATH.to_string(),
+                        super::super::VOICEMAIL_ENTER_MAILBOX_WAV_PATH.to_string(),
+                    ]);
+                }
+                retrieval.stage = RetrievalStage::Pin {
+                    mailbox_number: input,
+                };
+                RetrievalStep::Prompt(vec![super::super::VOICEMAIL_ENTER_PIN_WAV_PATH.to_string()])
+            }
+            RetrievalStage::Pin { mailbox_number } => {
+                let mailbox = match self.voicemail_port.find_mailbox(&mailbox_number).await {
+                    Ok(mailbox) => mailbox,
+                    Err(err) => {
+                        warn!(
+                            "[session {}] voicemail mailbox lookup failed: {}",
+                            self.call_id, err
+                        );
+                        return RetrievalStep::Goodbye(Vec::new());
+                    }
+                };
+                let mailbox = match mailbox {
+                    // ロック中は PIN を照合しない（案内は誤りと同じにしてロックの有無も明かさない）
+                    Some(mailbox) if mailbox.is_pin_locked(Utc::now()) => {
+                        warn!(
+                            "[session {}] voicemail mailbox={} is locked until {:?}",
+                            self.call_id, mailbox.mailbox_number, mailbox.pin_locked_until
+                        );
+                        None
+                    }
+                    Some(mailbox) if mailbox.verify_pin(&input) => {
+                        self.on_voicemail_pin_verified(&mailbox, &input).await;
+                        Some(mailbox)
+                    }
+                    Some(mailbox) => {
+                        self.record_voicemail_pin_failure(&mailbox).await;
+                        None
+                    }
+                    None => None,
+                };
+                let Some(mailbox) = mailbox else {
+                    warn!(
+                        "[session {}] voicemail authentication failed attempt={}",
+                        self.call_id,
+                        retrieval.failures + 1
+                    );
+                    if retrieval.record_auth_failure(max_attempts) {
+                        return RetrievalStep::Goodbye(vec![
+                            super::super::IVR_INVALID_WAV_PATH.to_string()
+                        ]);
+                    }
+                    return RetrievalStep::Prompt(vec![
+                        super::super::IVR_INVALID_WAV_PATH.to_string(),
+                        super::super::VOICEMAIL_ENTER_MAILBOX_WAV_PATH.to_string(),
+                    ]);
+                };
+                let messages = match self.voicemail_port.list_messages(mailbox.id).await {
+                    Ok(messages) => messages,
+                    Err(err) => {
+                        warn!(
+                            "[session {}] voicemail message list failed: {}",
+                            self.call_id, err
+                        );
+                        return RetrievalStep::Goodbye(Vec::new());
+                    }
+                };
+                info!(
+                    "[session {}] voicemail mailbox={} authenticated messages={}",
+                    self.call_id,
+                    mailbox.mailbox_number,
+                    messages.len()
+                );
+                if messages.is_empty() {
+                    return RetrievalStep::Goodbye(vec![
+                        super::super::VOICEMAIL_NO_MESSAGES_WAV_PATH.to_string(),
+                    ]);
+                }
+                retrieval.failures = 0;
+                retrieval.stage = RetrievalStage::Menu {
+                    mailbox,
+                    messages,
+                    current: None,
+                };
+                RetrievalStep::Prompt(vec![super::super::VOICEMAIL_MENU_WAV_PATH.to_string()])
+            }
+            RetrievalStage::Menu { .. } => RetrievalStep::Wait,
+        }
+    }
+
+    async fn on_voicemail_pin_verified(&self, mailbox: &VoicemailMailbox, pin: &str) {
+        if let Err(err) = self.voicemail_port.reset_pin_failures(mailbox.id).await {
+            warn!(
+                "[session {}] voicemail pin failure reset failed: {}",
+                self.call_id, err
+            );
+        }
+        if mailbox.needs_pin_rehash() {
+            if let Err(err) = self
+                .voicemail_port
+                .update_pin_hash(mailbox.id, hash_pin(pin))
+                .await
+            {
+                warn!(
+                    "[session {}] voicemail pin rehash failed: {}",
+                    self.call_id, err
+                );
+            }
+        }
+    }
+
+    /// 通話をまたいだ連続誤りをメールボックスに記録する（上限でロック）
+    async fn record_voicemail_pin_failure(&self, mailbox: &VoicemailMailbox) {
+        let cfg = config::voicemail_config();
+        match self
+            .voicemail_port
+            .record_pin_failure(mailbox.id, cfg.pin_lockout_attempts, cfg.pin_lockout)
+            .await
+        {
+            Ok(Some(locked_until)) => warn!(
+                "[session {}] voicemail mailbox={} locked until {} after repeated pin failures",
+                self.call_id, mailbox.mailbox_number, locked_until
+            ),
+            Ok(None) => {}
+            Err(err) => warn!(
+                "[session {}] voicemail pin failure record failed: {}",
+                self.call_id, err
+            ),
+        }
+    }
+
+    async fn handle_voicemail_menu(
+        &self,
+        retrieval: &mut VoicemailRetrieval,
+        digit: char,
+    ) -> RetrievalStep {
+        let RetrievalStage::Menu {
+            messages, current, ..
+        } = &mut retrieval.stage
+        else {
+            return RetrievalStep::Wait;
+        };
+        let menu = super::super::VOICEMAIL_MENU_WAV_PATH.to_string();
+        let invalid = RetrievalStep::Prompt(vec![
+            super::super::IVR_INVALID_WAV_PATH.to_string(),
+            menu.clone(),
+        ]);
+        match voicemail_menu_action_for_digit(digit) {
+            VoicemailMenuAction::Next => match next_message_index(*current, messages.len()) {
+                Some(index) => {
+                    *current = Some(index);
+                    let message = &messages[index];
+                    if let Err(err) = self.voicemail_port.mark_listened(message.id).await {
+                        warn!(
+                            "[session {}] failed to mark voicemail listened id={}: {}",
+                            self.call_id, message.id, err
+                        );
+                    }
+                    RetrievalStep::Prompt(vec![message.file_path.clone(), menu])
+                }
+                None => RetrievalStep::Prompt(vec![
+                    super::super::VOICEMAIL_END_OF_MESSAGES_WAV_PATH.to_string(),
+                    menu,
+                ]),
+            },
+            VoicemailMenuAction::Replay => match current.and_then(|index| messages.get(index)) {
+                Some(message) => RetrievalStep::Prompt(vec![message.file_path.clone(), menu]),
+                None => invalid,
+            },
+            VoicemailMenuAction::Delete => {
+                let Some(index) = *current else {
+                    return invalid;
+                };
+                let message = messages.remove(index);
+                *current = index.checked_sub(1);
+                if let Err(err) = self
+                    .voicemail_port
+                    .update_status(message.id, MESSAGE_STATUS_DELETED)
+                    .await
+                {
+                    warn!(
+                        "[session {}] failed to delete voicemail id={}: {}",
+                        self.call_id, message.id, err
+                    );
+                }
+                match tokio::fs::remove_file(&message.file_path).await {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == ErrorKind::NotFound => {}
+                    Err(err) => warn!(
+                        "[session {}] failed to remove voicemail file path={}: {:?}",
+                        self.call_id, message.file_path, err
+                    ),
+                }
+                RetrievalStep::Prompt(vec![
+                    super::super::VOICEMAIL_DELETED_WAV_PATH.to_string(),
+                    menu,
+                ])
+            }
+            VoicemailMenuAction::Save => {
+                let Some(message) = current.and_then(|index| messages.get_mut(index)) else {
+                    return invalid;
+                };
+                if let Err(err) = self
+                    .voicemail_port
+                    .update_status(message.id, MESSAGE_STATUS_SAVED)
+                    .await
+                {
+                    warn!(
+                        "[session {}] failed to save voicemail id={}: {}",
+                        self.call_id, message.id, err
+                    );
+                }
+                message.status = MESSAGE_STATUS_SAVED.to_string();
+                RetrievalStep::Prompt(vec![
+                    super::super::VOICEMAIL_SAVED_WAV_PATH.to_string(),
+                    menu,
+                ])
+            }
+            VoicemailMenuAction::Exit => RetrievalStep::Goodbye(Vec::new()),
+            VoicemailMenuAction::Invalid => invalid,
+        }
+    }
+
+    async fn apply_voicemail_step(&mut self, step: RetrievalStep) {
+        match step {
+            RetrievalStep::Wait => self.reset_ivr_timeout(),
+            RetrievalStep::Prompt(paths) => self.play_voicemail_prompts(&paths).await,
+            RetrievalStep::Goodbye(mut paths) => {
+                self.voicemail_retrieval = None;
+                self.stop_ivr_timeout();
+                paths.push(super::super::VOICEMAIL_GOODBYE_WAV_PATH.to_string());
+                let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+                match self.start_playback(&paths).await {
+                    // 再生完了で切断する（finish_playback のアナウンス終了処理）
+                    Ok(()) => self.announce_mode = true,
+                    Err(err) => {
+                        warn!(
+                            "[session {}] failed to play voicemail goodbye: {:?}",
+                            self.call_id, err
+                        );
+                        let _ = self.control_tx.try_send(SessionControlIn::AppHangup);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn play_voicemail_prompts(&mut self, paths: &[String]) {
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        if let Err(err) = self.start_playback(&paths).await {
+            warn!(
+                "[session {}] failed to play voicemail prompt: {:?}",
+                self.call_id, err
+            );
+            self.reset_ivr_timeout();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_digit_completes_on_hash_and_clears_on_star() {
+        let mut retrieval = VoicemailRetrieval::new();
+        assert_eq!(retrieval.collect_digit('1'), DigitInput::Pending);
+        assert_eq!(retrieval.collect_digit('9'), DigitInput::Pending);
+        assert_eq!(retrieval.collect_digit('*'), DigitInput::Pending);
+        assert_eq!(retrieval.collect_digit('1'), DigitInput::Pending);
+        assert_eq!(retrieval.collect_digit('0'), DigitInput::Pending);
+        assert_eq!(
+            retrieval.collect_digit('#'),
+            DigitInput::Complete("10".to_string())
+        );
+        assert_eq!(
+            retrieval.collect_digit('#'),
+            DigitInput::Complete(String::new())
+        );
+    }
+
+    #[test]
+    fn auth_failures_return_to_mailbox_prompt_until_limit() {
+        let mut retrieval = VoicemailRetrieval::new();
+        retrieval.stage = RetrievalStage::Pin {
+            mailbox_number: "1001".to_string(),
+        };
+        assert!(!retrieval.record_auth_failure(3));
+        assert!(matches!(retrieval.stage, RetrievalStage::Mailbox));
+        assert!(!retrieval.record_auth_failure(3));
+        assert!(retrieval.record_auth_failure(3));
+    }
+
+    #[test]
+    fn menu_digits_map_to_actions() {
+        assert_eq!(
+            voicemail_menu_action_for_digit('1'),
+            VoicemailMenuAction::Next
+        );
+        assert_eq!(
+            voicemail_menu_action_for_digit('2'),
+            VoicemailMenuAction::Replay
+        );
+        assert_eq!(
+            voicemail_menu_action_for_digit('7'),
+            VoicemailMenuAction::Delete
+        );
+        assert_eq!(
+            voicemail_menu_action_for_digit('9'),
+            VoicemailMenuAction::Save
+        );
+        assert_eq!(
+            voicemail_menu_action_for_digit('*'),
+            VoicemailMenuAction::Exit
+        );
+        assert_eq!(
+            voicemail_menu_action_for_digit('5'),
+            VoicemailMenuAction::Invalid
+        );
+        assert_eq!(next_message_index(None, 2), Some(0));
+        assert_eq!(next_message_index(Some(0), 2), Some(1));
+        assert_eq!(next_message_index(Some(1), 2), None);
+        assert_eq!(next_message_index(None, 0), None);
+    }
+
+    #[test]
+    fn access_number_matches_across_number_formats() {
+        assert!(is_voicemail_access_number(
+            Some("0501234567"),
+            Some("+81501234567")
+        ));
+        assert!(is_voicemail_access_number(Some("1417"), Some("1417")));
+        assert!(!is_voicemail_access_number(
+            Some("0501234568"),
+            Some("+81501234567")
+        ));
+        assert!(!is_voicemail_access_number(Some("0501234567"), None));
+        assert!(!is_voicemail_access_number(None, Some("1417")));
+    }
+}
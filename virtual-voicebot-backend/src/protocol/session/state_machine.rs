@@ -69,6 +69,7 @@ mod tests {
             call_id: CallId::new("call".to_string()).expect("valid test call id"),
             from: "from".to_string(),
             to: "to".to_string(),
+            identity: Default::default(),
             offer: super::super::types::Sdp::pcmu("127.0.0.1", 10000),
             session_timer: None,
         };
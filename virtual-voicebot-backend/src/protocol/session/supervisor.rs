@@ -0,0 +1,334 @@
+//! 監視者（スーパーバイザ）向けの音声タップとミキサ。
+//!
+//! 録音と同じ位置（A レグの受信・送信）で音声を受け、MediaTimerTick ごとに 20ms 分を
+//! 監視者へ送る。ささやき・割り込みの監視者音声も同じ tick で 1 フレームずつ取り出し、
+//! B レグ（担当者）向け・A レグ（発信者）向けの送出フレームに足し込む。
+
+use std::collections::VecDeque;
+
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::{TryRecvError, TrySendError};
+
+use crate::protocol::rtp::codec::mulaw_to_linear16;
+use crate::shared::audio::{linear16_to_mulaw, PCMU_FRAME_SAMPLES};
+use crate::shared::ports::live_call::{
+    LiveCallError, MonitorChannels, SupervisorChannel, SupervisorMode, SupervisorRequest,
+};
+
+/// 監視者へ送るフレームのバッファ（読み出しが詰まった接続の分は捨てる）
+const MONITOR_CHANNEL_FRAMES: usize = 50;
+/// 監視者音声の受信バッファ
+const TALK_CHANNEL_CHUNKS: usize = 50;
+/// 各キューに溜める上限（1 秒）。tick が追いつかない分は古い方から捨てる
+const MAX_QUEUED_SAMPLES: usize = PCMU_FRAME_SAMPLES * 50;
+/// 送出フレームへの足し込み待ちの上限（A/B レグの送出と tick のずれを吸収する分だけ）
+const MAX_PENDING_FRAMES: usize = 3;
+
+struct MonitorTap {
+    channels: MonitorChannels,
+    frames: mpsc::Sender<Vec<i16>>,
+}
+
+struct TalkTap {
+    mode: SupervisorMode,
+    audio: mpsc::Receiver<Vec<i16>>,
+}
+
+#[derive(Default)]
+pub(crate) struct SupervisorMixer {
+    monitors: Vec<MonitorTap>,
+    /// 話せる監視者は 1 通話に 1 人まで
+    talker: Option<TalkTap>,
+    /// A レグ受信（発信者の声）
+    caller: VecDeque<i16>,
+    /// A レグ送信（担当者・ボットの声）
+    callee: VecDeque<i16>,
+    talk: VecDeque<i16>,
+    to_caller: VecDeque<Vec<i16>>,
+    to_agent: VecDeque<Vec<i16>>,
+}
+
+impl SupervisorMixer {
+    /// 監視者を追加する。`bridged` は B レグと接続中か（ささやきは担当者がいないと意味がない）
+    pub(crate) fn attach(
+        &mut self,
+        request: SupervisorRequest,
+        bridged: bool,
+    ) -> Result<SupervisorChannel, LiveCallError> {
+        let talks = request.mode != SupervisorMode::Listen;
+        if talks {
+            if self.talker.is_some() {
+                return Err(LiveCallError::Rejected(
+                    "another supervisor is already talking on this call".to_string(),
+                ));
+            }
+            if request.mode == SupervisorMode::Whisper && !bridged {
+                return Err(LiveCallError::Rejected(
+                    "whisper requires a call bridged to an agent".to_string(),
+                ));
+            }
+        }
+        let (frame_tx, frame_rx) = mpsc::channel(MONITOR_CHANNEL_FRAMES);
+        self.monitors.push(MonitorTap {
+            channels: request.channels,
+            frames: frame_tx,
+        });
+        let talk = talks.then(|| {
+            let (talk_tx, talk_rx) = mpsc::channel(TALK_CHANNEL_CHUNKS);
+            self.talker = Some(TalkTap {
+                mode: request.mode,
+                audio: talk_rx,
+            });
+            talk_tx
+        });
+        Ok(SupervisorChannel {
+            frames: frame_rx,
+            talk,
+        })
+    }
+
+    fn is_active(&self) -> bool {
+        !self.monitors.is_empty()
+    }
+
+    /// 発信者から受けた音声（μ-law）
+    pub(crate) fn push_caller(&mut self, payload: &[u8]) {
+        if self.is_active() {
+            push_mulaw(&mut self.caller, payload);
+        }
+    }
+
+    /// 発信者へ送った音声（μ-law、監視者の割り込み音声を足し込んだ後）
+    pub(crate) fn push_callee(&mut self, payload: &[u8]) {
+        if self.is_active() {
+            push_mulaw(&mut self.callee, payload);
+        }
+    }
+
+    /// 20ms ごとに 1 フレームずつ監視者へ送り、監視者音声を 1 フレーム取り出す
+    pub(crate) fn tick(&mut self) {
+        if !self.is_active() {
+            return;
+        }
+        let caller = pop_frame(&mut self.caller);
+        let callee = pop_frame(&mut self.callee);
+        self.monitors.retain(|tap| {
+            let frame = match tap.channels {
+                MonitorChannels::Mixed => caller
+                    .iter()
+                    .zip(&callee)
+                    .map(|(a, b)| a.saturating_add(*b))
+                    .collect(),
+                MonitorChannels::Legs => caller
+                    .iter()
+                    .zip(&callee)
+                    .flat_map(|(a, b)| [*a, *b])
+                    .collect(),
+            };
+            !matches!(tap.frames.try_send(frame), Err(TrySendError::Closed(_)))
+        });
+        if !self.is_active() {
+            // 最後の監視者が抜けた（話している監視者も同じ接続なので一緒に外す）
+            *self = Self::default();
+            return;
+        }
+        self.tick_talker();
+    }
+
+    fn tick_talker(&mut self) {
+        let Some(talker) = self.talker.as_mut() else {
+            return;
+        };
+        let mode = talker.mode;
+        let mut disconnected = false;
+        loop {
+            match talker.audio.try_recv() {
+                Ok(chunk) => self.talk.extend(chunk),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                }
+            }
+        }
+        if disconnected {
+            self.talker = None;
+            self.talk.clear();
+            self.to_caller.clear();
+            self.to_agent.clear();
+            return;
+        }
+        trim_front(&mut self.talk);
+        if self.talk.len() < PCMU_FRAME_SAMPLES {
+            return;
+        }
+        let frame: Vec<i16> = self.talk.drain(..PCMU_FRAME_SAMPLES).collect();
+        match mode {
+            SupervisorMode::Listen => {}
+            SupervisorMode::Whisper => push_pending(&mut self.to_agent, frame),
+            SupervisorMode::Barge => {
+                push_pending(&mut self.to_caller, frame.clone());
+                push_pending(&mut self.to_agent, frame);
+            }
+        }
+    }
+
+    /// 発信者へ送る μ-law フレームに割り込み音声を足し込む
+    pub(crate) fn mix_to_caller(&mut self, payload: &mut [u8]) {
+        if let Some(frame) = self.to_caller.pop_front() {
+            mix_into(payload, &frame);
+        }
+    }
+
+    /// 担当者（B レグ）へ送る μ-law フレームにささやき・割り込み音声を足し込む
+    pub(crate) fn mix_to_agent(&mut self, payload: &mut [u8]) {
+        if let Some(frame) = self.to_agent.pop_front() {
+            mix_into(payload, &frame);
+        }
+    }
+}
+
+fn push_mulaw(queue: &mut VecDeque<i16>, payload: &[u8]) {
+    queue.extend(payload.iter().map(|&mu| mulaw_to_linear16(mu)));
+    trim_front(queue);
+}
+
+fn trim_front(queue: &mut VecDeque<i16>) {
+    if queue.len() > MAX_QUEUED_SAMPLES {
+        let excess = queue.len() - MAX_QUEUED_SAMPLES;
+        queue.drain(..excess);
+    }
+}
+
+/// 足りない分は無音で埋める
+fn pop_frame(queue: &mut VecDeque<i16>) -> Vec<i16> {
+    let take = queue.len().min(PCMU_FRAME_SAMPLES);
+    let mut frame: Vec<i16> = queue.drain(..take).collect();
+    frame.resize(PCMU_FRAME_SAMPLES, 0);
+    frame
+}
+
+fn push_pending(pending: &mut VecDeque<Vec<i16>>, frame: Vec<i16>) {
+    if pending.len() >= MAX_PENDING_FRAMES {
+        pending.pop_front();
+    }
+    pending.push_back(frame);
+}
+
+fn mix_into(payload: &mut [u8], frame: &[i16]) {
+    for (mu, sample) in payload.iter_mut().zip(frame) {
+        *mu = linear16_to_mulaw(mulaw_to_linear16(*mu).saturating_add(*sample));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(mode: SupervisorMode, channels: MonitorChannels) -> SupervisorRequest {
+        SupervisorRequest { mode, channels }
+    }
+
+    #[test]
+    fn listener_receives_mixed_and_per_leg_frames() {
+        let mut mixer = SupervisorMixer::default();
+        let mut mixed = mixer
+            .attach(
+                request(SupervisorMode::Listen, MonitorChannels::Mixed),
+                false,
+            )
+            .unwrap();
+        let mut legs = mixer
+            .attach(
+                request(SupervisorMode::Listen, MonitorChannels::Legs),
+                false,
+            )
+            .unwrap();
+        assert!(mixed.talk.is_none());
+
+        let caller = linear16_to_mulaw(1000);
+        let callee = linear16_to_mulaw(-500);
+        mixer.push_caller(&[caller; PCMU_FRAME_SAMPLES]);
+        mixer.push_callee(&[callee; PCMU_FRAME_SAMPLES]);
+        mixer.tick();
+
+        let frame = mixed.frames.try_recv().expect("mixed frame");
+        assert_eq!(frame.len(), PCMU_FRAME_SAMPLES);
+        assert_eq!(
+            frame[0],
+            mulaw_to_linear16(caller) + mulaw_to_linear16(callee)
+        );
+        let frame = legs.frames.try_recv().expect("legs frame");
+        assert_eq!(frame.len(), PCMU_FRAME_SAMPLES * 2);
+        assert_eq!(
+            &frame[..2],
+            &[mulaw_to_linear16(caller), mulaw_to_linear16(callee)]
+        );
+    }
+
+    #[test]
+    fn whisper_reaches_agent_only_and_needs_bridge() {
+        let mut mixer = SupervisorMixer::default();
+        let err = mixer
+            .attach(
+                request(SupervisorMode::Whisper, MonitorChannels::Mixed),
+                false,
+            )
+            .expect_err("not bridged");
+        assert!(matches!(err, LiveCallError::Rejected(_)));
+
+        let channel = mixer
+            .attach(
+                request(SupervisorMode::Whisper, MonitorChannels::Mixed),
+                true,
+            )
+            .unwrap();
+        assert!(mixer
+            .attach(request(SupervisorMode::Barge, MonitorChannels::Mixed), true)
+            .is_err());
+        let talk = channel.talk.as_ref().expect("talk channel");
+        talk.try_send(vec![2000; PCMU_FRAME_SAMPLES]).unwrap();
+        mixer.tick();
+
+        let silence = linear16_to_mulaw(0);
+        let mut to_caller = vec![silence; PCMU_FRAME_SAMPLES];
+        mixer.mix_to_caller(&mut to_caller);
+        assert!(to_caller.iter().all(|mu| *mu == silence));
+        let mut to_agent = vec![silence; PCMU_FRAME_SAMPLES];
+        mixer.mix_to_agent(&mut to_agent);
+        assert!(to_agent.iter().all(|mu| *mu == linear16_to_mulaw(2000)));
+    }
+
+    #[test]
+    fn barge_reaches_both_legs_and_closing_detaches() {
+        let mut mixer = SupervisorMixer::default();
+        let channel = mixer
+            .attach(
+                request(SupervisorMode::Barge, MonitorChannels::Mixed),
+                false,
+            )
+            .unwrap();
+        channel
+            .talk
+            .as_ref()
+            .unwrap()
+            .try_send(vec![2000; PCMU_FRAME_SAMPLES])
+            .unwrap();
+        mixer.tick();
+
+        let silence = linear16_to_mulaw(0);
+        let mut to_caller = vec![silence; PCMU_FRAME_SAMPLES];
+        mixer.mix_to_caller(&mut to_caller);
+        assert_eq!(to_caller[0], linear16_to_mulaw(2000));
+        let mut to_agent = vec![silence; PCMU_FRAME_SAMPLES];
+        mixer.mix_to_agent(&mut to_agent);
+        assert_eq!(to_agent[0], linear16_to_mulaw(2000));
+
+        drop(channel);
+        mixer.tick();
+        assert!(!mixer.is_active());
+        assert!(mixer.talker.is_none());
+        mixer.push_caller(&[silence; PCMU_FRAME_SAMPLES]);
+        assert!(mixer.caller.is_empty());
+    }
+}
@@ -3,12 +3,19 @@
 use std::time::Duration;
 
 use crate::protocol::session::b2bua::BLeg;
+use crate::shared::ports::call_log_port::{EmotionTurn, TransferAttempt};
+use crate::shared::ports::live_call::{
+    LiveCallError, LiveCallStatus, SupervisorChannel, SupervisorRequest,
+};
 use crate::shared::ports::rtp_sink::{RtpEvent, RtpEventSendError, RtpEventSink};
 use crate::shared::ports::session_lookup::{SessionLookup, SessionLookupFuture};
+use crate::shared::ports::voicemail_port::RecordedVoicemail;
 use thiserror::Error;
+use uuid::Uuid;
 
 /// Call-ID を表す（設計ドキュメント上はセッション識別子と一致させる）
 pub use crate::shared::entities::CallId;
+pub use crate::shared::entities::CallerIdentity;
 pub use crate::shared::ports::sip::{Sdp, SessionRefresher, SessionTimerInfo};
 
 #[derive(Clone, Debug)]
@@ -36,6 +43,10 @@ pub enum IvrState {
     VoicebotMode,
     Transferring,
     B2buaMode,
+    /// 留守番電話の再生メニュー（メールボックス番号/PIN/操作の DTMF 待ち）
+    VoicemailRetrieval,
+    /// 待ち呼キューで保留音を聞きながらエージェントの空きを待っている
+    Queued,
 }
 
 /// sip/session 間で受け取る制御イベント（SIP/タイマー/app など）
@@ -46,6 +57,7 @@ pub enum SessionControlIn {
         call_id: CallId,
         from: String,
         to: String,
+        identity: CallerIdentity,
         offer: Sdp,
         session_timer: Option<SessionTimerInfo>,
     },
@@ -77,10 +89,28 @@ pub enum SessionControlIn {
         reason: String,
         status: Option<u16>,
     },
+    /// 転送先グループの 1 メンバー分の呼び出しが終わった（成否を問わず）
+    TransferAttempt {
+        attempt: TransferAttempt,
+    },
+    /// 待ち呼キューから届いた待ち順位の案内音声
+    QueuePosition {
+        position: usize,
+        audio_path: String,
+    },
+    /// 待ち呼キューが割り当てたエージェントへ接続する
+    QueueConnect {
+        agent: String,
+    },
     /// BレグからのBYE
     BLegBye,
     /// IVR menu timeout
     IvrTimeout,
+    /// IVR 音声入力の認識結果（app → session）
+    IvrSpeechResult {
+        text: String,
+        intent: Option<String>,
+    },
     /// 転送中アナウンスの繰り返し
     TransferAnnounce,
     /// app から返ってきたボット応答音声（WAVファイルパス）
@@ -92,12 +122,52 @@ pub enum SessionControlIn {
         path: String,
         generation_id: PlaybackGenerationId,
     },
+    /// app から返ってきたボット応答音声（PCMU 20ms フレーム列、enqueue 再生）
+    AppBotAudioFramesEnqueue {
+        frames: Vec<Vec<u8>>,
+        generation_id: PlaybackGenerationId,
+    },
     /// app からの終了指示
     AppHangup,
     /// app からの転送指示
     AppTransferRequest {
         person: String,
     },
+    /// app が推定した発話ターンごとの感情（通話ログの感情タイムラインに積む）
+    AppEmotionTurn {
+        turn: EmotionTurn,
+    },
+    /// app（意図分類）からの録音一時停止の指示
+    AppRecordingPause,
+    /// 運用 API からの状態照会
+    ApiDescribe {
+        reply: oneshot::Sender<LiveCallStatus>,
+    },
+    /// 運用 API からの転送指示（グループ名・内線番号・SIP URI）
+    ApiTransfer {
+        target: String,
+        reply: oneshot::Sender<Result<(), LiveCallError>>,
+    },
+    /// 運用 API からの登録済みアナウンス再生
+    ApiPlayAnnouncement {
+        announcement_id: Uuid,
+        reply: oneshot::Sender<Result<(), LiveCallError>>,
+    },
+    /// 運用 API からの音声再生（合成済み WAV、再生中の音声は打ち切る）
+    ApiPlayAudio {
+        path: String,
+        reply: oneshot::Sender<Result<(), LiveCallError>>,
+    },
+    /// 運用 API からの録音の一時停止（paused=true）・再開（false）
+    ApiRecordingPause {
+        paused: bool,
+        reply: oneshot::Sender<Result<(), LiveCallError>>,
+    },
+    /// 運用 API からの監視者の参加（傍聴・ささやき・割り込み）
+    ApiSupervise {
+        request: SupervisorRequest,
+        reply: oneshot::Sender<Result<SupervisorChannel, LiveCallError>>,
+    },
     /// Session Timer (keepalive 含む) の失効
     SessionTimerFired,
     /// Session-Expires の更新時刻（refresher=uas 用）
@@ -230,12 +300,56 @@ pub enum SessionOut {
         path: String,
         generation_id: PlaybackGenerationId,
     },
+    /// app がストリーミング TTS からメモリ上で生成した PCMU フレームを session へ戻す（enqueue 再生）
+    AppEnqueueBotAudioFrames {
+        frames: Vec<Vec<u8>>,
+        generation_id: PlaybackGenerationId,
+    },
     /// app からの切断指示
     AppRequestHangup,
     /// app からの転送指示
     AppRequestTransfer {
         person: String,
     },
+    /// app からの録音一時停止の指示
+    AppRequestRecordingPause,
+    /// app が推定した発話ターンごとの感情
+    AppEmotionTurn {
+        turn: EmotionTurn,
+    },
+    /// IVR 音声入力の認識結果
+    AppIvrSpeechResult {
+        text: String,
+        intent: Option<String>,
+    },
+    /// 留守番電話の録音完了（通話ログ保存後に後処理へ渡す）
+    VoicemailRecorded {
+        message: RecordedVoicemail,
+    },
+    /// 待ち呼キューへの参加
+    QueueJoin {
+        queue: String,
+        priority: i32,
+    },
+    /// 待ち中にキューを離れた（切断・留守番電話など）
+    QueueLeave {
+        queue: String,
+    },
+    /// 割り当てられたエージェントが応答しなかった（キューへ戻す）
+    QueueConnectFailed {
+        queue: String,
+        agent: String,
+    },
+    /// エージェントとの通話（または呼び出し）が終わった
+    QueueAgentReleased {
+        queue: String,
+        agent: String,
+    },
+    /// 待ち中に折り返しを依頼された
+    QueueCallbackRequested {
+        queue: String,
+        caller_number: Option<String>,
+    },
     Metrics {
         name: &'static str,
         value: i64,
@@ -272,7 +386,7 @@ pub enum SessState {
 /// use crate::types::{next_session_state, CallId, SessState, SessionControlIn, Sdp};
 ///
 /// let s = SessState::Idle;
-/// let next = next_session_state(s, &SessionControlIn::SipInvite { call_id: CallId::new("call-1").unwrap(), from: "".into(), to: "".into(), offer: Sdp::pcmu("127.0.0.1", 10000), session_timer: None });
+/// let next = next_session_state(s, &SessionControlIn::SipInvite { call_id: CallId::new("call-1").unwrap(), from: "".into(), to: "".into(), identity: Default::default(), offer: Sdp::pcmu("127.0.0.1", 10000), session_timer: None });
 /// assert_eq!(next, SessState::Early);
 /// ```
 pub(crate) fn next_session_state(current: SessState, event: &SessionControlIn) -> SessState {
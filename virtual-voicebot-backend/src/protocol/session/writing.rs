@@ -10,10 +10,13 @@ use crate::protocol::session::types::*;
 use crate::protocol::session::{Session, SessionHandle};
 use crate::shared::config::SessionRuntimeConfig;
 use crate::shared::ports::app::{AppEventTx, AudioChunkTx};
+use crate::shared::ports::call_event_port::CallEventPublisher;
 use crate::shared::ports::call_log_port::CallLogPort;
 use crate::shared::ports::ingest::IngestPort;
 use crate::shared::ports::routing_port::RoutingPort;
+use crate::shared::ports::schedule_port::SchedulePort;
 use crate::shared::ports::storage::StoragePort;
+use crate::shared::ports::voicemail_port::VoicemailPort;
 
 /// セッションを生成し、SessionOut を上位レイヤに配線する（挙動は従来と同じ）。
 #[allow(clippy::too_many_arguments)]
@@ -32,6 +35,9 @@ pub fn spawn_call(
     storage_port: Arc<dyn StoragePort>,
     call_log_port: Arc<dyn CallLogPort>,
     routing_port: Arc<dyn RoutingPort>,
+    schedule_port: Arc<dyn SchedulePort>,
+    voicemail_port: Arc<dyn VoicemailPort>,
+    call_events: Arc<dyn CallEventPublisher>,
     runtime_cfg: Arc<SessionRuntimeConfig>,
 ) -> SessionHandle {
     Session::spawn(
@@ -49,6 +55,9 @@ pub fn spawn_call(
         storage_port,
         call_log_port,
         routing_port,
+        schedule_port,
+        voicemail_port,
+        call_events,
         runtime_cfg,
     )
 }
@@ -70,6 +79,9 @@ pub async fn spawn_session(
     storage_port: Arc<dyn StoragePort>,
     call_log_port: Arc<dyn CallLogPort>,
     routing_port: Arc<dyn RoutingPort>,
+    schedule_port: Arc<dyn SchedulePort>,
+    voicemail_port: Arc<dyn VoicemailPort>,
+    call_events: Arc<dyn CallEventPublisher>,
     runtime_cfg: Arc<SessionRuntimeConfig>,
 ) -> SessionHandle {
     let handle = spawn_call(
@@ -87,6 +99,9 @@ pub async fn spawn_session(
         storage_port,
         call_log_port,
         routing_port,
+        schedule_port,
+        voicemail_port,
+        call_events,
         runtime_cfg,
     );
     // Session manager の薄いラッパ経由で登録
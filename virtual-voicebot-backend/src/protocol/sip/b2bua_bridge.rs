@@ -6,6 +6,7 @@ use tokio::sync::mpsc;
 use crate::protocol::sip::message::{SipMessage, SipResponse};
 use crate::protocol::sip::tx::{SipTransportRequest, SipTransportTx};
 use crate::protocol::transport::TransportPeer;
+use crate::shared::metrics;
 
 #[derive(Debug, Clone)]
 pub struct B2buaSipMessage {
@@ -70,6 +71,7 @@ pub fn send(peer: TransportPeer, payload: Vec<u8>) -> bool {
     let Some(tx) = tx else {
         return false;
     };
+    metrics::record_sip_payload("out", &payload);
     match tx.try_send(SipTransportRequest {
         peer,
         src_port: sip_port,
@@ -4,6 +4,7 @@ use crate::protocol::sip::builder::{
     response_simple_from_request,
 };
 use crate::protocol::sip::codec::{parse_cseq_header, parse_sip_message, SipRequestBuilder};
+use crate::protocol::sip::identity::caller_identity_from_request;
 use crate::protocol::sip::message::{SipHeader, SipMessage, SipMethod, SipRequest, SipResponse};
 use crate::protocol::sip::register::RegisterClient;
 use crate::protocol::sip::transaction::{
@@ -16,6 +17,7 @@ use crate::protocol::sip::utils::extract_user_from_to;
 use crate::protocol::transport::{SipInput, TransportPeer};
 use crate::shared::config;
 use crate::shared::entities::CallId;
+use crate::shared::metrics;
 use crate::shared::ports::sip::{Sdp, SessionRefresher, SessionTimerInfo, SipCommand};
 use rand::Rng;
 use std::collections::HashMap;
@@ -542,6 +544,7 @@ fn spawn_register_task(
                     };
                     match (peer, payload) {
                         (Some(peer), Some(payload)) => {
+                            metrics::record_sip_payload("out", &payload);
                             if let Err(err) = transport_tx.try_send(SipTransportRequest {
                                 peer,
                                 src_port,
@@ -642,6 +645,8 @@ impl SipCore {
             }
         };
 
+        metrics::record_sip_start_line("in", text.lines().next().unwrap_or_default());
+
         if b2bua_bridge::dispatch_message(input.peer, &msg) {
             return vec![];
         }
@@ -947,10 +952,12 @@ impl SipCore {
                 req.body.len()
             );
         }
+        let identity = caller_identity_from_request(&req);
         vec![SipEvent::IncomingInvite {
             call_id: headers.call_id,
             from: headers.from,
             to: headers.to,
+            identity,
             offer,
             session_timer: session_timer.map(|cfg| SessionTimerInfo {
                 expires: cfg.expires,
@@ -1420,6 +1427,7 @@ impl SipCore {
                 if start.elapsed() >= max_duration {
                     log::warn!("[sip 100rel] PRACK timeout call_id={}", call_id);
                     if let Some(resp) = timeout_resp {
+                        metrics::record_sip_payload("out", &resp);
                         if let Err(err) = transport_tx.try_send(SipTransportRequest {
                             peer,
                             src_port,
@@ -1435,6 +1443,7 @@ impl SipCore {
                     }
                     break;
                 }
+                metrics::record_sip_payload("out", &payload);
                 if let Err(err) = transport_tx.try_send(SipTransportRequest {
                     peer,
                     src_port,
@@ -1494,6 +1503,7 @@ impl SipCore {
                     log::warn!("[sip] 2xx retransmit timeout (no ACK) call_id={}", call_id);
                     break;
                 }
+                metrics::record_sip_payload("out", &payload);
                 if let Err(err) = transport_tx.try_send(SipTransportRequest {
                     peer,
                     src_port,
@@ -1812,6 +1822,7 @@ impl SipCore {
             );
         }
 
+        metrics::record_sip_payload("out", &payload);
         if let Err(err) = self.transport_tx.try_send(SipTransportRequest {
             peer,
             src_port: self.cfg.sip_port,
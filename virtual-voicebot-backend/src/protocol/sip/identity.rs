@@ -0,0 +1,268 @@
+//! 着信 INVITE の発信者識別ヘッダ（PAI/PPI, Privacy, Remote-Party-ID, Diversion, History-Info）の解釈。
+
+use crate::protocol::sip::message::SipRequest;
+use crate::protocol::sip::parse_name_addr;
+use crate::protocol::sip::utils::extract_user_from_to;
+use crate::shared::entities::CallerIdentity;
+
+/// INVITE から発信者識別情報を組み立てる。
+pub(crate) fn caller_identity_from_request(req: &SipRequest) -> CallerIdentity {
+    let from = req.header_value("From").unwrap_or("");
+    let display_name = parse_name_addr(from)
+        .ok()
+        .and_then(|name_addr| name_addr.display)
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    let rpid = header_entries(req, "Remote-Party-ID").into_iter().next();
+    let asserted_number = ["P-Asserted-Identity", "P-Preferred-Identity"]
+        .iter()
+        .find_map(|name| asserted_number_from(&header_entries(req, name)))
+        .or_else(|| rpid.as_deref().and_then(identity_user));
+
+    let privacy = header_entries(req, "Privacy")
+        .iter()
+        .flat_map(|value| value.split(';'))
+        .any(|token| {
+            matches!(
+                token.trim().to_ascii_lowercase().as_str(),
+                "id" | "user" | "header"
+            )
+        })
+        || rpid.as_deref().is_some_and(rpid_requests_privacy);
+
+    let (original_called_number, diversion_reason) = original_called_party(req);
+
+    CallerIdentity {
+        display_number: identity_user(from),
+        display_name,
+        asserted_number,
+        privacy,
+        original_called_number,
+        diversion_reason,
+    }
+}
+
+/// 同名ヘッダの複数行とカンマ区切りの複数値を 1 件ずつに分解する。
+fn header_entries(req: &SipRequest, name: &str) -> Vec<String> {
+    req.headers
+        .iter()
+        .filter(|header| header.name.eq_ignore_ascii_case(name))
+        .flat_map(|header| split_header_list(&header.value))
+        .collect()
+}
+
+/// `<...>` と引用符の内側のカンマでは分割しない。
+fn split_header_list(value: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut current = String::new();
+    let mut in_angle = false;
+    let mut in_quote = false;
+    for ch in value.chars() {
+        match ch {
+            '"' => in_quote = !in_quote,
+            '<' if !in_quote => in_angle = true,
+            '>' if !in_quote => in_angle = false,
+            ',' if !in_quote && !in_angle => {
+                push_entry(&mut entries, &current);
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+    }
+    push_entry(&mut entries, &current);
+    entries
+}
+
+fn push_entry(entries: &mut Vec<String>, entry: &str) {
+    let entry = entry.trim();
+    if !entry.is_empty() {
+        entries.push(entry.to_string());
+    }
+}
+
+/// PAI は sip URI と tel URI を併記できる。番号として使える tel URI を優先する。
+fn asserted_number_from(entries: &[String]) -> Option<String> {
+    entries
+        .iter()
+        .find(|entry| is_tel_uri(entry))
+        .or_else(|| entries.first())
+        .and_then(|entry| identity_user(entry))
+}
+
+fn is_tel_uri(entry: &str) -> bool {
+    parse_name_addr(entry)
+        .map(|name_addr| name_addr.uri.scheme.eq_ignore_ascii_case("tel"))
+        .unwrap_or(false)
+}
+
+/// URI のユーザ部（ユーザ部パラメータは除く）。
+fn identity_user(entry: &str) -> Option<String> {
+    let user = extract_user_from_to(entry)?;
+    let user = user.split(';').next().unwrap_or("").trim();
+    (!user.is_empty()).then(|| user.to_string())
+}
+
+fn rpid_requests_privacy(entry: &str) -> bool {
+    let Ok(name_addr) = parse_name_addr(entry) else {
+        return false;
+    };
+    name_addr.params.iter().any(|(key, value)| {
+        key.eq_ignore_ascii_case("privacy")
+            && matches!(
+                value.trim_matches('"').to_ascii_lowercase().as_str(),
+                "full" | "uri"
+            )
+    })
+}
+
+/// 転送前の着信先と転送理由。
+/// Diversion は直近の転送が先頭に積まれるため末尾が元の着信先、History-Info は index 最小が元の着信先。
+fn original_called_party(req: &SipRequest) -> (Option<String>, Option<String>) {
+    let diversions = header_entries(req, "Diversion");
+    if let Some(original) = diversions.last() {
+        let reason = parse_name_addr(original)
+            .ok()
+            .and_then(|name_addr| param_value(&name_addr.params, "reason"));
+        return (identity_user(original), reason);
+    }
+
+    let history = header_entries(req, "History-Info");
+    // 転送されていなければ History-Info は 1 件だけ
+    if history.len() < 2 {
+        return (None, None);
+    }
+    let mut indexed: Vec<(Vec<u32>, &String)> = history
+        .iter()
+        .map(|entry| (history_index(entry), entry))
+        .collect();
+    indexed.sort_by(|a, b| a.0.cmp(&b.0));
+    let original = indexed[0].1;
+    let cause = history.iter().find_map(|entry| history_cause(entry));
+    (identity_user(original), cause)
+}
+
+fn history_index(entry: &str) -> Vec<u32> {
+    parse_name_addr(entry)
+        .ok()
+        .and_then(|name_addr| param_value(&name_addr.params, "index"))
+        .map(|index| {
+            index
+                .split('.')
+                .filter_map(|part| part.trim().parse().ok())
+                .collect()
+        })
+        .unwrap_or_else(|| vec![u32::MAX])
+}
+
+/// RFC 4458 の cause URI パラメータ（例: `;cause=302`）
+fn history_cause(entry: &str) -> Option<String> {
+    parse_name_addr(entry)
+        .ok()
+        .and_then(|name_addr| param_value(&name_addr.uri.params, "cause"))
+}
+
+fn param_value(params: &[(String, String)], name: &str) -> Option<String> {
+    params
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim_matches('"').to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::sip::message::{SipHeader, SipMethod};
+
+    fn invite(headers: &[(&str, &str)]) -> SipRequest {
+        SipRequest {
+            method: SipMethod::Invite,
+            uri: "sip:+81312345678@voicebot.example.com".to_string(),
+            version: "SIP/2.0".to_string(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| SipHeader::new(*name, *value))
+                .collect(),
+            body: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn withheld_from_with_asserted_identity_and_privacy() {
+        let req = invite(&[
+            (
+                "From",
+                "\"Anonymous\" <sip:anonymous@anonymous.invalid>;tag=1",
+            ),
+            (
+                "P-Asserted-Identity",
+                "<sip:+819012345678@carrier.example.com>, <tel:+819012345678>",
+            ),
+            ("Privacy", "id;critical"),
+        ]);
+        let identity = caller_identity_from_request(&req);
+        assert_eq!(identity.display_number.as_deref(), Some("anonymous"));
+        assert_eq!(identity.asserted_number.as_deref(), Some("+819012345678"));
+        assert_eq!(identity.routing_number(), Some("+819012345678"));
+        assert!(identity.privacy);
+    }
+
+    #[test]
+    fn remote_party_id_is_used_when_pai_is_absent() {
+        let req = invite(&[
+            ("From", "\"Taro\" <sip:0312345678@pbx.example.com>;tag=1"),
+            (
+                "Remote-Party-ID",
+                "<sip:+81312345678@pbx.example.com>;party=calling;privacy=full",
+            ),
+        ]);
+        let identity = caller_identity_from_request(&req);
+        assert_eq!(identity.display_name.as_deref(), Some("Taro"));
+        assert_eq!(identity.display_number.as_deref(), Some("0312345678"));
+        assert_eq!(identity.asserted_number.as_deref(), Some("+81312345678"));
+        assert!(identity.privacy);
+    }
+
+    #[test]
+    fn diversion_keeps_first_called_party() {
+        let req = invite(&[
+            ("From", "<sip:+819012345678@carrier.example.com>;tag=1"),
+            (
+                "Diversion",
+                "<sip:+81311112222@pbx.example.com>;reason=no-answer",
+            ),
+            (
+                "Diversion",
+                "<sip:+81333334444@pbx.example.com>;reason=unconditional",
+            ),
+            ("Privacy", "none"),
+        ]);
+        let identity = caller_identity_from_request(&req);
+        assert_eq!(
+            identity.original_called_number.as_deref(),
+            Some("+81333334444")
+        );
+        assert_eq!(identity.diversion_reason.as_deref(), Some("unconditional"));
+        assert!(!identity.privacy);
+    }
+
+    #[test]
+    fn history_info_original_target_uses_lowest_index() {
+        let req = invite(&[
+            ("From", "<sip:+819012345678@carrier.example.com>;tag=1"),
+            (
+                "History-Info",
+                "<sip:+81355556666@pbx.example.com;cause=302>;index=1.1, <sip:+81344445555@pbx.example.com>;index=1",
+            ),
+        ]);
+        let identity = caller_identity_from_request(&req);
+        assert_eq!(
+            identity.original_called_number.as_deref(),
+            Some("+81344445555")
+        );
+        assert_eq!(identity.diversion_reason.as_deref(), Some("302"));
+    }
+}
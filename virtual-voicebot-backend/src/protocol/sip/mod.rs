@@ -5,6 +5,7 @@ pub mod builder;
 pub mod codec;
 pub mod core;
 pub mod error;
+pub(crate) mod identity;
 pub mod message;
 pub mod parse;
 pub mod protocols;
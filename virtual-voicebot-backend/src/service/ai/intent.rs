@@ -1,10 +1,9 @@
-use std::path::PathBuf;
-use std::sync::OnceLock;
 use std::time::Duration;
 
 use anyhow::Result;
 use tokio::time::timeout;
 
+use crate::service::runtime_config::{self, ReloadError};
 use crate::shared::config;
 use crate::shared::ports::ai::{ChatMessage, Role};
 
@@ -14,8 +13,9 @@ const DEFAULT_INTENT_PROMPT: &str = r#"
 
 要件:
 - 出力はJSONのみ
-- 形式: {"intent":"identity|system_info|general_chat|weather|transfer","query":"<ユーザー発話>","params":{...}}
-- intentは identity / system_info / general_chat / weather / transfer のみ
+- 形式: {"intent":"identity|system_info|general_chat|weather|transfer|recording_pause","query":"<ユーザー発話>","params":{...}}
+- intentは identity / system_info / general_chat / weather / transfer / recording_pause のみ
+- recording_pauseは、カード番号・暗証番号など録音してはならない情報をこれから話すとき
 - queryは入力のユーザー発話をそのまま入れる
 - weatherのparamsには location / date(today) を入れる（不明ならnull）
 
@@ -25,57 +25,39 @@ const DEFAULT_INTENT_PROMPT: &str = r#"
 {"intent":"general_chat","query":"徳川家康について教えて"}
 {"intent":"weather","query":"今日の東京の天気は？","params":{"location":"東京","date":"today"}}
 {"intent":"transfer","query":"須田さんに繋いで","params":{"person":"須田"}}
+{"intent":"recording_pause","query":"カード番号を言うので録音を止めてください"}
 "#;
 
-const INTENT_PROMPT_FILE_NAME: &str = "intent_prompt.local.txt";
-const INTENT_PROMPT_EXAMPLE: &str = "intent_prompt.example.txt";
-
-static INTENT_PROMPT_CACHE: OnceLock<String> = OnceLock::new();
+pub(crate) const INTENT_PROMPT_FILE_NAME: &str = "intent_prompt.local.txt";
+pub(crate) const INTENT_PROMPT_EXAMPLE: &str = "intent_prompt.example.txt";
 
 pub fn init_intent_prompt() {
     let _ = intent_prompt();
 }
 
 pub fn intent_prompt() -> String {
-    INTENT_PROMPT_CACHE
-        .get_or_init(|| {
-            read_prompt_file()
-                .or_else(read_example_file)
-                .unwrap_or_else(|| DEFAULT_INTENT_PROMPT.trim().to_string())
-        })
-        .clone()
+    runtime_config::current().intent_prompt.clone()
 }
 
-fn read_prompt_file() -> Option<String> {
-    read_prompt_from(INTENT_PROMPT_FILE_NAME)
+/// 通話開始時に固定した意図分類プロンプト
+pub fn intent_prompt_for(call_id: &str) -> String {
+    runtime_config::for_call(call_id).intent_prompt.clone()
 }
 
-fn read_example_file() -> Option<String> {
-    read_prompt_from(INTENT_PROMPT_EXAMPLE)
+pub(crate) fn load_intent_prompt() -> Result<String, ReloadError> {
+    let prompt = match runtime_config::read_prompt_override(INTENT_PROMPT_FILE_NAME)? {
+        Some(prompt) => Some(prompt),
+        None => runtime_config::read_prompt_override(INTENT_PROMPT_EXAMPLE)?,
+    };
+    Ok(prompt.unwrap_or_else(default_intent_prompt))
 }
 
-fn read_prompt_from(name: &str) -> Option<String> {
-    // Try current working directory first, then executable directory.
-    let paths = [
-        PathBuf::from(name),
-        std::env::current_exe()
-            .ok()
-            .and_then(|p| p.parent().map(|d| d.join(name)))
-            .unwrap_or_default(),
-    ];
-    for path in paths {
-        if let Ok(text) = std::fs::read_to_string(&path) {
-            let trimmed = text.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
-            }
-        }
-    }
-    None
+pub(crate) fn default_intent_prompt() -> String {
+    DEFAULT_INTENT_PROMPT.trim().to_string()
 }
 
 pub async fn classify_intent(call_id: &str, text: String) -> Result<String> {
-    let prompt = intent_prompt();
+    let prompt = intent_prompt_for(call_id);
     let ai_cfg = config::ai_config();
     let text_len = text.chars().count();
     let text_for_cloud = text.clone();
@@ -1,18 +1,14 @@
 #![allow(dead_code)]
 
-use std::path::PathBuf;
-use std::sync::OnceLock;
-
 use anyhow::Result;
 
+use crate::service::runtime_config::{self, ReloadError};
 use crate::shared::config;
 use crate::shared::error::ai::LlmError;
 use crate::shared::ports::ai::{ChatMessage, LlmStream};
 
 const DEFAULT_SYSTEM_PROMPT: &str = "あなたはボイスボットです。120文字以内で回答してください。";
-const PROMPT_FILE_NAME: &str = "prompt.local.txt";
-
-static SYSTEM_PROMPT_CACHE: OnceLock<String> = OnceLock::new();
+pub(crate) const PROMPT_FILE_NAME: &str = "prompt.local.txt";
 
 pub fn init_system_prompt() {
     let _ = system_prompt();
@@ -20,8 +16,9 @@ pub fn init_system_prompt() {
 
 /// Provides the active system prompt used by the AI assistant.
 ///
-/// The returned string comes from a cached value that prefers a local override file when present;
-/// if no override is available, the built-in default prompt is returned.
+/// The returned string comes from the current runtime snapshot, which prefers a local override
+/// file when present and is replaced on hot reload; if no override is available, the built-in
+/// default prompt is returned.
 ///
 /// # Examples
 ///
@@ -32,29 +29,21 @@ pub fn init_system_prompt() {
 /// assert!(!prompt.is_empty());
 /// ```
 pub fn system_prompt() -> String {
-    SYSTEM_PROMPT_CACHE
-        .get_or_init(|| read_prompt_file().unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string()))
-        .clone()
+    runtime_config::current().system_prompt.clone()
 }
 
-fn read_prompt_file() -> Option<String> {
-    // Try current working directory first, then executable directory.
-    let paths = [
-        PathBuf::from(PROMPT_FILE_NAME),
-        std::env::current_exe()
-            .ok()
-            .and_then(|p| p.parent().map(|d| d.join(PROMPT_FILE_NAME)))
-            .unwrap_or_default(),
-    ];
-    for path in paths {
-        if let Ok(text) = std::fs::read_to_string(&path) {
-            let trimmed = text.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
-            }
-        }
-    }
-    None
+/// 通話開始時に固定したシステムプロンプト（通話中に再読込されても変わらない）
+pub fn system_prompt_for(call_id: &str) -> String {
+    runtime_config::for_call(call_id).system_prompt.clone()
+}
+
+pub(crate) fn load_system_prompt() -> Result<String, ReloadError> {
+    Ok(runtime_config::read_prompt_override(PROMPT_FILE_NAME)?
+        .unwrap_or_else(default_system_prompt))
+}
+
+pub(crate) fn default_system_prompt() -> String {
+    DEFAULT_SYSTEM_PROMPT.to_string()
 }
 
 /// LLM 呼び出しの薄いI/F（挙動は ai::handle_user_question_from_whisper のLLM部分と同じ）
@@ -74,7 +63,7 @@ pub async fn generate_answer_stream(
         ));
     }
 
-    let system_prompt = system_prompt();
+    let system_prompt = system_prompt_for(call_id);
     let first_token_timeout = config::llm_streaming_first_token_timeout();
     super::call_ollama_for_chat_stream(
         &messages,
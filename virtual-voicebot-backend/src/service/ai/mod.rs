@@ -9,11 +9,14 @@ use log::info;
 use reqwest::{multipart, Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fs;
 use std::future::Future;
 use std::io::Cursor;
+use std::path::PathBuf;
 use std::pin::Pin;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::time::{sleep, timeout};
@@ -27,6 +30,8 @@ use aws_sdk_s3 as s3;
 use aws_sdk_s3::primitives::ByteStream;
 use aws_sdk_transcribe as transcribe;
 
+use self::stage_health::{StageHealthSnapshot, StageKind};
+use self::tts_cache::TtsPhraseCache;
 use crate::shared::config;
 use crate::shared::error::ai::{AsrError, IntentError, LlmError, TtsError, WeatherError};
 use crate::shared::ports::ai::{
@@ -149,7 +154,9 @@ pub mod asr;
 pub mod intent;
 pub mod llm;
 pub mod ser;
+mod stage_health;
 pub mod tts;
+pub mod tts_cache;
 pub mod weather;
 
 fn http_client(timeout: Duration) -> Result<Client> {
@@ -167,6 +174,31 @@ pub struct LocalServicesStatusResponse {
     ok: bool,
     #[serde(rename = "localServices")]
     local_services: LocalServicesMap,
+    #[serde(rename = "stageHealth")]
+    stage_health: StageHealthMap,
+}
+
+/// ステージ別のブレーカー状態と遅延統計（キーは "local" / "cloud" / "raspi"）
+#[derive(Serialize)]
+struct StageHealthMap {
+    asr: BTreeMap<&'static str, StageHealthSnapshot>,
+    llm: BTreeMap<&'static str, StageHealthSnapshot>,
+    tts: BTreeMap<&'static str, StageHealthSnapshot>,
+}
+
+fn stage_health_map() -> StageHealthMap {
+    let registry = stage_health::registry();
+    let snapshot = |kind: StageKind, stages: [&'static str; 3]| {
+        stages
+            .into_iter()
+            .map(|stage| (stage, registry.snapshot(kind, stage)))
+            .collect()
+    };
+    StageHealthMap {
+        asr: snapshot(StageKind::Asr, ASR_FALLBACK_ORDER.map(AsrStage::as_str)),
+        llm: snapshot(StageKind::Llm, LLM_FALLBACK_ORDER.map(LlmStage::as_str)),
+        tts: snapshot(StageKind::Tts, TTS_FALLBACK_ORDER.map(TtsStage::as_str)),
+    }
 }
 
 #[derive(Serialize)]
@@ -224,13 +256,51 @@ pub async fn probe_local_services_status(
         ),
     );
 
+    // プローブ結果で local ステージの開いているブレーカーを half-open へ進める
+    let registry = stage_health::registry();
+    let now = Instant::now();
+    for (kind, entry) in [
+        (StageKind::Asr, &asr),
+        (StageKind::Llm, &llm),
+        (StageKind::Tts, &tts),
+    ] {
+        if entry.status != "disabled" {
+            registry.record_probe(kind, "local", entry.status == "ok", now);
+        }
+    }
+
     let local_services = LocalServicesMap { asr, llm, tts };
     Ok(LocalServicesStatusResponse {
         ok: local_services_all_healthy(&local_services),
         local_services,
+        stage_health: stage_health_map(),
     })
 }
 
+/// local ステージのブレーカーが開いている間、定期的にヘルスプローブを行う。
+pub fn spawn_stage_health_prober() {
+    let health_cfg = config::ai_stage_health_config();
+    if !health_cfg.breaker_enabled {
+        return;
+    }
+    let interval = health_cfg.probe_interval;
+    tokio::spawn(async move {
+        loop {
+            sleep(interval).await;
+            let registry = stage_health::registry();
+            let any_open = [StageKind::Asr, StageKind::Llm, StageKind::Tts]
+                .into_iter()
+                .any(|kind| registry.is_open(kind, "local"));
+            if !any_open {
+                continue;
+            }
+            if let Err(err) = probe_local_services_status(config::ai_config()).await {
+                log::warn!("[ai] local stage health probe failed: {}", err);
+            }
+        }
+    });
+}
+
 fn disabled_local_service_entry() -> LocalServiceEntry {
     LocalServiceEntry {
         status: "disabled",
@@ -322,6 +392,7 @@ const OPENAI_LLM_MODEL: &str = "gpt-4o-mini";
 const OPENAI_TTS_MODEL: &str = "gpt-4o-mini-tts";
 const OPENAI_TTS_VOICE: &str = "alloy";
 const OPENAI_TTS_PCM_SAMPLE_RATE: u32 = 24_000;
+const VOICEVOX_SPEAKER_ID: u32 = 3; // ずんだもん ノーマル
 
 fn openai_api_key(ai_cfg: &config::AiConfig) -> Option<&str> {
     ai_cfg
@@ -397,7 +468,7 @@ impl LlmStage {
 
 const LLM_FALLBACK_ORDER: [LlmStage; 3] = [LlmStage::Local, LlmStage::Cloud, LlmStage::Raspi];
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum TtsStage {
     Cloud,
     Local,
@@ -757,6 +828,7 @@ fn build_openai_chat_messages(
         let role = match msg.role {
             Role::User => "user",
             Role::Assistant => "assistant",
+            Role::System => "system",
         };
         openai_messages.push(OpenAiChatMessage {
             role: role.to_string(),
@@ -808,7 +880,7 @@ pub(super) async fn call_openai_chat_for_stage(
 
 pub(crate) async fn call_openai_intent(
     text: &str,
-    _call_id: &str,
+    call_id: &str,
     api_key: &str,
     base_url: &str,
     model: &str,
@@ -822,7 +894,7 @@ pub(crate) async fn call_openai_intent(
     }];
     let req = serde_json::json!({
         "model": model,
-        "messages": build_openai_chat_messages(&messages, &intent::intent_prompt()),
+        "messages": build_openai_chat_messages(&messages, &intent::intent_prompt_for(call_id)),
         "response_format": { "type": "json_object" },
     });
 
@@ -1049,7 +1121,7 @@ async fn synth_zundamon_stream_for_stage(
 ) -> Result<TtsStream> {
     let query_client = http_client(startup_timeout)?;
     let read_timeout = config::tts_streaming_first_chunk_timeout();
-    let speaker_id = 3; // ずんだもん ノーマル
+    let speaker_id = VOICEVOX_SPEAKER_ID;
 
     let query_url = join_url_path(base_url, "/audio_query");
     let query_resp = query_client
@@ -1094,15 +1166,23 @@ where
     Fut: Future<Output = Result<TtsStream>>,
 {
     let stage_name = stage.as_str();
+    if !stage_health::acquire(call_id, StageKind::Tts, stage_name) {
+        return None;
+    }
     log::debug!(
         "[tts {call_id}] TTS stream stage start: tts_stage={} timeout_ms={}",
         stage_name,
         startup_timeout.as_millis()
     );
 
+    let started = Instant::now();
     let stream = match timeout(startup_timeout, run()).await {
-        Ok(Ok(stream)) => stream,
+        Ok(Ok(stream)) => {
+            stage_health::record(StageKind::Tts, stage_name, started, true);
+            stream
+        }
         Ok(Err(err)) => {
+            stage_health::record(StageKind::Tts, stage_name, started, false);
             log::warn!(
                 "[tts {call_id}] TTS stream stage failed: tts_stage={} reason={}",
                 stage_name,
@@ -1111,6 +1191,7 @@ where
             return None;
         }
         Err(_) => {
+            stage_health::record(StageKind::Tts, stage_name, started, false);
             log::warn!(
                 "[tts {call_id}] TTS stream stage failed: tts_stage={} reason=timeout timeout_ms={}",
                 stage_name,
@@ -1138,15 +1219,23 @@ where
     Fut: Future<Output = Result<String>>,
 {
     let stage_name = stage.as_str();
+    if !stage_health::acquire(call_id, StageKind::Asr, stage_name) {
+        return None;
+    }
     log::debug!(
         "[asr {call_id}] ASR stage start: asr_stage={} timeout_ms={}",
         stage_name,
         stage_timeout.as_millis()
     );
 
+    let started = Instant::now();
     let text = match timeout(stage_timeout, run()).await {
-        Ok(Ok(text)) => text,
+        Ok(Ok(text)) => {
+            stage_health::record(StageKind::Asr, stage_name, started, true);
+            text
+        }
         Ok(Err(err)) => {
+            stage_health::record(StageKind::Asr, stage_name, started, false);
             log::warn!(
                 "[asr {call_id}] ASR stage failed: asr_stage={} reason={}",
                 stage_name,
@@ -1155,6 +1244,7 @@ where
             return None;
         }
         Err(_) => {
+            stage_health::record(StageKind::Asr, stage_name, started, false);
             log::warn!(
                 "[asr {call_id}] ASR stage failed: asr_stage={} reason=timeout timeout_ms={}",
                 stage_name,
@@ -1205,15 +1295,23 @@ where
     Fut: Future<Output = Result<String>>,
 {
     let stage_name = stage.as_str();
+    if !stage_health::acquire(call_id, StageKind::Llm, stage_name) {
+        return None;
+    }
     log::debug!(
         "[llm {call_id}] LLM stage start: llm_stage={} timeout_ms={}",
         stage_name,
         stage_timeout.as_millis()
     );
 
+    let started = Instant::now();
     let text = match timeout(stage_timeout, run()).await {
-        Ok(Ok(text)) => text,
+        Ok(Ok(text)) => {
+            stage_health::record(StageKind::Llm, stage_name, started, true);
+            text
+        }
         Ok(Err(err)) => {
+            stage_health::record(StageKind::Llm, stage_name, started, false);
             log::warn!(
                 "[llm {call_id}] LLM stage failed: llm_stage={} reason={}",
                 stage_name,
@@ -1222,6 +1320,7 @@ where
             return None;
         }
         Err(_) => {
+            stage_health::record(StageKind::Llm, stage_name, started, false);
             log::warn!(
                 "[llm {call_id}] LLM stage failed: llm_stage={} reason=timeout timeout_ms={}",
                 stage_name,
@@ -1250,15 +1349,23 @@ where
     Fut: Future<Output = Result<Vec<u8>>>,
 {
     let stage_name = stage.as_str();
+    if !stage_health::acquire(call_id, StageKind::Tts, stage_name) {
+        return None;
+    }
     log::debug!(
         "[tts {call_id}] TTS stage start: tts_stage={} timeout_ms={}",
         stage_name,
         stage_timeout.as_millis()
     );
 
+    let started = Instant::now();
     let wav_bytes = match timeout(stage_timeout, run()).await {
-        Ok(Ok(wav_bytes)) => wav_bytes,
+        Ok(Ok(wav_bytes)) => {
+            stage_health::record(StageKind::Tts, stage_name, started, true);
+            wav_bytes
+        }
         Ok(Err(err)) => {
+            stage_health::record(StageKind::Tts, stage_name, started, false);
             log::warn!(
                 "[tts {call_id}] TTS stage failed: tts_stage={} reason={}",
                 stage_name,
@@ -1267,6 +1374,7 @@ where
             return None;
         }
         Err(_) => {
+            stage_health::record(StageKind::Tts, stage_name, started, false);
             log::warn!(
                 "[tts {call_id}] TTS stage failed: tts_stage={} reason=timeout timeout_ms={}",
                 stage_name,
@@ -1297,7 +1405,9 @@ pub async fn transcribe_and_log(call_id: &str, wav_path: &str) -> Result<String>
     let openai_api_key_owned = openai_api_key(ai_cfg).map(str::to_string);
     let openai_base_url = ai_cfg.openai_base_url.clone();
 
-    for stage in ASR_FALLBACK_ORDER {
+    for stage in
+        stage_health::registry().ordered(StageKind::Asr, ASR_FALLBACK_ORDER, AsrStage::as_str)
+    {
         match stage {
             AsrStage::Local => {
                 if ai_cfg.asr_local_server_enabled {
@@ -1425,11 +1535,13 @@ pub async fn handle_user_question_from_whisper_llm_only(
         anyhow::bail!("all LLM stages failed");
     }
 
-    let system_prompt = llm::system_prompt();
+    let system_prompt = llm::system_prompt_for(call_id);
     let openai_llm_enabled = openai_llm_stage_enabled(ai_cfg);
     let openai_api_key_owned = openai_api_key(ai_cfg).map(str::to_string);
     let openai_base_url = ai_cfg.openai_base_url.clone();
-    for stage in LLM_FALLBACK_ORDER {
+    for stage in
+        stage_health::registry().ordered(StageKind::Llm, LLM_FALLBACK_ORDER, LlmStage::as_str)
+    {
         match stage {
             LlmStage::Local => {
                 if ai_cfg.llm_local_server_enabled {
@@ -1487,6 +1599,7 @@ pub async fn handle_user_question_from_whisper_llm_only(
                                 if gemini_llm_enabled(ai_cfg) {
                                     return call_gemini_with_http_timeout(
                                         &cloud_messages,
+                                        &system_prompt,
                                         ai_cfg.llm_cloud_timeout,
                                     )
                                     .await;
@@ -1691,6 +1804,7 @@ pub(super) async fn call_ollama_for_chat_stream(
         let role = match msg.role {
             Role::User => "user",
             Role::Assistant => "assistant",
+            Role::System => "system",
         };
         ollama_messages.push(OllamaMessage {
             role: role.to_string(),
@@ -1738,6 +1852,7 @@ pub(crate) async fn call_ollama_for_intent_stage(
         let role = match msg.role {
             Role::User => "user",
             Role::Assistant => "assistant",
+            Role::System => "system",
         };
         ollama_messages.push(OllamaMessage {
             role: role.to_string(),
@@ -1794,6 +1909,7 @@ pub(super) async fn call_ollama_for_weather(
         let role = match msg.role {
             Role::User => "user",
             Role::Assistant => "assistant",
+            Role::System => "system",
         };
         ollama_messages.push(OllamaMessage {
             role: role.to_string(),
@@ -1865,6 +1981,7 @@ async fn call_ollama_with_prompt_internal(
         let role = match msg.role {
             Role::User => "user",
             Role::Assistant => "assistant",
+            Role::System => "system",
         };
         ollama_messages.push(OllamaMessage {
             role: role.to_string(),
@@ -2036,6 +2153,18 @@ impl TtsPort for DefaultAiPort {
                 .map_err(|e| TtsError::SynthesisFailed(e.to_string()))
         })
     }
+
+    fn synth_phrase_to_wav(
+        &self,
+        call_id: String,
+        text: String,
+    ) -> AiFuture<Result<std::path::PathBuf, TtsError>> {
+        Box::pin(async move {
+            synth_phrase_to_wav(&call_id, &text)
+                .await
+                .map_err(|e| TtsError::SynthesisFailed(e.to_string()))
+        })
+    }
 }
 
 impl TtsStreamPort for DefaultAiPort {
@@ -2054,7 +2183,11 @@ impl TtsStreamPort for DefaultAiPort {
             let openai_api_key_owned = openai_api_key(ai_cfg).map(str::to_string);
             let openai_base_url = ai_cfg.openai_base_url.clone();
 
-            for stage in TTS_FALLBACK_ORDER {
+            for stage in stage_health::registry().ordered(
+                StageKind::Tts,
+                TTS_FALLBACK_ORDER,
+                TtsStage::as_str,
+            ) {
                 match stage {
                     TtsStage::Local => {
                         if ai_cfg.tts_local_server_enabled {
@@ -2165,6 +2298,14 @@ impl SerPort for DefaultAiPort {
 
 /// TTS 呼び出し（VoiceVox local / OpenAI cloud / raspi）。I/F はテキストと出力 WAV パス（従来どおり）。
 pub async fn synth_zundamon_wav(call_id: &str, text: &str, out_path: &str) -> Result<()> {
+    let (_, wav_bytes) = synth_wav_bytes(call_id, text).await?;
+    tokio::fs::write(out_path, &wav_bytes).await?;
+    info!("[tts {call_id}] TTS written to {}", out_path);
+    Ok(())
+}
+
+/// フォールバック順に TTS を試し、成功したステージと WAV を返す。
+async fn synth_wav_bytes(call_id: &str, text: &str) -> Result<(TtsStage, Vec<u8>)> {
     let ai_cfg = config::ai_config();
 
     if tts_stage_count(ai_cfg) == 0 {
@@ -2176,7 +2317,9 @@ pub async fn synth_zundamon_wav(call_id: &str, text: &str, out_path: &str) -> Re
     let openai_api_key_owned = openai_api_key(ai_cfg).map(str::to_string);
     let openai_base_url = ai_cfg.openai_base_url.clone();
 
-    for stage in TTS_FALLBACK_ORDER {
+    for stage in
+        stage_health::registry().ordered(StageKind::Tts, TTS_FALLBACK_ORDER, TtsStage::as_str)
+    {
         match stage {
             TtsStage::Local => {
                 if ai_cfg.tts_local_server_enabled {
@@ -2192,9 +2335,7 @@ pub async fn synth_zundamon_wav(call_id: &str, text: &str, out_path: &str) -> Re
                         })
                         .await
                     {
-                        tokio::fs::write(out_path, &wav_bytes).await?;
-                        info!("[tts {call_id}] TTS written to {}", out_path);
-                        return Ok(());
+                        return Ok((stage, wav_bytes));
                     }
                 }
             }
@@ -2219,9 +2360,7 @@ pub async fn synth_zundamon_wav(call_id: &str, text: &str, out_path: &str) -> Re
                         })
                         .await
                     {
-                        tokio::fs::write(out_path, &wav_bytes).await?;
-                        info!("[tts {call_id}] TTS written to {}", out_path);
-                        return Ok(());
+                        return Ok((stage, wav_bytes));
                     }
                 }
             }
@@ -2243,9 +2382,7 @@ pub async fn synth_zundamon_wav(call_id: &str, text: &str, out_path: &str) -> Re
                         )
                         .await
                         {
-                            tokio::fs::write(out_path, &wav_bytes).await?;
-                            info!("[tts {call_id}] TTS written to {}", out_path);
-                            return Ok(());
+                            return Ok((stage, wav_bytes));
                         }
                     } else {
                         log::warn!(
@@ -2261,6 +2398,126 @@ pub async fn synth_zundamon_wav(call_id: &str, text: &str, out_path: &str) -> Re
     anyhow::bail!("all TTS stages failed")
 }
 
+/// 設定上の最優先 TTS ステージ（キャッシュはこのステージの音声のみ保持する）。
+fn preferred_tts_stage(ai_cfg: &config::AiConfig) -> Option<TtsStage> {
+    TTS_FALLBACK_ORDER.into_iter().find(|stage| match stage {
+        TtsStage::Local => ai_cfg.tts_local_server_enabled,
+        TtsStage::Cloud => openai_tts_stage_enabled(ai_cfg),
+        TtsStage::Raspi => ai_cfg.tts_raspi_enabled && ai_cfg.tts_raspi_base_url.is_some(),
+    })
+}
+
+/// 声とエンジンを識別するキー。変わるとフレーズキャッシュは無効化される。
+fn tts_engine_key(ai_cfg: &config::AiConfig, stage: TtsStage) -> String {
+    match stage {
+        TtsStage::Local => format!(
+            "voicevox|{}|speaker={}",
+            ai_cfg.tts_local_server_base_url, VOICEVOX_SPEAKER_ID
+        ),
+        TtsStage::Cloud => format!(
+            "openai|{}|model={}|voice={}",
+            ai_cfg.openai_base_url, OPENAI_TTS_MODEL, OPENAI_TTS_VOICE
+        ),
+        TtsStage::Raspi => format!(
+            "voicevox|{}|speaker={}",
+            ai_cfg.tts_raspi_base_url.as_deref().unwrap_or_default(),
+            VOICEVOX_SPEAKER_ID
+        ),
+    }
+}
+
+static TTS_PHRASE_CACHE: OnceLock<Option<(TtsStage, TtsPhraseCache)>> = OnceLock::new();
+
+fn tts_phrase_cache() -> Option<&'static (TtsStage, TtsPhraseCache)> {
+    TTS_PHRASE_CACHE
+        .get_or_init(|| {
+            let cache_cfg = config::tts_cache_config();
+            if !cache_cfg.enabled {
+                return None;
+            }
+            let ai_cfg = config::ai_config();
+            let stage = preferred_tts_stage(ai_cfg)?;
+            match TtsPhraseCache::open(
+                PathBuf::from(&cache_cfg.dir),
+                cache_cfg.max_entries,
+                tts_engine_key(ai_cfg, stage),
+            ) {
+                Ok(cache) => {
+                    info!(
+                        "[tts cache] enabled dir={} tts_stage={} entries={}",
+                        cache_cfg.dir,
+                        stage.as_str(),
+                        cache.len()
+                    );
+                    Some((stage, cache))
+                }
+                Err(err) => {
+                    log::warn!(
+                        "[tts cache] disabled: failed to open dir={}: {}",
+                        cache_cfg.dir,
+                        err
+                    );
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// 定型文の TTS。キャッシュにあれば即返し、なければ合成して最優先ステージの音声のみ保存する。
+/// フォールバック先で合成された音声は別の声になり得るため保存しない。
+/// 返すのは通話ごとの一時ファイルで、再生後に消してよい（キャッシュの追い出しの影響を受けない）。
+pub async fn synth_phrase_to_wav(call_id: &str, text: &str) -> Result<PathBuf> {
+    let Some((preferred_stage, cache)) = tts_phrase_cache() else {
+        return tts::synth_to_wav(call_id, text, None)
+            .await
+            .map(PathBuf::from);
+    };
+    let out = PathBuf::from(tts::default_tts_output_path(call_id)?);
+    if cache.lookup(text, &out) {
+        log::debug!("[tts {call_id}] phrase cache hit path={}", out.display());
+        return Ok(out);
+    }
+
+    let (stage, wav_bytes) = synth_wav_bytes(call_id, text).await?;
+    if stage == *preferred_stage {
+        if let Err(err) = cache.store(text, &wav_bytes).await {
+            log::warn!("[tts {call_id}] phrase cache store failed: {}", err);
+        }
+    }
+    tokio::fs::write(&out, &wav_bytes).await?;
+    info!("[tts {call_id}] TTS written to {}", out.display());
+    Ok(out)
+}
+
+/// 起動時に定型文をまとめて合成し、初回通話の待ち時間をなくす。
+pub async fn warm_up_phrase_cache(phrases: Vec<String>) {
+    let Some((_, cache)) = tts_phrase_cache() else {
+        return;
+    };
+    let mut warmed = 0usize;
+    for phrase in phrases {
+        if phrase.trim().is_empty() || cache.contains(&phrase) {
+            continue;
+        }
+        match synth_phrase_to_wav("tts-warmup", &phrase).await {
+            Ok(path) => {
+                if cache.contains(&phrase) {
+                    warmed += 1;
+                }
+                // 再生しないので一時ファイルは不要
+                tokio::fs::remove_file(path).await.ok();
+            }
+            Err(err) => log::warn!("[tts cache] warm-up failed: {}", err),
+        }
+    }
+    info!(
+        "[tts cache] warm-up done synthesized={} entries={}",
+        warmed,
+        cache.len()
+    );
+}
+
 async fn synth_zundamon_for_stage(
     base_url: &str,
     _call_id: &str,
@@ -2268,7 +2525,7 @@ async fn synth_zundamon_for_stage(
     http_timeout: Duration,
 ) -> Result<Vec<u8>> {
     let client = http_client(http_timeout)?;
-    let speaker_id = 3; // ずんだもん ノーマル
+    let speaker_id = VOICEVOX_SPEAKER_ID;
 
     let query_url = join_url_path(base_url, "/audio_query");
     let query_resp = client
@@ -2325,11 +2582,12 @@ async fn synth_zundamon_for_stage(
 /// ```
 #[allow(dead_code)]
 async fn call_gemini(messages: &[ChatMessage]) -> Result<String> {
-    call_gemini_with_http_timeout(messages, config::timeouts().ai_http).await
+    call_gemini_with_http_timeout(messages, &llm::system_prompt(), config::timeouts().ai_http).await
 }
 
 async fn call_gemini_with_http_timeout(
     messages: &[ChatMessage],
+    system_prompt: &str,
     http_timeout: Duration,
 ) -> Result<String> {
     let client = http_client(http_timeout)?;
@@ -2347,17 +2605,18 @@ async fn call_gemini_with_http_timeout(
     );
 
     let mut contents = Vec::with_capacity(messages.len() + 1);
-    let system_prompt = llm::system_prompt();
     contents.push(GeminiContent {
         role: Some("user".to_string()),
         parts: vec![GeminiPart {
-            text: system_prompt,
+            text: system_prompt.to_string(),
         }],
     });
     for msg in messages {
         let role = match msg.role {
             Role::User => "user",
             Role::Assistant => "model",
+            // Gemini はsystemロールを持たないため user として渡す（共通プロンプトと同じ扱い）
+            Role::System => "user",
         };
         contents.push(GeminiContent {
             role: Some(role.to_string()),
@@ -0,0 +1,433 @@
+//! AI フォールバックステージ（ASR/LLM/TTS × local/cloud/raspi）ごとのサーキットブレーカーと遅延統計。
+//! 連続失敗でブレーカーを開き、開いている間は該当ステージをスキップしてタイムアウト待ちを避ける。
+//! クールダウン経過後（local はヘルスプローブ成功時も）に half-open として 1 リクエストだけ試行する。
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::shared::config::{self, AiStageHealthConfig};
+use crate::shared::metrics;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(super) enum StageKind {
+    Asr,
+    Llm,
+    Tts,
+}
+
+impl StageKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Asr => "asr",
+            Self::Llm => "llm",
+            Self::Tts => "tts",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BreakerState {
+    Closed,
+    Open {
+        until: Instant,
+    },
+    /// `trial_started` は試行中のリクエスト開始時刻（未試行なら None）
+    HalfOpen {
+        trial_started: Option<Instant>,
+    },
+}
+
+impl BreakerState {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open { .. } => "open",
+            Self::HalfOpen { .. } => "half_open",
+        }
+    }
+}
+
+struct Sample {
+    latency: Duration,
+    ok: bool,
+}
+
+struct StageStats {
+    state: BreakerState,
+    consecutive_failures: u32,
+    samples: VecDeque<Sample>,
+}
+
+impl Default for StageStats {
+    fn default() -> Self {
+        Self {
+            state: BreakerState::Closed,
+            consecutive_failures: 0,
+            samples: VecDeque::new(),
+        }
+    }
+}
+
+impl StageStats {
+    fn p50(&self, min_samples: usize) -> Option<Duration> {
+        let mut latencies: Vec<Duration> = self
+            .samples
+            .iter()
+            .filter(|sample| sample.ok)
+            .map(|sample| sample.latency)
+            .collect();
+        if latencies.is_empty() || latencies.len() < min_samples {
+            return None;
+        }
+        latencies.sort();
+        Some(latencies[(latencies.len() - 1) / 2])
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StageHealthSnapshot {
+    state: &'static str,
+    #[serde(rename = "consecutiveFailures")]
+    consecutive_failures: u32,
+    samples: usize,
+    #[serde(rename = "errorRate")]
+    error_rate: Option<f64>,
+    #[serde(rename = "p50LatencyMs")]
+    p50_latency_ms: Option<u64>,
+}
+
+pub(super) struct StageHealthRegistry {
+    cfg: AiStageHealthConfig,
+    stages: Mutex<HashMap<(StageKind, &'static str), StageStats>>,
+}
+
+impl StageHealthRegistry {
+    pub(super) fn new(cfg: AiStageHealthConfig) -> Self {
+        Self {
+            cfg,
+            stages: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// ステージを試行してよいか判定する。half-open では 1 リクエストだけ通す。
+    pub(super) fn try_acquire(&self, kind: StageKind, stage: &'static str, now: Instant) -> bool {
+        if !self.cfg.breaker_enabled {
+            return true;
+        }
+        let mut stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = stages.entry((kind, stage)).or_default();
+        match stats.state {
+            BreakerState::Closed => true,
+            BreakerState::Open { until } if now < until => false,
+            BreakerState::Open { .. } => {
+                stats.state = BreakerState::HalfOpen {
+                    trial_started: Some(now),
+                };
+                true
+            }
+            BreakerState::HalfOpen {
+                trial_started: Some(started),
+            } if now.saturating_duration_since(started) < self.cfg.cooldown => false,
+            // 試行が結果を残さず中断された場合もクールダウン後に再試行できるようにする
+            BreakerState::HalfOpen { .. } => {
+                stats.state = BreakerState::HalfOpen {
+                    trial_started: Some(now),
+                };
+                true
+            }
+        }
+    }
+
+    pub(super) fn record(
+        &self,
+        kind: StageKind,
+        stage: &'static str,
+        latency: Duration,
+        ok: bool,
+        now: Instant,
+    ) {
+        let mut stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = stages.entry((kind, stage)).or_default();
+        stats.samples.push_back(Sample { latency, ok });
+        while stats.samples.len() > self.cfg.stats_window {
+            stats.samples.pop_front();
+        }
+        if !self.cfg.breaker_enabled {
+            return;
+        }
+        let previous = stats.state;
+        if ok {
+            stats.consecutive_failures = 0;
+            stats.state = BreakerState::Closed;
+        } else {
+            stats.consecutive_failures = stats.consecutive_failures.saturating_add(1);
+            let reopen = matches!(previous, BreakerState::HalfOpen { .. })
+                || stats.consecutive_failures >= self.cfg.failure_threshold;
+            if reopen {
+                stats.state = BreakerState::Open {
+                    until: now + self.cfg.cooldown,
+                };
+            }
+        }
+        if previous.as_str() != stats.state.as_str() {
+            log::info!(
+                "[{}] circuit breaker {} -> {}: stage={} consecutive_failures={}",
+                kind.as_str(),
+                previous.as_str(),
+                stats.state.as_str(),
+                stage,
+                stats.consecutive_failures
+            );
+        }
+    }
+
+    /// ヘルスプローブの結果を反映する。開いているブレーカーのみが対象。
+    pub(super) fn record_probe(
+        &self,
+        kind: StageKind,
+        stage: &'static str,
+        healthy: bool,
+        now: Instant,
+    ) {
+        let mut stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(stats) = stages.get_mut(&(kind, stage)) else {
+            return;
+        };
+        if !matches!(stats.state, BreakerState::Open { .. }) {
+            return;
+        }
+        stats.state = if healthy {
+            log::info!(
+                "[{}] circuit breaker open -> half_open: stage={} reason=probe_ok",
+                kind.as_str(),
+                stage
+            );
+            BreakerState::HalfOpen {
+                trial_started: None,
+            }
+        } else {
+            BreakerState::Open {
+                until: now + self.cfg.cooldown,
+            }
+        };
+    }
+
+    pub(super) fn is_open(&self, kind: StageKind, stage: &'static str) -> bool {
+        let stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        stages
+            .get(&(kind, stage))
+            .is_some_and(|stats| matches!(stats.state, BreakerState::Open { .. }))
+    }
+
+    /// 設定順のうち p50 が算出できるステージ同士だけを速い順に並べ替える。
+    /// サンプル不足のステージは元の位置に残す。
+    pub(super) fn ordered<S: Copy, const N: usize>(
+        &self,
+        kind: StageKind,
+        stages: [S; N],
+        name: fn(S) -> &'static str,
+    ) -> [S; N] {
+        if !self.cfg.latency_ordering {
+            return stages;
+        }
+        let p50s: Vec<Option<Duration>> = {
+            let stats = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+            stages
+                .iter()
+                .map(|stage| {
+                    stats
+                        .get(&(kind, name(*stage)))
+                        .and_then(|s| s.p50(self.cfg.min_samples))
+                })
+                .collect()
+        };
+        let slots: Vec<usize> = (0..N).filter(|i| p50s[*i].is_some()).collect();
+        let mut measured = slots.clone();
+        measured.sort_by_key(|i| p50s[*i]);
+        let mut ordered = stages;
+        for (slot, source) in slots.into_iter().zip(measured) {
+            ordered[slot] = stages[source];
+        }
+        ordered
+    }
+
+    pub(super) fn snapshot(&self, kind: StageKind, stage: &'static str) -> StageHealthSnapshot {
+        let stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(stats) = stages.get(&(kind, stage)) else {
+            return StageHealthSnapshot {
+                state: BreakerState::Closed.as_str(),
+                consecutive_failures: 0,
+                samples: 0,
+                error_rate: None,
+                p50_latency_ms: None,
+            };
+        };
+        let samples = stats.samples.len();
+        let errors = stats.samples.iter().filter(|sample| !sample.ok).count();
+        StageHealthSnapshot {
+            state: stats.state.as_str(),
+            consecutive_failures: stats.consecutive_failures,
+            samples,
+            error_rate: (samples > 0).then(|| errors as f64 / samples as f64),
+            p50_latency_ms: stats
+                .p50(self.cfg.min_samples)
+                .map(|latency| latency.as_millis() as u64),
+        }
+    }
+}
+
+static REGISTRY: OnceLock<StageHealthRegistry> = OnceLock::new();
+
+pub(super) fn registry() -> &'static StageHealthRegistry {
+    REGISTRY.get_or_init(|| StageHealthRegistry::new(config::ai_stage_health_config().clone()))
+}
+
+/// ブレーカーが開いていればログを出して `false` を返す。
+pub(super) fn acquire(call_id: &str, kind: StageKind, stage: &'static str) -> bool {
+    if registry().try_acquire(kind, stage, Instant::now()) {
+        return true;
+    }
+    log::info!(
+        "[{} {call_id}] stage skipped: {}_stage={} reason=circuit_open",
+        kind.as_str(),
+        kind.as_str(),
+        stage
+    );
+    false
+}
+
+pub(super) fn record(kind: StageKind, stage: &'static str, started: Instant, ok: bool) {
+    let now = Instant::now();
+    let latency = now.saturating_duration_since(started);
+    let labels = [kind.as_str(), stage];
+    metrics::AI_STAGE_LATENCY.observe_duration(&labels, latency);
+    if !ok {
+        metrics::AI_STAGE_FAILURES.inc(&labels);
+    }
+    registry().record(kind, stage, latency, ok, now);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_cfg() -> AiStageHealthConfig {
+        AiStageHealthConfig {
+            breaker_enabled: true,
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(10),
+            probe_interval: Duration::from_secs(5),
+            latency_ordering: true,
+            stats_window: 10,
+            min_samples: 2,
+        }
+    }
+
+    #[test]
+    fn breaker_opens_after_threshold_and_half_opens_after_cooldown() {
+        let registry = StageHealthRegistry::new(test_cfg());
+        let t0 = Instant::now();
+        let latency = Duration::from_millis(100);
+
+        registry.record(StageKind::Asr, "local", latency, false, t0);
+        assert!(registry.try_acquire(StageKind::Asr, "local", t0));
+        registry.record(StageKind::Asr, "local", latency, false, t0);
+        assert!(registry.is_open(StageKind::Asr, "local"));
+        assert!(!registry.try_acquire(StageKind::Asr, "local", t0 + Duration::from_secs(5)));
+
+        // クールダウン後は 1 リクエストだけ通す
+        let t1 = t0 + Duration::from_secs(10);
+        assert!(registry.try_acquire(StageKind::Asr, "local", t1));
+        assert!(!registry.try_acquire(StageKind::Asr, "local", t1));
+        assert_eq!(
+            registry.snapshot(StageKind::Asr, "local").state,
+            "half_open"
+        );
+
+        registry.record(StageKind::Asr, "local", latency, true, t1);
+        assert_eq!(registry.snapshot(StageKind::Asr, "local").state, "closed");
+        assert!(registry.try_acquire(StageKind::Asr, "local", t1));
+    }
+
+    #[test]
+    fn failed_half_open_trial_reopens_breaker() {
+        let registry = StageHealthRegistry::new(test_cfg());
+        let t0 = Instant::now();
+        let latency = Duration::from_millis(100);
+        registry.record(StageKind::Llm, "local", latency, false, t0);
+        registry.record(StageKind::Llm, "local", latency, false, t0);
+
+        let t1 = t0 + Duration::from_secs(10);
+        assert!(registry.try_acquire(StageKind::Llm, "local", t1));
+        registry.record(StageKind::Llm, "local", latency, false, t1);
+        assert!(registry.is_open(StageKind::Llm, "local"));
+        assert!(!registry.try_acquire(StageKind::Llm, "local", t1 + Duration::from_secs(9)));
+    }
+
+    #[test]
+    fn probe_result_moves_open_breaker_to_half_open() {
+        let registry = StageHealthRegistry::new(test_cfg());
+        let t0 = Instant::now();
+        let latency = Duration::from_millis(100);
+        registry.record(StageKind::Tts, "local", latency, false, t0);
+        registry.record(StageKind::Tts, "local", latency, false, t0);
+
+        registry.record_probe(StageKind::Tts, "local", false, t0);
+        assert!(registry.is_open(StageKind::Tts, "local"));
+        registry.record_probe(StageKind::Tts, "local", true, t0);
+        assert!(registry.try_acquire(StageKind::Tts, "local", t0));
+        assert!(!registry.try_acquire(StageKind::Tts, "local", t0));
+    }
+
+    #[test]
+    fn disabled_breaker_always_allows() {
+        let registry = StageHealthRegistry::new(AiStageHealthConfig {
+            breaker_enabled: false,
+            ..test_cfg()
+        });
+        let t0 = Instant::now();
+        for _ in 0..5 {
+            registry.record(StageKind::Asr, "local", Duration::ZERO, false, t0);
+        }
+        assert!(registry.try_acquire(StageKind::Asr, "local", t0));
+        assert_eq!(registry.snapshot(StageKind::Asr, "local").samples, 5);
+    }
+
+    #[test]
+    fn ordered_sorts_measured_stages_by_p50_and_keeps_unmeasured_in_place() {
+        let registry = StageHealthRegistry::new(test_cfg());
+        let t0 = Instant::now();
+        for ms in [900, 1000, 1100] {
+            registry.record(StageKind::Llm, "local", Duration::from_millis(ms), true, t0);
+        }
+        for ms in [200, 300] {
+            registry.record(StageKind::Llm, "raspi", Duration::from_millis(ms), true, t0);
+        }
+        // cloud はサンプル不足（min_samples=2）
+        registry.record(StageKind::Llm, "cloud", Duration::from_millis(10), true, t0);
+
+        let ordered = registry.ordered(StageKind::Llm, ["local", "cloud", "raspi"], |s| s);
+        assert_eq!(ordered, ["raspi", "cloud", "local"]);
+
+        let snapshot = registry.snapshot(StageKind::Llm, "local");
+        assert_eq!(snapshot.p50_latency_ms, Some(1000));
+        assert_eq!(snapshot.error_rate, Some(0.0));
+    }
+
+    #[test]
+    fn ordered_keeps_config_order_when_disabled() {
+        let registry = StageHealthRegistry::new(AiStageHealthConfig {
+            latency_ordering: false,
+            ..test_cfg()
+        });
+        let t0 = Instant::now();
+        for _ in 0..3 {
+            registry.record(StageKind::Tts, "cloud", Duration::from_millis(1), true, t0);
+        }
+        let ordered = registry.ordered(StageKind::Tts, ["local", "cloud", "raspi"], |s| s);
+        assert_eq!(ordered, ["local", "cloud", "raspi"]);
+    }
+}
@@ -19,7 +19,7 @@ pub async fn synth_zundamon_wav(call_id: &str, text: &str, out_path: &str) -> Re
     super::synth_zundamon_wav(call_id, text, out_path).await
 }
 
-fn default_tts_output_path(call_id: &str) -> Result<String> {
+pub(super) fn default_tts_output_path(call_id: &str) -> Result<String> {
     let safe_call_id = sanitize_call_id_for_tmp_filename(call_id)?;
     let unique_suffix = SystemTime::now()
         .duration_since(UNIX_EPOCH)
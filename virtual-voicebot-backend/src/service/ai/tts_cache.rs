@@ -0,0 +1,318 @@
+//! 定型文（intent_router.yaml の固定応答など）の TTS 結果をディスクに保持するキャッシュ。
+//! キーは (テキスト, 声, エンジン) で、声・エンジンの設定が変わった場合は起動時に全破棄する。
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const ENGINE_KEY_FILE: &str = "engine.key";
+const ENTRY_EXTENSION: &str = "wav";
+
+pub struct TtsPhraseCache {
+    dir: PathBuf,
+    max_entries: usize,
+    engine_key: String,
+    /// エントリ名 -> 最終利用順（大きいほど新しい）
+    index: Mutex<LruIndex>,
+}
+
+#[derive(Default)]
+struct LruIndex {
+    entries: HashMap<String, u64>,
+    tick: u64,
+}
+
+impl LruIndex {
+    fn touch(&mut self, name: &str) {
+        self.tick += 1;
+        self.entries.insert(name.to_string(), self.tick);
+    }
+
+    fn pop_oldest(&mut self) -> Option<String> {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, tick)| **tick)
+            .map(|(name, _)| name.clone())?;
+        self.entries.remove(&oldest);
+        Some(oldest)
+    }
+}
+
+impl TtsPhraseCache {
+    /// キャッシュディレクトリを開く。保存済みのエンジンキーと異なる場合は既存エントリを破棄する。
+    pub fn open(dir: PathBuf, max_entries: usize, engine_key: String) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        let key_path = dir.join(ENGINE_KEY_FILE);
+        let stored_key = match std::fs::read_to_string(&key_path) {
+            Ok(value) => Some(value),
+            Err(err) if err.kind() == ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+        if stored_key.as_deref() != Some(engine_key.as_str()) {
+            let removed = remove_entries(&dir)?;
+            if stored_key.is_some() {
+                log::info!(
+                    "[tts cache] voice/engine config changed; invalidated entries={}",
+                    removed
+                );
+            }
+            std::fs::write(&key_path, &engine_key)?;
+        }
+
+        let mut existing = list_entries(&dir)?;
+        existing.sort_by_key(|(_, modified)| *modified);
+        let mut index = LruIndex::default();
+        for (name, _) in existing {
+            index.touch(&name);
+        }
+
+        let cache = Self {
+            dir,
+            max_entries: max_entries.max(1),
+            engine_key,
+            index: Mutex::new(index),
+        };
+        cache.evict_over_capacity();
+        Ok(cache)
+    }
+
+    /// キャッシュ済みの WAV を `dest`（通話ごとの一時パス）にハードリンク（できなければコピー）する。
+    /// ヒット時は LRU 上で最新に更新する。再生中に他の保存で追い出されても `dest` は残る。
+    pub fn lookup(&self, text: &str, dest: &Path) -> bool {
+        let name = self.entry_name(text);
+        let path = self.dir.join(&name);
+        let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        if !index.entries.contains_key(&name) {
+            return false;
+        }
+        // 追い出しは索引から外してからファイルを消すため、ロック中に取り出せば消されない
+        if let Err(err) =
+            std::fs::hard_link(&path, dest).or_else(|_| std::fs::copy(&path, dest).map(|_| ()))
+        {
+            index.entries.remove(&name);
+            if err.kind() != ErrorKind::NotFound {
+                log::warn!("[tts cache] failed to check out entry={}: {}", name, err);
+            }
+            return false;
+        }
+        index.touch(&name);
+        drop(index);
+        // 再起動後も利用順を引き継ぐため mtime を更新する（失敗しても動作には影響しない）
+        if let Ok(file) = std::fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        true
+    }
+
+    /// 合成済み WAV を保存し、キャッシュ内のパスを返す（再生には使わず `lookup` で取り出す）。
+    pub async fn store(&self, text: &str, wav_bytes: &[u8]) -> std::io::Result<PathBuf> {
+        let name = self.entry_name(text);
+        let path = self.dir.join(&name);
+        let tmp_path = self
+            .dir
+            .join(format!("{}.{}.tmp", name, uuid::Uuid::new_v4().simple()));
+        if let Err(err) = tokio::fs::write(&tmp_path, wav_bytes).await {
+            tokio::fs::remove_file(&tmp_path).await.ok();
+            return Err(err);
+        }
+        if let Err(err) = tokio::fs::rename(&tmp_path, &path).await {
+            tokio::fs::remove_file(&tmp_path).await.ok();
+            return Err(err);
+        }
+        self.index
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .touch(&name);
+        self.evict_over_capacity();
+        Ok(path)
+    }
+
+    pub fn contains(&self, text: &str) -> bool {
+        let name = self.entry_name(text);
+        self.index
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entries
+            .contains_key(&name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.index
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entries
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn entry_name(&self, text: &str) -> String {
+        let mut hash = FNV_OFFSET_BASIS;
+        hash = fnv1a(hash, self.engine_key.as_bytes());
+        hash = fnv1a(hash, &[0]);
+        hash = fnv1a(hash, text.as_bytes());
+        format!("{:016x}.{}", hash, ENTRY_EXTENSION)
+    }
+
+    fn evict_over_capacity(&self) {
+        let mut evicted = Vec::new();
+        {
+            let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+            while index.entries.len() > self.max_entries {
+                match index.pop_oldest() {
+                    Some(name) => evicted.push(name),
+                    None => break,
+                }
+            }
+        }
+        for name in evicted {
+            match std::fs::remove_file(self.dir.join(&name)) {
+                Ok(()) => log::debug!("[tts cache] evicted entry={}", name),
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => log::warn!("[tts cache] failed to evict entry={}: {}", name, err),
+            }
+        }
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// ファイル名に使うため、Rust のバージョンに依存しない FNV-1a を用いる。
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+fn is_entry_file(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some(ENTRY_EXTENSION)
+}
+
+fn list_entries(dir: &Path) -> std::io::Result<Vec<(String, SystemTime)>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if !is_entry_file(&path) {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let modified = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        entries.push((name.to_string(), modified));
+    }
+    Ok(entries)
+}
+
+fn remove_entries(dir: &Path) -> std::io::Result<usize> {
+    let mut removed = 0;
+    for (name, _) in list_entries(dir)? {
+        match std::fs::remove_file(dir.join(name)) {
+            Ok(()) => removed += 1,
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_cache(dir: &Path, max_entries: usize, engine_key: &str) -> TtsPhraseCache {
+        TtsPhraseCache::open(dir.to_path_buf(), max_entries, engine_key.to_string())
+            .expect("cache should open")
+    }
+
+    #[tokio::test]
+    async fn store_then_lookup_checks_out_cached_wav() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let out = tempfile::tempdir().expect("tempdir");
+        let dest = out.path().join("call-1.wav");
+        let cache = open_cache(dir.path(), 4, "voicevox|speaker=3");
+        assert!(!cache.lookup("おつなぎします", &dest));
+        assert!(!dest.exists());
+
+        cache.store("おつなぎします", b"RIFF").await.expect("store");
+        assert!(cache.lookup("おつなぎします", &dest));
+        assert_eq!(std::fs::read(&dest).expect("read"), b"RIFF");
+
+        // 再オープンしてもエントリは引き継がれる
+        drop(cache);
+        let reopened = open_cache(dir.path(), 4, "voicevox|speaker=3");
+        assert!(reopened.lookup("おつなぎします", &out.path().join("call-2.wav")));
+    }
+
+    #[tokio::test]
+    async fn checked_out_wav_survives_eviction_by_concurrent_store() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let out = tempfile::tempdir().expect("tempdir");
+        let dest = out.path().join("call-1.wav");
+        let cache = open_cache(dir.path(), 1, "engine");
+        let a = cache.store("a", b"a").await.expect("store a");
+        assert!(cache.lookup("a", &dest));
+
+        // 再生中に別の通話が保存して "a" を追い出しても、取り出した音声は残る
+        cache.store("b", b"b").await.expect("store b");
+        assert!(!a.exists());
+        assert!(!cache.contains("a"));
+        assert_eq!(std::fs::read(&dest).expect("read"), b"a");
+    }
+
+    #[tokio::test]
+    async fn store_evicts_least_recently_used_entry() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cache = open_cache(dir.path(), 2, "engine");
+        let a = cache.store("a", b"a").await.expect("store a");
+        cache.store("b", b"b").await.expect("store b");
+        let out = tempfile::tempdir().expect("tempdir");
+        assert!(cache.lookup("a", &out.path().join("a.wav")));
+        cache.store("c", b"c").await.expect("store c");
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains("a"));
+        assert!(!cache.contains("b"));
+        assert!(cache.contains("c"));
+        assert!(a.exists());
+    }
+
+    #[tokio::test]
+    async fn open_with_different_engine_key_invalidates_entries() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cache = open_cache(dir.path(), 4, "voicevox|speaker=3");
+        let stored = cache.store("こんにちは", b"wav").await.expect("store");
+        drop(cache);
+
+        let cache = open_cache(dir.path(), 4, "openai|voice=alloy");
+        assert!(cache.is_empty());
+        assert!(!stored.exists());
+        let out = tempfile::tempdir().expect("tempdir");
+        assert!(!cache.lookup("こんにちは", &out.path().join("call.wav")));
+    }
+
+    #[test]
+    fn entry_name_depends_on_text_and_engine() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let cache = open_cache(dir.path(), 4, "engine-a");
+        let name = cache.entry_name("text");
+        assert_eq!(name, cache.entry_name("text"));
+        assert_ne!(name, cache.entry_name("other"));
+        assert!(name.ends_with(".wav"));
+
+        let other = open_cache(dir.path(), 4, "engine-b");
+        assert_ne!(name, other.entry_name("text"));
+    }
+}
@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
 use std::sync::OnceLock;
 use std::time::{Duration, Instant};
 
@@ -7,6 +6,7 @@ use anyhow::{Context, Result};
 use serde_json::Value;
 use tokio::sync::Mutex;
 
+use crate::service::runtime_config::{self, ReloadError};
 use crate::shared::config;
 use crate::shared::ports::ai::{ChatMessage, Role, WeatherQuery};
 
@@ -36,10 +36,9 @@ const DEFAULT_WEATHER_PROMPT: &str = r#"
 - JSON以外の情報は使わない
 "#;
 
-const WEATHER_PROMPT_FILE_NAME: &str = "weather_prompt.local.txt";
-const WEATHER_PROMPT_EXAMPLE: &str = "weather_prompt.example.txt";
+pub(crate) const WEATHER_PROMPT_FILE_NAME: &str = "weather_prompt.local.txt";
+pub(crate) const WEATHER_PROMPT_EXAMPLE: &str = "weather_prompt.example.txt";
 
-static WEATHER_PROMPT_CACHE: OnceLock<String> = OnceLock::new();
 static WEATHER_CACHE: OnceLock<Mutex<HashMap<String, CachedReport>>> = OnceLock::new();
 
 pub async fn handle_weather(call_id: &str, query: WeatherQuery) -> Result<String> {
@@ -58,7 +57,7 @@ async fn summarize_weather(report: &WeatherReport, call_id: &str) -> String {
         "pops": report.pops,
     });
 
-    let prompt = weather_prompt();
+    let prompt = runtime_config::for_call(call_id).weather_prompt.clone();
     let ai_cfg = config::ai_config();
     let messages = vec![ChatMessage {
         role: Role::User,
@@ -342,42 +341,16 @@ fn location_to_area_code(location: &str) -> Option<String> {
     }
 }
 
-fn weather_prompt() -> String {
-    WEATHER_PROMPT_CACHE
-        .get_or_init(|| {
-            read_prompt_file()
-                .or_else(read_example_file)
-                .unwrap_or_else(|| DEFAULT_WEATHER_PROMPT.trim().to_string())
-        })
-        .clone()
+pub(crate) fn load_weather_prompt() -> Result<String, ReloadError> {
+    let prompt = match runtime_config::read_prompt_override(WEATHER_PROMPT_FILE_NAME)? {
+        Some(prompt) => Some(prompt),
+        None => runtime_config::read_prompt_override(WEATHER_PROMPT_EXAMPLE)?,
+    };
+    Ok(prompt.unwrap_or_else(default_weather_prompt))
 }
 
-fn read_prompt_file() -> Option<String> {
-    read_prompt_from(WEATHER_PROMPT_FILE_NAME)
-}
-
-fn read_example_file() -> Option<String> {
-    read_prompt_from(WEATHER_PROMPT_EXAMPLE)
-}
-
-fn read_prompt_from(name: &str) -> Option<String> {
-    // Try current working directory first, then executable directory.
-    let paths = [
-        PathBuf::from(name),
-        std::env::current_exe()
-            .ok()
-            .and_then(|p| p.parent().map(|d| d.join(name)))
-            .unwrap_or_default(),
-    ];
-    for path in paths {
-        if let Ok(text) = std::fs::read_to_string(&path) {
-            let trimmed = text.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
-            }
-        }
-    }
-    None
+pub(crate) fn default_weather_prompt() -> String {
+    DEFAULT_WEATHER_PROMPT.trim().to_string()
 }
 
 #[cfg(test)]
@@ -0,0 +1,297 @@
+//! 発信者メモリ（通話をまたいだ要約・重要事項）の組み立てと更新ロジック。
+//! 永続化は CallerMemoryPort に委譲し、ここでは LLM への入出力整形と、
+//! 保存期間を過ぎたメモリの定期削除のみを扱う。
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::service::call_control::router::sanitize_json_block;
+use crate::shared::config::{self, CallerMemoryConfig};
+use crate::shared::ports::ai::{ChatMessage, Role};
+use crate::shared::ports::caller_memory_port::{CallerMemory, CallerMemoryPort};
+
+/// 保存期間（`CALLER_MEMORY_RETENTION_DAYS`）を過ぎたメモリを `purge_interval` ごとに消す。
+/// 初回は起動直後に走る
+pub fn spawn_caller_memory_purge(port: Arc<dyn CallerMemoryPort>) {
+    let cfg = config::caller_memory_config();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(cfg.purge_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            purge_expired_once(port.as_ref(), cfg, Utc::now()).await;
+        }
+    });
+}
+
+async fn purge_expired_once(
+    port: &dyn CallerMemoryPort,
+    cfg: &CallerMemoryConfig,
+    now: DateTime<Utc>,
+) {
+    match port.purge_expired(cfg.retention_cutoff(now)).await {
+        Ok(0) => {}
+        Ok(purged) => log::info!("[caller_memory] purged expired entries={}", purged),
+        Err(err) => log::warn!("[caller_memory] purge failed: {}", err),
+    }
+}
+
+/// 過去の通話メモリを LLM へ渡す追加システム指示に変換する。
+pub(super) fn memory_context_message(memory: &CallerMemory) -> ChatMessage {
+    let mut content = format!(
+        "この発信者とは過去に{}回通話しています。以下は過去の会話の要約です。\
+         必要な場合のみ自然に活用し、要約の存在を読み上げないでください。\n要約: {}",
+        memory.call_count, memory.summary
+    );
+    if !memory.key_facts.is_empty() {
+        content.push_str("\n重要事項:");
+        for fact in &memory.key_facts {
+            content.push_str("\n- ");
+            content.push_str(fact);
+        }
+    }
+    ChatMessage {
+        role: Role::System,
+        content,
+    }
+}
+
+/// 前回までの要約と今回の会話履歴から、要約更新を依頼するメッセージを作る。
+pub(super) fn summary_request_messages(
+    previous: Option<&CallerMemory>,
+    history: &[ChatMessage],
+    cfg: &CallerMemoryConfig,
+) -> Vec<ChatMessage> {
+    let mut transcript = String::new();
+    for msg in history {
+        let speaker = match msg.role {
+            Role::User => "発信者",
+            Role::Assistant => "ボット",
+            Role::System => continue,
+        };
+        transcript.push_str(speaker);
+        transcript.push_str(": ");
+        transcript.push_str(&msg.content);
+        transcript.push('\n');
+    }
+    let (previous_summary, previous_facts) = match previous {
+        Some(memory) => (memory.summary.as_str(), memory.key_facts.join(" / ")),
+        None => ("なし", String::new()),
+    };
+    let instruction = format!(
+        "次回以降の通話で使う発信者メモリを更新してください。\
+         出力は JSON のみで {{\"summary\":\"...\",\"facts\":[\"...\"]}} の形式にしてください。\
+         summary は{}文字以内、facts は最大{}件とし、電話番号・カード番号・パスワードなどの機微情報は含めないでください。\n\
+         これまでの要約: {}\nこれまでの重要事項: {}\n今回の会話:\n{}",
+        cfg.max_summary_chars,
+        cfg.max_key_facts,
+        previous_summary,
+        previous_facts,
+        transcript
+    );
+    vec![ChatMessage {
+        role: Role::User,
+        content: instruction,
+    }]
+}
+
+/// LLM の応答を要約と重要事項に分解する。JSON でなければ全文を要約として扱う。
+pub(super) fn parse_summary_response(
+    raw: &str,
+    cfg: &CallerMemoryConfig,
+) -> Option<(String, Vec<String>)> {
+    #[derive(Deserialize)]
+    struct SummaryPayload {
+        #[serde(default)]
+        summary: String,
+        #[serde(default)]
+        facts: Vec<String>,
+    }
+
+    let sanitized = sanitize_json_block(raw);
+    let (summary, facts) = match serde_json::from_str::<SummaryPayload>(sanitized.as_str()) {
+        Ok(payload) => (payload.summary, payload.facts),
+        Err(_) => (sanitized, Vec::new()),
+    };
+    let summary = truncate_chars(summary.trim(), cfg.max_summary_chars);
+    if summary.is_empty() {
+        return None;
+    }
+    let facts = facts
+        .into_iter()
+        .map(|fact| fact.trim().to_string())
+        .filter(|fact| !fact.is_empty())
+        .take(cfg.max_key_facts)
+        .collect();
+    Some((summary, facts))
+}
+
+pub(super) fn next_memory(
+    phone_number: String,
+    previous: Option<&CallerMemory>,
+    summary: String,
+    key_facts: Vec<String>,
+    ended_at: DateTime<Utc>,
+) -> CallerMemory {
+    CallerMemory {
+        phone_number,
+        summary,
+        key_facts,
+        call_count: previous.map_or(0, |memory| memory.call_count) + 1,
+        last_call_at: ended_at,
+    }
+}
+
+fn truncate_chars(input: &str, max_chars: usize) -> String {
+    input.chars().take(max_chars).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::shared::ports::caller_memory_port::CallerMemoryFuture;
+
+    /// `purge_expired` に渡された境界だけを記録する
+    #[derive(Default)]
+    struct PurgeSpy {
+        not_before: Mutex<Vec<DateTime<Utc>>>,
+    }
+
+    impl CallerMemoryPort for PurgeSpy {
+        fn load(
+            &self,
+            _phone_number: &str,
+            _not_before: DateTime<Utc>,
+        ) -> CallerMemoryFuture<Option<CallerMemory>> {
+            Box::pin(async { Ok(None) })
+        }
+
+        fn save(&self, _memory: CallerMemory) -> CallerMemoryFuture<()> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn erase(&self, _phone_number: &str) -> CallerMemoryFuture<bool> {
+            Box::pin(async { Ok(false) })
+        }
+
+        fn purge_expired(&self, not_before: DateTime<Utc>) -> CallerMemoryFuture<u64> {
+            self.not_before.lock().unwrap().push(not_before);
+            Box::pin(async { Ok(1) })
+        }
+    }
+
+    fn test_cfg() -> CallerMemoryConfig {
+        CallerMemoryConfig {
+            enabled: true,
+            retention: Duration::from_secs(30 * 24 * 60 * 60),
+            max_summary_chars: 10,
+            max_key_facts: 2,
+            purge_interval: Duration::from_secs(3600),
+        }
+    }
+
+    fn sample_memory() -> CallerMemory {
+        CallerMemory {
+            phone_number: "+819012345678".to_string(),
+            summary: "前回は予約の変更".to_string(),
+            key_facts: vec!["名前は田中".to_string()],
+            call_count: 2,
+            last_call_at: DateTime::parse_from_rfc3339("2026-02-27T00:00:00+00:00")
+                .expect("valid timestamp")
+                .with_timezone(&Utc),
+        }
+    }
+
+    #[test]
+    fn memory_context_message_is_system_role_with_facts() {
+        let msg = memory_context_message(&sample_memory());
+        assert_eq!(msg.role, Role::System);
+        assert!(msg.content.contains("2回"));
+        assert!(msg.content.contains("前回は予約の変更"));
+        assert!(msg.content.contains("- 名前は田中"));
+    }
+
+    #[test]
+    fn parse_summary_response_reads_json_and_applies_limits() {
+        let raw =
+            "```json\n{\"summary\":\"0123456789abc\",\"facts\":[\"a\",\" \",\"b\",\"c\"]}\n```";
+        let (summary, facts) = parse_summary_response(raw, &test_cfg()).expect("parsed");
+        assert_eq!(summary, "0123456789");
+        assert_eq!(facts, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn parse_summary_response_falls_back_to_plain_text() {
+        let (summary, facts) = parse_summary_response("予約の相談", &test_cfg()).expect("parsed");
+        assert_eq!(summary, "予約の相談");
+        assert!(facts.is_empty());
+        assert!(parse_summary_response("  ", &test_cfg()).is_none());
+    }
+
+    #[test]
+    fn summary_request_messages_skip_system_messages() {
+        let history = vec![
+            memory_context_message(&sample_memory()),
+            ChatMessage {
+                role: Role::User,
+                content: "予約を変えたい".to_string(),
+            },
+            ChatMessage {
+                role: Role::Assistant,
+                content: "承知しました".to_string(),
+            },
+        ];
+        let messages = summary_request_messages(Some(&sample_memory()), &history, &test_cfg());
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].content.contains("発信者: 予約を変えたい"));
+        assert!(messages[0].content.contains("ボット: 承知しました"));
+        assert!(!messages[0].content.contains("要約の存在を読み上げない"));
+    }
+
+    #[test]
+    fn next_memory_increments_call_count() {
+        let previous = sample_memory();
+        let ended_at = Utc::now();
+        let memory = next_memory(
+            previous.phone_number.clone(),
+            Some(&previous),
+            "s".to_string(),
+            Vec::new(),
+            ended_at,
+        );
+        assert_eq!(memory.call_count, 3);
+        assert_eq!(memory.last_call_at, ended_at);
+        assert_eq!(
+            next_memory(
+                "+81".to_string(),
+                None,
+                "s".to_string(),
+                Vec::new(),
+                ended_at
+            )
+            .call_count,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn purge_expired_once_uses_retention_cutoff() {
+        let spy = PurgeSpy::default();
+        let cfg = test_cfg();
+        let now = Utc::now();
+        purge_expired_once(&spy, &cfg, now).await;
+        purge_expired_once(&spy, &cfg, now + chrono::Duration::hours(1)).await;
+        assert_eq!(
+            *spy.not_before.lock().unwrap(),
+            vec![
+                now - chrono::Duration::days(30),
+                now + chrono::Duration::hours(1) - chrono::Duration::days(30),
+            ]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! 音声感情認識（SER）の結果を対話へ反映するポリシー。
+//! 連続ターンの感情による有人エスカレーション判定と、LLM への口調指示の組み立てのみを扱う。
+
+use crate::shared::config::EmotionPolicyConfig;
+use crate::shared::ports::ai::{ChatMessage, Emotion, Role, SerResult};
+
+/// 通話ログ・設定で用いる感情ラベル。
+pub(super) fn emotion_label(emotion: Emotion) -> &'static str {
+    match emotion {
+        Emotion::Neutral => "neutral",
+        Emotion::Happy => "happy",
+        Emotion::Sad => "sad",
+        Emotion::Angry => "angry",
+        Emotion::Unknown => "unknown",
+    }
+}
+
+/// 通話中の感情推移を追跡し、エスカレーション条件の成立を判定する。
+#[derive(Debug, Default)]
+pub(super) struct EmotionTracker {
+    consecutive_matches: u32,
+    escalated: bool,
+    latest: Option<(Emotion, f32)>,
+}
+
+impl EmotionTracker {
+    /// 1 ターン分の SER 結果を反映する。エスカレーションすべきターンなら `true`（通話中 1 回のみ）。
+    pub(super) fn observe(&mut self, result: &SerResult, cfg: &EmotionPolicyConfig) -> bool {
+        self.latest = Some((result.emotion, result.confidence));
+        let label = emotion_label(result.emotion);
+        let matched = result.confidence >= cfg.min_confidence
+            && cfg.escalation_emotions.iter().any(|value| value == label);
+        if matched {
+            self.consecutive_matches = self.consecutive_matches.saturating_add(1);
+        } else {
+            self.consecutive_matches = 0;
+        }
+        if !cfg.escalation_enabled || self.escalated {
+            return false;
+        }
+        if self.consecutive_matches >= cfg.consecutive_turns {
+            self.escalated = true;
+            return true;
+        }
+        false
+    }
+
+    /// 直近ターンの感情に応じた口調指示。信頼度が閾値未満、または平常時は `None`。
+    pub(super) fn tone_message(&self, cfg: &EmotionPolicyConfig) -> Option<ChatMessage> {
+        if !cfg.tone_adjust_enabled {
+            return None;
+        }
+        let (emotion, confidence) = self.latest?;
+        if confidence < cfg.min_confidence {
+            return None;
+        }
+        let content = match emotion {
+            Emotion::Angry => {
+                "発信者は苛立っている様子です。まず短くお詫びし、言い訳をせず、\
+                 落ち着いた丁寧な口調で要点だけを簡潔に答えてください。"
+            }
+            Emotion::Sad => {
+                "発信者は落ち込んでいる様子です。気持ちに寄り添う一言を添え、\
+                 やさしくゆっくりとした口調で答えてください。"
+            }
+            Emotion::Neutral | Emotion::Happy | Emotion::Unknown => return None,
+        };
+        Some(ChatMessage {
+            role: Role::System,
+            content: content.to_string(),
+        })
+    }
+}
+
+/// 起動時に TTS キャッシュへ事前合成する定型文。
+pub(super) fn fixed_phrases(cfg: &EmotionPolicyConfig) -> Vec<String> {
+    if cfg.escalation_enabled {
+        vec![cfg.apology_text.clone()]
+    } else {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> EmotionPolicyConfig {
+        EmotionPolicyConfig {
+            escalation_enabled: true,
+            escalation_emotions: vec!["angry".to_string()],
+            min_confidence: 0.7,
+            consecutive_turns: 2,
+            transfer_person: Some("担当者".to_string()),
+            apology_text: "申し訳ございません。".to_string(),
+            tone_adjust_enabled: true,
+        }
+    }
+
+    fn ser(emotion: Emotion, confidence: f32) -> SerResult {
+        SerResult {
+            session_id: "call-1".to_string(),
+            stream_id: "main".to_string(),
+            emotion,
+            confidence,
+            arousal: None,
+            valence: None,
+        }
+    }
+
+    #[test]
+    fn escalates_once_after_consecutive_confident_turns() {
+        let cfg = cfg();
+        let mut tracker = EmotionTracker::default();
+        assert!(!tracker.observe(&ser(Emotion::Angry, 0.8), &cfg));
+        assert!(tracker.observe(&ser(Emotion::Angry, 0.9), &cfg));
+        assert!(
+            !tracker.observe(&ser(Emotion::Angry, 0.9), &cfg),
+            "escalation must fire only once per call"
+        );
+    }
+
+    #[test]
+    fn low_confidence_or_other_emotion_resets_streak() {
+        let cfg = cfg();
+        let mut tracker = EmotionTracker::default();
+        assert!(!tracker.observe(&ser(Emotion::Angry, 0.8), &cfg));
+        assert!(!tracker.observe(&ser(Emotion::Angry, 0.5), &cfg));
+        assert!(!tracker.observe(&ser(Emotion::Angry, 0.8), &cfg));
+        assert!(!tracker.observe(&ser(Emotion::Neutral, 0.9), &cfg));
+        assert!(!tracker.observe(&ser(Emotion::Angry, 0.8), &cfg));
+        assert!(tracker.observe(&ser(Emotion::Angry, 0.8), &cfg));
+    }
+
+    #[test]
+    fn disabled_escalation_still_tracks_tone() {
+        let mut cfg = cfg();
+        cfg.escalation_enabled = false;
+        let mut tracker = EmotionTracker::default();
+        assert!(!tracker.observe(&ser(Emotion::Angry, 0.8), &cfg));
+        assert!(!tracker.observe(&ser(Emotion::Angry, 0.8), &cfg));
+        let tone = tracker.tone_message(&cfg).expect("tone for angry caller");
+        assert_eq!(tone.role, Role::System);
+        assert!(fixed_phrases(&cfg).is_empty());
+    }
+
+    #[test]
+    fn tone_message_requires_confident_non_neutral_emotion() {
+        let cfg = cfg();
+        let mut tracker = EmotionTracker::default();
+        assert!(tracker.tone_message(&cfg).is_none());
+        tracker.observe(&ser(Emotion::Sad, 0.6), &cfg);
+        assert!(tracker.tone_message(&cfg).is_none());
+        tracker.observe(&ser(Emotion::Sad, 0.9), &cfg);
+        assert!(tracker.tone_message(&cfg).is_some());
+        tracker.observe(&ser(Emotion::Happy, 0.9), &cfg);
+        assert!(tracker.tone_message(&cfg).is_none());
+    }
+}
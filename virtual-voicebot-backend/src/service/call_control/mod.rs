@@ -3,7 +3,9 @@
 //! ai ポートを呼び出してボット音声(WAV)のパスを session に返す。
 //! transport/sip/rtp には依存せず、SessionOut 経由のイベントのみを返す。
 
-mod router;
+mod caller_memory;
+mod emotion;
+pub(crate) mod router;
 mod sentence_accumulator;
 mod wav_stream_chunker;
 
@@ -11,25 +13,31 @@ use std::collections::HashMap;
 use std::future::pending;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::io::AsyncWriteExt;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
 use tokio::time::{sleep, timeout};
 use tokio_stream::StreamExt;
 
 use crate::protocol::session::types::CallId;
 use crate::protocol::session::SessionOut;
-use crate::service::call_control::router::{
-    parse_intent_json, router_config, system_info_response, RouteAction, Router,
+use crate::service::call_control::caller_memory::{
+    memory_context_message, next_memory, parse_summary_response, summary_request_messages,
 };
+use crate::service::call_control::emotion::{emotion_label, EmotionTracker};
+use crate::service::call_control::router::{parse_intent_json, RouteAction, Router};
 use crate::service::call_control::sentence_accumulator::SentenceAccumulator;
 use crate::service::call_control::wav_stream_chunker::WavStreamChunker;
+use crate::service::routing::normalize_phone_number_e164;
+use crate::service::runtime_config;
 use crate::shared::config::{self, AppRuntimeConfig};
 use crate::shared::error::ai::TtsError;
 use crate::shared::ports::ai::{
     AiServices, AsrChunk, AsrStreamHandle, AsrStreamPort, ChatMessage, LlmStreamEvent,
-    LlmStreamPort, Role, SerInputPcm, TtsStream, TtsStreamPort, WeatherQuery,
+    LlmStreamPort, Role, SerInputPcm, SerResult, TtsStream, TtsStreamPort, WeatherQuery,
 };
+use crate::shared::ports::call_event_port::{CallEvent, CallEventPublisher};
+use crate::shared::ports::call_log_port::EmotionTurn;
+use crate::shared::ports::caller_memory_port::{CallerMemory, CallerMemoryPort};
 use crate::shared::ports::notification::{
     NotificationFuture, NotificationService as NotificationPort,
 };
@@ -41,6 +49,18 @@ pub use crate::shared::ports::app::{
     AudioChunkTx, EndReason,
 };
 pub use crate::shared::ports::notification::NotificationService as AppNotificationPort;
+pub use caller_memory::spawn_caller_memory_purge;
+
+/// 起動時に TTS キャッシュへ事前合成する定型文の一覧。
+pub fn fixed_phrases() -> Vec<String> {
+    let mut phrases = router::fixed_phrases();
+    for phrase in emotion::fixed_phrases(config::emotion_policy_config()) {
+        if !phrases.contains(&phrase) {
+            phrases.push(phrase);
+        }
+    }
+    phrases
+}
 
 const SORRY_WAV_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data/zundamon_sorry.wav");
 const SPEC_FILTER_KEYWORDS: [&str; 21] = [
@@ -69,6 +89,8 @@ const SPEC_FILTER_KEYWORDS: [&str; 21] = [
 
 const APP_EVENT_CHANNEL_CAPACITY: usize = 16;
 const APP_HISTORY_MAX_MESSAGES: usize = 20;
+/// お詫びの再生終了（PlaybackFinished）が届かないときに転送へ進むまでの待ち時間
+const ESCALATION_TRANSFER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(20);
 
 /// Starts and spawns an AppWorker task for the given call.
 ///
@@ -82,6 +104,7 @@ const APP_HISTORY_MAX_MESSAGES: usize = 20;
 /// - `ai_port`: AI service port used for ASR, NLU, TTS, weather, and related operations.
 /// - `phone_lookup`: optional phone lookup service used to resolve caller information.
 /// - `notification_port`: notification service used to emit ringing/missed/ended notifications.
+/// - `caller_memory_port`: per-caller memory store loaded at call start and updated at call end.
 ///
 /// # Returns
 ///
@@ -98,6 +121,10 @@ const APP_HISTORY_MAX_MESSAGES: usize = 20;
 /// use virtual_voicebot_backend::entities::CallId;
 /// use virtual_voicebot_backend::notification::NoopNotification;
 /// use virtual_voicebot_backend::ports::ai::AiServices;
+/// use virtual_voicebot_backend::ports::call_event_port::NoopCallEventPublisher;
+/// use virtual_voicebot_backend::ports::caller_memory_port::{
+///     CallerMemoryPort, NoopCallerMemoryPort,
+/// };
 /// use virtual_voicebot_backend::ports::notification::NotificationService;
 /// use virtual_voicebot_backend::ports::phone_lookup::{NoopPhoneLookup, PhoneLookupPort};
 /// use virtual_voicebot_backend::session::SessionOut;
@@ -106,6 +133,7 @@ const APP_HISTORY_MAX_MESSAGES: usize = 20;
 /// let ai_port: Arc<dyn AiServices> = Arc::new(DefaultAiPort::new());
 /// let phone_lookup: Arc<dyn PhoneLookupPort> = Arc::new(NoopPhoneLookup::new());
 /// let notification_port: Arc<dyn NotificationService> = Arc::new(NoopNotification::new());
+/// let caller_memory_port: Arc<dyn CallerMemoryPort> = Arc::new(NoopCallerMemoryPort::new());
 /// let tx = spawn_app_worker(
 ///     CallId::new("call-123").unwrap(),
 ///     session_tx,
@@ -116,6 +144,8 @@ const APP_HISTORY_MAX_MESSAGES: usize = 20;
 ///     None,
 ///     phone_lookup,
 ///     notification_port,
+///     caller_memory_port,
+///     Arc::new(NoopCallEventPublisher::new()),
 ///     AppRuntimeConfig::from_env(),
 /// );
 /// let _ = tx.try_send(AppEvent::CallStarted {
@@ -134,6 +164,8 @@ pub fn spawn_app_worker(
     audio_chunk_rx: Option<AudioChunkRx>,
     phone_lookup: Arc<dyn PhoneLookupPort>,
     notification_port: Arc<dyn NotificationPort>,
+    caller_memory_port: Arc<dyn CallerMemoryPort>,
+    call_events: Arc<dyn CallEventPublisher>,
     app_cfg: AppRuntimeConfig,
 ) -> AppEventTx {
     let (tx, rx) = app_event_channel(APP_EVENT_CHANNEL_CAPACITY);
@@ -148,6 +180,8 @@ pub fn spawn_app_worker(
         audio_chunk_rx,
         phone_lookup,
         notification_port,
+        caller_memory_port,
+        call_events,
         app_cfg,
     );
     tokio::spawn(async move { worker.run().await });
@@ -169,11 +203,35 @@ struct AppWorker {
     router: Router,
     notification_port: Arc<dyn NotificationPort>,
     notification_state: NotificationState,
+    caller_memory_port: Arc<dyn CallerMemoryPort>,
+    call_events: Arc<dyn CallEventPublisher>,
+    caller_number_e164: Option<String>,
+    caller_memory: Option<CallerMemory>,
     app_cfg: AppRuntimeConfig,
     next_stream_generation_id: u64,
     asr_stream_handle: Option<AsrStreamHandle>,
     asr_stream_connect_failed_for_turn: bool,
     pending_stream_eos: HashMap<String, usize>,
+    emotion_tracker: EmotionTracker,
+    next_emotion_turn: i32,
+    /// 処理中の発話ターンの計測（イベントストリームの応答時間に使う）
+    turn_timing: Option<TurnTiming>,
+    /// お詫びの再生が終わってから依頼する転送（感情エスカレーション）
+    pending_transfer: Option<PendingTransfer>,
+    escalation_transfer_timeout: std::time::Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TurnTiming {
+    started_at: Instant,
+    asr_done_at: Instant,
+}
+
+/// 再生終了を待っている転送。`deadline` を過ぎたら再生終了を待たずに依頼する
+#[derive(Debug)]
+struct PendingTransfer {
+    person: String,
+    deadline: tokio::time::Instant,
 }
 
 #[derive(Debug, Default)]
@@ -184,7 +242,7 @@ struct NotificationState {
 }
 
 #[derive(Debug)]
-struct TtsStreamEarlyStartError {
+struct TtsStreamPlaybackError {
     error: TtsError,
     emitted_segments: usize,
 }
@@ -203,6 +261,8 @@ impl AppWorker {
     /// - `ai_port`: AI service port (transcription, NLU, TTS, etc.).
     /// - `phone_lookup`: phone lookup service port.
     /// - `notification_port`: notification service used to emit ringing/missed/ended notifications.
+    /// - `caller_memory_port`: per-caller memory store.
+    /// - `call_events`: publisher for per-turn utterance/reply events on the live event stream.
     ///
     /// # Returns
     ///
@@ -236,6 +296,8 @@ impl AppWorker {
     ///     ai_impl,
     ///     phone_impl,
     ///     notif_impl,
+    ///     Arc::new(crate::shared::ports::caller_memory_port::NoopCallerMemoryPort::new()),
+    ///     Arc::new(crate::shared::ports::call_event_port::NoopCallEventPublisher::new()),
     ///     crate::shared::config::AppRuntimeConfig::from_env(),
     /// );
     /// ```
@@ -251,8 +313,13 @@ impl AppWorker {
         audio_chunk_rx: Option<AudioChunkRx>,
         phone_lookup: Arc<dyn PhoneLookupPort>,
         notification_port: Arc<dyn NotificationPort>,
+        caller_memory_port: Arc<dyn CallerMemoryPort>,
+        call_events: Arc<dyn CallEventPublisher>,
         app_cfg: AppRuntimeConfig,
     ) -> Self {
+        // 通話中に設定が再読込されても、この通話は開始時のスナップショットを使う
+        runtime_config::pin_call(call_id.as_str());
+        let router = Router::for_call(call_id.as_str());
         Self {
             call_id,
             session_out_tx,
@@ -265,14 +332,23 @@ impl AppWorker {
             tts_stream_port,
             audio_chunk_rx,
             phone_lookup,
-            router: Router::new(),
+            router,
             notification_port,
             notification_state: NotificationState::default(),
+            caller_memory_port,
+            call_events,
+            caller_number_e164: None,
+            caller_memory: None,
             app_cfg,
             next_stream_generation_id: 1,
             asr_stream_handle: None,
             asr_stream_connect_failed_for_turn: false,
             pending_stream_eos: HashMap::new(),
+            emotion_tracker: EmotionTracker::default(),
+            next_emotion_turn: 0,
+            turn_timing: None,
+            pending_transfer: None,
+            escalation_transfer_timeout: ESCALATION_TRANSFER_TIMEOUT,
         }
     }
 
@@ -294,8 +370,32 @@ impl AppWorker {
     /// // tokio::spawn(async move { worker.run().await });
     /// ```
     async fn run(mut self) {
+        self.run_loop().await;
+        runtime_config::release_call(self.call_id.as_str());
+    }
+
+    async fn run_loop(&mut self) {
         loop {
+            let transfer_deadline = self
+                .pending_transfer
+                .as_ref()
+                .map(|pending| pending.deadline);
             tokio::select! {
+                _ = async move {
+                    match transfer_deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline).await,
+                        None => pending::<()>().await,
+                    }
+                } => {
+                    if let Some(pending) = self.pending_transfer.take() {
+                        log::warn!(
+                            "[app {}] apology playback not finished within {:?} -> transfer",
+                            self.call_id,
+                            self.escalation_transfer_timeout
+                        );
+                        self.request_transfer(pending.person).await;
+                    }
+                }
                 ev = self.rx.recv() => {
                     let Some(ev) = ev else {
                         break;
@@ -393,7 +493,8 @@ impl AppWorker {
                 } else {
                     log::debug!("[app {}] caller missing", self.call_id);
                 }
-                self.handle_phone_lookup(caller).await;
+                self.handle_phone_lookup(caller.clone()).await;
+                self.load_caller_memory(caller).await;
                 true
             }
             AppEvent::AudioBuffered {
@@ -417,6 +518,13 @@ impl AppWorker {
                     );
                     return true;
                 }
+                if self.pending_transfer.is_some() {
+                    log::debug!(
+                        "[app {}] dropped audio while waiting to transfer",
+                        self.call_id
+                    );
+                    return true;
+                }
                 self.await_stream_eos_for_buffered_turn(&call_id, stream_id.as_str())
                     .await;
                 if let Err(e) = self
@@ -427,6 +535,48 @@ impl AppWorker {
                 }
                 true
             }
+            AppEvent::IvrSpeechInput {
+                call_id,
+                pcm_mulaw,
+                classify_intent,
+            } => {
+                if call_id != self.call_id {
+                    log::warn!(
+                        "[app {}] IvrSpeechInput received for mismatched call_id={}",
+                        self.call_id,
+                        call_id
+                    );
+                    return true;
+                }
+                if !self.active {
+                    log::debug!(
+                        "[app {}] dropped IVR speech because call not active",
+                        self.call_id
+                    );
+                    return true;
+                }
+                self.handle_ivr_speech_input(&call_id, pcm_mulaw, classify_intent)
+                    .await;
+                true
+            }
+            AppEvent::PlaybackFinished { call_id } => {
+                if call_id != self.call_id {
+                    log::warn!(
+                        "[app {}] PlaybackFinished received for mismatched call_id={}",
+                        self.call_id,
+                        call_id
+                    );
+                    return true;
+                }
+                if let Some(pending) = self.pending_transfer.take() {
+                    log::info!(
+                        "[app {}] apology playback finished -> transfer",
+                        self.call_id
+                    );
+                    self.request_transfer(pending.person).await;
+                }
+                true
+            }
             AppEvent::CallEnded {
                 call_id,
                 from,
@@ -455,6 +605,7 @@ impl AppWorker {
                 );
                 self.close_asr_stream_handle_best_effort();
                 self.notify_ended(call_id.as_str(), from, reason, duration_sec, timestamp);
+                self.save_caller_memory();
                 false
             }
         }
@@ -643,12 +794,37 @@ impl AppWorker {
         pcm_mulaw: Vec<u8>,
         pcm_linear16: Vec<i16>,
     ) -> anyhow::Result<()> {
-        self.analyze_ser(call_id, pcm_linear16).await;
+        let started_at = Instant::now();
+        let ser = self.analyze_ser(call_id, pcm_linear16).await;
         let user_text = self
             .take_streaming_asr_result_or_fallback(call_id, pcm_mulaw)
             .await;
 
         let trimmed = user_text.trim();
+        let asr_done_at = Instant::now();
+        self.turn_timing = Some(TurnTiming {
+            started_at,
+            asr_done_at,
+        });
+        if !trimmed.is_empty() {
+            self.call_events.publish(
+                call_id.as_str(),
+                CallEvent::UserUtterance {
+                    text: trimmed.to_string(),
+                    asr_ms: elapsed_ms(started_at, asr_done_at),
+                },
+            );
+        }
+        if let Some(result) = ser {
+            self.record_emotion_turn(&result, trimmed).await;
+            if self
+                .emotion_tracker
+                .observe(&result, config::emotion_policy_config())
+                && self.escalate_on_emotion(call_id, &result, trimmed).await
+            {
+                return Ok(());
+            }
+        }
         if trimmed.is_empty() {
             log::debug!("[app {call_id}] empty ASR text after filtering, playing sorry audio");
             let _ = self
@@ -666,6 +842,57 @@ impl AppWorker {
         self.handle_user_text(call_id, trimmed).await
     }
 
+    /// IVR の音声入力ノード向けに認識だけを行い、結果を session へ返す（対話はしない）。
+    async fn handle_ivr_speech_input(
+        &self,
+        call_id: &CallId,
+        pcm_mulaw: Vec<u8>,
+        classify_intent: bool,
+    ) {
+        let asr_chunks = vec![AsrChunk {
+            pcm_mulaw,
+            end: true,
+        }];
+        let text = match self
+            .ai_port
+            .transcribe_chunks(call_id.to_string(), asr_chunks)
+            .await
+        {
+            Ok(text) => text.trim().to_string(),
+            Err(err) => {
+                log::warn!("[app {call_id}] IVR speech ASR failed: {err:?}");
+                String::new()
+            }
+        };
+        let intent = if classify_intent && !text.is_empty() {
+            match self
+                .ai_port
+                .classify_intent(call_id.to_string(), text.clone())
+                .await
+            {
+                Ok(raw) => Some(parse_intent_json(&raw, &text).raw_intent),
+                Err(err) => {
+                    log::warn!("[app {call_id}] IVR speech intent classify failed: {err:?}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        log::debug!(
+            "[app {call_id}] IVR speech recognized text_len={} intent={:?}",
+            text.chars().count(),
+            intent
+        );
+        let _ = self
+            .session_out_tx
+            .send((
+                self.call_id.clone(),
+                SessionOut::AppIvrSpeechResult { text, intent },
+            ))
+            .await;
+    }
+
     async fn transcribe_asr(&self, call_id: &CallId, pcm_mulaw: Vec<u8>) -> String {
         let asr_chunks = vec![AsrChunk {
             pcm_mulaw,
@@ -685,7 +912,7 @@ impl AppWorker {
         }
     }
 
-    async fn analyze_ser(&self, call_id: &CallId, pcm_linear16: Vec<i16>) {
+    async fn analyze_ser(&self, call_id: &CallId, pcm_linear16: Vec<i16>) -> Option<SerResult> {
         let ser_input = SerInputPcm {
             session_id: call_id.to_string(),
             stream_id: "main".to_string(),
@@ -700,13 +927,109 @@ impl AppWorker {
                     result.emotion,
                     result.confidence
                 );
+                Some(result)
             }
             Err(err) => {
                 log::warn!("[app {call_id}] SER failed: {err}");
+                None
             }
         }
     }
 
+    /// ターンの感情と認識テキストを session へ送り、通話ログの感情タイムラインに残す。
+    async fn record_emotion_turn(&mut self, result: &SerResult, transcript: &str) {
+        let turn = self.next_emotion_turn;
+        self.next_emotion_turn = self.next_emotion_turn.saturating_add(1);
+        let _ = self
+            .session_out_tx
+            .send((
+                self.call_id.clone(),
+                SessionOut::AppEmotionTurn {
+                    turn: EmotionTurn {
+                        turn,
+                        occurred_at: chrono::Utc::now(),
+                        emotion: emotion_label(result.emotion).to_string(),
+                        confidence: result.confidence,
+                        transcript: transcript.to_string(),
+                    },
+                },
+            ))
+            .await;
+    }
+
+    /// 感情エスカレーション: お詫びを再生して有人転送を依頼する。
+    /// 転送先が解決できない場合は通常の応答処理を続けるため `false` を返す。
+    async fn escalate_on_emotion(
+        &mut self,
+        call_id: &CallId,
+        result: &SerResult,
+        trimmed: &str,
+    ) -> bool {
+        let cfg = config::emotion_policy_config();
+        let target = cfg
+            .transfer_person
+            .as_deref()
+            .and_then(|person| self.router.resolve_transfer_target(person));
+        let Some(resolved) = target else {
+            log::warn!(
+                "[app {call_id}] emotion escalation triggered but transfer target is not configured/resolvable (person={:?})",
+                cfg.transfer_person
+            );
+            return false;
+        };
+        log::info!(
+            "[app {call_id}] emotion escalation: emotion={:?} confidence={:.2} -> transfer",
+            result.emotion,
+            result.confidence
+        );
+        if !trimmed.is_empty() {
+            self.push_history(trimmed.to_string(), cfg.apology_text.clone());
+        }
+        self.apologize_then_transfer(call_id, cfg.apology_text.clone(), resolved)
+            .await;
+        true
+    }
+
+    /// お詫びを再生し、再生が終わってから（PlaybackFinished で）転送を依頼する。
+    /// お詫びを合成できなければすぐに転送する。
+    async fn apologize_then_transfer(&mut self, call_id: &CallId, apology: String, target: String) {
+        match self
+            .ai_port
+            .synth_phrase_to_wav(call_id.to_string(), apology)
+            .await
+        {
+            Ok(bot_wav) => {
+                self.pending_transfer = Some(PendingTransfer {
+                    person: target,
+                    deadline: tokio::time::Instant::now() + self.escalation_transfer_timeout,
+                });
+                let _ = self
+                    .session_out_tx
+                    .send((
+                        self.call_id.clone(),
+                        SessionOut::AppSendBotAudioFile {
+                            path: bot_wav.to_string_lossy().to_string(),
+                        },
+                    ))
+                    .await;
+            }
+            Err(e) => {
+                log::warn!("[app {call_id}] escalation apology TTS failed: {e:?}");
+                self.request_transfer(target).await;
+            }
+        }
+    }
+
+    async fn request_transfer(&mut self, person: String) {
+        let _ = self
+            .session_out_tx
+            .send((
+                self.call_id.clone(),
+                SessionOut::AppRequestTransfer { person },
+            ))
+            .await;
+    }
+
     async fn handle_user_text(&mut self, call_id: &CallId, trimmed: &str) -> anyhow::Result<()> {
         if is_spec_question(trimmed) {
             log::warn!(
@@ -714,10 +1037,10 @@ impl AppWorker {
                 call_id,
                 mask_pii(trimmed)
             );
-            let answer_text = system_info_response();
+            let answer_text = self.router.system_info_response();
             match self
                 .ai_port
-                .synth_to_wav(call_id.to_string(), answer_text, None)
+                .synth_phrase_to_wav(call_id.to_string(), answer_text)
                 .await
             {
                 Ok(bot_wav) => {
@@ -757,11 +1080,24 @@ impl AppWorker {
             intent_json_len
         );
 
-        let (answer_text, user_query) = match self.router.route(intent_result) {
-            RouteAction::FixedResponse(text) => (text, trimmed.to_string()),
-            RouteAction::SystemInfo => (system_info_response(), trimmed.to_string()),
+        let (answer_text, user_query, fixed_phrase) = match self.router.route(intent_result) {
+            RouteAction::FixedResponse(text) => (text, trimmed.to_string(), true),
+            RouteAction::SystemInfo => (
+                self.router.system_info_response(),
+                trimmed.to_string(),
+                true,
+            ),
             RouteAction::GeneralChat { query } => {
-                let mut messages = Vec::with_capacity(self.history.len() + 1);
+                let mut messages = Vec::with_capacity(self.history.len() + 3);
+                if let Some(memory) = &self.caller_memory {
+                    messages.push(memory_context_message(memory));
+                }
+                if let Some(tone) = self
+                    .emotion_tracker
+                    .tone_message(config::emotion_policy_config())
+                {
+                    messages.push(tone);
+                }
                 messages.extend(self.history.iter().cloned());
                 messages.push(ChatMessage {
                     role: Role::User,
@@ -782,23 +1118,33 @@ impl AppWorker {
                 date,
             } => {
                 let req = WeatherQuery { location, date };
-                let answer_text = match self.ai_port.handle_weather(call_id.to_string(), req).await
-                {
-                    Ok(text) => text,
+                match self.ai_port.handle_weather(call_id.to_string(), req).await {
+                    Ok(text) => (text, query, false),
                     Err(err) => {
                         log::warn!("[app {call_id}] weather failed: {err:?}");
-                        router_config().weather_error_response.clone()
+                        (self.router.weather_error_response(), query, true)
                     }
-                };
-                (answer_text, query)
+                }
+            }
+            RouteAction::RecordingPause => {
+                // 案内より先に止める（案内の途中から話し始めても録音に残さない）
+                let _ = self
+                    .session_out_tx
+                    .send((self.call_id.clone(), SessionOut::AppRequestRecordingPause))
+                    .await;
+                (
+                    self.router.recording_pause_response(),
+                    trimmed.to_string(),
+                    true,
+                )
             }
             RouteAction::Transfer { person } => {
-                let target = self.router.resolve_transfer_person(person.as_str());
+                let target = self.router.resolve_transfer_target(person.as_str());
                 if let Some(resolved) = target {
                     let confirm_message = self.router.transfer_confirm_message();
                     match self
                         .ai_port
-                        .synth_to_wav(call_id.to_string(), confirm_message.clone(), None)
+                        .synth_phrase_to_wav(call_id.to_string(), confirm_message)
                         .await
                     {
                         Ok(bot_wav) => {
@@ -827,7 +1173,7 @@ impl AppWorker {
                     let not_found = self.router.transfer_not_found_message();
                     match self
                         .ai_port
-                        .synth_to_wav(call_id.to_string(), not_found.clone(), None)
+                        .synth_phrase_to_wav(call_id.to_string(), not_found)
                         .await
                     {
                         Ok(bot_wav) => {
@@ -852,12 +1198,15 @@ impl AppWorker {
 
         self.push_history(user_query, answer_text.clone());
 
-        // TTS
-        match self
-            .ai_port
-            .synth_to_wav(call_id.to_string(), answer_text, None)
-            .await
-        {
+        // TTS（固定応答はフレーズキャッシュを利用）
+        let synth = if fixed_phrase {
+            self.ai_port
+                .synth_phrase_to_wav(call_id.to_string(), answer_text)
+        } else {
+            self.ai_port
+                .synth_to_wav(call_id.to_string(), answer_text, None)
+        };
+        match synth.await {
             Ok(bot_wav) => {
                 let _ = self
                     .session_out_tx
@@ -877,6 +1226,25 @@ impl AppWorker {
     }
 
     fn push_history(&mut self, user_query: String, answer_text: String) {
+        let now = Instant::now();
+        let (llm_ms, turn_ms) = self
+            .turn_timing
+            .take()
+            .map(|timing| {
+                (
+                    elapsed_ms(timing.asr_done_at, now),
+                    elapsed_ms(timing.started_at, now),
+                )
+            })
+            .unwrap_or_default();
+        self.call_events.publish(
+            self.call_id.as_str(),
+            CallEvent::BotReply {
+                text: answer_text.clone(),
+                llm_ms,
+                turn_ms,
+            },
+        );
         self.history.push(ChatMessage {
             role: Role::User,
             content: user_query,
@@ -1152,57 +1520,48 @@ impl AppWorker {
             }
         };
 
-        if config::tts_streaming_early_start_enabled() {
-            match self
-                .stream_tts_sentence_early_start(call_id, stream, generation_id)
-                .await
-            {
-                Ok(()) => return true,
-                Err(err) => {
-                    if err.emitted_segments > 0 {
-                        log::warn!(
-                            "[tts stream {call_id}] early-start failed after {} segment(s): {}; keep partial playback",
-                            err.emitted_segments,
-                            err.error
-                        );
-                        return true;
-                    }
-                    log::warn!(
-                        "[tts stream {call_id}] early-start failed before first segment: {}; fallback",
-                        err.error
-                    );
-                    return false;
-                }
-            }
-        }
-
+        // early-start 無効時は全体を受信し終えてから 1 セグメントとして再生する
+        let segment_samples = if config::tts_streaming_early_start_enabled() {
+            config::tts_streaming_early_start_samples()
+        } else {
+            usize::MAX
+        };
         match self
-            .collect_tts_stream_to_tmp(call_id, stream, generation_id)
+            .stream_tts_sentence_frames(call_id, stream, generation_id, segment_samples)
             .await
         {
-            Ok(path) => {
-                self.enqueue_streaming_bot_audio_file(path, generation_id)
-                    .await;
+            Ok(()) => true,
+            Err(err) if err.emitted_segments > 0 => {
+                log::warn!(
+                    "[tts stream {call_id}] streaming failed after {} segment(s): {}; keep partial playback",
+                    err.emitted_segments,
+                    err.error
+                );
                 true
             }
-            Err(e) => {
-                log::warn!("[tts stream {call_id}] collect/write failed: {e}; fallback");
+            Err(err) => {
+                log::warn!(
+                    "[tts stream {call_id}] streaming failed before first segment: {}; fallback",
+                    err.error
+                );
                 false
             }
         }
     }
 
-    async fn stream_tts_sentence_early_start(
+    /// TTS ストリームをメモリ上で PCMU フレームへ変換し、`segment_samples` ごとに session へ積む。
+    async fn stream_tts_sentence_frames(
         &mut self,
         call_id: &CallId,
         stream: TtsStream,
         generation_id: u64,
-    ) -> Result<(), TtsStreamEarlyStartError> {
+        segment_samples: usize,
+    ) -> Result<(), TtsStreamPlaybackError> {
         let first_chunk_timeout = config::tts_streaming_first_chunk_timeout();
         let total_timeout = config::tts_streaming_total_timeout();
         let mut emitted_segments = 0usize;
-        let mut next_segment_index = 0usize;
-        let mut chunker = WavStreamChunker::new(config::tts_streaming_early_start_bytes());
+        let mut chunker = WavStreamChunker::new(segment_samples);
+        let mut debug_wav = config::tts_streaming_debug_wav_dir().map(|_| Vec::new());
 
         let streamed = timeout(total_timeout, async {
             tokio::pin!(stream);
@@ -1216,60 +1575,32 @@ impl AppWorker {
                     )));
                 }
             };
-
-            match first {
-                Some(Ok(bytes)) => {
-                    let wav_segments = chunker.push(&bytes).map_err(|e| {
-                        TtsError::SynthesisFailed(format!("wav stream parse failed: {e}"))
-                    })?;
-                    for wav in wav_segments {
-                        self.write_and_enqueue_tts_stream_segment(
-                            call_id,
-                            generation_id,
-                            next_segment_index,
-                            wav,
-                        )
-                        .await?;
-                        emitted_segments += 1;
-                        next_segment_index += 1;
-                    }
-                }
-                Some(Err(e)) => return Err(e),
+            let mut next = match first {
+                Some(item) => Some(item),
                 None => return Err(TtsError::SynthesisFailed("empty TTS stream".to_string())),
-            }
+            };
 
-            while let Some(chunk) = stream.next().await {
-                match chunk {
-                    Ok(bytes) => {
-                        let wav_segments = chunker.push(&bytes).map_err(|e| {
-                            TtsError::SynthesisFailed(format!("wav stream parse failed: {e}"))
-                        })?;
-                        for wav in wav_segments {
-                            self.write_and_enqueue_tts_stream_segment(
-                                call_id,
-                                generation_id,
-                                next_segment_index,
-                                wav,
-                            )
-                            .await?;
-                            emitted_segments += 1;
-                            next_segment_index += 1;
-                        }
-                    }
-                    Err(e) => return Err(e),
+            while let Some(chunk) = next {
+                let bytes = chunk?;
+                if let Some(buf) = debug_wav.as_mut() {
+                    buf.extend_from_slice(&bytes);
+                }
+                let segments = chunker.push(&bytes).map_err(|e| {
+                    TtsError::SynthesisFailed(format!("wav stream parse failed: {e}"))
+                })?;
+                for frames in segments {
+                    self.enqueue_streaming_bot_audio_frames(frames, generation_id)
+                        .await;
+                    emitted_segments += 1;
                 }
+                next = stream.next().await;
             }
 
-            if let Some(wav) = chunker.finish().map_err(|e| {
+            if let Some(frames) = chunker.finish().map_err(|e| {
                 TtsError::SynthesisFailed(format!("wav stream finalize failed: {e}"))
             })? {
-                self.write_and_enqueue_tts_stream_segment(
-                    call_id,
-                    generation_id,
-                    next_segment_index,
-                    wav,
-                )
-                .await?;
+                self.enqueue_streaming_bot_audio_frames(frames, generation_id)
+                    .await;
                 emitted_segments += 1;
             }
 
@@ -1283,108 +1614,28 @@ impl AppWorker {
             TtsError::SynthesisFailed(format!("total timeout ({} ms)", total_timeout.as_millis()))
         });
 
+        if let Some(bytes) = debug_wav {
+            self.write_tts_stream_debug_wav(call_id, generation_id, &bytes)
+                .await;
+        }
+
         match streamed {
             Ok(Ok(())) => Ok(()),
-            Ok(Err(error)) => Err(TtsStreamEarlyStartError {
-                error,
-                emitted_segments,
-            }),
-            Err(error) => Err(TtsStreamEarlyStartError {
+            Ok(Err(error)) | Err(error) => Err(TtsStreamPlaybackError {
                 error,
                 emitted_segments,
             }),
         }
     }
 
-    async fn collect_tts_stream_to_tmp(
-        &self,
-        call_id: &CallId,
-        stream: TtsStream,
-        generation_id: u64,
-    ) -> Result<PathBuf, TtsError> {
-        let first_chunk_timeout = config::tts_streaming_first_chunk_timeout();
-        let total_timeout = config::tts_streaming_total_timeout();
-        let tmp_path = self.tts_stream_tmp_path(call_id, generation_id);
-
-        let write_result = timeout(total_timeout, async {
-            tokio::pin!(stream);
-            let mut wrote_any = false;
-
-            let first = tokio::select! {
-                item = stream.next() => item,
-                _ = sleep(first_chunk_timeout) => {
-                    return Err(TtsError::SynthesisFailed(format!(
-                        "first chunk timeout ({} ms)",
-                        first_chunk_timeout.as_millis()
-                    )));
-                }
-            };
-
-            let mut file = tokio::fs::File::create(&tmp_path)
-                .await
-                .map_err(|e| TtsError::SynthesisFailed(format!("tmp wav write failed: {e}")))?;
-
-            match first {
-                Some(Ok(bytes)) => {
-                    if !bytes.is_empty() {
-                        wrote_any = true;
-                    }
-                    file.write_all(&bytes).await.map_err(|e| {
-                        TtsError::SynthesisFailed(format!("tmp wav write failed: {e}"))
-                    })?;
-                }
-                Some(Err(e)) => return Err(e),
-                None => return Err(TtsError::SynthesisFailed("empty TTS stream".to_string())),
-            }
-
-            while let Some(chunk) = stream.next().await {
-                match chunk {
-                    Ok(bytes) => {
-                        if !bytes.is_empty() {
-                            wrote_any = true;
-                        }
-                        file.write_all(&bytes).await.map_err(|e| {
-                            TtsError::SynthesisFailed(format!("tmp wav write failed: {e}"))
-                        })?;
-                    }
-                    Err(e) => return Err(e),
-                }
-            }
-
-            file.flush()
-                .await
-                .map_err(|e| TtsError::SynthesisFailed(format!("tmp wav write failed: {e}")))?;
-
-            if !wrote_any {
-                return Err(TtsError::SynthesisFailed("empty TTS stream".to_string()));
-            }
-
-            Ok(())
-        })
-        .await
-        .map_err(|_| {
-            TtsError::SynthesisFailed(format!("total timeout ({} ms)", total_timeout.as_millis()))
-        });
-        match write_result {
-            Ok(Ok(())) => {}
-            Ok(Err(err)) | Err(err) => {
-                tokio::fs::remove_file(&tmp_path).await.ok();
-                return Err(err);
-            }
+    /// デバッグ用に受信したストリームをそのまま WAV として保存する（再生には使わない）。
+    async fn write_tts_stream_debug_wav(&self, call_id: &CallId, generation_id: u64, bytes: &[u8]) {
+        let Some(dir) = config::tts_streaming_debug_wav_dir() else {
+            return;
+        };
+        if bytes.is_empty() {
+            return;
         }
-        Ok(tmp_path)
-    }
-
-    fn tts_stream_tmp_path(&self, call_id: &CallId, generation_id: u64) -> PathBuf {
-        self.tts_stream_segment_tmp_path(call_id, generation_id, 0)
-    }
-
-    fn tts_stream_segment_tmp_path(
-        &self,
-        call_id: &CallId,
-        generation_id: u64,
-        segment_index: usize,
-    ) -> PathBuf {
         let ts = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
@@ -1397,26 +1648,34 @@ impl AppWorker {
                 _ => '_',
             })
             .collect();
-        PathBuf::from(format!(
-            "/tmp/tts_stream_output_{}_{}_{}_{}.wav",
-            safe_call_id, generation_id, segment_index, ts
-        ))
+        let path = PathBuf::from(dir).join(format!(
+            "tts_stream_{}_{}_{}.wav",
+            safe_call_id, generation_id, ts
+        ));
+        let result = async {
+            tokio::fs::create_dir_all(dir).await?;
+            tokio::fs::write(&path, bytes).await
+        }
+        .await;
+        if let Err(e) = result {
+            log::warn!(
+                "[tts stream {call_id}] debug wav write failed path={}: {e}",
+                path.display()
+            );
+        }
     }
 
-    async fn write_and_enqueue_tts_stream_segment(
-        &self,
-        call_id: &CallId,
-        generation_id: u64,
-        segment_index: usize,
-        wav_bytes: Vec<u8>,
-    ) -> Result<(), TtsError> {
-        let tmp_path = self.tts_stream_segment_tmp_path(call_id, generation_id, segment_index);
-        tokio::fs::write(&tmp_path, &wav_bytes)
-            .await
-            .map_err(|e| TtsError::SynthesisFailed(format!("tmp wav write failed: {e}")))?;
-        self.enqueue_streaming_bot_audio_file(tmp_path, generation_id)
+    async fn enqueue_streaming_bot_audio_frames(&self, frames: Vec<Vec<u8>>, generation_id: u64) {
+        let _ = self
+            .session_out_tx
+            .send((
+                self.call_id.clone(),
+                SessionOut::AppEnqueueBotAudioFrames {
+                    frames,
+                    generation_id,
+                },
+            ))
             .await;
-        Ok(())
     }
 
     async fn enqueue_streaming_bot_audio_file(&self, wav_path: PathBuf, generation_id: u64) {
@@ -1602,9 +1861,105 @@ impl AppWorker {
         }
     }
 
+    /// Loads the stored memory for the caller when caller memory is enabled.
+    ///
+    /// The caller number is normalized to E.164 so that `090-...` and `+8190...` share one
+    /// memory. Lookup failures are logged and the call continues without memory.
+    async fn load_caller_memory(&mut self, caller: Option<String>) {
+        if !self.app_cfg.caller_memory_enabled {
+            return;
+        }
+        let Some(caller) = caller.filter(|v| !v.trim().is_empty()) else {
+            return;
+        };
+        let phone_number = match normalize_phone_number_e164(&caller) {
+            Ok(value) => value,
+            Err(_) => {
+                log::debug!(
+                    "[app {}] caller memory skipped: non-E.164 caller={}",
+                    self.call_id,
+                    mask_phone(&caller)
+                );
+                return;
+            }
+        };
+        let not_before = config::caller_memory_config().retention_cutoff(chrono::Utc::now());
+        match self
+            .caller_memory_port
+            .load(&phone_number, not_before)
+            .await
+        {
+            Ok(memory) => {
+                log::debug!(
+                    "[app {}] caller memory loaded caller={} found={}",
+                    self.call_id,
+                    mask_phone(&phone_number),
+                    memory.is_some()
+                );
+                self.caller_memory = memory;
+                self.caller_number_e164 = Some(phone_number);
+            }
+            Err(err) => {
+                // 前回までの要約が分からないまま保存すると上書きで消えるので、この通話では更新しない
+                log::warn!(
+                    "[app {}] caller memory load failed caller={}: {}, skip update for this call",
+                    self.call_id,
+                    mask_phone(&phone_number),
+                    err
+                );
+            }
+        }
+    }
+
+    /// Summarizes this call into the caller's memory in the background.
+    ///
+    /// Runs after `CallEnded` so the LLM round-trip never delays the call itself; calls without
+    /// any conversation turn leave the stored memory untouched.
+    fn save_caller_memory(&mut self) {
+        if !self.app_cfg.caller_memory_enabled || self.history.is_empty() {
+            return;
+        }
+        let Some(phone_number) = self.caller_number_e164.take() else {
+            return;
+        };
+        let cfg = config::caller_memory_config().clone();
+        let previous = self.caller_memory.take();
+        let messages = summary_request_messages(previous.as_ref(), &self.history, &cfg);
+        let ai_port = self.ai_port.clone();
+        let caller_memory_port = self.caller_memory_port.clone();
+        let call_id = self.call_id.clone();
+        tokio::spawn(async move {
+            let raw = match ai_port.generate_answer(call_id.to_string(), messages).await {
+                Ok(raw) => raw,
+                Err(err) => {
+                    log::warn!("[app {call_id}] caller memory summary failed: {err:?}");
+                    return;
+                }
+            };
+            let Some((summary, key_facts)) = parse_summary_response(&raw, &cfg) else {
+                log::warn!("[app {call_id}] caller memory summary was empty");
+                return;
+            };
+            let memory = next_memory(
+                phone_number,
+                previous.as_ref(),
+                summary,
+                key_facts,
+                chrono::Utc::now(),
+            );
+            if let Err(err) = caller_memory_port.save(memory).await {
+                log::warn!("[app {call_id}] caller memory save failed: {err}");
+            }
+        });
+    }
+
     // build_prompt はロール分離に伴い廃止
 }
 
+fn elapsed_ms(from: Instant, to: Instant) -> u64 {
+    to.saturating_duration_since(from).as_millis() as u64
+}
+
 /// Detects whether a text appears to be a specification or policy question.
 ///
 /// # Returns
@@ -1643,9 +1998,14 @@ mod tests {
         Intent, IntentPort, LlmPort, SerInputPcm, SerOutcome, SerPort, TtsPort, TtsStream,
         TtsStreamPort, WeatherPort, WeatherQuery, WeatherResponse,
     };
+    use crate::shared::ports::call_event_port::NoopCallEventPublisher;
+    use crate::shared::ports::caller_memory_port::{
+        CallerMemoryError, CallerMemoryFuture, NoopCallerMemoryPort,
+    };
     use crate::shared::ports::notification::{
-        CallEndedNotifier, MissedCallNotifier, NotificationFuture, NotificationService,
-        RingingNotifier,
+        CallEndedNotifier, CallbackRequest, CallbackRequestNotifier, MissedCallNotifier,
+        NotificationFuture, NotificationService, RingingNotifier, VoicemailNotice,
+        VoicemailNotifier,
     };
     use crate::shared::ports::phone_lookup::{NoopPhoneLookup, PhoneLookupFuture, PhoneLookupPort};
 
@@ -2002,6 +2362,91 @@ mod tests {
         }
     }
 
+    impl VoicemailNotifier for NotificationSpy {
+        fn notify_voicemail(&self, _notice: VoicemailNotice) -> NotificationFuture {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    impl CallbackRequestNotifier for NotificationSpy {
+        fn notify_callback_request(&self, _request: CallbackRequest) -> NotificationFuture {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[derive(Clone)]
+    struct CallerMemorySpy {
+        state: Arc<Mutex<CallerMemorySpyState>>,
+    }
+
+    #[derive(Debug, Default)]
+    struct CallerMemorySpyState {
+        loaded_numbers: Vec<String>,
+        saved: Vec<CallerMemory>,
+        fail_load: bool,
+    }
+
+    impl CallerMemorySpy {
+        fn new() -> (Self, Arc<Mutex<CallerMemorySpyState>>) {
+            let state = Arc::new(Mutex::new(CallerMemorySpyState::default()));
+            (
+                Self {
+                    state: Arc::clone(&state),
+                },
+                state,
+            )
+        }
+    }
+
+    impl CallerMemoryPort for CallerMemorySpy {
+        fn load(
+            &self,
+            phone_number: &str,
+            _not_before: chrono::DateTime<chrono::Utc>,
+        ) -> CallerMemoryFuture<Option<CallerMemory>> {
+            let mut state = self
+                .state
+                .lock()
+                .expect("caller memory spy state mutex poisoned");
+            state.loaded_numbers.push(phone_number.to_string());
+            if state.fail_load {
+                return Box::pin(async {
+                    Err(CallerMemoryError::ReadFailed(
+                        "connection reset".to_string(),
+                    ))
+                });
+            }
+            let memory = CallerMemory {
+                phone_number: phone_number.to_string(),
+                summary: "前回は予約の相談".to_string(),
+                key_facts: Vec::new(),
+                call_count: 1,
+                last_call_at: chrono::Utc::now(),
+            };
+            Box::pin(async move { Ok(Some(memory)) })
+        }
+
+        fn save(&self, memory: CallerMemory) -> CallerMemoryFuture<()> {
+            let mut state = self
+                .state
+                .lock()
+                .expect("caller memory spy state mutex poisoned");
+            state.saved.push(memory);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn erase(&self, _phone_number: &str) -> CallerMemoryFuture<bool> {
+            Box::pin(async { Ok(false) })
+        }
+
+        fn purge_expired(
+            &self,
+            _not_before: chrono::DateTime<chrono::Utc>,
+        ) -> CallerMemoryFuture<u64> {
+            Box::pin(async { Ok(0) })
+        }
+    }
+
     struct ScopedTestEnv {
         previous: Vec<(&'static str, Option<String>)>,
     }
@@ -2036,7 +2481,7 @@ mod tests {
             ("VOICEBOT_TTS_STREAMING_EARLY_START_ENABLED", "true"),
             ("TTS_STREAMING_FIRST_CHUNK_TIMEOUT_MS", "20"),
             ("TTS_STREAMING_TOTAL_TIMEOUT_MS", "500"),
-            ("TTS_STREAMING_EARLY_START_BYTES", "8"),
+            ("TTS_STREAMING_EARLY_START_SAMPLES", "160"),
         ])
     }
 
@@ -2069,8 +2514,11 @@ mod tests {
             None,
             phone_lookup,
             notification_port,
+            Arc::new(NoopCallerMemoryPort::new()),
+            Arc::new(NoopCallEventPublisher::new()),
             AppRuntimeConfig {
                 phone_lookup_enabled: false,
+                caller_memory_enabled: false,
             },
         );
         (worker, call_id, session_out_rx)
@@ -2085,14 +2533,16 @@ mod tests {
             .expect("session_out channel closed")
     }
 
-    fn collect_enqueue_events(items: Vec<(CallId, SessionOut)>) -> Vec<(CallId, String, u64)> {
+    fn collect_enqueue_events(
+        items: Vec<(CallId, SessionOut)>,
+    ) -> Vec<(CallId, Vec<Vec<u8>>, u64)> {
         items
             .into_iter()
             .map(|(call_id, out)| match out {
-                SessionOut::AppEnqueueBotAudioFile {
-                    path,
+                SessionOut::AppEnqueueBotAudioFrames {
+                    frames,
                     generation_id,
-                } => (call_id, path, generation_id),
+                } => (call_id, frames, generation_id),
                 other => panic!("unexpected SessionOut: {other:?}"),
             })
             .collect()
@@ -2140,15 +2590,6 @@ mod tests {
         out
     }
 
-    fn parse_segment_index_from_tts_stream_tmp(path: &str) -> Option<usize> {
-        let file = path.rsplit('/').next()?;
-        let stem = file.strip_suffix(".wav")?;
-        let mut parts = stem.rsplitn(3, '_');
-        let _ts = parts.next()?;
-        let seg = parts.next()?;
-        seg.parse::<usize>().ok()
-    }
-
     fn fixed_timestamp() -> chrono::DateTime<chrono::FixedOffset> {
         chrono::DateTime::parse_from_rfc3339("2026-02-27T00:00:00+00:00")
             .expect("valid fixed timestamp")
@@ -2174,6 +2615,8 @@ mod tests {
             None,
             phone_lookup,
             notification_port,
+            Arc::new(NoopCallerMemoryPort::new()),
+            Arc::new(NoopCallEventPublisher::new()),
             app_cfg,
         );
         (worker, call_id, app_tx)
@@ -2190,6 +2633,7 @@ mod tests {
             Arc::new(notification_spy),
             AppRuntimeConfig {
                 phone_lookup_enabled: true,
+                caller_memory_enabled: false,
             },
         );
 
@@ -2272,6 +2716,119 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn call_started_loads_caller_memory_by_e164_and_skips_save_without_turns() {
+        let (caller_memory_spy, caller_memory_state) = CallerMemorySpy::new();
+        let call_id = CallId::new("call-caller-memory-test").expect("valid call id");
+        let (session_out_tx, _session_out_rx) = tokio_mpsc::channel(16);
+        let (_app_tx, app_rx) = app_event_channel(APP_EVENT_CHANNEL_CAPACITY);
+        let (ai_spy, _ai_state) = AppWorkerAiSpy::new();
+        let mut worker = AppWorker::new(
+            call_id.clone(),
+            session_out_tx,
+            app_rx,
+            Arc::new(ai_spy),
+            None,
+            None,
+            None,
+            None,
+            Arc::new(NoopPhoneLookup::new()),
+            Arc::new(NoopNotification::new()),
+            Arc::new(caller_memory_spy),
+            Arc::new(NoopCallEventPublisher::new()),
+            AppRuntimeConfig {
+                phone_lookup_enabled: false,
+                caller_memory_enabled: true,
+            },
+        );
+
+        worker
+            .handle_app_event(AppEvent::CallStarted {
+                call_id: call_id.clone(),
+                caller: Some("090-1234-5678".to_string()),
+            })
+            .await;
+        assert_eq!(
+            worker.caller_memory.as_ref().map(|m| m.summary.as_str()),
+            Some("前回は予約の相談")
+        );
+
+        worker
+            .handle_app_event(AppEvent::CallEnded {
+                call_id,
+                from: "090-1234-5678".to_string(),
+                reason: EndReason::Bye,
+                duration_sec: Some(3),
+                timestamp: fixed_timestamp(),
+            })
+            .await;
+        tokio::task::yield_now().await;
+
+        let state = caller_memory_state
+            .lock()
+            .expect("caller memory spy state mutex poisoned");
+        assert_eq!(state.loaded_numbers, vec!["+819012345678".to_string()]);
+        assert!(state.saved.is_empty());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn caller_memory_is_not_saved_after_load_failure() {
+        let (caller_memory_spy, caller_memory_state) = CallerMemorySpy::new();
+        caller_memory_state
+            .lock()
+            .expect("caller memory spy state mutex poisoned")
+            .fail_load = true;
+        let call_id = CallId::new("call-caller-memory-fail").expect("valid call id");
+        let (session_out_tx, _session_out_rx) = tokio_mpsc::channel(16);
+        let (_app_tx, app_rx) = app_event_channel(APP_EVENT_CHANNEL_CAPACITY);
+        let (ai_spy, _ai_state) = AppWorkerAiSpy::new();
+        let mut worker = AppWorker::new(
+            call_id.clone(),
+            session_out_tx,
+            app_rx,
+            Arc::new(ai_spy),
+            None,
+            None,
+            None,
+            None,
+            Arc::new(NoopPhoneLookup::new()),
+            Arc::new(NoopNotification::new()),
+            Arc::new(caller_memory_spy),
+            Arc::new(NoopCallEventPublisher::new()),
+            AppRuntimeConfig {
+                phone_lookup_enabled: false,
+                caller_memory_enabled: true,
+            },
+        );
+
+        worker
+            .handle_app_event(AppEvent::CallStarted {
+                call_id: call_id.clone(),
+                caller: Some("090-1234-5678".to_string()),
+            })
+            .await;
+        assert!(worker.caller_memory.is_none());
+        assert!(worker.caller_number_e164.is_none());
+
+        worker.push_history("予約を変えたい".to_string(), "承知しました".to_string());
+        worker
+            .handle_app_event(AppEvent::CallEnded {
+                call_id,
+                from: "090-1234-5678".to_string(),
+                reason: EndReason::Bye,
+                duration_sec: Some(3),
+                timestamp: fixed_timestamp(),
+            })
+            .await;
+        tokio::task::yield_now().await;
+
+        let state = caller_memory_state
+            .lock()
+            .expect("caller memory spy state mutex poisoned");
+        assert_eq!(state.loaded_numbers, vec!["+819012345678".to_string()]);
+        assert!(state.saved.is_empty());
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn handle_app_event_drops_audio_when_inactive() {
         let (ai_spy, ai_state) = AppWorkerAiSpy::new();
@@ -2283,6 +2840,7 @@ mod tests {
             Arc::new(notification_spy),
             AppRuntimeConfig {
                 phone_lookup_enabled: false,
+                caller_memory_enabled: false,
             },
         );
 
@@ -2311,6 +2869,7 @@ mod tests {
             Arc::new(notification_spy),
             AppRuntimeConfig {
                 phone_lookup_enabled: true,
+                caller_memory_enabled: false,
             },
         );
         let mismatched_call_id = CallId::new("call-app-state-other").expect("valid call id");
@@ -2395,6 +2954,7 @@ mod tests {
             Arc::new(notification_spy),
             AppRuntimeConfig {
                 phone_lookup_enabled: false,
+                caller_memory_enabled: false,
             },
         );
 
@@ -2422,6 +2982,104 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn escalation_transfers_when_playback_finished_never_arrives() {
+        let (ai_port, _ai_state) =
+            FakeAiPort::new(vec![PathBuf::from("/tmp/fake_apology_tts.wav")]);
+        let (mut worker, call_id, mut session_out_rx) =
+            build_tts_test_worker(Arc::new(ai_port), None);
+        let (app_tx, app_rx) = app_event_channel(APP_EVENT_CHANNEL_CAPACITY);
+        worker.rx = app_rx;
+        worker.escalation_transfer_timeout = Duration::from_millis(30);
+
+        worker
+            .apologize_then_transfer(
+                &call_id,
+                "申し訳ございません".to_string(),
+                "support".to_string(),
+            )
+            .await;
+        assert!(matches!(
+            recv_session_out(&mut session_out_rx).await,
+            (_, SessionOut::AppSendBotAudioFile { .. })
+        ));
+
+        let task = tokio::spawn(async move {
+            worker.run_loop().await;
+            worker
+        });
+        match recv_session_out(&mut session_out_rx).await {
+            (_, SessionOut::AppRequestTransfer { person }) => assert_eq!(person, "support"),
+            (_, other) => panic!("unexpected SessionOut: {other:?}"),
+        }
+        drop(app_tx);
+        let worker = timeout(Duration::from_millis(200), task)
+            .await
+            .expect("run loop should stop when the channel closes")
+            .expect("run task should not panic");
+        assert!(worker.pending_transfer.is_none());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn escalation_requests_transfer_after_apology_playback_finishes() {
+        let (ai_port, _ai_state) =
+            FakeAiPort::new(vec![PathBuf::from("/tmp/fake_apology_tts.wav")]);
+        let (mut worker, call_id, mut session_out_rx) =
+            build_tts_test_worker(Arc::new(ai_port), None);
+        worker.active = true;
+
+        worker
+            .apologize_then_transfer(
+                &call_id,
+                "申し訳ございません".to_string(),
+                "support".to_string(),
+            )
+            .await;
+        match recv_session_out(&mut session_out_rx).await {
+            (_, SessionOut::AppSendBotAudioFile { path }) => {
+                assert_eq!(path, "/tmp/fake_apology_tts.wav");
+            }
+            (_, other) => panic!("unexpected SessionOut: {other:?}"),
+        }
+        assert!(
+            session_out_rx.try_recv().is_err(),
+            "transfer must wait for the apology to finish"
+        );
+
+        // お詫びの途中の発話で応答ターンを始めない
+        assert!(
+            worker
+                .handle_app_event(AppEvent::AudioBuffered {
+                    call_id: call_id.clone(),
+                    stream_id: "stream-1".to_string(),
+                    pcm_mulaw: vec![0xFF; 160],
+                    pcm_linear16: vec![0; 160],
+                })
+                .await
+        );
+        assert!(session_out_rx.try_recv().is_err());
+
+        assert!(
+            worker
+                .handle_app_event(AppEvent::PlaybackFinished {
+                    call_id: call_id.clone(),
+                })
+                .await
+        );
+        match recv_session_out(&mut session_out_rx).await {
+            (_, SessionOut::AppRequestTransfer { person }) => assert_eq!(person, "support"),
+            (_, other) => panic!("unexpected SessionOut: {other:?}"),
+        }
+
+        worker
+            .handle_app_event(AppEvent::PlaybackFinished { call_id })
+            .await;
+        assert!(
+            session_out_rx.try_recv().is_err(),
+            "transfer is requested only once"
+        );
+    }
+
     #[test]
     fn sorry_wav_path_points_to_data_dir() {
         assert!(SORRY_WAV_PATH.ends_with("/data/zundamon_sorry.wav"));
@@ -2491,12 +3149,12 @@ mod tests {
         let _guard = test_lock().lock().await;
         let _env_guard = init_tts_streaming_test_env();
 
-        let wav = build_pcm16_mono_wav(&[1, 2, 3, 4, 5, 6], 24_000);
-        let mut chunks = split_bytes(wav, &[56]); // header + enough PCM for >=1 segment
-        chunks.push(Vec::new()); // no-op chunk is fine
-        let mut items: Vec<Result<Vec<u8>, TtsError>> = chunks
+        // 1 セグメント (160 samples) 分 + 端数を送ったところで失敗させる
+        let samples: Vec<i16> = (0..200).collect();
+        let wav = build_pcm16_mono_wav(&samples, 8_000);
+        let mut items: Vec<Result<Vec<u8>, TtsError>> = split_bytes(wav, &[44 + 400])
             .into_iter()
-            .filter(|c| !c.is_empty())
+            .take(1)
             .map(Ok)
             .collect();
         items.push(Err(TtsError::SynthesisFailed("stream broke".to_string())));
@@ -2526,9 +3184,12 @@ mod tests {
         let first = recv_session_out(&mut session_out_rx).await;
         let events = collect_enqueue_events(vec![first]);
         assert_eq!(events[0].0, call_id);
+        assert_eq!(events[0].1.len(), 1);
         assert_eq!(events[0].2, 202);
-        assert!(parse_segment_index_from_tts_stream_tmp(&events[0].1).is_some());
-        let _ = tokio::fs::remove_file(&events[0].1).await;
+        assert!(
+            session_out_rx.try_recv().is_err(),
+            "incomplete tail must not be enqueued after stream error"
+        );
     }
 
     #[tokio::test(flavor = "current_thread")]
@@ -2536,8 +3197,10 @@ mod tests {
         let _guard = test_lock().lock().await;
         let _env_guard = init_tts_streaming_test_env();
 
-        let wav = build_pcm16_mono_wav(&[10, 20, 30, 40, 50, 60, 70, 80, 90, 100], 24_000);
-        let items = split_bytes(wav, &[50, 8, 8, 8, 8])
+        // 24kHz 480 samples x 3 => 8kHz 160 samples x 3（チャンク境界はフレーム境界と無関係）
+        let samples: Vec<i16> = (0..1440).map(|i| (i % 100) as i16).collect();
+        let wav = build_pcm16_mono_wav(&samples, 24_000);
+        let items = split_bytes(wav, &[50, 999, 777, 1000])
             .into_iter()
             .map(Ok)
             .collect::<Vec<_>>();
@@ -2569,27 +3232,15 @@ mod tests {
             raw_events.push(recv_session_out(&mut session_out_rx).await);
         }
         let events = collect_enqueue_events(raw_events);
-        assert!(events.len() >= 3);
-        for (event_call_id, _path, generation_id) in &events {
+        for (event_call_id, frames, generation_id) in &events {
             assert_eq!(event_call_id, &call_id);
             assert_eq!(*generation_id, 303);
+            assert_eq!(frames.len(), 1);
+            assert_eq!(frames[0].len(), 160);
         }
-
-        let indices: Vec<usize> = events
-            .iter()
-            .map(|(_, path, _)| {
-                parse_segment_index_from_tts_stream_tmp(path)
-                    .expect("segment index must be encoded in tmp file name")
-            })
-            .collect();
-        assert_eq!(
-            indices,
-            vec![0, 1, 2],
-            "segments should be enqueued in order"
+        assert!(
+            session_out_rx.try_recv().is_err(),
+            "exactly three segments should be enqueued"
         );
-
-        for (_, path, _) in events {
-            let _ = tokio::fs::remove_file(path).await;
-        }
     }
 }
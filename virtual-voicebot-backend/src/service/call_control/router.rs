@@ -1,14 +1,16 @@
 use std::path::PathBuf;
-use std::sync::OnceLock;
 
 use serde::Deserialize;
 
+use crate::service::runtime_config::{self, ReloadError};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Intent {
     Identity,
     SystemInfo,
     Weather,
     Transfer,
+    RecordingPause,
     GeneralChat,
 }
 
@@ -37,6 +39,8 @@ pub enum RouteAction {
     Transfer {
         person: String,
     },
+    /// 録音を一時停止してから案内を流す（カード番号などを話してもらう前）
+    RecordingPause,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -48,6 +52,8 @@ pub struct RouterConfig {
     pub system_info: Option<SystemInfoConfig>,
     pub system_info_response: Option<String>,
     pub transfer: Option<TransferConfig>,
+    /// 録音を一時停止したことを伝える案内
+    pub recording_pause_response: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -68,6 +74,8 @@ pub struct TransferConfig {
 #[serde(default)]
 pub struct TransferEntry {
     pub aliases: Vec<String>,
+    /// Hunt group to ring for this person (defaults to the directory key itself).
+    pub hunt_group: Option<String>,
 }
 
 impl Default for RouterConfig {
@@ -79,6 +87,7 @@ impl Default for RouterConfig {
             system_info: Some(SystemInfoConfig::default()),
             system_info_response: None,
             transfer: Some(TransferConfig::default()),
+            recording_pause_response: "録音を停止しました。どうぞお話しください".to_string(),
         }
     }
 }
@@ -106,6 +115,7 @@ impl Default for TransferConfig {
                     "すがた".to_string(),
                     "すがたさん".to_string(),
                 ],
+                hunt_group: None,
             },
         );
         Self {
@@ -116,48 +126,33 @@ impl Default for TransferConfig {
     }
 }
 
-static ROUTER_CONFIG: OnceLock<RouterConfig> = OnceLock::new();
-
-pub fn router_config() -> &'static RouterConfig {
-    ROUTER_CONFIG.get_or_init(load_router_config)
+/// 設定上の固定応答文（TTS フレーズキャッシュのウォームアップ対象）。
+pub fn fixed_phrases() -> Vec<String> {
+    Router::new().fixed_phrases()
 }
 
-pub fn system_info_response() -> String {
-    if let Some(cfg) = &router_config().system_info {
-        return cfg.default.clone();
-    }
-    router_config()
-        .system_info_response
-        .clone()
-        .unwrap_or_else(|| SystemInfoConfig::default().default)
-}
-
-fn load_router_config() -> RouterConfig {
+/// intent_router.yaml を読み込む。ファイルが無ければ既定値、YAML として不正ならエラー
+pub(crate) fn load_router_config() -> Result<RouterConfig, ReloadError> {
     let path = config_path();
     match std::fs::read_to_string(&path) {
-        Ok(text) => match serde_yaml::from_str::<RouterConfig>(&text) {
-            Ok(cfg) => cfg,
-            Err(err) => {
-                log::warn!(
-                    "[router] failed to parse config {:?}: {:?}. Using default.",
-                    path,
-                    err
-                );
-                RouterConfig::default()
-            }
-        },
-        Err(err) => {
-            log::warn!(
-                "[router] config not found {:?}: {:?}. Using default.",
-                path,
-                err
-            );
-            RouterConfig::default()
+        Ok(text) => {
+            serde_yaml::from_str::<RouterConfig>(&text).map_err(|err| ReloadError::RouterConfig {
+                path: path.display().to_string(),
+                reason: err.to_string(),
+            })
         }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            log::warn!("[router] config not found {:?}. Using default.", path);
+            Ok(RouterConfig::default())
+        }
+        Err(err) => Err(ReloadError::RouterConfig {
+            path: path.display().to_string(),
+            reason: err.to_string(),
+        }),
     }
 }
 
-fn config_path() -> PathBuf {
+pub(crate) fn config_path() -> PathBuf {
     let base = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..");
     let default_path = base.join("intent_router.yaml");
     let env_path = std::env::var("INTENT_ROUTER_CONFIG").ok();
@@ -194,6 +189,7 @@ pub fn parse_intent_json(raw: &str, fallback_query: &str) -> IntentResult {
                 "system_info" => Intent::SystemInfo,
                 "weather" => Intent::Weather,
                 "transfer" => Intent::Transfer,
+                "recording_pause" => Intent::RecordingPause,
                 "general_chat" => Intent::GeneralChat,
                 _ => Intent::GeneralChat,
             };
@@ -236,7 +232,7 @@ pub fn parse_intent_json(raw: &str, fallback_query: &str) -> IntentResult {
     }
 }
 
-fn sanitize_json_block(input: &str) -> String {
+pub(super) fn sanitize_json_block(input: &str) -> String {
     let mut s = input.trim().to_string();
     if let Some(stripped) = s.strip_prefix("```") {
         s = stripped.trim().to_string();
@@ -255,12 +251,38 @@ pub struct Router {
 }
 
 impl Router {
+    /// 新しい通話向けの現行設定で作る
     pub fn new() -> Self {
         Self {
-            cfg: router_config().clone(),
+            cfg: runtime_config::current().router.clone(),
+        }
+    }
+
+    /// 通話開始時に固定した設定で作る
+    pub fn for_call(call_id: &str) -> Self {
+        Self {
+            cfg: runtime_config::for_call(call_id).router.clone(),
         }
     }
 
+    pub fn system_info_response(&self) -> String {
+        if let Some(cfg) = &self.cfg.system_info {
+            return cfg.default.clone();
+        }
+        self.cfg
+            .system_info_response
+            .clone()
+            .unwrap_or_else(|| SystemInfoConfig::default().default)
+    }
+
+    pub fn recording_pause_response(&self) -> String {
+        self.cfg.recording_pause_response.clone()
+    }
+
+    pub fn weather_error_response(&self) -> String {
+        self.cfg.weather_error_response.clone()
+    }
+
     pub fn route(&self, result: IntentResult) -> RouteAction {
         match result.intent {
             Intent::Identity => RouteAction::FixedResponse(self.cfg.identity_response.clone()),
@@ -278,9 +300,25 @@ impl Router {
             Intent::Transfer => RouteAction::Transfer {
                 person: result.person.unwrap_or_default(),
             },
+            Intent::RecordingPause => RouteAction::RecordingPause,
         }
     }
 
+    pub fn fixed_phrases(&self) -> Vec<String> {
+        let mut phrases = vec![
+            self.cfg.identity_response.clone(),
+            self.cfg.weather_error_response.clone(),
+            self.system_info_response(),
+            self.transfer_confirm_message(),
+            self.transfer_not_found_message(),
+            self.cfg.recording_pause_response.clone(),
+        ];
+        phrases.retain(|phrase| !phrase.trim().is_empty());
+        let mut seen = std::collections::HashSet::new();
+        phrases.retain(|phrase| seen.insert(phrase.clone()));
+        phrases
+    }
+
     pub fn transfer_confirm_message(&self) -> String {
         self.cfg
             .transfer
@@ -334,6 +372,24 @@ impl Router {
         }
         None
     }
+
+    /// Resolves a person identifier to the transfer target handed to the session.
+    ///
+    /// This is the entry's `hunt_group` when configured, otherwise the directory key
+    /// returned by [`Router::resolve_transfer_person`].
+    pub fn resolve_transfer_target(&self, person: &str) -> Option<String> {
+        let name = self.resolve_transfer_person(person)?;
+        let hunt_group = self
+            .cfg
+            .transfer
+            .as_ref()
+            .and_then(|cfg| cfg.directory.get(&name))
+            .and_then(|entry| entry.hunt_group.as_deref())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string);
+        Some(hunt_group.unwrap_or(name))
+    }
 }
 
 /// Normalize a person name by trimming and removing full-width and half-width spaces.
@@ -363,4 +419,68 @@ mod tests {
         assert_eq!(normalize_person("  "), "");
         assert_eq!(normalize_person(""), "");
     }
+
+    #[test]
+    fn fixed_phrases_are_deduplicated_and_skip_empty() {
+        let router = Router {
+            cfg: RouterConfig {
+                identity_response: "おつなぎします".to_string(),
+                weather_error_response: " ".to_string(),
+                ..RouterConfig::default()
+            },
+        };
+        let phrases = router.fixed_phrases();
+        assert_eq!(
+            phrases
+                .iter()
+                .filter(|phrase| phrase.as_str() == "おつなぎします")
+                .count(),
+            1
+        );
+        assert!(phrases.iter().all(|phrase| !phrase.trim().is_empty()));
+        assert!(phrases.contains(&router.transfer_not_found_message()));
+    }
+
+    #[test]
+    fn transfer_target_prefers_hunt_group() {
+        let mut transfer = TransferConfig::default();
+        transfer.directory.insert(
+            "営業".to_string(),
+            TransferEntry {
+                aliases: vec!["えいぎょう".to_string()],
+                hunt_group: Some("sales".to_string()),
+            },
+        );
+        let router = Router {
+            cfg: RouterConfig {
+                transfer: Some(transfer),
+                ..RouterConfig::default()
+            },
+        };
+        assert_eq!(
+            router.resolve_transfer_target("えいぎょう"),
+            Some("sales".to_string())
+        );
+        assert_eq!(
+            router.resolve_transfer_target("須田さん"),
+            Some("須田".to_string())
+        );
+        assert_eq!(router.resolve_transfer_target("unknown"), None);
+    }
+
+    #[test]
+    fn recording_pause_intent_routes_to_pause() {
+        let result = parse_intent_json(
+            r#"{"intent":"recording_pause","query":"カード番号を言います"}"#,
+            "fallback",
+        );
+        assert_eq!(result.intent, Intent::RecordingPause);
+        let router = Router {
+            cfg: RouterConfig::default(),
+        };
+        assert!(matches!(router.route(result), RouteAction::RecordingPause));
+        assert!(router
+            .fixed_phrases()
+            .contains(&router.recording_pause_response()));
+    }
 }
@@ -1,9 +1,15 @@
+//! TTS のストリーミング WAV 応答をメモリ上で PCMU（8kHz μ-law, 20ms フレーム）へ変換し、
+//! 一定サンプル数ごとのセグメントとして切り出す。一時 WAV ファイルは作らない。
+
+use crate::shared::audio::{
+    decimation_step_to_8k, linear16_to_mulaw, mulaw_to_frames, PCMU_FRAME_SAMPLES,
+};
+
 #[derive(Clone, Debug)]
 struct WavPcmFormat {
     audio_format: u16,
     channels: u16,
     sample_rate: u32,
-    byte_rate: u32,
     block_align: u16,
     bits_per_sample: u16,
 }
@@ -11,24 +17,35 @@ struct WavPcmFormat {
 #[derive(Debug)]
 pub(super) struct WavStreamChunker {
     header_buf: Vec<u8>,
+    /// 未変換の PCM バイト（サンプル境界に満たない端数）
     pcm_buf: Vec<u8>,
+    /// 8kHz に間引き済みの μ-law サンプル
+    mulaw_buf: Vec<u8>,
     fmt: Option<WavPcmFormat>,
     data_start: Option<usize>,
-    segment_pcm_bytes: usize,
+    decimation_step: usize,
+    decimation_phase: usize,
+    segment_samples: usize,
 }
 
 impl WavStreamChunker {
-    pub(super) fn new(segment_pcm_bytes: usize) -> Self {
+    /// `segment_samples` は 8kHz 換算のサンプル数。フレーム途中で区切らないよう 160 の倍数に切り上げる。
+    pub(super) fn new(segment_samples: usize) -> Self {
+        let frames = segment_samples.max(1).div_ceil(PCMU_FRAME_SAMPLES);
         Self {
             header_buf: Vec::new(),
             pcm_buf: Vec::new(),
+            mulaw_buf: Vec::new(),
             fmt: None,
             data_start: None,
-            segment_pcm_bytes: segment_pcm_bytes.max(1),
+            decimation_step: 1,
+            decimation_phase: 0,
+            segment_samples: frames.saturating_mul(PCMU_FRAME_SAMPLES),
         }
     }
 
-    pub(super) fn push(&mut self, bytes: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+    /// ストリームの断片を取り込み、しきい値に達したセグメント（PCMU フレーム列）を返す。
+    pub(super) fn push(&mut self, bytes: &[u8]) -> Result<Vec<Vec<Vec<u8>>>, String> {
         if bytes.is_empty() {
             return Ok(Vec::new());
         }
@@ -40,10 +57,12 @@ impl WavStreamChunker {
             self.pcm_buf.extend_from_slice(bytes);
         }
 
-        self.take_ready_segments()
+        self.encode_pending_samples();
+        Ok(self.take_ready_segments())
     }
 
-    pub(super) fn finish(&mut self) -> Result<Option<Vec<u8>>, String> {
+    /// 残りのサンプルを最後のセグメントとして返す（末尾フレームは無音で埋める）。
+    pub(super) fn finish(&mut self) -> Result<Option<Vec<Vec<u8>>>, String> {
         if self.fmt.is_none() {
             if self.header_buf.is_empty() {
                 return Err("empty wav stream".to_string());
@@ -51,20 +70,18 @@ impl WavStreamChunker {
             return Err("incomplete wav header".to_string());
         }
 
-        if self.pcm_buf.is_empty() {
-            return Ok(None);
-        }
-
-        let align = self.block_align_bytes();
-        if !self.pcm_buf.len().is_multiple_of(align) {
+        if !self.pcm_buf.is_empty() {
             return Err(format!(
-                "pcm payload size is not aligned: len={} align={align}",
+                "pcm payload size is not aligned: trailing_bytes={}",
                 self.pcm_buf.len()
             ));
         }
+        if self.mulaw_buf.is_empty() {
+            return Ok(None);
+        }
 
-        let pcm = std::mem::take(&mut self.pcm_buf);
-        Ok(Some(self.build_pcm_wav(&pcm)?))
+        let mulaw = std::mem::take(&mut self.mulaw_buf);
+        Ok(Some(mulaw_to_frames(&mulaw)))
     }
 
     fn try_parse_header(&mut self) -> Result<(), String> {
@@ -93,6 +110,8 @@ impl WavStreamChunker {
                     return Err("wav data chunk appeared before fmt chunk".to_string());
                 };
                 validate_fmt(&fmt)?;
+                self.decimation_step = decimation_step_to_8k(fmt.sample_rate)
+                    .ok_or_else(|| format!("unsupported wav sample_rate {}", fmt.sample_rate))?;
                 self.fmt = Some(fmt);
                 self.data_start = Some(chunk_data_start);
                 if b.len() > chunk_data_start {
@@ -119,7 +138,6 @@ impl WavStreamChunker {
                     audio_format: le_u16(&data[0..2]),
                     channels: le_u16(&data[2..4]),
                     sample_rate: le_u32(&data[4..8]),
-                    byte_rate: le_u32(&data[8..12]),
                     block_align: le_u16(&data[12..14]),
                     bits_per_sample: le_u16(&data[14..16]),
                 });
@@ -131,65 +149,29 @@ impl WavStreamChunker {
         Ok(())
     }
 
-    fn take_ready_segments(&mut self) -> Result<Vec<Vec<u8>>, String> {
+    fn encode_pending_samples(&mut self) {
         if self.fmt.is_none() {
-            return Ok(Vec::new());
-        }
-        let align = self.block_align_bytes();
-        let mut threshold = self.segment_pcm_bytes;
-        if threshold < align {
-            threshold = align;
+            return;
         }
-        threshold -= threshold % align;
-        if threshold == 0 {
-            threshold = align;
+        let whole = self.pcm_buf.len() - self.pcm_buf.len() % 2;
+        for sample in self.pcm_buf[..whole].chunks_exact(2) {
+            if self.decimation_phase == 0 {
+                let sample = i16::from_le_bytes([sample[0], sample[1]]);
+                self.mulaw_buf.push(linear16_to_mulaw(sample));
+            }
+            self.decimation_phase = (self.decimation_phase + 1) % self.decimation_step;
         }
+        self.pcm_buf.drain(..whole);
+    }
 
+    fn take_ready_segments(&mut self) -> Vec<Vec<Vec<u8>>> {
         let mut out = Vec::new();
-        while self.pcm_buf.len() >= threshold {
-            let rest = self.pcm_buf.split_off(threshold);
-            let pcm = std::mem::replace(&mut self.pcm_buf, rest);
-            out.push(self.build_pcm_wav(&pcm)?);
+        while self.mulaw_buf.len() >= self.segment_samples {
+            let rest = self.mulaw_buf.split_off(self.segment_samples);
+            let mulaw = std::mem::replace(&mut self.mulaw_buf, rest);
+            out.push(mulaw_to_frames(&mulaw));
         }
-        Ok(out)
-    }
-
-    fn block_align_bytes(&self) -> usize {
-        self.fmt
-            .as_ref()
-            .map(|f| usize::from(f.block_align.max(1)))
-            .unwrap_or(2)
-    }
-
-    fn build_pcm_wav(&self, pcm: &[u8]) -> Result<Vec<u8>, String> {
-        let fmt = self
-            .fmt
-            .as_ref()
-            .ok_or_else(|| "wav header not parsed".to_string())?;
-        validate_fmt(fmt)?;
-
-        let data_len =
-            u32::try_from(pcm.len()).map_err(|_| "pcm payload too large for wav".to_string())?;
-        let riff_size = 36u32
-            .checked_add(data_len)
-            .ok_or_else(|| "wav riff size overflow".to_string())?;
-
-        let mut out = Vec::with_capacity(44 + pcm.len());
-        out.extend_from_slice(b"RIFF");
-        out.extend_from_slice(&riff_size.to_le_bytes());
-        out.extend_from_slice(b"WAVE");
-        out.extend_from_slice(b"fmt ");
-        out.extend_from_slice(&16u32.to_le_bytes());
-        out.extend_from_slice(&fmt.audio_format.to_le_bytes());
-        out.extend_from_slice(&fmt.channels.to_le_bytes());
-        out.extend_from_slice(&fmt.sample_rate.to_le_bytes());
-        out.extend_from_slice(&fmt.byte_rate.to_le_bytes());
-        out.extend_from_slice(&fmt.block_align.to_le_bytes());
-        out.extend_from_slice(&fmt.bits_per_sample.to_le_bytes());
-        out.extend_from_slice(b"data");
-        out.extend_from_slice(&data_len.to_le_bytes());
-        out.extend_from_slice(pcm);
-        Ok(out)
+        out
     }
 }
 
@@ -223,6 +205,7 @@ fn le_u32(bytes: &[u8]) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::WavStreamChunker;
+    use crate::shared::audio::linear16_to_mulaw;
 
     fn pcm16_mono_wav_bytes(samples: &[i16], sample_rate: u32) -> Vec<u8> {
         let mut pcm = Vec::with_capacity(samples.len() * 2);
@@ -250,21 +233,57 @@ mod tests {
     }
 
     #[test]
-    fn emits_segments_and_tail_from_streamed_wav() {
-        let wav = pcm16_mono_wav_bytes(&[1, 2, 3, 4, 5, 6], 24_000);
-        let mut chunker = WavStreamChunker::new(8); // 4 samples (8 bytes)
+    fn emits_pcmu_segments_and_tail_from_streamed_wav() {
+        // 8kHz x 400 samples => 2 full frames + 80-sample tail
+        let samples: Vec<i16> = (0..400).map(|i| i as i16).collect();
+        let wav = pcm16_mono_wav_bytes(&samples, 8_000);
+        let mut chunker = WavStreamChunker::new(100); // rounded up to 160 samples
 
         let out0 = chunker.push(&wav[..10]).unwrap();
         assert!(out0.is_empty());
 
-        let out1 = chunker.push(&wav[10..47]).unwrap();
+        // header + 159 samples + 1 odd byte: not enough for a segment yet
+        let out1 = chunker.push(&wav[10..44 + 319]).unwrap();
         assert!(out1.is_empty());
 
-        let out2 = chunker.push(&wav[47..]).unwrap();
-        assert_eq!(out2.len(), 1);
+        let out2 = chunker.push(&wav[44 + 319..]).unwrap();
+        assert_eq!(out2.len(), 2);
+        assert!(out2.iter().all(|segment| segment.len() == 1));
+        assert_eq!(out2[0][0].len(), 160);
+        assert_eq!(out2[0][0][0], linear16_to_mulaw(0));
+        assert_eq!(out2[1][0][0], linear16_to_mulaw(160));
 
         let tail = chunker.finish().unwrap().unwrap();
-        assert!(tail.len() > 44);
+        assert_eq!(tail.len(), 1);
+        assert_eq!(tail[0].len(), 160);
+        assert_eq!(tail[0][79], linear16_to_mulaw(399));
+        assert_eq!(tail[0][80], 0xFF, "tail frame is padded with silence");
+    }
+
+    #[test]
+    fn decimates_24khz_across_chunk_boundaries() {
+        let samples: Vec<i16> = (0..480).map(|i| i as i16).collect();
+        let wav = pcm16_mono_wav_bytes(&samples, 24_000);
+        let mut chunker = WavStreamChunker::new(160);
+
+        let mut segments = Vec::new();
+        for chunk in wav.chunks(7) {
+            segments.extend(chunker.push(chunk).unwrap());
+        }
+        assert_eq!(segments.len(), 1);
+        let frame = &segments[0][0];
+        assert_eq!(frame[0], linear16_to_mulaw(0));
+        assert_eq!(frame[1], linear16_to_mulaw(3));
+        assert_eq!(frame[159], linear16_to_mulaw(477));
+        assert!(chunker.finish().unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_unsupported_sample_rate() {
+        let wav = pcm16_mono_wav_bytes(&[1, 2], 16_000);
+        let mut chunker = WavStreamChunker::new(160);
+        let err = chunker.push(&wav).unwrap_err();
+        assert!(err.contains("unsupported wav sample_rate"));
     }
 
     #[test]
@@ -0,0 +1,272 @@
+//! 通話イベントの配信ハブ。セッション・app から受けたイベントに連番を振り、
+//! 直近分をリングバッファに残しつつ購読者へ配る。
+//!
+//! 発行側（通話処理）は決して待たない。遅い購読者は broadcast から取りこぼし（Lagged）、
+//! その分はリングバッファから補うか、補えなければ reset を受け取って状態を取り直す。
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+
+use crate::shared::config;
+use crate::shared::ports::call_event_port::{CallEvent, CallEventPublisher};
+
+/// 購読者ごとの未読上限（超えた分は Lagged としてリングバッファから補う）
+const SUBSCRIBER_CHANNEL_CAPACITY: usize = 256;
+
+/// 配信用に整形済みのイベント
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamEvent {
+    /// 単調増加の連番（SSE の id。プロセス再起動で 1 から振り直す）
+    pub id: u64,
+    pub call_id: String,
+    /// SSE の event 名（ringing / answered / ivr_state / ivr_node / dtmf / transfer /
+    /// utterance / reply / quality_alert / ended）
+    pub event: &'static str,
+    /// JSON 文字列
+    pub data: String,
+}
+
+/// 購読開始時点の再送分と、以降のイベントを受ける口
+#[derive(Debug)]
+pub struct Subscription {
+    /// 指定 id より後でリングバッファに残っている分
+    pub replay: Vec<Arc<StreamEvent>>,
+    /// 指定 id の直後がすでにリングバッファから消えていた（取りこぼしあり）
+    pub gap: bool,
+    pub rx: broadcast::Receiver<Arc<StreamEvent>>,
+}
+
+#[derive(Debug)]
+struct HubState {
+    next_id: u64,
+    buffer: VecDeque<Arc<StreamEvent>>,
+}
+
+#[derive(Debug)]
+pub struct CallEventHub {
+    state: Mutex<HubState>,
+    tx: broadcast::Sender<Arc<StreamEvent>>,
+    replay_capacity: usize,
+}
+
+impl CallEventHub {
+    pub fn new(replay_capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(SUBSCRIBER_CHANNEL_CAPACITY);
+        Self {
+            state: Mutex::new(HubState {
+                next_id: 1,
+                buffer: VecDeque::with_capacity(replay_capacity),
+            }),
+            tx,
+            replay_capacity: replay_capacity.max(1),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(config::call_event_replay_buffer())
+    }
+
+    /// 購読を始める。`last_event_id` を渡すとその後のイベントから再送する
+    pub fn subscribe(&self, last_event_id: Option<u64>) -> Subscription {
+        // 再送分の切り出しと購読開始を同じロック内で行い、境目での取りこぼしを防ぐ
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (replay, gap) = match last_event_id {
+            Some(last) => replay_after(&state, last),
+            None => (Vec::new(), false),
+        };
+        Subscription {
+            replay,
+            gap,
+            rx: self.tx.subscribe(),
+        }
+    }
+
+    /// `last_event_id` より後でリングバッファに残っている分（取りこぼしがあれば true）
+    pub fn replay_after(&self, last_event_id: u64) -> (Vec<Arc<StreamEvent>>, bool) {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        replay_after(&state, last_event_id)
+    }
+
+    fn push(&self, call_id: &str, event: &'static str, data: Value) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let id = state.next_id;
+        state.next_id += 1;
+        let stream_event = Arc::new(StreamEvent {
+            id,
+            call_id: call_id.to_string(),
+            event,
+            data: data.to_string(),
+        });
+        if state.buffer.len() >= self.replay_capacity {
+            state.buffer.pop_front();
+        }
+        state.buffer.push_back(stream_event.clone());
+        // 購読者がいなければ送れないだけなので無視する
+        let _ = self.tx.send(stream_event);
+    }
+}
+
+fn replay_after(state: &HubState, last_event_id: u64) -> (Vec<Arc<StreamEvent>>, bool) {
+    let oldest = state
+        .buffer
+        .front()
+        .map(|event| event.id)
+        .unwrap_or(state.next_id);
+    if last_event_id >= state.next_id {
+        // 再起動などで連番が巻き戻っている。残っている分をすべて送る
+        return (state.buffer.iter().cloned().collect(), true);
+    }
+    let gap = last_event_id.saturating_add(1) < oldest;
+    let replay = state
+        .buffer
+        .iter()
+        .filter(|event| event.id > last_event_id)
+        .cloned()
+        .collect();
+    (replay, gap)
+}
+
+impl CallEventPublisher for CallEventHub {
+    fn publish(&self, call_id: &str, event: CallEvent) {
+        let (name, mut data) = event_payload(event);
+        if let Value::Object(fields) = &mut data {
+            fields.insert("callId".to_string(), json!(call_id));
+            fields.insert("at".to_string(), json!(Utc::now().to_rfc3339()));
+        }
+        self.push(call_id, name, data);
+    }
+}
+
+/// 同じイベントを複数の発行先へ配る（運用画面向けのハブと外部 webhook など）
+pub struct CallEventFanout {
+    targets: Vec<Arc<dyn CallEventPublisher>>,
+}
+
+impl CallEventFanout {
+    pub fn new(targets: Vec<Arc<dyn CallEventPublisher>>) -> Self {
+        Self { targets }
+    }
+}
+
+impl CallEventPublisher for CallEventFanout {
+    fn publish(&self, call_id: &str, event: CallEvent) {
+        for target in &self.targets {
+            target.publish(call_id, event.clone());
+        }
+    }
+}
+
+fn event_payload(event: CallEvent) -> (&'static str, Value) {
+    match event {
+        CallEvent::Ringing { from, to } => ("ringing", json!({ "from": from, "to": to })),
+        CallEvent::Answered => ("answered", json!({})),
+        CallEvent::IvrState { state } => ("ivr_state", json!({ "state": state })),
+        CallEvent::IvrNode {
+            event_type,
+            node_id,
+            dtmf_key,
+        } => (
+            "ivr_node",
+            json!({
+                "eventType": event_type,
+                "nodeId": node_id.map(|id| id.to_string()),
+                "dtmfKey": dtmf_key.map(|key| key.to_string()),
+            }),
+        ),
+        CallEvent::Dtmf { digit } => ("dtmf", json!({ "digit": digit.to_string() })),
+        CallEvent::Transfer {
+            status,
+            member,
+            outcome,
+            sip_status,
+        } => (
+            "transfer",
+            json!({
+                "status": status,
+                "member": member,
+                "outcome": outcome,
+                "sipStatus": sip_status,
+            }),
+        ),
+        CallEvent::UserUtterance { text, asr_ms } => {
+            ("utterance", json!({ "text": text, "asrMs": asr_ms }))
+        }
+        CallEvent::BotReply {
+            text,
+            llm_ms,
+            turn_ms,
+        } => (
+            "reply",
+            json!({ "text": text, "llmMs": llm_ms, "turnMs": turn_ms }),
+        ),
+        CallEvent::RecordingPaused { by } => ("recording_paused", json!({ "by": by })),
+        CallEvent::RecordingResumed { by } => ("recording_resumed", json!({ "by": by })),
+        CallEvent::QualityAlert { mos, threshold } => (
+            "quality_alert",
+            json!({ "mos": mos, "threshold": threshold }),
+        ),
+        CallEvent::Ended {
+            reason,
+            duration_sec,
+        } => (
+            "ended",
+            json!({ "reason": reason, "durationSec": duration_sec }),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dtmf(hub: &CallEventHub, call_id: &str, digit: char) {
+        hub.publish(call_id, CallEvent::Dtmf { digit });
+    }
+
+    #[tokio::test]
+    async fn subscriber_resumes_after_last_event_id() {
+        let hub = CallEventHub::new(10);
+        dtmf(&hub, "call-a", '1');
+        dtmf(&hub, "call-a", '2');
+        dtmf(&hub, "call-b", '3');
+
+        let mut sub = hub.subscribe(Some(1));
+        assert!(!sub.gap);
+        assert_eq!(
+            sub.replay.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        let data: Value = serde_json::from_str(&sub.replay[1].data).unwrap();
+        assert_eq!(data["callId"], "call-b");
+        assert_eq!(data["digit"], "3");
+
+        hub.publish("call-a", CallEvent::Answered);
+        let live = sub.rx.recv().await.unwrap();
+        assert_eq!((live.id, live.event), (4, "answered"));
+    }
+
+    #[test]
+    fn replay_reports_gap_when_buffer_rotated() {
+        let hub = CallEventHub::new(2);
+        for digit in ['1', '2', '3', '4'] {
+            dtmf(&hub, "call-a", digit);
+        }
+
+        let (replay, gap) = hub.replay_after(1);
+        assert!(gap);
+        assert_eq!(replay.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 4]);
+
+        let (replay, gap) = hub.replay_after(2);
+        assert!(!gap);
+        assert_eq!(replay.len(), 2);
+
+        // 再起動前の id で再接続してきた
+        let (replay, gap) = hub.replay_after(100);
+        assert!(gap);
+        assert_eq!(replay.len(), 2);
+    }
+}
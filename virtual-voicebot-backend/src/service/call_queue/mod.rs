@@ -0,0 +1,520 @@
+//! 待ち呼キュー（ACD）。空いたエージェントへ優先度順・到着順に呼を配り、待ち順位を案内する。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{FixedOffset, Utc};
+use log::{debug, info, warn};
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::{interval, Instant, MissedTickBehavior};
+
+use crate::shared::config::CallQueueConfig;
+use crate::shared::entities::CallId;
+use crate::shared::ports::ai::TtsPort;
+use crate::shared::ports::notification::{
+    CallbackRequest, CallbackRequestNotifier, NotificationError,
+};
+
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(1);
+const DIRECTIVE_CHANNEL_CAPACITY: usize = 64;
+
+/// キューからセッションへの指示（main がセッションへ渡す）
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueueDirective {
+    /// 待ち順位の案内音声を流す（合成済み定型文のため削除しないこと）
+    Position { position: usize, audio_path: String },
+    /// エージェントを呼び出して接続する
+    Connect { agent: String },
+}
+
+#[derive(Clone, Debug)]
+struct WaitingCall {
+    call_id: CallId,
+    priority: i32,
+    /// 到着順。接続に失敗して戻るときも元の順番を保つ
+    seq: u64,
+    last_announced_at: Instant,
+}
+
+impl WaitingCall {
+    /// 優先度が高いほど、同じ優先度なら早く来たほど先
+    fn ranks_before(&self, other: &WaitingCall) -> bool {
+        self.priority > other.priority || (self.priority == other.priority && self.seq < other.seq)
+    }
+}
+
+#[derive(Debug)]
+struct AgentSlot {
+    uri: String,
+    busy_with: Option<CallId>,
+    available_at: Instant,
+}
+
+#[derive(Debug)]
+struct QueueState {
+    config: CallQueueConfig,
+    waiting: Vec<WaitingCall>,
+    agents: Vec<AgentSlot>,
+    /// エージェントを呼び出し中・通話中の呼
+    dispatched: HashMap<CallId, WaitingCall>,
+}
+
+impl QueueState {
+    fn insert_waiting(&mut self, call: WaitingCall) -> usize {
+        let index = self
+            .waiting
+            .iter()
+            .position(|waiting| call.ranks_before(waiting))
+            .unwrap_or(self.waiting.len());
+        self.waiting.insert(index, call);
+        index + 1
+    }
+
+    /// 呼を担当していたエージェントを後処理時間の後に空きへ戻す
+    fn release_agent(&mut self, call_id: &CallId, agent: &str, now: Instant) -> bool {
+        let Some(slot) = self
+            .agents
+            .iter_mut()
+            .find(|slot| slot.uri == agent && slot.busy_with.as_ref() == Some(call_id))
+        else {
+            return false;
+        };
+        slot.busy_with = None;
+        slot.available_at = now + self.config.wrap_up;
+        true
+    }
+}
+
+/// キューの状態（時刻は呼び出し側から渡す）
+#[derive(Debug, Default)]
+pub struct CallQueues {
+    queues: HashMap<String, QueueState>,
+    next_seq: u64,
+}
+
+impl CallQueues {
+    pub fn new(configs: impl IntoIterator<Item = CallQueueConfig>, now: Instant) -> Self {
+        let queues = configs
+            .into_iter()
+            .map(|config| {
+                let agents = config
+                    .agents
+                    .iter()
+                    .map(|uri| AgentSlot {
+                        uri: uri.clone(),
+                        busy_with: None,
+                        available_at: now,
+                    })
+                    .collect();
+                (
+                    config.name.clone(),
+                    QueueState {
+                        config,
+                        waiting: Vec::new(),
+                        agents,
+                        dispatched: HashMap::new(),
+                    },
+                )
+            })
+            .collect();
+        Self {
+            queues,
+            next_seq: 0,
+        }
+    }
+
+    /// キューに入れて待ち順位を返す。未定義のキューなら None
+    pub fn join(
+        &mut self,
+        call_id: CallId,
+        queue: &str,
+        priority: i32,
+        now: Instant,
+    ) -> Option<usize> {
+        if !self.queues.contains_key(queue) {
+            return None;
+        }
+        self.leave(&call_id, now);
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let state = self.queues.get_mut(queue)?;
+        Some(state.insert_waiting(WaitingCall {
+            call_id,
+            priority,
+            seq,
+            last_announced_at: now,
+        }))
+    }
+
+    /// 待ち中の呼をキューから外す（切断・留守番電話・折り返し依頼）。
+    /// 割り当て直後で接続前に抜けた呼のエージェントはすぐ空きに戻す
+    pub fn leave(&mut self, call_id: &CallId, now: Instant) -> bool {
+        let mut removed = false;
+        for state in self.queues.values_mut() {
+            let before = state.waiting.len();
+            state.waiting.retain(|waiting| &waiting.call_id != call_id);
+            removed |= state.waiting.len() != before;
+            if state.dispatched.remove(call_id).is_some() {
+                for slot in state
+                    .agents
+                    .iter_mut()
+                    .filter(|slot| slot.busy_with.as_ref() == Some(call_id))
+                {
+                    slot.busy_with = None;
+                    slot.available_at = now;
+                }
+                removed = true;
+            }
+        }
+        removed
+    }
+
+    /// エージェントが応答しなかった呼を元の順番でキューへ戻す
+    pub fn connect_failed(&mut self, call_id: &CallId, queue: &str, agent: &str, now: Instant) {
+        let Some(state) = self.queues.get_mut(queue) else {
+            return;
+        };
+        state.release_agent(call_id, agent, now);
+        if let Some(mut call) = state.dispatched.remove(call_id) {
+            call.last_announced_at = now;
+            state.insert_waiting(call);
+        }
+    }
+
+    /// 接続済み（または呼び出し中）の呼が終わり、エージェントが空いた
+    pub fn release_agent(&mut self, call_id: &CallId, queue: &str, agent: &str, now: Instant) {
+        let Some(state) = self.queues.get_mut(queue) else {
+            return;
+        };
+        state.dispatched.remove(call_id);
+        state.release_agent(call_id, agent, now);
+    }
+
+    /// 空いているエージェントへ先頭の呼を割り当てる。最も長く空いているエージェントから使う
+    pub fn dispatch(&mut self, now: Instant) -> Vec<(CallId, String)> {
+        let mut assigned = Vec::new();
+        for state in self.queues.values_mut() {
+            while !state.waiting.is_empty() {
+                let Some(slot) = state
+                    .agents
+                    .iter_mut()
+                    .filter(|slot| slot.busy_with.is_none() && slot.available_at <= now)
+                    .min_by_key(|slot| slot.available_at)
+                else {
+                    break;
+                };
+                let call = state.waiting.remove(0);
+                slot.busy_with = Some(call.call_id.clone());
+                assigned.push((call.call_id.clone(), slot.uri.clone()));
+                state.dispatched.insert(call.call_id.clone(), call);
+            }
+        }
+        assigned
+    }
+
+    /// 案内間隔を過ぎた待ち呼とその順位
+    pub fn due_positions(&mut self, now: Instant) -> Vec<(CallId, usize)> {
+        let mut due = Vec::new();
+        for state in self.queues.values_mut() {
+            let interval = state.config.position_interval;
+            if interval.is_zero() {
+                continue;
+            }
+            for (index, waiting) in state.waiting.iter_mut().enumerate() {
+                if now.duration_since(waiting.last_announced_at) >= interval {
+                    waiting.last_announced_at = now;
+                    due.push((waiting.call_id.clone(), index + 1));
+                }
+            }
+        }
+        due
+    }
+
+    pub fn position(&self, call_id: &CallId) -> Option<usize> {
+        self.queues.values().find_map(|state| {
+            state
+                .waiting
+                .iter()
+                .position(|waiting| &waiting.call_id == call_id)
+                .map(|index| index + 1)
+        })
+    }
+}
+
+fn position_announcement_text(position: usize) -> String {
+    if position <= 1 {
+        "お待たせしております。まもなくおつなぎします。".to_string()
+    } else {
+        format!(
+            "お待たせしております。ただいま{}番目にお待ちいただいております。",
+            position
+        )
+    }
+}
+
+pub struct CallQueueService {
+    queues: Mutex<CallQueues>,
+    tts: Arc<dyn TtsPort>,
+    callback_notifier: Arc<dyn CallbackRequestNotifier>,
+}
+
+impl CallQueueService {
+    pub fn new<'a>(
+        configs: impl IntoIterator<Item = &'a CallQueueConfig>,
+        tts: Arc<dyn TtsPort>,
+        callback_notifier: Arc<dyn CallbackRequestNotifier>,
+    ) -> Self {
+        Self {
+            queues: Mutex::new(CallQueues::new(
+                configs.into_iter().cloned(),
+                Instant::now(),
+            )),
+            tts,
+            callback_notifier,
+        }
+    }
+
+    pub async fn join(&self, call_id: CallId, queue: &str, priority: i32) {
+        match self
+            .queues
+            .lock()
+            .await
+            .join(call_id.clone(), queue, priority, Instant::now())
+        {
+            Some(position) => info!(
+                "[call_queue] call_id={} joined queue={} priority={} position={}",
+                call_id, queue, priority, position
+            ),
+            None => warn!(
+                "[call_queue] call_id={} requested unknown queue={}",
+                call_id, queue
+            ),
+        }
+    }
+
+    pub async fn leave(&self, call_id: &CallId) {
+        if self.queues.lock().await.leave(call_id, Instant::now()) {
+            info!("[call_queue] call_id={} left queue", call_id);
+        }
+    }
+
+    pub async fn connect_failed(&self, call_id: &CallId, queue: &str, agent: &str) {
+        info!(
+            "[call_queue] call_id={} agent={} did not answer, requeued in {}",
+            call_id, agent, queue
+        );
+        self.queues
+            .lock()
+            .await
+            .connect_failed(call_id, queue, agent, Instant::now());
+    }
+
+    pub async fn release_agent(&self, call_id: &CallId, queue: &str, agent: &str) {
+        debug!(
+            "[call_queue] call_id={} released agent={} queue={}",
+            call_id, agent, queue
+        );
+        self.queues
+            .lock()
+            .await
+            .release_agent(call_id, queue, agent, Instant::now());
+    }
+
+    /// 折り返し依頼を受け付けてキューから外す
+    pub async fn request_callback(
+        &self,
+        call_id: CallId,
+        queue: String,
+        caller_number: Option<String>,
+    ) -> Result<(), NotificationError> {
+        self.leave(&call_id).await;
+        let jst = FixedOffset::east_opt(9 * 3600).expect("valid offset");
+        self.callback_notifier
+            .notify_callback_request(CallbackRequest {
+                call_id,
+                queue,
+                caller_number,
+                requested_at: Utc::now().with_timezone(&jst),
+            })
+            .await
+    }
+
+    /// 1 秒ごとに割り当てと順位案内を行うタスクを起動し、セッション向けの指示を返す
+    pub fn spawn_dispatcher(self: &Arc<Self>) -> mpsc::Receiver<(CallId, QueueDirective)> {
+        let (tx, rx) = mpsc::channel(DIRECTIVE_CHANNEL_CAPACITY);
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut tick = interval(DISPATCH_INTERVAL);
+            tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                tick.tick().await;
+                let now = Instant::now();
+                let (assigned, due) = {
+                    let mut queues = service.queues.lock().await;
+                    (queues.dispatch(now), queues.due_positions(now))
+                };
+                for (call_id, agent) in assigned {
+                    info!(
+                        "[call_queue] dispatching call_id={} to agent={}",
+                        call_id, agent
+                    );
+                    if tx
+                        .send((call_id, QueueDirective::Connect { agent }))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+                for (call_id, position) in due {
+                    let text = position_announcement_text(position);
+                    let audio_path = match service
+                        .tts
+                        .synth_phrase_to_wav(call_id.to_string(), text)
+                        .await
+                    {
+                        Ok(path) => path.to_string_lossy().into_owned(),
+                        Err(err) => {
+                            warn!(
+                                "[call_queue] position announcement synth failed call_id={}: {}",
+                                call_id, err
+                            );
+                            continue;
+                        }
+                    };
+                    if tx
+                        .send((
+                            call_id,
+                            QueueDirective::Position {
+                                position,
+                                audio_path,
+                            },
+                        ))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        });
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue_config(agents: &[&str]) -> CallQueueConfig {
+        CallQueueConfig {
+            name: "support".to_string(),
+            agents: agents.iter().map(|agent| agent.to_string()).collect(),
+            moh_announcement_id: None,
+            position_interval: Duration::from_secs(30),
+            agent_ring_timeout: Duration::from_secs(15),
+            wrap_up: Duration::from_secs(10),
+            voicemail_key: Some('1'),
+            callback_key: Some('2'),
+        }
+    }
+
+    fn call(id: &str) -> CallId {
+        CallId::new(id).unwrap()
+    }
+
+    #[test]
+    fn join_orders_by_priority_then_arrival() {
+        let now = Instant::now();
+        let mut queues = CallQueues::new([queue_config(&[])], now);
+        assert_eq!(queues.join(call("a"), "support", 0, now), Some(1));
+        assert_eq!(queues.join(call("b"), "support", 0, now), Some(2));
+        assert_eq!(queues.join(call("vip"), "support", 5, now), Some(1));
+        assert_eq!(queues.join(call("x"), "unknown", 0, now), None);
+
+        assert_eq!(queues.position(&call("vip")), Some(1));
+        assert_eq!(queues.position(&call("a")), Some(2));
+        assert_eq!(queues.position(&call("b")), Some(3));
+
+        assert!(queues.leave(&call("a"), now));
+        assert!(!queues.leave(&call("a"), now));
+        assert_eq!(queues.position(&call("b")), Some(2));
+    }
+
+    #[test]
+    fn dispatch_assigns_free_agents_and_honours_wrap_up() {
+        let now = Instant::now();
+        let mut queues = CallQueues::new([queue_config(&["sip:101@pbx"])], now);
+        queues.join(call("a"), "support", 0, now);
+        queues.join(call("b"), "support", 0, now);
+
+        assert_eq!(
+            queues.dispatch(now),
+            vec![(call("a"), "sip:101@pbx".to_string())]
+        );
+        // 通話中のエージェントには配らない
+        assert!(queues.dispatch(now).is_empty());
+
+        queues.release_agent(&call("a"), "support", "sip:101@pbx", now);
+        assert!(queues.dispatch(now + Duration::from_secs(5)).is_empty());
+        assert_eq!(
+            queues.dispatch(now + Duration::from_secs(10)),
+            vec![(call("b"), "sip:101@pbx".to_string())]
+        );
+    }
+
+    #[test]
+    fn connect_failure_requeues_call_at_original_position() {
+        let now = Instant::now();
+        let mut queues = CallQueues::new([queue_config(&["sip:101@pbx"])], now);
+        queues.join(call("a"), "support", 0, now);
+        queues.dispatch(now);
+        queues.join(call("b"), "support", 0, now);
+
+        queues.connect_failed(&call("a"), "support", "sip:101@pbx", now);
+        assert_eq!(queues.position(&call("a")), Some(1));
+        assert_eq!(queues.position(&call("b")), Some(2));
+    }
+
+    #[test]
+    fn leaving_before_connect_frees_agent_immediately() {
+        let now = Instant::now();
+        let mut queues = CallQueues::new([queue_config(&["sip:101@pbx"])], now);
+        queues.join(call("a"), "support", 0, now);
+        queues.join(call("b"), "support", 0, now);
+        queues.dispatch(now);
+
+        assert!(queues.leave(&call("a"), now));
+        assert_eq!(
+            queues.dispatch(now),
+            vec![(call("b"), "sip:101@pbx".to_string())]
+        );
+    }
+
+    #[test]
+    fn due_positions_follow_announcement_interval() {
+        let now = Instant::now();
+        let mut queues = CallQueues::new([queue_config(&[])], now);
+        queues.join(call("a"), "support", 0, now);
+        queues.join(call("b"), "support", 0, now + Duration::from_secs(20));
+
+        assert!(queues
+            .due_positions(now + Duration::from_secs(29))
+            .is_empty());
+        assert_eq!(
+            queues.due_positions(now + Duration::from_secs(30)),
+            vec![(call("a"), 1)]
+        );
+        assert_eq!(
+            queues.due_positions(now + Duration::from_secs(50)),
+            vec![(call("b"), 2)]
+        );
+    }
+
+    #[test]
+    fn position_announcement_text_mentions_position() {
+        assert!(position_announcement_text(3).contains("3番目"));
+        assert!(position_announcement_text(1).contains("まもなく"));
+    }
+}
@@ -0,0 +1,262 @@
+//! 通話中セッションの運用操作（一覧・強制切断・転送・アナウンス再生・読み上げ・録音の一時停止・監視）。
+//!
+//! 操作はすべて `SessionRegistry` からセッションの制御チャネルを引き、`SessionControlIn`
+//! として送る。セッション側が状態を見て受け付け可否を返す。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{info, warn};
+use tokio::sync::oneshot;
+use tokio::time::timeout;
+
+use crate::protocol::session::types::CallId;
+use crate::protocol::session::{SessionControlIn, SessionRegistry};
+use crate::shared::ports::ai::TtsPort;
+use crate::shared::ports::live_call::{
+    LiveCallCommand, LiveCallError, LiveCallFuture, LiveCallPort, LiveCallStatus,
+    SupervisorChannel, SupervisorRequest,
+};
+
+/// 状態照会の応答待ち（詰まったセッションで一覧全体を止めない）
+const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// 操作の応答待ち（アナウンスの解決・音声の読み込みを含む）
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone)]
+pub struct LiveCallService {
+    registry: SessionRegistry,
+    tts: Arc<dyn TtsPort>,
+}
+
+impl LiveCallService {
+    pub fn new(registry: SessionRegistry, tts: Arc<dyn TtsPort>) -> Self {
+        Self { registry, tts }
+    }
+
+    async fn describe(&self, call_id: CallId) -> Option<LiveCallStatus> {
+        let handle = self.registry.get(&call_id).await?;
+        let (reply, rx) = oneshot::channel();
+        handle
+            .control_tx
+            .send(SessionControlIn::ApiDescribe { reply })
+            .await
+            .ok()?;
+        timeout(DESCRIBE_TIMEOUT, rx).await.ok()?.ok()
+    }
+
+    async fn send<T>(
+        &self,
+        call_id: &str,
+        build: impl FnOnce(oneshot::Sender<Result<T, LiveCallError>>) -> SessionControlIn,
+    ) -> Result<T, LiveCallError> {
+        let not_found = || LiveCallError::NotFound(call_id.to_string());
+        let id = CallId::new(call_id).map_err(|_| not_found())?;
+        let handle = self.registry.get(&id).await.ok_or_else(not_found)?;
+        let (reply, rx) = oneshot::channel();
+        handle
+            .control_tx
+            .send(build(reply))
+            .await
+            .map_err(|_| not_found())?;
+        match timeout(COMMAND_TIMEOUT, rx).await {
+            Ok(Ok(result)) => result,
+            // 応答前にセッションが終了した
+            Ok(Err(_)) => Err(not_found()),
+            Err(_) => Err(LiveCallError::Failed("session did not respond".to_string())),
+        }
+    }
+
+    async fn hangup(&self, call_id: &str) -> Result<(), LiveCallError> {
+        let not_found = || LiveCallError::NotFound(call_id.to_string());
+        let id = CallId::new(call_id).map_err(|_| not_found())?;
+        let handle = self.registry.get(&id).await.ok_or_else(not_found)?;
+        handle
+            .control_tx
+            .send(SessionControlIn::AppHangup)
+            .await
+            .map_err(|_| not_found())
+    }
+
+    async fn say(&self, call_id: &str, text: String) -> Result<(), LiveCallError> {
+        if text.trim().is_empty() {
+            return Err(LiveCallError::Rejected("text is empty".to_string()));
+        }
+        // 合成前に通話の存在だけ確認しておく（終了済みの通話に TTS を回さない）
+        let id = CallId::new(call_id).map_err(|_| LiveCallError::NotFound(call_id.to_string()))?;
+        if self.registry.get(&id).await.is_none() {
+            return Err(LiveCallError::NotFound(call_id.to_string()));
+        }
+        let wav = self
+            .tts
+            .synth_to_wav(call_id.to_string(), text, None)
+            .await
+            .map_err(|e| LiveCallError::Failed(e.to_string()))?;
+        let path = wav.to_string_lossy().into_owned();
+        let result = self
+            .send(call_id, |reply| SessionControlIn::ApiPlayAudio {
+                path: path.clone(),
+                reply,
+            })
+            .await;
+        // セッションは応答前にフレームを読み込み済みなので、ここで消してよい
+        if let Err(e) = tokio::fs::remove_file(&wav).await {
+            warn!(
+                "[live_call {}] failed to remove synthesized audio {}: {}",
+                call_id, path, e
+            );
+        }
+        result
+    }
+}
+
+impl LiveCallPort for LiveCallService {
+    fn list_calls(&self) -> LiveCallFuture<Vec<LiveCallStatus>> {
+        let service = self.clone();
+        Box::pin(async move {
+            let mut calls = Vec::new();
+            for call_id in service.registry.list().await {
+                if let Some(status) = service.describe(call_id).await {
+                    calls.push(status);
+                }
+            }
+            calls.sort_by_key(|call| std::cmp::Reverse(call.duration_sec));
+            Ok(calls)
+        })
+    }
+
+    fn send_command(&self, call_id: String, command: LiveCallCommand) -> LiveCallFuture<()> {
+        let service = self.clone();
+        Box::pin(async move {
+            info!("[live_call {}] operator command {:?}", call_id, command);
+            match command {
+                LiveCallCommand::Hangup => service.hangup(&call_id).await,
+                LiveCallCommand::Transfer { target } => {
+                    service
+                        .send(&call_id, |reply| SessionControlIn::ApiTransfer {
+                            target,
+                            reply,
+                        })
+                        .await
+                }
+                LiveCallCommand::PlayAnnouncement { announcement_id } => {
+                    service
+                        .send(&call_id, |reply| SessionControlIn::ApiPlayAnnouncement {
+                            announcement_id,
+                            reply,
+                        })
+                        .await
+                }
+                LiveCallCommand::Say { text } => service.say(&call_id, text).await,
+                LiveCallCommand::PauseRecording | LiveCallCommand::ResumeRecording => {
+                    let paused = matches!(command, LiveCallCommand::PauseRecording);
+                    service
+                        .send(&call_id, |reply| SessionControlIn::ApiRecordingPause {
+                            paused,
+                            reply,
+                        })
+                        .await
+                }
+            }
+        })
+    }
+
+    fn attach_supervisor(
+        &self,
+        call_id: String,
+        request: SupervisorRequest,
+    ) -> LiveCallFuture<SupervisorChannel> {
+        let service = self.clone();
+        Box::pin(async move {
+            info!(
+                "[live_call {}] supervisor {} requested",
+                call_id,
+                request.mode.as_str()
+            );
+            service
+                .send(&call_id, |reply| SessionControlIn::ApiSupervise {
+                    request,
+                    reply,
+                })
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    use tokio::sync::mpsc;
+
+    use crate::protocol::session::SessionHandle;
+    use crate::shared::ports::ai::{AiFuture, TtsError};
+
+    struct NoTts;
+
+    impl TtsPort for NoTts {
+        fn synth_to_wav(
+            &self,
+            _call_id: String,
+            _text: String,
+            _path: Option<String>,
+        ) -> AiFuture<Result<PathBuf, TtsError>> {
+            Box::pin(async move { Err(TtsError::SynthesisFailed("unused".to_string())) })
+        }
+    }
+
+    async fn register(
+        registry: &SessionRegistry,
+        call_id: &str,
+    ) -> mpsc::Receiver<SessionControlIn> {
+        let (control_tx, control_rx) = mpsc::channel(4);
+        let (media_tx, _media_rx) = mpsc::channel(4);
+        registry
+            .insert(
+                CallId::new(call_id).unwrap(),
+                SessionHandle {
+                    control_tx,
+                    media_tx,
+                },
+            )
+            .await;
+        control_rx
+    }
+
+    #[tokio::test]
+    async fn command_for_unknown_call_is_not_found() {
+        let service = LiveCallService::new(SessionRegistry::new(), Arc::new(NoTts));
+
+        let err = service
+            .send_command("missing".to_string(), LiveCallCommand::Hangup)
+            .await
+            .expect_err("unknown call");
+
+        assert!(matches!(err, LiveCallError::NotFound(id) if id == "missing"));
+    }
+
+    #[tokio::test]
+    async fn transfer_returns_session_reply() {
+        let registry = SessionRegistry::new();
+        let mut control_rx = register(&registry, "call-1").await;
+        tokio::spawn(async move {
+            if let Some(SessionControlIn::ApiTransfer { target, reply }) = control_rx.recv().await {
+                let _ = reply.send(Err(LiveCallError::Rejected(target)));
+            }
+        });
+        let service = LiveCallService::new(registry, Arc::new(NoTts));
+
+        let err = service
+            .send_command(
+                "call-1".to_string(),
+                LiveCallCommand::Transfer {
+                    target: "sales".to_string(),
+                },
+            )
+            .await
+            .expect_err("session rejected");
+
+        assert!(matches!(err, LiveCallError::Rejected(target) if target == "sales"));
+    }
+}
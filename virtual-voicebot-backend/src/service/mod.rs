@@ -1,8 +1,14 @@
 pub mod ai;
 pub mod call_control;
+pub mod call_events;
+pub mod call_queue;
+pub mod live_call;
 pub mod rag;
 pub mod recording;
 pub mod routing;
+pub mod runtime_config;
+pub mod voicemail;
+pub mod webhook;
 
 pub use ai::DefaultAiPort;
 pub use call_control::{
@@ -0,0 +1,465 @@
+//! 録音の後処理（FLAC/Opus への変換・暗号化・鍵の入れ替え）と、保存形式を意識しない読み出し。
+//!
+//! 通話ディレクトリの `*.wav` を設定の形式に変換し、暗号化が有効なら `<name>.enc` にする。
+//! 配信・アップロードは `find` / `RecordingReader` を通して、変換・暗号化の有無に関係なく
+//! 平文の音声を読む。ブロッキング I/O なので呼び出し側で `spawn_blocking` すること。
+
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use super::encryption::{self, EncryptedFile, RecordingCryptoError, ENCRYPTED_SUFFIX};
+use crate::shared::config::{RecordingArchiveFormat, RecordingKey, RecordingStorageConfig};
+use crate::shared::media::flac::encode_wav_to_flac;
+
+/// 通話の録音本体のファイル名（拡張子なし）
+pub const MIXED_STEM: &str = "mixed";
+/// 配信・アップロードで扱う拡張子（変換前の WAV を先に探す）
+const AUDIO_EXTENSIONS: &[&str] = &["wav", "flac", "opus"];
+
+#[derive(Debug, Error)]
+pub enum RecordingArchiveError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Crypto(#[from] RecordingCryptoError),
+    #[error("recording encode failed: {0}")]
+    Encode(String),
+}
+
+/// 保存済みの録音ファイル
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredRecording {
+    pub path: PathBuf,
+    /// 復号後のファイル名（`mixed.flac.enc` なら `mixed.flac`）
+    pub file_name: String,
+    pub encrypted: bool,
+}
+
+impl StoredRecording {
+    pub fn content_type(&self) -> &'static str {
+        content_type_for(&self.file_name)
+    }
+}
+
+pub fn content_type_for(file_name: &str) -> &'static str {
+    match Path::new(file_name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+    {
+        Some("wav") => "audio/wav",
+        Some("flac") => "audio/flac",
+        Some("opus") | Some("ogg") => "audio/ogg",
+        _ => "application/octet-stream",
+    }
+}
+
+/// `<dir>/<file_name>` か、その暗号化版
+pub fn find(dir: &Path, file_name: &str) -> Option<StoredRecording> {
+    let plain = dir.join(file_name);
+    if plain.is_file() {
+        return Some(StoredRecording {
+            path: plain,
+            file_name: file_name.to_string(),
+            encrypted: false,
+        });
+    }
+    let encrypted = dir.join(format!("{file_name}{ENCRYPTED_SUFFIX}"));
+    encrypted.is_file().then(|| StoredRecording {
+        path: encrypted,
+        file_name: file_name.to_string(),
+        encrypted: true,
+    })
+}
+
+/// 通話の録音本体（`mixed.*`）を保存形式に関係なく探す
+pub fn find_mixed(dir: &Path) -> Option<StoredRecording> {
+    AUDIO_EXTENSIONS
+        .iter()
+        .find_map(|ext| find(dir, &format!("{MIXED_STEM}.{ext}")))
+}
+
+/// 平文・暗号化のどちらでも同じように読む
+pub enum RecordingReader {
+    Plain { file: fs::File, len: u64 },
+    Encrypted(Box<EncryptedFile>),
+}
+
+impl RecordingReader {
+    pub fn open(
+        recording: &StoredRecording,
+        keys: &[RecordingKey],
+    ) -> Result<Self, RecordingArchiveError> {
+        if recording.encrypted {
+            return Ok(Self::Encrypted(Box::new(EncryptedFile::open(
+                &recording.path,
+                keys,
+            )?)));
+        }
+        let file = fs::File::open(&recording.path)?;
+        let len = file.metadata()?.len();
+        Ok(Self::Plain { file, len })
+    }
+
+    pub fn len(&self) -> u64 {
+        match self {
+            Self::Plain { len, .. } => *len,
+            Self::Encrypted(file) => file.plain_len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn read_range(&mut self, start: u64, len: u64) -> Result<Vec<u8>, RecordingArchiveError> {
+        match self {
+            Self::Plain { file, len: total } => {
+                let len = len.min(total.saturating_sub(start));
+                let mut buf = vec![0u8; len as usize];
+                file.seek(SeekFrom::Start(start))?;
+                file.read_exact(&mut buf)?;
+                Ok(buf)
+            }
+            Self::Encrypted(file) => Ok(file.read_range(start, len)?),
+        }
+    }
+
+    pub fn read_all(&mut self) -> Result<Vec<u8>, RecordingArchiveError> {
+        self.read_range(0, self.len())
+    }
+}
+
+/// 後処理の結果
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ArchiveOutcome {
+    pub converted: usize,
+    pub reencrypted: usize,
+}
+
+/// 未変換の WAV が残っているか、暗号化済みファイルの鍵が現行の鍵と違う
+pub fn needs_archive(dir: &Path, cfg: &RecordingStorageConfig) -> bool {
+    if !cfg.archive_enabled() {
+        return false;
+    }
+    let active = cfg.active_key().map(|key| key.id.as_str());
+    list_files(dir).iter().any(|path| match extension(path) {
+        Some("wav") => true,
+        Some("enc") => active
+            .is_some_and(|active| encryption::key_id_of(path).is_ok_and(|key_id| key_id != active)),
+        _ => false,
+    })
+}
+
+/// 通話ディレクトリの録音を設定の形式・鍵にそろえる
+pub fn archive_dir(
+    dir: &Path,
+    cfg: &RecordingStorageConfig,
+) -> Result<ArchiveOutcome, RecordingArchiveError> {
+    let mut outcome = ArchiveOutcome::default();
+    let mut archived_mixed = None;
+    for path in list_files(dir) {
+        match extension(&path) {
+            Some("wav") => {
+                let output = convert(&path, cfg)?;
+                let output = match cfg.active_key() {
+                    Some(key) => encrypt_file(&output, key)?,
+                    None => output,
+                };
+                // WAV のまま暗号化した場合は encrypt_file が元の WAV を消している
+                if output != path && path.exists() {
+                    fs::remove_file(&path)?;
+                }
+                if file_stem(&path) == Some(MIXED_STEM) {
+                    archived_mixed = Some(output);
+                }
+                outcome.converted += 1;
+            }
+            Some("enc") => {
+                let Some(active) = cfg.active_key() else {
+                    continue;
+                };
+                if encryption::key_id_of(&path)? == active.id {
+                    continue;
+                }
+                let plain = EncryptedFile::open(&path, &cfg.encryption_keys)?.read_all()?;
+                write_atomically(&path, &encryption::encrypt(&plain, active)?)?;
+                outcome.reencrypted += 1;
+            }
+            _ => {}
+        }
+    }
+    if let Some(mixed) = archived_mixed {
+        update_meta(dir, &mixed, cfg)?;
+    }
+    Ok(outcome)
+}
+
+/// 設定の形式に変換したファイルのパス（WAV のままなら元のパス）
+fn convert(wav: &Path, cfg: &RecordingStorageConfig) -> Result<PathBuf, RecordingArchiveError> {
+    match cfg.archive_format {
+        RecordingArchiveFormat::Wav => Ok(wav.to_path_buf()),
+        RecordingArchiveFormat::Flac => to_flac(wav),
+        RecordingArchiveFormat::Opus => {
+            let output = wav.with_extension("opus");
+            match to_opus(wav, &output, cfg) {
+                Ok(()) => Ok(output),
+                Err(err) => {
+                    log::warn!(
+                        "[recording] opus encode failed for {} ({}), falling back to flac",
+                        wav.display(),
+                        err
+                    );
+                    let _ = fs::remove_file(&output);
+                    to_flac(wav)
+                }
+            }
+        }
+    }
+}
+
+fn to_flac(wav: &Path) -> Result<PathBuf, RecordingArchiveError> {
+    let output = wav.with_extension("flac");
+    encode_wav_to_flac(wav, &output).map_err(|e| {
+        let _ = fs::remove_file(&output);
+        RecordingArchiveError::Encode(e.to_string())
+    })?;
+    Ok(output)
+}
+
+fn to_opus(
+    wav: &Path,
+    output: &Path,
+    cfg: &RecordingStorageConfig,
+) -> Result<(), RecordingArchiveError> {
+    let result = Command::new(&cfg.ffmpeg_path)
+        .args(["-nostdin", "-y", "-loglevel", "error", "-i"])
+        .arg(wav)
+        .args(["-c:a", "libopus", "-application", "voip", "-b:a"])
+        .arg(format!("{}k", cfg.opus_bitrate_kbps))
+        .arg(output)
+        .output()
+        .map_err(|e| RecordingArchiveError::Encode(e.to_string()))?;
+    if !result.status.success() {
+        return Err(RecordingArchiveError::Encode(
+            String::from_utf8_lossy(&result.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 暗号化して `<path>.enc` にし、平文を消す
+fn encrypt_file(path: &Path, key: &RecordingKey) -> Result<PathBuf, RecordingArchiveError> {
+    let plain = fs::read(path)?;
+    let mut output = path.as_os_str().to_owned();
+    output.push(ENCRYPTED_SUFFIX);
+    let output = PathBuf::from(output);
+    write_atomically(&output, &encryption::encrypt(&plain, key)?)?;
+    fs::remove_file(path)?;
+    Ok(output)
+}
+
+fn write_atomically(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)
+}
+
+/// meta.json の `files.mixed` を変換後の名前にし、保存形式を書き足す
+fn update_meta(
+    dir: &Path,
+    mixed: &Path,
+    cfg: &RecordingStorageConfig,
+) -> Result<(), RecordingArchiveError> {
+    let meta_path = dir.join("meta.json");
+    let Ok(raw) = fs::read(&meta_path) else {
+        return Ok(());
+    };
+    let Ok(mut meta) = serde_json::from_slice::<Value>(&raw) else {
+        log::warn!("[recording] unreadable meta.json in {}", dir.display());
+        return Ok(());
+    };
+    let encrypted = extension(mixed) == Some("enc");
+    let served_name = mixed
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.trim_end_matches(ENCRYPTED_SUFFIX).to_string());
+    let format = served_name
+        .as_deref()
+        .and_then(|name| Path::new(name).extension())
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("wav")
+        .to_string();
+    if let Some(object) = meta.as_object_mut() {
+        if let (Some(files), Some(name)) = (
+            object.get_mut("files").and_then(Value::as_object_mut),
+            served_name,
+        ) {
+            files.insert("mixed".to_string(), Value::String(name));
+        }
+        object.insert(
+            "archive".to_string(),
+            json!({
+                "format": format,
+                "encrypted": encrypted,
+                "keyId": encrypted.then(|| cfg.active_key().map(|key| key.id.clone())).flatten(),
+                "archivedAt": chrono::Utc::now().to_rfc3339(),
+            }),
+        );
+    }
+    let json = serde_json::to_vec_pretty(&meta).map_err(std::io::Error::other)?;
+    write_atomically(&meta_path, &json)?;
+    Ok(())
+}
+
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    files
+}
+
+fn extension(path: &Path) -> Option<&str> {
+    path.extension().and_then(|ext| ext.to_str())
+}
+
+fn file_stem(path: &Path) -> Option<&str> {
+    path.file_stem().and_then(|stem| stem.to_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use hound::{SampleFormat, WavSpec, WavWriter};
+
+    fn storage_config(
+        format: RecordingArchiveFormat,
+        keys: Vec<RecordingKey>,
+    ) -> RecordingStorageConfig {
+        RecordingStorageConfig {
+            archive_format: format,
+            archive_after: Duration::from_secs(0),
+            opus_bitrate_kbps: 24,
+            // 存在しないコマンドにして FLAC へのフォールバックを確かめる
+            ffmpeg_path: "/nonexistent/ffmpeg".to_string(),
+            encryption_keys: keys,
+            retention: None,
+            retention_rules: Vec::new(),
+            janitor_interval: Duration::from_secs(3600),
+            tts_temp_max_age: Duration::from_secs(3600),
+        }
+    }
+
+    fn key(id: &str, byte: u8) -> RecordingKey {
+        RecordingKey {
+            id: id.to_string(),
+            key: [byte; 32],
+        }
+    }
+
+    fn write_wav(path: &Path) {
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(path, spec).unwrap();
+        for n in 0..8000 {
+            writer.write_sample(((n % 100) * 50) as i16).unwrap();
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn archives_to_encrypted_flac_and_rotates_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        write_wav(&dir.path().join("mixed.wav"));
+        std::fs::write(
+            dir.path().join("meta.json"),
+            r#"{"callId":"c1","files":{"mixed":"mixed.wav"}}"#,
+        )
+        .unwrap();
+        let cfg = storage_config(RecordingArchiveFormat::Opus, vec![key("old", 1)]);
+        assert!(needs_archive(dir.path(), &cfg));
+
+        let outcome = archive_dir(dir.path(), &cfg).unwrap();
+
+        assert_eq!(outcome.converted, 1);
+        assert!(!dir.path().join("mixed.wav").exists());
+        let stored = find_mixed(dir.path()).expect("archived recording");
+        assert_eq!(stored.file_name, "mixed.flac");
+        assert!(stored.encrypted);
+        assert_eq!(stored.content_type(), "audio/flac");
+        let meta: Value =
+            serde_json::from_slice(&std::fs::read(dir.path().join("meta.json")).unwrap()).unwrap();
+        assert_eq!(meta["files"]["mixed"], "mixed.flac");
+        assert_eq!(meta["archive"]["keyId"], "old");
+        let plain = RecordingReader::open(&stored, &cfg.encryption_keys)
+            .unwrap()
+            .read_all()
+            .unwrap();
+        assert_eq!(&plain[..4], b"fLaC");
+        assert!(!needs_archive(dir.path(), &cfg));
+
+        let rotated = storage_config(
+            RecordingArchiveFormat::Opus,
+            vec![key("new", 2), key("old", 1)],
+        );
+        assert!(needs_archive(dir.path(), &rotated));
+        let outcome = archive_dir(dir.path(), &rotated).unwrap();
+        assert_eq!(outcome.reencrypted, 1);
+        assert_eq!(encryption::key_id_of(&stored.path).unwrap(), "new");
+        let reread = RecordingReader::open(&stored, &[key("new", 2)])
+            .unwrap()
+            .read_all()
+            .unwrap();
+        assert_eq!(reread, plain);
+    }
+
+    #[test]
+    fn plain_reader_serves_ranges_and_wav_config_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        write_wav(&dir.path().join("mixed.wav"));
+        let cfg = storage_config(RecordingArchiveFormat::Wav, Vec::new());
+        assert!(!needs_archive(dir.path(), &cfg));
+
+        let stored = find_mixed(dir.path()).unwrap();
+        assert!(!stored.encrypted);
+        let mut reader = RecordingReader::open(&stored, &[]).unwrap();
+        assert_eq!(reader.read_range(0, 4).unwrap(), b"RIFF");
+        assert_eq!(reader.read_range(reader.len() - 2, 10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn encrypts_wav_in_place_when_format_is_wav() {
+        let dir = tempfile::tempdir().unwrap();
+        write_wav(&dir.path().join("mixed.wav"));
+        let original = std::fs::read(dir.path().join("mixed.wav")).unwrap();
+        let cfg = storage_config(RecordingArchiveFormat::Wav, vec![key("k1", 3)]);
+
+        archive_dir(dir.path(), &cfg).unwrap();
+
+        let stored = find_mixed(dir.path()).unwrap();
+        assert_eq!(stored.file_name, "mixed.wav");
+        assert!(stored.encrypted);
+        let mut reader = RecordingReader::open(&stored, &cfg.encryption_keys).unwrap();
+        assert_eq!(reader.len(), original.len() as u64);
+        assert_eq!(reader.read_range(8, 4).unwrap(), &original[8..12]);
+    }
+}
@@ -0,0 +1,321 @@
+//! 録音ファイルの保存時暗号化（AES-256-GCM、チャンク単位）。
+//!
+//! チャンクごとに認証タグを付けるので、HTTP の Range 要求でも必要なチャンクだけ復号できる。
+//! ヘッダに鍵 ID を書いておき、鍵を入れ替えた後も古い鍵で復号できるようにする。
+//!
+//! 形式: MAGIC(6) | 鍵 ID 長(1) | 鍵 ID | nonce 接頭辞(8) | チャンク長(4, BE) | チャンク...
+//! 各チャンクは暗号文 + タグ(16)。nonce は接頭辞 + チャンク番号(4, BE)、AAD はヘッダ全体 +
+//! 最終チャンクかどうか（途中で切り詰められたファイルを復号エラーにする）。
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use rand::RngCore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
+use thiserror::Error;
+
+use crate::shared::config::RecordingKey;
+
+/// 暗号化したファイルに付ける拡張子（`mixed.flac` → `mixed.flac.enc`）
+pub const ENCRYPTED_SUFFIX: &str = ".enc";
+
+const MAGIC: &[u8; 6] = b"VVREC1";
+const CHUNK_SIZE: u32 = 64 * 1024;
+const TAG_LEN: u64 = 16;
+const NONCE_PREFIX_LEN: usize = 8;
+
+#[derive(Debug, Error)]
+pub enum RecordingCryptoError {
+    #[error("not an encrypted recording")]
+    InvalidFormat,
+    #[error("unknown recording key: {0}")]
+    UnknownKey(String),
+    #[error("recording decryption failed")]
+    Decrypt,
+    #[error("recording encryption failed")]
+    Encrypt,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+struct Header {
+    bytes: Vec<u8>,
+    key_id: String,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    chunk_size: u32,
+}
+
+impl Header {
+    fn new(key_id: &str) -> Self {
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce_prefix);
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 1 + key_id.len() + 12);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(key_id.len() as u8);
+        bytes.extend_from_slice(key_id.as_bytes());
+        bytes.extend_from_slice(&nonce_prefix);
+        bytes.extend_from_slice(&CHUNK_SIZE.to_be_bytes());
+        Self {
+            bytes,
+            key_id: key_id.to_string(),
+            nonce_prefix,
+            chunk_size: CHUNK_SIZE,
+        }
+    }
+
+    fn parse(data: &[u8]) -> Result<Self, RecordingCryptoError> {
+        let rest = data
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or(RecordingCryptoError::InvalidFormat)?;
+        let (&id_len, rest) = rest
+            .split_first()
+            .ok_or(RecordingCryptoError::InvalidFormat)?;
+        let id_len = id_len as usize;
+        if rest.len() < id_len + NONCE_PREFIX_LEN + 4 {
+            return Err(RecordingCryptoError::InvalidFormat);
+        }
+        let key_id = std::str::from_utf8(&rest[..id_len])
+            .map_err(|_| RecordingCryptoError::InvalidFormat)?
+            .to_string();
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+        nonce_prefix.copy_from_slice(&rest[id_len..id_len + NONCE_PREFIX_LEN]);
+        let size_at = id_len + NONCE_PREFIX_LEN;
+        let chunk_size = u32::from_be_bytes(
+            rest[size_at..size_at + 4]
+                .try_into()
+                .map_err(|_| RecordingCryptoError::InvalidFormat)?,
+        );
+        if chunk_size == 0 {
+            return Err(RecordingCryptoError::InvalidFormat);
+        }
+        let header_len = MAGIC.len() + 1 + size_at + 4;
+        Ok(Self {
+            bytes: data[..header_len].to_vec(),
+            key_id,
+            nonce_prefix,
+            chunk_size,
+        })
+    }
+
+    fn nonce(&self, index: u32) -> Nonce {
+        let mut nonce = [0u8; 12];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[NONCE_PREFIX_LEN..].copy_from_slice(&index.to_be_bytes());
+        Nonce::assume_unique_for_key(nonce)
+    }
+
+    fn aad(&self, last: bool) -> Vec<u8> {
+        let mut aad = self.bytes.clone();
+        aad.push(last as u8);
+        aad
+    }
+
+    fn sealed_chunk_len(&self) -> u64 {
+        self.chunk_size as u64 + TAG_LEN
+    }
+}
+
+fn aead_key(key: &RecordingKey) -> Result<LessSafeKey, RecordingCryptoError> {
+    UnboundKey::new(&AES_256_GCM, &key.key)
+        .map(LessSafeKey::new)
+        .map_err(|_| RecordingCryptoError::Encrypt)
+}
+
+/// 平文全体を暗号化する
+pub fn encrypt(plain: &[u8], key: &RecordingKey) -> Result<Vec<u8>, RecordingCryptoError> {
+    if key.id.is_empty() || key.id.len() > u8::MAX as usize {
+        return Err(RecordingCryptoError::Encrypt);
+    }
+    let header = Header::new(&key.id);
+    let aead = aead_key(key)?;
+    let chunk_size = header.chunk_size as usize;
+    let chunk_count = plain.len().div_ceil(chunk_size).max(1);
+    if chunk_count > u32::MAX as usize {
+        return Err(RecordingCryptoError::Encrypt);
+    }
+    let mut out = Vec::with_capacity(header.bytes.len() + plain.len() + chunk_count * 16);
+    out.extend_from_slice(&header.bytes);
+    for index in 0..chunk_count {
+        let start = index * chunk_size;
+        let end = (start + chunk_size).min(plain.len());
+        let mut chunk = plain[start..end].to_vec();
+        aead.seal_in_place_append_tag(
+            header.nonce(index as u32),
+            Aad::from(header.aad(index + 1 == chunk_count)),
+            &mut chunk,
+        )
+        .map_err(|_| RecordingCryptoError::Encrypt)?;
+        out.extend_from_slice(&chunk);
+    }
+    Ok(out)
+}
+
+/// 暗号化したファイルの鍵 ID（再暗号化が必要かの判定用）
+pub fn key_id_of(path: &Path) -> Result<String, RecordingCryptoError> {
+    let mut head = Vec::new();
+    File::open(path)?
+        .take((MAGIC.len() + 1 + u8::MAX as usize + NONCE_PREFIX_LEN + 4) as u64)
+        .read_to_end(&mut head)?;
+    Ok(Header::parse(&head)?.key_id)
+}
+
+/// 暗号化した録音ファイル。Range 読み出しのために必要なチャンクだけ復号する
+pub struct EncryptedFile {
+    file: File,
+    header: Header,
+    aead: LessSafeKey,
+    chunk_count: u64,
+    plain_len: u64,
+}
+
+impl EncryptedFile {
+    pub fn open(path: &Path, keys: &[RecordingKey]) -> Result<Self, RecordingCryptoError> {
+        let mut file = File::open(path)?;
+        let total_len = file.metadata()?.len();
+        let mut head = Vec::new();
+        (&mut file)
+            .take((MAGIC.len() + 1 + u8::MAX as usize + NONCE_PREFIX_LEN + 4) as u64)
+            .read_to_end(&mut head)?;
+        let header = Header::parse(&head)?;
+        let key = keys
+            .iter()
+            .find(|key| key.id == header.key_id)
+            .ok_or_else(|| RecordingCryptoError::UnknownKey(header.key_id.clone()))?;
+        let aead = aead_key(key)?;
+        let body_len = total_len.saturating_sub(header.bytes.len() as u64);
+        let chunk_count = body_len.div_ceil(header.sealed_chunk_len());
+        let last_len = body_len - (chunk_count.max(1) - 1) * header.sealed_chunk_len();
+        if chunk_count == 0 || last_len < TAG_LEN {
+            return Err(RecordingCryptoError::InvalidFormat);
+        }
+        let plain_len = body_len - chunk_count * TAG_LEN;
+        Ok(Self {
+            file,
+            header,
+            aead,
+            chunk_count,
+            plain_len,
+        })
+    }
+
+    pub fn plain_len(&self) -> u64 {
+        self.plain_len
+    }
+
+    /// 平文の `start..start + len` を返す（範囲外は切り詰める）
+    pub fn read_range(&mut self, start: u64, len: u64) -> Result<Vec<u8>, RecordingCryptoError> {
+        let end = start.saturating_add(len).min(self.plain_len);
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        let chunk_size = self.header.chunk_size as u64;
+        let mut out = Vec::with_capacity((end - start) as usize);
+        for index in start / chunk_size..end.div_ceil(chunk_size) {
+            let chunk = self.read_chunk(index)?;
+            let chunk_start = index * chunk_size;
+            let from = start.saturating_sub(chunk_start) as usize;
+            let to = ((end - chunk_start) as usize).min(chunk.len());
+            out.extend_from_slice(&chunk[from..to]);
+        }
+        Ok(out)
+    }
+
+    pub fn read_all(&mut self) -> Result<Vec<u8>, RecordingCryptoError> {
+        self.read_range(0, self.plain_len)
+    }
+
+    fn read_chunk(&mut self, index: u64) -> Result<Vec<u8>, RecordingCryptoError> {
+        let sealed_len = self.header.sealed_chunk_len();
+        let offset = self.header.bytes.len() as u64 + index * sealed_len;
+        let last = index + 1 == self.chunk_count;
+        let len = if last {
+            self.plain_len - index * self.header.chunk_size as u64 + TAG_LEN
+        } else {
+            sealed_len
+        };
+        let mut buf = vec![0u8; len as usize];
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(&mut buf)?;
+        let plain_len = self
+            .aead
+            .open_in_place(
+                self.header.nonce(index as u32),
+                Aad::from(self.header.aad(last)),
+                &mut buf,
+            )
+            .map_err(|_| RecordingCryptoError::Decrypt)?
+            .len();
+        buf.truncate(plain_len);
+        Ok(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(id: &str, byte: u8) -> RecordingKey {
+        RecordingKey {
+            id: id.to_string(),
+            key: [byte; 32],
+        }
+    }
+
+    #[test]
+    fn range_reads_cross_chunk_boundaries() {
+        let plain: Vec<u8> = (0..(CHUNK_SIZE as usize * 2 + 1000))
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mixed.wav.enc");
+        std::fs::write(&path, encrypt(&plain, &key("k1", 7)).unwrap()).unwrap();
+
+        let keys = [key("k2", 9), key("k1", 7)];
+        let mut file = EncryptedFile::open(&path, &keys).unwrap();
+        assert_eq!(file.plain_len(), plain.len() as u64);
+        let start = CHUNK_SIZE as u64 - 10;
+        assert_eq!(
+            file.read_range(start, CHUNK_SIZE as u64 + 20).unwrap(),
+            &plain[start as usize..start as usize + CHUNK_SIZE as usize + 20]
+        );
+        assert_eq!(file.read_all().unwrap(), plain);
+        assert_eq!(key_id_of(&path).unwrap(), "k1");
+    }
+
+    #[test]
+    fn wrong_key_and_truncation_are_rejected() {
+        let plain = vec![42u8; CHUNK_SIZE as usize + 5];
+        let sealed = encrypt(&plain, &key("k1", 7)).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.enc");
+
+        std::fs::write(&path, &sealed).unwrap();
+        assert!(matches!(
+            EncryptedFile::open(&path, &[key("k2", 7)]),
+            Err(RecordingCryptoError::UnknownKey(id)) if id == "k1"
+        ));
+        let mut wrong = EncryptedFile::open(&path, &[key("k1", 8)]).unwrap();
+        assert!(matches!(
+            wrong.read_all(),
+            Err(RecordingCryptoError::Decrypt)
+        ));
+
+        // 最終チャンクを落とすと、残った最初のチャンクが「最終」として検証されて失敗する
+        let first_chunk_end = sealed.len() - (5 + TAG_LEN as usize);
+        std::fs::write(&path, &sealed[..first_chunk_end]).unwrap();
+        let mut truncated = EncryptedFile::open(&path, &[key("k1", 7)]).unwrap();
+        assert!(truncated.read_all().is_err());
+    }
+
+    #[test]
+    fn empty_plaintext_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.enc");
+        std::fs::write(&path, encrypt(&[], &key("k", 1)).unwrap()).unwrap();
+
+        let mut file = EncryptedFile::open(&path, &[key("k", 1)]).unwrap();
+        assert_eq!(file.plain_len(), 0);
+        assert!(file.read_all().unwrap().is_empty());
+    }
+}
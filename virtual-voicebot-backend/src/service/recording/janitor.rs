@@ -0,0 +1,336 @@
+//! 録音ディレクトリと TTS 一時ファイルの定期清掃。
+//!
+//! 1 周ごとに通話ディレクトリを見て、保存期間を過ぎたものを消し、通話終了から
+//! `archive_after` 経ったものを後処理（`archive::archive_dir`）する。
+//! 保存期間は meta.json の `callerCategory` / `actionCode`（通話ログ保存時に書く）で決める。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde_json::Value;
+
+use super::archive;
+use crate::shared::config::{self, RecordingStorageConfig};
+
+/// TTS が書き出す一時ファイルの置き場所と接頭辞
+const TTS_TEMP_DIR: &str = "/tmp";
+const TTS_TEMP_PREFIXES: &[&str] = &["tts_output_", "tts_stream_output_"];
+
+/// 1 周分の結果
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct JanitorReport {
+    pub expired: usize,
+    pub archived: usize,
+    pub reencrypted: usize,
+    pub tts_temp_removed: usize,
+}
+
+pub fn spawn_recording_janitor(base_dir: PathBuf) {
+    let cfg = config::recording_storage_config();
+    if cfg.archive_enabled() {
+        if let Some(key) = cfg.active_key() {
+            log::info!(
+                "[recording] archive format={:?} encryption key={}",
+                cfg.archive_format,
+                key.id
+            );
+        } else {
+            log::info!("[recording] archive format={:?}", cfg.archive_format);
+        }
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(cfg.janitor_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let base_dir = base_dir.clone();
+            let report = tokio::task::spawn_blocking(move || {
+                run_once(&base_dir, Path::new(TTS_TEMP_DIR), cfg, SystemTime::now())
+            })
+            .await;
+            match report {
+                Ok(report) if report != JanitorReport::default() => {
+                    log::info!("[recording] janitor {:?}", report);
+                }
+                Ok(_) => {}
+                Err(err) => log::warn!("[recording] janitor task failed: {}", err),
+            }
+        }
+    });
+}
+
+/// 通話ログ保存時に、保存期間の判定に使う分類を meta.json に書き足す
+pub fn write_retention_tags(
+    dir: &Path,
+    caller_category: &str,
+    action_code: &str,
+) -> std::io::Result<()> {
+    let meta_path = dir.join("meta.json");
+    let mut meta: Value = serde_json::from_slice(&fs::read(&meta_path)?)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    if let Some(object) = meta.as_object_mut() {
+        object.insert(
+            "callerCategory".to_string(),
+            Value::String(caller_category.to_string()),
+        );
+        object.insert(
+            "actionCode".to_string(),
+            Value::String(action_code.to_string()),
+        );
+    }
+    let json = serde_json::to_vec_pretty(&meta).map_err(std::io::Error::other)?;
+    fs::write(meta_path, json)
+}
+
+pub fn run_once(
+    base_dir: &Path,
+    tts_temp_dir: &Path,
+    cfg: &RecordingStorageConfig,
+    now: SystemTime,
+) -> JanitorReport {
+    let mut report = JanitorReport::default();
+    for dir in call_dirs(base_dir) {
+        let meta = read_meta(&dir);
+        let Some(ended_at) = ended_at(&dir, meta.as_ref()) else {
+            continue;
+        };
+        let age = now.duration_since(ended_at).unwrap_or_default();
+        let tag = |name: &str| {
+            meta.as_ref()
+                .and_then(|meta| meta.get(name))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        let retention = cfg.retention_for(&tag("callerCategory"), &tag("actionCode"));
+        if retention.is_some_and(|retention| age >= retention) {
+            match fs::remove_dir_all(&dir) {
+                Ok(()) => {
+                    log::info!("[recording] expired {}", dir.display());
+                    report.expired += 1;
+                }
+                Err(err) => log::warn!("[recording] remove {} failed: {}", dir.display(), err),
+            }
+            continue;
+        }
+        if age < cfg.archive_after || !archive::needs_archive(&dir, cfg) {
+            continue;
+        }
+        match archive::archive_dir(&dir, cfg) {
+            Ok(outcome) => {
+                report.archived += outcome.converted;
+                report.reencrypted += outcome.reencrypted;
+            }
+            Err(err) => log::warn!("[recording] archive {} failed: {}", dir.display(), err),
+        }
+    }
+    report.tts_temp_removed = remove_stale_tts_temp(tts_temp_dir, cfg.tts_temp_max_age, now);
+    report
+}
+
+fn call_dirs(base_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(base_dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+fn read_meta(dir: &Path) -> Option<Value> {
+    serde_json::from_slice(&fs::read(dir.join("meta.json")).ok()?).ok()
+}
+
+/// 録音の終了時刻。meta.json の `recordingEndedAt`（無い古い録音は開始時刻＋長さ）を使い、
+/// meta から分からないときだけ最後に書き込まれた時刻にする（圧縮・再暗号化で mtime は新しくなる）
+fn ended_at(dir: &Path, meta: Option<&Value>) -> Option<SystemTime> {
+    meta.and_then(meta_ended_at).or_else(|| last_modified(dir))
+}
+
+fn meta_ended_at(meta: &Value) -> Option<SystemTime> {
+    let timestamp = |name: &str| {
+        meta.get(name)
+            .and_then(Value::as_str)
+            .and_then(|value| humantime::parse_rfc3339_weak(value).ok())
+    };
+    if let Some(ended_at) = timestamp("recordingEndedAt") {
+        return Some(ended_at);
+    }
+    let duration = meta
+        .get("durationSec")
+        .and_then(Value::as_f64)
+        .and_then(|sec| Duration::try_from_secs_f64(sec).ok())?;
+    timestamp("recordingStartedAt").map(|started_at| started_at + duration)
+}
+
+/// 最後に書き込まれた時刻（録音中・後処理中のファイルも含む）
+fn last_modified(dir: &Path) -> Option<SystemTime> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+}
+
+fn remove_stale_tts_temp(dir: &Path, max_age: Duration, now: SystemTime) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if !TTS_TEMP_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let stale = meta.is_file()
+            && meta
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age >= max_age);
+        if stale && fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::config::{RecordingArchiveFormat, RecordingRetentionRule};
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn storage_config() -> RecordingStorageConfig {
+        RecordingStorageConfig {
+            archive_format: RecordingArchiveFormat::Wav,
+            archive_after: Duration::from_secs(600),
+            opus_bitrate_kbps: 24,
+            ffmpeg_path: "ffmpeg".to_string(),
+            encryption_keys: Vec::new(),
+            retention: Some(DAY * 30),
+            retention_rules: vec![RecordingRetentionRule {
+                caller_category: Some("spam".to_string()),
+                action_code: None,
+                retention: Some(DAY),
+            }],
+            janitor_interval: Duration::from_secs(3600),
+            tts_temp_max_age: Duration::from_secs(3600),
+        }
+    }
+
+    fn call_dir(base: &Path, name: &str, category: &str) -> PathBuf {
+        let dir = base.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("meta.json"),
+            r#"{"callId":"c","files":{"mixed":"mixed.wav"}}"#,
+        )
+        .unwrap();
+        write_retention_tags(&dir, category, "IV").unwrap();
+        dir
+    }
+
+    #[test]
+    fn expires_recordings_by_category_and_cleans_tts_temp() {
+        let base = tempfile::tempdir().unwrap();
+        let tts = tempfile::tempdir().unwrap();
+        let spam = call_dir(base.path(), "spam-call", "spam");
+        let registered = call_dir(base.path(), "registered-call", "registered");
+        fs::write(tts.path().join("tts_output_c_1.wav"), b"x").unwrap();
+        fs::write(tts.path().join("other.wav"), b"x").unwrap();
+
+        let cfg = storage_config();
+        let report = run_once(base.path(), tts.path(), &cfg, SystemTime::now() + DAY * 2);
+
+        assert_eq!(report.expired, 1);
+        assert_eq!(report.tts_temp_removed, 1);
+        assert!(!spam.exists());
+        assert!(registered.exists());
+        assert!(tts.path().join("other.wav").exists());
+        let meta = read_meta(&registered).unwrap();
+        assert_eq!(meta["callerCategory"], "registered");
+        assert_eq!(meta["actionCode"], "IV");
+
+        let report = run_once(base.path(), tts.path(), &cfg, SystemTime::now() + DAY * 31);
+        assert_eq!(report.expired, 1);
+        assert!(!registered.exists());
+    }
+
+    #[test]
+    fn keeps_recent_files() {
+        let base = tempfile::tempdir().unwrap();
+        let tts = tempfile::tempdir().unwrap();
+        let dir = call_dir(base.path(), "spam-call", "spam");
+        fs::write(tts.path().join("tts_stream_output_c_1.wav"), b"x").unwrap();
+
+        let report = run_once(
+            base.path(),
+            tts.path(),
+            &storage_config(),
+            SystemTime::now(),
+        );
+
+        assert_eq!(report, JanitorReport::default());
+        assert!(dir.exists());
+    }
+
+    #[test]
+    fn uses_recording_end_time_from_meta_over_file_mtime() {
+        let base = tempfile::tempdir().unwrap();
+        let tts = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let ended = base.path().join("ended-call");
+        fs::create_dir_all(&ended).unwrap();
+        fs::write(
+            ended.join("meta.json"),
+            format!(
+                r#"{{"callId":"c","recordingEndedAt":"{}","files":{{"mixed":"mixed.wav"}}}}"#,
+                humantime::format_rfc3339(now - DAY * 40)
+            ),
+        )
+        .unwrap();
+        // 録音の開始時刻と長さだけを書いていた古い meta
+        let legacy = base.path().join("legacy-call");
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(
+            legacy.join("meta.json"),
+            format!(
+                r#"{{"callId":"c","recordingStartedAt":"{}","durationSec":60.0,"files":{{"mixed":"mixed.wav"}}}}"#,
+                humantime::format_rfc3339(now - DAY * 31)
+            ),
+        )
+        .unwrap();
+        let recent = base.path().join("recent-call");
+        fs::create_dir_all(&recent).unwrap();
+        fs::write(
+            recent.join("meta.json"),
+            format!(
+                r#"{{"callId":"c","recordingEndedAt":"{}","files":{{"mixed":"mixed.wav"}}}}"#,
+                humantime::format_rfc3339(now - DAY)
+            ),
+        )
+        .unwrap();
+
+        let report = run_once(base.path(), tts.path(), &storage_config(), now);
+
+        // ファイルの mtime は今でも、録音の終了から 30 日を過ぎていれば消す
+        assert_eq!(report.expired, 2);
+        assert!(!ended.exists());
+        assert!(!legacy.exists());
+        assert!(recent.exists());
+    }
+}
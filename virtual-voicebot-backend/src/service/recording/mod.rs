@@ -2,6 +2,10 @@ use std::path::PathBuf;
 
 pub const RECORDINGS_DIR: &str = "storage/recordings";
 
+pub mod archive;
+pub mod encryption;
+pub mod janitor;
+pub mod signed_url;
 pub mod storage;
 
 pub fn recording_dir_name(call_id: &str) -> String {
@@ -12,7 +16,50 @@ pub fn recording_dir(call_id: &str) -> PathBuf {
     PathBuf::from(RECORDINGS_DIR).join(recording_dir_name(call_id))
 }
 
+/// 録音の配信 URL。`RECORDING_URL_SIGNING_SECRET` があれば期限付きの署名を付ける
 pub fn recording_url(base_url: &str, call_id: &str) -> String {
+    let cfg = crate::shared::config::http_security_config();
+    let expires = chrono::Utc::now().timestamp() + cfg.recording_url_ttl.as_secs() as i64;
+    build_recording_url(
+        base_url,
+        call_id,
+        cfg.recording_url_secret.as_deref(),
+        expires,
+    )
+}
+
+fn build_recording_url(
+    base_url: &str,
+    call_id: &str,
+    secret: Option<&[u8]>,
+    expires: i64,
+) -> String {
     let base = base_url.trim_end_matches('/');
-    format!("{}/recordings/{}/mixed.wav", base, call_id)
+    let url = format!("{}/recordings/{}/mixed.wav", base, call_id);
+    match secret {
+        Some(secret) => format!(
+            "{}?{}",
+            url,
+            signed_url::signed_query(secret, call_id, expires)
+        ),
+        None => url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_url_is_signed_only_with_secret() {
+        assert_eq!(
+            build_recording_url("http://host:18080/", "call-a", None, 100),
+            "http://host:18080/recordings/call-a/mixed.wav"
+        );
+        let signed = build_recording_url("http://host:18080", "call-a", Some(b"secret"), 100);
+        let query = signed
+            .strip_prefix("http://host:18080/recordings/call-a/mixed.wav?expires=100&sig=")
+            .unwrap();
+        assert_eq!(query.len(), 64);
+    }
 }
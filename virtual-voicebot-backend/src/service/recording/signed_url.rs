@@ -0,0 +1,139 @@
+//! 録音 URL の署名と検証。
+//!
+//! `RECORDING_URL_SIGNING_SECRET` を設定すると、`recording_url` が返す URL に
+//! `?expires=<UNIX 秒>&sig=<HMAC-SHA256 の hex>` が付き、HTTP サーバはこれを検証してから配信する。
+//! 署名は通話 ID と期限に対して行うので、同じ通話の別形式（後処理で変換した mixed.opus など）にも使える。
+
+use ring::hmac;
+use thiserror::Error;
+
+pub const EXPIRES_PARAM: &str = "expires";
+pub const SIGNATURE_PARAM: &str = "sig";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SignedUrlError {
+    #[error("signature is missing")]
+    Missing,
+    #[error("signature has expired")]
+    Expired,
+    #[error("signature is invalid")]
+    Invalid,
+}
+
+/// `expires=..&sig=..`（先頭の `?` は含まない）
+pub fn signed_query(secret: &[u8], call_id: &str, expires: i64) -> String {
+    let tag = hmac::sign(&signing_key(secret), &message(call_id, expires));
+    format!(
+        "{}={}&{}={}",
+        EXPIRES_PARAM,
+        expires,
+        SIGNATURE_PARAM,
+        hex_encode(tag.as_ref())
+    )
+}
+
+pub fn verify(
+    secret: &[u8],
+    call_id: &str,
+    expires: Option<&str>,
+    signature: Option<&str>,
+    now: i64,
+) -> Result<(), SignedUrlError> {
+    let (Some(expires), Some(signature)) = (expires, signature) else {
+        return Err(SignedUrlError::Missing);
+    };
+    let expires: i64 = expires.parse().map_err(|_| SignedUrlError::Invalid)?;
+    let signature = hex_decode(signature).ok_or(SignedUrlError::Invalid)?;
+    // 期限切れでも署名が正しいかを先に見る（改ざんした期限を「期限切れ」と返さない）
+    hmac::verify(&signing_key(secret), &message(call_id, expires), &signature)
+        .map_err(|_| SignedUrlError::Invalid)?;
+    if expires < now {
+        return Err(SignedUrlError::Expired);
+    }
+    Ok(())
+}
+
+fn signing_key(secret: &[u8]) -> hmac::Key {
+    hmac::Key::new(hmac::HMAC_SHA256, secret)
+}
+
+fn message(call_id: &str, expires: i64) -> Vec<u8> {
+    format!("recordings/{}\n{}", call_id, expires).into_bytes()
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hex_decode(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return None;
+    }
+    value
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"test-secret";
+
+    fn params(query: &str) -> (String, String) {
+        let mut expires = String::new();
+        let mut signature = String::new();
+        for pair in query.split('&') {
+            match pair.split_once('=') {
+                Some((EXPIRES_PARAM, value)) => expires = value.to_string(),
+                Some((SIGNATURE_PARAM, value)) => signature = value.to_string(),
+                _ => {}
+            }
+        }
+        (expires, signature)
+    }
+
+    #[test]
+    fn accepts_signature_until_it_expires() {
+        let (expires, signature) = params(&signed_query(SECRET, "call-a", 1_000));
+
+        assert_eq!(
+            verify(SECRET, "call-a", Some(&expires), Some(&signature), 999),
+            Ok(())
+        );
+        assert_eq!(
+            verify(SECRET, "call-a", Some(&expires), Some(&signature), 1_001),
+            Err(SignedUrlError::Expired)
+        );
+    }
+
+    #[test]
+    fn rejects_other_calls_tampered_expiry_and_missing_params() {
+        let (expires, signature) = params(&signed_query(SECRET, "call-a", 1_000));
+
+        assert_eq!(
+            verify(SECRET, "call-b", Some(&expires), Some(&signature), 0),
+            Err(SignedUrlError::Invalid)
+        );
+        assert_eq!(
+            verify(SECRET, "call-a", Some("2000"), Some(&signature), 0),
+            Err(SignedUrlError::Invalid)
+        );
+        assert_eq!(
+            verify(
+                b"other-secret",
+                "call-a",
+                Some(&expires),
+                Some(&signature),
+                0
+            ),
+            Err(SignedUrlError::Invalid)
+        );
+        assert_eq!(
+            verify(SECRET, "call-a", Some(&expires), None, 0),
+            Err(SignedUrlError::Missing)
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use hound::WavReader;
 
-use crate::shared::audio::linear16_to_mulaw;
+use crate::shared::audio::{decimation_step_to_8k, linear16_to_mulaw, mulaw_to_frames};
 use crate::shared::ports::storage::{StorageError, StoragePort};
 
 pub struct FileStoragePort;
@@ -35,29 +35,16 @@ fn load_wav_as_pcmu_frames(path: &str) -> Result<Vec<Vec<u8>>, StorageError> {
     for s in reader.samples::<i16>() {
         samples.push(s.map_err(|e| StorageError::Io(e.to_string()))?);
     }
-    let base_samples: Vec<i16> = match spec.sample_rate {
-        8000 => samples,
-        24000 => samples.iter().step_by(3).copied().collect(),
-        other => {
-            return Err(StorageError::UnsupportedFormat(format!(
-                "unsupported sample rate {other}"
-            )))
-        }
+    let Some(step) = decimation_step_to_8k(spec.sample_rate) else {
+        return Err(StorageError::UnsupportedFormat(format!(
+            "unsupported sample rate {}",
+            spec.sample_rate
+        )));
     };
-    let mut frames = Vec::new();
-    let mut cur = Vec::with_capacity(160);
-    for s in base_samples {
-        cur.push(linear16_to_mulaw(s));
-        if cur.len() == 160 {
-            frames.push(cur.clone());
-            cur.clear();
-        }
-    }
-    if !cur.is_empty() {
-        while cur.len() < 160 {
-            cur.push(0xFF);
-        }
-        frames.push(cur);
-    }
-    Ok(frames)
+    let mulaw: Vec<u8> = samples
+        .into_iter()
+        .step_by(step)
+        .map(linear16_to_mulaw)
+        .collect();
+    Ok(mulaw_to_frames(&mulaw))
 }
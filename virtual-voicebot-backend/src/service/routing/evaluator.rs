@@ -1,16 +1,23 @@
 use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
 use log::{info, warn};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use uuid::Uuid;
 
 use super::normalize_phone_number_e164;
+use super::schedule::{resolve_active_schedule, schedule_action, ScheduleAction};
+use crate::shared::config::{self, ScheduleRoutingConfig};
+use crate::shared::entities::CallerIdentity;
 use crate::shared::ports::routing_port::{
     RegisteredNumberRow, RoutingPort, RoutingPortError, RoutingRuleRow,
 };
+use crate::shared::ports::schedule_port::SchedulePort;
+use crate::shared::utils::{map_audio_file_url_to_cache_path, mask_pii};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ActionConfig {
     pub action_code: String,
     pub caller_category: String,
@@ -22,6 +29,8 @@ pub struct ActionConfig {
     pub announcement_audio_file_url: Option<String>,
     pub scenario_id: Option<String>,
     pub include_announcement: Option<bool>,
+    /// スケジュール段で決定した場合の schedules.id（通話ログに記録する）
+    pub schedule_id: Option<Uuid>,
 }
 
 impl ActionConfig {
@@ -37,6 +46,7 @@ impl ActionConfig {
             announcement_audio_file_url: None,
             scenario_id: None,
             include_announcement: None,
+            schedule_id: None,
         }
     }
 
@@ -52,6 +62,7 @@ impl ActionConfig {
             announcement_audio_file_url: None,
             scenario_id: None,
             include_announcement: None,
+            schedule_id: None,
         }
     }
 }
@@ -92,6 +103,7 @@ impl From<ActionConfigDto> for ActionConfig {
             announcement_audio_file_url: None,
             scenario_id: dto.scenario_id,
             include_announcement: dto.include_announcement,
+            schedule_id: None,
         }
     }
 }
@@ -141,31 +153,220 @@ impl From<serde_json::Error> for RoutingError {
     }
 }
 
+/// dry-run で記録する評価段の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceOutcome {
+    /// この段でアクションが決まった
+    Hit,
+    /// 該当なしで次の段へ進んだ
+    Miss,
+    /// 設定や入力の都合で評価しなかった
+    Skip,
+    /// 既定アクションへ落ちた
+    Fallback,
+}
+
+/// 評価段ごとの記録。`stage` は registered_numbers / caller_group / category /
+/// schedule / routing_rules / default などの段名
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutingTraceStep {
+    pub stage: &'static str,
+    pub outcome: TraceOutcome,
+    /// 照合した行の id（registered_numbers.id, caller_groups.id, call_action_rules.id など）
+    pub row_id: Option<Uuid>,
+    pub detail: Option<String>,
+}
+
+/// アナウンス ID から再生ファイルまでの解決結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnouncementResolution {
+    /// announcement / recording_announcement のどちらから引いたか
+    pub role: &'static str,
+    pub announcement_id: Uuid,
+    pub audio_file_url: Option<String>,
+    pub cache_path: Option<String>,
+    /// キャッシュにファイルが存在するか（serversync 済みか）
+    pub cached: bool,
+}
+
+/// 着信を発生させずに評価した結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutingExplanation {
+    pub caller: Option<String>,
+    pub did: Option<String>,
+    /// 評価に使った着信時刻（RFC 3339）
+    pub evaluated_at: String,
+    pub action: ActionConfig,
+    pub trace: Vec<RoutingTraceStep>,
+    pub announcements: Vec<AnnouncementResolution>,
+}
+
+#[derive(Debug, Default)]
+struct RoutingTrace {
+    steps: Vec<RoutingTraceStep>,
+}
+
+impl RoutingTrace {
+    fn record(
+        &mut self,
+        stage: &'static str,
+        outcome: TraceOutcome,
+        row_id: Option<Uuid>,
+        detail: Option<String>,
+    ) {
+        self.steps.push(RoutingTraceStep {
+            stage,
+            outcome,
+            row_id,
+            detail,
+        });
+    }
+}
+
 pub struct RuleEvaluator {
     routing_port: Arc<dyn RoutingPort>,
+    schedule_port: Option<Arc<dyn SchedulePort>>,
+    schedule_routing: ScheduleRoutingConfig,
 }
 
 impl RuleEvaluator {
     pub fn new(routing_port: Arc<dyn RoutingPort>) -> Self {
-        Self { routing_port }
+        Self {
+            routing_port,
+            schedule_port: None,
+            schedule_routing: config::schedule_routing_config().clone(),
+        }
+    }
+
+    /// スケジュール段（営業時間・休日）を有効にする（`SCHEDULE_ROUTING_ENABLED` が false なら素通り）。
+    pub fn with_schedule_port(mut self, schedule_port: Arc<dyn SchedulePort>) -> Self {
+        self.schedule_port = Some(schedule_port);
+        self
     }
 
+    /// 環境変数の代わりに使うスケジュール段の設定
+    pub fn with_schedule_routing(mut self, schedule_routing: ScheduleRoutingConfig) -> Self {
+        self.schedule_routing = schedule_routing;
+        self
+    }
+
+    /// `called_number` は着信先（To/Request-URI のユーザ部）。DID 別ルールの選択に使用する。
     pub async fn evaluate(
         &self,
-        caller_id: &str,
+        caller: &CallerIdentity,
+        called_number: Option<&str>,
         call_id: &str,
     ) -> Result<ActionConfig, RoutingError> {
+        self.evaluate_at(caller, called_number, call_id, Utc::now())
+            .await
+    }
+
+    /// `now` を着信時刻として評価する（スケジュール段の判定に使用）。
+    ///
+    /// 発信者番号は網側で保証された番号（PAI 等）を優先して照合する。
+    /// Privacy で非通知が要求された着信は、どのルールにも該当しなければ anonymousAction を適用する。
+    pub async fn evaluate_at(
+        &self,
+        caller: &CallerIdentity,
+        called_number: Option<&str>,
+        call_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<ActionConfig, RoutingError> {
+        let mut trace = RoutingTrace::default();
+        self.evaluate_traced(caller, called_number, call_id, now, &mut trace)
+            .await
+    }
+
+    /// 通話を発生させずに評価し、各段の照合結果とアナウンスの解決結果を返す。
+    pub async fn explain(
+        &self,
+        caller: &CallerIdentity,
+        called_number: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<RoutingExplanation, RoutingError> {
+        let mut trace = RoutingTrace::default();
+        let action = self
+            .evaluate_traced(caller, called_number, "dry-run", now, &mut trace)
+            .await?;
+        let mut announcements = Vec::new();
+        for (role, announcement_id) in [
+            ("announcement", action.announcement_id),
+            ("recording_announcement", action.recording_announcement_id),
+        ] {
+            if let Some(announcement_id) = announcement_id {
+                announcements.push(self.resolve_announcement(role, announcement_id).await?);
+            }
+        }
+        Ok(RoutingExplanation {
+            caller: caller.routing_number().map(str::to_string),
+            did: called_number.and_then(normalize_did),
+            evaluated_at: now.to_rfc3339(),
+            action,
+            trace: trace.steps,
+            announcements,
+        })
+    }
+
+    async fn resolve_announcement(
+        &self,
+        role: &'static str,
+        announcement_id: Uuid,
+    ) -> Result<AnnouncementResolution, RoutingError> {
+        let audio_file_url = self
+            .routing_port
+            .find_announcement_audio_file_url(announcement_id)
+            .await?;
+        let cache_path = audio_file_url.as_deref().and_then(|url| {
+            map_audio_file_url_to_cache_path(config::announcement_config().audio_dir.as_str(), url)
+        });
+        let cached = match cache_path.as_deref() {
+            Some(path) => tokio::fs::try_exists(path).await.unwrap_or(false),
+            None => false,
+        };
+        Ok(AnnouncementResolution {
+            role,
+            announcement_id,
+            audio_file_url,
+            cache_path,
+            cached,
+        })
+    }
+
+    async fn evaluate_traced(
+        &self,
+        caller: &CallerIdentity,
+        called_number: Option<&str>,
+        call_id: &str,
+        now: DateTime<Utc>,
+        trace: &mut RoutingTrace,
+    ) -> Result<ActionConfig, RoutingError> {
+        let caller_id = caller.routing_number().unwrap_or_default();
+        let privacy = caller.privacy;
+        let did = called_number.and_then(normalize_did);
         info!(
-            "[RuleEvaluator] call_id={} evaluating caller_id={}",
-            call_id, caller_id
+            "[RuleEvaluator] call_id={} evaluating caller_id={} privacy={} did={:?}",
+            call_id,
+            if privacy {
+                mask_pii(caller_id)
+            } else {
+                caller_id.to_string()
+            },
+            privacy,
+            did
         );
+        let did = did.as_deref();
 
         if is_anonymous(caller_id) {
             info!(
                 "[RuleEvaluator] call_id={} anonymous caller detected caller_id={}",
                 call_id, caller_id
             );
-            return self.get_anonymous_action(call_id).await;
+            trace.record("anonymous", TraceOutcome::Hit, None, None);
+            return self.get_anonymous_action(call_id, trace).await;
         }
 
         let normalized_caller_id = match normalize_phone_number_e164(caller_id) {
@@ -175,16 +376,24 @@ impl RuleEvaluator {
                     "[RuleEvaluator] call_id={} phone normalization failed: {}, fallback to defaultAction",
                     call_id, err
                 );
-                return self.get_default_action(call_id).await;
+                trace.record(
+                    "normalize",
+                    TraceOutcome::Fallback,
+                    None,
+                    Some(err.to_string()),
+                );
+                return self.get_fallback_action(privacy, did, call_id, trace).await;
             }
         };
-        info!(
-            "[RuleEvaluator] call_id={} normalized caller={} -> {}",
-            call_id, caller_id, normalized_caller_id
-        );
+        if !privacy {
+            info!(
+                "[RuleEvaluator] call_id={} normalized caller={} -> {}",
+                call_id, caller_id, normalized_caller_id
+            );
+        }
 
         if let Some(action) = self
-            .match_registered_number(&normalized_caller_id, call_id)
+            .match_registered_number(&normalized_caller_id, did, call_id, trace)
             .await?
         {
             info!(
@@ -199,7 +408,7 @@ impl RuleEvaluator {
         );
 
         match self
-            .match_caller_group(&normalized_caller_id, call_id)
+            .match_caller_group(&normalized_caller_id, did, call_id, trace)
             .await?
         {
             CallerGroupMatch::Matched(action) => {
@@ -220,20 +429,40 @@ impl RuleEvaluator {
                     "[RuleEvaluator] call_id={} stage=2 group_id={} has no active rule, fallback to defaultAction",
                     call_id, group_id
                 );
-                return self.get_default_action(call_id).await;
+                return self.get_fallback_action(privacy, did, call_id, trace).await;
             }
         }
 
-        let category = self.classify_caller(&normalized_caller_id, call_id).await?;
+        let category = self
+            .classify_caller(&normalized_caller_id, call_id, trace)
+            .await?;
+
+        if let Some(action) = self.match_schedule(category, call_id, now, trace).await? {
+            info!(
+                "[RuleEvaluator] call_id={} hit stage=schedule source=schedules schedule_id={:?} action_code={}",
+                call_id, action.schedule_id, action.action_code
+            );
+            return Ok(action);
+        }
+
         if matches!(category, CallerCategory::Unknown) {
             info!(
                 "[RuleEvaluator] call_id={} category=unknown uses defaultAction",
                 call_id
             );
-            return self.get_default_action(call_id).await;
+            trace.record(
+                "routing_rules",
+                TraceOutcome::Skip,
+                None,
+                Some("unknown callers use defaultAction".to_string()),
+            );
+            return self.get_fallback_action(privacy, did, call_id, trace).await;
         }
 
-        if let Some(action) = self.match_routing_rule(category, call_id).await? {
+        if let Some(action) = self
+            .match_routing_rule(category, did, call_id, trace)
+            .await?
+        {
             info!(
                 "[RuleEvaluator] call_id={} hit stage=3 source=routing_rules category={}",
                 call_id,
@@ -251,16 +480,22 @@ impl RuleEvaluator {
             "[RuleEvaluator] call_id={} fallback stage=4 source=system_settings.defaultAction",
             call_id
         );
-        self.get_default_action(call_id).await
+        self.get_fallback_action(privacy, did, call_id, trace).await
     }
 
     async fn match_registered_number(
         &self,
         caller_id: &str,
+        did: Option<&str>,
         call_id: &str,
+        trace: &mut RoutingTrace,
     ) -> Result<Option<ActionConfig>, RoutingError> {
-        let row = self.routing_port.find_registered_number(caller_id).await?;
+        let row = self
+            .routing_port
+            .find_registered_number(caller_id, did)
+            .await?;
         let Some(row) = row else {
+            trace.record("registered_numbers", TraceOutcome::Miss, None, None);
             return Ok(None);
         };
 
@@ -269,31 +504,55 @@ impl RuleEvaluator {
                 "[RuleEvaluator] call_id={} stage=1 group_id found, defer to stage=2 phone_number={} group_id={}",
                 call_id, caller_id, group_id
             );
+            trace.record(
+                "registered_numbers",
+                TraceOutcome::Miss,
+                Some(row.id),
+                Some(format!("defer to caller group {}", group_id)),
+            );
             return Ok(None);
         }
 
-        let action = to_action_config_from_registered(row);
-        let mut action = action;
+        let row_id = row.id;
+        let mut action = to_action_config_from_registered(row);
         action.caller_category = "registered".to_string();
         info!(
             "[RuleEvaluator] call_id={} stage=1 action_code={}",
             call_id, action.action_code
         );
+        trace.record(
+            "registered_numbers",
+            TraceOutcome::Hit,
+            Some(row_id),
+            Some(format!("action_code={}", action.action_code)),
+        );
         Ok(Some(action))
     }
 
     async fn match_caller_group(
         &self,
         caller_id: &str,
+        did: Option<&str>,
         call_id: &str,
+        trace: &mut RoutingTrace,
     ) -> Result<CallerGroupMatch, RoutingError> {
-        let group_id = self.routing_port.find_caller_group(caller_id).await?;
+        let group_id = self.routing_port.find_caller_group(caller_id, did).await?;
         let Some(group_id) = group_id else {
+            trace.record("caller_group", TraceOutcome::Miss, None, None);
             return Ok(CallerGroupMatch::NoGroup);
         };
 
-        let row = self.routing_port.find_call_action_rule(group_id).await?;
+        let row = self
+            .routing_port
+            .find_call_action_rule(group_id, did)
+            .await?;
         let Some(row) = row else {
+            trace.record(
+                "caller_group",
+                TraceOutcome::Fallback,
+                Some(group_id),
+                Some("group has no active call_action_rule".to_string()),
+            );
             return Ok(CallerGroupMatch::NoActiveRule { group_id });
         };
 
@@ -304,6 +563,15 @@ impl RuleEvaluator {
             "[RuleEvaluator] call_id={} stage=2 rule_id={} action_code={}",
             call_id, row.id, action.action_code
         );
+        trace.record(
+            "caller_group",
+            TraceOutcome::Hit,
+            Some(row.id),
+            Some(format!(
+                "group_id={} action_code={}",
+                group_id, action.action_code
+            )),
+        );
         Ok(CallerGroupMatch::Matched(action))
     }
 
@@ -311,40 +579,42 @@ impl RuleEvaluator {
         &self,
         caller_id: &str,
         call_id: &str,
+        trace: &mut RoutingTrace,
     ) -> Result<CallerCategory, RoutingError> {
-        if self.routing_port.is_spam(caller_id).await? {
-            info!(
-                "[RuleEvaluator] call_id={} classified category=spam",
-                call_id
-            );
-            return Ok(CallerCategory::Spam);
-        }
-
-        if self.routing_port.is_registered(caller_id).await? {
-            info!(
-                "[RuleEvaluator] call_id={} classified category=registered",
-                call_id
-            );
-            return Ok(CallerCategory::Registered);
-        }
-
+        let category = if self.routing_port.is_spam(caller_id).await? {
+            CallerCategory::Spam
+        } else if self.routing_port.is_registered(caller_id).await? {
+            CallerCategory::Registered
+        } else {
+            CallerCategory::Unknown
+        };
         info!(
-            "[RuleEvaluator] call_id={} classified category=unknown",
-            call_id
+            "[RuleEvaluator] call_id={} classified category={}",
+            call_id,
+            category.as_str()
         );
-        Ok(CallerCategory::Unknown)
+        trace.record(
+            "category",
+            TraceOutcome::Hit,
+            None,
+            Some(category.as_str().to_string()),
+        );
+        Ok(category)
     }
 
     async fn match_routing_rule(
         &self,
         category: CallerCategory,
+        did: Option<&str>,
         call_id: &str,
+        trace: &mut RoutingTrace,
     ) -> Result<Option<ActionConfig>, RoutingError> {
         let row = self
             .routing_port
-            .find_routing_rule(category.as_str())
+            .find_routing_rule(category.as_str(), did)
             .await?;
         let Some(row) = row else {
+            trace.record("routing_rules", TraceOutcome::Miss, None, None);
             return Ok(None);
         };
         let rule_id = row.id;
@@ -354,27 +624,224 @@ impl RuleEvaluator {
             "[RuleEvaluator] call_id={} stage=3 rule_id={} action_code={}",
             call_id, rule_id, action.action_code
         );
+        trace.record(
+            "routing_rules",
+            TraceOutcome::Hit,
+            Some(rule_id),
+            Some(format!("action_code={}", action.action_code)),
+        );
+        Ok(Some(action))
+    }
+
+    async fn match_schedule(
+        &self,
+        category: CallerCategory,
+        call_id: &str,
+        now: DateTime<Utc>,
+        trace: &mut RoutingTrace,
+    ) -> Result<Option<ActionConfig>, RoutingError> {
+        let Some(schedule_port) = &self.schedule_port else {
+            trace.record(
+                "schedule",
+                TraceOutcome::Skip,
+                None,
+                Some("schedule port not configured".to_string()),
+            );
+            return Ok(None);
+        };
+        let cfg = &self.schedule_routing;
+        if !cfg.enabled {
+            trace.record(
+                "schedule",
+                TraceOutcome::Skip,
+                None,
+                Some("schedule routing disabled".to_string()),
+            );
+            return Ok(None);
+        }
+        let schedules = match schedule_port.list_active().await {
+            Ok(schedules) => schedules,
+            Err(err) => {
+                // スケジュールが読めなくても着信処理は止めない
+                warn!(
+                    "[RuleEvaluator] call_id={} schedule lookup failed: {}, skip stage=schedule",
+                    call_id, err
+                );
+                trace.record(
+                    "schedule",
+                    TraceOutcome::Skip,
+                    None,
+                    Some(format!("lookup failed: {}", err)),
+                );
+                return Ok(None);
+            }
+        };
+        let local_now = now.with_timezone(&cfg.utc_offset).naive_local();
+        let Some(schedule) = resolve_active_schedule(&schedules, local_now) else {
+            info!(
+                "[RuleEvaluator] call_id={} miss stage=schedule local_time={}",
+                call_id, local_now
+            );
+            trace.record(
+                "schedule",
+                TraceOutcome::Miss,
+                None,
+                Some(format!("local_time={}", local_now)),
+            );
+            return Ok(None);
+        };
+
+        let mut action = match schedule_action(schedule) {
+            ScheduleAction::Continue => {
+                info!(
+                    "[RuleEvaluator] call_id={} stage=schedule schedule_id={} routes normally",
+                    call_id, schedule.id
+                );
+                trace.record(
+                    "schedule",
+                    TraceOutcome::Miss,
+                    Some(schedule.id),
+                    Some("schedule routes normally".to_string()),
+                );
+                return Ok(None);
+            }
+            ScheduleAction::RoutingRule {
+                rule_id,
+                fallback_code,
+            } => match self.routing_port.find_routing_rule_by_id(rule_id).await? {
+                Some(row) => to_action_config_from_routing_rule(row),
+                None => {
+                    let Some(action_code) = fallback_code else {
+                        warn!(
+                            "[RuleEvaluator] call_id={} schedule_id={} routing rule {} not found, skip stage=schedule",
+                            call_id, schedule.id, rule_id
+                        );
+                        trace.record(
+                            "schedule",
+                            TraceOutcome::Skip,
+                            Some(schedule.id),
+                            Some(format!("routing rule {} not found", rule_id)),
+                        );
+                        return Ok(None);
+                    };
+                    let mut action = ActionConfig::default_vr();
+                    action.action_code = action_code;
+                    action
+                }
+            },
+            ScheduleAction::Code {
+                action_code,
+                announcement_id,
+            } => {
+                let mut action = ActionConfig::default_vr();
+                action.action_code = action_code;
+                action.announcement_id = announcement_id;
+                action
+            }
+        };
+        action.caller_category = category.as_str().to_string();
+        action.schedule_id = Some(schedule.id);
+        trace.record(
+            "schedule",
+            TraceOutcome::Hit,
+            Some(schedule.id),
+            Some(format!("action_code={}", action.action_code)),
+        );
         Ok(Some(action))
     }
 
-    async fn get_default_action(&self, call_id: &str) -> Result<ActionConfig, RoutingError> {
+    /// どの段にも該当しなかった場合のアクション（非通知要求なら anonymousAction）。
+    async fn get_fallback_action(
+        &self,
+        privacy: bool,
+        did: Option<&str>,
+        call_id: &str,
+        trace: &mut RoutingTrace,
+    ) -> Result<ActionConfig, RoutingError> {
+        if privacy {
+            info!(
+                "[RuleEvaluator] call_id={} caller requested privacy, use anonymousAction",
+                call_id
+            );
+            return self.get_anonymous_action(call_id, trace).await;
+        }
+        self.get_default_action(did, call_id, trace).await
+    }
+
+    async fn get_default_action(
+        &self,
+        did: Option<&str>,
+        call_id: &str,
+        trace: &mut RoutingTrace,
+    ) -> Result<ActionConfig, RoutingError> {
+        if let Some(did) = did {
+            if let Some(mut action) = self.get_did_default_action(did, call_id).await? {
+                info!(
+                    "[RuleEvaluator] call_id={} use didDefaultActions did={} action_code={}",
+                    call_id, did, action.action_code
+                );
+                trace.record(
+                    "default",
+                    TraceOutcome::Fallback,
+                    None,
+                    Some(format!(
+                        "didDefaultActions[{}] action_code={}",
+                        did, action.action_code
+                    )),
+                );
+                action.caller_category = "unknown".to_string();
+                return Ok(action);
+            }
+        }
         let mut action = self
             .get_action_from_settings_or_fallback(
                 "defaultAction",
                 ActionConfig::default_vr(),
                 call_id,
+                trace,
             )
             .await?;
         action.caller_category = "unknown".to_string();
         Ok(action)
     }
 
-    async fn get_anonymous_action(&self, call_id: &str) -> Result<ActionConfig, RoutingError> {
+    /// system_settings.extra.didDefaultActions から着信番号別の既定アクションを取得する。
+    async fn get_did_default_action(
+        &self,
+        did: &str,
+        call_id: &str,
+    ) -> Result<Option<ActionConfig>, RoutingError> {
+        let extra = self.routing_port.get_system_settings_extra().await?;
+        let Some(raw_action) = extra
+            .as_ref()
+            .and_then(|extra| extra.get("didDefaultActions"))
+            .and_then(|actions| actions.get(did))
+        else {
+            return Ok(None);
+        };
+        match parse_stored_action(raw_action) {
+            Ok(action) => Ok(Some(action)),
+            Err(err) => {
+                warn!(
+                    "[RuleEvaluator] call_id={} failed to parse didDefaultActions[{}]: {}, fallback to defaultAction",
+                    call_id, did, err
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    async fn get_anonymous_action(
+        &self,
+        call_id: &str,
+        trace: &mut RoutingTrace,
+    ) -> Result<ActionConfig, RoutingError> {
         let mut action = self
             .get_action_from_settings_or_fallback(
                 "anonymousAction",
                 ActionConfig::default_bz(),
                 call_id,
+                trace,
             )
             .await?;
         action.caller_category = "anonymous".to_string();
@@ -386,6 +853,7 @@ impl RuleEvaluator {
         field_name: &str,
         fallback: ActionConfig,
         call_id: &str,
+        trace: &mut RoutingTrace,
     ) -> Result<ActionConfig, RoutingError> {
         let extra = self.routing_port.get_system_settings_extra().await?;
         let Some(extra) = extra else {
@@ -393,6 +861,15 @@ impl RuleEvaluator {
                 "[RuleEvaluator] call_id={} system_settings.extra not found, fallback action_code={}",
                 call_id, fallback.action_code
             );
+            trace.record(
+                "default",
+                TraceOutcome::Fallback,
+                None,
+                Some(format!(
+                    "system_settings.extra not found, builtin action_code={}",
+                    fallback.action_code
+                )),
+            );
             return Ok(fallback);
         };
 
@@ -403,20 +880,45 @@ impl RuleEvaluator {
                 field_name,
                 fallback.action_code
             );
+            trace.record(
+                "default",
+                TraceOutcome::Fallback,
+                None,
+                Some(format!(
+                    "{} missing, builtin action_code={}",
+                    field_name, fallback.action_code
+                )),
+            );
             return Ok(fallback);
         };
 
-        let raw_config = raw_action
-            .get("actionConfig")
-            .cloned()
-            .unwrap_or_else(|| raw_action.clone());
-        match serde_json::from_value::<ActionConfigDto>(raw_config) {
-            Ok(dto) => Ok(dto.into()),
+        match parse_stored_action(raw_action) {
+            Ok(action) => {
+                trace.record(
+                    "default",
+                    TraceOutcome::Fallback,
+                    None,
+                    Some(format!(
+                        "system_settings.{} action_code={}",
+                        field_name, action.action_code
+                    )),
+                );
+                Ok(action)
+            }
             Err(err) => {
                 warn!(
                     "[RuleEvaluator] call_id={} failed to parse {}: {}, fallback action_code={}",
                     call_id, field_name, err, fallback.action_code
                 );
+                trace.record(
+                    "default",
+                    TraceOutcome::Fallback,
+                    None,
+                    Some(format!(
+                        "{} unparsable ({}), builtin action_code={}",
+                        field_name, err, fallback.action_code
+                    )),
+                );
                 Ok(fallback)
             }
         }
@@ -431,6 +933,24 @@ fn default_false() -> bool {
     false
 }
 
+/// `{ actionType, actionConfig }` 形式と actionConfig 単体の両方を受け付ける。
+fn parse_stored_action(raw_action: &serde_json::Value) -> Result<ActionConfig, serde_json::Error> {
+    let raw_config = raw_action
+        .get("actionConfig")
+        .cloned()
+        .unwrap_or_else(|| raw_action.clone());
+    serde_json::from_value::<ActionConfigDto>(raw_config).map(Into::into)
+}
+
+/// 着信番号を DID として正規化する。電話番号として解釈できない値（内線名など）はそのまま使う。
+fn normalize_did(called_number: &str) -> Option<String> {
+    let trimmed = called_number.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(normalize_phone_number_e164(trimmed).unwrap_or_else(|_| trimmed.to_string()))
+}
+
 fn is_anonymous(caller_id: &str) -> bool {
     let trimmed = caller_id.trim();
     trimmed.is_empty()
@@ -450,6 +970,7 @@ fn to_action_config_from_registered(row: RegisteredNumberRow) -> ActionConfig {
         announcement_audio_file_url: None,
         scenario_id: None,
         include_announcement: None,
+        schedule_id: None,
     }
 }
 
@@ -465,6 +986,7 @@ fn to_action_config_from_routing_rule(row: RoutingRuleRow) -> ActionConfig {
         announcement_audio_file_url: None,
         scenario_id: None,
         include_announcement: None,
+        schedule_id: None,
     }
 }
 
@@ -472,14 +994,23 @@ fn to_action_config_from_routing_rule(row: RoutingRuleRow) -> ActionConfig {
 mod tests {
     use std::sync::Arc;
 
-    use super::{ActionConfig, ActionConfigDto, RuleEvaluator};
+    use super::{ActionConfig, ActionConfigDto, RuleEvaluator, TraceOutcome};
+    use crate::shared::config::ScheduleRoutingConfig;
+    use crate::shared::entities::CallerIdentity;
     use crate::shared::ports::routing_port::{
-        CallActionRuleRow, IvrDestinationRow, IvrMenuRow, NoopRoutingPort, RegisteredNumberRow,
-        RoutingFuture, RoutingPort, RoutingRuleRow,
+        CallActionRuleRow, IvrDestinationRow, IvrMenuRow, IvrSpeechRouteRow, NoopRoutingPort,
+        RegisteredNumberRow, RoutingFuture, RoutingPort, RoutingRuleRow,
+    };
+    use crate::shared::ports::schedule_port::{
+        Schedule, ScheduleFuture, SchedulePort, ScheduleTimeSlot, UpsertSchedule,
     };
     use serde_json::json;
     use uuid::Uuid;
 
+    fn caller(number: &str) -> CallerIdentity {
+        CallerIdentity::from_number(number)
+    }
+
     #[test]
     fn action_config_dto_parses_announcement_id() {
         let announcement_id = Uuid::now_v7();
@@ -551,7 +1082,7 @@ mod tests {
     async fn evaluate_returns_default_action_when_normalization_fails() {
         let evaluator = RuleEvaluator::new(Arc::new(NoopRoutingPort::new()));
         let action = evaluator
-            .evaluate("abc", "call-123")
+            .evaluate(&caller("abc"), None, "call-123")
             .await
             .expect("normalization failure should fallback to default action");
 
@@ -577,19 +1108,25 @@ mod tests {
         fn find_registered_number(
             &self,
             phone_number: &str,
+            did: Option<&str>,
         ) -> RoutingFuture<Option<RegisteredNumberRow>> {
-            self.noop.find_registered_number(phone_number)
+            self.noop.find_registered_number(phone_number, did)
         }
 
-        fn find_caller_group(&self, phone_number: &str) -> RoutingFuture<Option<Uuid>> {
-            self.noop.find_caller_group(phone_number)
+        fn find_caller_group(
+            &self,
+            phone_number: &str,
+            did: Option<&str>,
+        ) -> RoutingFuture<Option<Uuid>> {
+            self.noop.find_caller_group(phone_number, did)
         }
 
         fn find_call_action_rule(
             &self,
             group_id: Uuid,
+            did: Option<&str>,
         ) -> RoutingFuture<Option<CallActionRuleRow>> {
-            self.noop.find_call_action_rule(group_id)
+            self.noop.find_call_action_rule(group_id, did)
         }
 
         fn is_spam(&self, phone_number: &str) -> RoutingFuture<bool> {
@@ -600,7 +1137,11 @@ mod tests {
             self.noop.is_registered(phone_number)
         }
 
-        fn find_routing_rule(&self, category: &str) -> RoutingFuture<Option<RoutingRuleRow>> {
+        fn find_routing_rule(
+            &self,
+            category: &str,
+            _did: Option<&str>,
+        ) -> RoutingFuture<Option<RoutingRuleRow>> {
             let row = if category == "unknown" {
                 Some(RoutingRuleRow {
                     id: Uuid::now_v7(),
@@ -614,6 +1155,10 @@ mod tests {
             Box::pin(async move { Ok(row) })
         }
 
+        fn find_routing_rule_by_id(&self, rule_id: Uuid) -> RoutingFuture<Option<RoutingRuleRow>> {
+            self.noop.find_routing_rule_by_id(rule_id)
+        }
+
         fn get_system_settings_extra(&self) -> RoutingFuture<Option<serde_json::Value>> {
             let announcement_id = self.default_action_announcement_id;
             let value = json!({
@@ -684,6 +1229,20 @@ mod tests {
         ) -> RoutingFuture<Option<IvrDestinationRow>> {
             self.noop.find_ivr_invalid_destination_by_flow(flow_id)
         }
+
+        fn find_ivr_complete_destination(
+            &self,
+            input_node_id: Uuid,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop.find_ivr_complete_destination(input_node_id)
+        }
+
+        fn find_ivr_speech_routes(
+            &self,
+            input_node_id: Uuid,
+        ) -> RoutingFuture<Vec<IvrSpeechRouteRow>> {
+            self.noop.find_ivr_speech_routes(input_node_id)
+        }
     }
 
     struct GroupPriorityRoutingPort {
@@ -704,8 +1263,10 @@ mod tests {
         fn find_registered_number(
             &self,
             _phone_number: &str,
+            _did: Option<&str>,
         ) -> RoutingFuture<Option<RegisteredNumberRow>> {
             let row = RegisteredNumberRow {
+                id: Uuid::now_v7(),
                 action_code: "VR".to_string(),
                 ivr_flow_id: None,
                 recording_enabled: true,
@@ -716,7 +1277,11 @@ mod tests {
             Box::pin(async move { Ok(Some(row)) })
         }
 
-        fn find_caller_group(&self, _phone_number: &str) -> RoutingFuture<Option<Uuid>> {
+        fn find_caller_group(
+            &self,
+            _phone_number: &str,
+            _did: Option<&str>,
+        ) -> RoutingFuture<Option<Uuid>> {
             let group_id = self.group_id;
             Box::pin(async move { Ok(Some(group_id)) })
         }
@@ -724,6 +1289,7 @@ mod tests {
         fn find_call_action_rule(
             &self,
             _group_id: Uuid,
+            _did: Option<&str>,
         ) -> RoutingFuture<Option<CallActionRuleRow>> {
             let row = CallActionRuleRow {
                 id: Uuid::now_v7(),
@@ -742,8 +1308,16 @@ mod tests {
             self.noop.is_registered(phone_number)
         }
 
-        fn find_routing_rule(&self, category: &str) -> RoutingFuture<Option<RoutingRuleRow>> {
-            self.noop.find_routing_rule(category)
+        fn find_routing_rule(
+            &self,
+            category: &str,
+            did: Option<&str>,
+        ) -> RoutingFuture<Option<RoutingRuleRow>> {
+            self.noop.find_routing_rule(category, did)
+        }
+
+        fn find_routing_rule_by_id(&self, rule_id: Uuid) -> RoutingFuture<Option<RoutingRuleRow>> {
+            self.noop.find_routing_rule_by_id(rule_id)
         }
 
         fn get_system_settings_extra(&self) -> RoutingFuture<Option<serde_json::Value>> {
@@ -806,6 +1380,20 @@ mod tests {
         ) -> RoutingFuture<Option<IvrDestinationRow>> {
             self.noop.find_ivr_invalid_destination_by_flow(flow_id)
         }
+
+        fn find_ivr_complete_destination(
+            &self,
+            input_node_id: Uuid,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop.find_ivr_complete_destination(input_node_id)
+        }
+
+        fn find_ivr_speech_routes(
+            &self,
+            input_node_id: Uuid,
+        ) -> RoutingFuture<Vec<IvrSpeechRouteRow>> {
+            self.noop.find_ivr_speech_routes(input_node_id)
+        }
     }
 
     struct GroupWithoutActiveRulePort {
@@ -828,8 +1416,10 @@ mod tests {
         fn find_registered_number(
             &self,
             _phone_number: &str,
+            _did: Option<&str>,
         ) -> RoutingFuture<Option<RegisteredNumberRow>> {
             let row = RegisteredNumberRow {
+                id: Uuid::now_v7(),
                 action_code: "VR".to_string(),
                 ivr_flow_id: None,
                 recording_enabled: true,
@@ -840,7 +1430,11 @@ mod tests {
             Box::pin(async move { Ok(Some(row)) })
         }
 
-        fn find_caller_group(&self, _phone_number: &str) -> RoutingFuture<Option<Uuid>> {
+        fn find_caller_group(
+            &self,
+            _phone_number: &str,
+            _did: Option<&str>,
+        ) -> RoutingFuture<Option<Uuid>> {
             let group_id = self.group_id;
             Box::pin(async move { Ok(Some(group_id)) })
         }
@@ -848,6 +1442,7 @@ mod tests {
         fn find_call_action_rule(
             &self,
             _group_id: Uuid,
+            _did: Option<&str>,
         ) -> RoutingFuture<Option<CallActionRuleRow>> {
             Box::pin(async move { Ok(None) })
         }
@@ -860,7 +1455,11 @@ mod tests {
             self.noop.is_registered(phone_number)
         }
 
-        fn find_routing_rule(&self, category: &str) -> RoutingFuture<Option<RoutingRuleRow>> {
+        fn find_routing_rule(
+            &self,
+            category: &str,
+            _did: Option<&str>,
+        ) -> RoutingFuture<Option<RoutingRuleRow>> {
             let row = if category == "registered" {
                 Some(RoutingRuleRow {
                     id: Uuid::now_v7(),
@@ -874,6 +1473,10 @@ mod tests {
             Box::pin(async move { Ok(row) })
         }
 
+        fn find_routing_rule_by_id(&self, rule_id: Uuid) -> RoutingFuture<Option<RoutingRuleRow>> {
+            self.noop.find_routing_rule_by_id(rule_id)
+        }
+
         fn get_system_settings_extra(&self) -> RoutingFuture<Option<serde_json::Value>> {
             let announcement_id = self.default_action_announcement_id;
             let value = json!({
@@ -944,6 +1547,20 @@ mod tests {
         ) -> RoutingFuture<Option<IvrDestinationRow>> {
             self.noop.find_ivr_invalid_destination_by_flow(flow_id)
         }
+
+        fn find_ivr_complete_destination(
+            &self,
+            input_node_id: Uuid,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop.find_ivr_complete_destination(input_node_id)
+        }
+
+        fn find_ivr_speech_routes(
+            &self,
+            input_node_id: Uuid,
+        ) -> RoutingFuture<Vec<IvrSpeechRouteRow>> {
+            self.noop.find_ivr_speech_routes(input_node_id)
+        }
     }
 
     #[tokio::test]
@@ -952,7 +1569,7 @@ mod tests {
         let evaluator = RuleEvaluator::new(Arc::new(UnknownRoutingRulePort::new(announcement_id)));
 
         let action = evaluator
-            .evaluate("+81568686236", "call-unknown")
+            .evaluate(&caller("+81568686236"), None, "call-unknown")
             .await
             .expect("unknown should use defaultAction");
 
@@ -967,7 +1584,7 @@ mod tests {
         let evaluator = RuleEvaluator::new(Arc::new(GroupPriorityRoutingPort::new(group_id)));
 
         let action = evaluator
-            .evaluate("+819012345678", "call-group-priority")
+            .evaluate(&caller("+819012345678"), None, "call-group-priority")
             .await
             .expect("group rule should be evaluated before registered number action");
 
@@ -975,6 +1592,62 @@ mod tests {
         assert_eq!(action.caller_category, "registered");
     }
 
+    #[tokio::test]
+    async fn explain_traces_unknown_caller_through_each_stage() {
+        let announcement_id = Uuid::now_v7();
+        let evaluator = RuleEvaluator::new(Arc::new(UnknownRoutingRulePort::new(announcement_id)));
+
+        let explanation = evaluator
+            .explain(&caller("0568-68-6236"), None, utc("2026-02-10T01:00:00Z"))
+            .await
+            .expect("explain should succeed");
+
+        assert_eq!(explanation.action.action_code, "AN");
+        let stages: Vec<_> = explanation
+            .trace
+            .iter()
+            .map(|step| (step.stage, step.outcome))
+            .collect();
+        assert_eq!(
+            stages,
+            vec![
+                ("registered_numbers", TraceOutcome::Miss),
+                ("caller_group", TraceOutcome::Miss),
+                ("category", TraceOutcome::Hit),
+                ("schedule", TraceOutcome::Skip),
+                ("routing_rules", TraceOutcome::Skip),
+                ("default", TraceOutcome::Fallback),
+            ]
+        );
+        assert_eq!(explanation.trace[2].detail.as_deref(), Some("unknown"));
+        assert_eq!(explanation.announcements.len(), 1);
+        assert_eq!(
+            explanation.announcements[0].announcement_id,
+            announcement_id
+        );
+        assert!(!explanation.announcements[0].cached);
+    }
+
+    #[tokio::test]
+    async fn explain_records_matched_row_ids() {
+        let group_id = Uuid::now_v7();
+        let evaluator = RuleEvaluator::new(Arc::new(GroupPriorityRoutingPort::new(group_id)));
+
+        let explanation = evaluator
+            .explain(&caller("+819012345678"), None, utc("2026-02-10T01:00:00Z"))
+            .await
+            .expect("explain should succeed");
+
+        assert_eq!(explanation.action.action_code, "BZ");
+        assert_eq!(explanation.trace.len(), 2);
+        assert_eq!(explanation.trace[0].stage, "registered_numbers");
+        assert_eq!(explanation.trace[0].outcome, TraceOutcome::Miss);
+        assert!(explanation.trace[0].row_id.is_some());
+        assert_eq!(explanation.trace[1].stage, "caller_group");
+        assert_eq!(explanation.trace[1].outcome, TraceOutcome::Hit);
+        assert!(explanation.trace[1].row_id.is_some());
+    }
+
     #[tokio::test]
     async fn evaluate_falls_back_to_default_action_when_group_has_no_active_rule() {
         let group_id = Uuid::now_v7();
@@ -985,7 +1658,7 @@ mod tests {
         )));
 
         let action = evaluator
-            .evaluate("+819012345678", "call-group-disabled")
+            .evaluate(&caller("+819012345678"), None, "call-group-disabled")
             .await
             .expect("missing active group rule should fallback to defaultAction");
 
@@ -993,4 +1666,352 @@ mod tests {
         assert_eq!(action.caller_category, "unknown");
         assert_eq!(action.announcement_id, Some(announcement_id));
     }
+
+    struct FixedSchedulePort {
+        schedules: Vec<Schedule>,
+    }
+
+    impl SchedulePort for FixedSchedulePort {
+        fn list_active(&self) -> ScheduleFuture<Vec<Schedule>> {
+            let schedules = self.schedules.clone();
+            Box::pin(async move { Ok(schedules) })
+        }
+
+        fn upsert_schedule(&self, _schedule: UpsertSchedule) -> ScheduleFuture<()> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    /// 平日 18:00-09:00（JST）は閉局アナウンス
+    fn after_hours_schedule(announcement_id: Uuid) -> Schedule {
+        let id = Uuid::now_v7();
+        let now = chrono::Utc::now();
+        Schedule {
+            id,
+            name: "after-hours".to_string(),
+            description: None,
+            schedule_type: "business".to_string(),
+            is_active: true,
+            folder_id: None,
+            date_range_start: None,
+            date_range_end: None,
+            action_type: "closed".to_string(),
+            action_target: Some(announcement_id),
+            action_code: None,
+            version: 1,
+            created_at: now,
+            updated_at: now,
+            time_slots: (1..=5)
+                .map(|day| ScheduleTimeSlot {
+                    id: Uuid::now_v7(),
+                    schedule_id: id,
+                    day_of_week: Some(day),
+                    start_time: chrono::NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+                    end_time: chrono::NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+                    created_at: now,
+                })
+                .collect(),
+        }
+    }
+
+    /// 既定では無効のスケジュール段を JST で有効にする
+    fn schedule_routing_enabled() -> ScheduleRoutingConfig {
+        ScheduleRoutingConfig {
+            enabled: true,
+            utc_offset: chrono::FixedOffset::east_opt(9 * 60 * 60).unwrap(),
+        }
+    }
+
+    fn utc(rfc3339: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(rfc3339)
+            .expect("valid timestamp")
+            .with_timezone(&chrono::Utc)
+    }
+
+    #[tokio::test]
+    async fn evaluate_applies_matching_schedule_and_records_schedule_id() {
+        let announcement_id = Uuid::now_v7();
+        let schedule = after_hours_schedule(announcement_id);
+        let schedule_id = schedule.id;
+        let evaluator = RuleEvaluator::new(Arc::new(NoopRoutingPort::new()))
+            .with_schedule_port(Arc::new(FixedSchedulePort {
+                schedules: vec![schedule],
+            }))
+            .with_schedule_routing(schedule_routing_enabled());
+
+        // 2026-03-03(火) 22:00 JST
+        let action = evaluator
+            .evaluate_at(
+                &caller("09012345678"),
+                None,
+                "call-night",
+                utc("2026-03-03T13:00:00Z"),
+            )
+            .await
+            .expect("schedule action");
+        assert_eq!(action.action_code, "AN");
+        assert_eq!(action.announcement_id, Some(announcement_id));
+        assert_eq!(action.schedule_id, Some(schedule_id));
+
+        // 2026-03-03(火) 10:00 JST は営業時間内 → 既定アクション
+        let action = evaluator
+            .evaluate_at(
+                &caller("09012345678"),
+                None,
+                "call-day",
+                utc("2026-03-03T01:00:00Z"),
+            )
+            .await
+            .expect("default action");
+        assert_eq!(action.action_code, "VR");
+        assert_eq!(action.schedule_id, None);
+    }
+
+    #[tokio::test]
+    async fn evaluate_prefers_caller_specific_rule_over_schedule() {
+        let group_id = Uuid::now_v7();
+        let evaluator = RuleEvaluator::new(Arc::new(GroupPriorityRoutingPort::new(group_id)))
+            .with_schedule_port(Arc::new(FixedSchedulePort {
+                schedules: vec![after_hours_schedule(Uuid::now_v7())],
+            }))
+            .with_schedule_routing(schedule_routing_enabled());
+
+        let action = evaluator
+            .evaluate_at(
+                &caller("09012345678"),
+                None,
+                "call-vip",
+                utc("2026-03-03T13:00:00Z"),
+            )
+            .await
+            .expect("group action");
+        assert_eq!(action.schedule_id, None);
+        assert_eq!(action.caller_category, "registered");
+    }
+
+    const SUPPORT_DID: &str = "+81312345678";
+    const SUPPORT_VIP: &str = "+819011112222";
+
+    /// サポート窓口（SUPPORT_DID）宛てにだけ登録番号と既定アクションを持つポート
+    struct DidRoutingPort {
+        noop: NoopRoutingPort,
+    }
+
+    impl RoutingPort for DidRoutingPort {
+        fn find_registered_number(
+            &self,
+            phone_number: &str,
+            did: Option<&str>,
+        ) -> RoutingFuture<Option<RegisteredNumberRow>> {
+            let row = (phone_number == SUPPORT_VIP && did == Some(SUPPORT_DID)).then(|| {
+                RegisteredNumberRow {
+                    id: Uuid::now_v7(),
+                    action_code: "VB".to_string(),
+                    ivr_flow_id: None,
+                    recording_enabled: true,
+                    announce_enabled: false,
+                    announcement_id: None,
+                    group_id: None,
+                }
+            });
+            Box::pin(async move { Ok(row) })
+        }
+
+        fn find_caller_group(
+            &self,
+            phone_number: &str,
+            did: Option<&str>,
+        ) -> RoutingFuture<Option<Uuid>> {
+            self.noop.find_caller_group(phone_number, did)
+        }
+
+        fn find_call_action_rule(
+            &self,
+            group_id: Uuid,
+            did: Option<&str>,
+        ) -> RoutingFuture<Option<CallActionRuleRow>> {
+            self.noop.find_call_action_rule(group_id, did)
+        }
+
+        fn is_spam(&self, phone_number: &str) -> RoutingFuture<bool> {
+            self.noop.is_spam(phone_number)
+        }
+
+        fn is_registered(&self, phone_number: &str) -> RoutingFuture<bool> {
+            self.noop.is_registered(phone_number)
+        }
+
+        fn find_routing_rule(
+            &self,
+            category: &str,
+            did: Option<&str>,
+        ) -> RoutingFuture<Option<RoutingRuleRow>> {
+            self.noop.find_routing_rule(category, did)
+        }
+
+        fn find_routing_rule_by_id(&self, rule_id: Uuid) -> RoutingFuture<Option<RoutingRuleRow>> {
+            self.noop.find_routing_rule_by_id(rule_id)
+        }
+
+        fn get_system_settings_extra(&self) -> RoutingFuture<Option<serde_json::Value>> {
+            let value = json!({
+                "defaultAction": {
+                    "actionType": "allow",
+                    "actionConfig": { "actionCode": "VR" }
+                },
+                "didDefaultActions": {
+                    SUPPORT_DID: {
+                        "actionType": "allow",
+                        "actionConfig": { "actionCode": "IV" }
+                    }
+                }
+            });
+            Box::pin(async move { Ok(Some(value)) })
+        }
+
+        fn find_announcement_audio_file_url(
+            &self,
+            announcement_id: Uuid,
+        ) -> RoutingFuture<Option<String>> {
+            self.noop.find_announcement_audio_file_url(announcement_id)
+        }
+
+        fn find_ivr_menu(&self, flow_id: Uuid) -> RoutingFuture<Option<IvrMenuRow>> {
+            self.noop.find_ivr_menu(flow_id)
+        }
+
+        fn find_ivr_dtmf_destination(
+            &self,
+            keypad_node_id: Uuid,
+            dtmf_key: &str,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop
+                .find_ivr_dtmf_destination(keypad_node_id, dtmf_key)
+        }
+
+        fn find_ivr_dtmf_destination_by_flow(
+            &self,
+            flow_id: Uuid,
+            dtmf_key: &str,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop
+                .find_ivr_dtmf_destination_by_flow(flow_id, dtmf_key)
+        }
+
+        fn find_ivr_timeout_destination(
+            &self,
+            keypad_node_id: Uuid,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop.find_ivr_timeout_destination(keypad_node_id)
+        }
+
+        fn find_ivr_timeout_destination_by_flow(
+            &self,
+            flow_id: Uuid,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop.find_ivr_timeout_destination_by_flow(flow_id)
+        }
+
+        fn find_ivr_invalid_destination(
+            &self,
+            keypad_node_id: Uuid,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop.find_ivr_invalid_destination(keypad_node_id)
+        }
+
+        fn find_ivr_invalid_destination_by_flow(
+            &self,
+            flow_id: Uuid,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop.find_ivr_invalid_destination_by_flow(flow_id)
+        }
+
+        fn find_ivr_complete_destination(
+            &self,
+            input_node_id: Uuid,
+        ) -> RoutingFuture<Option<IvrDestinationRow>> {
+            self.noop.find_ivr_complete_destination(input_node_id)
+        }
+
+        fn find_ivr_speech_routes(
+            &self,
+            input_node_id: Uuid,
+        ) -> RoutingFuture<Vec<IvrSpeechRouteRow>> {
+            self.noop.find_ivr_speech_routes(input_node_id)
+        }
+    }
+
+    fn did_evaluator() -> RuleEvaluator {
+        RuleEvaluator::new(Arc::new(DidRoutingPort {
+            noop: NoopRoutingPort::new(),
+        }))
+    }
+
+    #[tokio::test]
+    async fn evaluate_applies_registered_number_only_on_its_did() {
+        let evaluator = did_evaluator();
+
+        // To ユーザ部は国内表記でも DID として正規化される
+        let action = evaluator
+            .evaluate(&caller(SUPPORT_VIP), Some("0312345678"), "call-support-vip")
+            .await
+            .expect("did-specific registered number");
+        assert_eq!(action.action_code, "VB");
+        assert_eq!(action.caller_category, "registered");
+
+        let action = evaluator
+            .evaluate(&caller(SUPPORT_VIP), Some("+81398765432"), "call-main-vip")
+            .await
+            .expect("main line default action");
+        assert_eq!(action.action_code, "VR");
+    }
+
+    #[tokio::test]
+    async fn evaluate_falls_back_to_did_default_action() {
+        let evaluator = did_evaluator();
+
+        let action = evaluator
+            .evaluate(&caller("+819099990000"), Some(SUPPORT_DID), "call-support")
+            .await
+            .expect("did default action");
+        assert_eq!(action.action_code, "IV");
+        assert_eq!(action.caller_category, "unknown");
+
+        let action = evaluator
+            .evaluate(&caller("+819099990000"), None, "call-no-did")
+            .await
+            .expect("global default action");
+        assert_eq!(action.action_code, "VR");
+    }
+
+    #[tokio::test]
+    async fn evaluate_routes_withheld_caller_by_asserted_identity() {
+        let evaluator = did_evaluator();
+
+        // From は非通知でも PAI の番号で登録番号に一致する
+        let withheld_vip = CallerIdentity {
+            display_number: Some("anonymous".to_string()),
+            asserted_number: Some(SUPPORT_VIP.to_string()),
+            privacy: true,
+            ..CallerIdentity::default()
+        };
+        let action = evaluator
+            .evaluate(&withheld_vip, Some(SUPPORT_DID), "call-withheld-vip")
+            .await
+            .expect("asserted identity matches registered number");
+        assert_eq!(action.action_code, "VB");
+
+        // どのルールにも該当しない非通知要求は既定アクションではなく anonymousAction
+        let withheld_unknown = CallerIdentity {
+            asserted_number: Some("+819099990000".to_string()),
+            privacy: true,
+            ..CallerIdentity::from_number("anonymous")
+        };
+        let action = evaluator
+            .evaluate(&withheld_unknown, Some(SUPPORT_DID), "call-withheld")
+            .await
+            .expect("anonymous action");
+        assert_eq!(action.caller_category, "anonymous");
+        assert_eq!(action.action_code, "BZ");
+    }
 }
@@ -1,7 +1,11 @@
 mod evaluator;
 mod executor;
+mod schedule;
 
-pub use evaluator::{ActionConfig, RoutingError, RuleEvaluator};
+pub use evaluator::{
+    ActionConfig, AnnouncementResolution, RoutingError, RoutingExplanation, RoutingTraceStep,
+    RuleEvaluator, TraceOutcome,
+};
 pub use executor::ActionExecutor;
 
 pub fn normalize_phone_number_e164(phone_number: &str) -> Result<String, RoutingError> {
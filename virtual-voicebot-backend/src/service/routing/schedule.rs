@@ -0,0 +1,294 @@
+//! スケジュール（営業時間・休日・特別日）の判定。
+//! DB から取得した有効スケジュールのうち、着信時刻（ローカル時刻）に該当するものを 1 件選ぶ。
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use uuid::Uuid;
+
+use crate::shared::ports::schedule_port::{Schedule, ScheduleTimeSlot};
+
+/// スケジュールが指示するアクション
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ScheduleAction {
+    /// `action_target` の routing_rules を適用する（見つからなければ `fallback_code`）
+    RoutingRule {
+        rule_id: Uuid,
+        fallback_code: Option<String>,
+    },
+    /// アクションコードを直接適用する
+    Code {
+        action_code: String,
+        announcement_id: Option<Uuid>,
+    },
+    /// 通常の評価を継続する（route で転送先の指定がない場合）
+    Continue,
+}
+
+/// `now` に該当するスケジュールを優先順位に従って 1 件返す。
+///
+/// 優先順位: override > holiday > special > business。
+/// 同じ種別では期間指定（date_range）ありを優先し、次に更新日時が新しいものを優先する。
+pub(super) fn resolve_active_schedule(
+    schedules: &[Schedule],
+    now: NaiveDateTime,
+) -> Option<&Schedule> {
+    schedules
+        .iter()
+        .filter(|schedule| schedule.is_active && schedule_matches(schedule, now))
+        .min_by(|a, b| {
+            type_rank(&a.schedule_type)
+                .cmp(&type_rank(&b.schedule_type))
+                .then_with(|| has_date_range(b).cmp(&has_date_range(a)))
+                .then_with(|| b.updated_at.cmp(&a.updated_at))
+                .then_with(|| a.id.cmp(&b.id))
+        })
+}
+
+pub(super) fn schedule_action(schedule: &Schedule) -> ScheduleAction {
+    let action_code = schedule
+        .action_code
+        .as_deref()
+        .map(str::trim)
+        .filter(|code| !code.is_empty())
+        .map(str::to_ascii_uppercase);
+    match schedule.action_type.as_str() {
+        "route" => match (schedule.action_target, action_code) {
+            (Some(rule_id), fallback_code) => ScheduleAction::RoutingRule {
+                rule_id,
+                fallback_code,
+            },
+            (None, Some(action_code)) => ScheduleAction::Code {
+                action_code,
+                announcement_id: None,
+            },
+            (None, None) => ScheduleAction::Continue,
+        },
+        "voicemail" => ScheduleAction::Code {
+            action_code: action_code.unwrap_or_else(|| "VM".to_string()),
+            announcement_id: schedule.action_target,
+        },
+        "announcement" => ScheduleAction::Code {
+            action_code: action_code.unwrap_or_else(|| "AN".to_string()),
+            announcement_id: schedule.action_target,
+        },
+        // closed: 案内があれば再生して切断、なければ拒否
+        _ => ScheduleAction::Code {
+            action_code: action_code.unwrap_or_else(|| {
+                if schedule.action_target.is_some() {
+                    "AN".to_string()
+                } else {
+                    "RJ".to_string()
+                }
+            }),
+            announcement_id: schedule.action_target,
+        },
+    }
+}
+
+fn type_rank(schedule_type: &str) -> u8 {
+    match schedule_type {
+        "override" => 0,
+        "holiday" => 1,
+        "special" => 2,
+        _ => 3,
+    }
+}
+
+fn has_date_range(schedule: &Schedule) -> bool {
+    schedule.date_range_start.is_some() || schedule.date_range_end.is_some()
+}
+
+fn schedule_matches(schedule: &Schedule, now: NaiveDateTime) -> bool {
+    let date = now.date();
+    if schedule.time_slots.is_empty() {
+        // 時間帯の指定がなければ期間内は終日該当（休日など）
+        return in_date_range(schedule, date);
+    }
+    let time = now.time();
+    schedule
+        .time_slots
+        .iter()
+        .any(|slot| slot_matches(schedule, slot, date, time))
+}
+
+fn slot_matches(
+    schedule: &Schedule,
+    slot: &ScheduleTimeSlot,
+    date: NaiveDate,
+    time: NaiveTime,
+) -> bool {
+    let starts_on = |day: NaiveDate| in_date_range(schedule, day) && day_matches(slot, day);
+    if slot.start_time < slot.end_time {
+        return starts_on(date) && slot.start_time <= time && time < slot.end_time;
+    }
+    // 日跨ぎ（例: 18:00-09:00）。終了側は前日の枠として扱う。
+    if starts_on(date) && time >= slot.start_time {
+        return true;
+    }
+    let previous = date - Duration::days(1);
+    starts_on(previous) && time < slot.end_time
+}
+
+fn day_matches(slot: &ScheduleTimeSlot, date: NaiveDate) -> bool {
+    match slot.day_of_week {
+        // 0 = 日曜（フロントエンドの表記に合わせる）
+        Some(day) => i64::from(day) == i64::from(date.weekday().num_days_from_sunday()),
+        None => true,
+    }
+}
+
+fn in_date_range(schedule: &Schedule, date: NaiveDate) -> bool {
+    if let Some(start) = schedule.date_range_start {
+        if date < start {
+            return false;
+        }
+    }
+    if let Some(end) = schedule.date_range_end {
+        if date > end {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y-%m-%d %H:%M")
+            .expect("valid datetime")
+    }
+
+    fn time(value: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(value, "%H:%M").expect("valid time")
+    }
+
+    fn schedule(
+        schedule_type: &str,
+        action_type: &str,
+        slots: &[(Option<i16>, &str, &str)],
+    ) -> Schedule {
+        let id = Uuid::now_v7();
+        let created_at = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        Schedule {
+            id,
+            name: format!("{schedule_type}-{action_type}"),
+            description: None,
+            schedule_type: schedule_type.to_string(),
+            is_active: true,
+            folder_id: None,
+            date_range_start: None,
+            date_range_end: None,
+            action_type: action_type.to_string(),
+            action_target: None,
+            action_code: None,
+            version: 1,
+            created_at,
+            updated_at: created_at,
+            time_slots: slots
+                .iter()
+                .map(|(day_of_week, start, end)| ScheduleTimeSlot {
+                    id: Uuid::now_v7(),
+                    schedule_id: id,
+                    day_of_week: *day_of_week,
+                    start_time: time(start),
+                    end_time: time(end),
+                    created_at,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn weekly_slot_matches_only_on_listed_weekday_and_hours() {
+        // 2026-03-02 は月曜
+        let business = schedule("business", "route", &[(Some(1), "09:00", "18:00")]);
+        let schedules = vec![business];
+        assert!(resolve_active_schedule(&schedules, at("2026-03-02", "09:00")).is_some());
+        assert!(resolve_active_schedule(&schedules, at("2026-03-02", "18:00")).is_none());
+        assert!(resolve_active_schedule(&schedules, at("2026-03-03", "10:00")).is_none());
+    }
+
+    #[test]
+    fn overnight_slot_spans_into_next_day() {
+        // 金曜 18:00 から土曜 09:00 まで
+        let after_hours = schedule("business", "closed", &[(Some(5), "18:00", "09:00")]);
+        let schedules = vec![after_hours];
+        assert!(resolve_active_schedule(&schedules, at("2026-03-06", "23:30")).is_some());
+        assert!(resolve_active_schedule(&schedules, at("2026-03-07", "08:59")).is_some());
+        assert!(resolve_active_schedule(&schedules, at("2026-03-07", "09:00")).is_none());
+        assert!(
+            resolve_active_schedule(&schedules, at("2026-03-06", "08:00")).is_none(),
+            "morning of the start weekday belongs to the previous day's slot"
+        );
+    }
+
+    #[test]
+    fn holiday_and_date_range_exceptions_take_precedence_over_weekly() {
+        let business = schedule("business", "route", &[(None, "09:00", "18:00")]);
+        let mut holiday = schedule("holiday", "closed", &[]);
+        holiday.date_range_start = NaiveDate::from_ymd_opt(2026, 5, 3);
+        holiday.date_range_end = NaiveDate::from_ymd_opt(2026, 5, 5);
+        let mut special = schedule("business", "announcement", &[(None, "09:00", "12:00")]);
+        special.date_range_start = NaiveDate::from_ymd_opt(2026, 3, 10);
+        special.date_range_end = NaiveDate::from_ymd_opt(2026, 3, 10);
+        let schedules = vec![business.clone(), holiday.clone(), special.clone()];
+
+        let hit = resolve_active_schedule(&schedules, at("2026-05-04", "10:00")).unwrap();
+        assert_eq!(hit.id, holiday.id);
+        let hit = resolve_active_schedule(&schedules, at("2026-03-10", "10:00")).unwrap();
+        assert_eq!(hit.id, special.id, "date-ranged schedule beats weekly one");
+        let hit = resolve_active_schedule(&schedules, at("2026-03-10", "13:00")).unwrap();
+        assert_eq!(hit.id, business.id);
+        assert!(resolve_active_schedule(&schedules, at("2026-05-06", "20:00")).is_none());
+    }
+
+    #[test]
+    fn inactive_schedule_is_ignored() {
+        let mut closed = schedule("override", "closed", &[]);
+        closed.is_active = false;
+        assert!(resolve_active_schedule(&[closed], at("2026-03-02", "10:00")).is_none());
+    }
+
+    #[test]
+    fn schedule_action_maps_action_type_to_code() {
+        let announcement_id = Uuid::now_v7();
+        let mut closed = schedule("business", "closed", &[]);
+        assert_eq!(
+            schedule_action(&closed),
+            ScheduleAction::Code {
+                action_code: "RJ".to_string(),
+                announcement_id: None
+            }
+        );
+        closed.action_target = Some(announcement_id);
+        assert_eq!(
+            schedule_action(&closed),
+            ScheduleAction::Code {
+                action_code: "AN".to_string(),
+                announcement_id: Some(announcement_id)
+            }
+        );
+
+        let mut voicemail = schedule("business", "voicemail", &[]);
+        voicemail.action_code = Some("vm".to_string());
+        assert!(matches!(
+            schedule_action(&voicemail),
+            ScheduleAction::Code { ref action_code, .. } if action_code == "VM"
+        ));
+
+        let mut route = schedule("business", "route", &[]);
+        assert_eq!(schedule_action(&route), ScheduleAction::Continue);
+        let rule_id = Uuid::now_v7();
+        route.action_target = Some(rule_id);
+        assert_eq!(
+            schedule_action(&route),
+            ScheduleAction::RoutingRule {
+                rule_id,
+                fallback_code: None
+            }
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! ルーター設定（intent_router.yaml）とプロンプトのホットリロード。
+//!
+//! 新しい通話は開始時点のスナップショットを `pin_call` で固定し、通話中に再読込されても
+//! 同じ設定・プロンプトを使い続ける。再読込で検証に失敗した場合は現行のスナップショットを維持する。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+use log::{info, warn};
+use thiserror::Error;
+
+use crate::service::ai::{self, intent, llm, weather};
+use crate::service::call_control::{
+    self,
+    router::{self, RouterConfig},
+};
+
+/// ある時点で有効なルーター設定とプロンプト一式
+#[derive(Debug, Clone)]
+pub struct RuntimeSnapshot {
+    /// 再読込のたびに増える世代番号（起動時は 1）
+    pub generation: u64,
+    pub router: RouterConfig,
+    pub system_prompt: String,
+    pub intent_prompt: String,
+    pub weather_prompt: String,
+}
+
+#[derive(Debug, Error)]
+pub enum ReloadError {
+    #[error("router config {path}: {reason}")]
+    RouterConfig { path: String, reason: String },
+    #[error("prompt file {path}: {reason}")]
+    Prompt { path: String, reason: String },
+}
+
+/// 現行スナップショットと通話ごとに固定したスナップショットを持つ
+#[derive(Debug)]
+struct SnapshotStore {
+    current: RwLock<Arc<RuntimeSnapshot>>,
+    pinned: Mutex<HashMap<String, Arc<RuntimeSnapshot>>>,
+    /// 再読込を直列化して世代番号の飛びを防ぐ
+    reload_lock: Mutex<()>,
+}
+
+impl SnapshotStore {
+    fn new(initial: RuntimeSnapshot) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(initial)),
+            pinned: Mutex::new(HashMap::new()),
+            reload_lock: Mutex::new(()),
+        }
+    }
+
+    fn current(&self) -> Arc<RuntimeSnapshot> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn pin(&self, call_id: &str) -> Arc<RuntimeSnapshot> {
+        let snapshot = self.current();
+        self.pinned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(call_id.to_string(), snapshot.clone());
+        snapshot
+    }
+
+    fn release(&self, call_id: &str) {
+        self.pinned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(call_id);
+    }
+
+    fn for_call(&self, call_id: &str) -> Arc<RuntimeSnapshot> {
+        let pinned = self
+            .pinned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(call_id)
+            .cloned();
+        pinned.unwrap_or_else(|| self.current())
+    }
+
+    /// `load` が成功したときだけ入れ替える。失敗時は現行のまま（ロールバック）
+    fn reload_with<F>(&self, load: F) -> Result<Arc<RuntimeSnapshot>, ReloadError>
+    where
+        F: FnOnce(u64) -> Result<RuntimeSnapshot, ReloadError>,
+    {
+        let _guard = self.reload_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let generation = self.current().generation + 1;
+        let snapshot = Arc::new(load(generation)?);
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = snapshot.clone();
+        Ok(snapshot)
+    }
+}
+
+static STORE: OnceLock<SnapshotStore> = OnceLock::new();
+
+fn store() -> &'static SnapshotStore {
+    STORE.get_or_init(|| SnapshotStore::new(initial_snapshot()))
+}
+
+/// 新しい通話に適用される現行スナップショット
+pub fn current() -> Arc<RuntimeSnapshot> {
+    store().current()
+}
+
+/// 通話に固定したスナップショット（未固定なら現行）
+pub fn for_call(call_id: &str) -> Arc<RuntimeSnapshot> {
+    store().for_call(call_id)
+}
+
+/// 通話開始時に現行スナップショットを固定する
+pub fn pin_call(call_id: &str) -> Arc<RuntimeSnapshot> {
+    store().pin(call_id)
+}
+
+/// 通話終了時に固定を解除する
+pub fn release_call(call_id: &str) {
+    store().release(call_id)
+}
+
+/// 設定ファイルを読み直して検証し、問題がなければ新しい通話向けに入れ替える。
+/// `trigger` はログ用（file / sighup / http）
+pub fn reload(trigger: &str) -> Result<Arc<RuntimeSnapshot>, ReloadError> {
+    match store().reload_with(load_snapshot) {
+        Ok(snapshot) => {
+            info!(
+                "[runtime_config] reloaded generation={} trigger={}",
+                snapshot.generation, trigger
+            );
+            // 変更された定型文を新しい通話の前に合成しておく
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                handle.spawn(ai::warm_up_phrase_cache(call_control::fixed_phrases()));
+            }
+            Ok(snapshot)
+        }
+        Err(err) => {
+            warn!(
+                "[runtime_config] reload rejected trigger={}, keeping generation={}: {}",
+                trigger,
+                current().generation,
+                err
+            );
+            Err(err)
+        }
+    }
+}
+
+/// 監視対象ファイルの更新時刻をポーリングし、変化があれば再読込する
+pub fn spawn_file_watcher(poll_interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last = watched_mtimes();
+        let mut ticker = tokio::time::interval(poll_interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let mtimes = watched_mtimes();
+            if mtimes != last {
+                last = mtimes;
+                let _ = reload("file");
+            }
+        }
+    })
+}
+
+/// SIGHUP を受けるたびに再読込する
+#[cfg(unix)]
+pub fn spawn_sighup_listener() -> std::io::Result<tokio::task::JoinHandle<()>> {
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+    Ok(tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            let _ = reload("sighup");
+        }
+    }))
+}
+
+fn load_snapshot(generation: u64) -> Result<RuntimeSnapshot, ReloadError> {
+    Ok(RuntimeSnapshot {
+        generation,
+        router: router::load_router_config()?,
+        system_prompt: llm::load_system_prompt()?,
+        intent_prompt: intent::load_intent_prompt()?,
+        weather_prompt: weather::load_weather_prompt()?,
+    })
+}
+
+/// 起動時は読めない項目だけ既定値にして起動を続ける
+fn initial_snapshot() -> RuntimeSnapshot {
+    fn or_default<T>(loaded: Result<T, ReloadError>, default: impl FnOnce() -> T) -> T {
+        loaded.unwrap_or_else(|err| {
+            warn!("[runtime_config] {}. Using default.", err);
+            default()
+        })
+    }
+    RuntimeSnapshot {
+        generation: 1,
+        router: or_default(router::load_router_config(), RouterConfig::default),
+        system_prompt: or_default(llm::load_system_prompt(), llm::default_system_prompt),
+        intent_prompt: or_default(intent::load_intent_prompt(), intent::default_intent_prompt),
+        weather_prompt: or_default(
+            weather::load_weather_prompt(),
+            weather::default_weather_prompt,
+        ),
+    }
+}
+
+fn watched_paths() -> Vec<PathBuf> {
+    let mut paths = vec![router::config_path()];
+    for name in [
+        llm::PROMPT_FILE_NAME,
+        intent::INTENT_PROMPT_FILE_NAME,
+        intent::INTENT_PROMPT_EXAMPLE,
+        weather::WEATHER_PROMPT_FILE_NAME,
+        weather::WEATHER_PROMPT_EXAMPLE,
+    ] {
+        paths.extend(prompt_paths(name));
+    }
+    paths
+}
+
+fn watched_mtimes() -> Vec<Option<SystemTime>> {
+    watched_paths()
+        .iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// プロンプトファイルの探索先（カレントディレクトリ、実行ファイルのディレクトリの順）
+fn prompt_paths(name: &str) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from(name)];
+    if let Some(dir) = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|d| d.to_path_buf()))
+    {
+        paths.push(dir.join(name));
+    }
+    paths
+}
+
+/// プロンプトの上書きファイルを読む。存在しない・空なら None、読み込み失敗はエラー
+pub(crate) fn read_prompt_override(name: &str) -> Result<Option<String>, ReloadError> {
+    for path in prompt_paths(name) {
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                let trimmed = text.trim();
+                if !trimmed.is_empty() {
+                    return Ok(Some(trimmed.to_string()));
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(ReloadError::Prompt {
+                    path: path.display().to_string(),
+                    reason: err.to_string(),
+                })
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(generation: u64, system_prompt: &str) -> RuntimeSnapshot {
+        RuntimeSnapshot {
+            generation,
+            router: RouterConfig::default(),
+            system_prompt: system_prompt.to_string(),
+            intent_prompt: "intent".to_string(),
+            weather_prompt: "weather".to_string(),
+        }
+    }
+
+    #[test]
+    fn pinned_call_keeps_snapshot_across_reload() {
+        let store = SnapshotStore::new(snapshot(1, "old"));
+        store.pin("call-a");
+
+        let reloaded = store
+            .reload_with(|generation| Ok(snapshot(generation, "new")))
+            .expect("reload should succeed");
+
+        assert_eq!(reloaded.generation, 2);
+        assert_eq!(store.for_call("call-a").system_prompt, "old");
+        assert_eq!(store.for_call("call-b").system_prompt, "new");
+        store.release("call-a");
+        assert_eq!(store.for_call("call-a").system_prompt, "new");
+    }
+
+    #[test]
+    fn failed_reload_keeps_current_snapshot() {
+        let store = SnapshotStore::new(snapshot(1, "old"));
+
+        let err = store
+            .reload_with(|_| {
+                Err(ReloadError::RouterConfig {
+                    path: "intent_router.yaml".to_string(),
+                    reason: "invalid yaml".to_string(),
+                })
+            })
+            .expect_err("invalid config should be rejected");
+
+        assert!(err.to_string().contains("invalid yaml"));
+        assert_eq!(store.current().generation, 1);
+        assert_eq!(store.current().system_prompt, "old");
+    }
+}
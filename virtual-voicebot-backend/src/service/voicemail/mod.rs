@@ -0,0 +1,531 @@
+//! 留守番電話の後処理（メールボックスへの保存・文字起こし・新着通知）。
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::FixedOffset;
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use log::{info, warn};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::service::routing::normalize_phone_number_e164;
+use crate::shared::audio::{decimation_step_to_8k, linear16_to_mulaw};
+use crate::shared::config;
+use crate::shared::ports::ai::{AsrChunk, AsrPort};
+use crate::shared::ports::notification::{VoicemailNotice, VoicemailNotifier};
+use crate::shared::ports::voicemail_port::{
+    NewVoicemailMessage, RecordedVoicemail, VoicemailError, VoicemailMailbox, VoicemailPort,
+};
+use crate::shared::ports::webhook_port::{
+    NoopWebhookPublisher, WebhookEvent, WebhookEventType, WebhookPublisher,
+};
+
+const SAMPLE_RATE: u32 = 8000;
+/// ASR へ渡す 1 チャンクのサンプル数（1 秒）
+const ASR_CHUNK_SAMPLES: usize = SAMPLE_RATE as usize;
+
+#[derive(Debug, Error)]
+pub enum VoicemailProcessError {
+    #[error(transparent)]
+    Port(#[from] VoicemailError),
+    #[error("audio failed: {0}")]
+    Audio(String),
+}
+
+pub struct VoicemailService {
+    port: Arc<dyn VoicemailPort>,
+    asr: Arc<dyn AsrPort>,
+    /// LINE・チャット・メールの通知チャネル（メールボックスの notify_line が有効な時だけ）
+    channel_notifier: Arc<dyn VoicemailNotifier>,
+    webhook_notifier: Arc<dyn VoicemailNotifier>,
+    /// 外部 webhook の購読（webhooks.yaml）への voicemail イベント
+    event_webhooks: Arc<dyn WebhookPublisher>,
+    dir: PathBuf,
+    min_duration_sec: u64,
+    transcription_enabled: bool,
+}
+
+impl VoicemailService {
+    pub fn new(
+        port: Arc<dyn VoicemailPort>,
+        asr: Arc<dyn AsrPort>,
+        channel_notifier: Arc<dyn VoicemailNotifier>,
+        webhook_notifier: Arc<dyn VoicemailNotifier>,
+    ) -> Self {
+        let cfg = config::voicemail_config();
+        Self {
+            port,
+            asr,
+            channel_notifier,
+            webhook_notifier,
+            event_webhooks: Arc::new(NoopWebhookPublisher::new()),
+            dir: PathBuf::from(&cfg.dir),
+            min_duration_sec: cfg.min_duration_sec,
+            transcription_enabled: cfg.transcription_enabled,
+        }
+    }
+
+    pub fn with_event_webhooks(mut self, event_webhooks: Arc<dyn WebhookPublisher>) -> Self {
+        self.event_webhooks = event_webhooks;
+        self
+    }
+
+    /// 通話録音から発信者側の音声を切り出してメールボックスに保存し、文字起こしと通知を行う。
+    /// 保存したメッセージ ID を返す（対象メールボックスがない・短すぎる録音は None）。
+    pub async fn process_recording(
+        &self,
+        recorded: RecordedVoicemail,
+    ) -> Result<Option<Uuid>, VoicemailProcessError> {
+        let did = recorded.called_number.as_deref().and_then(normalize_did);
+        let Some(mailbox) = self.port.find_mailbox_for_did(did.as_deref()).await? else {
+            warn!(
+                "[voicemail] call_log_id={} no mailbox for did={:?}, message discarded",
+                recorded.call_log_id, did
+            );
+            return Ok(None);
+        };
+
+        let message_id = Uuid::now_v7();
+        let file_path = self
+            .dir
+            .join(&mailbox.mailbox_number)
+            .join(format!("{message_id}.wav"));
+        let source = PathBuf::from(&recorded.recording_path);
+        let dest = file_path.clone();
+        let samples = tokio::task::spawn_blocking(move || extract_caller_channel(&source, &dest))
+            .await
+            .map_err(|e| VoicemailProcessError::Audio(e.to_string()))??;
+
+        let duration_sec = samples.len() / SAMPLE_RATE as usize;
+        if (duration_sec as u64) < self.min_duration_sec {
+            info!(
+                "[voicemail] call_log_id={} recording too short ({}s), message discarded",
+                recorded.call_log_id, duration_sec
+            );
+            let _ = tokio::fs::remove_file(&file_path).await;
+            return Ok(None);
+        }
+        let duration_sec = i32::try_from(duration_sec).unwrap_or(i32::MAX);
+
+        self.port
+            .insert_message(NewVoicemailMessage {
+                id: message_id,
+                mailbox_id: mailbox.id,
+                call_log_id: Some(recorded.call_log_id),
+                caller_number: recorded.caller_number.clone(),
+                duration_sec,
+                file_path: file_path.to_string_lossy().to_string(),
+                created_at: recorded.received_at,
+            })
+            .await?;
+        info!(
+            "[voicemail] message_id={} saved mailbox={} duration={}s",
+            message_id, mailbox.mailbox_number, duration_sec
+        );
+
+        let transcript = if self.transcription_enabled {
+            self.transcribe(message_id, &samples).await
+        } else {
+            None
+        };
+
+        self.notify(
+            &mailbox,
+            message_id,
+            &recorded,
+            &file_path,
+            duration_sec,
+            transcript,
+        )
+        .await;
+        Ok(Some(message_id))
+    }
+
+    async fn transcribe(&self, message_id: Uuid, samples: &[i16]) -> Option<String> {
+        let result = self
+            .asr
+            .transcribe_chunks(message_id.to_string(), asr_chunks(samples))
+            .await;
+        let transcript = match result {
+            Ok(text) if !text.trim().is_empty() => text.trim().to_string(),
+            Ok(_) => return None,
+            Err(err) => {
+                warn!(
+                    "[voicemail] message_id={} transcription failed: {}",
+                    message_id, err
+                );
+                return None;
+            }
+        };
+        if let Err(err) = self
+            .port
+            .update_transcript(message_id, transcript.clone())
+            .await
+        {
+            warn!(
+                "[voicemail] message_id={} failed to save transcript: {}",
+                message_id, err
+            );
+        }
+        Some(transcript)
+    }
+
+    async fn notify(
+        &self,
+        mailbox: &VoicemailMailbox,
+        message_id: Uuid,
+        recorded: &RecordedVoicemail,
+        file_path: &Path,
+        duration_sec: i32,
+        transcript: Option<String>,
+    ) {
+        self.event_webhooks.publish(WebhookEvent {
+            id: message_id,
+            event_type: WebhookEventType::Voicemail,
+            call_id: None,
+            occurred_at: recorded.received_at,
+            data: serde_json::json!({
+                "messageId": message_id.to_string(),
+                "callLogId": recorded.call_log_id.to_string(),
+                "mailbox": mailbox.mailbox_number,
+                "callerNumber": recorded.caller_number,
+                "durationSec": duration_sec,
+                "transcript": transcript,
+            }),
+        });
+        let jst = FixedOffset::east_opt(9 * 3600).expect("valid offset");
+        let notice = VoicemailNotice {
+            message_id,
+            mailbox_number: mailbox.mailbox_number.clone(),
+            caller_number: recorded.caller_number.clone(),
+            duration_sec,
+            transcript,
+            received_at: recorded.received_at.with_timezone(&jst),
+            webhook_url: mailbox.webhook_url.clone(),
+            audio_path: Some(file_path.to_path_buf()),
+        };
+        if mailbox.notify_line {
+            if let Err(err) = self.channel_notifier.notify_voicemail(notice.clone()).await {
+                warn!(
+                    "[voicemail] message_id={} channel notification failed: {}",
+                    message_id, err
+                );
+            }
+        }
+        if let Err(err) = self.webhook_notifier.notify_voicemail(notice).await {
+            warn!(
+                "[voicemail] message_id={} webhook notification failed: {}",
+                message_id, err
+            );
+        }
+    }
+}
+
+fn normalize_did(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(normalize_phone_number_e164(trimmed).unwrap_or_else(|_| trimmed.to_string()))
+}
+
+/// 通話録音の発信者側チャネル（L）を 8kHz モノラル WAV として `dest` に書き出し、サンプルを返す。
+fn extract_caller_channel(source: &Path, dest: &Path) -> Result<Vec<i16>, VoicemailProcessError> {
+    let audio_err = |e: hound::Error| VoicemailProcessError::Audio(e.to_string());
+    let mut reader = WavReader::open(source).map_err(audio_err)?;
+    let spec = reader.spec();
+    if spec.bits_per_sample != 16 || spec.channels == 0 {
+        return Err(VoicemailProcessError::Audio(format!(
+            "unsupported recording format channels={} bits={}",
+            spec.channels, spec.bits_per_sample
+        )));
+    }
+    let Some(step) = decimation_step_to_8k(spec.sample_rate) else {
+        return Err(VoicemailProcessError::Audio(format!(
+            "unsupported sample rate {}",
+            spec.sample_rate
+        )));
+    };
+    let samples = reader
+        .samples::<i16>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(audio_err)?;
+    let caller: Vec<i16> = samples
+        .into_iter()
+        .step_by(spec.channels as usize)
+        .step_by(step)
+        .collect();
+
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| VoicemailProcessError::Audio(e.to_string()))?;
+    }
+    let mono = WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::create(dest, mono).map_err(audio_err)?;
+    for sample in &caller {
+        writer.write_sample(*sample).map_err(audio_err)?;
+    }
+    writer.finalize().map_err(audio_err)?;
+    Ok(caller)
+}
+
+fn asr_chunks(samples: &[i16]) -> Vec<AsrChunk> {
+    let mut chunks: Vec<AsrChunk> = samples
+        .chunks(ASR_CHUNK_SAMPLES)
+        .map(|chunk| AsrChunk {
+            pcm_mulaw: chunk.iter().copied().map(linear16_to_mulaw).collect(),
+            end: false,
+        })
+        .collect();
+    if let Some(last) = chunks.last_mut() {
+        last.end = true;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use chrono::Utc;
+
+    use super::*;
+    use crate::shared::ports::ai::{AiFuture, AsrError};
+    use crate::shared::ports::notification::NotificationFuture;
+    use crate::shared::ports::voicemail_port::{hash_pin, VoicemailFuture, VoicemailMessage};
+
+    #[derive(Default)]
+    struct RecordingPort {
+        inserted: Mutex<Vec<NewVoicemailMessage>>,
+        transcripts: Mutex<Vec<(Uuid, String)>>,
+        mailbox_did: Mutex<Option<Option<String>>>,
+    }
+
+    impl VoicemailPort for RecordingPort {
+        fn find_mailbox_for_did(
+            &self,
+            did: Option<&str>,
+        ) -> VoicemailFuture<Option<VoicemailMailbox>> {
+            *self.mailbox_did.lock().unwrap() = Some(did.map(str::to_string));
+            let mailbox = VoicemailMailbox {
+                id: Uuid::nil(),
+                mailbox_number: "1001".to_string(),
+                did: None,
+                pin_hash: hash_pin("0000"),
+                pin_locked_until: None,
+                notify_line: false,
+                webhook_url: Some("https://example.com/hook".to_string()),
+            };
+            Box::pin(async move { Ok(Some(mailbox)) })
+        }
+
+        fn find_mailbox(&self, _mailbox_number: &str) -> VoicemailFuture<Option<VoicemailMailbox>> {
+            Box::pin(async { Ok(None) })
+        }
+
+        fn update_pin_hash(&self, _mailbox_id: Uuid, _pin_hash: String) -> VoicemailFuture<()> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn record_pin_failure(
+            &self,
+            _mailbox_id: Uuid,
+            _max_attempts: u32,
+            _lockout: std::time::Duration,
+        ) -> VoicemailFuture<Option<chrono::DateTime<Utc>>> {
+            Box::pin(async { Ok(None) })
+        }
+
+        fn reset_pin_failures(&self, _mailbox_id: Uuid) -> VoicemailFuture<()> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn insert_message(&self, message: NewVoicemailMessage) -> VoicemailFuture<()> {
+            self.inserted.lock().unwrap().push(message);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn update_transcript(&self, message_id: Uuid, transcript: String) -> VoicemailFuture<()> {
+            self.transcripts
+                .lock()
+                .unwrap()
+                .push((message_id, transcript));
+            Box::pin(async { Ok(()) })
+        }
+
+        fn list_messages(&self, _mailbox_id: Uuid) -> VoicemailFuture<Vec<VoicemailMessage>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+
+        fn mark_listened(&self, _message_id: Uuid) -> VoicemailFuture<()> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn update_status(&self, _message_id: Uuid, _status: &'static str) -> VoicemailFuture<()> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    struct FixedAsr {
+        chunk_counts: Mutex<Vec<(usize, bool)>>,
+    }
+
+    impl AsrPort for FixedAsr {
+        fn transcribe_chunks(
+            &self,
+            _call_id: String,
+            chunks: Vec<AsrChunk>,
+        ) -> AiFuture<Result<String, AsrError>> {
+            let last_is_end = chunks.last().map(|chunk| chunk.end).unwrap_or(false);
+            self.chunk_counts
+                .lock()
+                .unwrap()
+                .push((chunks.len(), last_is_end));
+            Box::pin(async { Ok(" 折り返しお願いします ".to_string()) })
+        }
+    }
+
+    #[derive(Default)]
+    struct CapturingNotifier {
+        notices: Mutex<Vec<VoicemailNotice>>,
+        events: Mutex<Vec<WebhookEvent>>,
+    }
+
+    impl VoicemailNotifier for CapturingNotifier {
+        fn notify_voicemail(&self, notice: VoicemailNotice) -> NotificationFuture {
+            self.notices.lock().unwrap().push(notice);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    impl WebhookPublisher for CapturingNotifier {
+        fn publish(&self, event: WebhookEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    fn write_stereo_recording(path: &Path, seconds: usize) {
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(path, spec).unwrap();
+        for _ in 0..seconds * SAMPLE_RATE as usize {
+            writer.write_sample(1000i16).unwrap();
+            writer.write_sample(-2000i16).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[tokio::test]
+    async fn process_recording_saves_caller_channel_transcribes_and_notifies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let recording_path = tmp.path().join("mixed.wav");
+        write_stereo_recording(&recording_path, 3);
+
+        let port = Arc::new(RecordingPort::default());
+        let asr = Arc::new(FixedAsr {
+            chunk_counts: Mutex::new(Vec::new()),
+        });
+        let line = Arc::new(CapturingNotifier::default());
+        let webhook = Arc::new(CapturingNotifier::default());
+        let mut service =
+            VoicemailService::new(port.clone(), asr.clone(), line.clone(), webhook.clone())
+                .with_event_webhooks(webhook.clone());
+        service.dir = tmp.path().join("voicemail");
+        service.min_duration_sec = 2;
+        service.transcription_enabled = true;
+
+        let message_id = service
+            .process_recording(RecordedVoicemail {
+                call_log_id: Uuid::now_v7(),
+                recording_path: recording_path.to_string_lossy().to_string(),
+                caller_number: Some("+819012345678".to_string()),
+                called_number: Some("0312345678".to_string()),
+                received_at: Utc::now(),
+            })
+            .await
+            .expect("process")
+            .expect("message saved");
+
+        assert_eq!(
+            *port.mailbox_did.lock().unwrap(),
+            Some(Some("+81312345678".to_string()))
+        );
+        let inserted = port.inserted.lock().unwrap().clone();
+        assert_eq!(inserted.len(), 1);
+        assert_eq!(inserted[0].id, message_id);
+        assert_eq!(inserted[0].duration_sec, 3);
+
+        let mut reader = WavReader::open(&inserted[0].file_path).unwrap();
+        assert_eq!(reader.spec().channels, 1);
+        assert!(reader.samples::<i16>().all(|s| s.unwrap() == 1000));
+
+        assert_eq!(*asr.chunk_counts.lock().unwrap(), vec![(3, true)]);
+        assert_eq!(
+            *port.transcripts.lock().unwrap(),
+            vec![(message_id, "折り返しお願いします".to_string())]
+        );
+
+        assert!(line.notices.lock().unwrap().is_empty(), "notify_line=false");
+        let notices = webhook.notices.lock().unwrap();
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].mailbox_number, "1001");
+        assert_eq!(
+            notices[0].transcript.as_deref(),
+            Some("折り返しお願いします")
+        );
+        assert_eq!(
+            notices[0].webhook_url.as_deref(),
+            Some("https://example.com/hook")
+        );
+        let events = webhook.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, WebhookEventType::Voicemail);
+        assert_eq!(events[0].data["transcript"], "折り返しお願いします");
+    }
+
+    #[tokio::test]
+    async fn process_recording_discards_short_recording() {
+        let tmp = tempfile::tempdir().unwrap();
+        let recording_path = tmp.path().join("mixed.wav");
+        write_stereo_recording(&recording_path, 1);
+
+        let port = Arc::new(RecordingPort::default());
+        let webhook = Arc::new(CapturingNotifier::default());
+        let mut service = VoicemailService::new(
+            port.clone(),
+            Arc::new(FixedAsr {
+                chunk_counts: Mutex::new(Vec::new()),
+            }),
+            Arc::new(CapturingNotifier::default()),
+            webhook.clone(),
+        );
+        service.dir = tmp.path().join("voicemail");
+        service.min_duration_sec = 2;
+
+        let result = service
+            .process_recording(RecordedVoicemail {
+                call_log_id: Uuid::now_v7(),
+                recording_path: recording_path.to_string_lossy().to_string(),
+                caller_number: None,
+                called_number: None,
+                received_at: Utc::now(),
+            })
+            .await
+            .expect("process");
+
+        assert!(result.is_none());
+        assert!(port.inserted.lock().unwrap().is_empty());
+        assert!(webhook.notices.lock().unwrap().is_empty());
+        let mailbox_dir = tmp.path().join("voicemail").join("1001");
+        assert_eq!(std::fs::read_dir(mailbox_dir).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,123 @@
+//! 配送ログ（`webhook_deliveries`）の一覧 API 向けの絞り込みと表示
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::shared::ports::webhook_port::{
+    WebhookDelivery, WebhookDeliveryFilter, DELIVERY_STATUS_DEAD, DELIVERY_STATUS_DELIVERED,
+    DELIVERY_STATUS_PENDING,
+};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+
+/// クエリ `status` / `subscription` / `callId` / `limit` から絞り込みを組み立てる。
+/// `status` が pending / delivered / dead 以外なら None
+pub fn delivery_filter(params: &HashMap<String, String>) -> Option<WebhookDeliveryFilter> {
+    let status = params.get("status").cloned();
+    if status.as_deref().is_some_and(|status| {
+        ![
+            DELIVERY_STATUS_PENDING,
+            DELIVERY_STATUS_DELIVERED,
+            DELIVERY_STATUS_DEAD,
+        ]
+        .contains(&status)
+    }) {
+        return None;
+    }
+    Some(WebhookDeliveryFilter {
+        status,
+        subscription_id: params.get("subscription").cloned(),
+        call_id: params.get("callId").cloned(),
+        limit: params
+            .get("limit")
+            .and_then(|limit| limit.parse::<i64>().ok())
+            .unwrap_or(DEFAULT_LIMIT)
+            .clamp(1, MAX_LIMIT),
+    })
+}
+
+#[derive(Serialize)]
+pub struct WebhookDeliveriesResponse {
+    pub deliveries: Vec<WebhookDeliveryView>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryView {
+    id: String,
+    subscription_id: String,
+    event_type: String,
+    event_id: String,
+    call_id: Option<String>,
+    url: String,
+    status: String,
+    attempts: i32,
+    /// pending の間だけ（送信済み・dead では次の送信はない）
+    next_attempt_at: Option<String>,
+    last_status_code: Option<i32>,
+    last_error: Option<String>,
+    created_at: String,
+    delivered_at: Option<String>,
+    payload: serde_json::Value,
+}
+
+impl From<&WebhookDelivery> for WebhookDeliveryView {
+    fn from(delivery: &WebhookDelivery) -> Self {
+        Self {
+            id: delivery.id.to_string(),
+            subscription_id: delivery.subscription_id.clone(),
+            event_type: delivery.event_type.clone(),
+            event_id: delivery.event_id.to_string(),
+            call_id: delivery.call_id.clone(),
+            url: delivery.url.clone(),
+            status: delivery.status.clone(),
+            attempts: delivery.attempts,
+            next_attempt_at: (delivery.status == DELIVERY_STATUS_PENDING)
+                .then(|| delivery.next_attempt_at.to_rfc3339()),
+            last_status_code: delivery.last_status_code,
+            last_error: delivery.last_error.clone(),
+            created_at: delivery.created_at.to_rfc3339(),
+            delivered_at: delivery.delivered_at.map(|at| at.to_rfc3339()),
+            payload: delivery.payload.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn delivery_filter_validates_status_and_clamps_limit() {
+        let filter = delivery_filter(&params(&[])).unwrap();
+        assert!(filter.status.is_none());
+        assert_eq!(filter.limit, DEFAULT_LIMIT);
+
+        let filter = delivery_filter(&params(&[
+            ("status", "dead"),
+            ("subscription", "crm"),
+            ("callId", "call-a"),
+            ("limit", "9999"),
+        ]))
+        .unwrap();
+        assert_eq!(filter.status.as_deref(), Some("dead"));
+        assert_eq!(filter.subscription_id.as_deref(), Some("crm"));
+        assert_eq!(filter.call_id.as_deref(), Some("call-a"));
+        assert_eq!(filter.limit, MAX_LIMIT);
+
+        assert_eq!(
+            delivery_filter(&params(&[("limit", "0")])).unwrap().limit,
+            1
+        );
+        assert!(delivery_filter(&params(&[("status", "failed")])).is_none());
+    }
+}
@@ -0,0 +1,264 @@
+//! 通話イベントを外部システム（CRM・チケット管理など）へ webhook で配る。
+//!
+//! 購読（送信先 URL・対象イベント・署名鍵・本文テンプレート）は webhooks.yaml に書く。
+//! イベントごとに購読先の本文を組み立てて配送キュー（`webhook_deliveries`）に積み（`queue`）、
+//! 送信・再試行・dead letter は `interface::notification::WebhookDeliveryWorker` が受け持つ。
+//! 送信時の署名は `signing`、配送ログ API の一覧・表示は `log` にまとめる。
+//!
+//! ringing / missed / ended / transcript_ready は通話イベント（`CallEventPublisher`）から、
+//! voicemail は留守番電話の後処理から受け取る。
+
+pub mod log;
+mod queue;
+mod signing;
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::shared::ports::webhook_port::{WebhookEvent, WebhookEventType};
+
+pub use queue::WebhookService;
+pub use signing::{signature, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+
+#[derive(Debug, Error)]
+pub enum WebhookConfigError {
+    #[error("failed to read {path}: {reason}")]
+    Read { path: String, reason: String },
+    #[error("invalid webhook subscription: {0}")]
+    Invalid(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub url: String,
+    /// 空なら全イベント
+    pub events: Vec<WebhookEventType>,
+    /// HMAC-SHA256 の署名鍵。未設定なら署名ヘッダを付けない
+    pub secret: Option<String>,
+    /// 本文のテンプレート。未設定なら標準の本文（`envelope`）をそのまま送る
+    pub template: Option<Value>,
+}
+
+impl WebhookSubscription {
+    pub fn accepts(&self, event_type: WebhookEventType) -> bool {
+        self.events.is_empty() || self.events.contains(&event_type)
+    }
+}
+
+#[derive(Deserialize)]
+struct WebhookFile {
+    #[serde(default)]
+    subscriptions: Vec<RawSubscription>,
+}
+
+#[derive(Deserialize)]
+struct RawSubscription {
+    id: String,
+    url: String,
+    #[serde(default)]
+    events: Vec<String>,
+    #[serde(default)]
+    secret: Option<String>,
+    #[serde(default)]
+    template: Option<Value>,
+    #[serde(default = "enabled_default")]
+    enabled: bool,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+/// webhooks.yaml を読み込む。ファイルが無ければ購読なし
+pub fn load_subscriptions() -> Result<Vec<WebhookSubscription>, WebhookConfigError> {
+    let path = config_path();
+    match std::fs::read_to_string(&path) {
+        Ok(text) => parse_subscriptions(&text),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(WebhookConfigError::Read {
+            path: path.display().to_string(),
+            reason: err.to_string(),
+        }),
+    }
+}
+
+pub(crate) fn config_path() -> PathBuf {
+    let base = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..");
+    std::env::var("WEBHOOK_CONFIG")
+        .ok()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| base.join("webhooks.yaml"))
+}
+
+fn parse_subscriptions(text: &str) -> Result<Vec<WebhookSubscription>, WebhookConfigError> {
+    let file: WebhookFile =
+        serde_yaml::from_str(text).map_err(|err| WebhookConfigError::Invalid(err.to_string()))?;
+    let mut subscriptions: Vec<WebhookSubscription> = Vec::new();
+    for raw in file.subscriptions.into_iter().filter(|raw| raw.enabled) {
+        let id = raw.id.trim().to_string();
+        if id.is_empty() || id.len() > 64 {
+            return Err(WebhookConfigError::Invalid(format!(
+                "id must be 1-64 characters: {:?}",
+                raw.id
+            )));
+        }
+        if subscriptions.iter().any(|existing| existing.id == id) {
+            return Err(WebhookConfigError::Invalid(format!("duplicate id {}", id)));
+        }
+        let url = raw.url.trim().to_string();
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(WebhookConfigError::Invalid(format!(
+                "{}: url must be http(s)",
+                id
+            )));
+        }
+        let events = raw
+            .events
+            .iter()
+            .map(|name| {
+                WebhookEventType::parse(name).ok_or_else(|| {
+                    WebhookConfigError::Invalid(format!("{}: unknown event {:?}", id, name))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        subscriptions.push(WebhookSubscription {
+            id,
+            url,
+            events,
+            secret: raw
+                .secret
+                .map(|secret| secret.trim().to_string())
+                .filter(|secret| !secret.is_empty()),
+            template: raw.template,
+        });
+    }
+    Ok(subscriptions)
+}
+
+/// 標準の本文。テンプレートからは `event` / `eventId` / `callId` / `occurredAt` / `data.*` で参照する
+pub fn envelope(event: &WebhookEvent) -> Value {
+    json!({
+        "event": event.event_type.as_str(),
+        "eventId": event.id.to_string(),
+        "callId": event.call_id,
+        "occurredAt": event.occurred_at.to_rfc3339(),
+        "data": event.data,
+    })
+}
+
+/// テンプレートの文字列中の `{{path}}` を本文の値で置き換える。
+/// 文字列全体が `{{path}}` なら値の型（数値・配列など）をそのまま使う
+pub fn render_payload(template: Option<&Value>, envelope: &Value) -> Value {
+    match template {
+        Some(template) => render_value(template, envelope),
+        None => envelope.clone(),
+    }
+}
+
+fn render_value(template: &Value, envelope: &Value) -> Value {
+    match template {
+        Value::String(text) => render_string(text, envelope),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_value(item, envelope))
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), render_value(value, envelope)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn render_string(text: &str, envelope: &Value) -> Value {
+    let trimmed = text.trim();
+    if let Some(path) = trimmed
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        .filter(|path| !path.contains("{{") && !path.contains("}}"))
+    {
+        return lookup(envelope, path.trim())
+            .cloned()
+            .unwrap_or(Value::Null);
+    }
+    Value::String(render_text(text, envelope))
+}
+
+/// 文字列中の `{{path}}` を値の文字列表現で置き換える（null・見つからない値は空文字）
+pub fn render_text(text: &str, context: &Value) -> String {
+    let mut rendered = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        match lookup(context, rest[start + 2..start + end].trim()) {
+            Some(Value::String(value)) => rendered.push_str(value),
+            Some(Value::Null) | None => {}
+            Some(value) => rendered.push_str(&value.to_string()),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |current, key| match current {
+            Value::Object(fields) => fields.get(key),
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) const CONFIG: &str = r#"
+subscriptions:
+  - id: crm
+    url: https://crm.example.com/hooks/voicebot
+    events: [ended, transcript-ready]
+    secret: s3cret
+    template:
+      type: "voicebot.{{event}}"
+      caller: "{{data.from}}"
+      seconds: "{{data.durationSec}}"
+      summary: "{{data.from}} から {{data.durationSec}} 秒"
+  - id: tickets
+    url: https://tickets.example.com/hook
+    events: [missed]
+  - id: disabled
+    url: https://example.com/off
+    enabled: false
+"#;
+
+    #[test]
+    fn parses_subscriptions_and_rejects_unknown_events() {
+        let subscriptions = parse_subscriptions(CONFIG).unwrap();
+        assert_eq!(subscriptions.len(), 2);
+        assert_eq!(
+            subscriptions[0].events,
+            vec![WebhookEventType::Ended, WebhookEventType::TranscriptReady]
+        );
+        assert_eq!(subscriptions[0].secret.as_deref(), Some("s3cret"));
+        assert!(subscriptions[1].secret.is_none());
+
+        let err = parse_subscriptions(
+            "subscriptions:\n  - id: a\n    url: https://example.com\n    events: [hangup]\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown event"));
+    }
+}
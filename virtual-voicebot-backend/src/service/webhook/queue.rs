@@ -0,0 +1,316 @@
+//! 通話イベントを webhook のイベントに読み替え、購読先ごとの配送を配送キューに積む
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::{envelope, render_payload, WebhookSubscription};
+use crate::service::recording;
+use crate::shared::ports::call_event_port::{CallEvent, CallEventPublisher};
+use crate::shared::ports::webhook_port::{
+    NewWebhookDelivery, WebhookDeliveryPort, WebhookEvent, WebhookEventType, WebhookPublisher,
+};
+
+/// 通話ごとに、終了時の通知に必要な分だけ覚えておく
+#[derive(Default)]
+struct CallTrack {
+    from: Option<String>,
+    to: Option<String>,
+    answered_at: Option<DateTime<Utc>>,
+    turns: Vec<Value>,
+}
+
+pub struct WebhookService {
+    subscriptions: Arc<Vec<WebhookSubscription>>,
+    port: Arc<dyn WebhookDeliveryPort>,
+    calls: Mutex<HashMap<String, CallTrack>>,
+    /// ended に録音の URL（署名付き）を載せるときの配信元
+    recording_base_url: Option<String>,
+}
+
+impl WebhookService {
+    pub fn new(
+        subscriptions: Arc<Vec<WebhookSubscription>>,
+        port: Arc<dyn WebhookDeliveryPort>,
+    ) -> Self {
+        Self {
+            subscriptions,
+            port,
+            calls: Mutex::new(HashMap::new()),
+            recording_base_url: None,
+        }
+    }
+
+    pub fn with_recording_base_url(mut self, base_url: Option<String>) -> Self {
+        self.recording_base_url = base_url;
+        self
+    }
+
+    /// 録音がある通話だけ、`RECORDING_URL_SIGNING_SECRET` に従って署名した URL を返す
+    fn recording_link(&self, call_id: &str) -> Option<String> {
+        let base_url = self.recording_base_url.as_deref()?;
+        recording::recording_dir(call_id)
+            .is_dir()
+            .then(|| recording::recording_url(base_url, call_id))
+    }
+
+    fn wants(&self, event_type: WebhookEventType) -> bool {
+        self.subscriptions
+            .iter()
+            .any(|subscription| subscription.accepts(event_type))
+    }
+
+    /// 購読先ごとの配送
+    fn deliveries_for(&self, event: &WebhookEvent) -> Vec<NewWebhookDelivery> {
+        let envelope = envelope(event);
+        self.subscriptions
+            .iter()
+            .filter(|subscription| subscription.accepts(event.event_type))
+            .map(|subscription| NewWebhookDelivery {
+                id: Uuid::now_v7(),
+                subscription_id: subscription.id.clone(),
+                event_type: event.event_type,
+                event_id: event.id,
+                call_id: event.call_id.clone(),
+                url: subscription.url.clone(),
+                payload: render_payload(subscription.template.as_ref(), &envelope),
+            })
+            .collect()
+    }
+
+    /// 通話イベントを webhook のイベントに読み替える
+    fn track(&self, call_id: &str, event: CallEvent, now: DateTime<Utc>) -> Vec<WebhookEvent> {
+        let new_event = |event_type, data| WebhookEvent {
+            id: Uuid::now_v7(),
+            event_type,
+            call_id: Some(call_id.to_string()),
+            occurred_at: now,
+            data,
+        };
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        match event {
+            CallEvent::Ringing { from, to } => {
+                let track = calls.entry(call_id.to_string()).or_default();
+                track.from = Some(from.clone());
+                track.to = Some(to.clone());
+                if !self.wants(WebhookEventType::Ringing) {
+                    return Vec::new();
+                }
+                vec![new_event(
+                    WebhookEventType::Ringing,
+                    json!({ "from": from, "to": to }),
+                )]
+            }
+            CallEvent::Answered => {
+                calls.entry(call_id.to_string()).or_default().answered_at = Some(now);
+                Vec::new()
+            }
+            CallEvent::UserUtterance { text, .. } => {
+                if self.wants(WebhookEventType::TranscriptReady) {
+                    calls
+                        .entry(call_id.to_string())
+                        .or_default()
+                        .turns
+                        .push(json!({
+                            "speaker": "caller",
+                            "text": text,
+                            "at": now.to_rfc3339(),
+                        }));
+                }
+                Vec::new()
+            }
+            CallEvent::BotReply { text, .. } => {
+                if self.wants(WebhookEventType::TranscriptReady) {
+                    calls
+                        .entry(call_id.to_string())
+                        .or_default()
+                        .turns
+                        .push(json!({
+                            "speaker": "bot",
+                            "text": text,
+                            "at": now.to_rfc3339(),
+                        }));
+                }
+                Vec::new()
+            }
+            CallEvent::Ended {
+                reason,
+                duration_sec,
+            } => {
+                let track = calls.remove(call_id).unwrap_or_default();
+                let mut events = Vec::new();
+                let (event_type, data) = match track.answered_at {
+                    Some(answered_at) => (
+                        WebhookEventType::Ended,
+                        json!({
+                            "from": track.from,
+                            "to": track.to,
+                            "reason": reason,
+                            "answeredAt": answered_at.to_rfc3339(),
+                            "durationSec": duration_sec,
+                            "recordingUrl": self.recording_link(call_id),
+                        }),
+                    ),
+                    None => (
+                        WebhookEventType::Missed,
+                        json!({ "from": track.from, "to": track.to, "reason": reason }),
+                    ),
+                };
+                if self.wants(event_type) {
+                    events.push(new_event(event_type, data));
+                }
+                if !track.turns.is_empty() {
+                    events.push(new_event(
+                        WebhookEventType::TranscriptReady,
+                        json!({ "from": track.from, "to": track.to, "turns": track.turns }),
+                    ));
+                }
+                events
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl WebhookPublisher for WebhookService {
+    fn publish(&self, event: WebhookEvent) {
+        let deliveries = self.deliveries_for(&event);
+        if deliveries.is_empty() {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            log::warn!(
+                "[webhook] {} event {} dropped: no runtime",
+                event.event_type.as_str(),
+                event.id
+            );
+            return;
+        };
+        let port = self.port.clone();
+        runtime.spawn(async move {
+            for delivery in deliveries {
+                let id = delivery.id;
+                let subscription_id = delivery.subscription_id.clone();
+                if let Err(err) = port.enqueue(delivery).await {
+                    log::warn!(
+                        "[webhook] enqueue {} for {} failed: {}",
+                        id,
+                        subscription_id,
+                        err
+                    );
+                }
+            }
+        });
+    }
+}
+
+impl CallEventPublisher for WebhookService {
+    fn publish(&self, call_id: &str, event: CallEvent) {
+        for webhook_event in self.track(call_id, event, Utc::now()) {
+            WebhookPublisher::publish(self, webhook_event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::webhook::parse_subscriptions;
+    use crate::service::webhook::tests::CONFIG;
+    use crate::shared::ports::webhook_port::NoopWebhookDeliveryPort;
+
+    fn service() -> WebhookService {
+        WebhookService::new(
+            Arc::new(parse_subscriptions(CONFIG).unwrap()),
+            Arc::new(NoopWebhookDeliveryPort::new()),
+        )
+    }
+
+    #[test]
+    fn answered_call_emits_ended_and_transcript_with_template() {
+        let service = service();
+        let now = Utc::now();
+        let ringing = CallEvent::Ringing {
+            from: "09012345678".to_string(),
+            to: "0312345678".to_string(),
+        };
+        assert!(service.track("call-a", ringing, now).is_empty());
+        service.track("call-a", CallEvent::Answered, now);
+        service.track(
+            "call-a",
+            CallEvent::UserUtterance {
+                text: "予約したい".to_string(),
+                asr_ms: 300,
+            },
+            now,
+        );
+        service.track(
+            "call-a",
+            CallEvent::BotReply {
+                text: "承知しました".to_string(),
+                llm_ms: 500,
+                turn_ms: 900,
+            },
+            now,
+        );
+        let events = service.track(
+            "call-a",
+            CallEvent::Ended {
+                reason: "bye".to_string(),
+                duration_sec: Some(42),
+            },
+            now,
+        );
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event_type, WebhookEventType::Ended);
+        assert_eq!(events[1].event_type, WebhookEventType::TranscriptReady);
+        assert_eq!(events[1].data["turns"][1]["speaker"], "bot");
+
+        let deliveries = service.deliveries_for(&events[0]);
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].subscription_id, "crm");
+        assert_eq!(
+            deliveries[0].payload,
+            json!({
+                "type": "voicebot.ended",
+                "caller": "09012345678",
+                "seconds": 42,
+                "summary": "09012345678 から 42 秒",
+            })
+        );
+    }
+
+    #[test]
+    fn unanswered_call_is_missed() {
+        let service = service();
+        let now = Utc::now();
+        service.track(
+            "call-b",
+            CallEvent::Ringing {
+                from: "09000000000".to_string(),
+                to: "0312345678".to_string(),
+            },
+            now,
+        );
+        let events = service.track(
+            "call-b",
+            CallEvent::Ended {
+                reason: "cancel".to_string(),
+                duration_sec: None,
+            },
+            now,
+        );
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, WebhookEventType::Missed);
+        let deliveries = service.deliveries_for(&events[0]);
+        assert_eq!(deliveries[0].subscription_id, "tickets");
+        assert_eq!(deliveries[0].payload["event"], "missed");
+        assert_eq!(deliveries[0].payload["data"]["from"], "09000000000");
+        assert!(service.calls.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,44 @@
+//! 配送の署名。受信側は `SIGNATURE_HEADER` を同じ鍵で計算し直して本文の改ざんを確かめる
+
+use ring::hmac;
+
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// `sha256=<hex>`。署名対象は `<timestamp>.<本文>`（受信側はタイムスタンプの古さも確かめる）
+pub fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let mut context = hmac::Context::with_key(&key);
+    context.update(timestamp.to_string().as_bytes());
+    context.update(b".");
+    context.update(body);
+    let hex: String = context
+        .sign()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let signed = signature("s3cret", 1_700_000_000, br#"{"event":"ended"}"#);
+        assert!(signed.starts_with("sha256="));
+        assert_eq!(signed.len(), "sha256=".len() + 64);
+        assert_ne!(
+            signed,
+            signature("s3cret", 1_700_000_001, br#"{"event":"ended"}"#)
+        );
+        assert_ne!(
+            signed,
+            signature("other", 1_700_000_000, br#"{"event":"ended"}"#)
+        );
+    }
+}
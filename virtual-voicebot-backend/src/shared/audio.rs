@@ -1,3 +1,7 @@
+/// Samples per 20 ms PCMU frame at 8 kHz.
+pub const PCMU_FRAME_SAMPLES: usize = 160;
+const PCMU_SILENCE: u8 = 0xFF;
+
 const SEG_UEND: [i16; 8] = [0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF, 0x1FFF, 0x3FFF, 0x7FFF];
 
 /// Converts a 16-bit linear PCM sample to an 8-bit G.711 mu-law sample.
@@ -33,3 +37,24 @@ fn search_g711_segment(value: i16, table: &[i16; 8]) -> u8 {
     }
     8
 }
+
+/// Returns the decimation step from `sample_rate` down to 8 kHz (8 kHz / 24 kHz only).
+pub fn decimation_step_to_8k(sample_rate: u32) -> Option<usize> {
+    match sample_rate {
+        8000 => Some(1),
+        24000 => Some(3),
+        _ => None,
+    }
+}
+
+/// Splits mu-law samples into 20 ms frames, padding the last frame with silence.
+pub fn mulaw_to_frames(mulaw: &[u8]) -> Vec<Vec<u8>> {
+    mulaw
+        .chunks(PCMU_FRAME_SAMPLES)
+        .map(|chunk| {
+            let mut frame = chunk.to_vec();
+            frame.resize(PCMU_FRAME_SAMPLES, PCMU_SILENCE);
+            frame
+        })
+        .collect()
+}
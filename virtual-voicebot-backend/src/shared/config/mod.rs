@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::sync::OnceLock;
 use std::time::Duration;
+use uuid::Uuid;
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -20,12 +21,14 @@ pub struct Config {
 #[derive(Clone, Debug)]
 pub struct AppRuntimeConfig {
     pub phone_lookup_enabled: bool,
+    pub caller_memory_enabled: bool,
 }
 
 impl AppRuntimeConfig {
     pub fn from_env() -> Self {
         Self {
             phone_lookup_enabled: env_bool("PHONE_LOOKUP_ENABLED", false),
+            caller_memory_enabled: caller_memory_config().enabled,
         }
     }
 }
@@ -43,11 +46,20 @@ static TTS_STREAMING_CONNECT_TIMEOUT: OnceLock<Duration> = OnceLock::new();
 static TTS_STREAMING_FIRST_CHUNK_TIMEOUT: OnceLock<Duration> = OnceLock::new();
 static TTS_STREAMING_TOTAL_TIMEOUT: OnceLock<Duration> = OnceLock::new();
 static TTS_STREAMING_EARLY_START_ENABLED: OnceLock<bool> = OnceLock::new();
-static TTS_STREAMING_EARLY_START_BYTES: OnceLock<usize> = OnceLock::new();
+static TTS_STREAMING_EARLY_START_SAMPLES: OnceLock<usize> = OnceLock::new();
+static TTS_STREAMING_DEBUG_WAV_DIR: OnceLock<Option<String>> = OnceLock::new();
 static ASR_STREAMING_SERVER_URL: OnceLock<String> = OnceLock::new();
 static ASR_STREAMING_CONNECT_TIMEOUT: OnceLock<Duration> = OnceLock::new();
 static ASR_STREAMING_FIRST_PARTIAL_TIMEOUT: OnceLock<Duration> = OnceLock::new();
 static ASR_STREAMING_FINAL_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+static CONFIG_RELOAD_POLL_INTERVAL: OnceLock<Duration> = OnceLock::new();
+static CALL_EVENT_REPLAY_BUFFER: OnceLock<usize> = OnceLock::new();
+static CALL_EVENT_HEARTBEAT: OnceLock<Duration> = OnceLock::new();
+static MEDIA_QUALITY_MOS_ALERT_THRESHOLD: OnceLock<f32> = OnceLock::new();
+static SUPERVISOR_API_TOKEN: OnceLock<Option<String>> = OnceLock::new();
+static RECORDING_PAUSE_DTMF: OnceLock<Option<String>> = OnceLock::new();
+static RECORDING_RESUME_DTMF: OnceLock<Option<String>> = OnceLock::new();
+static RECORDING_PAUSE_MAX: OnceLock<Option<Duration>> = OnceLock::new();
 
 pub fn voicebot_streaming_enabled() -> bool {
     *VOICEBOT_STREAMING_ENABLED.get_or_init(|| env_bool("VOICEBOT_STREAMING_ENABLED", false))
@@ -113,9 +125,46 @@ pub fn tts_streaming_early_start_enabled() -> bool {
         .get_or_init(|| env_bool("VOICEBOT_TTS_STREAMING_EARLY_START_ENABLED", false))
 }
 
-pub fn tts_streaming_early_start_bytes() -> usize {
-    *TTS_STREAMING_EARLY_START_BYTES
-        .get_or_init(|| env_u64("TTS_STREAMING_EARLY_START_BYTES", 24_000) as usize)
+/// Early-start segment size in 8 kHz samples (default 4000 = 500 ms).
+/// The deprecated `TTS_STREAMING_EARLY_START_BYTES` is still honored when the new variable is unset.
+pub fn tts_streaming_early_start_samples() -> usize {
+    *TTS_STREAMING_EARLY_START_SAMPLES.get_or_init(|| {
+        early_start_samples(
+            env_non_empty("TTS_STREAMING_EARLY_START_SAMPLES"),
+            env_non_empty("TTS_STREAMING_EARLY_START_BYTES"),
+        )
+    })
+}
+
+/// 旧設定のバイト数は VOICEVOX の 24 kHz / 16 bit モノラル PCM のもの。
+/// 1 サンプル（8 kHz）あたり 24 kHz × 2 bytes ÷ 8 kHz = 6 bytes で換算する
+const LEGACY_EARLY_START_BYTES_PER_SAMPLE: u64 = 6;
+
+fn early_start_samples(samples: Option<String>, legacy_bytes: Option<String>) -> usize {
+    const DEFAULT_SAMPLES: u64 = 4_000;
+    if let Some(samples) = samples {
+        return samples.trim().parse::<u64>().unwrap_or(DEFAULT_SAMPLES) as usize;
+    }
+    let Some(bytes) = legacy_bytes else {
+        return DEFAULT_SAMPLES as usize;
+    };
+    let converted = bytes
+        .trim()
+        .parse::<u64>()
+        .map(|bytes| (bytes / LEGACY_EARLY_START_BYTES_PER_SAMPLE).max(1))
+        .unwrap_or(DEFAULT_SAMPLES);
+    log::warn!(
+        "[config] TTS_STREAMING_EARLY_START_BYTES is deprecated; use TTS_STREAMING_EARLY_START_SAMPLES={} (8 kHz samples) instead",
+        converted
+    );
+    converted as usize
+}
+
+/// Optional directory to dump streamed TTS WAVs for debugging (unset = no file output).
+pub fn tts_streaming_debug_wav_dir() -> Option<&'static str> {
+    TTS_STREAMING_DEBUG_WAV_DIR
+        .get_or_init(|| env_non_empty("TTS_STREAMING_DEBUG_WAV_DIR"))
+        .as_deref()
 }
 
 pub fn asr_streaming_server_url() -> String {
@@ -142,6 +191,58 @@ pub fn asr_streaming_final_timeout() -> Duration {
         .get_or_init(|| env_duration_ms("ASR_STREAMING_FINAL_TIMEOUT_MS", 2_000))
 }
 
+/// intent_router.yaml / プロンプトファイルの更新監視間隔（0 で監視しない）
+pub fn config_reload_poll_interval() -> Duration {
+    *CONFIG_RELOAD_POLL_INTERVAL
+        .get_or_init(|| env_duration_sec("CONFIG_RELOAD_POLL_INTERVAL_SEC", 5))
+}
+
+/// イベントストリームの再送用に保持する直近イベント数（Last-Event-ID での再開に使う）
+pub fn call_event_replay_buffer() -> usize {
+    *CALL_EVENT_REPLAY_BUFFER
+        .get_or_init(|| env_u64("CALL_EVENT_REPLAY_BUFFER", 1000).max(1) as usize)
+}
+
+/// イベントストリームの ping 間隔
+pub fn call_event_heartbeat() -> Duration {
+    *CALL_EVENT_HEARTBEAT
+        .get_or_init(|| Duration::from_secs(env_u64("CALL_EVENT_HEARTBEAT_SEC", 15).max(1)))
+}
+
+/// 推定 MOS がこれを下回った通話を品質アラートとして扱う（0 で無効）
+pub fn media_quality_mos_alert_threshold() -> f32 {
+    *MEDIA_QUALITY_MOS_ALERT_THRESHOLD
+        .get_or_init(|| env_f32("MEDIA_QUALITY_MOS_ALERT_THRESHOLD", 3.5).max(0.0))
+}
+
+/// 通話モニタリング（傍聴・ささやき・割り込み）の WebSocket に必要なトークン。未設定なら無効
+pub fn supervisor_api_token() -> Option<&'static str> {
+    SUPERVISOR_API_TOKEN
+        .get_or_init(|| env_non_empty("SUPERVISOR_API_TOKEN"))
+        .as_deref()
+}
+
+/// 録音を一時停止する DTMF の並び（例: `*7`）。未設定なら DTMF では止めない
+pub fn recording_pause_dtmf() -> Option<&'static str> {
+    RECORDING_PAUSE_DTMF
+        .get_or_init(|| env_non_empty("RECORDING_PAUSE_DTMF"))
+        .as_deref()
+}
+
+/// 録音を再開する DTMF の並び（例: `*8`）
+pub fn recording_resume_dtmf() -> Option<&'static str> {
+    RECORDING_RESUME_DTMF
+        .get_or_init(|| env_non_empty("RECORDING_RESUME_DTMF"))
+        .as_deref()
+}
+
+/// 一時停止のまま放置されたときに自動で再開するまでの時間（0 で自動再開しない）
+pub fn recording_pause_max() -> Option<Duration> {
+    *RECORDING_PAUSE_MAX.get_or_init(|| {
+        Some(env_duration_sec("RECORDING_PAUSE_MAX_SEC", 300)).filter(|max| !max.is_zero())
+    })
+}
+
 fn default_asr_streaming_server_url() -> String {
     let source = ai_config().asr_local_server_url.trim();
     let (scheme, rest) = if let Some(rest) = source.strip_prefix("http://") {
@@ -166,6 +267,7 @@ pub struct SessionRuntimeConfig {
     pub ivr_timeout: Duration,
     pub transfer_target_uri: String,
     pub transfer_timeout: Duration,
+    pub hunt_groups: HuntGroupsConfig,
     pub registrar: Option<RegistrarConfig>,
     pub outbound: OutboundConfig,
     pub advertised_ip: String,
@@ -182,6 +284,7 @@ impl SessionRuntimeConfig {
             ivr_timeout: Duration::from_secs(env_u64("IVR_TIMEOUT_SEC", 10)),
             transfer_target_uri: transfer_target_uri_from_env(),
             transfer_timeout: Duration::from_secs(env_u64("TRANSFER_TIMEOUT_SEC", 30)),
+            hunt_groups: HuntGroupsConfig::from_env(),
             registrar,
             outbound,
             advertised_ip: base.advertised_ip.clone(),
@@ -190,6 +293,362 @@ impl SessionRuntimeConfig {
     }
 }
 
+/// 転送先グループの呼び出し方
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HuntStrategy {
+    /// メンバーごとの呼出時間で順番に呼び出す
+    #[default]
+    Sequential,
+    /// 全員を同時に呼び出し、最初の 200 OK 以外は CANCEL する
+    Simultaneous,
+    /// 呼び出し開始位置を通話ごとにずらしながら順番に呼び出す
+    RoundRobin,
+}
+
+/// 全メンバーが応答しなかったときの扱い
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HuntNoAnswerAction {
+    #[default]
+    Hangup,
+    Voicemail,
+    Voicebot,
+    /// 待ち呼キューに入れて空いたエージェントへ順番につなぐ
+    Queue,
+}
+
+#[derive(Clone, Debug)]
+pub struct HuntGroup {
+    pub name: String,
+    pub strategy: HuntStrategy,
+    /// 呼び出し先の SIP URI（番号のみの指定は既定の転送先ホストで補完済み）
+    pub members: Vec<String>,
+    pub member_ring_timeout: Duration,
+    pub no_answer: HuntNoAnswerAction,
+    /// no_answer=queue で入る待ち呼キュー（未指定ならグループ名と同じキュー）
+    pub queue: Option<String>,
+    /// 待ち呼キュー内の優先度（大きいほど先に案内する）
+    pub queue_priority: i32,
+    /// ラウンドロビンの次回開始位置（通話間で共有）
+    next_start: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl HuntGroup {
+    pub fn new(
+        name: impl Into<String>,
+        strategy: HuntStrategy,
+        members: Vec<String>,
+        member_ring_timeout: Duration,
+        no_answer: HuntNoAnswerAction,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            strategy,
+            members,
+            member_ring_timeout,
+            no_answer,
+            queue: None,
+            queue_priority: 0,
+            next_start: Default::default(),
+        }
+    }
+
+    /// 全員不応答時に入る待ち呼キュー名
+    pub fn queue_name(&self) -> &str {
+        self.queue.as_deref().unwrap_or(self.name.as_str())
+    }
+
+    /// 今回の通話で呼び出す順番。ラウンドロビンでは呼ぶたびに先頭が 1 つ進む
+    pub fn ring_order(&self) -> Vec<String> {
+        if self.strategy != HuntStrategy::RoundRobin || self.members.is_empty() {
+            return self.members.clone();
+        }
+        let start = self
+            .next_start
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+            % self.members.len();
+        self.members[start..]
+            .iter()
+            .chain(self.members[..start].iter())
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct HuntGroupsFile {
+    #[serde(default)]
+    default_group: Option<String>,
+    #[serde(default)]
+    groups: HashMap<String, HuntGroupEntry>,
+    #[serde(default)]
+    queues: HashMap<String, CallQueueEntry>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct HuntGroupEntry {
+    #[serde(default)]
+    strategy: HuntStrategy,
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    ring_timeout_sec: Option<u64>,
+    #[serde(default)]
+    no_answer: HuntNoAnswerAction,
+    #[serde(default)]
+    queue: Option<String>,
+    #[serde(default)]
+    queue_priority: i32,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct CallQueueEntry {
+    #[serde(default)]
+    agents: Vec<String>,
+    #[serde(default)]
+    moh_announcement_id: Option<Uuid>,
+    #[serde(default)]
+    position_interval_sec: Option<u64>,
+    #[serde(default)]
+    agent_ring_timeout_sec: Option<u64>,
+    #[serde(default)]
+    wrap_up_sec: Option<u64>,
+    #[serde(default)]
+    voicemail_key: Option<char>,
+    #[serde(default)]
+    callback_key: Option<char>,
+}
+
+/// 待ち呼キュー（ACD）の設定
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallQueueConfig {
+    pub name: String,
+    /// 着信を配るエージェントの SIP URI
+    pub agents: Vec<String>,
+    /// 保留音に使うアナウンス（未指定なら転送中の案内音声を繰り返す）
+    pub moh_announcement_id: Option<Uuid>,
+    /// 「N 番目にお待ちです」の案内間隔（0 なら案内しない）
+    pub position_interval: Duration,
+    pub agent_ring_timeout: Duration,
+    /// 通話終了・不応答の後、同じエージェントへ次の呼を配るまでの間隔
+    pub wrap_up: Duration,
+    /// 待ち中に押すと留守番電話へ切り替えるキー
+    pub voicemail_key: Option<char>,
+    /// 待ち中に押すと折り返し依頼を受け付けて切断するキー
+    pub callback_key: Option<char>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HuntGroupsConfig {
+    groups: HashMap<String, HuntGroup>,
+    default_group: HuntGroup,
+    queues: HashMap<String, CallQueueConfig>,
+    /// 番号だけの転送先を SIP URI に補完するときのホスト
+    fallback_uri: String,
+    /// 運用 API で転送できる、グループのメンバー以外の宛先（SIP URI に補完済み）
+    transfer_allowlist: Vec<String>,
+}
+
+impl HuntGroupsConfig {
+    fn from_env() -> Self {
+        // Defaults: TRANSFER_TARGET_SIP_URI only (single member, TRANSFER_TIMEOUT_SEC, hangup).
+        // Env: TRANSFER_HUNT_GROUPS_FILE (YAML) / TRANSFER_NO_ANSWER_ACTION /
+        //      LIVE_CALL_TRANSFER_ALLOWLIST.
+        let allowlist: Vec<String> = env_non_empty("LIVE_CALL_TRANSFER_ALLOWLIST")
+            .map(|value| value.split(',').map(str::to_string).collect())
+            .unwrap_or_default();
+        Self::load_groups().with_transfer_allowlist(&allowlist)
+    }
+
+    fn load_groups() -> Self {
+        let fallback_uri = transfer_target_uri_from_env();
+        let fallback = HuntGroup::new(
+            "default",
+            HuntStrategy::Sequential,
+            vec![fallback_uri.clone()],
+            Duration::from_secs(env_u64("TRANSFER_TIMEOUT_SEC", 30)),
+            env_non_empty("TRANSFER_NO_ANSWER_ACTION")
+                .and_then(|value| {
+                    serde_yaml::from_str::<HuntNoAnswerAction>(&value.to_ascii_lowercase()).ok()
+                })
+                .unwrap_or_default(),
+        );
+        let Some(path) = env_non_empty("TRANSFER_HUNT_GROUPS_FILE") else {
+            return Self::single(fallback, fallback_uri);
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text, fallback.clone(), &fallback_uri).unwrap_or_else(|err| {
+                log::warn!("[config] failed to parse hunt groups {}: {}", path, err);
+                Self::single(fallback, fallback_uri)
+            }),
+            Err(err) => {
+                log::warn!("[config] hunt groups file not found {}: {}", path, err);
+                Self::single(fallback, fallback_uri)
+            }
+        }
+    }
+
+    fn single(default_group: HuntGroup, fallback_uri: String) -> Self {
+        Self {
+            groups: HashMap::new(),
+            default_group,
+            queues: HashMap::new(),
+            fallback_uri,
+            transfer_allowlist: Vec::new(),
+        }
+    }
+
+    /// 運用 API の転送先に、グループのメンバー以外の番号・SIP URI を許可する
+    pub(crate) fn with_transfer_allowlist(mut self, entries: &[String]) -> Self {
+        self.transfer_allowlist = entries
+            .iter()
+            .filter_map(|entry| hunt_member_uri(entry, &self.fallback_uri))
+            .collect();
+        self
+    }
+
+    pub(crate) fn parse(text: &str, fallback: HuntGroup, fallback_uri: &str) -> Result<Self> {
+        let file: HuntGroupsFile = serde_yaml::from_str(text)?;
+        let mut groups = HashMap::new();
+        for (name, entry) in file.groups {
+            let members: Vec<String> = entry
+                .members
+                .iter()
+                .filter_map(|member| hunt_member_uri(member, fallback_uri))
+                .collect();
+            if members.is_empty() {
+                log::warn!("[config] hunt group {} has no members, skipped", name);
+                continue;
+            }
+            let ring_timeout = entry
+                .ring_timeout_sec
+                .filter(|sec| *sec > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(fallback.member_ring_timeout);
+            let mut group = HuntGroup::new(
+                name.clone(),
+                entry.strategy,
+                members,
+                ring_timeout,
+                entry.no_answer,
+            );
+            group.queue = entry.queue.filter(|queue| !queue.trim().is_empty());
+            group.queue_priority = entry.queue_priority;
+            groups.insert(name, group);
+        }
+        let mut queues = HashMap::new();
+        for (name, entry) in file.queues {
+            let agents: Vec<String> = entry
+                .agents
+                .iter()
+                .filter_map(|agent| hunt_member_uri(agent, fallback_uri))
+                .collect();
+            if agents.is_empty() {
+                log::warn!("[config] call queue {} has no agents, skipped", name);
+                continue;
+            }
+            let queue = CallQueueConfig {
+                name: name.clone(),
+                agents,
+                moh_announcement_id: entry.moh_announcement_id,
+                position_interval: Duration::from_secs(entry.position_interval_sec.unwrap_or(30)),
+                agent_ring_timeout: entry
+                    .agent_ring_timeout_sec
+                    .filter(|sec| *sec > 0)
+                    .map(Duration::from_secs)
+                    .unwrap_or(fallback.member_ring_timeout),
+                wrap_up: Duration::from_secs(entry.wrap_up_sec.unwrap_or(10)),
+                voicemail_key: entry.voicemail_key,
+                callback_key: entry.callback_key,
+            };
+            queues.insert(name, queue);
+        }
+        let default_group = file
+            .default_group
+            .as_deref()
+            .and_then(|name| groups.get(name).cloned())
+            .unwrap_or(fallback);
+        Ok(Self {
+            groups,
+            default_group,
+            queues,
+            fallback_uri: fallback_uri.to_string(),
+            transfer_allowlist: Vec::new(),
+        })
+    }
+
+    /// 転送先キー（電話帳の名前やグループ名）に対応するグループ。なければ既定グループ
+    pub fn resolve(&self, key: &str) -> &HuntGroup {
+        self.groups.get(key.trim()).unwrap_or(&self.default_group)
+    }
+
+    /// 運用者が指定した転送先。グループ名ならそのグループ、番号・SIP URI なら
+    /// その 1 件だけを呼び出すグループ（不応答なら切断）。番号・SIP URI はグループ・キューの
+    /// メンバー（内線）か LIVE_CALL_TRANSFER_ALLOWLIST にあるものだけ受け付ける（外線への不正転送対策）
+    pub fn resolve_target(&self, target: &str) -> Option<HuntGroup> {
+        let target = target.trim();
+        if let Some(group) = self.groups.get(target) {
+            return Some(group.clone());
+        }
+        if target == self.default_group.name {
+            return Some(self.default_group.clone());
+        }
+        let is_number = !target.is_empty()
+            && target
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, '+' | '*' | '#'));
+        if !is_number && !target.starts_with("sip:") && !target.starts_with("sips:") {
+            return None;
+        }
+        let uri = hunt_member_uri(target, &self.fallback_uri)?;
+        if !self.is_known_extension(&uri) && !self.transfer_allowlist.contains(&uri) {
+            log::warn!("[config] transfer target not allowed: {}", target);
+            return None;
+        }
+        Some(HuntGroup::new(
+            target,
+            HuntStrategy::Sequential,
+            vec![uri],
+            self.default_group.member_ring_timeout,
+            HuntNoAnswerAction::Hangup,
+        ))
+    }
+
+    /// グループ・キューのメンバーとして設定されている宛先か
+    fn is_known_extension(&self, uri: &str) -> bool {
+        std::iter::once(&self.default_group.members)
+            .chain(self.groups.values().map(|group| &group.members))
+            .chain(self.queues.values().map(|queue| &queue.agents))
+            .any(|members| members.iter().any(|member| member == uri))
+    }
+
+    pub fn queue(&self, name: &str) -> Option<&CallQueueConfig> {
+        self.queues.get(name.trim())
+    }
+
+    pub fn queues(&self) -> impl Iterator<Item = &CallQueueConfig> {
+        self.queues.values()
+    }
+}
+
+/// メンバー指定を SIP URI にする。番号だけなら既定の転送先と同じホストへ送る
+fn hunt_member_uri(member: &str, fallback_uri: &str) -> Option<String> {
+    let member = member.trim();
+    if member.is_empty() {
+        return None;
+    }
+    if member.contains(':') {
+        return Some(member.to_string());
+    }
+    let host = fallback_uri
+        .split_once(':')
+        .map(|(_, rest)| rest)
+        .unwrap_or(fallback_uri);
+    let host = host.rsplit_once('@').map(|(_, host)| host).unwrap_or(host);
+    Some(format!("sip:{}@{}", member, host))
+}
+
 impl Config {
     /// Create a Config populated from environment variables, falling back to sensible defaults when keys are absent.
     ///
@@ -811,6 +1270,573 @@ pub fn rtp_config() -> &'static RtpConfig {
     RTP_CONFIG.get_or_init(RtpConfig::from_env)
 }
 
+#[derive(Clone, Debug)]
+pub struct CallerMemoryConfig {
+    pub enabled: bool,
+    pub retention: Duration,
+    pub max_summary_chars: usize,
+    pub max_key_facts: usize,
+    /// 保存期間を過ぎたメモリを削除する間隔
+    pub purge_interval: Duration,
+}
+
+impl CallerMemoryConfig {
+    fn from_env() -> Self {
+        // Defaults: opt-in, 90 days retention, summary 400 chars, 10 key facts, purge hourly.
+        // Env: CALLER_MEMORY_ENABLED / CALLER_MEMORY_RETENTION_DAYS /
+        //      CALLER_MEMORY_MAX_SUMMARY_CHARS / CALLER_MEMORY_MAX_KEY_FACTS /
+        //      CALLER_MEMORY_PURGE_INTERVAL_SEC.
+        let retention_days = env_u64("CALLER_MEMORY_RETENTION_DAYS", 90);
+        Self {
+            enabled: env_bool("CALLER_MEMORY_ENABLED", false),
+            retention: Duration::from_secs(retention_days.saturating_mul(24 * 60 * 60)),
+            max_summary_chars: env_u64("CALLER_MEMORY_MAX_SUMMARY_CHARS", 400) as usize,
+            max_key_facts: env_u64("CALLER_MEMORY_MAX_KEY_FACTS", 10) as usize,
+            purge_interval: Duration::from_secs(
+                env_u64("CALLER_MEMORY_PURGE_INTERVAL_SEC", 3600).max(60),
+            ),
+        }
+    }
+
+    /// Oldest `last_call_at` still within the retention window.
+    pub fn retention_cutoff(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> chrono::DateTime<chrono::Utc> {
+        chrono::Duration::from_std(self.retention)
+            .ok()
+            .and_then(|retention| now.checked_sub_signed(retention))
+            .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC)
+    }
+}
+
+static CALLER_MEMORY_CONFIG: OnceLock<CallerMemoryConfig> = OnceLock::new();
+
+pub fn caller_memory_config() -> &'static CallerMemoryConfig {
+    CALLER_MEMORY_CONFIG.get_or_init(CallerMemoryConfig::from_env)
+}
+
+#[derive(Clone, Debug)]
+pub struct TtsCacheConfig {
+    pub enabled: bool,
+    pub dir: String,
+    pub max_entries: usize,
+}
+
+impl TtsCacheConfig {
+    fn from_env() -> Self {
+        // Defaults: enabled, data/tts_cache, 256 entries (LRU).
+        // Env: TTS_CACHE_ENABLED / TTS_CACHE_DIR / TTS_CACHE_MAX_ENTRIES.
+        Self {
+            enabled: env_bool("TTS_CACHE_ENABLED", true),
+            dir: env_non_empty("TTS_CACHE_DIR").unwrap_or_else(|| "data/tts_cache".to_string()),
+            max_entries: env_u64("TTS_CACHE_MAX_ENTRIES", 256).max(1) as usize,
+        }
+    }
+}
+
+static TTS_CACHE_CONFIG: OnceLock<TtsCacheConfig> = OnceLock::new();
+
+pub fn tts_cache_config() -> &'static TtsCacheConfig {
+    TTS_CACHE_CONFIG.get_or_init(TtsCacheConfig::from_env)
+}
+
+#[derive(Clone, Debug)]
+pub struct AiStageHealthConfig {
+    pub breaker_enabled: bool,
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+    pub probe_interval: Duration,
+    pub latency_ordering: bool,
+    pub stats_window: usize,
+    pub min_samples: usize,
+}
+
+impl AiStageHealthConfig {
+    fn from_env() -> Self {
+        // Defaults: breaker on (3 consecutive failures, 30s cooldown, 10s local probe),
+        // latency ordering off, p50 over the last 50 samples once 5 are collected.
+        Self {
+            breaker_enabled: env_bool("AI_BREAKER_ENABLED", true),
+            failure_threshold: env_u32("AI_BREAKER_FAILURE_THRESHOLD", 3).max(1),
+            cooldown: env_duration_ms("AI_BREAKER_COOLDOWN_MS", 30_000),
+            probe_interval: env_duration_ms("AI_BREAKER_PROBE_INTERVAL_MS", 10_000),
+            latency_ordering: env_bool("AI_STAGE_LATENCY_ORDERING", false),
+            stats_window: env_u64("AI_STAGE_STATS_WINDOW", 50).max(1) as usize,
+            min_samples: env_u64("AI_STAGE_LATENCY_MIN_SAMPLES", 5).max(1) as usize,
+        }
+    }
+}
+
+static AI_STAGE_HEALTH_CONFIG: OnceLock<AiStageHealthConfig> = OnceLock::new();
+
+pub fn ai_stage_health_config() -> &'static AiStageHealthConfig {
+    AI_STAGE_HEALTH_CONFIG.get_or_init(AiStageHealthConfig::from_env)
+}
+
+#[derive(Clone, Debug)]
+pub struct EmotionPolicyConfig {
+    pub escalation_enabled: bool,
+    /// 小文字の感情ラベル（neutral / happy / sad / angry）
+    pub escalation_emotions: Vec<String>,
+    pub min_confidence: f32,
+    pub consecutive_turns: u32,
+    /// intent_router.yaml の transfer.directory に登録された転送先名（別名可）
+    pub transfer_person: Option<String>,
+    pub apology_text: String,
+    pub tone_adjust_enabled: bool,
+}
+
+impl EmotionPolicyConfig {
+    fn from_env() -> Self {
+        // Defaults: escalation off (angry x2 turns >= 0.7 when enabled), tone adjustment on.
+        // Env: EMOTION_ESCALATION_ENABLED / EMOTION_ESCALATION_EMOTIONS /
+        //      EMOTION_ESCALATION_MIN_CONFIDENCE / EMOTION_ESCALATION_CONSECUTIVE_TURNS /
+        //      EMOTION_ESCALATION_TRANSFER_PERSON / EMOTION_ESCALATION_APOLOGY_TEXT /
+        //      EMOTION_TONE_ADJUST_ENABLED.
+        let escalation_emotions = env_non_empty("EMOTION_ESCALATION_EMOTIONS")
+            .unwrap_or_else(|| "angry".to_string())
+            .split(',')
+            .map(|value| value.trim().to_ascii_lowercase())
+            .filter(|value| !value.is_empty())
+            .collect();
+        Self {
+            escalation_enabled: env_bool("EMOTION_ESCALATION_ENABLED", false),
+            escalation_emotions,
+            min_confidence: env_f32("EMOTION_ESCALATION_MIN_CONFIDENCE", 0.7).clamp(0.0, 1.0),
+            consecutive_turns: env_u32("EMOTION_ESCALATION_CONSECUTIVE_TURNS", 2).max(1),
+            transfer_person: env_non_empty("EMOTION_ESCALATION_TRANSFER_PERSON"),
+            apology_text: env_non_empty("EMOTION_ESCALATION_APOLOGY_TEXT").unwrap_or_else(|| {
+                "ご不便をおかけして大変申し訳ございません。担当の者におつなぎしますので、少々お待ちください。"
+                    .to_string()
+            }),
+            tone_adjust_enabled: env_bool("EMOTION_TONE_ADJUST_ENABLED", true),
+        }
+    }
+}
+
+static EMOTION_POLICY_CONFIG: OnceLock<EmotionPolicyConfig> = OnceLock::new();
+
+pub fn emotion_policy_config() -> &'static EmotionPolicyConfig {
+    EMOTION_POLICY_CONFIG.get_or_init(EmotionPolicyConfig::from_env)
+}
+
+#[derive(Clone, Debug)]
+pub struct ScheduleRoutingConfig {
+    pub enabled: bool,
+    /// スケジュール判定に使うローカル時刻の UTC オフセット（夏時間は非対応）
+    pub utc_offset: chrono::FixedOffset,
+}
+
+impl ScheduleRoutingConfig {
+    fn from_env() -> Self {
+        // Defaults: disabled, +09:00 (JST).
+        // Env: SCHEDULE_ROUTING_ENABLED / SCHEDULE_TIMEZONE ("+09:00", "-0500", "UTC").
+        let default_offset = chrono::FixedOffset::east_opt(9 * 60 * 60).expect("valid offset");
+        let utc_offset = match env_non_empty("SCHEDULE_TIMEZONE") {
+            Some(raw) => parse_utc_offset(&raw).unwrap_or_else(|| {
+                log::warn!(
+                    "[config] invalid SCHEDULE_TIMEZONE={}, fallback to +09:00",
+                    raw
+                );
+                default_offset
+            }),
+            None => default_offset,
+        };
+        Self {
+            enabled: env_bool("SCHEDULE_ROUTING_ENABLED", false),
+            utc_offset,
+        }
+    }
+}
+
+static SCHEDULE_ROUTING_CONFIG: OnceLock<ScheduleRoutingConfig> = OnceLock::new();
+
+pub fn schedule_routing_config() -> &'static ScheduleRoutingConfig {
+    SCHEDULE_ROUTING_CONFIG.get_or_init(ScheduleRoutingConfig::from_env)
+}
+
+fn parse_utc_offset(raw: &str) -> Option<chrono::FixedOffset> {
+    let value = raw.trim();
+    if value.eq_ignore_ascii_case("utc") || value.eq_ignore_ascii_case("z") {
+        return chrono::FixedOffset::east_opt(0);
+    }
+    let (sign, rest) = match value.as_bytes().first()? {
+        b'+' => (1, &value[1..]),
+        b'-' => (-1, &value[1..]),
+        _ => return None,
+    };
+    let digits: String = rest.chars().filter(|c| *c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        1 | 2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+    chrono::FixedOffset::east_opt(sign * (hours * 60 * 60 + minutes * 60))
+}
+
+#[derive(Clone, Debug)]
+pub struct VoicemailConfig {
+    /// 留守番電話の再生メニューにつながる着信番号（To のユーザ部、E.164 または国内表記）
+    pub access_number: Option<String>,
+    pub dir: String,
+    /// これより短い録音（無言切断など）はメッセージとして保存しない
+    pub min_duration_sec: u64,
+    /// 1 通話で許す PIN の入力回数（超えたら切断）
+    pub max_pin_attempts: u32,
+    /// 通話をまたいで連続した PIN 誤りがこの回数に達したらメールボックスをロックする
+    pub pin_lockout_attempts: u32,
+    pub pin_lockout: Duration,
+    pub transcription_enabled: bool,
+    pub webhook_url: Option<String>,
+}
+
+impl VoicemailConfig {
+    fn from_env() -> Self {
+        // Defaults: no access number (retrieval disabled), storage/voicemail, 2s minimum,
+        // 3 PIN attempts per call, lock the mailbox for 15min after 5 consecutive failures,
+        // transcription on, no webhook.
+        // Env: VOICEMAIL_ACCESS_NUMBER / VOICEMAIL_DIR / VOICEMAIL_MIN_DURATION_SEC /
+        //      VOICEMAIL_MAX_PIN_ATTEMPTS / VOICEMAIL_PIN_LOCKOUT_ATTEMPTS /
+        //      VOICEMAIL_PIN_LOCKOUT_SEC / VOICEMAIL_TRANSCRIPTION_ENABLED / VOICEMAIL_WEBHOOK_URL.
+        Self {
+            access_number: env_non_empty("VOICEMAIL_ACCESS_NUMBER"),
+            dir: env_non_empty("VOICEMAIL_DIR").unwrap_or_else(|| "storage/voicemail".to_string()),
+            min_duration_sec: env_u64("VOICEMAIL_MIN_DURATION_SEC", 2),
+            max_pin_attempts: env_u32("VOICEMAIL_MAX_PIN_ATTEMPTS", 3).max(1),
+            pin_lockout_attempts: env_u32("VOICEMAIL_PIN_LOCKOUT_ATTEMPTS", 5).max(1),
+            pin_lockout: Duration::from_secs(env_u64("VOICEMAIL_PIN_LOCKOUT_SEC", 900).max(1)),
+            transcription_enabled: env_bool("VOICEMAIL_TRANSCRIPTION_ENABLED", true),
+            webhook_url: env_non_empty("VOICEMAIL_WEBHOOK_URL"),
+        }
+    }
+}
+
+static VOICEMAIL_CONFIG: OnceLock<VoicemailConfig> = OnceLock::new();
+
+pub fn voicemail_config() -> &'static VoicemailConfig {
+    VOICEMAIL_CONFIG.get_or_init(VoicemailConfig::from_env)
+}
+
+/// 録音の保存形式（後処理で WAV から変換する）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordingArchiveFormat {
+    /// 変換しない
+    Wav,
+    Flac,
+    /// Ogg Opus（ffmpeg で変換、使えなければ FLAC）
+    Opus,
+}
+
+/// 録音の暗号化鍵（AES-256-GCM）
+#[derive(Clone)]
+pub struct RecordingKey {
+    /// 暗号化したファイルに記録し、復号時に鍵を選ぶための ID
+    pub id: String,
+    pub key: [u8; 32],
+}
+
+impl std::fmt::Debug for RecordingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordingKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 発信者区分・アクションコードごとの録音保持期間
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordingRetentionRule {
+    pub caller_category: Option<String>,
+    pub action_code: Option<String>,
+    /// None なら無期限
+    pub retention: Option<Duration>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordingStorageConfig {
+    pub archive_format: RecordingArchiveFormat,
+    /// 通話終了からこの時間が経った録音を変換・暗号化する（直後の留守電処理・同期送信は WAV を読む）
+    pub archive_after: Duration,
+    pub opus_bitrate_kbps: u32,
+    pub ffmpeg_path: String,
+    /// 先頭が新しく暗号化する鍵。残りは既存ファイルの復号と再暗号化のためだけに持つ
+    pub encryption_keys: Vec<RecordingKey>,
+    /// 既定の保持期間（None なら無期限）
+    pub retention: Option<Duration>,
+    /// 先に書いたものから照合し、最初に一致した規則の保持期間を使う
+    pub retention_rules: Vec<RecordingRetentionRule>,
+    pub janitor_interval: Duration,
+    /// これより古い TTS の一時 WAV を削除する
+    pub tts_temp_max_age: Duration,
+}
+
+impl RecordingStorageConfig {
+    fn from_env() -> Self {
+        // Defaults: keep WAV, archive 10 minutes after the call, Opus 24kbps via `ffmpeg`,
+        // no encryption, keep forever, janitor hourly, TTS temp files older than 1 hour.
+        // Env: RECORDING_ARCHIVE_FORMAT / RECORDING_ARCHIVE_AFTER_SEC /
+        //      RECORDING_OPUS_BITRATE_KBPS / RECORDING_FFMPEG_PATH / RECORDING_ENCRYPTION_KEYS /
+        //      RECORDING_RETENTION_DAYS / RECORDING_RETENTION_RULES /
+        //      RECORDING_JANITOR_INTERVAL_SEC / TTS_TEMP_MAX_AGE_SEC.
+        let archive_format = match env_non_empty("RECORDING_ARCHIVE_FORMAT")
+            .map(|value| value.to_ascii_lowercase())
+            .as_deref()
+        {
+            None | Some("wav") => RecordingArchiveFormat::Wav,
+            Some("flac") => RecordingArchiveFormat::Flac,
+            Some("opus") => RecordingArchiveFormat::Opus,
+            Some(other) => {
+                log::warn!(
+                    "[config] unknown RECORDING_ARCHIVE_FORMAT={}, keeping wav",
+                    other
+                );
+                RecordingArchiveFormat::Wav
+            }
+        };
+        Self {
+            archive_format,
+            archive_after: env_duration_sec("RECORDING_ARCHIVE_AFTER_SEC", 600),
+            opus_bitrate_kbps: env_u32("RECORDING_OPUS_BITRATE_KBPS", 24).clamp(6, 128),
+            ffmpeg_path: env_non_empty("RECORDING_FFMPEG_PATH")
+                .unwrap_or_else(|| "ffmpeg".to_string()),
+            encryption_keys: env_non_empty("RECORDING_ENCRYPTION_KEYS")
+                .map(|value| parse_recording_keys(&value))
+                .unwrap_or_default(),
+            retention: days_to_retention(env_u64("RECORDING_RETENTION_DAYS", 0)),
+            retention_rules: env_non_empty("RECORDING_RETENTION_RULES")
+                .map(|value| parse_retention_rules(&value))
+                .unwrap_or_default(),
+            janitor_interval: Duration::from_secs(
+                env_u64("RECORDING_JANITOR_INTERVAL_SEC", 3600).max(60),
+            ),
+            tts_temp_max_age: env_duration_sec("TTS_TEMP_MAX_AGE_SEC", 3600),
+        }
+    }
+
+    pub fn active_key(&self) -> Option<&RecordingKey> {
+        self.encryption_keys.first()
+    }
+
+    pub fn key(&self, id: &str) -> Option<&RecordingKey> {
+        self.encryption_keys.iter().find(|key| key.id == id)
+    }
+
+    /// 変換・暗号化の後処理が必要か
+    pub fn archive_enabled(&self) -> bool {
+        self.archive_format != RecordingArchiveFormat::Wav || !self.encryption_keys.is_empty()
+    }
+
+    pub fn retention_for(&self, caller_category: &str, action_code: &str) -> Option<Duration> {
+        self.retention_rules
+            .iter()
+            .find(|rule| {
+                rule.caller_category
+                    .as_deref()
+                    .is_none_or(|category| category.eq_ignore_ascii_case(caller_category))
+                    && rule
+                        .action_code
+                        .as_deref()
+                        .is_none_or(|code| code.eq_ignore_ascii_case(action_code))
+            })
+            .map_or(self.retention, |rule| rule.retention)
+    }
+}
+
+fn days_to_retention(days: u64) -> Option<Duration> {
+    (days > 0).then(|| Duration::from_secs(days.saturating_mul(24 * 60 * 60)))
+}
+
+/// `<keyId>:<64 桁の hex>` をカンマ区切りで並べたもの。不正な鍵は捨てる
+fn parse_recording_keys(value: &str) -> Vec<RecordingKey> {
+    let mut keys = Vec::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.split_once(':').and_then(|(id, hex)| {
+            let id = id.trim();
+            let hex = hex.trim();
+            if id.is_empty() || hex.len() != 64 {
+                return None;
+            }
+            let mut key = [0u8; 32];
+            for (byte, pair) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+                *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+            }
+            Some(RecordingKey {
+                id: id.to_string(),
+                key,
+            })
+        });
+        match parsed {
+            Some(key) => keys.push(key),
+            None => log::error!(
+                "[config] ignoring invalid RECORDING_ENCRYPTION_KEYS entry (expected <id>:<64 hex>)"
+            ),
+        }
+    }
+    keys
+}
+
+/// `category:spam=7,action:VR=365,category:registered+action:VB=0` のような保持期間の規則（日数、0 は無期限）
+fn parse_retention_rules(value: &str) -> Vec<RecordingRetentionRule> {
+    let mut rules = Vec::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.rsplit_once('=').and_then(|(selector, days)| {
+            let days = days.trim().parse::<u64>().ok()?;
+            let mut rule = RecordingRetentionRule {
+                caller_category: None,
+                action_code: None,
+                retention: days_to_retention(days),
+            };
+            for part in selector.split('+') {
+                match part.trim().split_once(':')? {
+                    ("category", category) => {
+                        rule.caller_category = Some(category.trim().to_ascii_lowercase())
+                    }
+                    ("action", code) => rule.action_code = Some(code.trim().to_ascii_uppercase()),
+                    _ => return None,
+                }
+            }
+            (rule.caller_category.is_some() || rule.action_code.is_some()).then_some(rule)
+        });
+        match parsed {
+            Some(rule) => rules.push(rule),
+            None => log::warn!(
+                "[config] ignoring invalid RECORDING_RETENTION_RULES entry: {}",
+                entry
+            ),
+        }
+    }
+    rules
+}
+
+static RECORDING_STORAGE_CONFIG: OnceLock<RecordingStorageConfig> = OnceLock::new();
+
+pub fn recording_storage_config() -> &'static RecordingStorageConfig {
+    RECORDING_STORAGE_CONFIG.get_or_init(RecordingStorageConfig::from_env)
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpSecurityConfig {
+    /// /api/* で受け付ける Bearer トークン（空なら /api/* は全て 401）
+    pub api_tokens: Vec<String>,
+    /// 録音 URL の署名鍵。None なら /recordings/ は署名なしで配信する
+    pub recording_url_secret: Option<Vec<u8>>,
+    pub recording_url_ttl: Duration,
+    /// 許可する Origin。`*` を含めば全オリジン、空なら別オリジンからは呼べない
+    pub cors_allowed_origins: Vec<String>,
+}
+
+impl HttpSecurityConfig {
+    fn from_env() -> Self {
+        // Defaults: no API token (/api/* is refused), unsigned recording URLs valid for 1 hour
+        // once signed, no cross-origin access.
+        // Env: HTTP_API_TOKENS / RECORDING_URL_SIGNING_SECRET / RECORDING_URL_TTL_SEC /
+        //      HTTP_CORS_ALLOWED_ORIGINS.
+        let split = |value: String| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        Self {
+            api_tokens: env_non_empty("HTTP_API_TOKENS")
+                .map(split)
+                .unwrap_or_default(),
+            recording_url_secret: env_non_empty("RECORDING_URL_SIGNING_SECRET")
+                .map(String::into_bytes),
+            recording_url_ttl: Duration::from_secs(env_u64("RECORDING_URL_TTL_SEC", 3600).max(60)),
+            cors_allowed_origins: env_non_empty("HTTP_CORS_ALLOWED_ORIGINS")
+                .map(split)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// リクエストの Origin に返す `Access-Control-Allow-Origin`（許可しないなら None）
+    pub fn cors_allow_origin(&self, origin: Option<&str>) -> Option<String> {
+        if self
+            .cors_allowed_origins
+            .iter()
+            .any(|allowed| allowed == "*")
+        {
+            return Some("*".to_string());
+        }
+        origin
+            .filter(|origin| {
+                self.cors_allowed_origins
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(origin))
+            })
+            .map(str::to_string)
+    }
+}
+
+/// 外部 webhook の配送（購読の定義は webhooks.yaml、こちらは送信と再試行の設定）
+#[derive(Clone, Debug)]
+pub struct WebhookDeliveryConfig {
+    /// 初回を含む送信回数の上限。使い切ったら dead letter にする
+    pub max_attempts: u32,
+    pub retry_base: Duration,
+    pub retry_max_delay: Duration,
+    pub poll_interval: Duration,
+    pub timeout: Duration,
+    pub batch_size: i64,
+}
+
+impl WebhookDeliveryConfig {
+    fn from_env() -> Self {
+        // Defaults: 8 attempts, backoff 30s doubling up to 1 hour, poll every 5s,
+        // 10s request timeout, 20 deliveries per poll.
+        // Env: WEBHOOK_MAX_ATTEMPTS / WEBHOOK_RETRY_BASE_SEC / WEBHOOK_RETRY_MAX_DELAY_SEC /
+        //      WEBHOOK_POLL_INTERVAL_SEC / WEBHOOK_TIMEOUT_SEC / WEBHOOK_BATCH_SIZE.
+        Self {
+            max_attempts: env_u32("WEBHOOK_MAX_ATTEMPTS", 8).max(1),
+            retry_base: Duration::from_secs(env_u64("WEBHOOK_RETRY_BASE_SEC", 30).max(1)),
+            retry_max_delay: Duration::from_secs(
+                env_u64("WEBHOOK_RETRY_MAX_DELAY_SEC", 3600).max(1),
+            ),
+            poll_interval: Duration::from_secs(env_u64("WEBHOOK_POLL_INTERVAL_SEC", 5).max(1)),
+            timeout: Duration::from_secs(env_u64("WEBHOOK_TIMEOUT_SEC", 10).max(1)),
+            batch_size: env_u64("WEBHOOK_BATCH_SIZE", 20).clamp(1, 1000) as i64,
+        }
+    }
+
+    /// `attempts` 回目の送信に失敗した後、次に送るまでの待ち時間。None なら再試行しない
+    pub fn retry_delay(&self, attempts: u32) -> Option<Duration> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        Some(
+            self.retry_base
+                .saturating_mul(factor)
+                .min(self.retry_max_delay),
+        )
+    }
+}
+
+static WEBHOOK_DELIVERY_CONFIG: OnceLock<WebhookDeliveryConfig> = OnceLock::new();
+
+pub fn webhook_delivery_config() -> &'static WebhookDeliveryConfig {
+    WEBHOOK_DELIVERY_CONFIG.get_or_init(WebhookDeliveryConfig::from_env)
+}
+
+static HTTP_SECURITY_CONFIG: OnceLock<HttpSecurityConfig> = OnceLock::new();
+
+pub fn http_security_config() -> &'static HttpSecurityConfig {
+    HTTP_SECURITY_CONFIG.get_or_init(HttpSecurityConfig::from_env)
+}
+
 static IVR_TIMEOUT: OnceLock<Duration> = OnceLock::new();
 
 pub fn ivr_timeout() -> Duration {
@@ -885,6 +1911,14 @@ fn env_u64(key: &str, default_value: u64) -> u64 {
         .unwrap_or(default_value)
 }
 
+fn env_f32(key: &str, default_value: f32) -> f32 {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse::<f32>().ok())
+        .filter(|v| v.is_finite())
+        .unwrap_or(default_value)
+}
+
 fn env_i64(key: &str, default_value: i64) -> i64 {
     std::env::var(key)
         .ok()
@@ -981,6 +2015,265 @@ mod tests {
         );
     }
 
+    #[test]
+    fn recording_retention_rules_match_in_order() {
+        let cfg = RecordingStorageConfig {
+            archive_format: RecordingArchiveFormat::Wav,
+            archive_after: Duration::from_secs(0),
+            opus_bitrate_kbps: 24,
+            ffmpeg_path: "ffmpeg".to_string(),
+            encryption_keys: parse_recording_keys(&format!(
+                "new:{},old:{},broken:abc",
+                "11".repeat(32),
+                "2f".repeat(32)
+            )),
+            retention: days_to_retention(30),
+            retention_rules: parse_retention_rules(
+                "category:registered+action:VR=0, category:spam=7,action:vr=365,bogus=1",
+            ),
+            janitor_interval: Duration::from_secs(3600),
+            tts_temp_max_age: Duration::from_secs(3600),
+        };
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        assert_eq!(cfg.retention_rules.len(), 3);
+        assert_eq!(cfg.retention_for("registered", "VR"), None);
+        assert_eq!(cfg.retention_for("spam", "VR"), Some(day * 7));
+        assert_eq!(cfg.retention_for("unknown", "VR"), Some(day * 365));
+        assert_eq!(cfg.retention_for("unknown", "IV"), Some(day * 30));
+
+        assert_eq!(cfg.encryption_keys.len(), 2);
+        assert_eq!(cfg.active_key().map(|key| key.id.as_str()), Some("new"));
+        assert_eq!(cfg.key("old").map(|key| key.key[0]), Some(0x2f));
+        assert!(cfg.archive_enabled());
+    }
+
+    #[test]
+    fn early_start_samples_falls_back_to_legacy_bytes() {
+        assert_eq!(early_start_samples(None, None), 4_000);
+        assert_eq!(early_start_samples(Some("800".to_string()), None), 800);
+        // 旧設定の既定 24000 bytes（24 kHz / 16 bit で 500 ms）は 4000 サンプル
+        assert_eq!(early_start_samples(None, Some("24000".to_string())), 4_000);
+        assert_eq!(early_start_samples(None, Some("8".to_string())), 1);
+        assert_eq!(
+            early_start_samples(Some("800".to_string()), Some("24000".to_string())),
+            800
+        );
+    }
+
+    #[test]
+    fn cors_allow_origin_echoes_only_listed_origins() {
+        let mut cfg = HttpSecurityConfig {
+            api_tokens: Vec::new(),
+            recording_url_secret: None,
+            recording_url_ttl: Duration::from_secs(3600),
+            cors_allowed_origins: vec!["*".to_string()],
+        };
+        assert_eq!(cfg.cors_allow_origin(None), Some("*".to_string()));
+
+        cfg.cors_allowed_origins = Vec::new();
+        assert_eq!(
+            cfg.cors_allow_origin(Some("https://console.example.com")),
+            None
+        );
+
+        cfg.cors_allowed_origins = vec!["https://console.example.com".to_string()];
+        assert_eq!(
+            cfg.cors_allow_origin(Some("https://console.example.com")),
+            Some("https://console.example.com".to_string())
+        );
+        assert_eq!(
+            cfg.cors_allow_origin(Some("https://evil.example.com")),
+            None
+        );
+        assert_eq!(cfg.cors_allow_origin(None), None);
+    }
+
+    #[test]
+    fn webhook_retry_delay_doubles_until_attempts_run_out() {
+        let cfg = WebhookDeliveryConfig {
+            max_attempts: 4,
+            retry_base: Duration::from_secs(30),
+            retry_max_delay: Duration::from_secs(100),
+            poll_interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(10),
+            batch_size: 20,
+        };
+        assert_eq!(cfg.retry_delay(1), Some(Duration::from_secs(30)));
+        assert_eq!(cfg.retry_delay(2), Some(Duration::from_secs(60)));
+        assert_eq!(cfg.retry_delay(3), Some(Duration::from_secs(100)));
+        assert_eq!(cfg.retry_delay(4), None);
+    }
+
+    #[test]
+    fn parse_utc_offset_accepts_common_forms() {
+        let jst = chrono::FixedOffset::east_opt(9 * 3600);
+        assert_eq!(parse_utc_offset("+09:00"), jst);
+        assert_eq!(parse_utc_offset("+0900"), jst);
+        assert_eq!(parse_utc_offset("+9"), jst);
+        assert_eq!(parse_utc_offset("UTC"), chrono::FixedOffset::east_opt(0));
+        assert_eq!(
+            parse_utc_offset("-05:30"),
+            chrono::FixedOffset::west_opt(5 * 3600 + 30 * 60)
+        );
+        assert_eq!(parse_utc_offset("Asia/Tokyo"), None);
+        assert_eq!(parse_utc_offset("+25:00"), None);
+    }
+
+    #[test]
+    fn hunt_groups_parse_members_and_fall_back_to_default() {
+        let fallback = HuntGroup::new(
+            "default",
+            HuntStrategy::Sequential,
+            vec!["sip:zoiper@192.168.1.4:8000".to_string()],
+            Duration::from_secs(30),
+            HuntNoAnswerAction::Hangup,
+        );
+        let yaml = r#"
+default_group: sales
+groups:
+  sales:
+    strategy: simultaneous
+    members: ["101", "sip:102@pbx.example.com", " "]
+    ring_timeout_sec: 15
+    no_answer: voicemail
+  empty:
+    members: []
+"#;
+        let config = HuntGroupsConfig::parse(yaml, fallback, "sip:zoiper@192.168.1.4:8000")
+            .expect("yaml should parse");
+
+        let sales = config.resolve("sales");
+        assert_eq!(sales.strategy, HuntStrategy::Simultaneous);
+        assert_eq!(
+            sales.members,
+            vec![
+                "sip:101@192.168.1.4:8000".to_string(),
+                "sip:102@pbx.example.com".to_string()
+            ]
+        );
+        assert_eq!(sales.member_ring_timeout, Duration::from_secs(15));
+        assert_eq!(sales.no_answer, HuntNoAnswerAction::Voicemail);
+        // 未定義・メンバーなしのキーは default_group に寄せる
+        assert_eq!(config.resolve("empty").name, "sales");
+        assert_eq!(config.resolve("須田").name, "sales");
+    }
+
+    #[test]
+    fn hunt_groups_resolve_operator_transfer_target() {
+        let fallback = HuntGroup::new(
+            "default",
+            HuntStrategy::Sequential,
+            vec!["sip:zoiper@192.168.1.4:8000".to_string()],
+            Duration::from_secs(30),
+            HuntNoAnswerAction::Voicemail,
+        );
+        let yaml = r#"
+groups:
+  sales:
+    members: ["101", "102"]
+"#;
+        let config = HuntGroupsConfig::parse(yaml, fallback, "sip:zoiper@192.168.1.4:8000")
+            .expect("yaml should parse");
+
+        let sales = config.resolve_target("sales").expect("group");
+        assert_eq!(sales.members.len(), 2);
+        let extension = config.resolve_target("102").expect("extension");
+        assert_eq!(
+            extension.members,
+            vec!["sip:102@192.168.1.4:8000".to_string()]
+        );
+        assert_eq!(extension.no_answer, HuntNoAnswerAction::Hangup);
+        // 未定義の名前は既定グループに寄せず拒否する
+        assert!(config.resolve_target("須田").is_none());
+        // メンバー以外の番号・SIP URI は許可リストにあるものだけ
+        assert!(config.resolve_target("203").is_none());
+        assert!(config.resolve_target("+819012345678").is_none());
+        assert!(config.resolve_target("sip:alice@pbx.example.com").is_none());
+
+        let config = config.with_transfer_allowlist(&[
+            "203".to_string(),
+            " sip:alice@pbx.example.com".to_string(),
+        ]);
+        assert!(config.resolve_target("203").is_some());
+        let uri = config
+            .resolve_target("sip:alice@pbx.example.com")
+            .expect("allowlisted uri");
+        assert_eq!(uri.members, vec!["sip:alice@pbx.example.com".to_string()]);
+        assert!(config.resolve_target("sip:bob@pbx.example.com").is_none());
+    }
+
+    #[test]
+    fn hunt_groups_parse_call_queues() {
+        let fallback = HuntGroup::new(
+            "default",
+            HuntStrategy::Sequential,
+            vec!["sip:zoiper@192.168.1.4:8000".to_string()],
+            Duration::from_secs(30),
+            HuntNoAnswerAction::Hangup,
+        );
+        let yaml = r#"
+groups:
+  vip:
+    members: ["101"]
+    no_answer: queue
+    queue: support
+    queue_priority: 10
+  support:
+    members: ["102"]
+    no_answer: queue
+queues:
+  support:
+    agents: ["101", "102"]
+    moh_announcement_id: 0195f3a0-0000-7000-8000-000000000001
+    position_interval_sec: 45
+    wrap_up_sec: 5
+    voicemail_key: "1"
+    callback_key: "2"
+  nobody:
+    agents: []
+"#;
+        let config = HuntGroupsConfig::parse(yaml, fallback, "sip:zoiper@192.168.1.4:8000")
+            .expect("yaml should parse");
+
+        let vip = config.resolve("vip");
+        assert_eq!(vip.no_answer, HuntNoAnswerAction::Queue);
+        assert_eq!(vip.queue_name(), "support");
+        assert_eq!(vip.queue_priority, 10);
+        assert_eq!(config.resolve("support").queue_name(), "support");
+
+        let queue = config.queue("support").expect("queue should exist");
+        assert_eq!(
+            queue.agents,
+            vec![
+                "sip:101@192.168.1.4:8000".to_string(),
+                "sip:102@192.168.1.4:8000".to_string()
+            ]
+        );
+        assert!(queue.moh_announcement_id.is_some());
+        assert_eq!(queue.position_interval, Duration::from_secs(45));
+        assert_eq!(queue.agent_ring_timeout, Duration::from_secs(30));
+        assert_eq!(queue.wrap_up, Duration::from_secs(5));
+        assert_eq!(queue.voicemail_key, Some('1'));
+        assert_eq!(queue.callback_key, Some('2'));
+        assert!(config.queue("nobody").is_none());
+    }
+
+    #[test]
+    fn hunt_group_round_robin_rotates_start_member() {
+        let group = HuntGroup::new(
+            "support",
+            HuntStrategy::RoundRobin,
+            vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            Duration::from_secs(10),
+            HuntNoAnswerAction::Voicebot,
+        );
+        assert_eq!(group.ring_order(), vec!["a", "b", "c"]);
+        assert_eq!(group.clone().ring_order(), vec!["b", "c", "a"]);
+        assert_eq!(group.ring_order(), vec!["c", "a", "b"]);
+        assert_eq!(group.ring_order(), vec!["a", "b", "c"]);
+    }
+
     #[test]
     fn outbound_phone_number_check() {
         assert!(is_phone_number("09012345678"));
@@ -0,0 +1,35 @@
+/// 着信 INVITE から得た発信者の識別情報。
+///
+/// From ヘッダ（発信者が名乗る番号）と、P-Asserted-Identity 等の網側で保証された番号を区別して保持する。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallerIdentity {
+    /// From ヘッダのユーザ部（非通知なら "anonymous" など）
+    pub display_number: Option<String>,
+    /// From ヘッダの表示名
+    pub display_name: Option<String>,
+    /// P-Asserted-Identity / P-Preferred-Identity / Remote-Party-ID による発信者番号
+    pub asserted_number: Option<String>,
+    /// Privacy（id / user / header）や Remote-Party-ID の privacy で番号非通知が要求されている
+    pub privacy: bool,
+    /// Diversion / History-Info から得た転送前の着信先
+    pub original_called_number: Option<String>,
+    /// 転送理由（Diversion の reason / History-Info の cause）
+    pub diversion_reason: Option<String>,
+}
+
+impl CallerIdentity {
+    /// From の番号だけを持つ識別情報（網側の情報がない場合）。
+    pub fn from_number(number: impl Into<String>) -> Self {
+        Self {
+            display_number: Some(number.into()),
+            ..Self::default()
+        }
+    }
+
+    /// ルーティングに用いる番号。網側で保証された番号があればそれを優先する。
+    pub fn routing_number(&self) -> Option<&str> {
+        self.asserted_number
+            .as_deref()
+            .or(self.display_number.as_deref())
+    }
+}
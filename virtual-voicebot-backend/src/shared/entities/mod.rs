@@ -1,10 +1,12 @@
 pub mod call;
+pub mod caller_identity;
 pub mod identifiers;
 pub mod participant;
 pub mod recording;
 pub mod session;
 
 pub use call::{Call, CallError, CallState, EndReason};
+pub use caller_identity::CallerIdentity;
 pub use identifiers::{CallId, RecordingId, SessionId};
 pub use participant::Participant;
 pub use recording::{Recording, RecordingRef};
@@ -0,0 +1,467 @@
+//! 16bit PCM を FLAC にする最小限のエンコーダ。
+//!
+//! 固定予測（order 0〜4）と Rice 符号（パーティションなし）だけを使う。8kHz の通話音声なら
+//! これで WAV の 5〜6 割程度になり、Raspberry Pi でも録音 1 本あたり数十 ms で終わる。
+//! STREAMINFO の MD5 は 0（不明）を入れる（仕様上許される）。
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use hound::{SampleFormat, WavReader};
+
+/// 1 フレームのサンプル数（8kHz で約 0.5 秒）
+const BLOCK_SIZE: usize = 4096;
+const MAX_FIXED_ORDER: usize = 4;
+/// 4bit の Rice パラメータで使える最大値（15 はエスケープ）
+const MAX_RICE_PARAM: u32 = 14;
+
+/// WAV（16bit PCM、1〜2ch）を FLAC に変換する
+pub fn encode_wav_to_flac(wav_path: &Path, flac_path: &Path) -> Result<()> {
+    let mut reader = WavReader::new(BufReader::new(
+        File::open(wav_path).with_context(|| format!("open wav: {wav_path:?}"))?,
+    ))?;
+    let spec = reader.spec();
+    if spec.bits_per_sample != 16 || spec.sample_format != SampleFormat::Int {
+        bail!("unsupported wav format for flac: {:?}", spec);
+    }
+    let samples = reader.samples::<i16>().collect::<Result<Vec<_>, _>>()?;
+    let encoded = encode(&samples, spec.channels, spec.sample_rate)?;
+    let mut out = BufWriter::new(
+        File::create(flac_path).with_context(|| format!("create flac: {flac_path:?}"))?,
+    );
+    out.write_all(&encoded)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// インターリーブされた 16bit サンプルを FLAC ストリームにする
+pub fn encode(interleaved: &[i16], channels: u16, sample_rate: u32) -> Result<Vec<u8>> {
+    if !(1..=2).contains(&channels) {
+        bail!("unsupported channel count for flac: {channels}");
+    }
+    if sample_rate == 0 || sample_rate >= 1 << 20 {
+        bail!("unsupported sample rate for flac: {sample_rate}");
+    }
+    let channels = channels as usize;
+    let total_frames = interleaved.len() / channels;
+
+    let mut out = Vec::with_capacity(interleaved.len());
+    out.extend_from_slice(b"fLaC");
+    write_stream_info(&mut out, channels, sample_rate, total_frames as u64);
+
+    let mut channel_buf = vec![Vec::with_capacity(BLOCK_SIZE); channels];
+    for (frame_number, block) in interleaved[..total_frames * channels]
+        .chunks(BLOCK_SIZE * channels)
+        .enumerate()
+    {
+        for (ch, buf) in channel_buf.iter_mut().enumerate() {
+            buf.clear();
+            buf.extend(block.iter().skip(ch).step_by(channels).map(|&s| s as i32));
+        }
+        write_frame(&mut out, &channel_buf, sample_rate, frame_number as u64);
+    }
+    Ok(out)
+}
+
+fn write_stream_info(out: &mut Vec<u8>, channels: usize, sample_rate: u32, total: u64) {
+    let mut bits = BitWriter::default();
+    // 最後のメタデータブロック、種別 0（STREAMINFO）、長さ 34
+    bits.put(1, 1);
+    bits.put(0, 7);
+    bits.put(34, 24);
+    bits.put(BLOCK_SIZE as u64, 16);
+    bits.put(BLOCK_SIZE as u64, 16);
+    bits.put(0, 24);
+    bits.put(0, 24);
+    bits.put(sample_rate as u64, 20);
+    bits.put(channels as u64 - 1, 3);
+    bits.put(15, 5);
+    bits.put(total & ((1 << 36) - 1), 36);
+    for _ in 0..16 {
+        bits.put(0, 8);
+    }
+    out.extend_from_slice(&bits.finish());
+}
+
+fn write_frame(out: &mut Vec<u8>, channels: &[Vec<i32>], sample_rate: u32, frame_number: u64) {
+    let block_len = channels[0].len();
+    let mut bits = BitWriter::default();
+    // 同期コード + 固定ブロックサイズ
+    bits.put(0b11_1111_1111_1110, 14);
+    bits.put(0, 1);
+    bits.put(0, 1);
+    // ブロックサイズはヘッダ末尾の 16bit で指定する
+    bits.put(0b0111, 4);
+    let (rate_code, rate_tail) = sample_rate_code(sample_rate);
+    bits.put(rate_code, 4);
+    // 独立チャネル
+    bits.put(channels.len() as u64 - 1, 4);
+    // 16bit
+    bits.put(0b100, 3);
+    bits.put(0, 1);
+    for byte in utf8_coded(frame_number) {
+        bits.put(byte as u64, 8);
+    }
+    bits.put(block_len as u64 - 1, 16);
+    if let Some((value, width)) = rate_tail {
+        bits.put(value, width);
+    }
+    let header = bits.bytes();
+    bits.put(crc8(&header) as u64, 8);
+
+    for samples in channels {
+        write_subframe(&mut bits, samples);
+    }
+    let mut frame = bits.finish();
+    let crc = crc16(&frame);
+    frame.extend_from_slice(&crc.to_be_bytes());
+    out.extend_from_slice(&frame);
+}
+
+fn sample_rate_code(sample_rate: u32) -> (u64, Option<(u64, u32)>) {
+    match sample_rate {
+        8000 => (0b0100, None),
+        16000 => (0b0101, None),
+        22050 => (0b0110, None),
+        24000 => (0b0111, None),
+        32000 => (0b1000, None),
+        44100 => (0b1001, None),
+        48000 => (0b1010, None),
+        rate if rate % 10 == 0 && rate / 10 <= 0xFFFF => (0b1110, Some((rate as u64 / 10, 16))),
+        rate if rate <= 0xFFFF => (0b1101, Some((rate as u64, 16))),
+        // STREAMINFO の値を使う
+        _ => (0b0000, None),
+    }
+}
+
+fn write_subframe(bits: &mut BitWriter, samples: &[i32]) {
+    if samples.iter().all(|&s| s == samples[0]) {
+        bits.put(0, 1);
+        bits.put(0b000000, 6);
+        bits.put(0, 1);
+        bits.put_signed(samples[0], 16);
+        return;
+    }
+
+    let verbatim_bits = samples.len() as u64 * 16;
+    let best = (0..=MAX_FIXED_ORDER.min(samples.len() - 1))
+        .map(|order| {
+            let residual = fixed_residual(samples, order);
+            let (param, cost) = best_rice_param(&residual);
+            (order, residual, param, cost + order as u64 * 16)
+        })
+        .min_by_key(|(_, _, _, cost)| *cost);
+    match best {
+        Some((order, residual, param, cost)) if cost < verbatim_bits => {
+            bits.put(0, 1);
+            bits.put(0b001000 | order as u64, 6);
+            bits.put(0, 1);
+            for &warmup in &samples[..order] {
+                bits.put_signed(warmup, 16);
+            }
+            // Rice 符号（4bit パラメータ）、パーティション次数 0
+            bits.put(0b00, 2);
+            bits.put(0, 4);
+            bits.put(param as u64, 4);
+            for &r in &residual {
+                bits.put_rice(zigzag(r), param);
+            }
+        }
+        _ => {
+            bits.put(0, 1);
+            bits.put(0b000001, 6);
+            bits.put(0, 1);
+            for &s in samples {
+                bits.put_signed(s, 16);
+            }
+        }
+    }
+}
+
+fn fixed_residual(samples: &[i32], order: usize) -> Vec<i32> {
+    (order..samples.len())
+        .map(|n| {
+            let x = |k: usize| samples[n - k];
+            match order {
+                0 => x(0),
+                1 => x(0) - x(1),
+                2 => x(0) - 2 * x(1) + x(2),
+                3 => x(0) - 3 * x(1) + 3 * x(2) - x(3),
+                _ => x(0) - 4 * x(1) + 6 * x(2) - 4 * x(3) + x(4),
+            }
+        })
+        .collect()
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// 符号長が最小になる Rice パラメータと、そのビット数
+fn best_rice_param(residual: &[i32]) -> (u32, u64) {
+    let header_bits = 2 + 4 + 4;
+    (0..=MAX_RICE_PARAM)
+        .map(|param| {
+            let bits: u64 = residual
+                .iter()
+                .map(|&r| (zigzag(r) >> param) as u64 + 1 + param as u64)
+                .sum();
+            (param, bits + header_bits)
+        })
+        .min_by_key(|(_, bits)| *bits)
+        .unwrap_or((0, header_bits))
+}
+
+/// フレーム番号の「UTF-8 風」可変長符号
+fn utf8_coded(value: u64) -> Vec<u8> {
+    if value < 0x80 {
+        return vec![value as u8];
+    }
+    let mut len = 2;
+    while len < 7 && value >= 1 << (5 * len + 1) {
+        len += 1;
+    }
+    let mut bytes = vec![0u8; len];
+    let mut rest = value;
+    for byte in bytes.iter_mut().skip(1).rev() {
+        *byte = 0x80 | (rest & 0x3F) as u8;
+        rest >>= 6;
+    }
+    let prefix = !(0xFFu8 >> len);
+    bytes[0] = prefix | rest as u8;
+    bytes
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    filled: u32,
+}
+
+impl BitWriter {
+    fn put(&mut self, value: u64, width: u32) {
+        for shift in (0..width).rev() {
+            self.acc = (self.acc << 1) | ((value >> shift) & 1);
+            self.filled += 1;
+            if self.filled == 8 {
+                self.bytes.push(self.acc as u8);
+                self.acc = 0;
+                self.filled = 0;
+            }
+        }
+    }
+
+    fn put_signed(&mut self, value: i32, width: u32) {
+        self.put(value as u64 & ((1 << width) - 1), width);
+    }
+
+    fn put_rice(&mut self, value: u32, param: u32) {
+        let quotient = value >> param;
+        for _ in 0..quotient {
+            self.put(0, 1);
+        }
+        self.put(1, 1);
+        self.put(value as u64 & ((1 << param) - 1), param);
+    }
+
+    /// ここまでに書いた完全なバイト列（CRC 計算用）
+    fn bytes(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+
+    /// バイト境界まで 0 で埋めて取り出す
+    fn finish(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.bytes.push((self.acc << (8 - self.filled)) as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 出力したサブセット（CONSTANT / VERBATIM / FIXED、パーティション 0）だけを読むデコーダ
+    struct BitReader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl BitReader<'_> {
+        fn get(&mut self, width: u32) -> u64 {
+            (0..width).fold(0, |acc, _| {
+                let bit = (self.data[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+                self.pos += 1;
+                (acc << 1) | bit as u64
+            })
+        }
+
+        fn get_signed(&mut self, width: u32) -> i32 {
+            let value = self.get(width) as i64;
+            (if value >> (width - 1) == 1 {
+                value - (1 << width)
+            } else {
+                value
+            }) as i32
+        }
+
+        fn align(&mut self) {
+            self.pos = self.pos.div_ceil(8) * 8;
+        }
+    }
+
+    fn decode(data: &[u8]) -> (u16, Vec<i16>) {
+        assert_eq!(&data[..4], b"fLaC");
+        let mut r = BitReader {
+            data: &data[4..],
+            pos: 0,
+        };
+        assert_eq!(r.get(1), 1);
+        assert_eq!(r.get(7), 0);
+        assert_eq!(r.get(24), 34);
+        r.get(16 + 16 + 24 + 24);
+        assert_eq!(r.get(20), 8000);
+        let channels = r.get(3) as usize + 1;
+        r.get(5);
+        let total = r.get(36) as usize;
+        r.get(128);
+
+        let mut out = vec![0i16; total * channels];
+        let mut written = 0;
+        while written < total {
+            let frame_start = r.pos / 8;
+            assert_eq!(r.get(14), 0b11_1111_1111_1110);
+            r.get(2);
+            assert_eq!(r.get(4), 0b0111);
+            assert_eq!(r.get(4), 0b0100);
+            assert_eq!(r.get(4) as usize, channels - 1);
+            r.get(4);
+            let lead = r.get(8);
+            for _ in 1..(lead as u8).leading_ones() {
+                r.get(8);
+            }
+            let block = r.get(16) as usize + 1;
+            let header_end = r.pos / 8;
+            assert_eq!(r.get(8) as u8, crc8(&r.data[frame_start..header_end]));
+            for ch in 0..channels {
+                r.get(1);
+                let kind = r.get(6);
+                r.get(1);
+                let mut samples = Vec::with_capacity(block);
+                if kind == 0 {
+                    let value = r.get_signed(16);
+                    samples.resize(block, value);
+                } else if kind == 1 {
+                    samples.extend((0..block).map(|_| r.get_signed(16)));
+                } else {
+                    let order = (kind & 0x7) as usize;
+                    samples.extend((0..order).map(|_| r.get_signed(16)));
+                    assert_eq!(r.get(2), 0);
+                    assert_eq!(r.get(4), 0);
+                    let param = r.get(4) as u32;
+                    for n in order..block {
+                        let mut quotient = 0;
+                        while r.get(1) == 0 {
+                            quotient += 1;
+                        }
+                        let u = (quotient << param) | r.get(param) as u32;
+                        let residual = ((u >> 1) as i32) ^ -((u & 1) as i32);
+                        let x = |k: usize| samples[n - k];
+                        let prediction = match order {
+                            0 => 0,
+                            1 => x(1),
+                            2 => 2 * x(1) - x(2),
+                            3 => 3 * x(1) - 3 * x(2) + x(3),
+                            _ => 4 * x(1) - 6 * x(2) + 4 * x(3) - x(4),
+                        };
+                        samples.push(prediction + residual);
+                    }
+                }
+                for (i, s) in samples.into_iter().enumerate() {
+                    out[(written + i) * channels + ch] = s as i16;
+                }
+            }
+            r.align();
+            let frame_end = r.pos / 8;
+            let crc = r.get(16) as u16;
+            assert_eq!(crc, crc16(&r.data[frame_start..frame_end]));
+            written += block;
+        }
+        (channels as u16, out)
+    }
+
+    #[test]
+    fn round_trips_stereo_and_compresses_speech_like_signal() {
+        let frames = BLOCK_SIZE * 2 + 123;
+        let mut samples = Vec::with_capacity(frames * 2);
+        for n in 0..frames {
+            let t = n as f64 / 8000.0;
+            let tone = (t * 440.0 * std::f64::consts::TAU).sin() * 8000.0;
+            samples.push(tone as i16);
+            samples.push(if n < 3000 { 0 } else { (tone * 0.5) as i16 });
+        }
+
+        let encoded = encode(&samples, 2, 8000).unwrap();
+        let (channels, decoded) = decode(&encoded);
+
+        assert_eq!(channels, 2);
+        assert_eq!(decoded, samples);
+        assert!(encoded.len() < samples.len(), "len={}", encoded.len());
+    }
+
+    #[test]
+    fn noise_falls_back_to_verbatim_and_round_trips() {
+        let mut seed = 0x1234_5678u32;
+        let samples: Vec<i16> = (0..1000)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed as i16
+            })
+            .collect();
+
+        let encoded = encode(&samples, 1, 8000).unwrap();
+
+        assert_eq!(decode(&encoded), (1, samples));
+    }
+
+    #[test]
+    fn frame_numbers_use_utf8_coding() {
+        assert_eq!(utf8_coded(0x7F), vec![0x7F]);
+        assert_eq!(utf8_coded(0x80), vec![0xC2, 0x80]);
+        assert_eq!(utf8_coded(0x800), vec![0xE0, 0xA0, 0x80]);
+    }
+}
@@ -11,6 +11,7 @@ use serde::Serialize;
 use crate::protocol::rtp::codec::mulaw_to_linear16;
 use crate::service::recording;
 
+pub mod flac;
 pub mod merge;
 
 pub struct Recorder {
@@ -26,6 +27,10 @@ pub struct Recorder {
     write_meta: bool,
     rx_samples: VecDeque<i16>,
     tx_samples: VecDeque<i16>,
+    /// 一時停止中は受信・送信とも無音で埋める（タイムラインは進める）
+    paused: bool,
+    /// 一時停止した区間（録音タイムラインの秒、再開前なら終端は None）
+    paused_segments: Vec<(f64, Option<f64>)>,
 }
 
 impl Recorder {
@@ -35,8 +40,19 @@ impl Recorder {
 
     pub fn with_file(call_id: impl Into<String>, file_name: &str, write_meta: bool) -> Self {
         let call_id = call_id.into();
-        let dir_name = recording::recording_dir_name(&call_id);
         let dir = recording::recording_dir(&call_id);
+        Self::in_dir(call_id, dir, file_name, write_meta)
+    }
+
+    /// 録音ディレクトリを指定して作る（既定は `storage/recordings/<callId>`）
+    pub fn in_dir(
+        call_id: impl Into<String>,
+        dir: PathBuf,
+        file_name: &str,
+        write_meta: bool,
+    ) -> Self {
+        let call_id = call_id.into();
+        let dir_name = recording::recording_dir_name(&call_id);
         Self {
             call_id,
             dir,
@@ -50,6 +66,8 @@ impl Recorder {
             write_meta,
             rx_samples: VecDeque::new(),
             tx_samples: VecDeque::new(),
+            paused: false,
+            paused_segments: Vec::new(),
         }
     }
 
@@ -95,11 +113,48 @@ impl Recorder {
         self.started_at = Some(SystemTime::now());
         self.rx_samples.clear();
         self.tx_samples.clear();
+        if self.paused {
+            self.paused_segments.push((0.0, None));
+        }
         Ok(())
     }
 
+    /// 録音タイムライン上の現在位置（秒）。録音前は None
+    pub fn position_sec(&self) -> Option<f64> {
+        self.started_at?;
+        Some(self.samples_written as f64 / self.sample_rate as f64)
+    }
+
+    /// 一時停止する。以降 `resume` まで音声の代わりに無音を書く
+    pub fn pause(&mut self) {
+        if self.paused {
+            return;
+        }
+        self.paused = true;
+        if let Some(position) = self.position_sec() {
+            self.paused_segments.push((position, None));
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if !self.paused {
+            return;
+        }
+        self.paused = false;
+        let position = self.position_sec();
+        if let Some(segment) = self.paused_segments.last_mut() {
+            if segment.1.is_none() {
+                segment.1 = position;
+            }
+        }
+    }
+
     /// μ-law の受信PCMを追記する
     pub fn push_rx_mulaw(&mut self, pcm_mulaw: &[u8]) {
+        if self.paused {
+            Self::push_silence(&mut self.rx_samples, pcm_mulaw.len());
+            return;
+        }
         #[cfg(debug_assertions)]
         dump_raw_mulaw(pcm_mulaw);
         Self::push_mulaw(&mut self.rx_samples, pcm_mulaw);
@@ -107,6 +162,10 @@ impl Recorder {
 
     /// 送信側PCM（μ-law）を追記する
     pub fn push_tx_mulaw(&mut self, pcm_mulaw: &[u8]) {
+        if self.paused {
+            Self::push_silence(&mut self.tx_samples, pcm_mulaw.len());
+            return;
+        }
         #[cfg(debug_assertions)]
         dump_raw_mulaw(pcm_mulaw);
         Self::push_mulaw(&mut self.tx_samples, pcm_mulaw);
@@ -118,6 +177,10 @@ impl Recorder {
         }
     }
 
+    fn push_silence(queue: &mut VecDeque<i16>, samples: usize) {
+        queue.extend(std::iter::repeat_n(0, samples));
+    }
+
     pub fn flush_tick(&mut self) {
         const FRAME_SAMPLES: usize = 160;
         if self.writer.is_none() {
@@ -164,6 +227,13 @@ impl Recorder {
         while !self.rx_samples.is_empty() || !self.tx_samples.is_empty() {
             self.flush_tick();
         }
+        // 一時停止のまま終わった区間は録音の終端で閉じる
+        let end = self.position_sec();
+        for segment in &mut self.paused_segments {
+            if segment.1.is_none() {
+                segment.1 = end;
+            }
+        }
         if let Some(writer) = self.writer.take() {
             writer.finalize()?;
         }
@@ -182,13 +252,24 @@ impl Recorder {
         }
         #[allow(non_snake_case)]
         #[derive(Serialize)]
+        struct PausedSegment {
+            startSec: f64,
+            endSec: Option<f64>,
+        }
+        #[allow(non_snake_case)]
+        #[derive(Serialize)]
         struct Meta<'a> {
             callId: &'a str,
             recordingStartedAt: Option<String>,
+            /// 保存期間・後処理の起点（後処理で書き換わるファイルの mtime は使えない）
+            recordingEndedAt: String,
             sampleRate: u32,
             channels: u16,
             durationSec: f64,
             files: MetaFiles<'a>,
+            /// 一時停止して無音にした区間
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            pausedSegments: Vec<PausedSegment>,
         }
         let started_at = self
             .started_at
@@ -197,12 +278,21 @@ impl Recorder {
         let meta = Meta {
             callId: &self.call_id,
             recordingStartedAt: started_at,
+            recordingEndedAt: humantime::format_rfc3339(SystemTime::now()).to_string(),
             sampleRate: self.sample_rate,
             channels: self.channels,
             durationSec: duration_sec,
             files: MetaFiles {
                 mixed: self.file_name.as_str(),
             },
+            pausedSegments: self
+                .paused_segments
+                .iter()
+                .map(|&(start, end)| PausedSegment {
+                    startSec: start,
+                    endSec: end,
+                })
+                .collect(),
         };
         let meta_path = self.dir.join("meta.json");
         let json = serde_json::to_vec_pretty(&meta)?;
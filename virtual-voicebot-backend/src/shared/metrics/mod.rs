@@ -0,0 +1,325 @@
+//! Prometheus テキスト形式（/metrics）で公開するプロセス内メトリクス。
+//!
+//! 各層は下の static に直接加算する（記録はロック 1 回で終わり、通話処理を待たせない）。
+//! 通話数やキュー長のように現在値が別の場所にあるものは、スクレイプ時に HTTP 層で集計して
+//! `write_gauge` で書き足す。
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 秒単位の AI 処理時間のバケット境界
+const AI_LATENCY_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0];
+/// 秒単位の RTP ジッタのバケット境界
+const RTP_JITTER_BUCKETS: &[f64] = &[0.005, 0.01, 0.02, 0.03, 0.05, 0.1, 0.2];
+/// 推定 MOS のバケット境界
+const MOS_BUCKETS: &[f64] = &[2.0, 2.5, 3.0, 3.5, 3.8, 4.0, 4.2, 4.4];
+
+pub static SIP_REQUESTS: CounterVec = CounterVec::new(
+    "voicebot_sip_requests_total",
+    "SIP requests by direction (in/out) and method",
+    &["direction", "method"],
+);
+pub static SIP_RESPONSES: CounterVec = CounterVec::new(
+    "voicebot_sip_responses_total",
+    "SIP responses by direction (in/out) and status code",
+    &["direction", "code"],
+);
+pub static RTP_PACKETS: CounterVec = CounterVec::new(
+    "voicebot_rtp_packets_total",
+    "RTP packets by direction (in/out)",
+    &["direction"],
+);
+pub static RTP_PACKETS_LOST: CounterVec = CounterVec::new(
+    "voicebot_rtp_packets_lost_total",
+    "Inbound RTP packets lost, as reported in RTCP receiver reports",
+    &[],
+);
+pub static RTP_JITTER: HistogramVec = HistogramVec::new(
+    "voicebot_rtp_jitter_seconds",
+    "Inbound RTP interarrival jitter sampled at each RTCP report interval",
+    &[],
+    RTP_JITTER_BUCKETS,
+);
+pub static AI_STAGE_LATENCY: HistogramVec = HistogramVec::new(
+    "voicebot_ai_stage_duration_seconds",
+    "ASR/LLM/TTS request duration by fallback stage (local/cloud/raspi)",
+    &["kind", "stage"],
+    AI_LATENCY_BUCKETS,
+);
+pub static AI_STAGE_FAILURES: CounterVec = CounterVec::new(
+    "voicebot_ai_stage_failures_total",
+    "Failed ASR/LLM/TTS requests by fallback stage",
+    &["kind", "stage"],
+);
+pub static CALL_MOS: HistogramVec = HistogramVec::new(
+    "voicebot_call_mos",
+    "Estimated MOS (E-model) of inbound media per call",
+    &[],
+    MOS_BUCKETS,
+);
+pub static CALL_QUALITY_ALERTS: CounterVec = CounterVec::new(
+    "voicebot_call_quality_alerts_total",
+    "Calls whose estimated MOS fell below MEDIA_QUALITY_MOS_ALERT_THRESHOLD",
+    &[],
+);
+pub static SESSION_METRICS: CounterVec = CounterVec::new(
+    "voicebot_session_metric_total",
+    "Sum of values reported by sessions (SessionOut::Metrics) by name",
+    &["name"],
+);
+
+/// 登録済みのメトリクスをすべて書き出す
+pub fn render(out: &mut String) {
+    SIP_REQUESTS.render(out);
+    SIP_RESPONSES.render(out);
+    RTP_PACKETS.render(out);
+    RTP_PACKETS_LOST.render(out);
+    RTP_JITTER.render(out);
+    AI_STAGE_LATENCY.render(out);
+    AI_STAGE_FAILURES.render(out);
+    CALL_MOS.render(out);
+    CALL_QUALITY_ALERTS.render(out);
+    SESSION_METRICS.render(out);
+}
+
+/// SIP メッセージの開始行（"INVITE sip:.. SIP/2.0" / "SIP/2.0 200 OK"）から数える
+pub fn record_sip_start_line(direction: &str, start_line: &str) {
+    let mut tokens = start_line.split_whitespace();
+    match tokens.next() {
+        Some("SIP/2.0") => {
+            if let Some(code) = tokens.next() {
+                SIP_RESPONSES.inc(&[direction, code]);
+            }
+        }
+        Some(method) => SIP_REQUESTS.inc(&[direction, method]),
+        None => {}
+    }
+}
+
+/// 送信バイト列の先頭行で数える
+pub fn record_sip_payload(direction: &str, payload: &[u8]) {
+    let first_line = payload.split(|b| *b == b'\n').next().unwrap_or_default();
+    if let Ok(line) = std::str::from_utf8(first_line) {
+        record_sip_start_line(direction, line);
+    }
+}
+
+/// スクレイプ時に集計した現在値を書き出す
+pub fn write_gauge(out: &mut String, name: &str, help: &str, samples: &[(Vec<(&str, &str)>, f64)]) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    for (labels, value) in samples {
+        let _ = writeln!(out, "{}{} {}", name, format_labels(labels), value);
+    }
+}
+
+pub struct CounterVec {
+    name: &'static str,
+    help: &'static str,
+    label_names: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl CounterVec {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            label_names,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn inc(&self, labels: &[&str]) {
+        self.add(labels, 1);
+    }
+
+    pub fn add(&self, labels: &[&str], value: u64) {
+        let key = labels.iter().map(|label| label.to_string()).collect();
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = values.entry(key).or_insert(0);
+        *entry = entry.saturating_add(value);
+    }
+
+    fn render(&self, out: &mut String) {
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} counter", self.name);
+        for (key, value) in values.iter() {
+            let labels = zip_labels(self.label_names, key);
+            let _ = writeln!(out, "{}{} {}", self.name, format_labels(&labels), value);
+        }
+    }
+}
+
+#[derive(Default)]
+struct HistogramState {
+    /// 各境界以下の観測数（累積ではない。書き出し時に累積する）
+    bucket_counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+pub struct HistogramVec {
+    name: &'static str,
+    help: &'static str,
+    label_names: &'static [&'static str],
+    buckets: &'static [f64],
+    values: Mutex<BTreeMap<Vec<String>, HistogramState>>,
+}
+
+impl HistogramVec {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        buckets: &'static [f64],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            label_names,
+            buckets,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn observe(&self, labels: &[&str], value: f64) {
+        let key = labels.iter().map(|label| label.to_string()).collect();
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        let state = values.entry(key).or_insert_with(|| HistogramState {
+            bucket_counts: vec![0; self.buckets.len()],
+            ..Default::default()
+        });
+        if let Some(index) = self.buckets.iter().position(|bound| value <= *bound) {
+            state.bucket_counts[index] += 1;
+        }
+        state.sum += value;
+        state.count += 1;
+    }
+
+    pub fn observe_duration(&self, labels: &[&str], duration: Duration) {
+        self.observe(labels, duration.as_secs_f64());
+    }
+
+    fn render(&self, out: &mut String) {
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} histogram", self.name);
+        for (key, state) in values.iter() {
+            let labels = zip_labels(self.label_names, key);
+            let mut cumulative = 0;
+            for (bound, count) in self.buckets.iter().zip(&state.bucket_counts) {
+                cumulative += count;
+                let le = bound.to_string();
+                let mut bucket_labels = labels.clone();
+                bucket_labels.push(("le", le.as_str()));
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{} {}",
+                    self.name,
+                    format_labels(&bucket_labels),
+                    cumulative
+                );
+            }
+            let mut inf_labels = labels.clone();
+            inf_labels.push(("le", "+Inf"));
+            let _ = writeln!(
+                out,
+                "{}_bucket{} {}",
+                self.name,
+                format_labels(&inf_labels),
+                state.count
+            );
+            let label_text = format_labels(&labels);
+            let _ = writeln!(out, "{}_sum{} {}", self.name, label_text, state.sum);
+            let _ = writeln!(out, "{}_count{} {}", self.name, label_text, state.count);
+        }
+    }
+}
+
+fn zip_labels<'a>(names: &'a [&'static str], values: &'a [String]) -> Vec<(&'a str, &'a str)> {
+    names
+        .iter()
+        .copied()
+        .zip(values.iter().map(String::as_str))
+        .collect()
+}
+
+fn format_labels(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let body = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", body)
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counter_renders_labels_in_key_order() {
+        let counter = CounterVec::new("test_total", "help text", &["direction", "method"]);
+        counter.inc(&["in", "INVITE"]);
+        counter.add(&["in", "BYE"], 2);
+        counter.inc(&["in", "INVITE"]);
+
+        let mut out = String::new();
+        counter.render(&mut out);
+
+        assert_eq!(
+            out,
+            "# HELP test_total help text\n\
+             # TYPE test_total counter\n\
+             test_total{direction=\"in\",method=\"BYE\"} 2\n\
+             test_total{direction=\"in\",method=\"INVITE\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let histogram = HistogramVec::new("test_seconds", "help", &["stage"], &[0.5, 1.0]);
+        histogram.observe(&["local"], 0.25);
+        histogram.observe(&["local"], 0.75);
+        histogram.observe(&["local"], 3.0);
+
+        let mut out = String::new();
+        histogram.render(&mut out);
+
+        assert!(out.contains("test_seconds_bucket{stage=\"local\",le=\"0.5\"} 1\n"));
+        assert!(out.contains("test_seconds_bucket{stage=\"local\",le=\"1\"} 2\n"));
+        assert!(out.contains("test_seconds_bucket{stage=\"local\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("test_seconds_sum{stage=\"local\"} 4\n"));
+        assert!(out.contains("test_seconds_count{stage=\"local\"} 3\n"));
+    }
+
+    #[test]
+    fn gauge_escapes_label_values() {
+        let mut out = String::new();
+        write_gauge(
+            &mut out,
+            "test_gauge",
+            "help",
+            &[(vec![("state", "a\"b")], 2.0)],
+        );
+        assert!(out.ends_with("test_gauge{state=\"a\\\"b\"} 2\n"));
+    }
+}
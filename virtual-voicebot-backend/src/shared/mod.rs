@@ -4,6 +4,7 @@ pub mod entities;
 pub mod error;
 pub mod logging;
 pub mod media;
+pub mod metrics;
 pub mod ports;
 pub mod utils;
 
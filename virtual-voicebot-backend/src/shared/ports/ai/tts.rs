@@ -16,6 +16,16 @@ pub trait TtsPort: Send + Sync {
         text: String,
         path: Option<String>,
     ) -> AiFuture<Result<PathBuf, TtsError>>;
+
+    /// 定型文の合成。キャッシュを持つ実装は合成済み音声を再利用する。
+    /// 返却パスは共有される可能性があるため、呼び出し側で削除しないこと。
+    fn synth_phrase_to_wav(
+        &self,
+        call_id: String,
+        text: String,
+    ) -> AiFuture<Result<PathBuf, TtsError>> {
+        self.synth_to_wav(call_id, text, None)
+    }
 }
 
 pub trait TtsStreamPort: Send + Sync {
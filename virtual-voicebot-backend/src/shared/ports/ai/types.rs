@@ -7,6 +7,8 @@ pub struct AsrChunk {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Role {
+    /// 会話固有の追加指示（発信者メモリなど）。共通のシステムプロンプトの後に置かれる。
+    System,
     User,
     Assistant,
 }
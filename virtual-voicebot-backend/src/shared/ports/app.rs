@@ -22,6 +22,16 @@ pub enum AppEvent {
         pcm_mulaw: Vec<u8>,
         pcm_linear16: Vec<i16>,
     },
+    /// IVR の音声入力ノードで確定した発話区間（認識結果は AppIvrSpeechResult で戻す）
+    IvrSpeechInput {
+        call_id: CallId,
+        pcm_mulaw: Vec<u8>,
+        /// 遷移に意図指定がある場合のみ意図分類も行う
+        classify_intent: bool,
+    },
+    /// ボット音声の再生が終わった（再生キューが空になった・打ち切られた・再生できなかった）。
+    /// 新しい音声への差し替えでは送らない（差し替えた音声の終わりで送る）
+    PlaybackFinished { call_id: CallId },
     CallEnded {
         call_id: CallId,
         from: String,
@@ -80,6 +90,20 @@ impl fmt::Debug for AppEvent {
                 .field("pcm_mulaw_len", &pcm_mulaw.len())
                 .field("pcm_linear16_len", &pcm_linear16.len())
                 .finish(),
+            Self::IvrSpeechInput {
+                call_id,
+                pcm_mulaw,
+                classify_intent,
+            } => f
+                .debug_struct("IvrSpeechInput")
+                .field("call_id", call_id)
+                .field("pcm_mulaw_len", &pcm_mulaw.len())
+                .field("classify_intent", classify_intent)
+                .finish(),
+            Self::PlaybackFinished { call_id } => f
+                .debug_struct("PlaybackFinished")
+                .field("call_id", call_id)
+                .finish(),
             Self::CallEnded {
                 call_id,
                 from,
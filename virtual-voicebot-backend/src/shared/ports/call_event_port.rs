@@ -0,0 +1,81 @@
+use uuid::Uuid;
+
+/// 通話中に発生するリアルタイムイベント（運用画面へのストリーム配信用）
+#[derive(Clone, Debug, PartialEq)]
+pub enum CallEvent {
+    /// 着信して呼び出し中（180 送信）
+    Ringing {
+        from: String,
+        to: String,
+    },
+    /// 応答済み（ACK 受信）
+    Answered,
+    /// IVR 上の位置の変化（IvrMenuWaiting / VoicebotMode / B2buaMode など）
+    IvrState {
+        state: String,
+    },
+    /// DB 定義 IVR のノード遷移（ivr_session_events と同じ event_type）
+    IvrNode {
+        event_type: String,
+        node_id: Option<Uuid>,
+        dtmf_key: Option<char>,
+    },
+    Dtmf {
+        digit: char,
+    },
+    /// 転送の進捗。status は trying / ringing / answered / failed、メンバー単位の
+    /// 結果は attempt（outcome に answered / no_answer / busy など）
+    Transfer {
+        status: String,
+        member: Option<String>,
+        outcome: Option<String>,
+        sip_status: Option<u16>,
+    },
+    /// 発信者の発話（ASR 結果）。asr_ms は認識にかかった時間
+    UserUtterance {
+        text: String,
+        asr_ms: u64,
+    },
+    /// ボットの応答文。llm_ms は応答生成、turn_ms は発話区間確定から応答確定まで
+    BotReply {
+        text: String,
+        llm_ms: u64,
+        turn_ms: u64,
+    },
+    /// 録音の一時停止（by は dtmf / api / intent）
+    RecordingPaused {
+        by: String,
+    },
+    /// 録音の再開（by は dtmf / api / timeout）
+    RecordingResumed {
+        by: String,
+    },
+    /// 通話の推定 MOS がアラート閾値を下回った（通話終了時に判定）
+    QualityAlert {
+        mos: f32,
+        threshold: f32,
+    },
+    /// 通話終了
+    Ended {
+        reason: String,
+        duration_sec: Option<u64>,
+    },
+}
+
+/// イベントの発行口。通話処理を止めないよう、実装はブロックしないこと
+pub trait CallEventPublisher: Send + Sync {
+    fn publish(&self, call_id: &str, event: CallEvent);
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NoopCallEventPublisher;
+
+impl NoopCallEventPublisher {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl CallEventPublisher for NoopCallEventPublisher {
+    fn publish(&self, _call_id: &str, _event: CallEvent) {}
+}
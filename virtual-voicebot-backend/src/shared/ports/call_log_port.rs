@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::future::Future;
 use std::pin::Pin;
 
@@ -26,11 +27,20 @@ pub struct EndedCallLog {
     pub external_call_id: String,
     pub sip_call_id: String,
     pub caller_number: Option<String>,
+    /// P-Asserted-Identity 等による網側の発信者番号（From と異なる場合がある）
+    pub asserted_caller_number: Option<String>,
+    pub caller_display_name: Option<String>,
+    /// 発信者が番号非通知（Privacy）を要求した
+    pub caller_privacy: bool,
+    /// 転送着信の場合の転送前の着信先（Diversion / History-Info）
+    pub original_called_number: Option<String>,
+    pub diversion_reason: Option<String>,
     pub direction: String,
     pub callee_number: Option<String>,
     pub caller_category: String,
     pub action_code: String,
     pub ivr_flow_id: Option<Uuid>,
+    pub schedule_id: Option<Uuid>,
     pub answered_at: Option<DateTime<Utc>>,
     pub end_reason: String,
     pub status: String,
@@ -41,6 +51,15 @@ pub struct EndedCallLog {
     pub transfer_answered_at: Option<DateTime<Utc>>,
     pub transfer_ended_at: Option<DateTime<Utc>>,
     pub ivr_events: Vec<EndedIvrSessionEvent>,
+    pub emotion_timeline: Vec<EmotionTurn>,
+    /// IVR の入力ノードで収集した呼変数（変数名 -> 値）
+    pub call_variables: BTreeMap<String, String>,
+    /// 転送先グループの各メンバーへの呼び出し結果（発生順）
+    pub transfer_events: Vec<TransferAttempt>,
+    /// 受信メディアの品質（RTP を 1 パケットも受けなかった通話は None）
+    pub media_quality: Option<MediaQuality>,
+    /// 録音を一時停止した区間（発生順）
+    pub recording_pauses: Vec<RecordingPause>,
     pub recording: Option<EndedRecording>,
 }
 
@@ -58,6 +77,63 @@ pub struct EndedIvrSessionEvent {
     pub metadata: Option<Value>,
 }
 
+/// 1 発話ターン分の感情推定結果（SER）と、その発話の認識テキスト
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmotionTurn {
+    pub turn: i32,
+    pub occurred_at: DateTime<Utc>,
+    pub emotion: String,
+    pub confidence: f32,
+    pub transcript: String,
+}
+
+/// 転送先グループの 1 メンバー分の呼び出し結果
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferAttempt {
+    pub hunt_group: String,
+    pub member: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// answered / no_answer / busy / rejected / cancelled / failed
+    pub outcome: String,
+    pub sip_status: Option<u16>,
+}
+
+/// 通話全体の受信メディア品質の要約
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaQuality {
+    pub packets_received: u64,
+    /// 拡張最大シーケンス番号から求めた期待数との差（RTCP の cumulative lost と同じ）
+    pub packets_lost: u64,
+    pub max_jitter_ms: f32,
+    pub avg_jitter_ms: f32,
+    /// 後続パケットより遅れて届き、ジッタバッファで並べ替えたパケット数
+    pub reordered_packets: u64,
+    /// 再生位置を過ぎてから届いた（または重複した）ため捨てたパケット数
+    pub late_dropped_packets: u64,
+    /// 相手の受信レポート（LSR/DLSR）から求めた往復遅延。相手が RR を返さなければ None
+    pub rtt_ms: Option<f32>,
+    /// E-model（ITU-T G.107 簡易版、G.711 前提）による推定 MOS
+    pub mos: f32,
+    /// MOS がアラート閾値を下回った
+    pub below_threshold: bool,
+}
+
+/// 録音の一時停止区間（カード番号の読み上げなど、録音してはならない部分）
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordingPause {
+    pub paused_at: DateTime<Utc>,
+    /// 再開前に通話が終わった場合は通話終了時刻
+    pub resumed_at: DateTime<Utc>,
+    /// 録音タイムライン（recordingStartedAt 基準）上の位置。録音していない通話は None
+    pub start_sec: Option<f64>,
+    pub end_sec: Option<f64>,
+    /// dtmf / api / intent
+    pub paused_by: String,
+    /// dtmf / api / timeout / call_ended
+    pub resumed_by: String,
+}
+
 #[derive(Debug, Error)]
 pub enum CallLogPortError {
     #[error("write failed: {0}")]
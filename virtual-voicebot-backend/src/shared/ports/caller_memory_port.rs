@@ -0,0 +1,69 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// 発信者ごとの会話メモリ（E.164 正規化済み番号で一意）
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallerMemory {
+    pub phone_number: String,
+    pub summary: String,
+    pub key_facts: Vec<String>,
+    pub call_count: i32,
+    pub last_call_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Error)]
+pub enum CallerMemoryError {
+    #[error("read failed: {0}")]
+    ReadFailed(String),
+    #[error("write failed: {0}")]
+    WriteFailed(String),
+}
+
+pub type CallerMemoryFuture<T> = Pin<Box<dyn Future<Output = Result<T, CallerMemoryError>> + Send>>;
+
+pub trait CallerMemoryPort: Send + Sync {
+    /// `not_before` より古い最終通話のメモリは保持期間切れとして返さない。
+    fn load(
+        &self,
+        phone_number: &str,
+        not_before: DateTime<Utc>,
+    ) -> CallerMemoryFuture<Option<CallerMemory>>;
+    fn save(&self, memory: CallerMemory) -> CallerMemoryFuture<()>;
+    /// プライバシー要求による削除。削除した場合は `true`。
+    fn erase(&self, phone_number: &str) -> CallerMemoryFuture<bool>;
+    fn purge_expired(&self, not_before: DateTime<Utc>) -> CallerMemoryFuture<u64>;
+}
+
+#[derive(Default)]
+pub struct NoopCallerMemoryPort;
+
+impl NoopCallerMemoryPort {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl CallerMemoryPort for NoopCallerMemoryPort {
+    fn load(
+        &self,
+        _phone_number: &str,
+        _not_before: DateTime<Utc>,
+    ) -> CallerMemoryFuture<Option<CallerMemory>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn save(&self, _memory: CallerMemory) -> CallerMemoryFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn erase(&self, _phone_number: &str) -> CallerMemoryFuture<bool> {
+        Box::pin(async { Ok(false) })
+    }
+
+    fn purge_expired(&self, _not_before: DateTime<Utc>) -> CallerMemoryFuture<u64> {
+        Box::pin(async { Ok(0) })
+    }
+}
@@ -0,0 +1,156 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use thiserror::Error;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// 通話中セッションの状態（運用画面・API 向け）
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LiveCallStatus {
+    pub call_id: String,
+    /// SIP セッション状態（Idle / Early / Established / Terminating / Terminated）
+    pub session_state: String,
+    /// IVR 上の位置（IvrMenuWaiting / VoicebotMode / B2buaMode など）
+    pub ivr_state: String,
+    /// 発信者番号（非通知なら None）
+    pub caller: Option<String>,
+    /// 着信先 URI
+    pub callee: String,
+    /// 発信通話なら true
+    pub outbound: bool,
+    /// 応答時刻（RFC 3339、未応答なら None）
+    pub answered_at: Option<String>,
+    /// 応答からの経過秒数
+    pub duration_sec: u64,
+}
+
+/// 通話中セッションへの操作
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LiveCallCommand {
+    /// 強制切断（BYE）
+    Hangup,
+    /// 転送先グループ名・内線番号・SIP URI への転送
+    Transfer { target: String },
+    /// 登録済みアナウンスの再生
+    PlayAnnouncement { announcement_id: Uuid },
+    /// テキストを TTS で合成して再生
+    Say { text: String },
+    /// 録音の一時停止（全レグを無音にし、ASR にも音声を渡さない）
+    PauseRecording,
+    /// 録音の再開
+    ResumeRecording,
+}
+
+/// 監視者（スーパーバイザ）の参加方法
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SupervisorMode {
+    /// 傍聴のみ
+    Listen,
+    /// 監視者の音声を担当者（B レグ）にだけ聞かせる
+    Whisper,
+    /// 監視者の音声を発信者・担当者の両方に聞かせる
+    Barge,
+}
+
+impl SupervisorMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "listen" => Some(Self::Listen),
+            "whisper" => Some(Self::Whisper),
+            "barge" => Some(Self::Barge),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Listen => "listen",
+            Self::Whisper => "whisper",
+            Self::Barge => "barge",
+        }
+    }
+}
+
+/// 監視者へ送る音声のチャネル構成
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MonitorChannels {
+    /// 発信者と相手側（担当者・ボット）を混ぜたモノラル
+    Mixed,
+    /// 発信者・相手側を左右に分けたステレオ（サンプル単位でインターリーブ）
+    Legs,
+}
+
+impl MonitorChannels {
+    pub fn count(self) -> usize {
+        match self {
+            Self::Mixed => 1,
+            Self::Legs => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SupervisorRequest {
+    pub mode: SupervisorMode,
+    pub channels: MonitorChannels,
+}
+
+/// 監視セッションの音声経路（8kHz リニア PCM）。受信側を落とすと監視を終える
+#[derive(Debug)]
+pub struct SupervisorChannel {
+    /// 20ms ごとの通話音声（`Legs` なら 1 フレームに 2 チャネル分）
+    pub frames: mpsc::Receiver<Vec<i16>>,
+    /// 監視者の音声の送り先（Listen なら None）
+    pub talk: Option<mpsc::Sender<Vec<i16>>>,
+}
+
+#[derive(Debug, Error)]
+pub enum LiveCallError {
+    #[error("call not found: {0}")]
+    NotFound(String),
+    /// セッションの状態により受け付けられない（未応答・転送中など）
+    #[error("command rejected: {0}")]
+    Rejected(String),
+    #[error("command failed: {0}")]
+    Failed(String),
+}
+
+pub type LiveCallFuture<T> = Pin<Box<dyn Future<Output = Result<T, LiveCallError>> + Send>>;
+
+pub trait LiveCallPort: Send + Sync {
+    fn list_calls(&self) -> LiveCallFuture<Vec<LiveCallStatus>>;
+    fn send_command(&self, call_id: String, command: LiveCallCommand) -> LiveCallFuture<()>;
+    fn attach_supervisor(
+        &self,
+        call_id: String,
+        request: SupervisorRequest,
+    ) -> LiveCallFuture<SupervisorChannel>;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NoopLiveCallPort;
+
+impl NoopLiveCallPort {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl LiveCallPort for NoopLiveCallPort {
+    fn list_calls(&self) -> LiveCallFuture<Vec<LiveCallStatus>> {
+        Box::pin(async move { Ok(Vec::new()) })
+    }
+
+    fn send_command(&self, call_id: String, _command: LiveCallCommand) -> LiveCallFuture<()> {
+        Box::pin(async move { Err(LiveCallError::NotFound(call_id)) })
+    }
+
+    fn attach_supervisor(
+        &self,
+        call_id: String,
+        _request: SupervisorRequest,
+    ) -> LiveCallFuture<SupervisorChannel> {
+        Box::pin(async move { Err(LiveCallError::NotFound(call_id)) })
+    }
+}
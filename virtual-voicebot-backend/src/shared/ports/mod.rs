@@ -1,10 +1,13 @@
 pub mod ai;
 pub mod announcement_port;
 pub mod app;
+pub mod call_event_port;
 pub mod call_log_port;
 pub mod call_repository;
+pub mod caller_memory_port;
 pub mod folder_port;
 pub mod ingest;
+pub mod live_call;
 pub mod notification;
 pub mod phone_lookup;
 pub mod recording_repository;
@@ -18,3 +21,5 @@ pub mod settings_port;
 pub mod sip;
 pub mod storage;
 pub mod sync_outbox_port;
+pub mod voicemail_port;
+pub mod webhook_port;
@@ -0,0 +1,19 @@
+use chrono::{DateTime, FixedOffset};
+
+use crate::shared::entities::CallId;
+
+use super::NotificationFuture;
+
+/// 待ち呼キューで受け付けた折り返し依頼
+#[derive(Clone, Debug)]
+pub struct CallbackRequest {
+    pub call_id: CallId,
+    pub queue: String,
+    /// 非通知などで番号が分からない場合は None
+    pub caller_number: Option<String>,
+    pub requested_at: DateTime<FixedOffset>,
+}
+
+pub trait CallbackRequestNotifier: Send + Sync {
+    fn notify_callback_request(&self, request: CallbackRequest) -> NotificationFuture;
+}
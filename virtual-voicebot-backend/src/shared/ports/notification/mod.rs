@@ -11,14 +11,32 @@ pub enum NotificationError {
 
 pub type NotificationFuture = Pin<Box<dyn Future<Output = Result<(), NotificationError>> + Send>>;
 
+pub mod callback;
 pub mod ended;
 pub mod missed;
 pub mod ringing;
+pub mod voicemail;
 
+pub use callback::{CallbackRequest, CallbackRequestNotifier};
 pub use ended::CallEndedNotifier;
 pub use missed::MissedCallNotifier;
 pub use ringing::RingingNotifier;
+pub use voicemail::{VoicemailNotice, VoicemailNotifier};
 
-pub trait NotificationService: RingingNotifier + MissedCallNotifier + CallEndedNotifier {}
+pub trait NotificationService:
+    RingingNotifier
+    + MissedCallNotifier
+    + CallEndedNotifier
+    + VoicemailNotifier
+    + CallbackRequestNotifier
+{
+}
 
-impl<T> NotificationService for T where T: RingingNotifier + MissedCallNotifier + CallEndedNotifier {}
+impl<T> NotificationService for T where
+    T: RingingNotifier
+        + MissedCallNotifier
+        + CallEndedNotifier
+        + VoicemailNotifier
+        + CallbackRequestNotifier
+{
+}
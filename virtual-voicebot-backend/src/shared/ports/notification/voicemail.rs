@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset};
+use uuid::Uuid;
+
+use super::NotificationFuture;
+
+/// 新着留守番電話の通知内容
+#[derive(Clone, Debug)]
+pub struct VoicemailNotice {
+    pub message_id: Uuid,
+    pub mailbox_number: String,
+    pub caller_number: Option<String>,
+    pub duration_sec: i32,
+    /// 文字起こしに失敗した場合は None
+    pub transcript: Option<String>,
+    pub received_at: DateTime<FixedOffset>,
+    /// メールボックス個別の webhook 送信先（未設定なら送信側の既定値）
+    pub webhook_url: Option<String>,
+    /// 保存したメッセージの音声（メール通知の添付用）
+    pub audio_path: Option<PathBuf>,
+}
+
+pub trait VoicemailNotifier: Send + Sync {
+    fn notify_voicemail(&self, notice: VoicemailNotice) -> NotificationFuture;
+}
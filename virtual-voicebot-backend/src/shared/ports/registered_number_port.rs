@@ -9,6 +9,10 @@ use uuid::Uuid;
 pub struct RegisteredNumber {
     pub id: Uuid,
     pub phone_number: String,
+    /// 適用する着信番号（DID）。None は全 DID 共通
+    pub did: Option<String>,
+    /// "exact"（完全一致）または "prefix"（phone_number で始まる番号すべて）
+    pub match_type: String,
     pub name: Option<String>,
     pub category: String,
     pub action_code: String,
@@ -27,6 +31,10 @@ pub struct RegisteredNumber {
 pub struct UpsertRegisteredNumber {
     pub id: Uuid,
     pub phone_number: String,
+    /// 適用する着信番号（DID）。None は全 DID 共通
+    pub did: Option<String>,
+    /// "exact"（完全一致）または "prefix"（phone_number で始まる番号すべて）
+    pub match_type: String,
     pub name: Option<String>,
     pub category: String,
     pub action_code: String,
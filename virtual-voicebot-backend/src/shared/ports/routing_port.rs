@@ -7,6 +7,7 @@ use uuid::Uuid;
 
 #[derive(Clone, Debug)]
 pub struct RegisteredNumberRow {
+    pub id: Uuid,
     pub action_code: String,
     pub ivr_flow_id: Option<Uuid>,
     pub recording_enabled: bool,
@@ -36,6 +37,13 @@ pub struct IvrMenuRow {
     pub audio_file_url: Option<String>,
     pub timeout_sec: i32,
     pub max_retries: i32,
+    /// 入力ノードの種別（KEYPAD / DIGITS / SPEECH）
+    pub input_node_type: String,
+    pub min_digits: i16,
+    pub max_digits: i16,
+    pub terminator: Option<String>,
+    pub inter_digit_timeout_sec: i32,
+    pub variable_name: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -47,6 +55,14 @@ pub struct IvrDestinationRow {
     pub metadata_json: Option<String>,
 }
 
+/// SPEECH ノードの遷移（キーワードのいずれかを含む、または意図が一致すれば遷移）
+#[derive(Clone, Debug)]
+pub struct IvrSpeechRouteRow {
+    pub keywords: Vec<String>,
+    pub intent: Option<String>,
+    pub destination: IvrDestinationRow,
+}
+
 #[derive(Debug, Error)]
 pub enum RoutingPortError {
     #[error("read failed: {0}")]
@@ -56,15 +72,30 @@ pub enum RoutingPortError {
 pub type RoutingFuture<T> = Pin<Box<dyn Future<Output = Result<T, RoutingPortError>> + Send>>;
 
 pub trait RoutingPort: Send + Sync {
+    /// `did` は着信番号（E.164）。DID 一致の行を DID 未指定（NULL）の行より優先する。
     fn find_registered_number(
         &self,
         phone_number: &str,
+        did: Option<&str>,
     ) -> RoutingFuture<Option<RegisteredNumberRow>>;
-    fn find_caller_group(&self, phone_number: &str) -> RoutingFuture<Option<Uuid>>;
-    fn find_call_action_rule(&self, group_id: Uuid) -> RoutingFuture<Option<CallActionRuleRow>>;
+    fn find_caller_group(
+        &self,
+        phone_number: &str,
+        did: Option<&str>,
+    ) -> RoutingFuture<Option<Uuid>>;
+    fn find_call_action_rule(
+        &self,
+        group_id: Uuid,
+        did: Option<&str>,
+    ) -> RoutingFuture<Option<CallActionRuleRow>>;
     fn is_spam(&self, phone_number: &str) -> RoutingFuture<bool>;
     fn is_registered(&self, phone_number: &str) -> RoutingFuture<bool>;
-    fn find_routing_rule(&self, category: &str) -> RoutingFuture<Option<RoutingRuleRow>>;
+    fn find_routing_rule(
+        &self,
+        category: &str,
+        did: Option<&str>,
+    ) -> RoutingFuture<Option<RoutingRuleRow>>;
+    fn find_routing_rule_by_id(&self, rule_id: Uuid) -> RoutingFuture<Option<RoutingRuleRow>>;
     fn get_system_settings_extra(&self) -> RoutingFuture<Option<Value>>;
     fn find_announcement_audio_file_url(
         &self,
@@ -97,6 +128,13 @@ pub trait RoutingPort: Send + Sync {
         &self,
         flow_id: Uuid,
     ) -> RoutingFuture<Option<IvrDestinationRow>>;
+    /// DIGITS ノードの収集完了時の遷移先
+    fn find_ivr_complete_destination(
+        &self,
+        input_node_id: Uuid,
+    ) -> RoutingFuture<Option<IvrDestinationRow>>;
+    /// SPEECH ノードの遷移（定義順）
+    fn find_ivr_speech_routes(&self, input_node_id: Uuid) -> RoutingFuture<Vec<IvrSpeechRouteRow>>;
 }
 
 #[derive(Default)]
@@ -112,15 +150,24 @@ impl RoutingPort for NoopRoutingPort {
     fn find_registered_number(
         &self,
         _phone_number: &str,
+        _did: Option<&str>,
     ) -> RoutingFuture<Option<RegisteredNumberRow>> {
         Box::pin(async { Ok(None) })
     }
 
-    fn find_caller_group(&self, _phone_number: &str) -> RoutingFuture<Option<Uuid>> {
+    fn find_caller_group(
+        &self,
+        _phone_number: &str,
+        _did: Option<&str>,
+    ) -> RoutingFuture<Option<Uuid>> {
         Box::pin(async { Ok(None) })
     }
 
-    fn find_call_action_rule(&self, _group_id: Uuid) -> RoutingFuture<Option<CallActionRuleRow>> {
+    fn find_call_action_rule(
+        &self,
+        _group_id: Uuid,
+        _did: Option<&str>,
+    ) -> RoutingFuture<Option<CallActionRuleRow>> {
         Box::pin(async { Ok(None) })
     }
 
@@ -132,7 +179,15 @@ impl RoutingPort for NoopRoutingPort {
         Box::pin(async { Ok(false) })
     }
 
-    fn find_routing_rule(&self, _category: &str) -> RoutingFuture<Option<RoutingRuleRow>> {
+    fn find_routing_rule(
+        &self,
+        _category: &str,
+        _did: Option<&str>,
+    ) -> RoutingFuture<Option<RoutingRuleRow>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn find_routing_rule_by_id(&self, _rule_id: Uuid) -> RoutingFuture<Option<RoutingRuleRow>> {
         Box::pin(async { Ok(None) })
     }
 
@@ -194,4 +249,18 @@ impl RoutingPort for NoopRoutingPort {
     ) -> RoutingFuture<Option<IvrDestinationRow>> {
         Box::pin(async { Ok(None) })
     }
+
+    fn find_ivr_complete_destination(
+        &self,
+        _input_node_id: Uuid,
+    ) -> RoutingFuture<Option<IvrDestinationRow>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn find_ivr_speech_routes(
+        &self,
+        _input_node_id: Uuid,
+    ) -> RoutingFuture<Vec<IvrSpeechRouteRow>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
 }
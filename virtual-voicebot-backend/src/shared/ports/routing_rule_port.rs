@@ -9,6 +9,8 @@ use uuid::Uuid;
 pub struct RoutingRule {
     pub id: Uuid,
     pub caller_category: String,
+    /// 適用する着信番号（DID）。None は全 DID 共通
+    pub did: Option<String>,
     pub action_code: String,
     pub ivr_flow_id: Option<Uuid>,
     pub priority: i32,
@@ -23,6 +25,8 @@ pub struct RoutingRule {
 pub struct UpsertRoutingRule {
     pub id: Uuid,
     pub caller_category: String,
+    /// 適用する着信番号（DID）。None は全 DID 共通
+    pub did: Option<String>,
     pub action_code: String,
     pub ivr_flow_id: Option<Uuid>,
     pub priority: i32,
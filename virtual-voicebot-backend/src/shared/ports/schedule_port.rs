@@ -73,3 +73,22 @@ pub trait SchedulePort: Send + Sync {
     fn list_active(&self) -> ScheduleFuture<Vec<Schedule>>;
     fn upsert_schedule(&self, schedule: UpsertSchedule) -> ScheduleFuture<()>;
 }
+
+#[derive(Default)]
+pub struct NoopSchedulePort;
+
+impl NoopSchedulePort {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl SchedulePort for NoopSchedulePort {
+    fn list_active(&self) -> ScheduleFuture<Vec<Schedule>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn upsert_schedule(&self, _schedule: UpsertSchedule) -> ScheduleFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+}
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use crate::shared::entities::CallId;
+use crate::shared::entities::{CallId, CallerIdentity};
 
 #[derive(Clone, Debug)]
 pub struct Sdp {
@@ -41,6 +41,8 @@ pub enum SipEvent {
         call_id: CallId,
         from: String,
         to: String,
+        /// PAI/Privacy/Diversion 等から組み立てた発信者識別情報
+        identity: CallerIdentity,
         offer: Sdp,
         session_timer: Option<SessionTimerInfo>,
     },
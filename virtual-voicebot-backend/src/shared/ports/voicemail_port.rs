@@ -0,0 +1,329 @@
+use std::future::Future;
+use std::num::NonZeroU32;
+use std::pin::Pin;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use uuid::Uuid;
+
+pub const MESSAGE_STATUS_NEW: &str = "new";
+pub const MESSAGE_STATUS_SAVED: &str = "saved";
+pub const MESSAGE_STATUS_DELETED: &str = "deleted";
+
+/// PIN ハッシュの保存形式: `pbkdf2-sha256$<反復回数>$<ソルト hex>$<ハッシュ hex>`
+const PIN_HASH_SCHEME: &str = "pbkdf2-sha256";
+const PIN_HASH_ITERATIONS: u32 = 100_000;
+const PIN_SALT_LEN: usize = 16;
+const PIN_HASH_LEN: usize = 32;
+
+/// 留守番電話のメールボックス。`did` が NULL のメールボックスは全 DID 共通（既定）として扱う。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoicemailMailbox {
+    pub id: Uuid,
+    pub mailbox_number: String,
+    pub did: Option<String>,
+    /// `hash_pin(pin)` の値。旧形式（`mailbox_number:pin` の SHA-256 hex）も照合できる
+    pub pin_hash: String,
+    /// PIN の連続誤りでロックされている場合の解除時刻
+    pub pin_locked_until: Option<DateTime<Utc>>,
+    pub notify_line: bool,
+    /// 未設定なら VOICEMAIL_WEBHOOK_URL を使用する
+    pub webhook_url: Option<String>,
+}
+
+impl VoicemailMailbox {
+    pub fn verify_pin(&self, pin: &str) -> bool {
+        if pin.is_empty() {
+            return false;
+        }
+        if let Some(stored) = PinHash::parse(&self.pin_hash) {
+            return pbkdf2::verify(
+                pbkdf2::PBKDF2_HMAC_SHA256,
+                stored.iterations,
+                &stored.salt,
+                pin.as_bytes(),
+                &stored.hash,
+            )
+            .is_ok();
+        }
+        match decode_hex(&self.pin_hash) {
+            Some(legacy) if legacy.len() == PIN_HASH_LEN => {
+                let digest = Sha256::digest(format!("{}:{pin}", self.mailbox_number).as_bytes());
+                // 一致するまでの時間で推測されないよう全バイトを比較する
+                legacy
+                    .iter()
+                    .zip(digest.iter())
+                    .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                    == 0
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_pin_locked(&self, now: DateTime<Utc>) -> bool {
+        self.pin_locked_until.is_some_and(|until| until > now)
+    }
+
+    /// 旧形式や反復回数の少ないハッシュか（照合に成功したら `hash_pin` で置き換える）
+    pub fn needs_pin_rehash(&self) -> bool {
+        PinHash::parse(&self.pin_hash)
+            .map(|stored| stored.iterations.get() < PIN_HASH_ITERATIONS)
+            .unwrap_or(true)
+    }
+}
+
+/// PIN のハッシュ値（PBKDF2-HMAC-SHA256、メールボックスごとのランダムなソルト）
+pub fn hash_pin(pin: &str) -> String {
+    let mut salt = [0u8; PIN_SALT_LEN];
+    SystemRandom::new()
+        .fill(&mut salt)
+        .expect("system random source unavailable");
+    let iterations = NonZeroU32::new(PIN_HASH_ITERATIONS).expect("non-zero iterations");
+    let mut hash = [0u8; PIN_HASH_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        &salt,
+        pin.as_bytes(),
+        &mut hash,
+    );
+    format!(
+        "{PIN_HASH_SCHEME}${iterations}${}${}",
+        encode_hex(&salt),
+        encode_hex(&hash)
+    )
+}
+
+struct PinHash {
+    iterations: NonZeroU32,
+    salt: Vec<u8>,
+    hash: Vec<u8>,
+}
+
+impl PinHash {
+    fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split('$');
+        if parts.next()? != PIN_HASH_SCHEME {
+            return None;
+        }
+        let iterations = NonZeroU32::new(parts.next()?.parse().ok()?)?;
+        let salt = decode_hex(parts.next()?)?;
+        let hash = decode_hex(parts.next()?)?;
+        if parts.next().is_some() || salt.is_empty() || hash.is_empty() {
+            return None;
+        }
+        Some(Self {
+            iterations,
+            salt,
+            hash,
+        })
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoicemailMessage {
+    pub id: Uuid,
+    pub mailbox_id: Uuid,
+    pub call_log_id: Option<Uuid>,
+    pub caller_number: Option<String>,
+    pub duration_sec: i32,
+    /// 発信者側チャネルのみのモノラル WAV（電話での再生にそのまま使う）
+    pub file_path: String,
+    pub transcript: Option<String>,
+    pub status: String,
+    pub listened_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug)]
+pub struct NewVoicemailMessage {
+    pub id: Uuid,
+    pub mailbox_id: Uuid,
+    pub call_log_id: Option<Uuid>,
+    pub caller_number: Option<String>,
+    pub duration_sec: i32,
+    pub file_path: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 録音を終えた留守番電話の通話（session → 後処理）
+#[derive(Clone, Debug)]
+pub struct RecordedVoicemail {
+    pub call_log_id: Uuid,
+    /// 通話録音（mixed.wav: L=発信者 / R=応答メッセージ）
+    pub recording_path: String,
+    pub caller_number: Option<String>,
+    /// 着信先（DID）。メールボックスの選択に使う
+    pub called_number: Option<String>,
+    pub received_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Error)]
+pub enum VoicemailError {
+    #[error("read failed: {0}")]
+    ReadFailed(String),
+    #[error("write failed: {0}")]
+    WriteFailed(String),
+}
+
+pub type VoicemailFuture<T> = Pin<Box<dyn Future<Output = Result<T, VoicemailError>> + Send>>;
+
+pub trait VoicemailPort: Send + Sync {
+    /// DID 一致のメールボックスを優先し、なければ全 DID 共通のメールボックスを返す。
+    fn find_mailbox_for_did(&self, did: Option<&str>) -> VoicemailFuture<Option<VoicemailMailbox>>;
+    fn find_mailbox(&self, mailbox_number: &str) -> VoicemailFuture<Option<VoicemailMailbox>>;
+    fn update_pin_hash(&self, mailbox_id: Uuid, pin_hash: String) -> VoicemailFuture<()>;
+    /// PIN の誤りを数え、連続 `max_attempts` 回に達したら `lockout` の間ロックする。
+    /// 今回の誤りでロックした場合は解除時刻を返す。
+    fn record_pin_failure(
+        &self,
+        mailbox_id: Uuid,
+        max_attempts: u32,
+        lockout: Duration,
+    ) -> VoicemailFuture<Option<DateTime<Utc>>>;
+    fn reset_pin_failures(&self, mailbox_id: Uuid) -> VoicemailFuture<()>;
+    fn insert_message(&self, message: NewVoicemailMessage) -> VoicemailFuture<()>;
+    fn update_transcript(&self, message_id: Uuid, transcript: String) -> VoicemailFuture<()>;
+    /// 削除済みを除くメッセージ（未再生 → 保存済みの順、各々古い順）
+    fn list_messages(&self, mailbox_id: Uuid) -> VoicemailFuture<Vec<VoicemailMessage>>;
+    fn mark_listened(&self, message_id: Uuid) -> VoicemailFuture<()>;
+    fn update_status(&self, message_id: Uuid, status: &'static str) -> VoicemailFuture<()>;
+}
+
+#[derive(Default)]
+pub struct NoopVoicemailPort;
+
+impl NoopVoicemailPort {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl VoicemailPort for NoopVoicemailPort {
+    fn find_mailbox_for_did(
+        &self,
+        _did: Option<&str>,
+    ) -> VoicemailFuture<Option<VoicemailMailbox>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn find_mailbox(&self, _mailbox_number: &str) -> VoicemailFuture<Option<VoicemailMailbox>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn update_pin_hash(&self, _mailbox_id: Uuid, _pin_hash: String) -> VoicemailFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn record_pin_failure(
+        &self,
+        _mailbox_id: Uuid,
+        _max_attempts: u32,
+        _lockout: Duration,
+    ) -> VoicemailFuture<Option<DateTime<Utc>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn reset_pin_failures(&self, _mailbox_id: Uuid) -> VoicemailFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn insert_message(&self, _message: NewVoicemailMessage) -> VoicemailFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn update_transcript(&self, _message_id: Uuid, _transcript: String) -> VoicemailFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn list_messages(&self, _mailbox_id: Uuid) -> VoicemailFuture<Vec<VoicemailMessage>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn mark_listened(&self, _message_id: Uuid) -> VoicemailFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn update_status(&self, _message_id: Uuid, _status: &'static str) -> VoicemailFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mailbox_with_hash(pin_hash: String) -> VoicemailMailbox {
+        VoicemailMailbox {
+            id: Uuid::nil(),
+            mailbox_number: "1001".to_string(),
+            did: None,
+            pin_hash,
+            pin_locked_until: None,
+            notify_line: true,
+            webhook_url: None,
+        }
+    }
+
+    #[test]
+    fn pin_hash_uses_pbkdf2_with_random_salt() {
+        let mailbox = mailbox_with_hash(hash_pin("4321"));
+        assert!(mailbox.pin_hash.starts_with("pbkdf2-sha256$100000$"));
+        assert!(mailbox.verify_pin("4321"));
+        assert!(!mailbox.verify_pin("1234"));
+        assert!(!mailbox.verify_pin(""));
+        assert!(!mailbox.needs_pin_rehash());
+        // 同じ PIN でもソルトが違えば別の値になる
+        assert_ne!(hash_pin("4321"), hash_pin("4321"));
+    }
+
+    #[test]
+    fn pin_lock_expires_at_locked_until() {
+        let now = Utc::now();
+        let mut mailbox = mailbox_with_hash(hash_pin("4321"));
+        assert!(!mailbox.is_pin_locked(now));
+        mailbox.pin_locked_until = Some(now + chrono::Duration::minutes(15));
+        assert!(mailbox.is_pin_locked(now));
+        assert!(!mailbox.is_pin_locked(now + chrono::Duration::minutes(16)));
+    }
+
+    #[test]
+    fn legacy_sha256_pin_hash_verifies_and_needs_rehash() {
+        // SELECT encode(sha256(convert_to('1001:4321', 'UTF8')), 'hex')
+        let legacy = encode_hex(&Sha256::digest(b"1001:4321"));
+        let mailbox = mailbox_with_hash(legacy);
+        assert!(mailbox.verify_pin("4321"));
+        assert!(!mailbox.verify_pin("1234"));
+        assert!(mailbox.needs_pin_rehash());
+    }
+
+    #[test]
+    fn malformed_pin_hash_never_verifies() {
+        for pin_hash in [
+            "",
+            "pbkdf2-sha256$0$00$00",
+            "pbkdf2-sha256$1000$zz$00",
+            "abc",
+        ] {
+            assert!(!mailbox_with_hash(pin_hash.to_string()).verify_pin("4321"));
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// 外部システムへ webhook で送るイベントの種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WebhookEventType {
+    /// 着信して呼び出し中
+    Ringing,
+    /// 応答しないまま終わった通話
+    Missed,
+    /// 応答した通話の終了
+    Ended,
+    /// 留守番電話の新着（文字起こし済み）
+    Voicemail,
+    /// 通話の会話（発信者の発話とボットの応答）が揃った（通話終了時）
+    TranscriptReady,
+}
+
+impl WebhookEventType {
+    pub const ALL: [WebhookEventType; 5] = [
+        WebhookEventType::Ringing,
+        WebhookEventType::Missed,
+        WebhookEventType::Ended,
+        WebhookEventType::Voicemail,
+        WebhookEventType::TranscriptReady,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WebhookEventType::Ringing => "ringing",
+            WebhookEventType::Missed => "missed",
+            WebhookEventType::Ended => "ended",
+            WebhookEventType::Voicemail => "voicemail",
+            WebhookEventType::TranscriptReady => "transcript_ready",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase().replace('-', "_");
+        Self::ALL
+            .into_iter()
+            .find(|event_type| event_type.as_str() == value)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WebhookEvent {
+    pub id: Uuid,
+    pub event_type: WebhookEventType,
+    pub call_id: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    /// イベント固有の項目（camelCase の JSON オブジェクト）
+    pub data: Value,
+}
+
+/// イベントの発行口。通話処理を止めないよう、実装はブロックしないこと
+pub trait WebhookPublisher: Send + Sync {
+    fn publish(&self, event: WebhookEvent);
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NoopWebhookPublisher;
+
+impl NoopWebhookPublisher {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl WebhookPublisher for NoopWebhookPublisher {
+    fn publish(&self, _event: WebhookEvent) {}
+}
+
+pub const DELIVERY_STATUS_PENDING: &str = "pending";
+pub const DELIVERY_STATUS_DELIVERED: &str = "delivered";
+/// 再試行を使い切った（dead letter）。再送 API で pending に戻せる
+pub const DELIVERY_STATUS_DEAD: &str = "dead";
+
+#[derive(Clone, Debug)]
+pub struct NewWebhookDelivery {
+    pub id: Uuid,
+    pub subscription_id: String,
+    pub event_type: WebhookEventType,
+    pub event_id: Uuid,
+    pub call_id: Option<String>,
+    pub url: String,
+    /// 送信する本文（テンプレート適用済み）
+    pub payload: Value,
+}
+
+#[derive(Clone, Debug)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub subscription_id: String,
+    pub event_type: String,
+    pub event_id: Uuid,
+    pub call_id: Option<String>,
+    pub url: String,
+    pub payload: Value,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// 配送ログの絞り込み条件
+#[derive(Clone, Debug, Default)]
+pub struct WebhookDeliveryFilter {
+    pub status: Option<String>,
+    pub subscription_id: Option<String>,
+    pub call_id: Option<String>,
+    pub limit: i64,
+}
+
+/// 1 回の送信の失敗
+#[derive(Clone, Debug)]
+pub struct WebhookAttemptFailure {
+    pub status_code: Option<i32>,
+    pub error: String,
+    /// 次の再試行時刻。None なら再試行しない（dead letter へ）
+    pub retry_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookDeliveryError {
+    #[error("write failed: {0}")]
+    WriteFailed(String),
+    #[error("read failed: {0}")]
+    ReadFailed(String),
+}
+
+pub type WebhookDeliveryFuture<T> =
+    Pin<Box<dyn Future<Output = Result<T, WebhookDeliveryError>> + Send>>;
+
+pub trait WebhookDeliveryPort: Send + Sync {
+    fn enqueue(&self, delivery: NewWebhookDelivery) -> WebhookDeliveryFuture<()>;
+    /// 送信時刻を過ぎた pending を取り出す。取り出した分は `lease` の間ほかの取り出しから外す
+    fn claim_due(
+        &self,
+        limit: i64,
+        lease: std::time::Duration,
+    ) -> WebhookDeliveryFuture<Vec<WebhookDelivery>>;
+    fn mark_delivered(&self, id: Uuid, status_code: i32) -> WebhookDeliveryFuture<()>;
+    fn mark_failed(&self, id: Uuid, failure: WebhookAttemptFailure) -> WebhookDeliveryFuture<()>;
+    /// 新しい順の配送ログ
+    fn list(&self, filter: WebhookDeliveryFilter) -> WebhookDeliveryFuture<Vec<WebhookDelivery>>;
+    /// dead letter を pending に戻してすぐ再送する。対象がなければ false
+    fn requeue(&self, id: Uuid) -> WebhookDeliveryFuture<bool>;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NoopWebhookDeliveryPort;
+
+impl NoopWebhookDeliveryPort {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl WebhookDeliveryPort for NoopWebhookDeliveryPort {
+    fn enqueue(&self, _delivery: NewWebhookDelivery) -> WebhookDeliveryFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn claim_due(
+        &self,
+        _limit: i64,
+        _lease: std::time::Duration,
+    ) -> WebhookDeliveryFuture<Vec<WebhookDelivery>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn mark_delivered(&self, _id: Uuid, _status_code: i32) -> WebhookDeliveryFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn mark_failed(&self, _id: Uuid, _failure: WebhookAttemptFailure) -> WebhookDeliveryFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn list(&self, _filter: WebhookDeliveryFilter) -> WebhookDeliveryFuture<Vec<WebhookDelivery>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn requeue(&self, _id: Uuid) -> WebhookDeliveryFuture<bool> {
+        Box::pin(async { Ok(false) })
+    }
+}
@@ -1,6 +1,7 @@
 use std::env;
 use std::fs;
 use std::io::Write;
+use std::sync::Arc;
 
 use reqwest::header::RANGE;
 use reqwest::StatusCode;
@@ -8,7 +9,16 @@ use tempfile::tempdir;
 use tokio::net::TcpListener;
 
 use virtual_voicebot_backend::interface::http;
+use virtual_voicebot_backend::service::call_events::CallEventHub;
 use virtual_voicebot_backend::shared::logging;
+use virtual_voicebot_backend::shared::ports::live_call::NoopLiveCallPort;
+
+/// HTTP のセキュリティ設定はプロセスで 1 度だけ読まれるので、各テストの先頭で同じ値を入れる
+const API_TOKEN: &str = "e2e-token";
+
+fn use_api_token() {
+    env::set_var("HTTP_API_TOKENS", API_TOKEN);
+}
 
 struct ServerGuard(tokio::task::JoinHandle<()>);
 
@@ -20,6 +30,7 @@ impl Drop for ServerGuard {
 
 #[tokio::test]
 async fn recording_http_e2e() -> Result<(), Box<dyn std::error::Error>> {
+    use_api_token();
     let temp = tempdir()?;
     let base_dir = temp.path().join("storage/recordings");
     let log_dir = match env::var("E2E_LOG_DIR") {
@@ -44,7 +55,14 @@ async fn recording_http_e2e() -> Result<(), Box<dyn std::error::Error>> {
 
     let listener = TcpListener::bind("127.0.0.1:0").await?;
     let addr = listener.local_addr()?;
-    let handle = http::spawn_recording_server_with_listener(listener, base_dir, None).await;
+    let handle = http::spawn_recording_server_with_listener(
+        listener,
+        base_dir,
+        None,
+        Arc::new(NoopLiveCallPort::new()),
+        Arc::new(CallEventHub::new(16)),
+    )
+    .await;
     let _guard = ServerGuard(handle);
 
     let base_url = format!("http://{}", addr);
@@ -152,17 +170,29 @@ async fn recording_http_e2e() -> Result<(), Box<dyn std::error::Error>> {
 
 #[tokio::test]
 async fn sync_status_without_db_returns_503() -> Result<(), Box<dyn std::error::Error>> {
+    use_api_token();
     let temp = tempdir()?;
     let base_dir = temp.path().join("storage/recordings");
     fs::create_dir_all(&base_dir)?;
 
     let listener = TcpListener::bind("127.0.0.1:0").await?;
     let addr = listener.local_addr()?;
-    let handle = http::spawn_recording_server_with_listener(listener, base_dir, None).await;
+    let handle = http::spawn_recording_server_with_listener(
+        listener,
+        base_dir,
+        None,
+        Arc::new(NoopLiveCallPort::new()),
+        Arc::new(CallEventHub::new(16)),
+    )
+    .await;
     let _guard = ServerGuard(handle);
 
     let url = format!("http://{}/api/sync/status", addr);
-    let res = reqwest::get(url).await?;
+    let res = reqwest::Client::new()
+        .get(url)
+        .bearer_auth(API_TOKEN)
+        .send()
+        .await?;
     assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
 
     let body: serde_json::Value = res.json().await?;
@@ -171,3 +201,49 @@ async fn sync_status_without_db_returns_503() -> Result<(), Box<dyn std::error::
 
     Ok(())
 }
+
+#[tokio::test]
+async fn api_requires_bearer_token() -> Result<(), Box<dyn std::error::Error>> {
+    use_api_token();
+    let temp = tempdir()?;
+    let base_dir = temp.path().join("storage/recordings");
+    fs::create_dir_all(&base_dir)?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let handle = http::spawn_recording_server_with_listener(
+        listener,
+        base_dir,
+        None,
+        Arc::new(NoopLiveCallPort::new()),
+        Arc::new(CallEventHub::new(16)),
+    )
+    .await;
+    let _guard = ServerGuard(handle);
+
+    let client = reqwest::Client::new();
+    let calls_url = format!("http://{}/api/calls", addr);
+    let res = client.get(&calls_url).send().await?;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = client.get(&calls_url).bearer_auth("wrong").send().await?;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = client
+        .post(format!(
+            "http://{}/api/calls/test_call/transfer?target=sip:attacker@example.com",
+            addr
+        ))
+        .send()
+        .await?;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    // monitor 以外の `/monitor` で終わる経路は API トークンの確認を飛ばさない
+    let res = client
+        .post(format!("http://{}/api/admin/config/reload/monitor", addr))
+        .send()
+        .await?;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client.get(&calls_url).bearer_auth(API_TOKEN).send().await?;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    Ok(())
+}
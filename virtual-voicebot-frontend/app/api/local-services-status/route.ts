@@ -1,6 +1,7 @@
 import { NextResponse } from "next/server"
 
 import type { LocalServicesStatusResponse } from "@/lib/api/local-services-status"
+import { backendAuthHeaders } from "@/lib/api/backend-auth"
 
 export const runtime = "nodejs"
 
@@ -32,6 +33,7 @@ export async function GET() {
       {
         method: "GET",
         cache: "no-store",
+        headers: backendAuthHeaders(),
       },
       BACKEND_TIMEOUT_MS,
     )
@@ -1,6 +1,7 @@
 import { NextResponse } from "next/server"
 
 import type { SyncStatusResponse } from "@/lib/api/sync-status"
+import { backendAuthHeaders } from "@/lib/api/backend-auth"
 
 export const runtime = "nodejs"
 
@@ -10,6 +11,7 @@ export async function GET() {
     const response = await fetch(`${backendUrl}/api/sync/status`, {
       method: "GET",
       cache: "no-store",
+      headers: backendAuthHeaders(),
     })
 
     if (!response.ok) {
@@ -0,0 +1,5 @@
+// バックエンドの /api/* は HTTP_API_TOKENS の Bearer トークンが必須（未設定なら 401）
+export function backendAuthHeaders(): Record<string, string> {
+  const token = process.env.BACKEND_API_TOKEN?.trim()
+  return token ? { Authorization: `Bearer ${token}` } : {}
+}